@@ -0,0 +1,438 @@
+//! Bearer tokens with per-tool scopes
+//!
+//! Each configured token carries a set of [`Scope`]s and, optionally,
+//! individual tool names it may call. Every tool declares the scope it
+//! requires (see [`declared_scope`]); a token may call a tool when it holds
+//! that scope, names the tool explicitly, or holds [`Scope::Admin`]. This
+//! allows, for example, read-only dashboard tokens and execute-only
+//! automation tokens. A tool that declares no scope fails closed and is left
+//! to admin tokens.
+//!
+//! Tokens are configured with `--auth-tokens` as `token=grant,grant;...`,
+//! where a grant is a scope name, `tool:<name>` or `client:<id>`:
+//!
+//! ```text
+//! dashboard-token=read;ci-token=execute,tool:get_diagram;ops-token=admin
+//! alice-token=write,client:alice
+//! ```
+//!
+//! A `client:<id>` grant binds the token to a client identity: its tool calls
+//! act as `clientId` `<id>`, which is then what locks and history actors
+//! record, and a call naming another `clientId` is refused. Otherwise
+//! `clientId` is whatever the caller claims. The transport runs a bound
+//! token's calls inside [`as_client`], and only that identity, read back with
+//! [`authenticated_client`], is recorded as an element's `created_by`.
+//!
+//! Without any tokens authentication is off and every caller holds every
+//! scope. Only the direct HTTP transport enforces tokens; the server refuses
+//! to start with tokens configured for any other transport.
+
+use crate::mcp::protocol::JsonRpcError;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+/// JSON-RPC error code for calls the token is not authorized for
+pub const UNAUTHORIZED_CODE: i32 = -32001;
+
+tokio::task_local! {
+    static AUTHENTICATED_CLIENT: String;
+}
+
+/// Tools that change server configuration or the workspace as a whole
+const ADMIN_TOOLS: &[&str] = &[
+    "set_workspace_directory",
+    "set_wasm_components_path",
+    "set_diagrams_path",
+    "create_workspace_structure",
+    "export_workspace",
+    "import_workspace",
+    "snapshot_state",
+    "restore_state",
+    "retry_pending_persists",
+    "register_webhook",
+    "unregister_webhook",
+    "list_webhooks",
+    "debug_wit_analysis",
+];
+
+/// Tools that load or run WASM components
+const EXECUTE_TOOLS: &[&str] = &[
+    "execute_component",
+    "get_execution_result",
+    "cancel_execution",
+    "load_wasm_component",
+];
+
+/// Tools that change diagrams, editor state or exported files, besides the
+/// tools that mutate existing diagrams (see [`crate::backend::MUTATING_TOOLS`])
+const WRITE_TOOLS: &[&str] = &[
+    "create_diagram",
+    "duplicate_diagram",
+    "convert_diagram_type",
+    "generate_diagram_from_wit",
+    "import_rust_types",
+    "select_elements",
+    "select_all",
+    "clear_selection",
+    "acquire_lock",
+    "release_lock",
+    "lock_element",
+    "unlock_element",
+    "refresh_wasm_interfaces",
+    "rescan_workspace",
+    "export_sensor_data",
+];
+
+/// Tools that only inspect diagrams, components and sensor data.
+/// `check_integrity` is listed here but needs [`Scope::Write`] to repair.
+const READ_TOOLS: &[&str] = &[
+    "server_info",
+    "list_diagrams",
+    "get_diagram",
+    "get_diagram_size",
+    "get_diagram_type_capabilities",
+    "get_content_bounds",
+    "get_history",
+    "get_selection",
+    "get_edges_for_node",
+    "get_attachment",
+    "list_attachments",
+    "find_nodes",
+    "suggest_targets",
+    "compare_diagrams",
+    "compute_layout_hints",
+    "detect_cycles",
+    "assert_acyclic",
+    "is_reachable",
+    "shortest_path",
+    "export_diagram",
+    "check_integrity",
+    "validate_workspace",
+    "validate_wit",
+    "get_workspace_info",
+    "scan_wasm_components",
+    "check_wasm_component_status",
+    "get_component_status",
+    "get_component_path",
+    "get_component_wit_info",
+    "get_component_dependency_graph",
+    "find_components_by_interface",
+    "inspect_component",
+    "export_component_wit",
+    "latest_readings",
+    "sensor_stats",
+    "detect_gaps",
+];
+
+/// A category of operations a token may be granted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Scope {
+    /// Inspect diagrams, components and sensor data
+    Read,
+    /// Create and modify diagrams
+    Write,
+    /// Load and run WASM components
+    Execute,
+    /// Workspace configuration; implies every other scope
+    Admin,
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Scope::Read => "read",
+            Scope::Write => "write",
+            Scope::Execute => "execute",
+            Scope::Admin => "admin",
+        })
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "read" => Ok(Scope::Read),
+            "write" => Ok(Scope::Write),
+            "execute" => Ok(Scope::Execute),
+            "admin" => Ok(Scope::Admin),
+            other => Err(format!(
+                "Unknown scope '{other}' (expected read, write, execute or admin)"
+            )),
+        }
+    }
+}
+
+/// The scope a tool declares, or `None` for a tool no list names
+pub fn declared_scope(tool: &str) -> Option<Scope> {
+    if ADMIN_TOOLS.contains(&tool) {
+        Some(Scope::Admin)
+    } else if EXECUTE_TOOLS.contains(&tool) {
+        Some(Scope::Execute)
+    } else if WRITE_TOOLS.contains(&tool) || crate::backend::MUTATING_TOOLS.contains(&tool) {
+        Some(Scope::Write)
+    } else if READ_TOOLS.contains(&tool) {
+        Some(Scope::Read)
+    } else {
+        None
+    }
+}
+
+/// The scope a tool call requires.
+///
+/// `check_integrity` with `repair` rewrites diagrams and needs
+/// [`Scope::Write`]. Undeclared tools need [`Scope::Admin`].
+pub fn required_scope(tool: &str, arguments: Option<&serde_json::Value>) -> Scope {
+    let repairs = arguments.is_some_and(|args| args["repair"].as_bool() == Some(true));
+    if tool == "check_integrity" && repairs {
+        return Scope::Write;
+    }
+    declared_scope(tool).unwrap_or(Scope::Admin)
+}
+
+/// What one token is allowed to do
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Grants {
+    pub scopes: BTreeSet<Scope>,
+    /// Tools allowed regardless of their scope
+    pub tools: BTreeSet<String>,
+    /// Client identity the token's calls act as
+    pub client: Option<String>,
+    /// Names the token without revealing it, e.g. as the owner of the event
+    /// subscriptions it opens; `token-<n>` for the n-th configured token
+    pub token_id: Option<String>,
+}
+
+impl Grants {
+    /// Grants held when authentication is off
+    pub fn all() -> Self {
+        Self {
+            scopes: BTreeSet::from([Scope::Admin]),
+            tools: BTreeSet::new(),
+            client: None,
+            token_id: None,
+        }
+    }
+
+    pub fn allows(&self, scope: Scope) -> bool {
+        self.scopes.contains(&Scope::Admin) || self.scopes.contains(&scope)
+    }
+
+    /// Whether the token may call `tool` with `arguments`; `None` asks
+    /// whether it may call the tool at all, as when listing tools
+    pub fn allows_tool(&self, tool: &str, arguments: Option<&serde_json::Value>) -> bool {
+        self.tools.contains(tool) || self.allows(required_scope(tool, arguments))
+    }
+
+    /// Make a tool call act as the token's client, if it is bound to one
+    ///
+    /// Fills in a missing `clientId` and refuses one naming another client.
+    pub fn bind_client(&self, arguments: &mut Option<serde_json::Value>) -> Result<(), String> {
+        let Some(client) = &self.client else {
+            return Ok(());
+        };
+        let arguments = arguments.get_or_insert_with(|| serde_json::json!({}));
+        let Some(arguments) = arguments.as_object_mut() else {
+            return Ok(());
+        };
+        match arguments.get("clientId").and_then(|id| id.as_str()) {
+            Some(claimed) if claimed != client => Err(format!(
+                "Token is bound to client '{client}' and cannot act as '{claimed}'"
+            )),
+            _ => {
+                arguments.insert("clientId".to_string(), serde_json::json!(client));
+                Ok(())
+            }
+        }
+    }
+
+    /// Parse a comma-separated list of scopes, `tool:<name>` and `client:<id>` entries
+    fn parse(list: &str) -> Result<Self, String> {
+        let mut grants = Self::default();
+        for grant in list.split(',').map(str::trim).filter(|g| !g.is_empty()) {
+            if let Some(tool) = grant.strip_prefix("tool:") {
+                grants.tools.insert(tool.trim().to_string());
+            } else if let Some(client) = grant.strip_prefix("client:") {
+                let client = client.trim();
+                if client.is_empty() || grants.client.is_some() {
+                    return Err("A token takes exactly one non-empty client:<id> grant".to_string());
+                }
+                grants.client = Some(client.to_string());
+            } else {
+                grants.scopes.insert(grant.parse()?);
+            }
+        }
+        Ok(grants)
+    }
+}
+
+/// Why a request could not be authenticated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    MissingToken,
+    InvalidToken,
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AuthError::MissingToken => "Missing bearer token",
+            AuthError::InvalidToken => "Invalid bearer token",
+        })
+    }
+}
+
+/// Configured bearer tokens
+#[derive(Debug, Clone, Default)]
+pub struct TokenAuth {
+    tokens: HashMap<String, Grants>,
+}
+
+impl TokenAuth {
+    /// Parse `token=grant,grant;token=grant`; an empty spec disables
+    /// authentication
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut tokens = HashMap::new();
+        let entries = spec.split(';').map(str::trim).filter(|e| !e.is_empty());
+        for (index, entry) in entries.enumerate() {
+            let (token, grants) = entry
+                .split_once('=')
+                .ok_or_else(|| "Auth token entries must look like token=scope,...".to_string())?;
+            let token = token.trim();
+            if token.is_empty() {
+                return Err("Auth tokens must not be empty".to_string());
+            }
+            let mut grants = Grants::parse(grants)?;
+            if grants.scopes.is_empty() && grants.tools.is_empty() {
+                return Err(format!(
+                    "Auth token '{}…' grants nothing",
+                    token.chars().take(4).collect::<String>()
+                ));
+            }
+            grants.token_id = Some(format!("token-{}", index + 1));
+            tokens.insert(token.to_string(), grants);
+        }
+        Ok(Self { tokens })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.tokens.is_empty()
+    }
+
+    /// Grants for an `Authorization` header value
+    pub fn authenticate(&self, authorization: Option<&str>) -> Result<Grants, AuthError> {
+        if !self.is_enabled() {
+            return Ok(Grants::all());
+        }
+        let token = authorization
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim)
+            .ok_or(AuthError::MissingToken)?;
+        self.tokens
+            .get(token)
+            .cloned()
+            .ok_or(AuthError::InvalidToken)
+    }
+}
+
+/// Run `future` as a call from `client`, the identity a `client:<id>` grant
+/// vouches for; `None` runs it unauthenticated
+pub async fn as_client<F: std::future::Future>(client: Option<String>, future: F) -> F::Output {
+    match client {
+        Some(client) => AUTHENTICATED_CLIENT.scope(client, future).await,
+        None => future.await,
+    }
+}
+
+/// The client identity the current call's token vouches for, if any. A
+/// `clientId` argument alone is only a claim and never counts.
+pub fn authenticated_client() -> Option<String> {
+    AUTHENTICATED_CLIENT.try_with(Clone::clone).ok()
+}
+
+/// The `-32001 Unauthorized` error for a call the token may not make
+pub fn unauthorized(reason: impl Into<String>) -> JsonRpcError {
+    JsonRpcError {
+        code: UNAUTHORIZED_CODE,
+        message: "Unauthorized".to_string(),
+        data: Some(serde_json::json!(reason.into())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_are_limited_to_their_scopes() {
+        let auth = TokenAuth::parse("dash=read; ci=execute,tool:get_diagram ;ops=admin").unwrap();
+        assert!(auth.is_enabled());
+        assert_eq!(auth.authenticate(None), Err(AuthError::MissingToken));
+        assert_eq!(
+            auth.authenticate(Some("Bearer nope")),
+            Err(AuthError::InvalidToken)
+        );
+
+        let dash = auth.authenticate(Some("Bearer dash")).unwrap();
+        assert!(dash.allows_tool("list_diagrams", None));
+        assert!(!dash.allows_tool("create_node", None));
+        assert!(!dash.allows_tool("execute_component", None));
+
+        // Repairing and exporting write, undeclared tools fail closed
+        let repair = serde_json::json!({"repair": true});
+        assert!(dash.allows_tool("check_integrity", None));
+        assert!(!dash.allows_tool("check_integrity", Some(&repair)));
+        assert!(!dash.allows_tool("export_sensor_data", None));
+        assert!(!dash.allows_tool("no_such_tool", None));
+        assert_eq!(required_scope("no_such_tool", None), Scope::Admin);
+
+        let ci = auth.authenticate(Some("Bearer ci")).unwrap();
+        assert!(ci.allows_tool("execute_component", None));
+        assert!(ci.allows_tool("get_diagram", None));
+        assert!(!ci.allows_tool("list_diagrams", None));
+
+        let ops = auth.authenticate(Some("Bearer ops")).unwrap();
+        assert!(ops.allows_tool("set_workspace_directory", None));
+        assert!(ops.allows_tool("delete_diagram", None));
+        assert_eq!(dash.token_id.as_deref(), Some("token-1"));
+        assert_eq!(ops.token_id.as_deref(), Some("token-3"));
+
+        let alice = TokenAuth::parse("alice=write,client:alice")
+            .unwrap()
+            .authenticate(Some("Bearer alice"))
+            .unwrap();
+        let mut anonymous = None;
+        alice.bind_client(&mut anonymous).unwrap();
+        assert_eq!(anonymous.unwrap()["clientId"], "alice");
+        let mut own = Some(serde_json::json!({"diagramId": "d1", "clientId": "alice"}));
+        assert!(alice.bind_client(&mut own).is_ok());
+        let mut impostor = Some(serde_json::json!({"diagramId": "d1", "clientId": "bob"}));
+        assert!(alice.bind_client(&mut impostor).is_err());
+        let mut unbound = Some(serde_json::json!({"clientId": "bob"}));
+        dash.bind_client(&mut unbound).unwrap();
+        assert_eq!(unbound.unwrap()["clientId"], "bob");
+
+        assert!(TokenAuth::parse("x=read,client:a,client:b").is_err());
+        assert!(TokenAuth::parse("x=client:a").is_err());
+        assert!(TokenAuth::parse("x=read,superuser").is_err());
+        assert!(TokenAuth::parse("x=").is_err());
+        assert!(TokenAuth::parse("")
+            .unwrap()
+            .authenticate(None)
+            .unwrap()
+            .allows_tool("import_workspace", None));
+    }
+
+    #[tokio::test]
+    async fn test_only_bound_calls_have_an_authenticated_client() {
+        assert_eq!(authenticated_client(), None);
+        let client = as_client(Some("alice".to_string()), async { authenticated_client() });
+        assert_eq!(client.await.as_deref(), Some("alice"));
+        assert_eq!(
+            as_client(None, async { authenticated_client() }).await,
+            None
+        );
+    }
+}
@@ -2,23 +2,84 @@
 //!
 //! This is a simplified version to get the basic structure working first.
 
+use crate::auth::authenticated_client;
+use crate::cpu_pool::CpuPool;
 use crate::database::{
-    config::DatabaseBackend, factory::DatabaseManager, BoxedDatasetManager, DatabaseConfig,
+    config::DatabaseBackend, export_sensor_data_with, factory::DatabaseManager, format_timestamp,
+    parse_time_range, BoxedDatasetManager, DatabaseConfig, DatabaseError, ExportFormat,
+    SensorDataRepository, StorageBackend, StorageRegistry,
 };
-use crate::model::{DiagramModel, Edge, ElementType, Node, Position};
-use crate::persistence::PersistenceManager;
+use crate::events::{
+    property_changes, DiagramFilter, EventBus, OverflowPolicy, PropertyChange, ServerEvent,
+    DEFAULT_EVENT_BUFFER_SIZE,
+};
+use crate::history::{
+    self, affected_elements, HistoryEntry, HistoryFilter, HistoryLog, DEFAULT_HISTORY_SIZE,
+};
+use crate::idempotency::{
+    IdempotencyCache, Lookup, IDEMPOTENCY_KEY_ARG, IDEMPOTENT_TOOLS, REPLAY_MARKER,
+};
+use crate::locking::{LockManager, DEFAULT_LOCK_TTL_SECS};
+use crate::model::{
+    normalize_id, DiagramModel, ElementType, IdPrefixes, InvalidId, ModelElement, Node, Position,
+    Viewport, EDGE_ID_PREFIX, READ_ONLY_KEY,
+};
+use crate::operations::compartments::{
+    check_members, class_svg, has_compartments, set_compartment_visibility, CompartmentVisibility,
+};
+use crate::operations::hierarchy::{
+    add_subtask, add_to_container, remove_subtask, set_collapsed, HierarchyError,
+};
+use crate::operations::{
+    apply_force_layout, compare_diagrams, connections, content_bounds, content_extent,
+    create_hyperedge, cycle_closed_by, default_directed, default_merge_offset, default_position,
+    diagram_type_spec, directed_layers, duplicate_diagram, edge_element, extract_subgraph,
+    find_cycles, find_path, fit_to_content, guarded_change, incident_edges, is_directed, is_edge,
+    is_hyperedge, layout_hints, links, merge_diagram, must_be_acyclic, normalize_coordinates,
+    partition_fields, project_diagram, project_element, reconnect_edge, resolve_style,
+    reverse_edge, set_type_style, shortest_path, snap_position, subdiagram_link, suggest_targets,
+    type_styles, DiagramFormat, DuplicateOptions, LabelLimits, LabelTooLong, PageCursor,
+    PlacementStrategy, SnapshotCache, TypeStyle, ACYCLIC_KEY, CANVAS_MARGIN,
+    DEFAULT_MAX_ATTRIBUTE_NAME_LENGTH, DEFAULT_MAX_CLASS_NAME_LENGTH, DEFAULT_MAX_LABEL_LENGTH,
+    DEFAULT_MAX_METHOD_SIGNATURE_LENGTH, DEFAULT_PAGE_SIZE, DIAGRAM_TYPES, DIRECTED_PROPERTY,
+    HYPEREDGE_TYPE, LAYOUT_HINT_ALGORITHMS, MAX_PAGE_SIZE, PARENT_DIAGRAM_KEY,
+};
+use crate::oplog;
+use crate::persistence::{
+    ArchivedAttachment, AttachmentError, AttachmentLimits, AttachmentStore, DeadLetterStore,
+    IdCollisionStrategy, IdMapping, PersistenceFormat, PersistenceManager, StateSnapshotStore,
+    WorkspaceArchive,
+};
+use crate::progress;
+use crate::streaming;
+use crate::tool_flags::ToolFlags;
+use crate::validation::{
+    check_integrity, repair_diagram, repair_integrity, validate_diagram, ValidationIssue,
+};
+use crate::warnings::{deprecations, warnings_item};
 use crate::wasm::{
-    FileSystemWatcher, WasmExecutionEngine, WasmFileWatcher, WasmPipelineEngine,
-    WasmSimulationEngine,
+    build_dependency_graph, find_components_by_interface, section_metadata, CancelOutcome,
+    CustomSection, EngineOptions, ExecutionConcurrency, ExecutionQueueConfig, FileSystemWatcher,
+    InstancePoolConfig, PoolOverflow, ResultCacheConfig, ResultCacheStats, WasmExecutionEngine,
+    WasmFileWatcher, WasmOptLevel, WasmPipelineEngine, WasmSimulationEngine, CUSTOM_SECTIONS_KEY,
+    DEFAULT_EPOCH_TICK_MS, DEFAULT_MAX_INSTANCES, DEFAULT_MAX_WASM_STACK,
+    DEFAULT_RESULT_CACHE_TTL_SECS,
+};
+use crate::webhooks::{
+    WebhookPayload, WebhookRegistry, DEFAULT_MAX_ATTEMPTS as DEFAULT_WEBHOOK_ATTEMPTS,
+    SIGNATURE_HEADER,
 };
+use base64::prelude::*;
 use clap::Parser;
+use futures::FutureExt;
 use pulseengine_mcp_cli_derive::McpConfig;
 use pulseengine_mcp_protocol::*;
 use pulseengine_mcp_server::{BackendError, McpBackend};
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::panic::AssertUnwindSafe;
 use std::path::PathBuf;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 /// Configuration for the GLSP backend
 #[derive(Debug, Clone, McpConfig, Parser)]
@@ -32,11 +93,43 @@ pub struct GlspConfig {
     #[clap(short, long, default_value = "../workspace/diagrams")]
     pub diagrams_path: String,
 
+    /// Encoding of newly saved diagram files: 'json' or 'messagepack' (files of either format are always readable). MessagePack files keep the .glsp.json and .glsp.layout.json names
+    #[clap(long, default_value = "json")]
+    pub persistence_format: String,
+
+    /// Append saves to a per-diagram operation log, rewriting the diagram files after this many entries (0 rewrites them on every save)
+    #[clap(long, default_value = "100")]
+    pub log_compaction_threshold: usize,
+
+    /// Directory for recovery copies of diagrams that failed to save
+    #[clap(long, default_value = "../workspace/dead-letter")]
+    pub dead_letter_path: String,
+
+    /// Threads for CPU-bound tools such as layout and component execution (0 uses one per CPU)
+    #[clap(long, default_value = "0")]
+    pub cpu_pool_size: usize,
+
+    /// Directory for the full-server checkpoints of snapshot_state
+    #[clap(long, default_value = "../workspace/state-snapshots")]
+    pub state_snapshot_path: String,
+
+    /// Largest single file attach_file accepts, in bytes (default 10 MiB)
+    #[clap(long, default_value = "10485760")]
+    pub max_attachment_bytes: u64,
+
+    /// Largest total size of the files attached to one diagram, in bytes (default 50 MiB)
+    #[clap(long, default_value = "52428800")]
+    pub max_diagram_attachment_bytes: u64,
+
+    /// Directory for sensor data exports
+    #[clap(long, default_value = "../workspace/exports")]
+    pub export_path: String,
+
     /// HTTP server port
     #[clap(short, long, default_value = "3000")]
     pub port: u16,
 
-    /// Transport type: 'stdio', 'http', or 'http-streaming' (default: http-streaming)
+    /// Transport type: 'stdio', 'http', 'http-streaming', 'websocket' or 'http-direct' (default: http-streaming). Only http-direct sends CORS headers, so browser clients on another origin need it
     #[clap(long, default_value = "http-streaming")]
     pub transport: String,
 
@@ -44,7 +137,7 @@ pub struct GlspConfig {
     #[clap(short, long)]
     pub force: bool,
 
-    /// Database backend type (postgresql, influxdb, redis, mock)
+    /// Database backend type (postgresql, influxdb, redis, sqlite, mock or memory); unknown names fail startup when the database is enabled
     #[clap(long, default_value = "mock")]
     pub database_backend: String,
 
@@ -68,6 +161,166 @@ pub struct GlspConfig {
     #[clap(long)]
     pub enable_database: bool,
 
+    /// Allowed CORS origins for the http-direct transport (comma-separated, '*' for any; empty disables CORS). Other transports do not expose their HTTP layer and refuse to start with origins set
+    #[clap(long, default_value = "")]
+    pub cors_allowed_origins: String,
+
+    /// Allowed CORS methods (comma-separated, http-direct transport)
+    #[clap(long, default_value = "GET,POST,PUT,OPTIONS")]
+    pub cors_allowed_methods: String,
+
+    /// Allowed CORS request headers (comma-separated, http-direct transport)
+    #[clap(long, default_value = "content-type,authorization")]
+    pub cors_allowed_headers: String,
+
+    /// Allow credentialed CORS requests (requires an explicit origin list; http-direct transport)
+    #[clap(long)]
+    pub cors_allow_credentials: bool,
+
+    /// Largest accepted /messages body in bytes (http-direct transport); larger requests get 413
+    #[clap(long, default_value = "8388608")]
+    pub http_max_body_bytes: usize,
+
+    /// Seconds a client may take to send a /messages body (http-direct transport); slower requests get 408
+    #[clap(long, default_value = "30")]
+    pub http_request_timeout_secs: u64,
+
+    /// Diagram type create_diagram uses when the call gives none; must be a declared type (see get_diagram_type_capabilities). Empty makes diagramType required
+    #[clap(long, default_value = "")]
+    pub default_diagram_type: String,
+
+    /// Where create_node places nodes given without a position: 'grid', 'next-free-slot' or 'below-last'
+    #[clap(long, default_value = "next-free-slot")]
+    pub placement_strategy: String,
+
+    /// Snap node positions set on create/move to multiples of this value (e.g. 1 or 0.5); 0 keeps them exact
+    #[clap(long, default_value = "0")]
+    pub coordinate_grid: f64,
+
+    /// Longest node label, in characters
+    #[clap(long, default_value = "200")]
+    pub max_label_length: usize,
+
+    /// Longest name of a UML class, interface or enum node, in characters
+    #[clap(long, default_value = "100")]
+    pub max_class_name_length: usize,
+
+    /// Longest UML attribute name, in characters
+    #[clap(long, default_value = "100")]
+    pub max_attribute_name_length: usize,
+
+    /// Longest UML method signature as shown in the class box (e.g. '+area(): f64'), in characters
+    #[clap(long, default_value = "200")]
+    pub max_method_signature_length: usize,
+
+    /// Instantiate every component in a throwaway store at startup and report failures via /ready
+    #[clap(long)]
+    pub instantiation_check: bool,
+
+    /// Repeat the instantiation check every N seconds (0 runs it at startup only)
+    #[clap(long, default_value = "0")]
+    pub instantiation_check_interval_secs: u64,
+
+    /// Components (comma-separated names) to compile during startup rather than on first execution; /ready fails while any of them failed
+    #[clap(long, default_value = "")]
+    pub preload_components: String,
+
+    /// Also instantiate preloaded components in a throwaway store
+    #[clap(long)]
+    pub preload_instantiate: bool,
+
+    /// Recent operations get_history keeps per diagram; 0 disables the audit trail
+    #[clap(long, default_value = "200")]
+    pub history_size: usize,
+
+    /// Number of events an /events connection may fall behind before it overflows
+    #[clap(long, default_value = "256")]
+    pub sse_buffer_size: usize,
+
+    /// What to do when an /events connection overflows: 'resync' or 'disconnect'
+    #[clap(long, default_value = "resync")]
+    pub sse_overflow_policy: String,
+
+    /// Webhook URLs (comma-separated) that receive a POST for every diagram change; more can be registered with register_webhook
+    #[clap(long, default_value = "")]
+    pub webhook_urls: String,
+
+    /// Secret for signing requests to the webhooks given by --webhook-urls; their requests are unsigned when unset
+    #[clap(long)]
+    pub webhook_secret: Option<String>,
+
+    /// Delivery attempts per change before a webhook request is dropped; retries back off exponentially
+    #[clap(long, default_value = "5")]
+    pub webhook_max_attempts: u32,
+
+    /// Cranelift optimization level for WASM components: 'none' (fast compile), 'speed' or 'speed-and-size'
+    #[clap(long, default_value = "speed")]
+    pub wasm_opt_level: String,
+
+    /// Directory for precompiled WASM modules; compiled modules are only kept in memory when unset
+    #[clap(long)]
+    pub wasm_precompile_cache_dir: Option<String>,
+
+    /// Reusable instances kept per WASM component (0 instantiates on every call)
+    #[clap(long, default_value = "0")]
+    pub instance_pool_size: usize,
+
+    /// What a call does when all pooled instances are busy: 'queue' or 'reject'
+    #[clap(long, default_value = "queue")]
+    pub instance_pool_overflow: String,
+
+    /// Bytes of stack WASM code may use; deeper recursion fails the call with a stack overflow error (default 512 KiB)
+    #[clap(long, default_value = "524288")]
+    pub max_wasm_stack: usize,
+
+    /// Instances, including core instances inside a component, one execution may create
+    #[clap(long, default_value = "100")]
+    pub max_instances: usize,
+
+    /// Component executions allowed to run at the same time
+    #[clap(long, default_value = "10")]
+    pub max_concurrent_executions: usize,
+
+    /// What an execution beyond the limit does: 'queue' (wait for a slot) or 'reject' (fail with component busy)
+    #[clap(long, default_value = "queue")]
+    pub execution_overflow: String,
+
+    /// Longest a queued execution waits for a slot before failing with component busy
+    #[clap(long, default_value = "30000")]
+    pub execution_queue_timeout_ms: u64,
+
+    /// Execution results cached by component, function and arguments (0 disables the cache)
+    #[clap(long, default_value = "0")]
+    pub result_cache_size: usize,
+
+    /// Seconds a cached execution result stays valid
+    #[clap(long, default_value = "300")]
+    pub result_cache_ttl_secs: u64,
+
+    /// Milliseconds between epoch ticks: timeouts and cancellation stop a running component at the next tick. Shorter reacts faster but wakes the server more often (minimum 1)
+    #[clap(long, default_value = "10")]
+    pub epoch_tick_ms: u64,
+
+    /// Meter WASM fuel so profiled executions report fuel consumption. Metering slows every execution, so it is off by default
+    #[clap(long)]
+    pub wasm_fuel_metering: bool,
+
+    /// Diagram types (comma-separated, '*' for all) whose new nodes and edges get IDs prefixed with the node type or 'edge', e.g. 'task-<uuid>'
+    #[clap(long, default_value = "")]
+    pub id_prefix_diagram_types: String,
+
+    /// Tools to disable (comma-separated); they are hidden from tools/list and calls fail with method not found
+    #[clap(long, default_value = "")]
+    pub disabled_tools: String,
+
+    /// JSON file with {"disabled": [tool names]}; re-read when it changes, so tools can be toggled without a restart
+    #[clap(long)]
+    pub tool_flags_file: Option<String>,
+
+    /// Bearer tokens for the http-direct transport as 'token=grant,...;token=...', where a grant is read, write, execute, admin, tool:<name> or client:<id> (the clientId the token's calls act as); empty disables authentication. Other transports cannot enforce tokens and refuse to start with them
+    #[clap(long, default_value = "")]
+    pub auth_tokens: String,
+
     /// Server name (auto-populated)
     #[mcp(auto_populate)]
     #[clap(skip)]
@@ -79,11 +332,28 @@ pub struct GlspConfig {
     pub server_version: String,
 }
 
+/// Transports `run_server` can serve, as named by `--transport`
+pub const TRANSPORTS: &[&str] = &[
+    "stdio",
+    "http",
+    "http-streaming",
+    "websocket",
+    "http-direct",
+];
+
 impl Default for GlspConfig {
     fn default() -> Self {
         Self {
             wasm_path: "../workspace/adas-wasm-components".to_string(),
             diagrams_path: "../workspace/diagrams".to_string(),
+            persistence_format: "json".to_string(),
+            log_compaction_threshold: 100,
+            dead_letter_path: "../workspace/dead-letter".to_string(),
+            cpu_pool_size: 0,
+            state_snapshot_path: "../workspace/state-snapshots".to_string(),
+            max_attachment_bytes: 10 * 1024 * 1024,
+            max_diagram_attachment_bytes: 50 * 1024 * 1024,
+            export_path: "../workspace/exports".to_string(),
             port: 3000,
             transport: "http-streaming".to_string(),
             force: false,
@@ -93,6 +363,46 @@ impl Default for GlspConfig {
             database_name: "glsp_sensors".to_string(),
             database_user: None,
             enable_database: false,
+            cors_allowed_origins: String::new(),
+            cors_allowed_methods: "GET,POST,PUT,OPTIONS".to_string(),
+            cors_allowed_headers: "content-type,authorization".to_string(),
+            cors_allow_credentials: false,
+            http_max_body_bytes: 8 * 1024 * 1024,
+            http_request_timeout_secs: 30,
+            default_diagram_type: String::new(),
+            placement_strategy: "next-free-slot".to_string(),
+            coordinate_grid: 0.0,
+            max_label_length: DEFAULT_MAX_LABEL_LENGTH,
+            max_class_name_length: DEFAULT_MAX_CLASS_NAME_LENGTH,
+            max_attribute_name_length: DEFAULT_MAX_ATTRIBUTE_NAME_LENGTH,
+            max_method_signature_length: DEFAULT_MAX_METHOD_SIGNATURE_LENGTH,
+            instantiation_check: false,
+            instantiation_check_interval_secs: 0,
+            preload_components: String::new(),
+            preload_instantiate: false,
+            sse_buffer_size: DEFAULT_EVENT_BUFFER_SIZE,
+            sse_overflow_policy: "resync".to_string(),
+            webhook_urls: String::new(),
+            webhook_secret: None,
+            webhook_max_attempts: DEFAULT_WEBHOOK_ATTEMPTS,
+            history_size: DEFAULT_HISTORY_SIZE,
+            wasm_opt_level: "speed".to_string(),
+            wasm_precompile_cache_dir: None,
+            instance_pool_size: 0,
+            instance_pool_overflow: "queue".to_string(),
+            max_wasm_stack: DEFAULT_MAX_WASM_STACK,
+            max_instances: DEFAULT_MAX_INSTANCES,
+            max_concurrent_executions: 10,
+            execution_overflow: "queue".to_string(),
+            execution_queue_timeout_ms: 30_000,
+            result_cache_size: 0,
+            result_cache_ttl_secs: DEFAULT_RESULT_CACHE_TTL_SECS,
+            epoch_tick_ms: DEFAULT_EPOCH_TICK_MS,
+            wasm_fuel_metering: false,
+            id_prefix_diagram_types: String::new(),
+            disabled_tools: String::new(),
+            tool_flags_file: None,
+            auth_tokens: String::new(),
             server_name: "GLSP MCP Server".to_string(),
             server_version: env!("CARGO_PKG_VERSION").to_string(),
         }
@@ -111,7 +421,7 @@ impl GlspConfig {
             "influxdb" | "influx" => DatabaseBackend::InfluxDB,
             "redis" => DatabaseBackend::Redis,
             "sqlite" => DatabaseBackend::SQLite,
-            "mock" => DatabaseBackend::Mock,
+            "mock" | "memory" => DatabaseBackend::Mock,
             _ => {
                 return Err(format!(
                     "Unknown database backend: {}",
@@ -137,6 +447,114 @@ impl GlspConfig {
         Ok(config)
     }
 
+    /// Compilation settings for the WASM execution engines
+    pub fn engine_options(&self) -> EngineOptions {
+        let opt_level = self
+            .wasm_opt_level
+            .parse::<WasmOptLevel>()
+            .unwrap_or_else(|e| {
+                warn!("{e}; using speed");
+                WasmOptLevel::Speed
+            });
+        let overflow = self
+            .instance_pool_overflow
+            .parse::<PoolOverflow>()
+            .unwrap_or_else(|e| {
+                warn!("{e}; using queue");
+                PoolOverflow::Queue
+            });
+        let execution_overflow = self
+            .execution_overflow
+            .parse::<PoolOverflow>()
+            .unwrap_or_else(|e| {
+                warn!("{e}; using queue");
+                PoolOverflow::Queue
+            });
+        EngineOptions {
+            opt_level,
+            precompile_cache_dir: self.wasm_precompile_cache_dir.as_ref().map(PathBuf::from),
+            instance_pool: InstancePoolConfig {
+                size: self.instance_pool_size,
+                overflow,
+            },
+            max_wasm_stack: self.max_wasm_stack,
+            max_instances: self.max_instances,
+            execution_queue: ExecutionQueueConfig {
+                overflow: execution_overflow,
+                max_wait: std::time::Duration::from_millis(self.execution_queue_timeout_ms),
+            },
+            result_cache: ResultCacheConfig {
+                max_entries: self.result_cache_size,
+                ttl: std::time::Duration::from_secs(self.result_cache_ttl_secs),
+            },
+            epoch_tick: std::time::Duration::from_millis(self.epoch_tick_ms),
+            fuel_metering: self.wasm_fuel_metering,
+        }
+    }
+
+    /// The diagram type applied when create_diagram gives none, if configured.
+    /// Fails for a type that has no declaration.
+    pub fn default_diagram_type(&self) -> std::result::Result<Option<&str>, String> {
+        let diagram_type = self.default_diagram_type.trim();
+        if diagram_type.is_empty() {
+            return Ok(None);
+        }
+        match diagram_type_spec(diagram_type) {
+            Some(spec) => Ok(Some(spec.diagram_type)),
+            None => Err(format!(
+                "Unknown default diagram type '{diagram_type}' (available: {})",
+                DIAGRAM_TYPES
+                    .iter()
+                    .map(|spec| spec.diagram_type)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    /// Fails when an option only the http-direct transport implements is set
+    /// for another transport, which would silently ignore it
+    pub fn check_transport_options(&self) -> std::result::Result<(), String> {
+        if self.transport == "http-direct" {
+            return Ok(());
+        }
+        let direct_only = [
+            ("--auth-tokens", &self.auth_tokens),
+            ("--cors-allowed-origins", &self.cors_allowed_origins),
+        ];
+        match direct_only
+            .iter()
+            .find(|(_, value)| !value.trim().is_empty())
+        {
+            Some((option, _)) => Err(format!(
+                "{option} is only supported by the http-direct transport, not by {}",
+                self.transport
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Names of the components to preload at startup
+    pub fn preload_component_names(&self) -> Vec<String> {
+        self.preload_components
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Tool feature flags from `disabled_tools` and `tool_flags_file`
+    pub fn tool_flags(&self) -> ToolFlags {
+        let disabled = self
+            .disabled_tools
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string);
+        ToolFlags::new(disabled, self.tool_flags_file.as_ref().map(PathBuf::from))
+    }
+
     /// Get the base directory for diagram storage, ensuring it exists
     pub async fn ensure_diagrams_dir(&self) -> std::result::Result<PathBuf, std::io::Error> {
         let path = PathBuf::from(&self.diagrams_path);
@@ -173,6 +591,45 @@ pub enum GlspError {
 
     #[error("Backend error: {0}")]
     Backend(#[from] BackendError),
+
+    #[error("Diagram is locked by {holder}")]
+    DiagramLocked { holder: String },
+
+    #[error("Element {element_id} is locked by {holder}")]
+    ElementLocked { element_id: String, holder: String },
+
+    /// The diagram is marked read-only
+    #[error("Diagram {diagram_id} is read-only")]
+    DiagramReadOnly { diagram_id: String },
+
+    #[error("Invalid ID: {0}")]
+    InvalidId(#[from] InvalidId),
+
+    #[error("Tool is disabled: {0}")]
+    ToolDisabled(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+
+    /// A tool handler panicked; details are only in the server log
+    #[error("Internal error (incident {incident_id})")]
+    Panic { incident_id: String },
+
+    /// The change would close a cycle in a diagram that must stay acyclic
+    #[error("Change would create a cycle: {}", .cycle.join(" -> "))]
+    WouldCreateCycle { cycle: Vec<String> },
+
+    /// The change would make an element contain itself
+    #[error("Change would create a containment cycle: {}", .cycle.join(" -> "))]
+    ContainmentCycle { cycle: Vec<String> },
+
+    /// A label or UML member is longer than the configured limit
+    #[error("{field} is {length} characters long, the limit is {limit}")]
+    LabelTooLong {
+        field: String,
+        length: usize,
+        limit: usize,
+    },
 }
 
 impl From<GlspError> for Error {
@@ -187,10 +644,233 @@ impl From<GlspError> for Error {
             GlspError::Io(e) => Error::internal_error(format!("IO error: {e}")),
             GlspError::Json(e) => Error::internal_error(format!("JSON error: {e}")),
             GlspError::Backend(e) => Error::internal_error(format!("Backend error: {e}")),
+            GlspError::DiagramLocked { holder } => {
+                Error::internal_error(format!("Diagram is locked by {holder}"))
+            }
+            GlspError::ElementLocked { element_id, holder } => {
+                Error::internal_error(format!("Element {element_id} is locked by {holder}"))
+            }
+            GlspError::DiagramReadOnly { diagram_id } => Error::internal_error(format!(
+                "DiagramReadOnly: diagram {diagram_id} is read-only; clear the flag with set_diagram_readonly to change it"
+            )),
+            GlspError::InvalidId(e) => Error::internal_error(format!("Invalid ID: {e}")),
+            GlspError::ToolDisabled(tool) => {
+                Error::method_not_found(format!("Tool is disabled: {tool}"))
+            }
+            GlspError::Database(e) => Error::internal_error(format!("Database error: {e}")),
+            GlspError::Panic { incident_id } => Error::internal_error(format!(
+                "Internal error while running the tool (incident {incident_id})"
+            )),
+            GlspError::WouldCreateCycle { cycle } => Error::internal_error(format!(
+                "WouldCreateCycle: the diagram must stay acyclic and the change would create the cycle {}",
+                cycle.join(" -> ")
+            )),
+            GlspError::ContainmentCycle { cycle } => Error::internal_error(format!(
+                "ContainmentCycle: containment must stay a tree and the change would make {} contain itself through {} -> {}",
+                cycle[0],
+                cycle.join(" -> "),
+                cycle[0]
+            )),
+            GlspError::LabelTooLong {
+                field,
+                length,
+                limit,
+            } => Error::internal_error(format!(
+                "LabelTooLong: {field} is {length} characters long and may have at most {limit}"
+            )),
+        }
+    }
+}
+
+impl From<LabelTooLong> for GlspError {
+    fn from(err: LabelTooLong) -> Self {
+        GlspError::LabelTooLong {
+            field: err.field,
+            length: err.length,
+            limit: err.limit,
+        }
+    }
+}
+
+/// What `create_diagram` does when a diagram with the requested name exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IfExists {
+    /// Create another diagram with the same name
+    #[default]
+    Create,
+    /// Return the existing diagram instead
+    Reuse,
+    /// Fail without creating anything
+    Error,
+}
+
+impl std::str::FromStr for IfExists {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "create" => Ok(IfExists::Create),
+            "reuse" => Ok(IfExists::Reuse),
+            "error" => Ok(IfExists::Error),
+            other => Err(format!(
+                "Unknown ifExists policy '{other}' (expected create, reuse or error)"
+            )),
+        }
+    }
+}
+
+/// Text of a panic payload, for the server log
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string())
+}
+
+/// Arguments naming a single element that a mutating tool changes; element
+/// locks are checked against them
+const ELEMENT_ID_ARGS: &[&str] = &["elementId", "nodeId", "edgeId"];
+
+/// Arguments listing elements that a mutating tool changes
+const ELEMENT_IDS_ARGS: &[&str] = &["elementIds", "nodeIds"];
+
+/// Mutating tools that move, restyle, copy into or remove elements they do
+/// not name; a lock on any element of a diagram they modify blocks them
+const WHOLE_DIAGRAM_TOOLS: &[&str] = &[
+    "delete_diagram",
+    "merge_diagrams",
+    "apply_layout",
+    "normalize_coordinates",
+    "set_type_style",
+    "extract_subgraph",
+    "check_integrity",
+];
+
+/// Tools that modify a diagram and are therefore subject to edit locks
+pub(crate) const MUTATING_TOOLS: &[&str] = &[
+    "delete_diagram",
+    "merge_diagrams",
+    "attach_file",
+    "set_diagram_metadata",
+    "set_viewport",
+    "set_type_style",
+    "add_diagram_tags",
+    "create_node",
+    "create_edge",
+    "create_edges",
+    "create_hyperedge",
+    "reconnect_edge",
+    "reverse_edge",
+    "delete_element",
+    "update_element",
+    "set_compartment_visibility",
+    "add_subtask",
+    "add_to_container",
+    "remove_subtask",
+    "set_task_collapsed",
+    "apply_layout",
+    "normalize_coordinates",
+    "extract_subgraph",
+    "save_diagram",
+    "set_diagram_readonly",
+];
+
+/// Mutating tools whose handlers report the elements they touch with
+/// [`history::touched`]; the history of every other mutating tool diffs a
+/// copy of the diagram taken before the call
+const TRACKED_TOOLS: &[&str] = &[
+    "delete_diagram",
+    "merge_diagrams",
+    "attach_file",
+    "set_diagram_metadata",
+    "set_viewport",
+    "set_type_style",
+    "add_diagram_tags",
+    "create_node",
+    "create_edge",
+    "create_edges",
+    "create_hyperedge",
+    "reconnect_edge",
+    "reverse_edge",
+    "delete_element",
+    "update_element",
+    "set_compartment_visibility",
+    "save_diagram",
+    "set_diagram_readonly",
+    "check_integrity",
+];
+
+/// Tools that replace or remove whole diagrams of the workspace; the
+/// diagrams they changed are listed in the `diagramIds` and
+/// `removedDiagramIds` of their result
+const WORKSPACE_TOOLS: &[&str] = &["import_workspace", "restore_state"];
+
+/// Whether a call is a `check_integrity` that repairs what it finds
+fn repairs_integrity(tool: &str, args: Option<&serde_json::Value>) -> bool {
+    tool == "check_integrity" && args.is_some_and(|args| args["repair"].as_bool() == Some(true))
+}
+
+/// Diagrams a mutating tool call changes, as named by its arguments.
+///
+/// Most tools change the one `diagramId`; `merge_diagrams` changes its
+/// `targetId` and, with `deleteSource`, its `sourceId`. A `check_integrity`
+/// repair changes its `diagramId`; see [`GlspBackend::changed_diagrams`]
+/// for one without.
+fn modified_diagrams(tool: &str, args: Option<&serde_json::Value>) -> Vec<String> {
+    let mutating = MUTATING_TOOLS.contains(&tool) || repairs_integrity(tool, args);
+    let Some(args) = args.filter(|_| mutating) else {
+        return Vec::new();
+    };
+    let keys: &[&str] = match tool {
+        "merge_diagrams" if args["deleteSource"].as_bool() == Some(true) => {
+            &["targetId", "sourceId"]
         }
+        "merge_diagrams" => &["targetId"],
+        _ => &["diagramId"],
+    };
+    keys.iter()
+        .filter_map(|key| args[*key].as_str())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Diagrams a [`WORKSPACE_TOOLS`] call reports having changed
+fn reported_diagrams(tool: &str, outcome: &CallToolResult) -> Vec<String> {
+    if !WORKSPACE_TOOLS.contains(&tool) {
+        return Vec::new();
     }
+    let Some(result) = outcome.content.iter().find_map(|content| match content {
+        Content::Text { text } => serde_json::from_str::<serde_json::Value>(text).ok(),
+        _ => None,
+    }) else {
+        return Vec::new();
+    };
+    ["diagramIds", "removedDiagramIds"]
+        .iter()
+        .filter_map(|key| result[*key].as_array())
+        .flatten()
+        .filter_map(|id| id.as_str().map(str::to_string))
+        .collect()
 }
 
+/// Mutating tools that edit one element (`elementId`) in place; their events
+/// list each changed field with its old and new value
+const PROPERTY_CHANGE_TOOLS: &[&str] = &["update_element"];
+
+/// Tools whose work is CPU-bound; they run it on the [`CpuPool`] so a large
+/// diagram does not stall the async runtime
+pub const CPU_BOUND_TOOLS: &[&str] = &["apply_layout", "compute_layout_hints", "execute_component"];
+
+/// Schema description of `clientId` on the tools that create elements
+const CREATOR_CLIENT_ID: &str = "Client making the call, checked against edit locks. It is not recorded as the elements' createdBy: only an authenticated identity is (an http-direct token with a client:<id> grant), and calls without one leave createdBy unset";
+
+/// Mutating tools that still work on a read-only diagram
+const READ_ONLY_EXEMPT_TOOLS: &[&str] = &["save_diagram", "set_diagram_readonly"];
+
+/// Sensors a streamed `latest_readings` looks up per database query
+const LATEST_READINGS_CHUNK: usize = 100;
+
 /// GLSP Backend implementation - The core server backend for AI-native diagram modeling
 ///
 /// This backend provides a complete implementation of the Model Context Protocol (MCP)
@@ -228,10 +908,21 @@ pub struct GlspBackend {
     wasm_watcher: std::sync::Arc<tokio::sync::Mutex<WasmFileWatcher>>,
     filesystem_watcher: std::sync::Arc<tokio::sync::RwLock<FileSystemWatcher>>,
     persistence: std::sync::Arc<PersistenceManager>,
+    dead_letters: std::sync::Arc<DeadLetterStore>,
+    state_snapshots: std::sync::Arc<StateSnapshotStore>,
+    cpu_pool: CpuPool,
+    attachments: std::sync::Arc<AttachmentStore>,
     database_manager: Option<std::sync::Arc<DatabaseManager>>,
     execution_engine: Option<std::sync::Arc<WasmExecutionEngine>>,
     pipeline_engine: Option<std::sync::Arc<WasmPipelineEngine>>,
     simulation_engine: Option<std::sync::Arc<WasmSimulationEngine>>,
+    locks: std::sync::Arc<tokio::sync::Mutex<LockManager>>,
+    events: std::sync::Arc<EventBus>,
+    idempotency: std::sync::Arc<tokio::sync::Mutex<IdempotencyCache>>,
+    history: std::sync::Arc<tokio::sync::Mutex<HistoryLog>>,
+    page_snapshots: std::sync::Arc<tokio::sync::Mutex<SnapshotCache>>,
+    tool_flags: std::sync::Arc<ToolFlags>,
+    webhooks: std::sync::Arc<WebhookRegistry>,
 }
 
 impl GlspBackend {
@@ -247,16 +938,47 @@ impl GlspBackend {
             GlspError::NotImplemented(format!("Failed to start filesystem watcher: {e}"))
         })?;
 
+        config
+            .check_transport_options()
+            .map_err(GlspError::NotImplemented)?;
+        if let Some(diagram_type) = config
+            .default_diagram_type()
+            .map_err(GlspError::NotImplemented)?
+        {
+            info!("Diagrams created without a type will be {diagram_type} diagrams");
+        }
+
         let diagrams_path = PathBuf::from(&config.diagrams_path);
-        let persistence = PersistenceManager::new(diagrams_path);
+        let persistence_format = config
+            .persistence_format
+            .parse::<PersistenceFormat>()
+            .map_err(GlspError::NotImplemented)?;
+        let persistence = PersistenceManager::with_format(diagrams_path, persistence_format)
+            .with_compaction_threshold(config.log_compaction_threshold);
 
         // Ensure storage directory exists
         persistence.ensure_storage_dir().await.map_err(|e| {
             GlspError::NotImplemented(format!("Failed to create storage directory: {e}"))
         })?;
 
+        let dead_letters = DeadLetterStore::new(&config.dead_letter_path);
+        let state_snapshots = StateSnapshotStore::new(&config.state_snapshot_path);
+        let cpu_pool = CpuPool::new(config.cpu_pool_size);
+        info!("CPU pool runs up to {} jobs at once", cpu_pool.size());
+        let attachments = AttachmentStore::new(
+            std::path::Path::new(&config.diagrams_path).join("attachments"),
+            AttachmentLimits {
+                max_attachment_bytes: config.max_attachment_bytes,
+                max_diagram_bytes: config.max_diagram_attachment_bytes,
+            },
+        );
+
         // Initialize database if enabled
         let database_manager = if config.enable_database {
+            // A misspelled backend is a configuration mistake, not an outage,
+            // so refuse to start rather than run without a database
+            StorageRegistry::default().resolve(&config.database_backend)?;
+
             info!("Initializing database connection...");
             match config.to_database_config() {
                 Ok(db_config) => {
@@ -300,11 +1022,14 @@ impl GlspBackend {
 
                             // Create execution engine with sensor support
                             match WasmExecutionEngine::with_dataset_manager(
-                                10,
+                                config.max_concurrent_executions,
                                 dataset_manager_arc.clone(),
+                                config.engine_options(),
                             ) {
                                 Ok(exec_engine) => {
-                                    let exec_engine_arc = std::sync::Arc::new(exec_engine);
+                                    let exec_engine_arc = std::sync::Arc::new(
+                                        exec_engine.with_cpu_pool(cpu_pool.clone()),
+                                    );
 
                                     // Create pipeline engine
                                     let pipeline_engine =
@@ -353,9 +1078,13 @@ impl GlspBackend {
             }
         } else {
             // Create basic execution engine without sensor support
-            match WasmExecutionEngine::new(10) {
+            match WasmExecutionEngine::with_options(
+                config.max_concurrent_executions,
+                config.engine_options(),
+            ) {
                 Ok(exec_engine) => {
-                    let exec_engine_arc = std::sync::Arc::new(exec_engine);
+                    let exec_engine_arc =
+                        std::sync::Arc::new(exec_engine.with_cpu_pool(cpu_pool.clone()));
                     let pipeline_engine = WasmPipelineEngine::new(exec_engine_arc.clone(), 5);
                     let pipeline_engine_arc = std::sync::Arc::new(pipeline_engine);
 
@@ -378,21 +1107,62 @@ impl GlspBackend {
             }
         };
 
+        let overflow_policy = config
+            .sse_overflow_policy
+            .parse::<OverflowPolicy>()
+            .unwrap_or_else(|e| {
+                warn!("{e}; using resync");
+                OverflowPolicy::default()
+            });
+        let events = std::sync::Arc::new(EventBus::new(config.sse_buffer_size, overflow_policy));
+
+        let webhooks = WebhookRegistry::new(config.webhook_max_attempts);
+        for url in config
+            .webhook_urls
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+        {
+            webhooks
+                .register(url, DiagramFilter::all(), config.webhook_secret.clone())
+                .map_err(GlspError::NotImplemented)?;
+            info!("Diagram changes are posted to webhook {url}");
+        }
+
+        let tool_flags = config.tool_flags();
+        let disabled_tools = tool_flags.disabled();
+        if !disabled_tools.is_empty() {
+            info!("Disabled tools: {:?}", disabled_tools);
+        }
+
         // Create backend instance
+        let history = HistoryLog::new(config.history_size);
         let backend = Self {
             config,
             models: std::sync::Arc::new(tokio::sync::Mutex::new(HashMap::new())),
             wasm_watcher: std::sync::Arc::new(tokio::sync::Mutex::new(wasm_watcher)),
             filesystem_watcher: std::sync::Arc::new(tokio::sync::RwLock::new(filesystem_watcher)),
             persistence: std::sync::Arc::new(persistence),
+            dead_letters: std::sync::Arc::new(dead_letters),
+            state_snapshots: std::sync::Arc::new(state_snapshots),
+            cpu_pool,
+            attachments: std::sync::Arc::new(attachments),
             database_manager,
             execution_engine,
             pipeline_engine,
             simulation_engine,
+            locks: std::sync::Arc::new(tokio::sync::Mutex::new(LockManager::new())),
+            events,
+            idempotency: std::sync::Arc::new(tokio::sync::Mutex::new(IdempotencyCache::new())),
+            history: std::sync::Arc::new(tokio::sync::Mutex::new(history)),
+            page_snapshots: std::sync::Arc::new(tokio::sync::Mutex::new(SnapshotCache::new())),
+            tool_flags: std::sync::Arc::new(tool_flags),
+            webhooks: std::sync::Arc::new(webhooks),
         };
 
         // Load existing diagrams from disk
         backend.load_all_diagrams().await?;
+        backend.restore_dead_letters().await;
 
         // Perform initial WASM component scan with statistics
         info!("Performing initial WASM component scan...");
@@ -400,9 +1170,12 @@ impl GlspBackend {
             let mut wasm_watcher = backend.wasm_watcher.lock().await;
 
             // Initialize with execution engine
-            *wasm_watcher = wasm_watcher.clone().with_execution_engine(3).map_err(|e| {
-                GlspError::NotImplemented(format!("Failed to init execution engine: {e}"))
-            })?;
+            *wasm_watcher = wasm_watcher
+                .clone()
+                .with_execution_engine(3, backend.config.engine_options())
+                .map_err(|e| {
+                    GlspError::NotImplemented(format!("Failed to init execution engine: {e}"))
+                })?;
 
             // Start file watching for real-time updates
             if let Err(e) = wasm_watcher.start_file_watching().await {
@@ -413,11 +1186,136 @@ impl GlspBackend {
             if let Err(e) = wasm_watcher.scan_components().await {
                 error!("Failed to perform initial WASM component scan: {}", e);
             }
+
+            let summary = wasm_watcher.get_load_summary();
+            if summary.failed > 0 {
+                warn!(
+                    "{} of {} WASM components failed to load; see get_component_status",
+                    summary.failed,
+                    summary.loaded + summary.failed
+                );
+            }
+        }
+
+        backend.preload_components().await;
+
+        if backend.config.instantiation_check {
+            backend.run_instantiation_checks().await;
+
+            let interval_secs = backend.config.instantiation_check_interval_secs;
+            if interval_secs > 0 {
+                let periodic = backend.clone();
+                tokio::spawn(async move {
+                    let mut interval =
+                        tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+                    interval.tick().await;
+                    loop {
+                        interval.tick().await;
+                        periodic.run_instantiation_checks().await;
+                    }
+                });
+            }
         }
 
         Ok(backend)
     }
 
+    /// Compile, and optionally instantiate, the configured preload list.
+    ///
+    /// These components are the ones a deployment cannot afford to start
+    /// cold, so every failure is logged as an error.
+    async fn preload_components(&self) {
+        let names = self.config.preload_component_names();
+        if names.is_empty() {
+            return;
+        }
+        info!("Preloading {} WASM component(s)...", names.len());
+        let preloads = self
+            .wasm_watcher
+            .lock()
+            .await
+            .preload_components(&names, self.config.preload_instantiate)
+            .await;
+        for preload in &preloads {
+            match &preload.error {
+                Some(e) => error!("Failed to preload WASM component '{}': {e}", preload.name),
+                None => info!(
+                    "Preloaded WASM component '{}' in {} ms",
+                    preload.name,
+                    preload.duration_us.unwrap_or_default() / 1000
+                ),
+            }
+        }
+        let failed = preloads.iter().filter(|p| !p.success).count();
+        if failed > 0 {
+            error!(
+                "{failed} of {} preloaded WASM components failed; /ready reports not ready",
+                preloads.len()
+            );
+        }
+    }
+
+    /// Instantiate every loaded component and record the outcome
+    async fn run_instantiation_checks(&self) {
+        let checks = self
+            .wasm_watcher
+            .lock()
+            .await
+            .run_instantiation_checks()
+            .await;
+        let failed = checks.iter().filter(|c| !c.success).count();
+        if failed > 0 {
+            warn!(
+                "{} of {} WASM components failed to instantiate; see get_component_status",
+                failed,
+                checks.len()
+            );
+        } else {
+            info!(
+                "All {} WASM components instantiated successfully",
+                checks.len()
+            );
+        }
+    }
+
+    /// Health check plus a check that every preloaded component loaded and,
+    /// when instantiation checks are enabled, that every component
+    /// instantiated on the last run
+    pub async fn readiness_check(&self) -> std::result::Result<(), GlspError> {
+        self.health_check().await?;
+
+        let summary = self.wasm_watcher.lock().await.get_load_summary();
+        let failed: Vec<&str> = summary
+            .preloads
+            .iter()
+            .filter(|p| !p.success)
+            .map(|p| p.name.as_str())
+            .collect();
+        if !failed.is_empty() {
+            return Err(GlspError::ToolExecution(format!(
+                "Preloaded WASM components failed to load: {}",
+                failed.join(", ")
+            )));
+        }
+
+        if self.config.instantiation_check {
+            let failed: Vec<&str> = summary
+                .instantiation_checks
+                .iter()
+                .filter(|c| !c.success)
+                .map(|c| c.name.as_str())
+                .collect();
+            if !failed.is_empty() {
+                return Err(GlspError::ToolExecution(format!(
+                    "WASM components failed to instantiate: {}",
+                    failed.join(", ")
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn get_server_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::default(),
@@ -434,6 +1332,38 @@ impl GlspBackend {
         }
     }
 
+    /// Versions and capabilities a client can check before relying on them.
+    ///
+    /// Batches, NDJSON and progress streaming, `/events` and bearer tokens
+    /// are features of the http-direct transport; over the framework
+    /// transports their flags are false.
+    pub fn server_description(&self) -> serde_json::Value {
+        let direct = self.config.transport == "http-direct";
+        json!({
+            "name": self.config.server_name,
+            "version": self.config.server_version,
+            "protocolVersion": ProtocolVersion::default(),
+            "jsonrpc": "2.0",
+            "transport": self.config.transport,
+            "transports": TRANSPORTS,
+            "capabilities": {
+                "tools": true,
+                "resources": true,
+                "prompts": true,
+                "batch": direct,
+                "streaming": direct,
+                "progress": direct,
+                "events": direct,
+                "auth": direct && !self.config.auth_tokens.trim().is_empty(),
+                "idempotencyKeys": true,
+            },
+            "cpuPool": {
+                "size": self.cpu_pool.size(),
+                "tools": CPU_BOUND_TOOLS,
+            },
+        })
+    }
+
     pub async fn health_check(&self) -> std::result::Result<(), GlspError> {
         // Check if WASM components directory exists
         if !std::path::Path::new(&self.config.wasm_path).exists() {
@@ -474,6 +1404,18 @@ impl GlspBackend {
         }
     }
 
+    /// Create the storage backend named by `--database-backend`
+    pub async fn create_storage(&self) -> std::result::Result<Box<dyn StorageBackend>, String> {
+        if self.database_manager.is_none() {
+            return Err("Database not enabled".to_string());
+        }
+        let db_config = self.config.to_database_config()?;
+        StorageRegistry::default()
+            .create(&self.config.database_backend, db_config)
+            .await
+            .map_err(|e| format!("Failed to create storage backend: {e}"))
+    }
+
     /// Check if database features are enabled and healthy
     pub async fn is_database_enabled(&self) -> bool {
         if let Some(db_manager) = &self.database_manager {
@@ -756,6 +1698,11 @@ impl GlspBackend {
         &self,
         _request: PaginatedRequestParam,
     ) -> std::result::Result<ListToolsResult, GlspError> {
+        // diagramType may be omitted once the server has a default
+        let create_diagram_required = match self.config.default_diagram_type() {
+            Ok(Some(_)) => json!([]),
+            _ => json!(["diagramType"]),
+        };
         let tools = vec![
             // Core diagram tools
             Tool {
@@ -764,16 +1711,25 @@ impl GlspBackend {
                 input_schema: json!({
                     "type": "object",
                     "properties": {
+                        "idempotencyKey": {
+                            "type": "string",
+                            "description": "Retrying with the same key and arguments returns the original result instead of creating a duplicate"
+                        },
                         "diagramType": {
                             "type": "string",
-                            "description": "Type of diagram to create (e.g., 'workflow', 'bpmn', 'uml')"
+                            "description": "Type of diagram to create (e.g., 'workflow', 'bpmn', 'uml'). Required unless the server has a default diagram type; the result reports the type applied"
                         },
                         "name": {
                             "type": "string",
                             "description": "Name for the new diagram"
+                        },
+                        "ifExists": {
+                            "type": "string",
+                            "enum": ["create", "reuse", "error"],
+                            "description": "When a diagram with this name already exists: 'create' another (default), 'reuse' the existing one, or fail with 'error'. The result reports whether a diagram was created"
                         }
                     },
-                    "required": ["diagramType"]
+                    "required": create_diagram_required
                 }),
             },
             Tool {
@@ -791,166 +1747,235 @@ impl GlspBackend {
                 }),
             },
             Tool {
-                name: "create_node".to_string(),
-                description: "Create a new node in the diagram".to_string(),
+                name: "merge_diagrams".to_string(),
+                description: "Copy all nodes and edges of a source diagram into a target diagram under new IDs, returning the ID map".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "diagramId": {"type": "string"},
-                        "nodeType": {"type": "string"},
-                        "position": {
+                        "targetId": {"type": "string", "description": "Diagram to merge into"},
+                        "sourceId": {"type": "string", "description": "Diagram whose elements are copied"},
+                        "offset": {
                             "type": "object",
                             "properties": {
                                 "x": {"type": "number"},
                                 "y": {"type": "number"}
                             },
-                            "required": ["x", "y"]
+                            "description": "Shift applied to merged positions. Defaults to placing the source below the target's content"
                         },
-                        "label": {"type": "string"}
+                        "deleteSource": {"type": "boolean", "description": "Delete the source diagram after merging (default false). The merge stands even if the delete fails; the result then has sourceDeleted false and the reason in sourceDeleteError"},
+                        "clientId": {"type": "string"}
                     },
-                    "required": ["diagramId", "nodeType", "position"]
+                    "required": ["targetId", "sourceId"]
                 }),
             },
             Tool {
-                name: "create_edge".to_string(),
-                description: "Create a new edge connecting two nodes".to_string(),
+                name: "duplicate_diagram".to_string(),
+                description: "Copy a diagram into a new diagram with fresh element IDs, keeping edges and containment intact. Returns the new diagram ID and the ID map".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "diagramId": {"type": "string"},
-                        "edgeType": {"type": "string"},
-                        "sourceId": {"type": "string"},
-                        "targetId": {"type": "string"},
-                        "label": {"type": "string"}
+                        "diagramId": {"type": "string", "description": "Diagram to copy"},
+                        "newName": {"type": "string", "description": "Name of the copy (default: \"<name> (copy)\")"},
+                        "copyProperties": {"type": "boolean", "description": "Copy element properties (default true)"},
+                        "copyMetadata": {"type": "boolean", "description": "Copy diagram metadata and tags, without timestamp entries (default true)"}
                     },
-                    "required": ["diagramId", "edgeType", "sourceId", "targetId"]
+                    "required": ["diagramId"]
                 }),
             },
             Tool {
-                name: "delete_element".to_string(),
-                description: "Delete an element from the diagram".to_string(),
+                name: "compare_diagrams".to_string(),
+                description: "Compare two diagrams, e.g. a diagram and its duplicate or an imported variant. Elements are matched by ID, then nodes by type and label, then edges by their matched endpoints; each match reports its method and confidence. Returns added, removed and changed elements with field-level differences".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "diagramId": {"type": "string"},
-                        "elementId": {"type": "string"}
+                        "leftId": {"type": "string", "description": "Diagram compared from"},
+                        "rightId": {"type": "string", "description": "Diagram compared to"}
                     },
-                    "required": ["diagramId", "elementId"]
+                    "required": ["leftId", "rightId"]
                 }),
             },
             Tool {
-                name: "update_element".to_string(),
-                description: "Update properties of an existing element".to_string(),
+                name: "extract_subgraph".to_string(),
+                description: "Move nodes (with their children and the edges between them) into a new diagram and leave a subdiagram-reference node in their place. Edges crossing the selection are reconnected to the reference node".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
                         "diagramId": {"type": "string"},
-                        "elementId": {"type": "string"},
-                        "properties": {"type": "object"},
-                        "position": {
-                            "type": "object",
-                            "properties": {
-                                "x": {"type": "number"},
-                                "y": {"type": "number"}
-                            }
-                        }
+                        "nodeIds": {
+                            "type": "array",
+                            "items": {"type": "string"},
+                            "minItems": 1
+                        },
+                        "newDiagramName": {"type": "string"},
+                        "clientId": {"type": "string"}
                     },
-                    "required": ["diagramId", "elementId"]
+                    "required": ["diagramId", "nodeIds", "newDiagramName"]
                 }),
             },
             Tool {
-                name: "apply_layout".to_string(),
-                description: "Apply automatic layout to the diagram".to_string(),
+                name: "server_info".to_string(),
+                description: "Server version, MCP protocol version, the transport in use and the ones available, and capability flags (batch, streaming, progress, events, auth). Call it first to avoid sending requests the server cannot handle; over http-direct it is also a JSON-RPC method of the same name".to_string(),
                 input_schema: json!({
                     "type": "object",
-                    "properties": {
-                        "diagramId": {"type": "string"},
-                        "algorithm": {
-                            "type": "string",
-                            "enum": ["hierarchical", "force", "circular", "grid"]
-                        },
-                        "direction": {
-                            "type": "string",
-                            "enum": ["top-bottom", "left-right", "bottom-top", "right-left"]
-                        }
-                    },
-                    "required": ["diagramId", "algorithm"]
+                    "properties": {}
                 }),
             },
             Tool {
-                name: "export_diagram".to_string(),
-                description: "Export diagram in various formats".to_string(),
+                name: "get_diagram_type_capabilities".to_string(),
+                description: "List the node types (with property schemas) and edge types (with allowed source/target node types) of a diagram type. create_node and create_edge validate against this; types without a declaration accept anything".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "diagramId": {"type": "string"},
-                        "format": {
-                            "type": "string",
-                            "enum": ["svg", "png", "json", "dot"]
-                        }
-                    },
-                    "required": ["diagramId", "format"]
+                        "diagramType": {"type": "string", "description": "Omit to list the declared diagram types"}
+                    }
                 }),
             },
             Tool {
-                name: "save_diagram".to_string(),
-                description: "Save a diagram to disk (creates both content and layout files)"
-                    .to_string(),
+                name: "create_node".to_string(),
+                description: "Create a new node in the diagram. Labels longer than the server's limit (a separate, usually shorter one for class, interface and enum names) and over-long UML attribute names or method signatures fail with LabelTooLong".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "diagramId": {"type": "string"}
-                    },
-                    "required": ["diagramId"]
-                }),
-            },
-            // Selection tools
-            Tool {
-                name: "select_elements".to_string(),
-                description: "Select one or more elements in the diagram".to_string(),
+                        "idempotencyKey": {
+                            "type": "string",
+                            "description": "Retrying with the same key and arguments returns the original result instead of creating a duplicate"
+                        },
+                        "diagramId": {"type": "string"},
+                        "nodeType": {"type": "string"},
+                        "position": {
+                            "type": "object",
+                            "description": "Node position; when omitted the server picks a free spot using the configured placement strategy",
+                            "properties": {
+                                "x": {"type": "number"},
+                                "y": {"type": "number"}
+                            },
+                            "required": ["x", "y"]
+                        },
+                        "label": {"type": "string"},
+                        "clientId": {"type": "string", "description": CREATOR_CLIENT_ID}
+                    },
+                    "required": ["diagramId", "nodeType"]
+                }),
+            },
+            Tool {
+                name: "create_edge".to_string(),
+                description: "Create a new edge connecting two nodes".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
+                        "idempotencyKey": {
+                            "type": "string",
+                            "description": "Retrying with the same key and arguments returns the original result instead of creating a duplicate"
+                        },
                         "diagramId": {"type": "string"},
-                        "elementIds": {
-                            "type": "array",
-                            "items": {"type": "string"}
+                        "edgeType": {"type": "string"},
+                        "sourceId": {"type": "string"},
+                        "targetId": {"type": "string"},
+                        "label": {"type": "string"},
+                        "directed": {
+                            "type": "boolean",
+                            "description": "Whether the edge points from source to target. Defaults by edge type: association and link edges are undirected, all others directed"
                         },
-                        "mode": {
+                        "clientId": {"type": "string", "description": CREATOR_CLIENT_ID}
+                    },
+                    "required": ["diagramId", "edgeType", "sourceId", "targetId"]
+                }),
+            },
+            Tool {
+                name: "create_edges".to_string(),
+                description: "Create many edges in one call. Every edge is validated first (endpoints exist, the diagram type allows the connection); edgeIds lists the new IDs in request order, with null for edges that failed and are described in errors. With atomic, any failure creates nothing and the call fails".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "idempotencyKey": {
                             "type": "string",
-                            "enum": ["single", "multiple", "range"],
-                            "default": "single"
+                            "description": "Retrying with the same key and arguments returns the original result instead of creating duplicates"
                         },
-                        "append": {"type": "boolean", "default": false}
+                        "diagramId": {"type": "string"},
+                        "edges": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "edgeType": {"type": "string"},
+                                    "sourceId": {"type": "string"},
+                                    "targetId": {"type": "string"},
+                                    "label": {"type": "string"},
+                                    "directed": {"type": "boolean"}
+                                },
+                                "required": ["edgeType", "sourceId", "targetId"]
+                            }
+                        },
+                        "atomic": {
+                            "type": "boolean",
+                            "description": "Create all edges or none (default: false)"
+                        },
+                        "clientId": {"type": "string", "description": CREATOR_CLIENT_ID}
                     },
-                    "required": ["diagramId", "elementIds"]
+                    "required": ["diagramId", "edges"]
                 }),
             },
             Tool {
-                name: "select_all".to_string(),
-                description: "Select all elements in the diagram".to_string(),
+                name: "suggest_targets".to_string(),
+                description: "List the nodes a new edge of the given type from a source node could connect to: allowed by the diagram type, not already connected by an edge of that type and, for hierarchy edges such as inheritance, not closing a cycle".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "diagramId": {"type": "string"}
+                        "diagramId": {"type": "string"},
+                        "sourceId": {"type": "string"},
+                        "edgeType": {"type": "string"}
                     },
-                    "required": ["diagramId"]
+                    "required": ["diagramId", "sourceId", "edgeType"]
                 }),
             },
             Tool {
-                name: "clear_selection".to_string(),
-                description: "Clear the current selection".to_string(),
+                name: "create_hyperedge".to_string(),
+                description: "Create one edge joining several sources to several targets, such as a fork or join. Cycle detection and layout treat it as a connection from every source to every target".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "diagramId": {"type": "string"}
+                        "diagramId": {"type": "string"},
+                        "sources": {"type": "array", "items": {"type": "string"}, "minItems": 1},
+                        "targets": {"type": "array", "items": {"type": "string"}, "minItems": 1},
+                        "label": {"type": "string"},
+                        "directed": {
+                            "type": "boolean",
+                            "description": "Whether the hyperedge points from its sources to its targets (default true)"
+                        },
+                        "clientId": {"type": "string", "description": CREATOR_CLIENT_ID}
                     },
-                    "required": ["diagramId"]
+                    "required": ["diagramId", "sources", "targets"]
                 }),
             },
             Tool {
-                name: "get_selection".to_string(),
-                description: "Get the currently selected elements".to_string(),
+                name: "reconnect_edge".to_string(),
+                description: "Move one or both endpoints of an existing edge, keeping its label, route and properties. Returns the updated edge".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "edgeId": {"type": "string"},
+                        "sourceId": {"type": "string", "description": "New source node"},
+                        "targetId": {"type": "string", "description": "New target node"}
+                    },
+                    "required": ["diagramId", "edgeId"]
+                }),
+            },
+            Tool {
+                name: "reverse_edge".to_string(),
+                description: "Swap the source and target of an edge, along with its port anchors and route. Fails if the diagram type does not allow the reversed connection. Returns the updated edge".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "edgeId": {"type": "string"}
+                    },
+                    "required": ["diagramId", "edgeId"]
+                }),
+            },
+            Tool {
+                name: "detect_cycles".to_string(),
+                description: "Find cycles formed by directed edges. Undirected edges such as associations never create cycles".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
@@ -959,698 +1984,5898 @@ impl GlspBackend {
                     "required": ["diagramId"]
                 }),
             },
-            // WASM component tools
             Tool {
-                name: "scan_wasm_components".to_string(),
-                description: "Scan for WASM components in the watch directory".to_string(),
+                name: "assert_acyclic".to_string(),
+                description: format!("Check that a diagram's directed edges form a DAG. Succeeds when there is no cycle and fails listing the cycles otherwise; mustBeAcyclic reports whether the diagram enforces this on every change (see {ACYCLIC_KEY} in set_diagram_metadata)"),
                 input_schema: json!({
                     "type": "object",
-                    "properties": {},
-                    "additionalProperties": false
+                    "properties": {
+                        "diagramId": {"type": "string"}
+                    },
+                    "required": ["diagramId"]
                 }),
             },
             Tool {
-                name: "check_wasm_component_status".to_string(),
-                description: "Check the status of a specific WASM component".to_string(),
+                name: "is_reachable".to_string(),
+                description: "Check whether one node can be reached from another. Directed edges are only followed from source to target; returns one shortest path when reachable".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "componentName": {"type": "string"}
+                        "diagramId": {"type": "string"},
+                        "fromId": {"type": "string"},
+                        "toId": {"type": "string"}
                     },
-                    "required": ["componentName"]
+                    "required": ["diagramId", "fromId", "toId"]
                 }),
             },
             Tool {
-                name: "load_wasm_component".to_string(),
-                description: "Load a WASM component into a diagram".to_string(),
+                name: "shortest_path".to_string(),
+                description: "Find the shortest path between two nodes, following directed edges from source to target only. Counts edges, or sums a numeric edge property such as 'cost' when weightProperty is given (edges without it cost 1). Returns the node and edge IDs in order and the total cost".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
                         "diagramId": {"type": "string"},
-                        "componentName": {"type": "string"},
-                        "position": {
-                            "type": "object",
-                            "properties": {
-                                "x": {"type": "number"},
-                                "y": {"type": "number"}
-                            },
-                            "required": ["x", "y"]
+                        "fromId": {"type": "string"},
+                        "toId": {"type": "string"},
+                        "weightProperty": {
+                            "type": "string",
+                            "description": "Edge property holding a non-negative cost; omit to count edges"
                         }
                     },
-                    "required": ["diagramId", "componentName", "position"]
+                    "required": ["diagramId", "fromId", "toId"]
                 }),
             },
             Tool {
-                name: "refresh_wasm_interfaces".to_string(),
-                description: "Refresh interface data for all WASM components in a diagram"
-                    .to_string(),
+                name: "delete_element".to_string(),
+                description: "Delete an element from the diagram, together with the edges attached to it. The IDs of those edges are returned as deletedEdges; a lock on any of them blocks the delete".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "diagramId": {"type": "string"}
+                        "diagramId": {"type": "string"},
+                        "elementId": {"type": "string"}
                     },
-                    "required": ["diagramId"]
+                    "required": ["diagramId", "elementId"]
                 }),
             },
             Tool {
-                name: "get_component_path".to_string(),
-                description: "Get the file path for a WASM component".to_string(),
+                name: "update_element".to_string(),
+                description: "Update properties of an existing element. UML attribute names and method signatures longer than the server's limits fail with LabelTooLong".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "componentName": {
-                            "type": "string",
-                            "description": "Name of the WASM component"
+                        "diagramId": {"type": "string"},
+                        "elementId": {"type": "string"},
+                        "properties": {"type": "object"},
+                        "position": {
+                            "type": "object",
+                            "properties": {
+                                "x": {"type": "number"},
+                                "y": {"type": "number"}
+                            }
                         }
                     },
-                    "required": ["componentName"]
+                    "required": ["diagramId", "elementId"]
                 }),
             },
             Tool {
-                name: "get_component_wit_info".to_string(),
-                description: "Get WIT interface information for a selected component in a diagram"
-                    .to_string(),
+                name: "apply_layout".to_string(),
+                description: "Apply automatic layout to the diagram. Reports progress when the call carries a progressToken".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "diagramId": {
+                        "diagramId": {"type": "string"},
+                        "algorithm": {
                             "type": "string",
-                            "description": "ID of the diagram containing the component"
+                            "enum": ["hierarchical", "force", "circular", "grid"]
                         },
-                        "elementId": {
+                        "direction": {
                             "type": "string",
-                            "description": "ID of the component element to analyze"
+                            "enum": ["top-bottom", "left-right", "bottom-top", "right-left"]
+                        },
+                        "seed": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "Seed for the force algorithm. The same seed and the same graph always produce the same positions; a random seed is used when omitted"
                         }
                     },
-                    "required": ["diagramId", "elementId"]
+                    "required": ["diagramId", "algorithm"]
                 }),
             },
             Tool {
-                name: "debug_wit_analysis".to_string(),
-                description: "Debug WIT interface analysis for a specific WASM component file"
-                    .to_string(),
+                name: "compute_layout_hints".to_string(),
+                description: format!("Compute the structure of a layout without moving anything: per node a rank (layer for hierarchical, row for grid) and an order within the rank, for clients that place nodes themselves. Hierarchical ranks follow directed edges and the order reduces edge crossings. Algorithms: {}", LAYOUT_HINT_ALGORITHMS.join(", ")),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "componentPath": {
+                        "diagramId": {"type": "string"},
+                        "algorithm": {
                             "type": "string",
-                            "description": "Full file path to the WASM component file to analyze"
+                            "enum": LAYOUT_HINT_ALGORITHMS
                         }
                     },
-                    "required": ["componentPath"]
+                    "required": ["diagramId", "algorithm"]
                 }),
             },
-
-            // Workspace management tools
             Tool {
-                name: "set_workspace_directory".to_string(),
-                description: "Set workspace root directory (creates wasm-components and diagrams subdirectories)".to_string(),
+                name: "normalize_coordinates".to_string(),
+                description: "Translate all nodes and edge waypoints so the top-left of the content sits at an origin plus a margin, keeping relative positions. Returns the applied translation".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "workspace_path": {
-                            "type": "string",
-                            "description": "Absolute path to the workspace root directory"
-                        },
-                        "create_if_missing": {
-                            "type": "boolean",
-                            "description": "Create the directory if it doesn't exist",
-                            "default": true
+                        "diagramId": {"type": "string"},
+                        "originX": {"type": "number", "default": 0},
+                        "originY": {"type": "number", "default": 0},
+                        "margin": {
+                            "type": "number",
+                            "minimum": 0,
+                            "default": 0,
+                            "description": "Space left between the origin and the content on both axes"
                         }
                     },
-                    "required": ["workspace_path"]
+                    "required": ["diagramId"]
                 }),
             },
             Tool {
-                name: "get_workspace_info".to_string(),
-                description: "Get current workspace information and statistics".to_string(),
+                name: "get_content_bounds".to_string(),
+                description: "Get the area covered by a diagram's nodes and edge waypoints, and a canvas size that fits it with a margin, without fetching the whole diagram".to_string(),
                 input_schema: json!({
                     "type": "object",
-                    "properties": {}
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "margin": {
+                            "type": "number",
+                            "minimum": 0,
+                            "default": CANVAS_MARGIN,
+                            "description": "Space around the content in the suggested canvas"
+                        }
+                    },
+                    "required": ["diagramId"]
                 }),
             },
             Tool {
-                name: "set_wasm_components_path".to_string(),
-                description: "Set custom WASM components directory path".to_string(),
+                name: "export_diagram".to_string(),
+                description: "Export diagram in various formats. png is returned as a base64 image content block; all other formats as text".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "wasm_path": {
+                        "diagramId": {"type": "string"},
+                        "format": {
                             "type": "string",
-                            "description": "Absolute path to WASM components directory"
+                            "enum": ["svg", "png", "json", "dot", "plantuml", "mermaid"]
+                        },
+                        "accept": {
+                            "type": "string",
+                            "description": "HTTP Accept header value, e.g. image/svg+xml, text/vnd.plantuml or text/vnd.mermaid, used when format is absent. Unknown types fall back to json"
                         }
                     },
-                    "required": ["wasm_path"]
+                    "required": ["diagramId"]
                 }),
             },
             Tool {
-                name: "set_diagrams_path".to_string(),
-                description: "Set custom diagrams storage directory path".to_string(),
+                name: "save_diagram".to_string(),
+                description: "Save a diagram to disk (creates both content and layout files)"
+                    .to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "diagrams_path": {
+                        "diagramId": {"type": "string"}
+                    },
+                    "required": ["diagramId"]
+                }),
+            },
+            // Selection tools
+            Tool {
+                name: "select_elements".to_string(),
+                description: "Select one or more elements in the diagram".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "elementIds": {
+                            "type": "array",
+                            "items": {"type": "string"}
+                        },
+                        "mode": {
                             "type": "string",
-                            "description": "Absolute path to diagrams storage directory"
-                        }
+                            "enum": ["single", "multiple", "range"],
+                            "default": "single"
+                        },
+                        "append": {"type": "boolean", "default": false}
                     },
-                    "required": ["diagrams_path"]
+                    "required": ["diagramId", "elementIds"]
                 }),
             },
             Tool {
-                name: "rescan_workspace".to_string(),
-                description: "Trigger immediate rescan of current workspace (WASM components and diagrams)".to_string(),
+                name: "select_all".to_string(),
+                description: "Select all elements in the diagram".to_string(),
                 input_schema: json!({
                     "type": "object",
-                    "properties": {}
+                    "properties": {
+                        "diagramId": {"type": "string"}
+                    },
+                    "required": ["diagramId"]
                 }),
             },
             Tool {
-                name: "validate_workspace".to_string(),
-                description: "Validate workspace structure and permissions".to_string(),
+                name: "clear_selection".to_string(),
+                description: "Clear the current selection".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "workspace_path": {
-                            "type": "string",
-                            "description": "Path to workspace directory to validate"
-                        }
+                        "diagramId": {"type": "string"}
                     },
-                    "required": ["workspace_path"]
+                    "required": ["diagramId"]
                 }),
             },
             Tool {
-                name: "create_workspace_structure".to_string(),
-                description: "Create standard workspace directory structure (wasm-components and diagrams subdirectories)".to_string(),
+                name: "get_selection".to_string(),
+                description: "Get the currently selected elements".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "workspace_path": {
-                            "type": "string",
-                            "description": "Path to workspace root where subdirectories will be created"
-                        }
+                        "diagramId": {"type": "string"}
                     },
-                    "required": ["workspace_path"]
+                    "required": ["diagramId"]
                 }),
             },
-        ];
-
-        Ok(ListToolsResult {
+            // WASM component tools
+            Tool {
+                name: "scan_wasm_components".to_string(),
+                description: "Scan for WASM components in the watch directory".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {},
+                    "additionalProperties": false
+                }),
+            },
+            Tool {
+                name: "check_wasm_component_status".to_string(),
+                description: "Check the status of a specific WASM component".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "componentName": {"type": "string"}
+                    },
+                    "required": ["componentName"]
+                }),
+            },
+            Tool {
+                name: "export_component_wit".to_string(),
+                description: "Reconstruct the WIT source of a loaded component from the type information embedded in its binary: its world with all imports and exports, plus the packages it refers to. Works without the original WIT files; returned as plain text".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "componentName": {"type": "string"}
+                    },
+                    "required": ["componentName"]
+                }),
+            },
+            Tool {
+                name: "get_component_status".to_string(),
+                description: "Get per-component load results from component scans, including components that failed to load and why".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "componentName": {
+                            "type": "string",
+                            "description": "Only return the load result for this component"
+                        }
+                    }
+                }),
+            },
+            Tool {
+                name: "get_component_dependency_graph".to_string(),
+                description: "Build a dependency graph of the loaded components: for each component, the interfaces it imports and which components export them. Imports nothing provides are flagged as unsatisfied; wasi: interfaces count as host-provided".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "renderDiagram": {
+                            "type": "boolean",
+                            "description": "Also create a wit-schema diagram of the graph (default false)"
+                        }
+                    }
+                }),
+            },
+            Tool {
+                name: "find_components_by_interface".to_string(),
+                description: "Find the loaded components that export or import a WIT interface, with the world each targets, e.g. to see which components can provide sensor-input. A bare name matches the interface in any package; a qualified name matches its version first and then any version".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "interfaceName": {
+                            "type": "string",
+                            "description": "Interface name, bare (sensor-input) or qualified (adas:sensors/sensor-input@0.1.0)"
+                        },
+                        "direction": {
+                            "type": "string",
+                            "enum": ["export", "import", "both"],
+                            "description": "Which side of the interface to look for (default both)"
+                        }
+                    },
+                    "required": ["interfaceName"]
+                }),
+            },
+            Tool {
+                name: "execute_component".to_string(),
+                description: "Start executing an exported method of a WASM component. Returns an execution ID; use get_execution_result to fetch the outcome. When the concurrent execution limit is reached the call fails with component busy, or the execution waits for a slot, depending on the server's overflow policy".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "componentName": {"type": "string"},
+                        "method": {
+                            "type": "string",
+                            "description": "Exported function to call (default 'main')"
+                        },
+                        "args": {"type": "object"},
+                        "timeoutMs": {"type": "integer", "description": "Execution timeout (default 30000)"},
+                        "maxMemoryMb": {"type": "integer", "description": "Memory limit (default 64)"},
+                        "profile": {
+                            "type": "boolean",
+                            "description": "Record instantiation time, fuel consumption (when the server runs with --wasm-fuel-metering, otherwise 0) and peak memory"
+                        },
+                        "noCache": {
+                            "type": "boolean",
+                            "default": false,
+                            "description": "Always run the component, even if the result cache holds a result for these inputs; use for nondeterministic components"
+                        },
+                        "trace": {
+                            "type": "boolean",
+                            "default": false,
+                            "description": "Record entry, exit and duration of the exported function call; get_execution_result then lists the spans under trace. Adds overhead and bypasses the result cache"
+                        }
+                    },
+                    "required": ["componentName"]
+                }),
+            },
+            Tool {
+                name: "get_execution_result".to_string(),
+                description: "Get the progress or final result (including profiling data) of a component execution".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "executionId": {"type": "string"}
+                    },
+                    "required": ["executionId"]
+                }),
+            },
+            Tool {
+                name: "cancel_execution".to_string(),
+                description: "Stop a running component execution. Its result becomes an error of 'Cancelled'; cancelling an execution that already finished changes nothing".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "executionId": {
+                            "type": "string",
+                            "description": "ID returned by execute_component"
+                        }
+                    },
+                    "required": ["executionId"]
+                }),
+            },
+            Tool {
+                name: "retry_pending_persists".to_string(),
+                description: "Retry saving every diagram whose earlier save failed and was kept in the dead-letter directory".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {}
+                }),
+            },
+            Tool {
+                name: "register_webhook".to_string(),
+                description: format!("Have the server POST a JSON change summary (diagramId, revision, tool, delta, timestamp, and for update_element the changed fields with old and new values) to a URL whenever a diagram changes. Failed deliveries are retried with backoff. Requests carry a {SIGNATURE_HEADER} header, sha256=<hex> HMAC-SHA256 of the body keyed by the secret; a secret is generated and returned when none is given"),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "url": {
+                            "type": "string",
+                            "description": "http or https URL to POST to"
+                        },
+                        "diagramIds": {
+                            "description": "\"all\" (default) or an array of diagram IDs to watch",
+                            "oneOf": [
+                                {"type": "string", "enum": ["all"]},
+                                {"type": "array", "items": {"type": "string"}}
+                            ]
+                        },
+                        "secret": {
+                            "type": "string",
+                            "description": "Key for the request signature"
+                        }
+                    },
+                    "required": ["url"]
+                }),
+            },
+            Tool {
+                name: "unregister_webhook".to_string(),
+                description: "Stop posting diagram changes to a webhook".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "webhookId": {"type": "string"}
+                    },
+                    "required": ["webhookId"]
+                }),
+            },
+            Tool {
+                name: "list_webhooks".to_string(),
+                description: "Registered webhooks with the diagrams they watch; secrets are not shown".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {}
+                }),
+            },
+            Tool {
+                name: "load_wasm_component".to_string(),
+                description: "Load a WASM component into a diagram".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "componentName": {"type": "string"},
+                        "position": {
+                            "type": "object",
+                            "properties": {
+                                "x": {"type": "number"},
+                                "y": {"type": "number"}
+                            },
+                            "required": ["x", "y"]
+                        }
+                    },
+                    "required": ["diagramId", "componentName", "position"]
+                }),
+            },
+            Tool {
+                name: "refresh_wasm_interfaces".to_string(),
+                description: "Refresh interface data for all WASM components in a diagram"
+                    .to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"}
+                    },
+                    "required": ["diagramId"]
+                }),
+            },
+            Tool {
+                name: "get_component_path".to_string(),
+                description: "Get the file path for a WASM component".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "componentName": {
+                            "type": "string",
+                            "description": "Name of the WASM component"
+                        }
+                    },
+                    "required": ["componentName"]
+                }),
+            },
+            Tool {
+                name: "inspect_component".to_string(),
+                description: "Show a WASM component's custom sections as a metadata map: text sections are decoded (as JSON when possible), binary sections report their size only".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "componentName": {
+                            "type": "string",
+                            "description": "Name of the WASM component"
+                        }
+                    },
+                    "required": ["componentName"]
+                }),
+            },
+            Tool {
+                name: "get_component_wit_info".to_string(),
+                description: "Get WIT interface information for a selected component in a diagram"
+                    .to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {
+                            "type": "string",
+                            "description": "ID of the diagram containing the component"
+                        },
+                        "elementId": {
+                            "type": "string",
+                            "description": "ID of the component element to analyze"
+                        }
+                    },
+                    "required": ["diagramId", "elementId"]
+                }),
+            },
+            Tool {
+                name: "debug_wit_analysis".to_string(),
+                description: "Debug WIT interface analysis for a specific WASM component file"
+                    .to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "componentPath": {
+                            "type": "string",
+                            "description": "Full file path to the WASM component file to analyze"
+                        }
+                    },
+                    "required": ["componentPath"]
+                }),
+            },
+
+            // Workspace management tools
+            Tool {
+                name: "set_workspace_directory".to_string(),
+                description: "Set workspace root directory (creates wasm-components and diagrams subdirectories)".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "workspace_path": {
+                            "type": "string",
+                            "description": "Absolute path to the workspace root directory"
+                        },
+                        "create_if_missing": {
+                            "type": "boolean",
+                            "description": "Create the directory if it doesn't exist",
+                            "default": true
+                        }
+                    },
+                    "required": ["workspace_path"]
+                }),
+            },
+            Tool {
+                name: "get_workspace_info".to_string(),
+                description: "Get current workspace information and statistics".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {}
+                }),
+            },
+            Tool {
+                name: "set_wasm_components_path".to_string(),
+                description: "Set custom WASM components directory path".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "wasm_path": {
+                            "type": "string",
+                            "description": "Absolute path to WASM components directory"
+                        }
+                    },
+                    "required": ["wasm_path"]
+                }),
+            },
+            Tool {
+                name: "set_diagrams_path".to_string(),
+                description: "Set custom diagrams storage directory path".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagrams_path": {
+                            "type": "string",
+                            "description": "Absolute path to diagrams storage directory"
+                        }
+                    },
+                    "required": ["diagrams_path"]
+                }),
+            },
+            Tool {
+                name: "rescan_workspace".to_string(),
+                description: "Trigger immediate rescan of current workspace (WASM components and diagrams)".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {}
+                }),
+            },
+            Tool {
+                name: "validate_workspace".to_string(),
+                description: "Validate workspace structure and permissions".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "workspace_path": {
+                            "type": "string",
+                            "description": "Path to workspace directory to validate"
+                        }
+                    },
+                    "required": ["workspace_path"]
+                }),
+            },
+            Tool {
+                name: "create_workspace_structure".to_string(),
+                description: "Create standard workspace directory structure (wasm-components and diagrams subdirectories)".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "workspace_path": {
+                            "type": "string",
+                            "description": "Path to workspace root where subdirectories will be created"
+                        }
+                    },
+                    "required": ["workspace_path"]
+                }),
+            },
+            // Query tools
+            Tool {
+                name: "get_diagram_size".to_string(),
+                description: "Measure a diagram before exporting or fetching it: bytes of its content and layout files in the configured persistence format, bytes of its JSON model as get_diagram returns it, node and edge counts and the total size of its attachments".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"}
+                    },
+                    "required": ["diagramId"]
+                }),
+            },
+            Tool {
+                name: "get_history".to_string(),
+                description: "Audit trail of a diagram: its most recent changes, newest first, each with timestamp, actor (the clientId the call passed), operation (the tool name), revision and the IDs of the elements it created, changed or deleted. Use it to answer who changed an element and when; use compare_diagrams for the content difference. Only the most recent changes since the server started are kept".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "limit": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Most entries to return (default 50)"
+                        },
+                        "operation": {
+                            "type": "string",
+                            "description": "Only entries of this tool, e.g. update_element"
+                        },
+                        "actor": {
+                            "type": "string",
+                            "description": "Only entries made by this clientId"
+                        },
+                        "elementId": {
+                            "type": "string",
+                            "description": "Only entries that affected this element"
+                        }
+                    },
+                    "required": ["diagramId"]
+                }),
+            },
+            Tool {
+                name: "get_diagram".to_string(),
+                description: "Get the full model of a diagram, including element timestamps and authors, the stored viewport and the bounds of its content. Pass fields to return only part of each element".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "fields": {
+                            "type": "array",
+                            "items": {"type": "string"},
+                            "description": "Element fields to return, e.g. [\"id\", \"position\"]. Unknown fields are ignored with a warning"
+                        },
+                        "pageSize": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "description": "Return elements in pages of this size (nodes first, then edges) with a nextCursor"
+                        },
+                        "cursor": {
+                            "type": "string",
+                            "description": "nextCursor from the previous page. Pages come from a snapshot taken at the first page, so concurrent edits do not shift them"
+                        }
+                    },
+                    "required": ["diagramId"]
+                }),
+            },
+            Tool {
+                name: "list_diagrams".to_string(),
+                description: "List all diagrams with their type and timestamps".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "updatedSince": {
+                            "type": "string",
+                            "description": "Only include diagrams updated at or after this RFC 3339 timestamp"
+                        },
+                        "tag": {
+                            "type": "string",
+                            "description": "Only include diagrams carrying this tag"
+                        }
+                    }
+                }),
+            },
+            Tool {
+                name: "set_diagram_readonly".to_string(),
+                description: "Mark a diagram read-only, e.g. to freeze an approved design, or make it editable again. While read-only, every tool that changes it fails with DiagramReadOnly; reads and exports still work. The flag is persisted and shown as readOnly by list_diagrams and get_diagram".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "readOnly": {"type": "boolean"}
+                    },
+                    "required": ["diagramId", "readOnly"]
+                }),
+            },
+            Tool {
+                name: "set_diagram_metadata".to_string(),
+                description: format!("Merge arbitrary metadata (e.g. description, owner) into a diagram. Keys set to null are removed. Setting {ACYCLIC_KEY} to true makes changes that would create a cycle of directed edges fail with WouldCreateCycle; it is refused while the diagram has cycles"),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "metadata": {"type": "object"}
+                    },
+                    "required": ["diagramId", "metadata"]
+                }),
+            },
+            Tool {
+                name: "set_viewport".to_string(),
+                description: "Store the user's pan and zoom so a reopened diagram restores the same view. get_diagram returns it along with the content bounds".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "viewport": {
+                            "type": "object",
+                            "properties": {
+                                "x": {"type": "number"},
+                                "y": {"type": "number"},
+                                "zoom": {"type": "number", "exclusiveMinimum": 0}
+                            },
+                            "required": ["x", "y", "zoom"]
+                        }
+                    },
+                    "required": ["diagramId", "viewport"]
+                }),
+            },
+            Tool {
+                name: "set_type_style".to_string(),
+                description: "Set the default style of an element type in a diagram, e.g. every 'task' node. The SVG export applies it and get_diagram reports it under typeStyles; elements override it with the same keys in their style or properties. A null style removes the default".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "elementType": {"type": "string", "description": "Node or edge type the style applies to"},
+                        "style": {
+                            "type": ["object", "null"],
+                            "properties": {
+                                "fill": {"type": "string", "description": "Fill color"},
+                                "stroke": {"type": "string", "description": "Outline or line color"},
+                                "strokeWidth": {"type": "number", "minimum": 0},
+                                "fontFamily": {"type": "string"},
+                                "fontSize": {"type": "number", "exclusiveMinimum": 0},
+                                "fontColor": {"type": "string"}
+                            },
+                            "additionalProperties": false
+                        }
+                    },
+                    "required": ["diagramId", "elementType", "style"]
+                }),
+            },
+            Tool {
+                name: "add_diagram_tags".to_string(),
+                description: "Add tags to a diagram for organization and filtering".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "tags": {
+                            "type": "array",
+                            "items": {"type": "string"}
+                        }
+                    },
+                    "required": ["diagramId", "tags"]
+                }),
+            },
+            Tool {
+                name: "find_nodes".to_string(),
+                description: "Find nodes in a diagram by type, label and modification time. Over the HTTP transport the nodes can be streamed as NDJSON by accepting application/x-ndjson; streamed nodes come in no particular order and the closing response carries their count".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "nodeType": {"type": "string"},
+                        "label": {
+                            "type": "string",
+                            "description": "Case-insensitive substring match on the node label"
+                        },
+                        "updatedSince": {
+                            "type": "string",
+                            "description": "Only include nodes updated at or after this RFC 3339 timestamp"
+                        }
+                    },
+                    "required": ["diagramId"]
+                }),
+            },
+            Tool {
+                name: "get_edges_for_node".to_string(),
+                description: "Get all edges touching a node, split into incoming and outgoing, with the element on the other end of each edge".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "nodeId": {"type": "string"}
+                    },
+                    "required": ["diagramId", "nodeId"]
+                }),
+            },
+            Tool {
+                name: "set_compartment_visibility".to_string(),
+                description: "Collapse or expand the attribute and method compartments of a UML class node. The node is resized to fit and its new bounds are returned; SVG and PlantUML exports honor the collapsed state".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "nodeId": {"type": "string"},
+                        "compartments": {
+                            "type": "object",
+                            "description": "Visibility per compartment; omitted compartments keep their current state",
+                            "properties": {
+                                "attributes": {"type": "boolean"},
+                                "methods": {"type": "boolean"}
+                            }
+                        }
+                    },
+                    "required": ["diagramId", "nodeId", "compartments"]
+                }),
+            },
+            Tool {
+                name: "add_subtask".to_string(),
+                description: "Nest a task inside a composite task. The parent lists it as a child and the subtask names the parent in its parentTask property; a task already nested elsewhere is moved. Fails with ContainmentCycle if the parent is the task itself or one of its subtasks. A subtask of a collapsed parent is hidden".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "parentId": {"type": "string", "description": "Composite task to nest into"},
+                        "nodeId": {"type": "string", "description": "Task to become a subtask"}
+                    },
+                    "required": ["diagramId", "parentId", "nodeId"]
+                }),
+            },
+            Tool {
+                name: "add_to_container".to_string(),
+                description: "Move a node into a container node. The node leaves its previous container, so every node has exactly one, and takes the container's visibility. Fails with ContainmentCycle if the container is the node itself or lies inside it".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "containerId": {"type": "string", "description": "Container to move the node into"},
+                        "nodeId": {"type": "string", "description": "Node to move"}
+                    },
+                    "required": ["diagramId", "containerId", "nodeId"]
+                }),
+            },
+            Tool {
+                name: "remove_subtask".to_string(),
+                description: "Move a subtask out of its composite task back to the top level of the diagram. It becomes visible again".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "nodeId": {"type": "string"}
+                    },
+                    "required": ["diagramId", "nodeId"]
+                }),
+            },
+            Tool {
+                name: "set_task_collapsed".to_string(),
+                description: "Collapse or expand a composite task. Collapsing hides all subtasks below it; expanding shows them again except those below a subtask that is still collapsed. Returns the subtasks whose visibility changed".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "nodeId": {"type": "string"},
+                        "collapsed": {"type": "boolean"}
+                    },
+                    "required": ["diagramId", "nodeId", "collapsed"]
+                }),
+            },
+            Tool {
+                name: "convert_diagram_type".to_string(),
+                description: "Convert a diagram into a new diagram of another compatible type using the declared node/edge type mapping. Elements without a counterpart become notes and are reported".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "targetType": {
+                            "type": "string",
+                            "description": "Diagram type to convert to (e.g., 'bpmn', 'uml-activity')"
+                        }
+                    },
+                    "required": ["diagramId", "targetType"]
+                }),
+            },
+            Tool {
+                name: "generate_diagram_from_wit".to_string(),
+                description: "Generate a wit-schema diagram from WIT source: one node per interface listing its functions, one node per world, and import/export edges from each world to its interfaces".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "witSource": {
+                            "type": "string",
+                            "description": "WIT source of a single package"
+                        },
+                        "name": {
+                            "type": "string",
+                            "description": "Diagram name (defaults to the WIT package name)"
+                        }
+                    },
+                    "required": ["witSource"]
+                }),
+            },
+            Tool {
+                name: "import_rust_types".to_string(),
+                description: "Generate a uml-class diagram from Rust source: structs become classes with their fields as attributes, enums list their variants, traits become interfaces, inherent impl methods are added to their type, and trait impls and supertraits become generalization edges. Unsupported items are reported as warnings".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "source": {
+                            "type": "string",
+                            "description": "Rust source of one file; inline modules are included"
+                        },
+                        "name": {
+                            "type": "string",
+                            "description": "Diagram name (defaults to 'Rust types')"
+                        }
+                    },
+                    "required": ["source"]
+                }),
+            },
+            Tool {
+                name: "validate_wit".to_string(),
+                description: "Parse WIT source without creating a diagram. Returns a summary of the package (interfaces with their functions, worlds with import/export counts) or the parse errors with line and column".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "source": {
+                            "type": "string",
+                            "description": "WIT source of a single package"
+                        }
+                    },
+                    "required": ["source"]
+                }),
+            },
+            Tool {
+                name: "attach_file".to_string(),
+                description: "Attach a file, such as a datasheet or reference image, to a diagram. An attachment with the same name is replaced. Fails when the file or the diagram's attachments in total exceed the configured size limits".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string", "description": "ID of the diagram"},
+                        "name": {"type": "string", "description": "File name of the attachment, without directories"},
+                        "bytes": {"type": "string", "description": "File contents, base64 encoded"}
+                    },
+                    "required": ["diagramId", "name", "bytes"]
+                }),
+            },
+            Tool {
+                name: "list_attachments".to_string(),
+                description: "List the files attached to a diagram with their sizes".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string", "description": "ID of the diagram"}
+                    },
+                    "required": ["diagramId"]
+                }),
+            },
+            Tool {
+                name: "get_attachment".to_string(),
+                description: "Get a file attached to a diagram; its contents are returned base64 encoded".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string", "description": "ID of the diagram"},
+                        "name": {"type": "string", "description": "Name of the attachment"}
+                    },
+                    "required": ["diagramId", "name"]
+                }),
+            },
+            Tool {
+                name: "export_workspace".to_string(),
+                description: "Export every diagram in the workspace, with its attachments, as a single JSON archive with a manifest".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {}
+                }),
+            },
+            Tool {
+                name: "import_workspace".to_string(),
+                description: "Restore diagrams from a workspace archive produced by export_workspace. The strategy decides what happens to diagrams whose IDs collide with existing ones; a new ID is a fresh UUID, links between the imported diagrams follow it, and idMap lists the oldId and newId of every imported diagram in archive order, so a diagram the archive holds twice has an entry for each copy; remappedIds lists the entries whose ID changed. Diagrams are validated for dangling references and duplicate element IDs before anything is imported. Reports progress per saved diagram when the call carries a progressToken".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "archive": {
+                            "type": ["object", "string"],
+                            "description": "Workspace archive (object or JSON string)"
+                        },
+                        "mode": {
+                            "type": "string",
+                            "enum": ["strict", "repair"],
+                            "description": "strict (default) rejects the whole import if any diagram is malformed; repair drops dangling edges and references, re-keys elements and reports what it changed"
+                        },
+                        "strategy": {
+                            "type": "string",
+                            "enum": ["always-new", "keep-if-free", "error-on-collision"],
+                            "description": "always-new gives every diagram a new ID; keep-if-free (default) only reassigns colliding IDs; error-on-collision imports nothing if any ID is taken"
+                        }
+                    },
+                    "required": ["archive"]
+                }),
+            },
+            Tool {
+                name: "snapshot_state".to_string(),
+                description: "Checkpoint the whole server under a name: every diagram with its metadata and attachments, written to disk as one file. A checkpoint of the same name is replaced".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string", "description": "Name of the checkpoint"}
+                    },
+                    "required": ["name"]
+                }),
+            },
+            Tool {
+                name: "restore_state".to_string(),
+                description: "Replace the whole server state with a checkpoint taken by snapshot_state. Diagrams not in the checkpoint are deleted. The checkpoint is validated first; if it is malformed nothing changes".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string", "description": "Name of the checkpoint"}
+                    },
+                    "required": ["name"]
+                }),
+            },
+            Tool {
+                name: "check_integrity".to_string(),
+                description: "Scan stored diagrams for dangling edges and child references, duplicate or mis-keyed element IDs and nodes no container holds. Checks every diagram unless diagramId is given; with repair, fixes what it found and saves the diagram. A repair is an edit like any other: it fails on a read-only or locked diagram and shows up in get_history".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string", "description": "Diagram to check (default: all)"},
+                        "repair": {
+                            "type": "boolean",
+                            "description": "Drop dangling edges and references, re-key elements and attach orphaned nodes to the root (default false)"
+                        },
+                        "clientId": {"type": "string"}
+                    }
+                }),
+            },
+            // Locking tools
+            Tool {
+                name: "acquire_lock".to_string(),
+                description: "Acquire an exclusive edit lock on a diagram. While held, mutating tools from other clients are rejected. Re-acquiring renews the TTL".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "clientId": {
+                            "type": "string",
+                            "description": "Identifier of the client taking the lock; pass the same clientId to mutating tools"
+                        },
+                        "ttlSeconds": {
+                            "type": "integer",
+                            "description": "Lock lifetime in seconds (default 300, at most 86400)"
+                        }
+                    },
+                    "required": ["diagramId", "clientId"]
+                }),
+            },
+            Tool {
+                name: "release_lock".to_string(),
+                description: "Release an edit lock held on a diagram".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "clientId": {"type": "string"}
+                    },
+                    "required": ["diagramId", "clientId"]
+                }),
+            },
+            Tool {
+                name: "lock_element".to_string(),
+                description: "Lock a single node or edge so that other clients cannot change it while the rest of the diagram stays editable. Mutating tools naming a locked element from another client fail with ElementLocked, as do tools that rewrite the whole diagram (apply_layout, normalize_coordinates, set_type_style, merge_diagrams, extract_subgraph, delete_diagram) and deleting a node whose edges are locked. Re-locking renews the TTL; get_diagram lists the locks under elementLocks".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "elementId": {"type": "string"},
+                        "clientId": {
+                            "type": "string",
+                            "description": "Identifier of the client taking the lock; pass the same clientId to mutating tools"
+                        },
+                        "ttlSeconds": {
+                            "type": "integer",
+                            "description": "Lock lifetime in seconds (default 300, at most 86400)"
+                        }
+                    },
+                    "required": ["diagramId", "elementId", "clientId"]
+                }),
+            },
+            Tool {
+                name: "unlock_element".to_string(),
+                description: "Release a lock held on a single node or edge".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "diagramId": {"type": "string"},
+                        "elementId": {"type": "string"},
+                        "clientId": {"type": "string"}
+                    },
+                    "required": ["diagramId", "elementId", "clientId"]
+                }),
+            },
+            Tool {
+                name: "sensor_stats".to_string(),
+                description: "Summary statistics of a sensor's values over a time range: count, min, max, mean and last value. Value fields are null when the range is empty. Times in the result are integer microseconds since the Unix epoch (fields ending in Us)".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "sensorId": {"type": "string"},
+                        "startTime": {
+                            "type": ["string", "integer"],
+                            "description": "Start of the range: an RFC 3339 timestamp or integer epoch milliseconds"
+                        },
+                        "endTime": {
+                            "type": ["string", "integer"],
+                            "description": "End of the range: an RFC 3339 timestamp or integer epoch milliseconds"
+                        }
+                    },
+                    "required": ["sensorId", "startTime", "endTime"]
+                }),
+            },
+            Tool {
+                name: "detect_gaps".to_string(),
+                description: "Find stretches of missing data in a sensor's readings over a time range: consecutive readings spaced more than half an interval wider than expected, so that jitter is not reported, e.g. dropped camera frames in a recording. Each gap reports the readings around it and how many readings the interval would have put inside. Times in the result are integer microseconds since the Unix epoch (fields ending in Us)".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "sensorId": {"type": "string"},
+                        "startTime": {
+                            "type": ["string", "integer"],
+                            "description": "Start of the range: an RFC 3339 timestamp or integer epoch milliseconds"
+                        },
+                        "endTime": {
+                            "type": ["string", "integer"],
+                            "description": "End of the range: an RFC 3339 timestamp or integer epoch milliseconds"
+                        },
+                        "expectedIntervalMs": {
+                            "type": "number",
+                            "exclusiveMinimum": 0,
+                            "description": "Expected time between readings in milliseconds, e.g. 33.3 for a 30 Hz camera"
+                        }
+                    },
+                    "required": ["sensorId", "startTime", "endTime", "expectedIntervalMs"]
+                }),
+            },
+            Tool {
+                name: "latest_readings".to_string(),
+                description: "Newest timestamp and value of each sensor, in one query. Timestamps are RFC 3339 in UTC with microseconds, alongside timestampUs. Sensors without readings are listed under missing instead of failing the call. Over the HTTP transport the readings can be streamed as NDJSON by accepting application/x-ndjson; streamed readings are looked up a chunk of sensors at a time and the closing response lists the missing sensors".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "sensorIds": {
+                            "type": "array",
+                            "items": {"type": "string"},
+                            "description": "Sensors to look up (default: every sensor)"
+                        }
+                    }
+                }),
+            },
+            Tool {
+                name: "export_sensor_data".to_string(),
+                description: "Export a sensor's readings over a time range to a file in the export directory. CSV has timestamp,value columns; Parquet has typed timestamp (UTC microseconds) and value (double) columns. Over the HTTP transport the readings can also be streamed as NDJSON, as they are written, by accepting application/x-ndjson".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "sensorId": {"type": "string"},
+                        "startTime": {
+                            "type": ["string", "integer"],
+                            "description": "Start of the range: an RFC 3339 timestamp or integer epoch milliseconds"
+                        },
+                        "endTime": {
+                            "type": ["string", "integer"],
+                            "description": "End of the range: an RFC 3339 timestamp or integer epoch milliseconds"
+                        },
+                        "format": {
+                            "type": "string",
+                            "enum": ["csv", "parquet"],
+                            "description": "Output format (default: csv)"
+                        }
+                    },
+                    "required": ["sensorId", "startTime", "endTime"]
+                }),
+            },
+        ];
+        let tools = tools
+            .into_iter()
+            .filter(|tool| self.tool_flags.is_enabled(&tool.name))
+            .collect();
+
+        Ok(ListToolsResult {
             tools,
             next_cursor: None,
         })
     }
 
-    pub async fn call_tool(
-        &self,
-        request: CallToolRequestParam,
-    ) -> std::result::Result<CallToolResult, GlspError> {
-        match request.name.as_str() {
-            "create_diagram" => self.create_diagram(request.arguments).await,
-            "delete_diagram" => self.delete_diagram(request.arguments).await,
-            "create_node" => self.create_node(request.arguments).await,
-            "create_edge" => self.create_edge(request.arguments).await,
-            "delete_element" => self.delete_element(request.arguments).await,
-            "update_element" => self.update_element(request.arguments).await,
-            "apply_layout" => self.apply_layout(request.arguments).await,
-            "export_diagram" => self.export_diagram(request.arguments).await,
-            "save_diagram" => self.save_diagram_tool(request.arguments).await,
-            "select_elements" => self.select_elements(request.arguments).await,
-            "select_all" => self.select_all(request.arguments).await,
-            "clear_selection" => self.clear_selection(request.arguments).await,
-            "get_selection" => self.get_selection(request.arguments).await,
-            "scan_wasm_components" => self.scan_wasm_components().await,
-            "check_wasm_component_status" => {
-                self.check_wasm_component_status(request.arguments).await
+    pub async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        if !self.tool_flags.is_enabled(&request.name) {
+            return Err(GlspError::ToolDisabled(request.name));
+        }
+        self.check_diagram_lock(&request.name, request.arguments.as_ref())
+            .await?;
+
+        let diagram_id = request
+            .arguments
+            .as_ref()
+            .and_then(|a| a["diagramId"].as_str())
+            .map(str::to_string);
+        let tool = request.name.clone();
+        let modified = self
+            .changed_diagrams(&tool, request.arguments.as_ref())
+            .await;
+
+        let idempotency_key = request
+            .arguments
+            .as_ref()
+            .and_then(|a| a[IDEMPOTENCY_KEY_ARG].as_str())
+            .filter(|_| IDEMPOTENT_TOOLS.contains(&tool.as_str()))
+            .map(str::to_string);
+        let idempotent_arguments = idempotency_key.as_ref().and(request.arguments.clone());
+        if let (Some(key), Some(arguments)) = (&idempotency_key, &idempotent_arguments) {
+            match self.idempotency.lock().await.lookup(key, &tool, arguments) {
+                Lookup::Miss => {}
+                Lookup::Replay(stored) => {
+                    info!("Replaying {} result for idempotency key {}", tool, key);
+                    let mut replay: CallToolResult = serde_json::from_value(stored)?;
+                    replay.content.push(Content::text(
+                        json!({ REPLAY_MARKER: true, "idempotencyKey": key }).to_string(),
+                    ));
+                    return Ok(replay);
+                }
+                Lookup::Conflict => {
+                    return Err(GlspError::ToolExecution(format!(
+                        "Idempotency key {key} was already used for a different call"
+                    )));
+                }
+            }
+        }
+
+        // Webhooks receive the delta, and the history of tools that do not
+        // report the elements they touch records those in the delta, so keep
+        // the diagrams as they were. Workspace tools only learn which
+        // diagrams they replace as they run; keep the watched ones
+        let diff_history = self.config.history_size > 0 && !TRACKED_TOOLS.contains(&tool.as_str());
+        let mut before: HashMap<String, DiagramModel> = {
+            let models = self.models.lock().await;
+            let candidates: Vec<&String> = if WORKSPACE_TOOLS.contains(&tool.as_str()) {
+                models
+                    .keys()
+                    .filter(|id| self.webhooks.is_watched(id))
+                    .collect()
+            } else {
+                modified
+                    .iter()
+                    .filter(|id| self.webhooks.is_watched(id) || diff_history)
+                    .collect()
+            };
+            candidates
+                .into_iter()
+                .filter_map(|id| Some((id.clone(), models.get(id)?.clone())))
+                .collect()
+        };
+        let actor = request
+            .arguments
+            .as_ref()
+            .and_then(|a| a["clientId"].as_str())
+            .map(str::to_string);
+        let edited = match (&diagram_id, &request.arguments) {
+            (Some(id), Some(args)) if PROPERTY_CHANGE_TOOLS.contains(&tool.as_str()) => {
+                match Self::element_id_arg(args, "elementId") {
+                    Ok(element_id) => self
+                        .models
+                        .lock()
+                        .await
+                        .get(id)
+                        .and_then(|diagram| diagram.elements.get(&element_id))
+                        .cloned(),
+                    Err(_) => None,
+                }
+            }
+            _ => None,
+        };
+
+        // A panicking handler fails its own request instead of the server
+        let (result, mut touched) =
+            history::collect(AssertUnwindSafe(self.dispatch_tool(request)).catch_unwind()).await;
+        let mut result = match result {
+            Ok(result) => result,
+            Err(panic) => {
+                let incident_id = uuid::Uuid::new_v4().to_string();
+                error!(
+                    "Tool '{}' panicked (incident {}): {}",
+                    tool,
+                    incident_id,
+                    panic_message(panic.as_ref())
+                );
+                Err(GlspError::Panic { incident_id })
+            }
+        };
+
+        // Deprecation notices ride along as an extra content item
+        if let Ok(outcome) = &mut result {
+            if outcome.is_error != Some(true) {
+                if let Some(item) = warnings_item(&deprecations(&tool)) {
+                    outcome.content.push(Content::text(item));
+                }
+            }
+        }
+
+        if let (Ok(outcome), Some(key), Some(arguments)) =
+            (&result, &idempotency_key, &idempotent_arguments)
+        {
+            if outcome.is_error != Some(true) {
+                self.idempotency.lock().await.store(
+                    key,
+                    &tool,
+                    arguments,
+                    serde_json::to_value(outcome)?,
+                );
+            }
+        }
+
+        if let Ok(outcome) = &result {
+            if outcome.is_error != Some(true) {
+                let mut changed = modified;
+                changed.extend(reported_diagrams(&tool, outcome));
+                for diagram_id in changed {
+                    let before = before.remove(&diagram_id);
+                    let touched = touched.remove(&diagram_id);
+                    self.publish_change(
+                        diagram_id,
+                        &tool,
+                        before,
+                        touched,
+                        edited.as_ref(),
+                        &actor,
+                    )
+                    .await;
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Record a successful change to one diagram: in its history, with its
+    /// webhooks and as a `DiagramUpdate` event. `before` is the diagram as it
+    /// was, when it was kept; `touched` the elements the handler reported;
+    /// `edited` the element an in-place edit changed.
+    async fn publish_change(
+        &self,
+        diagram_id: String,
+        tool: &str,
+        before: Option<DiagramModel>,
+        touched: Option<Vec<String>>,
+        edited: Option<&ModelElement>,
+        actor: &Option<String>,
+    ) {
+        let models = self.models.lock().await;
+        let current = models.get(&diagram_id);
+        let revision = current.map(|d| d.revision);
+        let changes: Vec<PropertyChange> = edited
+            .and_then(|old| {
+                let new = current?.elements.get(&old.id)?;
+                Some(property_changes(old, new))
+            })
+            .unwrap_or_default();
+        let delta = match (&before, current) {
+            (Some(before), Some(current)) => oplog::diff(before.updated_at, before, current).ops,
+            _ => Vec::new(),
+        };
+        drop(models);
+
+        self.history.lock().await.record(
+            &diagram_id,
+            HistoryEntry {
+                timestamp: chrono::Utc::now(),
+                actor: actor.clone(),
+                operation: tool.to_string(),
+                revision,
+                element_ids: if TRACKED_TOOLS.contains(&tool) {
+                    touched.unwrap_or_default()
+                } else {
+                    affected_elements(&delta)
+                },
+            },
+        );
+        self.webhooks.notify(&WebhookPayload {
+            diagram_id: diagram_id.clone(),
+            revision,
+            tool: tool.to_string(),
+            delta,
+            changes: changes.clone(),
+            timestamp: chrono::Utc::now(),
+        });
+        self.events.publish(ServerEvent::DiagramUpdate {
+            diagram_id,
+            revision,
+            tool: tool.to_string(),
+            changes,
+        });
+    }
+
+    /// Route a tool call to its handler
+    async fn dispatch_tool(
+        &self,
+        request: CallToolRequestParam,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        match request.name.as_str() {
+            "create_diagram" => self.create_diagram(request.arguments).await,
+            "delete_diagram" => self.delete_diagram(request.arguments).await,
+            "merge_diagrams" => self.merge_diagrams(request.arguments).await,
+            "duplicate_diagram" => self.duplicate_diagram(request.arguments).await,
+            "compare_diagrams" => self.compare_diagrams(request.arguments).await,
+            "extract_subgraph" => self.extract_subgraph(request.arguments).await,
+            "server_info" => Ok(CallToolResult {
+                content: vec![Content::text(serde_json::to_string_pretty(
+                    &self.server_description(),
+                )?)],
+                is_error: Some(false),
+            }),
+            "get_diagram_type_capabilities" => {
+                self.get_diagram_type_capabilities(request.arguments).await
+            }
+            "create_node" => self.create_node(request.arguments).await,
+            "create_edge" => self.create_edge(request.arguments).await,
+            "create_edges" => self.create_edges(request.arguments).await,
+            "suggest_targets" => self.suggest_targets(request.arguments).await,
+            "create_hyperedge" => self.create_hyperedge(request.arguments).await,
+            "reconnect_edge" => self.reconnect_edge(request.arguments).await,
+            "reverse_edge" => self.reverse_edge(request.arguments).await,
+            "detect_cycles" => self.detect_cycles(request.arguments).await,
+            "assert_acyclic" => self.assert_acyclic(request.arguments).await,
+            "is_reachable" => self.is_reachable(request.arguments).await,
+            "shortest_path" => self.shortest_path(request.arguments).await,
+            "delete_element" => self.delete_element(request.arguments).await,
+            "update_element" => self.update_element(request.arguments).await,
+            "apply_layout" => self.apply_layout(request.arguments).await,
+            "compute_layout_hints" => self.compute_layout_hints(request.arguments).await,
+            "normalize_coordinates" => self.normalize_coordinates(request.arguments).await,
+            "get_content_bounds" => self.get_content_bounds(request.arguments).await,
+            "export_diagram" => self.export_diagram(request.arguments).await,
+            "save_diagram" => self.save_diagram_tool(request.arguments).await,
+            "retry_pending_persists" => self.retry_pending_persists().await,
+            "register_webhook" => self.register_webhook(request.arguments).await,
+            "unregister_webhook" => self.unregister_webhook(request.arguments).await,
+            "list_webhooks" => self.list_webhooks().await,
+            "select_elements" => self.select_elements(request.arguments).await,
+            "select_all" => self.select_all(request.arguments).await,
+            "clear_selection" => self.clear_selection(request.arguments).await,
+            "get_selection" => self.get_selection(request.arguments).await,
+            "scan_wasm_components" => self.scan_wasm_components().await,
+            "check_wasm_component_status" => {
+                self.check_wasm_component_status(request.arguments).await
+            }
+            "get_component_status" => self.get_component_status(request.arguments).await,
+            "get_component_dependency_graph" => {
+                self.get_component_dependency_graph(request.arguments).await
+            }
+            "find_components_by_interface" => {
+                self.find_components_by_interface(request.arguments).await
+            }
+            "execute_component" => self.execute_component(request.arguments).await,
+            "get_execution_result" => self.get_execution_result(request.arguments).await,
+            "cancel_execution" => self.cancel_execution(request.arguments).await,
+            "load_wasm_component" => self.load_wasm_component(request.arguments).await,
+            "refresh_wasm_interfaces" => self.refresh_wasm_interfaces(request.arguments).await,
+            "get_component_path" => self.get_component_path(request.arguments).await,
+            "inspect_component" => self.inspect_component(request.arguments).await,
+            "get_component_wit_info" => self.get_component_wit_info(request.arguments).await,
+            "debug_wit_analysis" => self.debug_wit_analysis(request.arguments).await,
+            "export_component_wit" => self.export_component_wit(request.arguments).await,
+
+            // Workspace management tools
+            "set_workspace_directory" => self.set_workspace_directory_tool(request.arguments).await,
+            "get_workspace_info" => self.get_current_workspace_tool().await,
+            "set_wasm_components_path" => {
+                self.set_wasm_components_path_tool(request.arguments).await
+            }
+            "set_diagrams_path" => self.set_diagrams_path_tool(request.arguments).await,
+            "rescan_workspace" => self.rescan_workspace_tool().await,
+            "validate_workspace" => self.validate_workspace_tool(request.arguments).await,
+            "create_workspace_structure" => {
+                self.create_workspace_structure_tool(request.arguments)
+                    .await
+            }
+            "get_diagram" => self.get_diagram(request.arguments).await,
+            "list_diagrams" => self.list_diagrams(request.arguments).await,
+            "find_nodes" => self.find_nodes(request.arguments).await,
+            "set_diagram_metadata" => self.set_diagram_metadata(request.arguments).await,
+            "set_diagram_readonly" => self.set_diagram_readonly(request.arguments).await,
+            "set_viewport" => self.set_viewport(request.arguments).await,
+            "set_type_style" => self.set_type_style(request.arguments).await,
+            "add_diagram_tags" => self.add_diagram_tags(request.arguments).await,
+            "get_edges_for_node" => self.get_edges_for_node(request.arguments).await,
+            "set_compartment_visibility" => {
+                self.set_compartment_visibility(request.arguments).await
+            }
+            "add_subtask" => self.add_subtask(request.arguments).await,
+            "add_to_container" => self.add_to_container(request.arguments).await,
+            "remove_subtask" => self.remove_subtask(request.arguments).await,
+            "set_task_collapsed" => self.set_task_collapsed(request.arguments).await,
+            "convert_diagram_type" => self.convert_diagram_type(request.arguments).await,
+            "generate_diagram_from_wit" => self.generate_diagram_from_wit(request.arguments).await,
+            "import_rust_types" => self.import_rust_types(request.arguments).await,
+            "validate_wit" => self.validate_wit(request.arguments).await,
+            "attach_file" => self.attach_file(request.arguments).await,
+            "list_attachments" => self.list_attachments(request.arguments).await,
+            "get_diagram_size" => self.get_diagram_size(request.arguments).await,
+            "get_history" => self.get_history(request.arguments).await,
+            "get_attachment" => self.get_attachment(request.arguments).await,
+            "export_workspace" => self.export_workspace().await,
+            "import_workspace" => self.import_workspace(request.arguments).await,
+            "snapshot_state" => self.snapshot_state(request.arguments).await,
+            "restore_state" => self.restore_state(request.arguments).await,
+            "check_integrity" => self.check_integrity(request.arguments).await,
+
+            // Locking tools
+            "acquire_lock" => self.acquire_lock(request.arguments).await,
+            "release_lock" => self.release_lock(request.arguments).await,
+            "lock_element" => self.lock_element(request.arguments).await,
+            "unlock_element" => self.unlock_element(request.arguments).await,
+
+            // Sensor tools
+            "sensor_stats" => self.sensor_stats(request.arguments).await,
+            "detect_gaps" => self.detect_gaps(request.arguments).await,
+            "latest_readings" => self.latest_readings(request.arguments).await,
+            "export_sensor_data" => self.export_sensor_data(request.arguments).await,
+
+            _ => Err(GlspError::NotImplemented(format!(
+                "Tool not implemented: {}",
+                request.name
+            ))),
+        }
+    }
+
+    /// Concurrent execution use, when WASM execution is available
+    pub fn execution_concurrency(&self) -> Option<ExecutionConcurrency> {
+        self.execution_engine
+            .as_ref()
+            .map(|engine| engine.concurrency())
+    }
+
+    /// Hits and misses of the execution result cache, when it is enabled
+    pub async fn result_cache_stats(&self) -> Option<ResultCacheStats> {
+        self.wasm_watcher.lock().await.result_cache_stats()
+    }
+
+    /// Event bus feeding the `/events` stream
+    pub fn events(&self) -> std::sync::Arc<EventBus> {
+        self.events.clone()
+    }
+
+    pub async fn list_resources(
+        &self,
+        _request: PaginatedRequestParam,
+    ) -> std::result::Result<ListResourcesResult, GlspError> {
+        let models = self.models.lock().await;
+        let mut resources = vec![
+            Resource {
+                uri: "diagram://list".to_string(),
+                name: "Diagram List".to_string(),
+                description: Some("List of all available diagrams".to_string()),
+                mime_type: Some("application/json".to_string()),
+                annotations: None,
+                raw: None,
+            },
+            Resource {
+                uri: "wasm://components/list".to_string(),
+                name: "WASM Components List".to_string(),
+                description: Some("List of all available WASM components".to_string()),
+                mime_type: Some("application/json".to_string()),
+                annotations: None,
+                raw: None,
+            },
+        ];
+
+        // Add resources for each loaded diagram
+        for (id, diagram) in models.iter() {
+            resources.push(Resource {
+                uri: format!("diagram://model/{id}"),
+                name: diagram.name.clone(),
+                description: Some(format!("{} diagram", diagram.diagram_type)),
+                mime_type: Some("application/json".to_string()),
+                annotations: None,
+                raw: None,
+            });
+
+            resources.push(Resource {
+                uri: format!("diagram://validation/{id}"),
+                name: format!("{} Validation", diagram.name),
+                description: Some("Validation results for the diagram".to_string()),
+                mime_type: Some("application/json".to_string()),
+                annotations: None,
+                raw: None,
+            });
+        }
+
+        // Add resources for individual WASM components
+        let wasm_watcher = self.wasm_watcher.lock().await;
+        let wasm_components = wasm_watcher.get_components();
+        for component in wasm_components {
+            resources.push(Resource {
+                uri: format!("wasm://component/{}", component.name),
+                name: format!("WASM Component: {}", component.name),
+                description: Some(format!("Details for {} component", component.name)),
+                mime_type: Some("application/json".to_string()),
+                annotations: None,
+                raw: None,
+            });
+
+            // Add WIT-specific resources for each component
+            resources.push(Resource {
+                uri: format!("wasm://component/{}/wit", component.name),
+                name: format!("WIT Analysis: {}", component.name),
+                description: Some(format!(
+                    "WIT interface analysis for {} component",
+                    component.name
+                )),
+                mime_type: Some("application/json".to_string()),
+                annotations: None,
+                raw: None,
+            });
+
+            resources.push(Resource {
+                uri: format!("wasm://component/{}/wit/raw", component.name),
+                name: format!("Raw WIT: {}", component.name),
+                description: Some(format!("Raw WIT content for {} component", component.name)),
+                mime_type: Some("text/plain".to_string()),
+                annotations: None,
+                raw: None,
+            });
+
+            resources.push(Resource {
+                uri: format!("wasm://component/{}/interfaces", component.name),
+                name: format!("Interfaces: {}", component.name),
+                description: Some(format!("All interfaces for {} component", component.name)),
+                mime_type: Some("application/json".to_string()),
+                annotations: None,
+                raw: None,
+            });
+        }
+
+        Ok(ListResourcesResult {
+            resources,
+            next_cursor: None,
+        })
+    }
+
+    pub async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+    ) -> std::result::Result<ReadResourceResult, GlspError> {
+        // Parse the URI to determine what resource is being requested
+        if request.uri.starts_with("diagram://model/") {
+            let diagram_id = request.uri.strip_prefix("diagram://model/").unwrap_or("");
+
+            let models = self.models.lock().await;
+            if let Some(model) = models.get(diagram_id) {
+                // Return the diagram model as JSON
+                let content = serde_json::to_string(model)
+                    .map_err(|e| GlspError::NotImplemented(format!("Serialization error: {e}")))?;
+
+                Ok(ReadResourceResult {
+                    contents: vec![ResourceContents {
+                        uri: request.uri.clone(),
+                        mime_type: Some("application/json".to_string()),
+                        text: Some(content),
+                        blob: None,
+                    }],
+                })
+            } else {
+                Err(GlspError::NotImplemented(format!(
+                    "Diagram not found: {diagram_id}"
+                )))
+            }
+        } else if request.uri.starts_with("diagram://validation/") {
+            // Return a simple validation result
+            let _diagram_id = request
+                .uri
+                .strip_prefix("diagram://validation/")
+                .unwrap_or("");
+            let validation = json!({
+                "isValid": true,
+                "issues": []
+            });
+
+            Ok(ReadResourceResult {
+                contents: vec![ResourceContents {
+                    uri: request.uri.clone(),
+                    mime_type: Some("application/json".to_string()),
+                    text: Some(validation.to_string()),
+                    blob: None,
+                }],
+            })
+        } else if request.uri == "diagram://list" {
+            // Return list of diagrams from both memory and disk
+            let models = self.models.lock().await;
+            let mut diagram_infos = Vec::new();
+
+            // Add loaded diagrams with their info
+            for (id, diagram) in models.iter() {
+                diagram_infos.push(json!({
+                    "id": id,
+                    "name": diagram.name,
+                    "diagramType": diagram.diagram_type,
+                    "createdAt": diagram.created_at,
+                    "updatedAt": diagram.updated_at,
+                }));
+            }
+
+            let list = json!({
+                "diagrams": diagram_infos
+            });
+
+            Ok(ReadResourceResult {
+                contents: vec![ResourceContents {
+                    uri: request.uri.clone(),
+                    mime_type: Some("application/json".to_string()),
+                    text: Some(list.to_string()),
+                    blob: None,
+                }],
+            })
+        } else if request.uri == "wasm://components/list" {
+            // Get WASM components from the wasm watcher (with analyzed data)
+            let wasm_watcher = self.wasm_watcher.lock().await;
+            let wasm_components = wasm_watcher.get_components();
+
+            let component_list: Vec<serde_json::Value> = wasm_components
+                .iter()
+                .map(|component| {
+                    json!({
+                        "name": component.name,
+                        "path": component.path,
+                        "description": format!("WASM component: {}", component.name),
+                        "status": if component.file_exists { "available" } else { "missing" },
+                        "interfaces": component.interfaces.len(),
+                        "uri": format!("wasm://component/{}", component.name)
+                    })
+                })
+                .collect();
+
+            let available_count = wasm_components.iter().filter(|c| c.file_exists).count();
+            let missing_count = wasm_components.len() - available_count;
+
+            let wasm_list = json!({
+                "components": component_list,
+                "total": component_list.len(),
+                "available": available_count,
+                "missing": missing_count
+            });
+
+            Ok(ReadResourceResult {
+                contents: vec![ResourceContents {
+                    uri: request.uri.clone(),
+                    mime_type: Some("application/json".to_string()),
+                    text: Some(wasm_list.to_string()),
+                    blob: None,
+                }],
+            })
+        } else if request.uri.starts_with("wasm://component/") {
+            let path = request
+                .uri
+                .strip_prefix("wasm://component/")
+                .ok_or_else(|| {
+                    GlspError::NotImplemented(format!(
+                        "Invalid WASM component URI: {}",
+                        request.uri
+                    ))
+                })?;
+
+            if let Some((component_name, suffix)) = path.split_once('/') {
+                match suffix {
+                    "wit" => self.get_component_wit_analysis(component_name).await,
+                    "wit/raw" => self.get_component_raw_wit(component_name).await,
+                    "interfaces" => self.get_component_interfaces(component_name).await,
+                    _ => Err(GlspError::NotImplemented(format!(
+                        "Unknown component resource: {}",
+                        request.uri
+                    ))),
+                }
+            } else {
+                self.get_wasm_component_details(path).await
+            }
+        } else {
+            Err(GlspError::NotImplemented(format!(
+                "Resource type not supported: {}",
+                request.uri
+            )))
+        }
+    }
+
+    // Helper methods for component-specific resources
+    async fn get_wasm_component_details(
+        &self,
+        component_name: &str,
+    ) -> std::result::Result<ReadResourceResult, GlspError> {
+        let wasm_watcher = self.wasm_watcher.lock().await;
+        let component = wasm_watcher.get_component(component_name).ok_or_else(|| {
+            GlspError::NotImplemented(format!("WASM component not found: {component_name}"))
+        })?;
+
+        let content = json!({
+            "name": component.name,
+            "path": component.path,
+            "description": component.description,
+            "fileExists": component.file_exists,
+            "lastSeen": component.last_seen,
+            "removedAt": component.removed_at,
+            "interfaces": component.interfaces,
+            "metadata": component.metadata,
+            "witInterfaces": component.wit_interfaces,
+            "dependencies": component.dependencies
+        });
+
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents {
+                uri: format!("wasm://component/{component_name}"),
+                mime_type: Some("application/json".to_string()),
+                text: Some(content.to_string()),
+                blob: None,
+            }],
+        })
+    }
+
+    async fn get_component_interfaces(
+        &self,
+        component_name: &str,
+    ) -> std::result::Result<ReadResourceResult, GlspError> {
+        let wasm_watcher = self.wasm_watcher.lock().await;
+        let component = wasm_watcher.get_component(component_name).ok_or_else(|| {
+            GlspError::NotImplemented(format!("WASM component not found: {component_name}"))
+        })?;
+
+        let content = json!({
+            "componentName": component.name,
+            "interfaces": component.interfaces,
+            "totalInterfaces": component.interfaces.len()
+        });
+
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents {
+                uri: format!("wasm://component/{component_name}/interfaces"),
+                mime_type: Some("application/json".to_string()),
+                text: Some(content.to_string()),
+                blob: None,
+            }],
+        })
+    }
+
+    async fn get_component_wit_analysis(
+        &self,
+        component_name: &str,
+    ) -> std::result::Result<ReadResourceResult, GlspError> {
+        let wasm_watcher = self.wasm_watcher.lock().await;
+        let component = wasm_watcher.get_component(component_name).ok_or_else(|| {
+            GlspError::NotImplemented(format!("WASM component not found: {component_name}"))
+        })?;
+
+        // Analyze WIT interfaces specifically
+        let mut imports = Vec::new();
+        let mut exports = Vec::new();
+
+        for interface in &component.interfaces {
+            let interface_data = json!({
+                "name": interface.name,
+                "functions": interface.functions.iter().map(|f| json!({
+                    "name": f.name,
+                    "parameters": f.params.iter().map(|p| json!({
+                        "name": p.name,
+                        "type": p.param_type
+                    })).collect::<Vec<_>>(),
+                    "returns": f.returns.iter().map(|r| json!({
+                        "name": r.name,
+                        "type": r.param_type
+                    })).collect::<Vec<_>>()
+                })).collect::<Vec<_>>()
+            });
+
+            match interface.interface_type.as_str() {
+                "import" => imports.push(interface_data),
+                "export" => exports.push(interface_data),
+                _ => {}
+            }
+        }
+
+        let content = json!({
+            "componentName": component.name,
+            "witAnalysis": {
+                "imports": imports,
+                "exports": exports,
+                "summary": {
+                    "totalImports": imports.len(),
+                    "totalExports": exports.len(),
+                    "totalFunctions": component.interfaces.iter()
+                        .map(|i| i.functions.len())
+                        .sum::<usize>()
+                }
+            },
+            "metadata": component.metadata,
+            "dependencies": component.dependencies
+        });
+
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents {
+                uri: format!("wasm://component/{component_name}/wit"),
+                mime_type: Some("application/json".to_string()),
+                text: Some(content.to_string()),
+                blob: None,
+            }],
+        })
+    }
+
+    async fn get_component_raw_wit(
+        &self,
+        component_name: &str,
+    ) -> std::result::Result<ReadResourceResult, GlspError> {
+        let wasm_watcher = self.wasm_watcher.lock().await;
+        let component = wasm_watcher.get_component(component_name).ok_or_else(|| {
+            GlspError::NotImplemented(format!("WASM component not found: {component_name}"))
+        })?;
+
+        let wit_content = component
+            .wit_interfaces
+            .clone()
+            .unwrap_or_else(|| "// No WIT content available for this component".to_string());
+
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents {
+                uri: format!("wasm://component/{component_name}/wit/raw"),
+                mime_type: Some("text/plain".to_string()),
+                text: Some(wit_content),
+                blob: None,
+            }],
+        })
+    }
+
+    pub async fn list_prompts(
+        &self,
+        _request: PaginatedRequestParam,
+    ) -> std::result::Result<ListPromptsResult, GlspError> {
+        // Return empty prompts for now
+        Ok(ListPromptsResult {
+            prompts: vec![],
+            next_cursor: None,
+        })
+    }
+
+    pub async fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+    ) -> std::result::Result<GetPromptResult, GlspError> {
+        Err(GlspError::NotImplemented(format!(
+            "Prompt not found: {}",
+            request.name
+        )))
+    }
+}
+
+impl GlspBackend {
+    // Tool implementation methods
+    async fn create_diagram(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let default_applied = args["diagramType"].as_str().is_none();
+        let diagram_type = match args["diagramType"].as_str() {
+            Some(diagram_type) => diagram_type,
+            None => self
+                .config
+                .default_diagram_type()
+                .ok()
+                .flatten()
+                .ok_or_else(|| GlspError::ToolExecution("Missing diagramType".to_string()))?,
+        };
+        let name = args["name"].as_str().unwrap_or("Untitled Diagram");
+        let if_exists = match args["ifExists"].as_str().map(str::parse::<IfExists>) {
+            None => IfExists::default(),
+            Some(Ok(policy)) => policy,
+            Some(Err(message)) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(message)],
+                    is_error: Some(true),
+                });
+            }
+        };
+
+        let mut models = self.models.lock().await;
+
+        // Check and insert under the same lock so two concurrent calls with
+        // `reuse` cannot both create the diagram
+        if if_exists != IfExists::Create {
+            let existing = models
+                .values()
+                .filter(|d| d.name == name)
+                .min_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+            if let Some(existing) = existing {
+                let existing_id = existing.id.clone();
+                let existing_type = existing.diagram_type.clone();
+                drop(models);
+
+                if if_exists == IfExists::Error {
+                    return Ok(CallToolResult {
+                        content: vec![Content::text(format!(
+                            "A diagram named '{name}' already exists with ID: {existing_id}"
+                        ))],
+                        is_error: Some(true),
+                    });
+                }
+                if existing_type != diagram_type {
+                    return Ok(CallToolResult {
+                        content: vec![Content::text(format!(
+                            "Diagram '{name}' ({existing_id}) is a {existing_type} diagram, not {diagram_type}"
+                        ))],
+                        is_error: Some(true),
+                    });
+                }
+
+                return Ok(CallToolResult {
+                    content: vec![
+                        Content::text(format!("Reused diagram '{name}' with ID: {existing_id}")),
+                        Content::text(serde_json::to_string(&json!({
+                            "diagramId": existing_id,
+                            "created": false,
+                            "diagramType": diagram_type,
+                            "defaultTypeApplied": default_applied
+                        }))?),
+                    ],
+                    is_error: Some(false),
+                });
+            }
+        }
+
+        let mut diagram = DiagramModel::new(diagram_type);
+        diagram.name = name.to_string();
+        let diagram_id = diagram.id.clone();
+
+        // Save to memory
+        models.insert(diagram_id.clone(), diagram);
+        drop(models); // Release the lock before saving to disk
+
+        // Save to disk
+        if let Err(e) = self.save_diagram(&diagram_id).await {
+            error!("Failed to save new diagram to disk: {e}");
+        }
+
+        Ok(CallToolResult {
+            content: vec![
+                Content::text(format!("Created diagram '{name}' with ID: {diagram_id}")),
+                Content::text(serde_json::to_string(&json!({
+                    "diagramId": diagram_id,
+                    "created": true,
+                    "diagramType": diagram_type,
+                    "defaultTypeApplied": default_applied
+                }))?),
+            ],
+            is_error: Some(false),
+        })
+    }
+
+    async fn delete_diagram(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+
+        // Get the diagram name before deletion for the response
+        let diagram_name = {
+            let models = self.models.lock().await;
+            models.get(diagram_id).map(|d| d.name.clone())
+        };
+
+        // Remove from memory
+        let mut models = self.models.lock().await;
+        let removed = models.remove(diagram_id);
+        drop(models); // Release the lock before filesystem operations
+        self.locks.lock().await.clear(diagram_id);
+
+        if removed.is_none() {
+            return Err(GlspError::ToolExecution(format!(
+                "Diagram not found: {diagram_id}"
+            )));
+        }
+
+        // Delete from disk using persistence manager
+        let name_for_deletion = diagram_name.as_deref().unwrap_or("Unknown");
+        if let Err(e) = self.delete_diagram_files(name_for_deletion).await {
+            error!("Failed to delete diagram files from disk: {e}");
+            return Err(GlspError::ToolExecution(format!(
+                "Failed to delete diagram files: {e}"
+            )));
+        }
+
+        if let Err(e) = self.attachments.remove_all(diagram_id).await {
+            warn!("Failed to delete attachments of diagram {diagram_id}: {e}");
+        }
+        // A pending persist would bring the diagram back on the next start
+        if let Err(e) = self.dead_letters.remove(diagram_id).await {
+            warn!("Failed to clear dead letter of deleted diagram {diagram_id}: {e}");
+        }
+
+        info!("Deleted diagram '{name_for_deletion}' (ID: {diagram_id})");
+
+        Ok(CallToolResult {
+            content: vec![Content::text(format!(
+                "Successfully deleted diagram '{name_for_deletion}' (ID: {diagram_id})"
+            ))],
+            is_error: Some(false),
+        })
+    }
+
+    async fn merge_diagrams(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let target_id = args["targetId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing targetId".to_string()))?;
+        let source_id = args["sourceId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing sourceId".to_string()))?;
+        let delete_source = args["deleteSource"].as_bool().unwrap_or(false);
+
+        if target_id == source_id {
+            return Err(GlspError::ToolExecution(
+                "Cannot merge a diagram into itself".to_string(),
+            ));
+        }
+
+        let mut models = self.models.lock().await;
+        let source = models
+            .get(source_id)
+            .cloned()
+            .ok_or_else(|| GlspError::ToolExecution(format!("Diagram not found: {source_id}")))?;
+        let target = models
+            .get_mut(target_id)
+            .ok_or_else(|| GlspError::ToolExecution(format!("Diagram not found: {target_id}")))?;
+
+        let offset = if args["offset"].is_null() {
+            default_merge_offset(target, &source)
+        } else {
+            Position {
+                x: args["offset"]["x"].as_f64().unwrap_or(0.0),
+                y: args["offset"]["y"].as_f64().unwrap_or(0.0),
+            }
+        };
+        let id_map = guarded_change(target, |target| merge_diagram(target, &source, &offset))
+            .map_err(|cycle| GlspError::WouldCreateCycle { cycle })?;
+        history::touched(target_id, id_map.values());
+        drop(models); // Release the lock before saving
+
+        if let Err(e) = self.save_diagram(target_id).await {
+            error!("Failed to save diagram after merge: {}", e);
+        }
+
+        // The merge is already saved, so a failed delete is reported rather
+        // than failing the call
+        let delete_error = if delete_source {
+            match self
+                .delete_diagram(Some(json!({"diagramId": source_id})))
+                .await
+            {
+                Ok(_) => None,
+                Err(e) => {
+                    warn!(
+                        "Merged diagram {source_id} into {target_id} but could not delete it: {e}"
+                    );
+                    Some(e.to_string())
+                }
+            }
+        } else {
+            None
+        };
+
+        info!(
+            "Merged {} elements from diagram {} into {}",
+            id_map.len(),
+            source_id,
+            target_id
+        );
+
+        let mut result = json!({
+            "targetId": target_id,
+            "sourceId": source_id,
+            "offset": {"x": offset.x, "y": offset.y},
+            "idMap": id_map,
+            "sourceDeleted": delete_source && delete_error.is_none(),
+        });
+        if let Some(error) = delete_error {
+            result["sourceDeleteError"] = json!(error);
+        }
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn duplicate_diagram(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let options = DuplicateOptions {
+            properties: args["copyProperties"].as_bool().unwrap_or(true),
+            metadata: args["copyMetadata"].as_bool().unwrap_or(true),
+        };
+
+        let mut models = self.models.lock().await;
+        let source = models
+            .get(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution(format!("Diagram not found: {diagram_id}")))?;
+        let name = args["newName"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{} (copy)", source.name));
+
+        let (copy, id_map) = duplicate_diagram(source, &name, options);
+        let new_id = copy.id.clone();
+        models.insert(new_id.clone(), copy);
+        drop(models); // Release the lock before saving
+
+        if let Err(e) = self.save_diagram(&new_id).await {
+            error!("Failed to save duplicated diagram: {}", e);
+        }
+
+        info!(
+            "Duplicated diagram {} as {} ({} elements)",
+            diagram_id,
+            new_id,
+            id_map.len()
+        );
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "diagramId": new_id,
+                "sourceDiagramId": diagram_id,
+                "name": name,
+                "idMap": id_map,
+            }))?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn extract_subgraph(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let node_ids: Vec<String> = args["nodeIds"]
+            .as_array()
+            .ok_or_else(|| GlspError::ToolExecution("Missing nodeIds array".to_string()))?
+            .iter()
+            .filter_map(|id| id.as_str().map(str::to_string))
+            .collect();
+        let name = args["newDiagramName"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing newDiagramName".to_string()))?;
+
+        let mut models = self.models.lock().await;
+        let source = models
+            .get_mut(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution(format!("Diagram not found: {diagram_id}")))?;
+        let extracted = guarded_change(source, |source| extract_subgraph(source, &node_ids, name))
+            .map_err(|cycle| GlspError::WouldCreateCycle { cycle })?;
+        let extraction = match extracted {
+            Ok(extraction) => extraction,
+            Err(message) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(message)],
+                    is_error: Some(true),
+                })
+            }
+        };
+        let new_id = extraction.diagram.id.clone();
+        models.insert(new_id.clone(), extraction.diagram);
+        drop(models); // Release the lock before saving
+
+        for id in [new_id.as_str(), diagram_id] {
+            if let Err(e) = self.save_diagram(id).await {
+                error!(
+                    "Failed to save diagram {} after extracting subgraph: {}",
+                    id, e
+                );
+            }
+        }
+
+        info!(
+            "Extracted {} elements of diagram {} into {}",
+            extraction.moved.len(),
+            diagram_id,
+            new_id
+        );
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "diagramId": new_id,
+                "sourceDiagramId": diagram_id,
+                "name": name,
+                "referenceNodeId": extraction.reference_id,
+                "movedElements": extraction.moved,
+                "reconnectedEdges": extraction.reconnected,
+            }))?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn get_diagram_type_capabilities(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let diagram_type = args.as_ref().and_then(|args| args["diagramType"].as_str());
+
+        let result = match diagram_type {
+            None => json!({
+                "diagramTypes": DIAGRAM_TYPES.iter().map(|d| d.diagram_type).collect::<Vec<_>>(),
+            }),
+            Some(diagram_type) => match diagram_type_spec(diagram_type) {
+                Some(spec) => spec.to_json(),
+                None => json!({
+                    "diagramType": diagram_type,
+                    "declared": false,
+                    "message": "No declaration for this diagram type; any node or edge type is accepted",
+                }),
+            },
+        };
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn create_node(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let node_type = args["nodeType"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing nodeType".to_string()))?;
+
+        let requested_position = if args["position"].is_null() {
+            None
+        } else {
+            Some(Position {
+                x: args["position"]["x"]
+                    .as_f64()
+                    .ok_or_else(|| GlspError::ToolExecution("Missing position.x".to_string()))?,
+                y: args["position"]["y"]
+                    .as_f64()
+                    .ok_or_else(|| GlspError::ToolExecution("Missing position.y".to_string()))?,
+            })
+        };
+
+        let label = args["label"].as_str().map(|s| s.to_string());
+
+        if let Some(Err(message)) = args["properties"].as_object().map(check_members) {
+            return Ok(CallToolResult {
+                content: vec![Content::text(message)],
+                is_error: Some(true),
+            });
+        }
+        let limits = self.label_limits();
+        if let Some(label) = &label {
+            limits.check_label(node_type, label)?;
+        }
+        if let Some(properties) = args["properties"].as_object() {
+            limits.check_members(properties)?;
+        }
+
+        let mut models = self.models.lock().await;
+        let diagram = models
+            .get_mut(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+
+        if let Some(spec) = diagram_type_spec(&diagram.diagram_type) {
+            if let Err(message) = spec.check_node_type(node_type) {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(message)],
+                    is_error: Some(true),
+                });
+            }
+        }
+
+        let position = match requested_position {
+            Some(position) => position,
+            None => {
+                let strategy = self
+                    .config
+                    .placement_strategy
+                    .parse::<PlacementStrategy>()
+                    .unwrap_or_else(|e| {
+                        warn!("{}; using next-free-slot", e);
+                        PlacementStrategy::NextFreeSlot
+                    });
+                default_position(diagram, strategy)
+            }
+        };
+        let position = snap_position(position, self.config.coordinate_grid);
+        let (x, y) = (position.x, position.y);
+
+        let mut node = Node::new(node_type, position, label);
+        node.base.id = IdPrefixes::parse(&self.config.id_prefix_diagram_types)
+            .generate(&diagram.diagram_type, node_type);
+        let node_id = node.base.id.clone();
+        node.base.created_by = authenticated_client();
+
+        // Add custom properties if provided
+        if let Some(properties) = args["properties"].as_object() {
+            for (key, value) in properties {
+                node.base.properties.insert(key.clone(), value.clone());
+            }
+        }
+
+        diagram.add_element(node.base);
+        diagram.add_child_to_root(&node_id);
+        history::touched(diagram_id, [&node_id]);
+        drop(models); // Release the lock before saving
+
+        // Save to disk
+        if let Err(e) = self.save_diagram(diagram_id).await {
+            error!("Failed to save diagram after creating node: {}", e);
+        }
+
+        Ok(CallToolResult {
+            content: vec![
+                Content::text(format!(
+                    "Created {node_type} node with ID: {node_id} at ({x}, {y})"
+                )),
+                Content::text(serde_json::to_string(&json!({
+                    "diagramId": diagram_id,
+                    "nodeId": node_id
+                }))?),
+            ],
+            is_error: Some(false),
+        })
+    }
+
+    async fn detect_cycles(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+
+        let models = self.models.lock().await;
+        let diagram = models
+            .get(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+
+        let cycles = find_cycles(diagram);
+        let undirected_edges = diagram
+            .elements
+            .values()
+            .filter(|e| is_edge(e) && !is_directed(e))
+            .count();
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "diagramId": diagram_id,
+                "hasCycles": !cycles.is_empty(),
+                "cycles": cycles,
+                "ignoredUndirectedEdges": undirected_edges,
+            }))?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn assert_acyclic(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+
+        let models = self.models.lock().await;
+        let diagram = models
+            .get(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+        let cycles = find_cycles(diagram);
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "diagramId": diagram_id,
+                "acyclic": cycles.is_empty(),
+                "mustBeAcyclic": must_be_acyclic(diagram),
+                "cycles": cycles,
+            }))?)],
+            is_error: Some(!cycles.is_empty()),
+        })
+    }
+
+    async fn compare_diagrams(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let left_id = args["leftId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing leftId".to_string()))?;
+        let right_id = args["rightId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing rightId".to_string()))?;
+
+        let models = self.models.lock().await;
+        let diagram = |id: &str| {
+            models
+                .get(id)
+                .ok_or_else(|| GlspError::ToolExecution(format!("Diagram not found: {id}")))
+        };
+        let comparison = compare_diagrams(diagram(left_id)?, diagram(right_id)?);
+        drop(models);
+
+        let mut result = serde_json::to_value(&comparison)?;
+        result["leftId"] = json!(left_id);
+        result["rightId"] = json!(right_id);
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn is_reachable(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let from_id: &str = &Self::element_id_arg(&args, "fromId")?;
+        let to_id: &str = &Self::element_id_arg(&args, "toId")?;
+
+        let models = self.models.lock().await;
+        let diagram = models
+            .get(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+
+        for id in [from_id, to_id] {
+            if !diagram.elements.contains_key(id) {
+                return Err(GlspError::ToolExecution(format!("Element not found: {id}")));
+            }
+        }
+
+        let path = find_path(diagram, from_id, to_id);
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "diagramId": diagram_id,
+                "fromId": from_id,
+                "toId": to_id,
+                "reachable": path.is_some(),
+                "path": path,
+            }))?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn shortest_path(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let from_id: &str = &Self::element_id_arg(&args, "fromId")?;
+        let to_id: &str = &Self::element_id_arg(&args, "toId")?;
+        let weight_property = args["weightProperty"].as_str();
+
+        let models = self.models.lock().await;
+        let diagram = models
+            .get(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+
+        for id in [from_id, to_id] {
+            if !diagram.elements.contains_key(id) {
+                return Err(GlspError::ToolExecution(format!("Element not found: {id}")));
+            }
+        }
+
+        let path = match shortest_path(diagram, from_id, to_id, weight_property) {
+            Ok(path) => path,
+            Err(message) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(message)],
+                    is_error: Some(true),
+                });
+            }
+        };
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "diagramId": diagram_id,
+                "fromId": from_id,
+                "toId": to_id,
+                "weightProperty": weight_property,
+                "reachable": path.is_some(),
+                "nodes": path.as_ref().map(|p| &p.nodes),
+                "edges": path.as_ref().map(|p| &p.edges),
+                "cost": path.as_ref().map(|p| p.cost),
+            }))?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn create_edge(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let edge_type = args["edgeType"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing edgeType".to_string()))?;
+        let source_id: &str = &Self::element_id_arg(&args, "sourceId")?;
+        let target_id: &str = &Self::element_id_arg(&args, "targetId")?;
+
+        let label = args["label"].as_str().map(|s| s.to_string());
+        let directed = args["directed"]
+            .as_bool()
+            .unwrap_or_else(|| default_directed(edge_type));
+
+        let mut models = self.models.lock().await;
+        let diagram = models
+            .get_mut(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+
+        let mut edge_element =
+            match self.new_edge(diagram, edge_type, source_id, target_id, label, directed) {
+                Ok(edge) => edge,
+                Err(message) => {
+                    return Ok(CallToolResult {
+                        content: vec![Content::text(message)],
+                        is_error: Some(true),
+                    })
+                }
+            };
+        if directed {
+            if let Some(cycle) = cycle_closed_by(diagram, &[(source_id, target_id)], &[]) {
+                return Err(GlspError::WouldCreateCycle { cycle });
+            }
+        }
+        edge_element.created_by = authenticated_client();
+        let edge_id = edge_element.id.clone();
+
+        diagram.add_element(edge_element);
+        diagram.add_child_to_root(&edge_id);
+        history::touched(diagram_id, [&edge_id]);
+        drop(models); // Release the lock before saving
+
+        // Save to disk
+        if let Err(e) = self.save_diagram(diagram_id).await {
+            error!("Failed to save diagram after creating edge: {}", e);
+        }
+
+        Ok(CallToolResult {
+            content: vec![
+                Content::text(format!("Created {edge_type} edge with ID: {edge_id}")),
+                Content::text(serde_json::to_string(&json!({
+                    "diagramId": diagram_id,
+                    "edgeId": edge_id
+                }))?),
+            ],
+            is_error: Some(false),
+        })
+    }
+
+    /// Build an edge for `diagram` after checking that both endpoints exist
+    /// and that the diagram type allows the connection
+    fn new_edge(
+        &self,
+        diagram: &DiagramModel,
+        edge_type: &str,
+        source_id: &str,
+        target_id: &str,
+        label: Option<String>,
+        directed: bool,
+    ) -> std::result::Result<ModelElement, String> {
+        // Verify source and target exist
+        if !diagram.elements.contains_key(source_id) {
+            return Err(format!("Source element {source_id} not found"));
+        }
+        if !diagram.elements.contains_key(target_id) {
+            return Err(format!("Target element {target_id} not found"));
+        }
+
+        if let Some(spec) = diagram_type_spec(&diagram.diagram_type) {
+            let source_type = diagram.elements[source_id].element_type.as_str();
+            let target_type = diagram.elements[target_id].element_type.as_str();
+            spec.check_edge(edge_type, source_type, target_type)?;
+        }
+
+        let mut edge = edge_element(edge_type, source_id, target_id, label, directed);
+        edge.id = IdPrefixes::parse(&self.config.id_prefix_diagram_types)
+            .generate(&diagram.diagram_type, EDGE_ID_PREFIX);
+        Ok(edge)
+    }
+
+    async fn create_edges(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let specs = args["edges"]
+            .as_array()
+            .ok_or_else(|| GlspError::ToolExecution("Missing edges array".to_string()))?;
+        let atomic = args["atomic"].as_bool().unwrap_or(false);
+        let created_by = authenticated_client();
+
+        let mut models = self.models.lock().await;
+        let diagram = models
+            .get_mut(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+
+        // Validate every edge before creating any
+        let built: Vec<std::result::Result<ModelElement, String>> = specs
+            .iter()
+            .map(|spec| -> std::result::Result<ModelElement, String> {
+                let edge_type = spec["edgeType"].as_str().ok_or("Missing edgeType")?;
+                let endpoint = |key: &str| -> std::result::Result<String, String> {
+                    let raw = spec[key].as_str().ok_or_else(|| format!("Missing {key}"))?;
+                    normalize_id(raw).map_err(|e| e.to_string())
+                };
+                let (source_id, target_id) = (endpoint("sourceId")?, endpoint("targetId")?);
+                let directed = spec["directed"]
+                    .as_bool()
+                    .unwrap_or_else(|| default_directed(edge_type));
+                let mut edge = self.new_edge(
+                    diagram,
+                    edge_type,
+                    &source_id,
+                    &target_id,
+                    spec["label"].as_str().map(str::to_string),
+                    directed,
+                )?;
+                edge.created_by = created_by.clone();
+                Ok(edge)
+            })
+            .collect();
+        // An edge that would close a cycle fails like an invalid one; the
+        // batch's earlier edges count as already added
+        let mut built = built;
+        let mut added: Vec<(String, String)> = Vec::new();
+        for edge in built.iter_mut() {
+            let Ok(candidate) = &*edge else { continue };
+            let (Some(source), Some(target)) = (&candidate.source_id, &candidate.target_id) else {
+                continue;
+            };
+            if !is_directed(candidate) {
+                continue;
+            }
+            added.push((source.clone(), target.clone()));
+            let pairs: Vec<(&str, &str)> = added
+                .iter()
+                .map(|(source, target)| (source.as_str(), target.as_str()))
+                .collect();
+            if let Some(cycle) = cycle_closed_by(diagram, &pairs, &[]) {
+                added.pop();
+                *edge = Err(format!(
+                    "WouldCreateCycle: the diagram must stay acyclic and the edge would create the cycle {}",
+                    cycle.join(" -> ")
+                ));
+            }
+        }
+        let errors: Vec<serde_json::Value> = built
+            .iter()
+            .enumerate()
+            .filter_map(|(index, edge)| edge.as_ref().err().map(|e| (index, e)))
+            .map(|(index, message)| json!({"index": index, "message": message}))
+            .collect();
+
+        if atomic && !errors.is_empty() {
+            let result = json!({
+                "diagramId": diagram_id,
+                "created": 0,
+                "errors": errors,
+            });
+            return Ok(CallToolResult {
+                content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
+                is_error: Some(true),
+            });
+        }
+
+        // In request order, null where the edge failed
+        let edge_ids: Vec<Option<String>> = built
+            .iter()
+            .map(|edge| edge.as_ref().ok().map(|edge| edge.id.clone()))
+            .collect();
+        let created = diagram.add_root_elements(built.into_iter().flatten());
+        history::touched(diagram_id, edge_ids.iter().flatten());
+        drop(models); // Release the lock before saving
+
+        if created > 0 {
+            if let Err(e) = self.save_diagram(diagram_id).await {
+                error!("Failed to save diagram after creating edges: {}", e);
+            }
+        }
+
+        let result = json!({
+            "diagramId": diagram_id,
+            "created": created,
+            "edgeIds": edge_ids,
+            "errors": errors,
+        });
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn suggest_targets(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let edge_type = args["edgeType"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing edgeType".to_string()))?;
+        let source_id: &str = &Self::element_id_arg(&args, "sourceId")?;
+
+        let models = self.models.lock().await;
+        let diagram = models
+            .get(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+        let targets = match suggest_targets(diagram, source_id, edge_type) {
+            Ok(targets) => targets,
+            Err(message) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(message)],
+                    is_error: Some(true),
+                })
+            }
+        };
+        drop(models);
+
+        let result = json!({
+            "diagramId": diagram_id,
+            "sourceId": source_id,
+            "edgeType": edge_type,
+            "count": targets.len(),
+            "targetIds": targets,
+        });
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn create_hyperedge(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let id_list = |key: &str| -> std::result::Result<Vec<String>, GlspError> {
+            args[key]
+                .as_array()
+                .ok_or_else(|| GlspError::ToolExecution(format!("Missing {key} array")))?
+                .iter()
+                .filter_map(|id| id.as_str())
+                .map(|id| Ok(normalize_id(id)?))
+                .collect()
+        };
+        let sources = id_list("sources")?;
+        let targets = id_list("targets")?;
+        let label = args["label"].as_str().map(str::to_string);
+        let directed = args["directed"]
+            .as_bool()
+            .unwrap_or_else(|| default_directed(HYPEREDGE_TYPE));
+
+        let mut models = self.models.lock().await;
+        let diagram = models
+            .get_mut(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+
+        if directed {
+            let pairs: Vec<(&str, &str)> = sources
+                .iter()
+                .flat_map(|source| {
+                    targets
+                        .iter()
+                        .map(move |target| (source.as_str(), target.as_str()))
+                })
+                .collect();
+            if let Some(cycle) = cycle_closed_by(diagram, &pairs, &[]) {
+                return Err(GlspError::WouldCreateCycle { cycle });
+            }
+        }
+
+        let hyperedge_id = match create_hyperedge(diagram, &sources, &targets, label) {
+            Ok(id) => id,
+            Err(message) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(message)],
+                    is_error: Some(true),
+                })
+            }
+        };
+        if let Some(element) = diagram.elements.get_mut(&hyperedge_id) {
+            element.created_by = authenticated_client();
+            element
+                .properties
+                .insert(DIRECTED_PROPERTY.to_string(), json!(directed));
+        }
+        diagram.add_child_to_root(&hyperedge_id);
+        history::touched(diagram_id, [&hyperedge_id]);
+        drop(models); // Release the lock before saving
+
+        // Save to disk
+        if let Err(e) = self.save_diagram(diagram_id).await {
+            error!("Failed to save diagram after creating hyperedge: {}", e);
+        }
+
+        Ok(CallToolResult {
+            content: vec![Content::text(format!(
+                "Created hyperedge with ID: {hyperedge_id}"
+            ))],
+            is_error: Some(false),
+        })
+    }
+
+    async fn reconnect_edge(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let edge_id: &str = &Self::element_id_arg(&args, "edgeId")?;
+        let source_id = match args.get("sourceId") {
+            Some(_) => Some(Self::element_id_arg(&args, "sourceId")?),
+            None => None,
+        };
+        let target_id = match args.get("targetId") {
+            Some(_) => Some(Self::element_id_arg(&args, "targetId")?),
+            None => None,
+        };
+
+        let mut models = self.models.lock().await;
+        let diagram = models
+            .get_mut(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+
+        if let Some(edge) = diagram.elements.get(edge_id).filter(|e| is_directed(e)) {
+            let source = source_id.as_deref().or(edge.source_id.as_deref());
+            let target = target_id.as_deref().or(edge.target_id.as_deref());
+            if let (Some(source), Some(target)) = (source, target) {
+                if let Some(cycle) = cycle_closed_by(diagram, &[(source, target)], &[edge_id]) {
+                    return Err(GlspError::WouldCreateCycle { cycle });
+                }
+            }
+        }
+
+        let edge =
+            match reconnect_edge(diagram, edge_id, source_id.as_deref(), target_id.as_deref()) {
+                Ok(edge) => edge.clone(),
+                Err(message) => {
+                    return Ok(CallToolResult {
+                        content: vec![Content::text(message)],
+                        is_error: Some(true),
+                    })
+                }
+            };
+        history::touched(diagram_id, [edge_id]);
+        drop(models); // Release the lock before saving
+
+        // Save to disk
+        if let Err(e) = self.save_diagram(diagram_id).await {
+            error!("Failed to save diagram after reconnecting edge: {}", e);
+        }
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "diagramId": diagram_id,
+                "edge": edge,
+            }))?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn reverse_edge(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let edge_id: &str = &Self::element_id_arg(&args, "edgeId")?;
+
+        let mut models = self.models.lock().await;
+        let diagram = models
+            .get_mut(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+
+        // The reversed edge must still be a connection the diagram type allows
+        if let (Some(spec), Some(edge)) = (
+            diagram_type_spec(&diagram.diagram_type),
+            diagram.elements.get(edge_id),
+        ) {
+            let endpoint_type = |id: &Option<String>| {
+                id.as_ref()
+                    .and_then(|id| diagram.elements.get(id))
+                    .map(|e| e.element_type.as_str())
+            };
+            if let (Some(source_type), Some(target_type)) = (
+                endpoint_type(&edge.target_id),
+                endpoint_type(&edge.source_id),
+            ) {
+                if let Err(message) =
+                    spec.check_edge(edge.element_type.as_str(), source_type, target_type)
+                {
+                    return Ok(CallToolResult {
+                        content: vec![Content::text(message)],
+                        is_error: Some(true),
+                    });
+                }
+            }
+        }
+
+        if let Some(edge) = diagram.elements.get(edge_id).filter(|e| is_directed(e)) {
+            let reversed: Vec<(&str, &str)> = connections(edge)
+                .into_iter()
+                .map(|(source, target)| (target, source))
+                .collect();
+            if let Some(cycle) = cycle_closed_by(diagram, &reversed, &[edge_id]) {
+                return Err(GlspError::WouldCreateCycle { cycle });
+            }
+        }
+
+        let edge = match reverse_edge(diagram, edge_id) {
+            Ok(edge) => edge.clone(),
+            Err(message) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(message)],
+                    is_error: Some(true),
+                })
+            }
+        };
+        history::touched(diagram_id, [edge_id]);
+        drop(models); // Release the lock before saving
+
+        // Save to disk
+        if let Err(e) = self.save_diagram(diagram_id).await {
+            error!("Failed to save diagram after reversing edge: {}", e);
+        }
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "diagramId": diagram_id,
+                "edge": edge,
+            }))?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn delete_element(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let element_id: &str = &Self::element_id_arg(&args, "elementId")?;
+
+        let mut models = self.models.lock().await;
+        let diagram = models
+            .get_mut(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+
+        // Edges attached to a deleted node go with it
+        let cascaded = incident_edges(diagram, element_id);
+        match diagram.remove_element(element_id) {
+            Some(_) => {
+                for edge_id in &cascaded {
+                    diagram.remove_element(edge_id);
+                }
+                history::touched(
+                    diagram_id,
+                    std::iter::once(element_id).chain(cascaded.iter().map(String::as_str)),
+                );
+                drop(models); // Release the lock before saving
+                let mut locks = self.locks.lock().await;
+                locks.clear_element(diagram_id, element_id);
+                for edge_id in &cascaded {
+                    locks.clear_element(diagram_id, edge_id);
+                }
+                drop(locks);
+
+                // Save to disk
+                if let Err(e) = self.save_diagram(diagram_id).await {
+                    error!("Failed to save diagram after deleting element: {}", e);
+                }
+
+                let mut content = vec![Content::text(format!(
+                    "Deleted element with ID: {element_id}"
+                ))];
+                if !cascaded.is_empty() {
+                    content.push(Content::text(json!({"deletedEdges": cascaded}).to_string()));
+                }
+                Ok(CallToolResult {
+                    content,
+                    is_error: Some(false),
+                })
+            }
+            None => Ok(CallToolResult {
+                content: vec![Content::text(format!("Element {element_id} not found"))],
+                is_error: Some(true),
+            }),
+        }
+    }
+
+    async fn update_element(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let element_id: &str = &Self::element_id_arg(&args, "elementId")?;
+
+        if let Some(Err(message)) = args["properties"].as_object().map(check_members) {
+            return Ok(CallToolResult {
+                content: vec![Content::text(message)],
+                is_error: Some(true),
+            });
+        }
+        if let Some(properties) = args["properties"].as_object() {
+            self.label_limits().check_members(properties)?;
+        }
+
+        let mut models = self.models.lock().await;
+        let diagram = models
+            .get_mut(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+
+        // Giving an undirected edge a direction may close a cycle
+        if args["properties"][DIRECTED_PROPERTY].as_bool() == Some(true) {
+            if let Some(edge) = diagram.elements.get(element_id).filter(|e| !is_directed(e)) {
+                let pairs = connections(edge);
+                if let Some(cycle) = cycle_closed_by(diagram, &pairs, &[element_id]) {
+                    return Err(GlspError::WouldCreateCycle { cycle });
+                }
+            }
+        }
+
+        let element = diagram
+            .get_element_mut(element_id)
+            .ok_or_else(|| GlspError::ToolExecution("Element not found".to_string()))?;
+
+        if let Some(properties) = args["properties"].as_object() {
+            for (key, value) in properties {
+                element.properties.insert(key.clone(), value.clone());
+            }
+        }
+
+        let mut moved_to = None;
+        if let Some(position) = args["position"].as_object() {
+            if let (Some(x), Some(y)) = (position["x"].as_f64(), position["y"].as_f64()) {
+                let snapped = snap_position(Position { x, y }, self.config.coordinate_grid);
+                if let Some(bounds) = &mut element.bounds {
+                    bounds.x = snapped.x;
+                    bounds.y = snapped.y;
+                    moved_to = Some(snapped);
+                }
+            }
+        }
+
+        element.touch();
+        diagram.updated_at = chrono::Utc::now();
+        history::touched(diagram_id, [element_id]);
+        drop(models); // Release the lock before saving
+
+        // Save to disk
+        if let Err(e) = self.save_diagram(diagram_id).await {
+            error!("Failed to save diagram after updating element: {}", e);
+        }
+
+        let message = match moved_to {
+            Some(Position { x, y }) => {
+                format!("Updated element with ID: {element_id} at ({x}, {y})")
+            }
+            None => format!("Updated element with ID: {element_id}"),
+        };
+        Ok(CallToolResult {
+            content: vec![Content::text(message)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn apply_layout(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let algorithm = args["algorithm"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing algorithm".to_string()))?;
+        let seed = match &args["seed"] {
+            serde_json::Value::Null => None,
+            value => Some(value.as_u64().ok_or_else(|| {
+                GlspError::ToolExecution("seed must be a non-negative integer".to_string())
+            })?),
+        };
+
+        if !["grid", "hierarchical", "force"].contains(&algorithm) {
+            return Ok(CallToolResult {
+                content: vec![Content::text(format!(
+                    "Layout algorithm '{algorithm}' not implemented yet"
+                ))],
+                is_error: Some(true),
+            });
+        }
+
+        // Lay out a copy on the CPU pool so other calls can use the models
+        // meanwhile, then move the elements to where the copy put them
+        let snapshot = self
+            .models
+            .lock()
+            .await
+            .get(diagram_id)
+            .cloned()
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+        progress::report(
+            0.0,
+            Some(2.0),
+            &format!(
+                "Applying {algorithm} layout to {} elements",
+                snapshot.elements.len()
+            ),
+        );
+
+        let used_seed = (algorithm == "force")
+            .then(|| seed.unwrap_or_else(|| uuid::Uuid::new_v4().as_u64_pair().0));
+        let job_algorithm = algorithm.to_string();
+        let revision_before = snapshot.revision;
+        let laid_out = self
+            .cpu_pool
+            .run(move || {
+                let mut diagram = snapshot;
+                match (job_algorithm.as_str(), used_seed) {
+                    ("grid", _) => Self::apply_grid_layout(&mut diagram),
+                    ("hierarchical", _) => Self::apply_hierarchical_layout(&mut diagram),
+                    (_, Some(seed)) => {
+                        apply_force_layout(&mut diagram, seed);
+                    }
+                    _ => {}
+                }
+                diagram
+            })
+            .await
+            .map_err(|e| GlspError::ToolExecution(e.to_string()))?;
+
+        let mut models = self.models.lock().await;
+        let diagram = models
+            .get_mut(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+        for (id, element) in diagram.elements.iter_mut() {
+            if let Some(bounds) = laid_out.elements.get(id).and_then(|e| e.bounds.clone()) {
+                element.bounds = Some(bounds);
+            }
+        }
+        diagram.revision += laid_out.revision.saturating_sub(revision_before);
+
+        drop(models); // Release the lock before saving
+        progress::report(1.0, Some(2.0), "Saving layout");
+
+        // Save to disk
+        if let Err(e) = self.save_diagram(diagram_id).await {
+            error!("Failed to save diagram after applying layout: {}", e);
+        }
+        progress::report(2.0, Some(2.0), "Layout saved");
+
+        let message = match used_seed {
+            Some(seed) => {
+                format!("Applied {algorithm} layout to diagram {diagram_id} (seed {seed})")
+            }
+            None => format!("Applied {algorithm} layout to diagram {diagram_id}"),
+        };
+        Ok(CallToolResult {
+            content: vec![Content::text(message)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn compute_layout_hints(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let algorithm = args["algorithm"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing algorithm".to_string()))?;
+
+        let diagram = self
+            .models
+            .lock()
+            .await
+            .get(diagram_id)
+            .cloned()
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+        let revision = diagram.revision;
+        let job_algorithm = algorithm.to_string();
+        let hints = match self
+            .cpu_pool
+            .run(move || layout_hints(&diagram, &job_algorithm))
+            .await
+            .map_err(|e| GlspError::ToolExecution(e.to_string()))?
+        {
+            Ok(hints) => hints,
+            Err(message) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(message)],
+                    is_error: Some(true),
+                })
+            }
+        };
+
+        let result = json!({
+            "diagramId": diagram_id,
+            "algorithm": algorithm,
+            "revision": revision,
+            "rankCount": hints.last().map_or(0, |hint| hint.rank + 1),
+            "hints": hints,
+        });
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn get_content_bounds(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let margin = args["margin"].as_f64().unwrap_or(CANVAS_MARGIN);
+        if !margin.is_finite() || margin < 0.0 {
+            return Ok(CallToolResult {
+                content: vec![Content::text("margin must not be negative".to_string())],
+                is_error: Some(true),
+            });
+        }
+
+        let models = self.models.lock().await;
+        let diagram = models
+            .get(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+        let extent = content_extent(diagram);
+        drop(models);
+
+        let result = match extent {
+            Some(extent) => json!({
+                "diagramId": diagram_id,
+                "empty": false,
+                "minX": extent.x,
+                "minY": extent.y,
+                "maxX": extent.x + extent.width,
+                "maxY": extent.y + extent.height,
+                "width": extent.width,
+                "height": extent.height,
+                "canvas": fit_to_content(&extent, margin),
+            }),
+            None => json!({
+                "diagramId": diagram_id,
+                "empty": true,
+            }),
+        };
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn normalize_coordinates(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let origin_x = args["originX"].as_f64().unwrap_or(0.0);
+        let origin_y = args["originY"].as_f64().unwrap_or(0.0);
+        let margin = args["margin"].as_f64().unwrap_or(0.0);
+        if margin < 0.0 {
+            return Ok(CallToolResult {
+                content: vec![Content::text("margin must not be negative".to_string())],
+                is_error: Some(true),
+            });
+        }
+
+        let mut models = self.models.lock().await;
+        let diagram = models
+            .get_mut(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+        let origin = Position {
+            x: origin_x + margin,
+            y: origin_y + margin,
+        };
+        let Some(translation) = normalize_coordinates(diagram, origin) else {
+            return Ok(CallToolResult {
+                content: vec![Content::text(format!(
+                    "Diagram {diagram_id} has no positioned nodes to normalize"
+                ))],
+                is_error: Some(true),
+            });
+        };
+        let moved = translation.x != 0.0 || translation.y != 0.0;
+        let bounds = content_bounds(diagram);
+        drop(models); // Release the lock before saving
+
+        if moved {
+            if let Err(e) = self.save_diagram(diagram_id).await {
+                error!(
+                    "Failed to save diagram after normalizing coordinates: {}",
+                    e
+                );
+            }
+        }
+
+        let result = json!({
+            "diagramId": diagram_id,
+            "translation": {"x": translation.x, "y": translation.y},
+            "contentBounds": bounds,
+        });
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn export_diagram(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let format = match DiagramFormat::resolve(args["format"].as_str(), args["accept"].as_str())
+        {
+            Ok(format) => format,
+            Err(message) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(message)],
+                    is_error: Some(true),
+                })
+            }
+        };
+
+        let rendered = self.render_diagram(diagram_id, format).await?;
+        let content = match format {
+            DiagramFormat::Png => {
+                Content::image(BASE64_STANDARD.encode(&rendered), format.media_type())
+            }
+            _ => {
+                let name = match format {
+                    DiagramFormat::Json => "JSON",
+                    DiagramFormat::Svg => "SVG",
+                    DiagramFormat::PlantUml => "PlantUML",
+                    _ => "Mermaid",
+                };
+                Content::text(format!(
+                    "Exported diagram as {name}:\n{}",
+                    String::from_utf8_lossy(&rendered)
+                ))
+            }
+        };
+        Ok(CallToolResult {
+            content: vec![content],
+            is_error: Some(false),
+        })
+    }
+
+    /// Render a diagram in an export format; text formats are UTF-8
+    pub async fn render_diagram(
+        &self,
+        diagram_id: &str,
+        format: DiagramFormat,
+    ) -> std::result::Result<Vec<u8>, GlspError> {
+        let models = self.models.lock().await;
+        let diagram = models
+            .get(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+
+        Ok(match format {
+            DiagramFormat::Json => serde_json::to_string_pretty(diagram)
+                .map_err(|e| GlspError::ToolExecution(format!("JSON serialization failed: {e}")))?
+                .into_bytes(),
+            DiagramFormat::Svg => Self::generate_svg(diagram).into_bytes(),
+            DiagramFormat::Png => crate::operations::render_png(diagram),
+            DiagramFormat::PlantUml => crate::operations::to_plantuml(diagram).into_bytes(),
+            DiagramFormat::Mermaid => crate::operations::to_mermaid(diagram).into_bytes(),
+        })
+    }
+
+    // Selection tool implementations - simplified for now
+    async fn select_elements(
+        &self,
+        _args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        Ok(CallToolResult {
+            content: vec![Content::text("Selection functionality not yet implemented")],
+            is_error: Some(false),
+        })
+    }
+
+    async fn select_all(
+        &self,
+        _args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        Ok(CallToolResult {
+            content: vec![Content::text(
+                "Select all functionality not yet implemented",
+            )],
+            is_error: Some(false),
+        })
+    }
+
+    async fn clear_selection(
+        &self,
+        _args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        Ok(CallToolResult {
+            content: vec![Content::text(
+                "Clear selection functionality not yet implemented",
+            )],
+            is_error: Some(false),
+        })
+    }
+
+    async fn get_selection(
+        &self,
+        _args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        Ok(CallToolResult {
+            content: vec![Content::text(
+                "Get selection functionality not yet implemented",
+            )],
+            is_error: Some(false),
+        })
+    }
+
+    // WASM component implementations
+    async fn scan_wasm_components(&self) -> std::result::Result<CallToolResult, GlspError> {
+        // Trigger rescan and get components from the wasm watcher
+        let wasm_watcher = self.wasm_watcher.lock().await;
+        let components = wasm_watcher.get_components();
+        let available = components.iter().filter(|c| c.file_exists).count();
+        let missing = components.len() - available;
+
+        // Convert components to JSON format expected by the client
+        let components_json: Vec<_> = components
+            .iter()
+            .map(|c| {
+                json!({
+                    "name": c.name,
+                    "path": c.path,
+                    "fileExists": c.file_exists,
+                    "lastSeen": c.last_seen.map(|dt| dt.to_rfc3339()),
+                    "metadata": c.metadata,
+                    "interfaces": c.interfaces
+                })
+            })
+            .collect();
+
+        let result = json!({
+            "components": components_json,
+            "summary": {
+                "total": components.len(),
+                "available": available,
+                "missing": missing
+            }
+        });
+
+        Ok(CallToolResult {
+            content: vec![Content::text(result.to_string())],
+            is_error: Some(false),
+        })
+    }
+
+    async fn check_wasm_component_status(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let component_name = args["componentName"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing componentName".to_string()))?;
+
+        let wasm_watcher = self.wasm_watcher.lock().await;
+
+        match wasm_watcher.get_component(component_name) {
+            Some(component) => {
+                let status = json!({
+                    "name": component.name,
+                    "path": component.path,
+                    "fileExists": component.file_exists,
+                    "status": if component.file_exists { "available" } else { "missing" },
+                    "lastSeen": component.last_seen,
+                    "removedAt": component.removed_at,
+                    "interfaces": component.interfaces.len(),
+                    "description": component.description
+                });
+
+                Ok(CallToolResult {
+                    content: vec![Content::text(
+                        serde_json::to_string_pretty(&status).map_err(|e| {
+                            GlspError::ToolExecution(format!("JSON serialization failed: {e}"))
+                        })?,
+                    )],
+                    is_error: Some(false),
+                })
+            }
+            None => Ok(CallToolResult {
+                content: vec![Content::text(format!(
+                    "WASM component '{component_name}' not found"
+                ))],
+                is_error: Some(true),
+            }),
+        }
+    }
+
+    async fn get_component_dependency_graph(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let render_diagram = args
+            .as_ref()
+            .and_then(|a| a["renderDiagram"].as_bool())
+            .unwrap_or(false);
+
+        let wasm_watcher = self.wasm_watcher.lock().await;
+        let graph = build_dependency_graph(&wasm_watcher.get_components());
+        drop(wasm_watcher);
+
+        let mut result = serde_json::to_value(&graph)?;
+        if render_diagram {
+            let diagram = crate::operations::diagram_from_dependency_graph(&graph);
+            let diagram_id = diagram.id.clone();
+            self.models.lock().await.insert(diagram_id.clone(), diagram);
+
+            if let Err(e) = self.save_diagram(&diagram_id).await {
+                error!("Failed to save dependency graph diagram: {}", e);
+            }
+            result["diagramId"] = json!(diagram_id);
+        }
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn find_components_by_interface(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let interface_name = args["interfaceName"]
+            .as_str()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| GlspError::ToolExecution("Missing interfaceName".to_string()))?;
+        let direction = match args["direction"].as_str().unwrap_or("both") {
+            "both" => None,
+            direction @ ("export" | "import") => Some(direction),
+            other => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(format!(
+                        "Unknown direction '{other}' (expected export, import or both)"
+                    ))],
+                    is_error: Some(true),
+                })
+            }
+        };
+
+        let wasm_watcher = self.wasm_watcher.lock().await;
+        let matches =
+            find_components_by_interface(&wasm_watcher.get_components(), interface_name, direction);
+        drop(wasm_watcher);
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "interfaceName": interface_name,
+                "count": matches.len(),
+                "matches": matches,
+            }))?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn get_component_status(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let component_name = args
+            .as_ref()
+            .and_then(|a| a["componentName"].as_str())
+            .map(str::to_string);
+
+        let wasm_watcher = self.wasm_watcher.lock().await;
+        let summary = wasm_watcher.get_load_summary();
+        let mut profiling = wasm_watcher.get_profile_stats();
+        drop(wasm_watcher);
+
+        let status = match component_name {
+            Some(name) => match summary.results.iter().find(|r| r.name == name) {
+                Some(result) => {
+                    let mut status = serde_json::to_value(result)?;
+                    status["profiling"] = serde_json::to_value(profiling.remove(&name))?;
+                    if let Some(check) =
+                        summary.instantiation_checks.iter().find(|c| c.name == name)
+                    {
+                        status["instantiation"] = serde_json::to_value(check)?;
+                    }
+                    status
+                }
+                None => {
+                    return Ok(CallToolResult {
+                        content: vec![Content::text(format!(
+                            "No load result recorded for component '{name}'"
+                        ))],
+                        is_error: Some(true),
+                    })
+                }
+            },
+            None => {
+                let mut status = serde_json::to_value(&summary)?;
+                status["profiling"] = serde_json::to_value(&profiling)?;
+                status
+            }
+        };
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&status)?)],
+            is_error: Some(false),
+        })
+    }
+
+    /// Label length limits from the configuration
+    fn label_limits(&self) -> LabelLimits {
+        LabelLimits {
+            label: self.config.max_label_length,
+            class_name: self.config.max_class_name_length,
+            attribute_name: self.config.max_attribute_name_length,
+            method_signature: self.config.max_method_signature_length,
+        }
+    }
+
+    /// Read a client-supplied element ID argument and normalize it to canonical form
+    fn element_id_arg(
+        args: &serde_json::Value,
+        key: &str,
+    ) -> std::result::Result<String, GlspError> {
+        let raw = args[key]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution(format!("Missing {key}")))?;
+        Ok(normalize_id(raw)?)
+    }
+
+    async fn execute_component(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let component_name = args["componentName"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing componentName".to_string()))?;
+        let method = args["method"].as_str().unwrap_or("main");
+        let timeout_ms = args["timeoutMs"].as_u64().unwrap_or(30000);
+        let max_memory_mb = args["maxMemoryMb"].as_u64().unwrap_or(64) as u32;
+        let profile = args["profile"].as_bool().unwrap_or(false);
+        let no_cache = args["noCache"].as_bool().unwrap_or(false);
+        let trace = args["trace"].as_bool().unwrap_or(false);
+        let method_args = args.get("args").cloned().unwrap_or(json!({}));
+
+        let wasm_watcher = self.wasm_watcher.lock().await;
+        let execution_id = wasm_watcher
+            .execute_component(
+                component_name,
+                method,
+                method_args,
+                timeout_ms,
+                max_memory_mb,
+                profile,
+                no_cache,
+                trace,
+            )
+            .await
+            .map_err(|e| GlspError::ToolExecution(format!("Failed to execute component: {e}")))?;
+        let cached = wasm_watcher
+            .get_execution_result(&execution_id)
+            .is_some_and(|result| result.cached);
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "executionId": execution_id,
+                "componentName": component_name,
+                "method": method,
+                "profile": profile,
+                "trace": trace,
+                "cached": cached
+            }))?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn cancel_execution(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let execution_id = args["executionId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing executionId".to_string()))?;
+
+        let outcome = self
+            .wasm_watcher
+            .lock()
+            .await
+            .cancel_execution(execution_id);
+        if outcome == CancelOutcome::NotFound {
+            return Ok(CallToolResult {
+                content: vec![Content::text(format!(
+                    "Execution '{execution_id}' not found"
+                ))],
+                is_error: Some(true),
+            });
+        }
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "executionId": execution_id,
+                "outcome": outcome,
+            }))?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn get_execution_result(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let execution_id = args["executionId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing executionId".to_string()))?;
+
+        let wasm_watcher = self.wasm_watcher.lock().await;
+        let status = match wasm_watcher.get_execution_result(execution_id) {
+            Some(result) => json!({ "status": "completed", "result": result }),
+            None => match wasm_watcher.get_execution_progress(execution_id) {
+                Some(progress) => json!({ "status": "running", "progress": progress }),
+                None => {
+                    return Ok(CallToolResult {
+                        content: vec![Content::text(format!(
+                            "Execution '{execution_id}' not found"
+                        ))],
+                        is_error: Some(true),
+                    })
+                }
+            },
+        };
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&status)?)],
+            is_error: Some(false),
+        })
+    }
+
+    /// Reject mutating tool calls on diagrams, or on elements named in the
+    /// arguments, locked by another client.
+    ///
+    /// Callers identify themselves with an optional `clientId` argument; calls
+    /// without one are treated as anonymous and are rejected while any lock is held.
+    async fn check_diagram_lock(
+        &self,
+        tool_name: &str,
+        args: Option<&serde_json::Value>,
+    ) -> std::result::Result<(), GlspError> {
+        let Some(args) = args else {
+            return Ok(());
+        };
+        let client_id = args["clientId"].as_str();
+        let modified = self.changed_diagrams(tool_name, Some(args)).await;
+
+        if !READ_ONLY_EXEMPT_TOOLS.contains(&tool_name) {
+            for diagram_id in &modified {
+                self.check_writable(diagram_id).await?;
+            }
+        }
+
+        // Deleting a node also deletes the edges attached to it
+        let cascaded = match (tool_name, args["diagramId"].as_str()) {
+            ("delete_element", Some(diagram_id)) => {
+                let element_id = args["elementId"]
+                    .as_str()
+                    .and_then(|id| normalize_id(id).ok());
+                let models = self.models.lock().await;
+                match (models.get(diagram_id), element_id) {
+                    (Some(diagram), Some(element_id)) => incident_edges(diagram, &element_id),
+                    _ => Vec::new(),
+                }
+            }
+            _ => Vec::new(),
+        };
+
+        let mut locks = self.locks.lock().await;
+        for diagram_id in &modified {
+            locks
+                .check(diagram_id, client_id)
+                .map_err(|holder| GlspError::DiagramLocked { holder })?;
+        }
+
+        if WHOLE_DIAGRAM_TOOLS.contains(&tool_name) {
+            for diagram_id in &modified {
+                let locked: Vec<String> = locks.element_locks(diagram_id).into_keys().collect();
+                locks
+                    .check_elements(diagram_id, locked.iter().map(String::as_str), client_id)
+                    .map_err(|(element_id, holder)| GlspError::ElementLocked {
+                        element_id,
+                        holder,
+                    })?;
+            }
+            return Ok(());
+        }
+
+        // Element arguments name elements of the `diagramId` diagram
+        let Some(diagram_id) = args["diagramId"].as_str().filter(|_| !modified.is_empty()) else {
+            return Ok(());
+        };
+
+        let single = ELEMENT_ID_ARGS.iter().filter_map(|key| args[*key].as_str());
+        let listed = ELEMENT_IDS_ARGS
+            .iter()
+            .filter_map(|key| args[*key].as_array())
+            .flatten()
+            .filter_map(|id| id.as_str());
+        let element_ids: Vec<String> = single
+            .chain(listed)
+            .filter_map(|id| normalize_id(id).ok())
+            .chain(cascaded)
+            .collect();
+        locks
+            .check_elements(
+                diagram_id,
+                element_ids.iter().map(String::as_str),
+                client_id,
+            )
+            .map_err(|(element_id, holder)| GlspError::ElementLocked { element_id, holder })
+    }
+
+    /// Diagrams a call changes: those [`modified_diagrams`] names and, for a
+    /// `check_integrity` repair of the whole workspace, every writable
+    /// diagram with integrity issues
+    async fn changed_diagrams(&self, tool: &str, args: Option<&serde_json::Value>) -> Vec<String> {
+        let workspace_repair =
+            repairs_integrity(tool, args) && args.is_some_and(|args| args["diagramId"].is_null());
+        if !workspace_repair {
+            return modified_diagrams(tool, args);
+        }
+        let models = self.models.lock().await;
+        let mut diagram_ids: Vec<String> = models
+            .values()
+            .filter(|diagram| !diagram.is_read_only() && !check_integrity(diagram).is_empty())
+            .map(|diagram| diagram.id.clone())
+            .collect();
+        diagram_ids.sort();
+        diagram_ids
+    }
+
+    /// Fail with `DiagramReadOnly` if the diagram is marked read-only
+    async fn check_writable(&self, diagram_id: &str) -> std::result::Result<(), GlspError> {
+        match self.models.lock().await.get(diagram_id) {
+            Some(diagram) if diagram.is_read_only() => Err(GlspError::DiagramReadOnly {
+                diagram_id: diagram_id.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    async fn lock_element(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let element_id: &str = &Self::element_id_arg(&args, "elementId")?;
+        let client_id = args["clientId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing clientId".to_string()))?;
+        let ttl_secs = args["ttlSeconds"].as_u64().unwrap_or(DEFAULT_LOCK_TTL_SECS);
+
+        match self.models.lock().await.get(diagram_id) {
+            None => {
+                return Err(GlspError::ToolExecution(format!(
+                    "Diagram not found: {diagram_id}"
+                )))
+            }
+            Some(diagram) if !diagram.elements.contains_key(element_id) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(format!("Element {element_id} not found"))],
+                    is_error: Some(true),
+                })
+            }
+            Some(_) => {}
+        }
+
+        let mut locks = self.locks.lock().await;
+        locks
+            .check(diagram_id, Some(client_id))
+            .map_err(|holder| GlspError::DiagramLocked { holder })?;
+        let lock = locks
+            .acquire_element(diagram_id, element_id, client_id, ttl_secs)
+            .map_err(|holder| GlspError::ElementLocked {
+                element_id: element_id.to_string(),
+                holder,
+            })?;
+        drop(locks);
+
+        info!(
+            "Client '{client_id}' locked element {element_id} of diagram {diagram_id} until {}",
+            lock.expires_at
+        );
+
+        let result = json!({
+            "diagramId": diagram_id,
+            "elementId": element_id,
+            "holder": lock.holder,
+            "acquiredAt": lock.acquired_at,
+            "expiresAt": lock.expires_at
+        });
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn unlock_element(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let element_id: &str = &Self::element_id_arg(&args, "elementId")?;
+        let client_id = args["clientId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing clientId".to_string()))?;
+
+        let released = self
+            .locks
+            .lock()
+            .await
+            .release_element(diagram_id, element_id, client_id)
+            .map_err(|holder| GlspError::ElementLocked {
+                element_id: element_id.to_string(),
+                holder,
+            })?;
+
+        let message = if released {
+            info!("Client '{client_id}' released lock on element {element_id} of diagram {diagram_id}");
+            format!("Released lock on element {element_id}")
+        } else {
+            format!("Element {element_id} was not locked")
+        };
+
+        Ok(CallToolResult {
+            content: vec![Content::text(message)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn acquire_lock(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let client_id = args["clientId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing clientId".to_string()))?;
+        let ttl_secs = args["ttlSeconds"].as_u64().unwrap_or(DEFAULT_LOCK_TTL_SECS);
+
+        if !self.models.lock().await.contains_key(diagram_id) {
+            return Err(GlspError::ToolExecution(format!(
+                "Diagram not found: {diagram_id}"
+            )));
+        }
+
+        let lock = self
+            .locks
+            .lock()
+            .await
+            .acquire(diagram_id, client_id, ttl_secs)
+            .map_err(|holder| GlspError::DiagramLocked { holder })?;
+
+        info!(
+            "Client '{client_id}' locked diagram {diagram_id} until {}",
+            lock.expires_at
+        );
+
+        let result = json!({
+            "diagramId": diagram_id,
+            "holder": lock.holder,
+            "acquiredAt": lock.acquired_at,
+            "expiresAt": lock.expires_at
+        });
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn release_lock(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let client_id = args["clientId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing clientId".to_string()))?;
+
+        let released = self
+            .locks
+            .lock()
+            .await
+            .release(diagram_id, client_id)
+            .map_err(|holder| GlspError::DiagramLocked { holder })?;
+
+        let message = if released {
+            info!("Client '{client_id}' released lock on diagram {diagram_id}");
+            format!("Released lock on diagram {diagram_id}")
+        } else {
+            format!("Diagram {diagram_id} was not locked")
+        };
+
+        Ok(CallToolResult {
+            content: vec![Content::text(message)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn sensor_stats(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let sensor_id = args["sensorId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing sensorId".to_string()))?;
+        let (start, end) = match parse_time_range(&args["startTime"], &args["endTime"]) {
+            Ok(range) => range,
+            Err(e) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(e.to_string())],
+                    is_error: Some(true),
+                })
+            }
+        };
+
+        let database_manager = self
+            .database_manager
+            .as_ref()
+            .ok_or_else(|| GlspError::ToolExecution("Database not enabled".to_string()))?;
+        let database = database_manager.backend().await;
+        let result = database
+            .read()
+            .await
+            .sensor_stats(sensor_id, start, end)
+            .await;
+
+        match result {
+            Ok(stats) => Ok(CallToolResult {
+                content: vec![Content::text(serde_json::to_string_pretty(&stats)?)],
+                is_error: Some(false),
+            }),
+            Err(e) => Ok(CallToolResult {
+                content: vec![Content::text(format!("Failed to get sensor stats: {e}"))],
+                is_error: Some(true),
+            }),
+        }
+    }
+
+    async fn detect_gaps(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let sensor_id = args["sensorId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing sensorId".to_string()))?;
+        let (start, end) = match parse_time_range(&args["startTime"], &args["endTime"]) {
+            Ok(range) => range,
+            Err(e) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(e.to_string())],
+                    is_error: Some(true),
+                })
+            }
+        };
+        let interval_ms = args["expectedIntervalMs"]
+            .as_f64()
+            .ok_or_else(|| GlspError::ToolExecution("Missing expectedIntervalMs".to_string()))?;
+
+        let database_manager = self
+            .database_manager
+            .as_ref()
+            .ok_or_else(|| GlspError::ToolExecution("Database not enabled".to_string()))?;
+        let database = database_manager.backend().await;
+        let result = database
+            .read()
+            .await
+            .gap_report(sensor_id, start, end, (interval_ms * 1000.0).round() as i64)
+            .await;
+
+        match result {
+            Ok(report) => Ok(CallToolResult {
+                content: vec![Content::text(serde_json::to_string_pretty(&report)?)],
+                is_error: Some(false),
+            }),
+            Err(e) => Ok(CallToolResult {
+                content: vec![Content::text(format!("Failed to detect gaps: {e}"))],
+                is_error: Some(true),
+            }),
+        }
+    }
+
+    async fn latest_readings(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let requested: Option<Vec<String>> = args
+            .as_ref()
+            .and_then(|args| args["sensorIds"].as_array())
+            .map(|ids| {
+                ids.iter()
+                    .filter_map(|id| id.as_str().map(str::to_string))
+                    .collect()
+            });
+
+        let database_manager = self
+            .database_manager
+            .as_ref()
+            .ok_or_else(|| GlspError::ToolExecution("Database not enabled".to_string()))?;
+        let database = database_manager.backend().await;
+        let database = database.read().await;
+        let result = match requested {
+            Some(sensor_ids) => Ok(sensor_ids),
+            None => database.list_sensors().await,
+        };
+
+        // Streamed readings are fetched and sent a chunk of sensors at a time
+        if streaming::is_active() {
+            let sensor_ids = match result {
+                Ok(sensor_ids) => sensor_ids,
+                Err(e) => {
+                    return Ok(CallToolResult {
+                        content: vec![Content::text(format!("Failed to get latest readings: {e}"))],
+                        is_error: Some(true),
+                    })
+                }
+            };
+            streaming::start(json!({ "sensors": sensor_ids.len() }));
+            let mut count = 0;
+            let mut missing = Vec::new();
+            for chunk in sensor_ids.chunks(LATEST_READINGS_CHUNK) {
+                let latest = match database.latest_readings(chunk).await {
+                    Ok(latest) => latest,
+                    Err(e) => {
+                        return Ok(CallToolResult {
+                            content: vec![Content::text(format!(
+                                "Failed to get latest readings: {e}"
+                            ))],
+                            is_error: Some(true),
+                        })
+                    }
+                };
+                missing.extend(
+                    chunk
+                        .iter()
+                        .filter(|id| !latest.iter().any(|r| &r.sensor_id == *id)),
+                );
+                for r in &latest {
+                    streaming::item(&json!({
+                        "sensorId": r.sensor_id,
+                        "timestamp": format_timestamp(r.timestamp_us),
+                        "timestampUs": r.timestamp_us,
+                        "value": r.value,
+                    }));
+                }
+                count += latest.len();
+            }
+            return Ok(CallToolResult {
+                content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                    "count": count,
+                    "missing": missing,
+                    "streamed": true,
+                }))?)],
+                is_error: Some(false),
+            });
+        }
+
+        let result = match result {
+            Ok(sensor_ids) => database
+                .latest_readings(&sensor_ids)
+                .await
+                .map(|latest| (sensor_ids, latest)),
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok((sensor_ids, latest)) => {
+                let missing: Vec<&String> = sensor_ids
+                    .iter()
+                    .filter(|id| !latest.iter().any(|r| &r.sensor_id == *id))
+                    .collect();
+                let readings: Vec<_> = latest
+                    .iter()
+                    .map(|r| {
+                        json!({
+                            "sensorId": r.sensor_id,
+                            "timestamp": format_timestamp(r.timestamp_us),
+                            "timestampUs": r.timestamp_us,
+                            "value": r.value,
+                        })
+                    })
+                    .collect();
+                Ok(CallToolResult {
+                    content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                        "readings": readings,
+                        "missing": missing,
+                    }))?)],
+                    is_error: Some(false),
+                })
+            }
+            Err(e) => Ok(CallToolResult {
+                content: vec![Content::text(format!("Failed to get latest readings: {e}"))],
+                is_error: Some(true),
+            }),
+        }
+    }
+
+    async fn export_sensor_data(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let sensor_id = args["sensorId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing sensorId".to_string()))?;
+        let (start, end) = match parse_time_range(&args["startTime"], &args["endTime"]) {
+            Ok(range) => range,
+            Err(e) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(e.to_string())],
+                    is_error: Some(true),
+                })
+            }
+        };
+        let format = match args["format"].as_str().map(str::parse::<ExportFormat>) {
+            None => ExportFormat::default(),
+            Some(Ok(format)) => format,
+            Some(Err(e)) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(e)],
+                    is_error: Some(true),
+                })
+            }
+        };
+
+        let database_manager = self
+            .database_manager
+            .as_ref()
+            .ok_or_else(|| GlspError::ToolExecution("Database not enabled".to_string()))?;
+
+        let file_stem: String = sensor_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let path = PathBuf::from(&self.config.export_path).join(format!(
+            "{file_stem}-{}-{}.{}",
+            start,
+            end,
+            format.extension()
+        ));
+
+        let database = database_manager.backend().await;
+        let database = database.read().await;
+        // A streamed export also sends every reading as it is written
+        let streamed = streaming::is_active();
+        if streamed {
+            streaming::start(json!({
+                "sensorId": sensor_id,
+                "format": format,
+                "path": path,
+            }));
+        }
+        let result = export_sensor_data_with(
+            &**database,
+            sensor_id,
+            start,
+            end,
+            format,
+            &path,
+            |readings| {
+                if !streamed {
+                    return;
+                }
+                for r in readings {
+                    streaming::item(&json!({
+                        "timestamp": format_timestamp(r.timestamp_us),
+                        "timestampUs": r.timestamp_us,
+                        "value": r.scalar_value(),
+                    }));
+                }
+            },
+        )
+        .await;
+
+        match result {
+            Ok(summary) => {
+                info!(
+                    "Exported {} readings of sensor {} to {:?}",
+                    summary.rows, sensor_id, summary.path
+                );
+                Ok(CallToolResult {
+                    content: vec![Content::text(serde_json::to_string_pretty(&summary)?)],
+                    is_error: Some(false),
+                })
+            }
+            Err(e) => Ok(CallToolResult {
+                content: vec![Content::text(format!("Failed to export sensor data: {e}"))],
+                is_error: Some(true),
+            }),
+        }
+    }
+
+    async fn save_diagram_tool(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+
+        match self.save_diagram(diagram_id).await {
+            Ok(_) => Ok(CallToolResult {
+                content: vec![Content::text(format!(
+                    "Successfully saved diagram {diagram_id} to disk"
+                ))],
+                is_error: Some(false),
+            }),
+            Err(e) => Ok(CallToolResult {
+                content: vec![Content::text(format!("Failed to save diagram: {e}"))],
+                is_error: Some(true),
+            }),
+        }
+    }
+
+    async fn retry_pending_persists(&self) -> std::result::Result<CallToolResult, GlspError> {
+        let letters =
+            self.dead_letters.pending().await.map_err(|e| {
+                GlspError::ToolExecution(format!("Failed to read dead letters: {e}"))
+            })?;
+
+        let mut saved = Vec::new();
+        let mut failed = Vec::new();
+        for letter in letters {
+            // The in-memory model is at least as new as the recovery copy
+            self.models
+                .lock()
+                .await
+                .entry(letter.diagram_id.clone())
+                .or_insert(letter.diagram);
+            match self.save_diagram(&letter.diagram_id).await {
+                Ok(()) => saved.push(letter.diagram_id),
+                Err(e) => failed.push(json!({
+                    "diagramId": letter.diagram_id,
+                    "error": e.to_string(),
+                })),
+            }
+        }
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "saved": saved,
+                "failed": failed,
+            }))?)],
+            is_error: Some(!failed.is_empty()),
+        })
+    }
+
+    async fn register_webhook(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let url = args["url"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing url".to_string()))?;
+        let filter = match &args["diagramIds"] {
+            serde_json::Value::Null => Ok(DiagramFilter::all()),
+            ids => DiagramFilter::from_json(ids),
+        };
+        // Without a secret the receiver could not tell our requests apart
+        // from anyone else's, so generate one
+        let generated = args["secret"].is_null().then(|| {
+            format!(
+                "{}{}",
+                uuid::Uuid::new_v4().simple(),
+                uuid::Uuid::new_v4().simple()
+            )
+        });
+        let secret = generated
+            .clone()
+            .or_else(|| args["secret"].as_str().map(str::to_string));
+
+        let hook = match filter.and_then(|filter| self.webhooks.register(url, filter, secret)) {
+            Ok(hook) => hook,
+            Err(e) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(e)],
+                    is_error: Some(true),
+                })
+            }
+        };
+        info!("Registered webhook {} for {}", hook.id, hook.url);
+
+        let mut result = json!({
+            "webhookId": hook.id,
+            "webhook": hook,
+            "signatureHeader": SIGNATURE_HEADER,
+        });
+        if let Some(secret) = generated {
+            result["secret"] = json!(secret);
+        }
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn unregister_webhook(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let webhook_id = args
+            .as_ref()
+            .and_then(|args| args["webhookId"].as_str())
+            .ok_or_else(|| GlspError::ToolExecution("Missing webhookId".to_string()))?;
+
+        if !self.webhooks.unregister(webhook_id) {
+            return Ok(CallToolResult {
+                content: vec![Content::text(format!("Webhook {webhook_id} not found"))],
+                is_error: Some(true),
+            });
+        }
+        info!("Unregistered webhook {webhook_id}");
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(
+                &json!({ "webhookId": webhook_id, "unregistered": true }),
+            )?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn list_webhooks(&self) -> std::result::Result<CallToolResult, GlspError> {
+        let webhooks = self.webhooks.list();
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "count": webhooks.len(),
+                "webhooks": webhooks,
+            }))?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn load_wasm_component(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let component_name = args["componentName"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing componentName".to_string()))?;
+
+        let position = snap_position(
+            Position {
+                x: args["position"]["x"].as_f64().unwrap_or(100.0),
+                y: args["position"]["y"].as_f64().unwrap_or(100.0),
+            },
+            self.config.coordinate_grid,
+        );
+
+        // Check if component exists and is available
+        // Use the flexible component finding method from WasmFileWatcher
+        let component = {
+            let wasm_watcher = self.wasm_watcher.lock().await;
+
+            wasm_watcher
+                .find_component_flexible(component_name)
+                .cloned()
+                .ok_or_else(|| {
+                    GlspError::ToolExecution(format!("WASM component '{component_name}' not found"))
+                })?
+        };
+
+        if !component.file_exists {
+            return Ok(CallToolResult {
+                content: vec![Content::text(format!(
+                    "Cannot load component '{component_name}': file is missing at {}",
+                    component.path
+                ))],
+                is_error: Some(true),
+            });
+        }
+
+        // Get the diagram
+        let mut models = self.models.lock().await;
+        let diagram = models
+            .get_mut(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution(format!("Diagram '{diagram_id}' not found")))?;
+
+        // Create a WASM component node
+        let mut node = Node::new("wasm-component", position, Some(component.name.clone()));
+
+        // Add component-specific properties
+        node.base
+            .properties
+            .insert("componentName".to_string(), json!(component.name));
+        node.base
+            .properties
+            .insert("componentPath".to_string(), json!(component.path));
+        node.base
+            .properties
+            .insert("description".to_string(), json!(component.description));
+
+        // Add interface data for rendering
+        node.base
+            .properties
+            .insert("interfaces".to_string(), json!(component.interfaces));
+        node.base
+            .properties
+            .insert("status".to_string(), json!("available"));
+        node.base.properties.insert(
+            "importsCount".to_string(),
+            json!(component
+                .interfaces
+                .iter()
+                .filter(|i| i.interface_type == "import")
+                .count()),
+        );
+        node.base.properties.insert(
+            "exportsCount".to_string(),
+            json!(component
+                .interfaces
+                .iter()
+                .filter(|i| i.interface_type == "export")
+                .count()),
+        );
+        node.base.properties.insert(
+            "totalFunctions".to_string(),
+            json!(component
+                .interfaces
+                .iter()
+                .map(|i| i.functions.len())
+                .sum::<usize>()),
+        );
+
+        let node_id = node.base.id.clone();
+        diagram.add_element(node.base);
+        diagram.add_child_to_root(&node_id);
+        drop(models); // Release the lock before saving
+
+        // Save to disk
+        if let Err(e) = self.save_diagram(diagram_id).await {
+            error!("Failed to save diagram after loading WASM component: {}", e);
+        }
+
+        Ok(CallToolResult {
+            content: vec![Content::text(format!(
+                "Loaded WASM component '{component_name}' into diagram with ID: {node_id}"
+            ))],
+            is_error: Some(false),
+        })
+    }
+
+    async fn refresh_wasm_interfaces(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+
+        let mut models = self.models.lock().await;
+        let diagram = models
+            .get_mut(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution(format!("Diagram '{diagram_id}' not found")))?;
+
+        let mut updated_count = 0;
+        let wasm_watcher = self.wasm_watcher.lock().await;
+
+        // Find all WASM components in the diagram
+        let mut elements_to_update = Vec::new();
+        for (element_id, element) in diagram.elements.iter() {
+            // Check if this is a WASM component
+            let is_wasm_component = match &element.element_type {
+                ElementType::Component => true,
+                ElementType::Custom(type_name) => type_name == "wasm-component",
+                _ => false,
+            };
+
+            if is_wasm_component {
+                // Try to get component name from properties or label
+                if let Some(component_name) = element
+                    .properties
+                    .get("componentName")
+                    .and_then(|v| v.as_str())
+                    .or(element.label.as_deref())
+                {
+                    // Find the component using flexible name matching
+                    if let Some(component) = wasm_watcher.find_component_flexible(component_name) {
+                        elements_to_update.push((element_id.clone(), component.clone()));
+                    }
+                }
+            }
+        }
+        drop(wasm_watcher); // Release the wasm_watcher lock
+
+        // Update each WASM component with interface data
+        for (element_id, component) in elements_to_update {
+            if let Some(element) = diagram.elements.get_mut(&element_id) {
+                // Add interface data for rendering
+                element
+                    .properties
+                    .insert("interfaces".to_string(), json!(component.interfaces));
+                element.properties.insert(
+                    "status".to_string(),
+                    json!(if component.file_exists {
+                        "available"
+                    } else {
+                        "missing"
+                    }),
+                );
+                element.properties.insert(
+                    "importsCount".to_string(),
+                    json!(component
+                        .interfaces
+                        .iter()
+                        .filter(|i| i.interface_type == "import")
+                        .count()),
+                );
+                element.properties.insert(
+                    "exportsCount".to_string(),
+                    json!(component
+                        .interfaces
+                        .iter()
+                        .filter(|i| i.interface_type == "export")
+                        .count()),
+                );
+                element.properties.insert(
+                    "totalFunctions".to_string(),
+                    json!(component
+                        .interfaces
+                        .iter()
+                        .map(|i| i.functions.len())
+                        .sum::<usize>()),
+                );
+
+                updated_count += 1;
+            }
+        }
+
+        drop(models); // Release the lock before saving
+
+        // Save to disk if we updated any components
+        if updated_count > 0 {
+            if let Err(e) = self.save_diagram(diagram_id).await {
+                error!("Failed to save diagram after refreshing interfaces: {}", e);
+            }
+        }
+
+        Ok(CallToolResult {
+            content: vec![Content::text(format!("Refreshed interface data for {updated_count} WASM components in diagram {diagram_id}"))],
+            is_error: Some(false),
+        })
+    }
+
+    // Layout helpers
+    fn apply_grid_layout(diagram: &mut DiagramModel) {
+        let mut x = 50.0;
+        let mut y = 50.0;
+        let spacing_x = 150.0;
+        let _spacing_y = 100.0;
+        let cols = 4;
+        let mut col = 0;
+
+        for (_, element) in diagram.elements.iter_mut() {
+            if element.element_type != ElementType::Graph && element.bounds.is_some() {
+                if let Some(bounds) = &mut element.bounds {
+                    bounds.x = x;
+                    bounds.y = y;
+                }
+
+                col += 1;
+                if col >= cols {
+                    col = 0;
+                    x = 50.0;
+                    y += 100.0;
+                } else {
+                    x += spacing_x;
+                }
             }
-            "load_wasm_component" => self.load_wasm_component(request.arguments).await,
-            "refresh_wasm_interfaces" => self.refresh_wasm_interfaces(request.arguments).await,
-            "get_component_path" => self.get_component_path(request.arguments).await,
-            "get_component_wit_info" => self.get_component_wit_info(request.arguments).await,
-            "debug_wit_analysis" => self.debug_wit_analysis(request.arguments).await,
+        }
+        diagram.revision += 1;
+    }
 
-            // Workspace management tools
-            "set_workspace_directory" => self.set_workspace_directory_tool(request.arguments).await,
-            "get_workspace_info" => self.get_current_workspace_tool().await,
-            "set_wasm_components_path" => {
-                self.set_wasm_components_path_tool(request.arguments).await
+    /// One row per layer of directed edges; undirected edges do not affect layering
+    fn apply_hierarchical_layout(diagram: &mut DiagramModel) {
+        let spacing_x = 150.0;
+        let spacing_y = 100.0;
+        let layers = directed_layers(diagram);
+        let mut next_x: HashMap<usize, f64> = HashMap::new();
+
+        let mut ids: Vec<String> = diagram
+            .elements
+            .values()
+            .filter(|e| e.element_type != ElementType::Graph && e.bounds.is_some())
+            .map(|e| e.id.clone())
+            .collect();
+        ids.sort();
+
+        for id in ids {
+            let layer = layers.get(&id).copied().unwrap_or(0);
+            let x = next_x.entry(layer).or_insert(50.0);
+            if let Some(bounds) = diagram
+                .elements
+                .get_mut(&id)
+                .and_then(|e| e.bounds.as_mut())
+            {
+                bounds.x = *x;
+                bounds.y = 50.0 + layer as f64 * spacing_y;
             }
-            "set_diagrams_path" => self.set_diagrams_path_tool(request.arguments).await,
-            "rescan_workspace" => self.rescan_workspace_tool().await,
-            "validate_workspace" => self.validate_workspace_tool(request.arguments).await,
-            "create_workspace_structure" => {
-                self.create_workspace_structure_tool(request.arguments)
-                    .await
+            *x += spacing_x;
+        }
+        diagram.revision += 1;
+    }
+
+    fn generate_svg(diagram: &DiagramModel) -> String {
+        let mut svg = match content_extent(diagram) {
+            Some(extent) => {
+                let canvas = fit_to_content(&extent, CANVAS_MARGIN);
+                format!(
+                    r#"<svg width="{}" height="{}" viewBox="{} {} {} {}" xmlns="http://www.w3.org/2000/svg">"#,
+                    canvas.width, canvas.height, canvas.x, canvas.y, canvas.width, canvas.height
+                )
+            }
+            None => {
+                String::from(r#"<svg width="800" height="600" xmlns="http://www.w3.org/2000/svg">"#)
             }
+        };
+        let styles = type_styles(diagram);
 
-            _ => Err(GlspError::NotImplemented(format!(
-                "Tool not implemented: {}",
-                request.name
-            ))),
+        // Add elements
+        for element in diagram.elements.values() {
+            if element.element_type != ElementType::Graph {
+                if let Some(bounds) = &element.bounds {
+                    let style = resolve_style(&styles, element);
+                    if has_compartments(element) {
+                        svg.push_str(&class_svg(element, bounds, &style));
+                    } else if element.element_type.is_node_like() {
+                        svg.push_str(&format!(
+                            r#"<rect x="{}" y="{}" width="{}" height="{}"{}/>"#,
+                            bounds.x,
+                            bounds.y,
+                            bounds.width,
+                            bounds.height,
+                            style.shape_attributes("lightblue")
+                        ));
+
+                        if let Some(label) = element.properties.get("label") {
+                            if let Some(label_text) = label.as_str() {
+                                svg.push_str(&format!(
+                                    r#"<text x="{}" y="{}" text-anchor="middle" dominant-baseline="middle"{}>{}</text>"#,
+                                    bounds.x + bounds.width / 2.0,
+                                    bounds.y + bounds.height / 2.0,
+                                    style.text_attributes(),
+                                    label_text
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
         }
+
+        // Edges, with an arrowhead only when directed
+        svg.push_str(
+            r#"<defs><marker id="arrow" markerWidth="10" markerHeight="7" refX="10" refY="3.5" orient="auto"><polygon points="0 0, 10 3.5, 0 7"/></marker></defs>"#,
+        );
+        let center = |id: &str| {
+            let bounds = diagram.elements.get(id)?.bounds.as_ref()?;
+            Some((
+                bounds.x + bounds.width / 2.0,
+                bounds.y + bounds.height / 2.0,
+            ))
+        };
+        for (edge, source, target) in links(diagram) {
+            if let (Some((x1, y1)), Some((x2, y2))) = (center(source), center(target)) {
+                let marker = if is_directed(edge) {
+                    r#" marker-end="url(#arrow)""#
+                } else {
+                    ""
+                };
+                let stroke = resolve_style(&styles, edge).line_attributes();
+                svg.push_str(&format!(
+                    r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}"{stroke}{marker}/>"#
+                ));
+            }
+        }
+
+        svg.push_str("</svg>");
+        svg
     }
 
-    pub async fn list_resources(
-        &self,
-        _request: PaginatedRequestParam,
-    ) -> std::result::Result<ListResourcesResult, GlspError> {
+    // Persistence helper methods
+    async fn load_all_diagrams(&self) -> std::result::Result<(), GlspError> {
+        let diagram_infos = self
+            .persistence
+            .list_diagrams()
+            .await
+            .map_err(|e| GlspError::NotImplemented(format!("Failed to list diagrams: {e}")))?;
+
+        let mut models = self.models.lock().await;
+
+        for info in diagram_infos {
+            match self.persistence.load_diagram(&info.file_name).await {
+                Ok(diagram) => {
+                    info!("Loaded diagram '{}' from disk", info.name);
+                    models.insert(diagram.id.clone(), diagram);
+                }
+                Err(e) => {
+                    error!("Failed to load diagram '{}': {e}", info.file_name);
+                }
+            }
+        }
+
+        info!("Loaded {} diagrams from disk", models.len());
+        Ok(())
+    }
+
+    /// Surface dead letters left by an earlier run.
+    ///
+    /// A recovery copy newer than the diagram loaded from disk replaces it in
+    /// memory, so the next successful save persists the lost change.
+    async fn restore_dead_letters(&self) {
+        let letters = match self.dead_letters.pending().await {
+            Ok(letters) => letters,
+            Err(e) => {
+                error!(
+                    "Failed to read dead letters from {:?}: {e}",
+                    self.dead_letters.get_dir()
+                );
+                return;
+            }
+        };
+        if letters.is_empty() {
+            return;
+        }
+
+        warn!(
+            "{} diagram(s) failed to save in an earlier run; use retry_pending_persists to write them",
+            letters.len()
+        );
+        let mut models = self.models.lock().await;
+        for letter in letters {
+            warn!(
+                "Pending persist for diagram '{}' ({}) since {}: {}",
+                letter.diagram_name, letter.diagram_id, letter.failed_at, letter.error
+            );
+            let is_newer = models
+                .get(&letter.diagram_id)
+                .is_none_or(|loaded| loaded.updated_at < letter.diagram.updated_at);
+            if is_newer {
+                models.insert(letter.diagram_id.clone(), letter.diagram);
+            }
+        }
+    }
+
+    async fn save_diagram(&self, diagram_id: &str) -> std::result::Result<(), GlspError> {
         let models = self.models.lock().await;
-        let mut resources = vec![
-            Resource {
-                uri: "diagram://list".to_string(),
-                name: "Diagram List".to_string(),
-                description: Some("List of all available diagrams".to_string()),
-                mime_type: Some("application/json".to_string()),
-                annotations: None,
-                raw: None,
-            },
-            Resource {
-                uri: "wasm://components/list".to_string(),
-                name: "WASM Components List".to_string(),
-                description: Some("List of all available WASM components".to_string()),
-                mime_type: Some("application/json".to_string()),
-                annotations: None,
-                raw: None,
-            },
-        ];
 
-        // Add resources for each loaded diagram
-        for (id, diagram) in models.iter() {
-            resources.push(Resource {
-                uri: format!("diagram://model/{id}"),
-                name: diagram.name.clone(),
-                description: Some(format!("{} diagram", diagram.diagram_type)),
-                mime_type: Some("application/json".to_string()),
-                annotations: None,
-                raw: None,
-            });
+        if let Some(diagram) = models.get(diagram_id) {
+            if let Err(e) = self.persistence.save_diagram(diagram).await {
+                match self.dead_letters.record(diagram, &e.to_string()).await {
+                    Ok(path) => error!(
+                        "Failed to save diagram '{}' ({diagram_id}): {e}; recovery copy written to {:?}",
+                        diagram.name, path
+                    ),
+                    Err(dead_letter_error) => error!(
+                        "Failed to save diagram '{}' ({diagram_id}): {e}; writing the recovery copy also failed: {dead_letter_error}",
+                        diagram.name
+                    ),
+                }
+                return Err(GlspError::NotImplemented(format!(
+                    "Failed to save diagram: {e}"
+                )));
+            }
+            info!("Saved diagram '{}' to disk", diagram.name);
+            match self.dead_letters.remove(diagram_id).await {
+                Ok(true) => info!("Cleared pending persist for diagram '{}'", diagram.name),
+                Ok(false) => {}
+                Err(e) => warn!("Failed to clear dead letter for '{diagram_id}': {e}"),
+            }
+            Ok(())
+        } else {
+            Err(GlspError::NotImplemented(format!(
+                "Diagram not found: {diagram_id}"
+            )))
+        }
+    }
+
+    async fn delete_diagram_files(&self, diagram_name: &str) -> std::result::Result<(), GlspError> {
+        self.persistence
+            .delete_diagram(diagram_name)
+            .await
+            .map_err(|e| {
+                GlspError::NotImplemented(format!("Failed to delete diagram files: {e}"))
+            })?;
+        info!("Deleted diagram files for '{}'", diagram_name);
+        Ok(())
+    }
+
+    pub fn get_filesystem_watcher(&self) -> std::sync::Arc<tokio::sync::RwLock<FileSystemWatcher>> {
+        self.filesystem_watcher.clone()
+    }
+
+    pub fn get_wasm_watcher(&self) -> std::sync::Arc<tokio::sync::Mutex<WasmFileWatcher>> {
+        self.wasm_watcher.clone()
+    }
+
+    async fn get_component_path(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+
+        let component_name = args["componentName"].as_str().ok_or_else(|| {
+            GlspError::ToolExecution("Missing componentName parameter".to_string())
+        })?;
+
+        // Look up the component using flexible name matching
+        let wasm_watcher = self.wasm_watcher.lock().await;
+        let component = wasm_watcher
+            .find_component_flexible(component_name)
+            .ok_or_else(|| {
+                GlspError::ToolExecution(format!("WASM component not found: {component_name}"))
+            })?;
+
+        Ok(CallToolResult {
+            content: vec![Content::text(component.path.clone())],
+            is_error: Some(false),
+        })
+    }
+
+    async fn inspect_component(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+
+        let component_name = args["componentName"].as_str().ok_or_else(|| {
+            GlspError::ToolExecution("Missing componentName parameter".to_string())
+        })?;
+
+        let wasm_watcher = self.wasm_watcher.lock().await;
+        let component = wasm_watcher
+            .find_component_flexible(component_name)
+            .ok_or_else(|| {
+                GlspError::ToolExecution(format!("WASM component not found: {component_name}"))
+            })?;
+
+        let sections: Vec<CustomSection> = component
+            .metadata
+            .get(CUSTOM_SECTIONS_KEY)
+            .cloned()
+            .map(serde_json::from_value)
+            .transpose()?
+            .unwrap_or_default();
+
+        let result = json!({
+            "component": component.name,
+            "path": component.path,
+            "metadata": section_metadata(&sections),
+            "sections": sections
+                .iter()
+                .map(|s| json!({"name": s.name, "size": s.size, "encoding": s.encoding}))
+                .collect::<Vec<_>>(),
+        });
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
+            is_error: Some(false),
+        })
+    }
+
+    async fn get_component_wit_info(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        use crate::wasm::WitAnalyzer;
+        use std::path::PathBuf;
 
-            resources.push(Resource {
-                uri: format!("diagram://validation/{id}"),
-                name: format!("{} Validation", diagram.name),
-                description: Some("Validation results for the diagram".to_string()),
-                mime_type: Some("application/json".to_string()),
-                annotations: None,
-                raw: None,
-            });
-        }
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
 
-        // Add resources for individual WASM components
-        let wasm_watcher = self.wasm_watcher.lock().await;
-        let wasm_components = wasm_watcher.get_components();
-        for component in wasm_components {
-            resources.push(Resource {
-                uri: format!("wasm://component/{}", component.name),
-                name: format!("WASM Component: {}", component.name),
-                description: Some(format!("Details for {} component", component.name)),
-                mime_type: Some("application/json".to_string()),
-                annotations: None,
-                raw: None,
-            });
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId parameter".to_string()))?;
 
-            // Add WIT-specific resources for each component
-            resources.push(Resource {
-                uri: format!("wasm://component/{}/wit", component.name),
-                name: format!("WIT Analysis: {}", component.name),
-                description: Some(format!(
-                    "WIT interface analysis for {} component",
-                    component.name
-                )),
-                mime_type: Some("application/json".to_string()),
-                annotations: None,
-                raw: None,
-            });
+        let element_id: &str = &Self::element_id_arg(&args, "elementId")?;
 
-            resources.push(Resource {
-                uri: format!("wasm://component/{}/wit/raw", component.name),
-                name: format!("Raw WIT: {}", component.name),
-                description: Some(format!("Raw WIT content for {} component", component.name)),
-                mime_type: Some("text/plain".to_string()),
-                annotations: None,
-                raw: None,
-            });
+        // Get the diagram
+        let models = self.models.lock().await;
+        let diagram = models
+            .get(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution(format!("Diagram not found: {diagram_id}")))?;
 
-            resources.push(Resource {
-                uri: format!("wasm://component/{}/interfaces", component.name),
-                name: format!("Interfaces: {}", component.name),
-                description: Some(format!("All interfaces for {} component", component.name)),
-                mime_type: Some("application/json".to_string()),
-                annotations: None,
-                raw: None,
+        // Get the element
+        let element = diagram
+            .elements
+            .get(element_id)
+            .ok_or_else(|| GlspError::ToolExecution(format!("Element not found: {element_id}")))?;
+
+        // Check if it's a WASM component
+        let is_wasm_component = match &element.element_type {
+            ElementType::Component => true,
+            ElementType::Custom(type_name) => type_name == "wasm-component",
+            _ => false,
+        };
+
+        if !is_wasm_component {
+            return Ok(CallToolResult {
+                content: vec![Content::text(format!(
+                    "Element '{}' is not a WASM component (type: {})",
+                    element_id, element.element_type
+                ))],
+                is_error: Some(true),
             });
         }
 
-        Ok(ListResourcesResult {
-            resources,
-            next_cursor: None,
-        })
-    }
+        // First, try to get the component file path from element properties
+        let component_path = if let Some(path_value) = element.properties.get("componentPath") {
+            path_value.as_str().map(|s| s.to_string())
+        } else {
+            None
+        };
 
-    pub async fn read_resource(
-        &self,
-        request: ReadResourceRequestParam,
-    ) -> std::result::Result<ReadResourceResult, GlspError> {
-        // Parse the URI to determine what resource is being requested
-        if request.uri.starts_with("diagram://model/") {
-            let diagram_id = request.uri.strip_prefix("diagram://model/").unwrap_or("");
+        // If we have a path, use it directly
+        let component_file_path = if let Some(path) = component_path {
+            PathBuf::from(path)
+        } else {
+            // Fallback: Try to find by component name
+            let component_name = element
+                .properties
+                .get("componentName")
+                .and_then(|v| v.as_str())
+                .or(element.properties.get("name").and_then(|v| v.as_str()))
+                .or(element.label.as_deref())
+                .ok_or_else(|| {
+                    GlspError::ToolExecution(
+                        "Component name not found in element properties".to_string(),
+                    )
+                })?;
 
-            let models = self.models.lock().await;
-            if let Some(model) = models.get(diagram_id) {
-                // Return the diagram model as JSON
-                let content = serde_json::to_string(model)
-                    .map_err(|e| GlspError::NotImplemented(format!("Serialization error: {e}")))?;
+            // Look up the component in the watcher using flexible name matching
+            let wasm_watcher = self.wasm_watcher.lock().await;
+            let component = wasm_watcher
+                .find_component_flexible(component_name)
+                .ok_or_else(|| {
+                    GlspError::ToolExecution(format!(
+                        "WASM component file not found: {component_name}"
+                    ))
+                })?;
 
-                Ok(ReadResourceResult {
-                    contents: vec![ResourceContents {
-                        uri: request.uri.clone(),
-                        mime_type: Some("application/json".to_string()),
-                        text: Some(content),
-                        blob: None,
-                    }],
-                })
-            } else {
-                Err(GlspError::NotImplemented(format!(
-                    "Diagram not found: {diagram_id}"
-                )))
-            }
-        } else if request.uri.starts_with("diagram://validation/") {
-            // Return a simple validation result
-            let _diagram_id = request
-                .uri
-                .strip_prefix("diagram://validation/")
-                .unwrap_or("");
-            let validation = json!({
-                "isValid": true,
-                "issues": []
+            PathBuf::from(&component.path)
+        };
+
+        // Verify the file exists
+        if !component_file_path.exists() {
+            return Ok(CallToolResult {
+                content: vec![Content::text(format!(
+                    "WASM component file not found at path: {}",
+                    component_file_path.display()
+                ))],
+                is_error: Some(true),
             });
+        }
 
-            Ok(ReadResourceResult {
-                contents: vec![ResourceContents {
-                    uri: request.uri.clone(),
-                    mime_type: Some("application/json".to_string()),
-                    text: Some(validation.to_string()),
-                    blob: None,
-                }],
-            })
-        } else if request.uri == "diagram://list" {
-            // Return list of diagrams from both memory and disk
-            let models = self.models.lock().await;
-            let mut diagram_infos = Vec::new();
+        // Analyze the component
+        match WitAnalyzer::analyze_component(&component_file_path).await {
+            Ok(analysis) => {
+                // Return structured WIT information suitable for properties panel
+                let wit_info = json!({
+                    "componentName": analysis.component_name,
+                    "worldName": analysis.world_name,
+                    "filePath": component_file_path.to_string_lossy(),
+                    "imports": analysis.imports.iter().map(|interface| json!({
+                        "name": interface.name,
+                        "namespace": interface.namespace,
+                        "package": interface.package,
+                        "version": interface.version,
+                        "functions": interface.functions.iter().map(|func| json!({
+                            "name": func.name,
+                            "params": func.params.iter().map(|p| json!({
+                                "name": p.name,
+                                "type": p.param_type.name
+                            })).collect::<Vec<_>>(),
+                            "results": func.results.iter().map(|r| json!({
+                                "name": r.name,
+                                "type": r.param_type.name
+                            })).collect::<Vec<_>>(),
+                            "isAsync": func.is_async
+                        })).collect::<Vec<_>>(),
+                        "types": interface.types.iter().map(|t| json!({
+                            "name": t.name,
+                            "definition": format!("{:?}", t.type_def)
+                        })).collect::<Vec<_>>()
+                    })).collect::<Vec<_>>(),
+                    "exports": analysis.exports.iter().map(|interface| json!({
+                        "name": interface.name,
+                        "namespace": interface.namespace,
+                        "package": interface.package,
+                        "version": interface.version,
+                        "functions": interface.functions.iter().map(|func| json!({
+                            "name": func.name,
+                            "params": func.params.iter().map(|p| json!({
+                                "name": p.name,
+                                "type": p.param_type.name
+                            })).collect::<Vec<_>>(),
+                            "results": func.results.iter().map(|r| json!({
+                                "name": r.name,
+                                "type": r.param_type.name
+                            })).collect::<Vec<_>>(),
+                            "isAsync": func.is_async
+                        })).collect::<Vec<_>>(),
+                        "types": interface.types.iter().map(|t| json!({
+                            "name": t.name,
+                            "definition": format!("{:?}", t.type_def)
+                        })).collect::<Vec<_>>()
+                    })).collect::<Vec<_>>(),
+                    "dependencies": analysis.dependencies.iter().map(|dep| json!({
+                        "package": dep.package,
+                        "version": dep.version,
+                        "interfaces": dep.interfaces
+                    })).collect::<Vec<_>>(),
+                    "summary": {
+                        "importsCount": analysis.imports.len(),
+                        "exportsCount": analysis.exports.len(),
+                        "totalFunctions": analysis.imports.iter()
+                            .chain(analysis.exports.iter())
+                            .map(|i| i.functions.len())
+                            .sum::<usize>(),
+                        "typesCount": analysis.types.len(),
+                        "dependenciesCount": analysis.dependencies.len()
+                    }
+                });
 
-            // Add loaded diagrams with their info
-            for (id, diagram) in models.iter() {
-                diagram_infos.push(json!({
-                    "id": id,
-                    "name": diagram.name,
-                    "diagramType": diagram.diagram_type,
-                    "createdAt": diagram.created_at,
-                    "updatedAt": diagram.updated_at,
-                }));
+                Ok(CallToolResult {
+                    content: vec![Content::text(
+                        serde_json::to_string_pretty(&wit_info).map_err(|e| {
+                            GlspError::ToolExecution(format!("Failed to serialize WIT info: {e}"))
+                        })?,
+                    )],
+                    is_error: Some(false),
+                })
             }
+            Err(error) => Ok(CallToolResult {
+                content: vec![Content::text(format!(
+                    "Failed to analyze component WIT interfaces: {error}"
+                ))],
+                is_error: Some(true),
+            }),
+        }
+    }
 
-            let list = json!({
-                "diagrams": diagram_infos
-            });
+    async fn export_component_wit(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        use crate::wasm::WitAnalyzer;
 
-            Ok(ReadResourceResult {
-                contents: vec![ResourceContents {
-                    uri: request.uri.clone(),
-                    mime_type: Some("application/json".to_string()),
-                    text: Some(list.to_string()),
-                    blob: None,
-                }],
-            })
-        } else if request.uri == "wasm://components/list" {
-            // Get WASM components from the wasm watcher (with analyzed data)
-            let wasm_watcher = self.wasm_watcher.lock().await;
-            let wasm_components = wasm_watcher.get_components();
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let component_name = args["componentName"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing componentName".to_string()))?;
 
-            let component_list: Vec<serde_json::Value> = wasm_components
-                .iter()
-                .map(|component| {
-                    json!({
-                        "name": component.name,
-                        "path": component.path,
-                        "description": format!("WASM component: {}", component.name),
-                        "status": if component.file_exists { "available" } else { "missing" },
-                        "interfaces": component.interfaces.len(),
-                        "uri": format!("wasm://component/{}", component.name)
+        let path = {
+            let wasm_watcher = self.wasm_watcher.lock().await;
+            match wasm_watcher.get_component(component_name) {
+                Some(component) if component.file_exists => component.path.clone(),
+                Some(_) => {
+                    return Ok(CallToolResult {
+                        content: vec![Content::text(format!(
+                            "WASM component '{component_name}' is missing from disk"
+                        ))],
+                        is_error: Some(true),
                     })
-                })
-                .collect();
+                }
+                None => {
+                    return Ok(CallToolResult {
+                        content: vec![Content::text(format!(
+                            "WASM component '{component_name}' not found"
+                        ))],
+                        is_error: Some(true),
+                    })
+                }
+            }
+        };
 
-            let available_count = wasm_components.iter().filter(|c| c.file_exists).count();
-            let missing_count = wasm_components.len() - available_count;
+        let bytes = tokio::fs::read(&path).await.map_err(|e| {
+            GlspError::ToolExecution(format!("Failed to read component {path}: {e}"))
+        })?;
+        match WitAnalyzer::reconstruct_wit(&bytes) {
+            Ok(wit) => Ok(CallToolResult {
+                content: vec![Content::text(wit)],
+                is_error: Some(false),
+            }),
+            Err(e) => Ok(CallToolResult {
+                content: vec![Content::text(format!(
+                    "Cannot reconstruct WIT for '{component_name}': {e:#}"
+                ))],
+                is_error: Some(true),
+            }),
+        }
+    }
 
-            let wasm_list = json!({
-                "components": component_list,
-                "total": component_list.len(),
-                "available": available_count,
-                "missing": missing_count
+    /// Debug tool to analyze WIT interfaces for a specific component file
+    async fn debug_wit_analysis(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        use crate::wasm::WitAnalyzer;
+        use std::path::PathBuf;
+
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+
+        let component_path = args["componentPath"].as_str().ok_or_else(|| {
+            GlspError::ToolExecution("Missing componentPath parameter".to_string())
+        })?;
+
+        let path = PathBuf::from(component_path);
+
+        if !path.exists() {
+            return Ok(CallToolResult {
+                content: vec![Content::text(format!(
+                    "Component file not found: {component_path}"
+                ))],
+                is_error: Some(true),
             });
+        }
 
-            Ok(ReadResourceResult {
-                contents: vec![ResourceContents {
-                    uri: request.uri.clone(),
-                    mime_type: Some("application/json".to_string()),
-                    text: Some(wasm_list.to_string()),
-                    blob: None,
-                }],
-            })
-        } else if request.uri.starts_with("wasm://component/") {
-            let path = request
-                .uri
-                .strip_prefix("wasm://component/")
-                .ok_or_else(|| {
-                    GlspError::NotImplemented(format!(
-                        "Invalid WASM component URI: {}",
-                        request.uri
-                    ))
-                })?;
+        // Analyze the component with the WIT analyzer
+        match WitAnalyzer::analyze_component(&path).await {
+            Ok(analysis) => {
+                let debug_info = json!({
+                    "analysis": "WIT Debug Analysis",
+                    "componentName": analysis.component_name,
+                    "worldName": analysis.world_name,
+                    "filePath": component_path,
+                    "imports": analysis.imports.iter().map(|interface| json!({
+                        "name": interface.name,
+                        "type": "import",
+                        "functions": interface.functions.iter().map(|f| f.name.clone()).collect::<Vec<_>>(),
+                        "functionCount": interface.functions.len(),
+                        "types": interface.types.iter().map(|t| t.name.clone()).collect::<Vec<_>>(),
+                        "typeCount": interface.types.len()
+                    })).collect::<Vec<_>>(),
+                    "exports": analysis.exports.iter().map(|interface| json!({
+                        "name": interface.name,
+                        "type": "export",
+                        "functions": interface.functions.iter().map(|f| f.name.clone()).collect::<Vec<_>>(),
+                        "functionCount": interface.functions.len(),
+                        "types": interface.types.iter().map(|t| t.name.clone()).collect::<Vec<_>>(),
+                        "typeCount": interface.types.len()
+                    })).collect::<Vec<_>>(),
+                    "summary": {
+                        "totalImports": analysis.imports.len(),
+                        "totalExports": analysis.exports.len(),
+                        "totalInterfaces": analysis.imports.len() + analysis.exports.len(),
+                        "totalTypes": analysis.types.len(),
+                        "totalDependencies": analysis.dependencies.len(),
+                        "hasRawWit": analysis.raw_wit.is_some()
+                    },
+                    "expectedForVideoAIPipeline": {
+                        "expectedImports": ["video-decoder", "object-detection"],
+                        "expectedExports": ["pipeline-control"],
+                        "note": "If this is the video-ai-pipeline component, we should see exactly these interfaces"
+                    }
+                });
 
-            if let Some((component_name, suffix)) = path.split_once('/') {
-                match suffix {
-                    "wit" => self.get_component_wit_analysis(component_name).await,
-                    "wit/raw" => self.get_component_raw_wit(component_name).await,
-                    "interfaces" => self.get_component_interfaces(component_name).await,
-                    _ => Err(GlspError::NotImplemented(format!(
-                        "Unknown component resource: {}",
-                        request.uri
-                    ))),
-                }
-            } else {
-                self.get_wasm_component_details(path).await
+                Ok(CallToolResult {
+                    content: vec![Content::text(serde_json::to_string_pretty(&debug_info)
+                        .map_err(|e| GlspError::ToolExecution(format!("Failed to serialize debug info: {e}")))?)],
+                    is_error: Some(false),
+                })
+            }
+            Err(error) => {
+                Ok(CallToolResult {
+                    content: vec![Content::text(format!("WIT Analysis Failed: {error}\n\nThis might indicate:\n1. File is not a valid WASM component\n2. Component doesn't have WIT interfaces\n3. WIT parser error\n\nFalling back to basic file info..."))],
+                    is_error: Some(true),
+                })
             }
-        } else {
-            Err(GlspError::NotImplemented(format!(
-                "Resource type not supported: {}",
-                request.uri
-            )))
         }
     }
 
-    // Helper methods for component-specific resources
-    async fn get_wasm_component_details(
+    // Workspace management tool wrapper methods
+
+    async fn set_workspace_directory_tool(
         &self,
-        component_name: &str,
-    ) -> std::result::Result<ReadResourceResult, GlspError> {
-        let wasm_watcher = self.wasm_watcher.lock().await;
-        let component = wasm_watcher.get_component(component_name).ok_or_else(|| {
-            GlspError::NotImplemented(format!("WASM component not found: {component_name}"))
-        })?;
+        arguments: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = arguments.unwrap_or_default();
+        let workspace_path = args
+            .get("workspace_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                GlspError::ToolExecution("workspace_path parameter is required".to_string())
+            })?;
 
-        let content = json!({
-            "name": component.name,
-            "path": component.path,
-            "description": component.description,
-            "fileExists": component.file_exists,
-            "lastSeen": component.last_seen,
-            "removedAt": component.removed_at,
-            "interfaces": component.interfaces,
-            "metadata": component.metadata,
-            "witInterfaces": component.wit_interfaces,
-            "dependencies": component.dependencies
-        });
+        let create_if_missing = args
+            .get("create_if_missing")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
 
-        Ok(ReadResourceResult {
-            contents: vec![ResourceContents {
-                uri: format!("wasm://component/{component_name}"),
-                mime_type: Some("application/json".to_string()),
-                text: Some(content.to_string()),
-                blob: None,
-            }],
+        // Create workspace directory if requested and it doesn't exist
+        if create_if_missing && !std::path::Path::new(workspace_path).exists() {
+            std::fs::create_dir_all(workspace_path).map_err(|e| {
+                GlspError::ToolExecution(format!("Failed to create workspace directory: {e}"))
+            })?;
+        }
+
+        self.set_workspace_directory(workspace_path.to_string())
+            .await?;
+
+        Ok(CallToolResult {
+            content: vec![Content::text(format!(
+                "Successfully set workspace directory to: {workspace_path}"
+            ))],
+            is_error: Some(false),
+        })
+    }
+
+    async fn get_current_workspace_tool(&self) -> std::result::Result<CallToolResult, GlspError> {
+        let workspace_info = self.get_current_workspace().await?;
+
+        Ok(CallToolResult {
+            content: vec![Content::text(
+                serde_json::to_string_pretty(&workspace_info).map_err(|e| {
+                    GlspError::ToolExecution(format!("Failed to serialize workspace info: {e}"))
+                })?,
+            )],
+            is_error: Some(false),
         })
     }
 
-    async fn get_component_interfaces(
+    async fn set_wasm_components_path_tool(
         &self,
-        component_name: &str,
-    ) -> std::result::Result<ReadResourceResult, GlspError> {
-        let wasm_watcher = self.wasm_watcher.lock().await;
-        let component = wasm_watcher.get_component(component_name).ok_or_else(|| {
-            GlspError::NotImplemented(format!("WASM component not found: {component_name}"))
-        })?;
+        arguments: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = arguments.unwrap_or_default();
+        let wasm_path = args
+            .get("wasm_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                GlspError::ToolExecution("wasm_path parameter is required".to_string())
+            })?;
 
-        let content = json!({
-            "componentName": component.name,
-            "interfaces": component.interfaces,
-            "totalInterfaces": component.interfaces.len()
-        });
+        self.set_wasm_components_path(wasm_path.to_string()).await?;
 
-        Ok(ReadResourceResult {
-            contents: vec![ResourceContents {
-                uri: format!("wasm://component/{component_name}/interfaces"),
-                mime_type: Some("application/json".to_string()),
-                text: Some(content.to_string()),
-                blob: None,
-            }],
+        Ok(CallToolResult {
+            content: vec![Content::text(format!(
+                "Successfully set WASM components path to: {wasm_path}"
+            ))],
+            is_error: Some(false),
         })
     }
 
-    async fn get_component_wit_analysis(
+    async fn set_diagrams_path_tool(
         &self,
-        component_name: &str,
-    ) -> std::result::Result<ReadResourceResult, GlspError> {
-        let wasm_watcher = self.wasm_watcher.lock().await;
-        let component = wasm_watcher.get_component(component_name).ok_or_else(|| {
-            GlspError::NotImplemented(format!("WASM component not found: {component_name}"))
-        })?;
+        arguments: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = arguments.unwrap_or_default();
+        let diagrams_path = args
+            .get("diagrams_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                GlspError::ToolExecution("diagrams_path parameter is required".to_string())
+            })?;
 
-        // Analyze WIT interfaces specifically
-        let mut imports = Vec::new();
-        let mut exports = Vec::new();
+        self.set_diagrams_path(diagrams_path.to_string()).await?;
 
-        for interface in &component.interfaces {
-            let interface_data = json!({
-                "name": interface.name,
-                "functions": interface.functions.iter().map(|f| json!({
-                    "name": f.name,
-                    "parameters": f.params.iter().map(|p| json!({
-                        "name": p.name,
-                        "type": p.param_type
-                    })).collect::<Vec<_>>(),
-                    "returns": f.returns.iter().map(|r| json!({
-                        "name": r.name,
-                        "type": r.param_type
-                    })).collect::<Vec<_>>()
-                })).collect::<Vec<_>>()
-            });
+        Ok(CallToolResult {
+            content: vec![Content::text(format!(
+                "Successfully set diagrams path to: {diagrams_path}"
+            ))],
+            is_error: Some(false),
+        })
+    }
 
-            match interface.interface_type.as_str() {
-                "import" => imports.push(interface_data),
-                "export" => exports.push(interface_data),
-                _ => {}
-            }
-        }
+    async fn rescan_workspace_tool(&self) -> std::result::Result<CallToolResult, GlspError> {
+        self.rescan_workspace().await?;
 
-        let content = json!({
-            "componentName": component.name,
-            "witAnalysis": {
-                "imports": imports,
-                "exports": exports,
-                "summary": {
-                    "totalImports": imports.len(),
-                    "totalExports": exports.len(),
-                    "totalFunctions": component.interfaces.iter()
-                        .map(|i| i.functions.len())
-                        .sum::<usize>()
-                }
-            },
-            "metadata": component.metadata,
-            "dependencies": component.dependencies
-        });
+        Ok(CallToolResult {
+            content: vec![Content::text(
+                "Successfully rescanned workspace for WASM components and diagrams".to_string(),
+            )],
+            is_error: Some(false),
+        })
+    }
 
-        Ok(ReadResourceResult {
-            contents: vec![ResourceContents {
-                uri: format!("wasm://component/{component_name}/wit"),
-                mime_type: Some("application/json".to_string()),
-                text: Some(content.to_string()),
-                blob: None,
-            }],
+    async fn validate_workspace_tool(
+        &self,
+        arguments: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = arguments.unwrap_or_default();
+        let workspace_path = args
+            .get("workspace_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                GlspError::ToolExecution("workspace_path parameter is required".to_string())
+            })?;
+
+        let validation_result = self.validate_workspace_paths(workspace_path).await?;
+
+        Ok(CallToolResult {
+            content: vec![Content::text(
+                serde_json::to_string_pretty(&validation_result).map_err(|e| {
+                    GlspError::ToolExecution(format!("Failed to serialize validation result: {e}"))
+                })?,
+            )],
+            is_error: Some(false),
         })
     }
 
-    async fn get_component_raw_wit(
+    async fn create_workspace_structure_tool(
         &self,
-        component_name: &str,
-    ) -> std::result::Result<ReadResourceResult, GlspError> {
-        let wasm_watcher = self.wasm_watcher.lock().await;
-        let component = wasm_watcher.get_component(component_name).ok_or_else(|| {
-            GlspError::NotImplemented(format!("WASM component not found: {component_name}"))
+        arguments: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = arguments.unwrap_or_default();
+        let workspace_path = args
+            .get("workspace_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                GlspError::ToolExecution("workspace_path parameter is required".to_string())
+            })?;
+
+        use std::path::Path;
+        let workspace = Path::new(workspace_path);
+
+        // Create workspace root
+        std::fs::create_dir_all(workspace).map_err(|e| {
+            GlspError::ToolExecution(format!("Failed to create workspace directory: {e}"))
         })?;
 
-        let wit_content = component
-            .wit_interfaces
-            .clone()
-            .unwrap_or_else(|| "// No WIT content available for this component".to_string());
+        // Create subdirectories
+        let wasm_dir = workspace.join("wasm-components");
+        let diagrams_dir = workspace.join("diagrams");
 
-        Ok(ReadResourceResult {
-            contents: vec![ResourceContents {
-                uri: format!("wasm://component/{component_name}/wit/raw"),
-                mime_type: Some("text/plain".to_string()),
-                text: Some(wit_content),
-                blob: None,
-            }],
+        std::fs::create_dir_all(&wasm_dir).map_err(|e| {
+            GlspError::ToolExecution(format!("Failed to create wasm-components directory: {e}"))
+        })?;
+        std::fs::create_dir_all(&diagrams_dir).map_err(|e| {
+            GlspError::ToolExecution(format!("Failed to create diagrams directory: {e}"))
+        })?;
+
+        Ok(CallToolResult {
+            content: vec![Content::text(format!(
+                "Successfully created workspace structure:\n- {}\n- {}\n- {}",
+                workspace_path,
+                wasm_dir.display(),
+                diagrams_dir.display()
+            ))],
+            is_error: Some(false),
         })
     }
 
-    pub async fn list_prompts(
+    /// Parse the optional `updatedSince` RFC 3339 filter argument
+    fn updated_since_arg(
+        args: &serde_json::Value,
+    ) -> std::result::Result<Option<chrono::DateTime<chrono::Utc>>, GlspError> {
+        Self::timestamp_arg(args, "updatedSince")
+    }
+
+    /// Optional RFC 3339 timestamp argument
+    fn timestamp_arg(
+        args: &serde_json::Value,
+        key: &str,
+    ) -> std::result::Result<Option<chrono::DateTime<chrono::Utc>>, GlspError> {
+        args[key]
+            .as_str()
+            .map(|raw| {
+                chrono::DateTime::parse_from_rfc3339(raw)
+                    .map(|t| t.with_timezone(&chrono::Utc))
+                    .map_err(|e| GlspError::ToolExecution(format!("Invalid {key}: {e}")))
+            })
+            .transpose()
+    }
+
+    async fn get_diagram(
         &self,
-        _request: PaginatedRequestParam,
-    ) -> std::result::Result<ListPromptsResult, GlspError> {
-        // Return empty prompts for now
-        Ok(ListPromptsResult {
-            prompts: vec![],
-            next_cursor: None,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+
+        let requested: Option<Vec<&str>> = args["fields"]
+            .as_array()
+            .map(|fields| fields.iter().filter_map(|f| f.as_str()).collect());
+        let (fields, unknown) = match &requested {
+            Some(requested) => {
+                let (fields, unknown) = partition_fields(requested);
+                (Some(fields), unknown)
+            }
+            None => (None, Vec::new()),
+        };
+        if !unknown.is_empty() {
+            warn!(
+                "get_diagram ignoring unknown fields: {}",
+                unknown.join(", ")
+            );
+        }
+
+        let mut result = if !args["pageSize"].is_null() || !args["cursor"].is_null() {
+            self.get_diagram_page(&args, diagram_id, fields.as_deref())
+                .await?
+        } else {
+            let models = self.models.lock().await;
+            let diagram = models
+                .get(diagram_id)
+                .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+            match &fields {
+                Some(fields) => project_diagram(diagram, fields),
+                None => serde_json::to_value(diagram)?,
+            }
+        };
+
+        if let Some(diagram) = self.models.lock().await.get(diagram_id) {
+            result["viewport"] = json!(diagram.viewport);
+            result["contentBounds"] = json!(content_bounds(diagram));
+            let mut subdiagrams: Vec<_> = diagram
+                .elements
+                .values()
+                .filter_map(|e| {
+                    subdiagram_link(e).map(|linked| json!({"nodeId": e.id, "diagramId": linked}))
+                })
+                .collect();
+            subdiagrams.sort_by(|a, b| a["nodeId"].as_str().cmp(&b["nodeId"].as_str()));
+            result["subdiagrams"] = json!(subdiagrams);
+            let mut hyperedges: Vec<_> = diagram
+                .elements
+                .values()
+                .filter(|e| is_hyperedge(e))
+                .map(|e| {
+                    json!({
+                        "id": e.id,
+                        "sources": e.sources,
+                        "targets": e.targets,
+                        "label": e.label,
+                        "directed": is_directed(e),
+                    })
+                })
+                .collect();
+            hyperedges.sort_by(|a, b| a["id"].as_str().cmp(&b["id"].as_str()));
+            result["hyperedges"] = json!(hyperedges);
+            result["parentDiagramId"] = json!(diagram.metadata.get(PARENT_DIAGRAM_KEY));
+            result["typeStyles"] = json!(type_styles(diagram));
+            result["readOnly"] = json!(diagram.is_read_only());
+        }
+        result["elementLocks"] = json!(self.locks.lock().await.element_locks(diagram_id));
+
+        if !unknown.is_empty() {
+            result["warnings"] = json!(unknown
+                .iter()
+                .map(|f| format!("Unknown field '{f}' ignored"))
+                .collect::<Vec<_>>());
+        }
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
+            is_error: Some(false),
         })
     }
 
-    pub async fn get_prompt(
+    /// One page of a diagram's elements, served from a snapshot taken at the first page
+    async fn get_diagram_page(
         &self,
-        request: GetPromptRequestParam,
-    ) -> std::result::Result<GetPromptResult, GlspError> {
-        Err(GlspError::NotImplemented(format!(
-            "Prompt not found: {}",
-            request.name
-        )))
+        args: &serde_json::Value,
+        diagram_id: &str,
+        fields: Option<&[&str]>,
+    ) -> std::result::Result<serde_json::Value, GlspError> {
+        let page_size = args["pageSize"]
+            .as_u64()
+            .map_or(DEFAULT_PAGE_SIZE, |n| n as usize)
+            .clamp(1, MAX_PAGE_SIZE);
+
+        let mut snapshots = self.page_snapshots.lock().await;
+        let cursor = match args["cursor"].as_str() {
+            Some(cursor) => PageCursor::decode(cursor).map_err(GlspError::ToolExecution)?,
+            None => {
+                let diagram = self
+                    .models
+                    .lock()
+                    .await
+                    .get(diagram_id)
+                    .cloned()
+                    .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+                PageCursor {
+                    snapshot_id: snapshots.insert(diagram),
+                    offset: 0,
+                }
+            }
+        };
+
+        let snapshot = snapshots.get(&cursor.snapshot_id).ok_or_else(|| {
+            GlspError::ToolExecution(
+                "Cursor expired or unknown; restart from the first page".to_string(),
+            )
+        })?;
+        if snapshot.diagram.id != diagram_id {
+            return Err(GlspError::ToolExecution(format!(
+                "Cursor does not belong to diagram {diagram_id}"
+            )));
+        }
+
+        let render = |element: &crate::model::ModelElement| match fields {
+            Some(fields) => Ok(project_element(element, fields)),
+            None => serde_json::to_value(element),
+        };
+        let (ids, next_offset) = snapshot.page(cursor.offset, page_size);
+        let elements = ids
+            .iter()
+            .map(|id| render(&snapshot.diagram.elements[id]))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut result = json!({
+            "diagramId": diagram_id,
+            "revision": snapshot.diagram.revision,
+            "totalElements": snapshot.order.len(),
+            "offset": cursor.offset,
+            "elements": elements,
+            "nextCursor": next_offset.map(|offset| PageCursor {
+                snapshot_id: cursor.snapshot_id.clone(),
+                offset,
+            }
+            .encode()),
+        });
+        if cursor.offset == 0 {
+            let diagram = &snapshot.diagram;
+            result["diagram"] = json!({
+                "id": diagram.id,
+                "name": diagram.name,
+                "diagram_type": diagram.diagram_type,
+                "root": render(&diagram.root)?,
+            });
+        }
+
+        if next_offset.is_none() {
+            snapshots.remove(&cursor.snapshot_id);
+        }
+        Ok(result)
     }
-}
 
-impl GlspBackend {
-    // Tool implementation methods
-    async fn create_diagram(
+    async fn list_diagrams(
         &self,
         args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
-        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
-        let diagram_type = args["diagramType"]
-            .as_str()
-            .ok_or_else(|| GlspError::ToolExecution("Missing diagramType".to_string()))?;
-        let name = args["name"].as_str().unwrap_or("Untitled Diagram");
-
-        let mut diagram = DiagramModel::new(diagram_type);
-        diagram.name = name.to_string();
-        let diagram_id = diagram.id.clone();
+        let args = args.unwrap_or_default();
+        let updated_since = Self::updated_since_arg(&args)?;
+        let tag = args["tag"].as_str();
 
-        // Save to memory
-        let mut models = self.models.lock().await;
-        models.insert(diagram_id.clone(), diagram.clone());
-        drop(models); // Release the lock before saving to disk
+        let models = self.models.lock().await;
+        let mut diagrams: Vec<&DiagramModel> = models
+            .values()
+            .filter(|d| updated_since.is_none_or(|since| d.updated_at >= since))
+            .filter(|d| tag.is_none_or(|t| d.has_tag(t)))
+            .collect();
+        diagrams.sort_by_key(|d| std::cmp::Reverse(d.updated_at));
 
-        // Save to disk
-        if let Err(e) = self.save_diagram(&diagram_id).await {
-            error!("Failed to save new diagram to disk: {e}");
-        }
+        let list: Vec<serde_json::Value> = diagrams
+            .iter()
+            .map(|d| {
+                json!({
+                    "id": d.id,
+                    "name": d.name,
+                    "diagramType": d.diagram_type,
+                    "createdAt": d.created_at,
+                    "updatedAt": d.updated_at,
+                    "tags": d.tags,
+                    "readOnly": d.is_read_only(),
+                    "elementCount": d.get_all_element_ids().len()
+                })
+            })
+            .collect();
 
         Ok(CallToolResult {
-            content: vec![Content::text(format!(
-                "Created diagram '{name}' with ID: {diagram_id}"
-            ))],
+            content: vec![Content::text(serde_json::to_string_pretty(
+                &json!({ "diagrams": list }),
+            )?)],
             is_error: Some(false),
         })
     }
 
-    async fn delete_diagram(
+    async fn set_diagram_metadata(
         &self,
         args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
@@ -1658,44 +7883,41 @@ impl GlspBackend {
         let diagram_id = args["diagramId"]
             .as_str()
             .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let metadata = args["metadata"]
+            .as_object()
+            .ok_or_else(|| GlspError::ToolExecution("Missing metadata object".to_string()))?;
 
-        // Get the diagram name before deletion for the response
-        let diagram_name = {
-            let models = self.models.lock().await;
-            models.get(diagram_id).map(|d| d.name.clone())
-        };
-
-        // Remove from memory
         let mut models = self.models.lock().await;
-        let removed = models.remove(diagram_id);
-        drop(models); // Release the lock before filesystem operations
-
-        if removed.is_none() {
-            return Err(GlspError::ToolExecution(format!(
-                "Diagram not found: {diagram_id}"
-            )));
+        let diagram = models
+            .get_mut(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+        if metadata.get(ACYCLIC_KEY) == Some(&json!(true)) {
+            let cycles = find_cycles(diagram);
+            if !cycles.is_empty() {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                        "error": format!("Diagram has cycles and cannot be marked {ACYCLIC_KEY}; remove them first"),
+                        "cycles": cycles,
+                    }))?)],
+                    is_error: Some(true),
+                });
+            }
         }
+        diagram.merge_metadata(metadata);
+        let result = json!({ "diagramId": diagram_id, "metadata": diagram.metadata });
+        drop(models); // Release the lock before saving
 
-        // Delete from disk using persistence manager
-        let name_for_deletion = diagram_name.as_deref().unwrap_or("Unknown");
-        if let Err(e) = self.delete_diagram_files(name_for_deletion).await {
-            error!("Failed to delete diagram files from disk: {e}");
-            return Err(GlspError::ToolExecution(format!(
-                "Failed to delete diagram files: {e}"
-            )));
+        if let Err(e) = self.save_diagram(diagram_id).await {
+            error!("Failed to save diagram after updating metadata: {}", e);
         }
 
-        info!("Deleted diagram '{name_for_deletion}' (ID: {diagram_id})");
-
         Ok(CallToolResult {
-            content: vec![Content::text(format!(
-                "Successfully deleted diagram '{name_for_deletion}' (ID: {diagram_id})"
-            ))],
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
             is_error: Some(false),
         })
     }
 
-    async fn create_node(
+    async fn set_diagram_readonly(
         &self,
         args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
@@ -1703,54 +7925,39 @@ impl GlspBackend {
         let diagram_id = args["diagramId"]
             .as_str()
             .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
-        let node_type = args["nodeType"]
-            .as_str()
-            .ok_or_else(|| GlspError::ToolExecution("Missing nodeType".to_string()))?;
-
-        let position = Position {
-            x: args["position"]["x"]
-                .as_f64()
-                .ok_or_else(|| GlspError::ToolExecution("Missing position.x".to_string()))?,
-            y: args["position"]["y"]
-                .as_f64()
-                .ok_or_else(|| GlspError::ToolExecution("Missing position.y".to_string()))?,
-        };
-
-        let label = args["label"].as_str().map(|s| s.to_string());
+        let read_only = args["readOnly"]
+            .as_bool()
+            .ok_or_else(|| GlspError::ToolExecution("Missing readOnly".to_string()))?;
 
         let mut models = self.models.lock().await;
         let diagram = models
             .get_mut(diagram_id)
             .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
-
-        let mut node = Node::new(node_type, position, label);
-        let node_id = node.base.id.clone();
-
-        // Add custom properties if provided
-        if let Some(properties) = args["properties"].as_object() {
-            for (key, value) in properties {
-                node.base.properties.insert(key.clone(), value.clone());
-            }
+        if read_only {
+            diagram
+                .metadata
+                .insert(READ_ONLY_KEY.to_string(), json!(true));
+        } else {
+            diagram.metadata.remove(READ_ONLY_KEY);
         }
-
-        diagram.add_element(node.base);
-        diagram.add_child_to_root(&node_id);
+        diagram.updated_at = chrono::Utc::now();
         drop(models); // Release the lock before saving
 
-        // Save to disk
         if let Err(e) = self.save_diagram(diagram_id).await {
-            error!("Failed to save diagram after creating node: {}", e);
+            error!(
+                "Failed to save diagram after changing its read-only flag: {}",
+                e
+            );
         }
 
+        let result = json!({ "diagramId": diagram_id, "readOnly": read_only });
         Ok(CallToolResult {
-            content: vec![Content::text(format!(
-                "Created {node_type} node with ID: {node_id}"
-            ))],
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
             is_error: Some(false),
         })
     }
 
-    async fn create_edge(
+    async fn set_type_style(
         &self,
         args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
@@ -1758,79 +7965,47 @@ impl GlspBackend {
         let diagram_id = args["diagramId"]
             .as_str()
             .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
-        let edge_type = args["edgeType"]
+        let element_type = args["elementType"]
             .as_str()
-            .ok_or_else(|| GlspError::ToolExecution("Missing edgeType".to_string()))?;
-        let source_id = args["sourceId"]
-            .as_str()
-            .ok_or_else(|| GlspError::ToolExecution("Missing sourceId".to_string()))?;
-        let target_id = args["targetId"]
-            .as_str()
-            .ok_or_else(|| GlspError::ToolExecution("Missing targetId".to_string()))?;
-
-        let label = args["label"].as_str().map(|s| s.to_string());
+            .filter(|t| !t.trim().is_empty())
+            .ok_or_else(|| GlspError::ToolExecution("Missing elementType".to_string()))?;
+        let style = match &args["style"] {
+            serde_json::Value::Null => None,
+            style => match serde_json::from_value::<TypeStyle>(style.clone())
+                .map_err(|e| format!("Invalid style: {e}"))
+                .and_then(|s| s.validate().map(|_| s))
+            {
+                Ok(style) => Some(style),
+                Err(message) => {
+                    return Ok(CallToolResult {
+                        content: vec![Content::text(message)],
+                        is_error: Some(true),
+                    })
+                }
+            },
+        };
 
         let mut models = self.models.lock().await;
         let diagram = models
             .get_mut(diagram_id)
             .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
-
-        // Verify source and target exist
-        if !diagram.elements.contains_key(source_id) {
-            return Ok(CallToolResult {
-                content: vec![Content::text(format!(
-                    "Source element {source_id} not found"
-                ))],
-                is_error: Some(true),
-            });
-        }
-
-        if !diagram.elements.contains_key(target_id) {
-            return Ok(CallToolResult {
-                content: vec![Content::text(format!(
-                    "Target element {target_id} not found"
-                ))],
-                is_error: Some(true),
-            });
-        }
-
-        let edge = Edge::new(
-            edge_type,
-            source_id.to_string(),
-            target_id.to_string(),
-            label,
-        );
-        let edge_id = edge.base.id.clone();
-
-        // Convert Edge to ModelElement with sourceId and targetId in properties
-        let mut edge_element = edge.base;
-        edge_element.properties.insert(
-            "sourceId".to_string(),
-            serde_json::Value::String(source_id.to_string()),
-        );
-        edge_element.properties.insert(
-            "targetId".to_string(),
-            serde_json::Value::String(target_id.to_string()),
-        );
-
-        diagram.add_element(edge_element);
-        diagram.add_child_to_root(&edge_id);
+        set_type_style(diagram, element_type, style);
+        diagram.revision += 1;
+        diagram.updated_at = chrono::Utc::now();
+        let result = json!({ "diagramId": diagram_id, "typeStyles": type_styles(diagram) });
         drop(models); // Release the lock before saving
 
-        // Save to disk
         if let Err(e) = self.save_diagram(diagram_id).await {
-            error!("Failed to save diagram after creating edge: {}", e);
+            error!("Failed to save diagram after setting type style: {}", e);
         }
 
         Ok(CallToolResult {
-            content: vec![Content::text(format!(
-                "Created {edge_type} edge with ID: {edge_id}"
-            ))],
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
             is_error: Some(false),
         })
     }
 
-    async fn delete_element(
+    async fn set_viewport(
         &self,
         args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
@@ -1838,39 +8013,42 @@ impl GlspBackend {
         let diagram_id = args["diagramId"]
             .as_str()
             .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
-        let element_id = args["elementId"]
-            .as_str()
-            .ok_or_else(|| GlspError::ToolExecution("Missing elementId".to_string()))?;
+        let viewport = match serde_json::from_value::<Viewport>(args["viewport"].clone())
+            .map_err(|e| format!("Invalid viewport: {e}"))
+            .and_then(|v| v.validate().map(|_| v))
+        {
+            Ok(viewport) => viewport,
+            Err(message) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(message)],
+                    is_error: Some(true),
+                })
+            }
+        };
 
         let mut models = self.models.lock().await;
         let diagram = models
             .get_mut(diagram_id)
             .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+        diagram.viewport = Some(viewport);
+        let result = json!({
+            "diagramId": diagram_id,
+            "viewport": diagram.viewport,
+            "contentBounds": content_bounds(diagram),
+        });
+        drop(models); // Release the lock before saving
 
-        match diagram.remove_element(element_id) {
-            Some(_) => {
-                drop(models); // Release the lock before saving
-
-                // Save to disk
-                if let Err(e) = self.save_diagram(diagram_id).await {
-                    error!("Failed to save diagram after deleting element: {}", e);
-                }
-
-                Ok(CallToolResult {
-                    content: vec![Content::text(format!(
-                        "Deleted element with ID: {element_id}"
-                    ))],
-                    is_error: Some(false),
-                })
-            }
-            None => Ok(CallToolResult {
-                content: vec![Content::text(format!("Element {element_id} not found"))],
-                is_error: Some(true),
-            }),
+        if let Err(e) = self.save_diagram(diagram_id).await {
+            error!("Failed to save diagram after setting viewport: {}", e);
         }
+
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
+            is_error: Some(false),
+        })
     }
 
-    async fn update_element(
+    async fn add_diagram_tags(
         &self,
         args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
@@ -1878,50 +8056,29 @@ impl GlspBackend {
         let diagram_id = args["diagramId"]
             .as_str()
             .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
-        let element_id = args["elementId"]
-            .as_str()
-            .ok_or_else(|| GlspError::ToolExecution("Missing elementId".to_string()))?;
+        let tags = args["tags"]
+            .as_array()
+            .ok_or_else(|| GlspError::ToolExecution("Missing tags array".to_string()))?;
 
         let mut models = self.models.lock().await;
         let diagram = models
             .get_mut(diagram_id)
             .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
-
-        let element = diagram
-            .get_element_mut(element_id)
-            .ok_or_else(|| GlspError::ToolExecution("Element not found".to_string()))?;
-
-        if let Some(properties) = args["properties"].as_object() {
-            for (key, value) in properties {
-                element.properties.insert(key.clone(), value.clone());
-            }
-        }
-
-        if let Some(position) = args["position"].as_object() {
-            if let (Some(x), Some(y)) = (position["x"].as_f64(), position["y"].as_f64()) {
-                if let Some(bounds) = &mut element.bounds {
-                    bounds.x = x;
-                    bounds.y = y;
-                }
-            }
-        }
-
+        let added = diagram.add_tags(tags.iter().filter_map(|t| t.as_str()));
+        let result = json!({ "diagramId": diagram_id, "added": added, "tags": diagram.tags });
         drop(models); // Release the lock before saving
 
-        // Save to disk
         if let Err(e) = self.save_diagram(diagram_id).await {
-            error!("Failed to save diagram after updating element: {}", e);
+            error!("Failed to save diagram after adding tags: {}", e);
         }
 
         Ok(CallToolResult {
-            content: vec![Content::text(format!(
-                "Updated element with ID: {element_id}"
-            ))],
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
             is_error: Some(false),
         })
     }
 
-    async fn apply_layout(
+    async fn find_nodes(
         &self,
         args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
@@ -1929,45 +8086,52 @@ impl GlspBackend {
         let diagram_id = args["diagramId"]
             .as_str()
             .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
-        let algorithm = args["algorithm"]
-            .as_str()
-            .ok_or_else(|| GlspError::ToolExecution("Missing algorithm".to_string()))?;
+        let node_type = args["nodeType"].as_str();
+        let label = args["label"].as_str().map(str::to_lowercase);
+        let updated_since = Self::updated_since_arg(&args)?;
 
-        let mut models = self.models.lock().await;
+        let models = self.models.lock().await;
         let diagram = models
-            .get_mut(diagram_id)
+            .get(diagram_id)
             .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
 
-        // Simple layout implementation
-        match algorithm {
-            "grid" => Self::apply_grid_layout(diagram),
-            "hierarchical" => Self::apply_hierarchical_layout(diagram),
-            _ => {
-                return Ok(CallToolResult {
-                    content: vec![Content::text(format!(
-                        "Layout algorithm '{algorithm}' not implemented yet"
-                    ))],
-                    is_error: Some(true),
-                });
-            }
-        }
-
-        drop(models); // Release the lock before saving
+        let matches = diagram
+            .elements
+            .values()
+            .filter(|e| e.id != diagram.root.id && !crate::operations::is_edge(e))
+            .filter(|e| node_type.is_none_or(|t| e.element_type.as_str() == t))
+            .filter(|e| {
+                label.as_ref().is_none_or(|needle| {
+                    e.label
+                        .as_ref()
+                        .is_some_and(|l| l.to_lowercase().contains(needle))
+                })
+            })
+            .filter(|e| updated_since.is_none_or(|since| e.updated_at.is_some_and(|t| t >= since)));
 
-        // Save to disk
-        if let Err(e) = self.save_diagram(diagram_id).await {
-            error!("Failed to save diagram after applying layout: {}", e);
+        // Streamed nodes go out as they are found, in no particular order
+        if streaming::is_active() {
+            streaming::start(json!({ "diagramId": diagram_id }));
+            let count = matches.inspect(|node| streaming::item(node)).count();
+            return Ok(CallToolResult {
+                content: vec![Content::text(serde_json::to_string_pretty(
+                    &json!({ "count": count, "streamed": true }),
+                )?)],
+                is_error: Some(false),
+            });
         }
 
+        let mut nodes: Vec<&crate::model::ModelElement> = matches.collect();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
         Ok(CallToolResult {
-            content: vec![Content::text(format!(
-                "Applied {algorithm} layout to diagram {diagram_id}"
-            ))],
+            content: vec![Content::text(serde_json::to_string_pretty(
+                &json!({ "count": nodes.len(), "nodes": nodes }),
+            )?)],
             is_error: Some(false),
         })
     }
 
-    async fn export_diagram(
+    async fn get_edges_for_node(
         &self,
         args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
@@ -1975,171 +8139,183 @@ impl GlspBackend {
         let diagram_id = args["diagramId"]
             .as_str()
             .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
-        let format = args["format"]
-            .as_str()
-            .ok_or_else(|| GlspError::ToolExecution("Missing format".to_string()))?;
+        let node_id: &str = &Self::element_id_arg(&args, "nodeId")?;
 
         let models = self.models.lock().await;
         let diagram = models
             .get(diagram_id)
             .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
 
-        match format {
-            "json" => {
-                let json_str = serde_json::to_string_pretty(diagram).map_err(|e| {
-                    GlspError::ToolExecution(format!("JSON serialization failed: {e}"))
-                })?;
-                Ok(CallToolResult {
-                    content: vec![Content::text(format!(
-                        "Exported diagram as JSON:\\n{json_str}"
-                    ))],
-                    is_error: Some(false),
-                })
-            }
-            "svg" => {
-                let svg = Self::generate_svg(diagram);
-                Ok(CallToolResult {
-                    content: vec![Content::text(format!("Exported diagram as SVG:\\n{svg}"))],
-                    is_error: Some(false),
-                })
-            }
-            _ => Ok(CallToolResult {
-                content: vec![Content::text(format!(
-                    "Export format '{format}' not supported yet"
-                ))],
+        if !diagram.elements.contains_key(node_id) {
+            return Ok(CallToolResult {
+                content: vec![Content::text(format!("Node {node_id} not found"))],
                 is_error: Some(true),
-            }),
+            });
         }
-    }
 
-    // Selection tool implementations - simplified for now
-    async fn select_elements(
-        &self,
-        _args: Option<serde_json::Value>,
-    ) -> std::result::Result<CallToolResult, GlspError> {
+        let edges = crate::operations::edges_for_node(diagram, node_id);
+        let result = json!({
+            "nodeId": node_id,
+            "incoming": edges.incoming,
+            "outgoing": edges.outgoing
+        });
+
         Ok(CallToolResult {
-            content: vec![Content::text("Selection functionality not yet implemented")],
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
             is_error: Some(false),
         })
     }
 
-    async fn select_all(
+    async fn set_compartment_visibility(
         &self,
-        _args: Option<serde_json::Value>,
+        args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let node_id: &str = &Self::element_id_arg(&args, "nodeId")?;
+        let compartments = args["compartments"]
+            .as_object()
+            .ok_or_else(|| GlspError::ToolExecution("Missing compartments".to_string()))?;
+
+        let mut models = self.models.lock().await;
+        let diagram = models
+            .get_mut(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+        let element = diagram
+            .elements
+            .get_mut(node_id)
+            .ok_or_else(|| GlspError::ToolExecution(format!("Node {node_id} not found")))?;
+
+        let current = CompartmentVisibility::of(element);
+        let visibility = CompartmentVisibility {
+            attributes: compartments
+                .get("attributes")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(current.attributes),
+            methods: compartments
+                .get("methods")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(current.methods),
+        };
+        let bounds = set_compartment_visibility(element, visibility);
+        element.touch();
+        diagram.revision += 1;
+        diagram.updated_at = chrono::Utc::now();
+        history::touched(diagram_id, [node_id]);
+        drop(models); // Release the lock before saving
+
+        if let Err(e) = self.save_diagram(diagram_id).await {
+            error!("Failed to save diagram after changing compartments: {}", e);
+        }
+
+        let result = json!({
+            "nodeId": node_id,
+            "compartments": visibility,
+            "bounds": bounds
+        });
+
         Ok(CallToolResult {
-            content: vec![Content::text(
-                "Select all functionality not yet implemented",
-            )],
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
             is_error: Some(false),
         })
     }
 
-    async fn clear_selection(
+    /// Apply a change to the task hierarchy of a diagram and save it; the
+    /// change's error becomes an error result
+    async fn change_hierarchy<T>(
         &self,
-        _args: Option<serde_json::Value>,
+        diagram_id: &str,
+        change: impl FnOnce(&mut DiagramModel) -> std::result::Result<T, HierarchyError>,
+        describe: impl FnOnce(T) -> serde_json::Value,
     ) -> std::result::Result<CallToolResult, GlspError> {
+        let mut models = self.models.lock().await;
+        let diagram = models
+            .get_mut(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
+        let outcome = match change(diagram) {
+            Ok(outcome) => outcome,
+            Err(HierarchyError::ContainmentCycle(cycle)) => {
+                return Err(GlspError::ContainmentCycle { cycle })
+            }
+            Err(HierarchyError::Invalid(message)) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(message)],
+                    is_error: Some(true),
+                })
+            }
+        };
+        diagram.revision += 1;
+        diagram.updated_at = chrono::Utc::now();
+        drop(models); // Release the lock before saving
+
+        if let Err(e) = self.save_diagram(diagram_id).await {
+            error!(
+                "Failed to save diagram after changing the task hierarchy: {}",
+                e
+            );
+        }
+
         Ok(CallToolResult {
-            content: vec![Content::text(
-                "Clear selection functionality not yet implemented",
-            )],
+            content: vec![Content::text(serde_json::to_string_pretty(&describe(
+                outcome,
+            ))?)],
             is_error: Some(false),
         })
     }
 
-    async fn get_selection(
+    async fn add_subtask(
         &self,
-        _args: Option<serde_json::Value>,
+        args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
-        Ok(CallToolResult {
-            content: vec![Content::text(
-                "Get selection functionality not yet implemented",
-            )],
-            is_error: Some(false),
-        })
-    }
-
-    // WASM component implementations
-    async fn scan_wasm_components(&self) -> std::result::Result<CallToolResult, GlspError> {
-        // Trigger rescan and get components from the wasm watcher
-        let wasm_watcher = self.wasm_watcher.lock().await;
-        let components = wasm_watcher.get_components();
-        let available = components.iter().filter(|c| c.file_exists).count();
-        let missing = components.len() - available;
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let parent_id = Self::element_id_arg(&args, "parentId")?;
+        let node_id = Self::element_id_arg(&args, "nodeId")?;
 
-        // Convert components to JSON format expected by the client
-        let components_json: Vec<_> = components
-            .iter()
-            .map(|c| {
+        self.change_hierarchy(
+            diagram_id,
+            |diagram| add_subtask(diagram, &parent_id, &node_id),
+            |previous| {
                 json!({
-                    "name": c.name,
-                    "path": c.path,
-                    "fileExists": c.file_exists,
-                    "lastSeen": c.last_seen.map(|dt| dt.to_rfc3339()),
-                    "metadata": c.metadata,
-                    "interfaces": c.interfaces
+                    "parentId": parent_id,
+                    "nodeId": node_id,
+                    "previousParentId": previous
                 })
-            })
-            .collect();
-
-        let result = json!({
-            "components": components_json,
-            "summary": {
-                "total": components.len(),
-                "available": available,
-                "missing": missing
-            }
-        });
-
-        Ok(CallToolResult {
-            content: vec![Content::text(result.to_string())],
-            is_error: Some(false),
-        })
+            },
+        )
+        .await
     }
 
-    async fn check_wasm_component_status(
+    async fn add_to_container(
         &self,
         args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
         let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
-        let component_name = args["componentName"]
+        let diagram_id = args["diagramId"]
             .as_str()
-            .ok_or_else(|| GlspError::ToolExecution("Missing componentName".to_string()))?;
-
-        let wasm_watcher = self.wasm_watcher.lock().await;
-
-        match wasm_watcher.get_component(component_name) {
-            Some(component) => {
-                let status = json!({
-                    "name": component.name,
-                    "path": component.path,
-                    "fileExists": component.file_exists,
-                    "status": if component.file_exists { "available" } else { "missing" },
-                    "lastSeen": component.last_seen,
-                    "removedAt": component.removed_at,
-                    "interfaces": component.interfaces.len(),
-                    "description": component.description
-                });
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let container_id = Self::element_id_arg(&args, "containerId")?;
+        let node_id = Self::element_id_arg(&args, "nodeId")?;
 
-                Ok(CallToolResult {
-                    content: vec![Content::text(
-                        serde_json::to_string_pretty(&status).map_err(|e| {
-                            GlspError::ToolExecution(format!("JSON serialization failed: {e}"))
-                        })?,
-                    )],
-                    is_error: Some(false),
+        self.change_hierarchy(
+            diagram_id,
+            |diagram| add_to_container(diagram, &container_id, &node_id),
+            |previous| {
+                json!({
+                    "containerId": container_id,
+                    "nodeId": node_id,
+                    "previousContainerId": previous
                 })
-            }
-            None => Ok(CallToolResult {
-                content: vec![Content::text(format!(
-                    "WASM component '{component_name}' not found"
-                ))],
-                is_error: Some(true),
-            }),
-        }
+            },
+        )
+        .await
     }
 
-    async fn save_diagram_tool(
+    async fn remove_subtask(
         &self,
         args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
@@ -2147,22 +8323,38 @@ impl GlspBackend {
         let diagram_id = args["diagramId"]
             .as_str()
             .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let node_id = Self::element_id_arg(&args, "nodeId")?;
+
+        self.change_hierarchy(
+            diagram_id,
+            |diagram| Ok(remove_subtask(diagram, &node_id)?),
+            |previous| json!({"nodeId": node_id, "previousParentId": previous}),
+        )
+        .await
+    }
 
-        match self.save_diagram(diagram_id).await {
-            Ok(_) => Ok(CallToolResult {
-                content: vec![Content::text(format!(
-                    "Successfully saved diagram {diagram_id} to disk"
-                ))],
-                is_error: Some(false),
-            }),
-            Err(e) => Ok(CallToolResult {
-                content: vec![Content::text(format!("Failed to save diagram: {e}"))],
-                is_error: Some(true),
-            }),
-        }
+    async fn set_task_collapsed(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let node_id = Self::element_id_arg(&args, "nodeId")?;
+        let collapsed = args["collapsed"]
+            .as_bool()
+            .ok_or_else(|| GlspError::ToolExecution("Missing collapsed".to_string()))?;
+
+        self.change_hierarchy(
+            diagram_id,
+            |diagram| Ok(set_collapsed(diagram, &node_id, collapsed)?),
+            |changed| json!({"nodeId": node_id, "collapsed": collapsed, "changed": changed}),
+        )
+        .await
     }
 
-    async fn load_wasm_component(
+    async fn convert_diagram_type(
         &self,
         args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
@@ -2170,803 +8362,808 @@ impl GlspBackend {
         let diagram_id = args["diagramId"]
             .as_str()
             .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
-        let component_name = args["componentName"]
+        let target_type = args["targetType"]
             .as_str()
-            .ok_or_else(|| GlspError::ToolExecution("Missing componentName".to_string()))?;
-
-        let position = Position {
-            x: args["position"]["x"].as_f64().unwrap_or(100.0),
-            y: args["position"]["y"].as_f64().unwrap_or(100.0),
-        };
-
-        // Check if component exists and is available
-        // Use the flexible component finding method from WasmFileWatcher
-        let component = {
-            let wasm_watcher = self.wasm_watcher.lock().await;
-
-            wasm_watcher
-                .find_component_flexible(component_name)
-                .cloned()
-                .ok_or_else(|| {
-                    GlspError::ToolExecution(format!("WASM component '{component_name}' not found"))
-                })?
-        };
-
-        if !component.file_exists {
-            return Ok(CallToolResult {
-                content: vec![Content::text(format!(
-                    "Cannot load component '{component_name}': file is missing at {}",
-                    component.path
-                ))],
-                is_error: Some(true),
-            });
-        }
+            .ok_or_else(|| GlspError::ToolExecution("Missing targetType".to_string()))?;
 
-        // Get the diagram
         let mut models = self.models.lock().await;
-        let diagram = models
-            .get_mut(diagram_id)
-            .ok_or_else(|| GlspError::ToolExecution(format!("Diagram '{diagram_id}' not found")))?;
+        let source = models
+            .get(diagram_id)
+            .ok_or_else(|| GlspError::ToolExecution("Diagram not found".to_string()))?;
 
-        // Create a WASM component node
-        let mut node = Node::new("wasm-component", position, Some(component.name.clone()));
+        let conversion = crate::operations::convert_diagram(source, target_type)
+            .map_err(GlspError::ToolExecution)?;
+        let new_id = conversion.diagram.id.clone();
+        models.insert(new_id.clone(), conversion.diagram);
+        drop(models); // Release the lock before saving
 
-        // Add component-specific properties
-        node.base
-            .properties
-            .insert("componentName".to_string(), json!(component.name));
-        node.base
-            .properties
-            .insert("componentPath".to_string(), json!(component.path));
-        node.base
-            .properties
-            .insert("description".to_string(), json!(component.description));
+        if let Err(e) = self.save_diagram(&new_id).await {
+            error!("Failed to save converted diagram: {}", e);
+        }
 
-        // Add interface data for rendering
-        node.base
-            .properties
-            .insert("interfaces".to_string(), json!(component.interfaces));
-        node.base
-            .properties
-            .insert("status".to_string(), json!("available"));
-        node.base.properties.insert(
-            "importsCount".to_string(),
-            json!(component
-                .interfaces
-                .iter()
-                .filter(|i| i.interface_type == "import")
-                .count()),
-        );
-        node.base.properties.insert(
-            "exportsCount".to_string(),
-            json!(component
-                .interfaces
-                .iter()
-                .filter(|i| i.interface_type == "export")
-                .count()),
-        );
-        node.base.properties.insert(
-            "totalFunctions".to_string(),
-            json!(component
-                .interfaces
-                .iter()
-                .map(|i| i.functions.len())
-                .sum::<usize>()),
-        );
+        let result = json!({
+            "diagramId": new_id,
+            "sourceDiagramId": diagram_id,
+            "targetType": target_type,
+            "mappedElements": conversion.mapped,
+            "unmappedElements": conversion.unmapped
+        });
 
-        let node_id = node.base.id.clone();
-        diagram.add_element(node.base);
-        diagram.add_child_to_root(&node_id);
-        drop(models); // Release the lock before saving
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
+            is_error: Some(false),
+        })
+    }
 
-        // Save to disk
-        if let Err(e) = self.save_diagram(diagram_id).await {
-            error!("Failed to save diagram after loading WASM component: {}", e);
-        }
+    async fn validate_wit(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let source = args["source"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing source".to_string()))?;
+
+        // Invalid WIT is a successful validation, not a tool failure
+        let result = match crate::operations::validate_wit(source) {
+            Ok(package) => json!({
+                "valid": true,
+                "package": package,
+                "errors": []
+            }),
+            Err(errors) => json!({
+                "valid": false,
+                "errors": errors
+            }),
+        };
 
         Ok(CallToolResult {
-            content: vec![Content::text(format!(
-                "Loaded WASM component '{component_name}' into diagram with ID: {node_id}"
-            ))],
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
             is_error: Some(false),
         })
     }
 
-    async fn refresh_wasm_interfaces(
+    async fn generate_diagram_from_wit(
         &self,
         args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
         let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
-        let diagram_id = args["diagramId"]
+        let wit_source = args["witSource"]
             .as_str()
-            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
-
-        let mut models = self.models.lock().await;
-        let diagram = models
-            .get_mut(diagram_id)
-            .ok_or_else(|| GlspError::ToolExecution(format!("Diagram '{diagram_id}' not found")))?;
-
-        let mut updated_count = 0;
-        let wasm_watcher = self.wasm_watcher.lock().await;
-
-        // Find all WASM components in the diagram
-        let mut elements_to_update = Vec::new();
-        for (element_id, element) in diagram.elements.iter() {
-            // Check if this is a WASM component
-            let is_wasm_component = match &element.element_type {
-                ElementType::Component => true,
-                ElementType::Custom(type_name) => type_name == "wasm-component",
-                _ => false,
-            };
+            .ok_or_else(|| GlspError::ToolExecution("Missing witSource".to_string()))?;
 
-            if is_wasm_component {
-                // Try to get component name from properties or label
-                if let Some(component_name) = element
-                    .properties
-                    .get("componentName")
-                    .and_then(|v| v.as_str())
-                    .or(element.label.as_deref())
-                {
-                    // Find the component using flexible name matching
-                    if let Some(component) = wasm_watcher.find_component_flexible(component_name) {
-                        elements_to_update.push((element_id.clone(), component.clone()));
-                    }
-                }
-            }
+        let mut generated =
+            crate::operations::diagram_from_wit(wit_source).map_err(GlspError::ToolExecution)?;
+        if let Some(name) = args["name"].as_str() {
+            generated.diagram.name = name.to_string();
         }
-        drop(wasm_watcher); // Release the wasm_watcher lock
-
-        // Update each WASM component with interface data
-        for (element_id, component) in elements_to_update {
-            if let Some(element) = diagram.elements.get_mut(&element_id) {
-                // Add interface data for rendering
-                element
-                    .properties
-                    .insert("interfaces".to_string(), json!(component.interfaces));
-                element.properties.insert(
-                    "status".to_string(),
-                    json!(if component.file_exists {
-                        "available"
-                    } else {
-                        "missing"
-                    }),
-                );
-                element.properties.insert(
-                    "importsCount".to_string(),
-                    json!(component
-                        .interfaces
-                        .iter()
-                        .filter(|i| i.interface_type == "import")
-                        .count()),
-                );
-                element.properties.insert(
-                    "exportsCount".to_string(),
-                    json!(component
-                        .interfaces
-                        .iter()
-                        .filter(|i| i.interface_type == "export")
-                        .count()),
-                );
-                element.properties.insert(
-                    "totalFunctions".to_string(),
-                    json!(component
-                        .interfaces
-                        .iter()
-                        .map(|i| i.functions.len())
-                        .sum::<usize>()),
-                );
-
-                updated_count += 1;
+        if let Some(client_id) = authenticated_client() {
+            for element in generated.diagram.elements.values_mut() {
+                if element.created_at.is_some() {
+                    element.created_by = Some(client_id.clone());
+                }
             }
         }
 
+        let diagram_id = generated.diagram.id.clone();
+        let name = generated.diagram.name.clone();
+        let mut models = self.models.lock().await;
+        models.insert(diagram_id.clone(), generated.diagram);
         drop(models); // Release the lock before saving
 
-        // Save to disk if we updated any components
-        if updated_count > 0 {
-            if let Err(e) = self.save_diagram(diagram_id).await {
-                error!("Failed to save diagram after refreshing interfaces: {}", e);
-            }
+        if let Err(e) = self.save_diagram(&diagram_id).await {
+            error!("Failed to save generated WIT diagram: {}", e);
         }
 
+        let result = json!({
+            "diagramId": diagram_id,
+            "name": name,
+            "interfaces": generated.interface_count,
+            "worlds": generated.world_count,
+            "edges": generated.edge_count
+        });
+
         Ok(CallToolResult {
-            content: vec![Content::text(format!("Refreshed interface data for {updated_count} WASM components in diagram {diagram_id}"))],
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
             is_error: Some(false),
         })
     }
 
-    // Layout helpers
-    fn apply_grid_layout(diagram: &mut DiagramModel) {
-        let mut x = 50.0;
-        let mut y = 50.0;
-        let spacing_x = 150.0;
-        let _spacing_y = 100.0;
-        let cols = 4;
-        let mut col = 0;
-
-        for (_, element) in diagram.elements.iter_mut() {
-            if element.element_type != ElementType::Graph && element.bounds.is_some() {
-                if let Some(bounds) = &mut element.bounds {
-                    bounds.x = x;
-                    bounds.y = y;
-                }
-
-                col += 1;
-                if col >= cols {
-                    col = 0;
-                    x = 50.0;
-                    y += 100.0;
-                } else {
-                    x += spacing_x;
-                }
-            }
-        }
-        diagram.revision += 1;
-    }
-
-    fn apply_hierarchical_layout(diagram: &mut DiagramModel) {
-        let y = 50.0;
-        let mut x = 50.0;
-        let spacing_x = 150.0;
+    async fn import_rust_types(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let source = args["source"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing source".to_string()))?;
 
-        for (_, element) in diagram.elements.iter_mut() {
-            if element.element_type != ElementType::Graph && element.bounds.is_some() {
-                if let Some(bounds) = &mut element.bounds {
-                    bounds.x = x;
-                    bounds.y = y;
-                }
-                x += spacing_x;
+        let mut generated = match crate::operations::diagram_from_rust(source) {
+            Ok(generated) => generated,
+            Err(message) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(message)],
+                    is_error: Some(true),
+                })
             }
+        };
+        if let Some(name) = args["name"].as_str() {
+            generated.diagram.name = name.to_string();
         }
-        diagram.revision += 1;
-    }
-
-    fn generate_svg(diagram: &DiagramModel) -> String {
-        let mut svg =
-            String::from(r#"<svg width="800" height="600" xmlns="http://www.w3.org/2000/svg">"#);
-
-        // Add elements
-        for element in diagram.elements.values() {
-            if element.element_type != ElementType::Graph {
-                if let Some(bounds) = &element.bounds {
-                    if element.element_type.is_node_like() {
-                        svg.push_str(&format!(
-                            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="lightblue" stroke="black" stroke-width="1"/>"#,
-                            bounds.x, bounds.y, bounds.width, bounds.height
-                        ));
-
-                        if let Some(label) = element.properties.get("label") {
-                            if let Some(label_text) = label.as_str() {
-                                svg.push_str(&format!(
-                                    r#"<text x="{}" y="{}" text-anchor="middle" dominant-baseline="middle">{}</text>"#,
-                                    bounds.x + bounds.width / 2.0,
-                                    bounds.y + bounds.height / 2.0,
-                                    label_text
-                                ));
-                            }
-                        }
-                    }
+        if let Some(client_id) = authenticated_client() {
+            for element in generated.diagram.elements.values_mut() {
+                if element.created_at.is_some() {
+                    element.created_by = Some(client_id.clone());
                 }
             }
         }
 
-        svg.push_str("</svg>");
-        svg
-    }
-
-    // Persistence helper methods
-    async fn load_all_diagrams(&self) -> std::result::Result<(), GlspError> {
-        let diagram_infos = self
-            .persistence
-            .list_diagrams()
-            .await
-            .map_err(|e| GlspError::NotImplemented(format!("Failed to list diagrams: {e}")))?;
-
+        let diagram_id = generated.diagram.id.clone();
+        let name = generated.diagram.name.clone();
         let mut models = self.models.lock().await;
+        models.insert(diagram_id.clone(), generated.diagram);
+        drop(models); // Release the lock before saving
 
-        for info in diagram_infos {
-            match self.persistence.load_diagram(&info.file_name).await {
-                Ok(diagram) => {
-                    info!("Loaded diagram '{}' from disk", info.name);
-                    models.insert(diagram.id.clone(), diagram);
-                }
-                Err(e) => {
-                    error!("Failed to load diagram '{}': {e}", info.file_name);
-                }
-            }
-        }
-
-        info!("Loaded {} diagrams from disk", models.len());
-        Ok(())
-    }
-
-    async fn save_diagram(&self, diagram_id: &str) -> std::result::Result<(), GlspError> {
-        let models = self.models.lock().await;
-
-        if let Some(diagram) = models.get(diagram_id) {
-            self.persistence
-                .save_diagram(diagram)
-                .await
-                .map_err(|e| GlspError::NotImplemented(format!("Failed to save diagram: {e}")))?;
-            info!("Saved diagram '{}' to disk", diagram.name);
-            Ok(())
-        } else {
-            Err(GlspError::NotImplemented(format!(
-                "Diagram not found: {diagram_id}"
-            )))
+        if let Err(e) = self.save_diagram(&diagram_id).await {
+            error!("Failed to save imported Rust diagram: {}", e);
         }
-    }
-
-    async fn delete_diagram_files(&self, diagram_name: &str) -> std::result::Result<(), GlspError> {
-        self.persistence
-            .delete_diagram(diagram_name)
-            .await
-            .map_err(|e| {
-                GlspError::NotImplemented(format!("Failed to delete diagram files: {e}"))
-            })?;
-        info!("Deleted diagram files for '{}'", diagram_name);
-        Ok(())
-    }
 
-    pub fn get_filesystem_watcher(&self) -> std::sync::Arc<tokio::sync::RwLock<FileSystemWatcher>> {
-        self.filesystem_watcher.clone()
-    }
+        let result = json!({
+            "diagramId": diagram_id,
+            "name": name,
+            "classes": generated.class_count,
+            "enums": generated.enum_count,
+            "interfaces": generated.interface_count,
+            "edges": generated.edge_count,
+            "warnings": generated.warnings,
+        });
 
-    pub fn get_wasm_watcher(&self) -> std::sync::Arc<tokio::sync::Mutex<WasmFileWatcher>> {
-        self.wasm_watcher.clone()
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
+            is_error: Some(false),
+        })
     }
 
-    async fn get_component_path(
+    async fn attach_file(
         &self,
         args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
         let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let name = args["name"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing name".to_string()))?;
+        let data = args["bytes"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing bytes".to_string()))?;
 
-        let component_name = args["componentName"].as_str().ok_or_else(|| {
-            GlspError::ToolExecution("Missing componentName parameter".to_string())
-        })?;
+        if !self.models.lock().await.contains_key(diagram_id) {
+            return Err(GlspError::ToolExecution(format!(
+                "Diagram not found: {diagram_id}"
+            )));
+        }
+        let bytes = match BASE64_STANDARD.decode(data) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(format!("bytes is not valid base64: {e}"))],
+                    is_error: Some(true),
+                })
+            }
+        };
 
-        // Look up the component using flexible name matching
-        let wasm_watcher = self.wasm_watcher.lock().await;
-        let component = wasm_watcher
-            .find_component_flexible(component_name)
-            .ok_or_else(|| {
-                GlspError::ToolExecution(format!("WASM component not found: {component_name}"))
-            })?;
+        let attachment = match self.attachments.attach(diagram_id, name, &bytes).await {
+            Ok(attachment) => attachment,
+            Err(AttachmentError::Io(e)) => return Err(GlspError::Io(e)),
+            Err(e) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(e.to_string())],
+                    is_error: Some(true),
+                })
+            }
+        };
+        info!(
+            "Attached '{}' ({} bytes) to diagram {diagram_id}",
+            attachment.name, attachment.size
+        );
 
         Ok(CallToolResult {
-            content: vec![Content::text(component.path.clone())],
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "diagramId": diagram_id,
+                "attachment": attachment,
+            }))?)],
             is_error: Some(false),
         })
     }
 
-    async fn get_component_wit_info(
+    async fn get_diagram_size(
         &self,
         args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
-        use crate::wasm::WitAnalyzer;
-        use std::path::PathBuf;
-
         let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
-
         let diagram_id = args["diagramId"]
             .as_str()
-            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId parameter".to_string()))?;
-
-        let element_id = args["elementId"]
-            .as_str()
-            .ok_or_else(|| GlspError::ToolExecution("Missing elementId parameter".to_string()))?;
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
 
-        // Get the diagram
         let models = self.models.lock().await;
         let diagram = models
             .get(diagram_id)
             .ok_or_else(|| GlspError::ToolExecution(format!("Diagram not found: {diagram_id}")))?;
-
-        // Get the element
-        let element = diagram
+        let (content_bytes, layout_bytes) = self.persistence.encoded_size(diagram)?;
+        let json_bytes = serde_json::to_vec(diagram)?.len();
+        let edges = diagram.elements.values().filter(|e| is_edge(e)).count();
+        let nodes = diagram
             .elements
-            .get(element_id)
-            .ok_or_else(|| GlspError::ToolExecution(format!("Element not found: {element_id}")))?;
-
-        // Check if it's a WASM component
-        let is_wasm_component = match &element.element_type {
-            ElementType::Component => true,
-            ElementType::Custom(type_name) => type_name == "wasm-component",
-            _ => false,
-        };
-
-        if !is_wasm_component {
-            return Ok(CallToolResult {
-                content: vec![Content::text(format!(
-                    "Element '{}' is not a WASM component (type: {})",
-                    element_id, element.element_type
-                ))],
-                is_error: Some(true),
-            });
-        }
-
-        // First, try to get the component file path from element properties
-        let component_path = if let Some(path_value) = element.properties.get("componentPath") {
-            path_value.as_str().map(|s| s.to_string())
-        } else {
-            None
-        };
-
-        // If we have a path, use it directly
-        let component_file_path = if let Some(path) = component_path {
-            PathBuf::from(path)
-        } else {
-            // Fallback: Try to find by component name
-            let component_name = element
-                .properties
-                .get("componentName")
-                .and_then(|v| v.as_str())
-                .or(element.properties.get("name").and_then(|v| v.as_str()))
-                .or(element.label.as_deref())
-                .ok_or_else(|| {
-                    GlspError::ToolExecution(
-                        "Component name not found in element properties".to_string(),
-                    )
-                })?;
-
-            // Look up the component in the watcher using flexible name matching
-            let wasm_watcher = self.wasm_watcher.lock().await;
-            let component = wasm_watcher
-                .find_component_flexible(component_name)
-                .ok_or_else(|| {
-                    GlspError::ToolExecution(format!(
-                        "WASM component file not found: {component_name}"
-                    ))
-                })?;
-
-            PathBuf::from(&component.path)
-        };
-
-        // Verify the file exists
-        if !component_file_path.exists() {
-            return Ok(CallToolResult {
-                content: vec![Content::text(format!(
-                    "WASM component file not found at path: {}",
-                    component_file_path.display()
-                ))],
-                is_error: Some(true),
-            });
-        }
+            .values()
+            .filter(|e| !is_edge(e) && e.element_type != ElementType::Graph)
+            .count();
+        drop(models);
 
-        // Analyze the component
-        match WitAnalyzer::analyze_component(&component_file_path).await {
-            Ok(analysis) => {
-                // Return structured WIT information suitable for properties panel
-                let wit_info = json!({
-                    "componentName": analysis.component_name,
-                    "worldName": analysis.world_name,
-                    "filePath": component_file_path.to_string_lossy(),
-                    "imports": analysis.imports.iter().map(|interface| json!({
-                        "name": interface.name,
-                        "namespace": interface.namespace,
-                        "package": interface.package,
-                        "version": interface.version,
-                        "functions": interface.functions.iter().map(|func| json!({
-                            "name": func.name,
-                            "params": func.params.iter().map(|p| json!({
-                                "name": p.name,
-                                "type": p.param_type.name
-                            })).collect::<Vec<_>>(),
-                            "results": func.results.iter().map(|r| json!({
-                                "name": r.name,
-                                "type": r.param_type.name
-                            })).collect::<Vec<_>>(),
-                            "isAsync": func.is_async
-                        })).collect::<Vec<_>>(),
-                        "types": interface.types.iter().map(|t| json!({
-                            "name": t.name,
-                            "definition": format!("{:?}", t.type_def)
-                        })).collect::<Vec<_>>()
-                    })).collect::<Vec<_>>(),
-                    "exports": analysis.exports.iter().map(|interface| json!({
-                        "name": interface.name,
-                        "namespace": interface.namespace,
-                        "package": interface.package,
-                        "version": interface.version,
-                        "functions": interface.functions.iter().map(|func| json!({
-                            "name": func.name,
-                            "params": func.params.iter().map(|p| json!({
-                                "name": p.name,
-                                "type": p.param_type.name
-                            })).collect::<Vec<_>>(),
-                            "results": func.results.iter().map(|r| json!({
-                                "name": r.name,
-                                "type": r.param_type.name
-                            })).collect::<Vec<_>>(),
-                            "isAsync": func.is_async
-                        })).collect::<Vec<_>>(),
-                        "types": interface.types.iter().map(|t| json!({
-                            "name": t.name,
-                            "definition": format!("{:?}", t.type_def)
-                        })).collect::<Vec<_>>()
-                    })).collect::<Vec<_>>(),
-                    "dependencies": analysis.dependencies.iter().map(|dep| json!({
-                        "package": dep.package,
-                        "version": dep.version,
-                        "interfaces": dep.interfaces
-                    })).collect::<Vec<_>>(),
-                    "summary": {
-                        "importsCount": analysis.imports.len(),
-                        "exportsCount": analysis.exports.len(),
-                        "totalFunctions": analysis.imports.iter()
-                            .chain(analysis.exports.iter())
-                            .map(|i| i.functions.len())
-                            .sum::<usize>(),
-                        "typesCount": analysis.types.len(),
-                        "dependenciesCount": analysis.dependencies.len()
-                    }
-                });
+        let attachments = self.attachments.list(diagram_id).await?;
 
-                Ok(CallToolResult {
-                    content: vec![Content::text(
-                        serde_json::to_string_pretty(&wit_info).map_err(|e| {
-                            GlspError::ToolExecution(format!("Failed to serialize WIT info: {e}"))
-                        })?,
-                    )],
-                    is_error: Some(false),
-                })
-            }
-            Err(error) => Ok(CallToolResult {
-                content: vec![Content::text(format!(
-                    "Failed to analyze component WIT interfaces: {error}"
-                ))],
-                is_error: Some(true),
-            }),
-        }
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "diagramId": diagram_id,
+                "format": self.persistence.format().as_str(),
+                "serializedBytes": content_bytes + layout_bytes,
+                "contentBytes": content_bytes,
+                "layoutBytes": layout_bytes,
+                "jsonBytes": json_bytes,
+                "nodeCount": nodes,
+                "edgeCount": edges,
+                "attachmentCount": attachments.len(),
+                "attachmentBytes": attachments.iter().map(|a| a.size).sum::<u64>(),
+            }))?)],
+            is_error: Some(false),
+        })
     }
 
-    /// Debug tool to analyze WIT interfaces for a specific component file
-    async fn debug_wit_analysis(
+    async fn get_history(
         &self,
         args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
-        use crate::wasm::WitAnalyzer;
-        use std::path::PathBuf;
-
         let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let limit = args["limit"].as_u64().unwrap_or(50).max(1) as usize;
+        let element_id = if args["elementId"].is_null() {
+            None
+        } else {
+            Some(Self::element_id_arg(&args, "elementId")?)
+        };
+        let filter = HistoryFilter {
+            operation: args["operation"].as_str().map(str::to_string),
+            actor: args["actor"].as_str().map(str::to_string),
+        };
 
-        let component_path = args["componentPath"].as_str().ok_or_else(|| {
-            GlspError::ToolExecution("Missing componentPath parameter".to_string())
-        })?;
-
-        let path = PathBuf::from(component_path);
-
-        if !path.exists() {
-            return Ok(CallToolResult {
-                content: vec![Content::text(format!(
-                    "Component file not found: {component_path}"
-                ))],
-                is_error: Some(true),
-            });
-        }
-
-        // Analyze the component with the WIT analyzer
-        match WitAnalyzer::analyze_component(&path).await {
-            Ok(analysis) => {
-                let debug_info = json!({
-                    "analysis": "WIT Debug Analysis",
-                    "componentName": analysis.component_name,
-                    "worldName": analysis.world_name,
-                    "filePath": component_path,
-                    "imports": analysis.imports.iter().map(|interface| json!({
-                        "name": interface.name,
-                        "type": "import",
-                        "functions": interface.functions.iter().map(|f| f.name.clone()).collect::<Vec<_>>(),
-                        "functionCount": interface.functions.len(),
-                        "types": interface.types.iter().map(|t| t.name.clone()).collect::<Vec<_>>(),
-                        "typeCount": interface.types.len()
-                    })).collect::<Vec<_>>(),
-                    "exports": analysis.exports.iter().map(|interface| json!({
-                        "name": interface.name,
-                        "type": "export",
-                        "functions": interface.functions.iter().map(|f| f.name.clone()).collect::<Vec<_>>(),
-                        "functionCount": interface.functions.len(),
-                        "types": interface.types.iter().map(|t| t.name.clone()).collect::<Vec<_>>(),
-                        "typeCount": interface.types.len()
-                    })).collect::<Vec<_>>(),
-                    "summary": {
-                        "totalImports": analysis.imports.len(),
-                        "totalExports": analysis.exports.len(),
-                        "totalInterfaces": analysis.imports.len() + analysis.exports.len(),
-                        "totalTypes": analysis.types.len(),
-                        "totalDependencies": analysis.dependencies.len(),
-                        "hasRawWit": analysis.raw_wit.is_some()
-                    },
-                    "expectedForVideoAIPipeline": {
-                        "expectedImports": ["video-decoder", "object-detection"],
-                        "expectedExports": ["pipeline-control"],
-                        "note": "If this is the video-ai-pipeline component, we should see exactly these interfaces"
-                    }
-                });
-
-                Ok(CallToolResult {
-                    content: vec![Content::text(serde_json::to_string_pretty(&debug_info)
-                        .map_err(|e| GlspError::ToolExecution(format!("Failed to serialize debug info: {e}")))?)],
-                    is_error: Some(false),
-                })
-            }
-            Err(error) => {
-                Ok(CallToolResult {
-                    content: vec![Content::text(format!("WIT Analysis Failed: {error}\n\nThis might indicate:\n1. File is not a valid WASM component\n2. Component doesn't have WIT interfaces\n3. WIT parser error\n\nFalling back to basic file info..."))],
-                    is_error: Some(true),
-                })
-            }
+        if !self.models.lock().await.contains_key(diagram_id) {
+            return Err(GlspError::ToolExecution(format!(
+                "Diagram not found: {diagram_id}"
+            )));
         }
-    }
+        let history = self.history.lock().await;
+        let entries: Vec<HistoryEntry> = match &element_id {
+            Some(id) => history
+                .recent(diagram_id, &filter, usize::MAX)
+                .into_iter()
+                .filter(|entry| entry.element_ids.contains(id))
+                .take(limit)
+                .collect(),
+            None => history.recent(diagram_id, &filter, limit),
+        };
+        let enabled = history.is_enabled();
+        drop(history);
 
-    // Workspace management tool wrapper methods
+        Ok(CallToolResult {
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "diagramId": diagram_id,
+                "enabled": enabled,
+                "count": entries.len(),
+                "entries": entries,
+            }))?)],
+            is_error: Some(false),
+        })
+    }
 
-    async fn set_workspace_directory_tool(
+    async fn list_attachments(
         &self,
-        arguments: Option<serde_json::Value>,
+        args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
-        let args = arguments.unwrap_or_default();
-        let workspace_path = args
-            .get("workspace_path")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| {
-                GlspError::ToolExecution("workspace_path parameter is required".to_string())
-            })?;
-
-        let create_if_missing = args
-            .get("create_if_missing")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(true);
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
 
-        // Create workspace directory if requested and it doesn't exist
-        if create_if_missing && !std::path::Path::new(workspace_path).exists() {
-            std::fs::create_dir_all(workspace_path).map_err(|e| {
-                GlspError::ToolExecution(format!("Failed to create workspace directory: {e}"))
-            })?;
+        if !self.models.lock().await.contains_key(diagram_id) {
+            return Err(GlspError::ToolExecution(format!(
+                "Diagram not found: {diagram_id}"
+            )));
         }
-
-        self.set_workspace_directory(workspace_path.to_string())
-            .await?;
+        let attachments = self.attachments.list(diagram_id).await?;
+        let limits = self.attachments.limits();
 
         Ok(CallToolResult {
-            content: vec![Content::text(format!(
-                "Successfully set workspace directory to: {workspace_path}"
-            ))],
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "diagramId": diagram_id,
+                "attachments": attachments,
+                "totalBytes": attachments.iter().map(|a| a.size).sum::<u64>(),
+                "maxAttachmentBytes": limits.max_attachment_bytes,
+                "maxDiagramBytes": limits.max_diagram_bytes,
+            }))?)],
             is_error: Some(false),
         })
     }
 
-    async fn get_current_workspace_tool(&self) -> std::result::Result<CallToolResult, GlspError> {
-        let workspace_info = self.get_current_workspace().await?;
+    async fn get_attachment(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let diagram_id = args["diagramId"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing diagramId".to_string()))?;
+        let name = args["name"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing name".to_string()))?;
+
+        let bytes = match self.attachments.get(diagram_id, name).await {
+            Ok(bytes) => bytes,
+            Err(AttachmentError::Io(e)) => return Err(GlspError::Io(e)),
+            Err(e) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(e.to_string())],
+                    is_error: Some(true),
+                })
+            }
+        };
 
         Ok(CallToolResult {
-            content: vec![Content::text(
-                serde_json::to_string_pretty(&workspace_info).map_err(|e| {
-                    GlspError::ToolExecution(format!("Failed to serialize workspace info: {e}"))
-                })?,
-            )],
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "diagramId": diagram_id,
+                "name": name,
+                "size": bytes.len(),
+                "bytes": BASE64_STANDARD.encode(&bytes),
+            }))?)],
             is_error: Some(false),
         })
     }
 
-    async fn set_wasm_components_path_tool(
-        &self,
-        arguments: Option<serde_json::Value>,
-    ) -> std::result::Result<CallToolResult, GlspError> {
-        let args = arguments.unwrap_or_default();
-        let wasm_path = args
-            .get("wasm_path")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| {
-                GlspError::ToolExecution("wasm_path parameter is required".to_string())
-            })?;
+    /// Every diagram, oldest first, with its attachments
+    async fn workspace_archive(&self) -> std::result::Result<WorkspaceArchive, GlspError> {
+        let models = self.models.lock().await;
+        let mut diagrams: Vec<DiagramModel> = models.values().cloned().collect();
+        drop(models);
+        diagrams.sort_by_key(|d| d.created_at);
+
+        let mut archive = WorkspaceArchive::new(diagrams);
+        for diagram in &archive.manifest.diagrams {
+            for attachment in self.attachments.list(&diagram.id).await? {
+                let bytes = self
+                    .attachments
+                    .get(&diagram.id, &attachment.name)
+                    .await
+                    .map_err(|e| GlspError::ToolExecution(e.to_string()))?;
+                archive.attachments.push(ArchivedAttachment {
+                    diagram_id: diagram.id.clone(),
+                    name: attachment.name,
+                    data: BASE64_STANDARD.encode(bytes),
+                });
+            }
+        }
+        Ok(archive)
+    }
 
-        self.set_wasm_components_path(wasm_path.to_string()).await?;
+    async fn export_workspace(&self) -> std::result::Result<CallToolResult, GlspError> {
+        let archive = self.workspace_archive().await?;
+        info!(
+            "Exported workspace archive with {} diagrams and {} attachments",
+            archive.manifest.diagram_count,
+            archive.attachments.len()
+        );
 
         Ok(CallToolResult {
-            content: vec![Content::text(format!(
-                "Successfully set WASM components path to: {wasm_path}"
-            ))],
+            content: vec![Content::text(serde_json::to_string_pretty(&archive)?)],
             is_error: Some(false),
         })
     }
 
-    async fn set_diagrams_path_tool(
+    async fn check_integrity(
         &self,
-        arguments: Option<serde_json::Value>,
+        args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
-        let args = arguments.unwrap_or_default();
-        let diagrams_path = args
-            .get("diagrams_path")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| {
-                GlspError::ToolExecution("diagrams_path parameter is required".to_string())
-            })?;
+        let args = args.unwrap_or_default();
+        let diagram_id = args["diagramId"].as_str();
+        let repair = args["repair"].as_bool().unwrap_or(false);
 
-        self.set_diagrams_path(diagrams_path.to_string()).await?;
+        let mut models = self.models.lock().await;
+        let mut diagram_ids: Vec<String> = match diagram_id {
+            Some(id) if !models.contains_key(id) => {
+                return Err(GlspError::ToolExecution(format!("Diagram not found: {id}")))
+            }
+            Some(id) => vec![id.to_string()],
+            None => models.keys().cloned().collect(),
+        };
+        diagram_ids.sort();
+
+        // Repairs went through the read-only and lock checks of `call_tool`;
+        // read-only diagrams of the workspace are only checked
+        let mut issues: Vec<ValidationIssue> = Vec::new();
+        let mut repaired = Vec::new();
+        for id in &diagram_ids {
+            let diagram = models.get_mut(id).expect("diagram IDs come from the map");
+            let repairing = repair && !diagram.is_read_only();
+            let found = if repairing {
+                repair_integrity(diagram)
+            } else {
+                check_integrity(diagram)
+            };
+            if repairing && !found.is_empty() {
+                repaired.push(id.clone());
+                history::touched(id, found.iter().map(|issue| issue.element_id.clone()));
+            }
+            issues.extend(found);
+        }
+        drop(models); // Release the lock before saving
+
+        for id in &repaired {
+            if let Err(e) = self.save_diagram(id).await {
+                error!("Failed to save diagram {id} after integrity repair: {e}");
+            }
+        }
+        if !issues.is_empty() {
+            warn!(
+                "Integrity check found {} issues in {} diagrams{}",
+                issues.len(),
+                diagram_ids.len(),
+                if repair { " and repaired them" } else { "" }
+            );
+        }
 
         Ok(CallToolResult {
-            content: vec![Content::text(format!(
-                "Successfully set diagrams path to: {diagrams_path}"
-            ))],
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "checkedDiagrams": diagram_ids.len(),
+                "issues": issues,
+                "repairedDiagrams": repaired,
+            }))?)],
             is_error: Some(false),
         })
     }
 
-    async fn rescan_workspace_tool(&self) -> std::result::Result<CallToolResult, GlspError> {
-        self.rescan_workspace().await?;
+    async fn import_workspace(
+        &self,
+        args: Option<serde_json::Value>,
+    ) -> std::result::Result<CallToolResult, GlspError> {
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let mut archive: WorkspaceArchive = match &args["archive"] {
+            serde_json::Value::String(raw) => serde_json::from_str(raw)?,
+            serde_json::Value::Object(_) => serde_json::from_value(args["archive"].clone())?,
+            _ => return Err(GlspError::ToolExecution("Missing archive".to_string())),
+        };
+        if let Err(e) = archive.check_version() {
+            return Ok(CallToolResult {
+                content: vec![Content::text(e)],
+                is_error: Some(true),
+            });
+        }
+        let repair = match args["mode"].as_str().unwrap_or("strict") {
+            "strict" => false,
+            "repair" => true,
+            other => {
+                return Err(GlspError::ToolExecution(format!(
+                    "Unknown import mode '{other}' (expected strict or repair)"
+                )))
+            }
+        };
+        let strategy = match args["strategy"]
+            .as_str()
+            .map(str::parse::<IdCollisionStrategy>)
+        {
+            None => IdCollisionStrategy::default(),
+            Some(Ok(strategy)) => strategy,
+            Some(Err(e)) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(e)],
+                    is_error: Some(true),
+                })
+            }
+        };
+
+        // One step for validation, then one per saved diagram
+        let total = Some(archive.diagrams.len() as f64 + 1.0);
+        progress::report(0.0, total, "Validating archive");
+
+        // Validate everything before committing anything
+        let issues: Vec<ValidationIssue> = if repair {
+            archive
+                .diagrams
+                .iter_mut()
+                .flat_map(repair_diagram)
+                .collect()
+        } else {
+            archive.diagrams.iter().flat_map(validate_diagram).collect()
+        };
+        if !repair && !issues.is_empty() {
+            warn!(
+                "Rejected workspace import with {} validation issues",
+                issues.len()
+            );
+            return Ok(CallToolResult {
+                content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                    "imported": 0,
+                    "error": "Archive failed validation; nothing was imported. Retry with mode 'repair' to drop the offending entries",
+                    "issues": issues
+                }))?)],
+                is_error: Some(true),
+            });
+        }
+
+        let mut models = self.models.lock().await;
+        let existing_ids = models.keys().cloned().collect();
+        let existing_names = models.values().map(|d| d.name.clone()).collect();
+        let id_map = match archive.resolve_collisions(&existing_ids, &existing_names, strategy) {
+            Ok(id_map) => id_map,
+            Err(taken) => {
+                drop(models);
+                return Ok(CallToolResult {
+                    content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                        "imported": 0,
+                        "error": "Diagram IDs are already taken; nothing was imported. Retry with strategy 'keep-if-free' or 'always-new' to assign new IDs",
+                        "collisions": taken
+                    }))?)],
+                    is_error: Some(true),
+                });
+            }
+        };
+        let remapped: Vec<&IdMapping> = id_map.iter().filter(|m| m.is_remapped()).collect();
+
+        let imported: Vec<String> = archive.diagrams.iter().map(|d| d.id.clone()).collect();
+        for diagram in archive.diagrams {
+            models.insert(diagram.id.clone(), diagram);
+        }
+        drop(models); // Release the lock before saving
+
+        // An attachment that cannot be restored does not undo the import
+        let mut attachment_errors = Vec::new();
+        let mut restored_attachments = 0;
+        for attachment in &archive.attachments {
+            if !imported.contains(&attachment.diagram_id) {
+                continue;
+            }
+            let stored = match BASE64_STANDARD.decode(&attachment.data) {
+                Ok(bytes) => self
+                    .attachments
+                    .attach(&attachment.diagram_id, &attachment.name, &bytes)
+                    .await
+                    .map_err(|e| e.to_string()),
+                Err(e) => Err(format!("Invalid base64 data: {e}")),
+            };
+            match stored {
+                Ok(_) => restored_attachments += 1,
+                Err(e) => attachment_errors.push(json!({
+                    "diagramId": attachment.diagram_id,
+                    "name": attachment.name,
+                    "error": e,
+                })),
+            }
+        }
+
+        for (index, diagram_id) in imported.iter().enumerate() {
+            progress::report(
+                index as f64 + 1.0,
+                total,
+                &format!("Saving diagram {} of {}", index + 1, imported.len()),
+            );
+            if let Err(e) = self.save_diagram(diagram_id).await {
+                error!("Failed to save imported diagram {diagram_id}: {e}");
+            }
+        }
+        progress::report(imported.len() as f64 + 1.0, total, "Import complete");
+
+        info!(
+            "Imported {} diagrams ({} remapped)",
+            imported.len(),
+            remapped.len()
+        );
+
+        let result = json!({
+            "imported": imported.len(),
+            "diagramIds": imported,
+            "remappedIds": remapped,
+            "idMap": id_map,
+            "attachments": restored_attachments,
+            "attachmentErrors": attachment_errors,
+            "repaired": issues
+        });
 
         Ok(CallToolResult {
-            content: vec![Content::text(
-                "Successfully rescanned workspace for WASM components and diagrams".to_string(),
-            )],
+            content: vec![Content::text(serde_json::to_string_pretty(&result)?)],
             is_error: Some(false),
         })
     }
 
-    async fn validate_workspace_tool(
+    async fn snapshot_state(
         &self,
-        arguments: Option<serde_json::Value>,
+        args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
-        let args = arguments.unwrap_or_default();
-        let workspace_path = args
-            .get("workspace_path")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| {
-                GlspError::ToolExecution("workspace_path parameter is required".to_string())
-            })?;
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let name = args["name"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing name".to_string()))?;
 
-        let validation_result = self.validate_workspace_paths(workspace_path).await?;
+        let archive = self.workspace_archive().await?;
+        let path = match self.state_snapshots.save(name, &archive).await {
+            Ok(path) => path,
+            Err(e) if e.kind() == std::io::ErrorKind::InvalidInput => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(e.to_string())],
+                    is_error: Some(true),
+                })
+            }
+            Err(e) => return Err(e.into()),
+        };
+        info!(
+            "Saved state snapshot '{name}' with {} diagrams to {}",
+            archive.manifest.diagram_count,
+            path.display()
+        );
 
         Ok(CallToolResult {
-            content: vec![Content::text(
-                serde_json::to_string_pretty(&validation_result).map_err(|e| {
-                    GlspError::ToolExecution(format!("Failed to serialize validation result: {e}"))
-                })?,
-            )],
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "name": name,
+                "path": path.display().to_string(),
+                "createdAt": archive.manifest.exported_at,
+                "diagrams": archive.manifest.diagram_count,
+                "attachments": archive.attachments.len(),
+            }))?)],
             is_error: Some(false),
         })
     }
 
-    async fn create_workspace_structure_tool(
+    async fn restore_state(
         &self,
-        arguments: Option<serde_json::Value>,
+        args: Option<serde_json::Value>,
     ) -> std::result::Result<CallToolResult, GlspError> {
-        let args = arguments.unwrap_or_default();
-        let workspace_path = args
-            .get("workspace_path")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| {
-                GlspError::ToolExecution("workspace_path parameter is required".to_string())
-            })?;
+        let args = args.ok_or_else(|| GlspError::ToolExecution("Missing arguments".to_string()))?;
+        let name = args["name"]
+            .as_str()
+            .ok_or_else(|| GlspError::ToolExecution("Missing name".to_string()))?;
 
-        use std::path::Path;
-        let workspace = Path::new(workspace_path);
+        let archive = match self.state_snapshots.load(name).await {
+            Ok(archive) => archive,
+            Err(e) => {
+                return Ok(CallToolResult {
+                    content: vec![Content::text(format!(
+                        "Cannot read state snapshot '{name}': {e}"
+                    ))],
+                    is_error: Some(true),
+                })
+            }
+        };
 
-        // Create workspace root
-        std::fs::create_dir_all(workspace).map_err(|e| {
-            GlspError::ToolExecution(format!("Failed to create workspace directory: {e}"))
-        })?;
+        // Validate everything, attachments included, before replacing anything
+        let issues: Vec<ValidationIssue> =
+            archive.diagrams.iter().flat_map(validate_diagram).collect();
+        let mut problems: Vec<String> = archive.check_version().err().into_iter().collect();
+        let mut ids = HashSet::new();
+        for diagram in &archive.diagrams {
+            if !ids.insert(diagram.id.as_str()) {
+                problems.push(format!("Diagram ID {} appears more than once", diagram.id));
+            }
+        }
+        let mut attachments = Vec::new();
+        for attachment in &archive.attachments {
+            if !ids.contains(attachment.diagram_id.as_str()) {
+                problems.push(format!(
+                    "Attachment {} belongs to unknown diagram {}",
+                    attachment.name, attachment.diagram_id
+                ));
+                continue;
+            }
+            match BASE64_STANDARD.decode(&attachment.data) {
+                Ok(bytes) => attachments.push((attachment, bytes)),
+                Err(e) => problems.push(format!(
+                    "Attachment {} of diagram {} has invalid base64 data: {e}",
+                    attachment.name, attachment.diagram_id
+                )),
+            }
+        }
+        if !issues.is_empty() || !problems.is_empty() {
+            warn!(
+                "Rejected state snapshot '{name}' with {} problems",
+                issues.len() + problems.len()
+            );
+            return Ok(CallToolResult {
+                content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                    "restored": false,
+                    "error": "State snapshot failed validation; the current state was left unchanged",
+                    "issues": issues,
+                    "problems": problems,
+                }))?)],
+                is_error: Some(true),
+            });
+        }
 
-        // Create subdirectories
-        let wasm_dir = workspace.join("wasm-components");
-        let diagrams_dir = workspace.join("diagrams");
+        let restored_names: HashSet<String> =
+            archive.diagrams.iter().map(|d| d.name.clone()).collect();
+        let restored_ids: Vec<String> = archive.diagrams.iter().map(|d| d.id.clone()).collect();
+        let mut models = self.models.lock().await;
+        let previous = std::mem::replace(
+            &mut *models,
+            archive
+                .diagrams
+                .into_iter()
+                .map(|d| (d.id.clone(), d))
+                .collect(),
+        );
+        drop(models); // Release the lock before filesystem operations
 
-        std::fs::create_dir_all(&wasm_dir).map_err(|e| {
-            GlspError::ToolExecution(format!("Failed to create wasm-components directory: {e}"))
-        })?;
-        std::fs::create_dir_all(&diagrams_dir).map_err(|e| {
-            GlspError::ToolExecution(format!("Failed to create diagrams directory: {e}"))
-        })?;
+        {
+            let mut locks = self.locks.lock().await;
+            for id in previous.keys().chain(&restored_ids) {
+                locks.clear(id);
+            }
+        }
+
+        // The in-memory state is already replaced; file errors are reported
+        // but do not bring the old state back
+        let mut file_errors = Vec::new();
+        for (id, diagram) in &previous {
+            if !restored_names.contains(&diagram.name) {
+                if let Err(e) = self.delete_diagram_files(&diagram.name).await {
+                    file_errors.push(format!("Deleting files of diagram {id}: {e}"));
+                }
+            }
+            if let Err(e) = self.attachments.remove_all(id).await {
+                file_errors.push(format!("Deleting attachments of diagram {id}: {e}"));
+            }
+            // The snapshot replaces any persist still pending from before
+            if let Err(e) = self.dead_letters.remove(id).await {
+                file_errors.push(format!("Deleting dead letter of diagram {id}: {e}"));
+            }
+        }
+        for (attachment, bytes) in &attachments {
+            if let Err(e) = self
+                .attachments
+                .attach(&attachment.diagram_id, &attachment.name, bytes)
+                .await
+            {
+                file_errors.push(format!(
+                    "Restoring attachment {} of diagram {}: {e}",
+                    attachment.name, attachment.diagram_id
+                ));
+            }
+        }
+        for id in &restored_ids {
+            if let Err(e) = self.save_diagram(id).await {
+                file_errors.push(format!("Saving diagram {id}: {e}"));
+            }
+        }
+
+        let removed: Vec<&String> = previous
+            .keys()
+            .filter(|id| !restored_ids.contains(id))
+            .collect();
+        for e in &file_errors {
+            error!("Restoring state snapshot '{name}': {e}");
+        }
+        info!(
+            "Restored state snapshot '{name}': {} diagrams, {} removed",
+            restored_ids.len(),
+            removed.len()
+        );
 
         Ok(CallToolResult {
-            content: vec![Content::text(format!(
-                "Successfully created workspace structure:\n- {}\n- {}\n- {}",
-                workspace_path,
-                wasm_dir.display(),
-                diagrams_dir.display()
-            ))],
+            content: vec![Content::text(serde_json::to_string_pretty(&json!({
+                "restored": true,
+                "name": name,
+                "diagramIds": restored_ids,
+                "removedDiagramIds": removed,
+                "attachments": attachments.len(),
+                "fileErrors": file_errors,
+            }))?)],
             is_error: Some(false),
         })
     }
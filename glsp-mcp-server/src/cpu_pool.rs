@@ -0,0 +1,135 @@
+//! Bounded thread pool for CPU-bound tool work
+//!
+//! Layout algorithms and WASM calls run for as long as their input demands
+//! and never yield, so on the async runtime they would hold a worker thread
+//! and stall quick tool calls queued behind it. [`CpuPool`] runs such work on
+//! Tokio's blocking threads instead, with at most `size` jobs at a time; more
+//! jobs wait for a free slot rather than spawning more threads. IO-bound
+//! tools stay on the runtime.
+
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// Why a job did not produce a result
+#[derive(Debug, thiserror::Error)]
+pub enum CpuPoolError {
+    #[error("CPU-bound job panicked: {0}")]
+    Panicked(String),
+    #[error("CPU-bound job was cancelled")]
+    Cancelled,
+}
+
+/// Runs CPU-bound jobs off the async runtime, a bounded number at a time
+#[derive(Debug, Clone)]
+pub struct CpuPool {
+    slots: Arc<Semaphore>,
+    size: usize,
+}
+
+impl CpuPool {
+    /// A pool running up to `size` jobs at once; zero uses one slot per
+    /// available CPU
+    pub fn new(size: usize) -> Self {
+        let size = match size {
+            0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            size => size,
+        };
+        Self {
+            slots: Arc::new(Semaphore::new(size)),
+            size,
+        }
+    }
+
+    /// Number of jobs that may run at once
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Jobs that could start right now without waiting
+    pub fn available(&self) -> usize {
+        self.slots.available_permits()
+    }
+
+    /// Run `job` on a blocking thread once a slot is free
+    pub async fn run<T, F>(&self, job: F) -> Result<T, CpuPoolError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let slot = self
+            .slots
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| CpuPoolError::Cancelled)?;
+        let handle = tokio::task::spawn_blocking(move || {
+            let _slot = slot;
+            job()
+        });
+        handle.await.map_err(|e| match e.try_into_panic() {
+            Ok(panic) => CpuPoolError::Panicked(
+                panic
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string()),
+            ),
+            Err(_) => CpuPoolError::Cancelled,
+        })
+    }
+
+    /// Drive `future` to completion on a blocking thread once a slot is
+    /// free, for async work whose awaits wrap long synchronous calls
+    pub async fn run_future<F>(&self, future: F) -> Result<F::Output, CpuPoolError>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let runtime = tokio::runtime::Handle::current();
+        self.run(move || runtime.block_on(future)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_pool_bounds_concurrent_jobs() {
+        let pool = CpuPool::new(2);
+        assert_eq!(pool.size(), 2);
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let jobs: Vec<_> = (0..6)
+            .map(|i| {
+                let (pool, running, peak) = (pool.clone(), running.clone(), peak.clone());
+                tokio::spawn(async move {
+                    pool.run(move || {
+                        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now, Ordering::SeqCst);
+                        std::thread::sleep(Duration::from_millis(20));
+                        running.fetch_sub(1, Ordering::SeqCst);
+                        i * 2
+                    })
+                    .await
+                })
+            })
+            .collect();
+        let mut results = Vec::new();
+        for job in jobs {
+            results.push(job.await.unwrap().unwrap());
+        }
+
+        assert_eq!(results, vec![0, 2, 4, 6, 8, 10]);
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+        assert_eq!(pool.available(), 2);
+
+        let panicked = pool.run(|| panic!("layout exploded")).await;
+        assert!(matches!(panicked, Err(CpuPoolError::Panicked(m)) if m == "layout exploded"));
+        assert_eq!(pool.run_future(async { 7 }).await.unwrap(), 7);
+    }
+}
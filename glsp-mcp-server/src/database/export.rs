@@ -0,0 +1,279 @@
+//! Export of sensor readings to files for offline analysis
+//!
+//! Readings are fetched in fixed time windows and written out window by
+//! window, so only one window of readings is held in memory no matter how
+//! long the exported range is. CSV files have `timestamp,value` columns with
+//! RFC 3339 timestamps; Parquet files (behind the `parquet` feature) have a
+//! UTC microsecond `timestamp` column and an optional `DOUBLE` `value` column,
+//! one row group per non-empty window. Readings without a scalar value keep
+//! their row with an empty (null) value.
+
+use crate::database::{
+    DatabaseError, DatabaseResult, SensorDataRepository, SensorQuery, SensorReading,
+};
+use chrono::SecondsFormat;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Width of the time window fetched per query (one minute)
+pub const EXPORT_WINDOW_US: i64 = 60_000_000;
+
+/// File format of a sensor export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+
+    /// File extension, which is the same as the format name
+    pub fn extension(&self) -> &'static str {
+        self.as_str()
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(ExportFormat::Csv),
+            "parquet" => Ok(ExportFormat::Parquet),
+            other => Err(format!(
+                "Unknown export format '{other}' (expected csv or parquet)"
+            )),
+        }
+    }
+}
+
+/// What an export wrote
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSummary {
+    pub sensor_id: String,
+    pub format: ExportFormat,
+    pub path: PathBuf,
+    pub rows: u64,
+    pub start_time_us: i64,
+    pub end_time_us: i64,
+}
+
+/// Export one sensor's readings in `[start_time_us, end_time_us]` to `path`.
+///
+/// Fails with `TimeRangeError` when the range is inverted and with
+/// `SensorNotFound` for unknown sensors; a known sensor with no readings in
+/// the range produces a file with no rows.
+pub async fn export_sensor_data<R: SensorDataRepository + ?Sized>(
+    repository: &R,
+    sensor_id: &str,
+    start_time_us: i64,
+    end_time_us: i64,
+    format: ExportFormat,
+    path: &Path,
+) -> DatabaseResult<ExportSummary> {
+    export_sensor_data_with(
+        repository,
+        sensor_id,
+        start_time_us,
+        end_time_us,
+        format,
+        path,
+        |_| {},
+    )
+    .await
+}
+
+/// [`export_sensor_data`], also handing each window of readings, in time
+/// order, to `on_window` once it is written
+pub async fn export_sensor_data_with<R: SensorDataRepository + ?Sized>(
+    repository: &R,
+    sensor_id: &str,
+    start_time_us: i64,
+    end_time_us: i64,
+    format: ExportFormat,
+    path: &Path,
+    mut on_window: impl FnMut(&[SensorReading]),
+) -> DatabaseResult<ExportSummary> {
+    SensorQuery::time_range(start_time_us, end_time_us).validate()?;
+    if !repository
+        .list_sensors()
+        .await?
+        .iter()
+        .any(|s| s == sensor_id)
+    {
+        return Err(DatabaseError::SensorNotFound(sensor_id.to_string()));
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut sink = ExportSink::create(format, path)?;
+    let mut rows = 0u64;
+
+    let mut window_start = start_time_us;
+    while window_start <= end_time_us {
+        // Query ranges are inclusive, so windows end one microsecond early
+        let window_end = window_start
+            .saturating_add(EXPORT_WINDOW_US - 1)
+            .min(end_time_us);
+        let query = SensorQuery::time_range(window_start, window_end)
+            .with_sensors(vec![sensor_id.to_string()]);
+        let mut readings = repository.query_readings(&query).await?;
+        if !readings.is_empty() {
+            readings.sort_by_key(|r| r.timestamp_us);
+            sink.write_window(&readings)?;
+            on_window(&readings);
+            rows += readings.len() as u64;
+        }
+
+        if window_end == i64::MAX {
+            break;
+        }
+        window_start = window_end + 1;
+    }
+    sink.finish()?;
+
+    Ok(ExportSummary {
+        sensor_id: sensor_id.to_string(),
+        format,
+        path: path.to_path_buf(),
+        rows,
+        start_time_us,
+        end_time_us,
+    })
+}
+
+enum ExportSink {
+    Csv(BufWriter<File>),
+    #[cfg(feature = "parquet")]
+    Parquet(parquet_sink::ParquetSink),
+}
+
+impl ExportSink {
+    fn create(format: ExportFormat, path: &Path) -> DatabaseResult<Self> {
+        match format {
+            ExportFormat::Csv => {
+                let mut writer = BufWriter::new(File::create(path)?);
+                writeln!(writer, "timestamp,value")?;
+                Ok(ExportSink::Csv(writer))
+            }
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => Ok(ExportSink::Parquet(parquet_sink::ParquetSink::create(
+                path,
+            )?)),
+            #[cfg(not(feature = "parquet"))]
+            ExportFormat::Parquet => Err(DatabaseError::FeatureNotSupported {
+                feature: "Parquet export not compiled in".to_string(),
+            }),
+        }
+    }
+
+    fn write_window(&mut self, readings: &[SensorReading]) -> DatabaseResult<()> {
+        match self {
+            ExportSink::Csv(writer) => {
+                for reading in readings {
+                    let timestamp = reading
+                        .timestamp()
+                        .to_rfc3339_opts(SecondsFormat::Micros, true);
+                    match reading.scalar_value() {
+                        Some(value) => writeln!(writer, "{timestamp},{value}")?,
+                        None => writeln!(writer, "{timestamp},")?,
+                    }
+                }
+                Ok(())
+            }
+            #[cfg(feature = "parquet")]
+            ExportSink::Parquet(sink) => sink.write_window(readings),
+        }
+    }
+
+    fn finish(self) -> DatabaseResult<()> {
+        match self {
+            ExportSink::Csv(mut writer) => Ok(writer.flush()?),
+            #[cfg(feature = "parquet")]
+            ExportSink::Parquet(sink) => sink.finish(),
+        }
+    }
+}
+
+#[cfg(feature = "parquet")]
+mod parquet_sink {
+    use crate::database::{DatabaseError, DatabaseResult, SensorReading};
+    use parquet::data_type::{DoubleType, Int64Type};
+    use parquet::errors::ParquetError;
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use std::fs::File;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    const SCHEMA: &str = "
+        message sensor_reading {
+            REQUIRED INT64 timestamp (TIMESTAMP(MICROS, true));
+            OPTIONAL DOUBLE value;
+        }
+    ";
+
+    fn parquet_error(e: ParquetError) -> DatabaseError {
+        DatabaseError::SerializationError(format!("Parquet: {e}"))
+    }
+
+    pub struct ParquetSink {
+        writer: SerializedFileWriter<File>,
+    }
+
+    impl ParquetSink {
+        pub fn create(path: &Path) -> DatabaseResult<Self> {
+            let schema = Arc::new(parse_message_type(SCHEMA).map_err(parquet_error)?);
+            let properties = Arc::new(WriterProperties::builder().build());
+            let writer = SerializedFileWriter::new(File::create(path)?, schema, properties)
+                .map_err(parquet_error)?;
+            Ok(Self { writer })
+        }
+
+        /// Write one window of readings as a row group
+        pub fn write_window(&mut self, readings: &[SensorReading]) -> DatabaseResult<()> {
+            let timestamps: Vec<i64> = readings.iter().map(|r| r.timestamp_us).collect();
+            let scalars: Vec<Option<f64>> = readings.iter().map(|r| r.scalar_value()).collect();
+            let values: Vec<f64> = scalars.iter().flatten().copied().collect();
+            let definition_levels: Vec<i16> =
+                scalars.iter().map(|v| i16::from(v.is_some())).collect();
+
+            let mut row_group = self.writer.next_row_group().map_err(parquet_error)?;
+            if let Some(mut column) = row_group.next_column().map_err(parquet_error)? {
+                column
+                    .typed::<Int64Type>()
+                    .write_batch(&timestamps, None, None)
+                    .map_err(parquet_error)?;
+                column.close().map_err(parquet_error)?;
+            }
+            if let Some(mut column) = row_group.next_column().map_err(parquet_error)? {
+                column
+                    .typed::<DoubleType>()
+                    .write_batch(&values, Some(&definition_levels), None)
+                    .map_err(parquet_error)?;
+                column.close().map_err(parquet_error)?;
+            }
+            row_group.close().map_err(parquet_error)?;
+            Ok(())
+        }
+
+        pub fn finish(self) -> DatabaseResult<()> {
+            self.writer.close().map_err(parquet_error)?;
+            Ok(())
+        }
+    }
+}
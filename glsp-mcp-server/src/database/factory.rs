@@ -220,6 +220,7 @@ pub struct MockDatabaseBackend {
     connected: bool,
     readings: Arc<Mutex<Vec<SensorReading>>>,
     metadata: Arc<Mutex<HashMap<String, SensorMetadata>>>,
+    config: Arc<Mutex<HashMap<String, serde_json::Value>>>,
 }
 
 impl MockDatabaseBackend {
@@ -228,6 +229,7 @@ impl MockDatabaseBackend {
             connected: false,
             readings: Arc::new(Mutex::new(Vec::new())),
             metadata: Arc::new(Mutex::new(HashMap::new())),
+            config: Arc::new(Mutex::new(HashMap::new())),
         })
     }
 }
@@ -438,6 +440,11 @@ impl SensorDataRepository for MockDatabaseBackend {
 
         Ok((initial_len - readings.len()) as u64)
     }
+
+    async fn latest_readings(&self, sensor_ids: &[String]) -> DatabaseResult<Vec<LatestReading>> {
+        let readings = self.readings.lock().await;
+        Ok(LatestReading::select(sensor_ids, readings.iter()))
+    }
 }
 
 #[async_trait]
@@ -529,16 +536,22 @@ impl MetadataStore for MockDatabaseBackend {
         Ok(())
     }
 
-    async fn store_config(&mut self, _key: &str, _value: &serde_json::Value) -> DatabaseResult<()> {
+    async fn store_config(&mut self, key: &str, value: &serde_json::Value) -> DatabaseResult<()> {
+        let mut config = self.config.lock().await;
+        config.insert(key.to_string(), value.clone());
         Ok(())
     }
 
-    async fn get_config(&self, _key: &str) -> DatabaseResult<Option<serde_json::Value>> {
-        Ok(None)
+    async fn get_config(&self, key: &str) -> DatabaseResult<Option<serde_json::Value>> {
+        let config = self.config.lock().await;
+        Ok(config.get(key).cloned())
     }
 
     async fn list_config_keys(&self) -> DatabaseResult<Vec<String>> {
-        Ok(vec![])
+        let config = self.config.lock().await;
+        let mut keys: Vec<String> = config.keys().cloned().collect();
+        keys.sort();
+        Ok(keys)
     }
 }
 
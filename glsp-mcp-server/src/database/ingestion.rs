@@ -0,0 +1,415 @@
+//! Streaming ingestion of sensor readings
+//!
+//! Live recordings arrive as a continuous stream of newline-delimited JSON
+//! records (`{"sensor_id": "...", "timestamp": 1700000000000000, "value": 1.5}`).
+//! Records are validated one by one, queued on a bounded channel and written
+//! to the database in batches by a background writer. When the queue is full
+//! the reader waits, which stops it from pulling more data off the stream and
+//! pushes back on the producer.
+//!
+//! Per-record validation failures and batch write failures are reported as
+//! [`IngestEvent`]s on the caller's event channel; neither ends the stream.
+
+use crate::database::{
+    DatabaseInterface, SensorBatch, SensorDataRepository, SensorDataType, SensorReading,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Default number of readings written per batch
+pub const DEFAULT_INGEST_BATCH_SIZE: usize = 500;
+
+/// Default number of readings that may wait for the writer
+pub const DEFAULT_INGEST_QUEUE_CAPACITY: usize = 5_000;
+
+/// Default maximum time a partial batch waits before it is written
+pub const DEFAULT_INGEST_FLUSH_INTERVAL_MS: u64 = 250;
+
+/// Shared database handle as returned by `DatabaseManager::backend`
+pub type SharedDatabase = Arc<RwLock<Box<dyn DatabaseInterface>>>;
+
+/// Tuning for an ingestion stream
+#[derive(Debug, Clone)]
+pub struct IngestionConfig {
+    pub batch_size: usize,
+    pub queue_capacity: usize,
+    pub flush_interval: Duration,
+    /// Recorded as the `source` of every written batch
+    pub source: String,
+}
+
+impl Default for IngestionConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: DEFAULT_INGEST_BATCH_SIZE,
+            queue_capacity: DEFAULT_INGEST_QUEUE_CAPACITY,
+            flush_interval: Duration::from_millis(DEFAULT_INGEST_FLUSH_INTERVAL_MS),
+            source: "stream".to_string(),
+        }
+    }
+}
+
+/// A single scalar reading as sent by a streaming client
+#[derive(Debug, Clone, Deserialize)]
+pub struct IngestRecord {
+    #[serde(alias = "sensorId")]
+    pub sensor_id: String,
+    /// Microseconds since Unix epoch
+    #[serde(alias = "timestamp_us", alias = "timestampUs")]
+    pub timestamp: i64,
+    pub value: f64,
+}
+
+impl IngestRecord {
+    /// Validate the record and convert it into a stored reading.
+    ///
+    /// The value is stored as a little-endian `f64` payload and mirrored in
+    /// the reading metadata so it stays readable from JSON queries.
+    pub fn into_reading(self) -> Result<SensorReading, String> {
+        if self.sensor_id.trim().is_empty() {
+            return Err("sensor_id must not be empty".to_string());
+        }
+        if self.timestamp < 0 {
+            return Err(format!(
+                "timestamp must not be negative: {}",
+                self.timestamp
+            ));
+        }
+        if !self.value.is_finite() {
+            return Err(format!("value must be a finite number: {}", self.value));
+        }
+
+        let mut reading = SensorReading::new(
+            self.sensor_id,
+            self.timestamp,
+            SensorDataType::Generic {
+                sensor_type: "scalar".to_string(),
+                data_size: std::mem::size_of::<f64>(),
+            },
+            self.value.to_le_bytes().to_vec(),
+        );
+        reading
+            .metadata
+            .insert("value".to_string(), serde_json::json!(self.value));
+        Ok(reading)
+    }
+}
+
+/// Feedback sent back to the client while a stream is ingested
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum IngestEvent {
+    /// A record could not be parsed or failed validation
+    #[serde(rename_all = "camelCase")]
+    Rejected { line: u64, error: String },
+    /// A batch could not be written; its readings are lost
+    #[serde(rename_all = "camelCase")]
+    BatchFailed { count: usize, error: String },
+    /// Reading from the stream failed; no further records are accepted
+    #[serde(rename_all = "camelCase")]
+    StreamError { error: String },
+    /// Final summary, sent once every queued reading has been written
+    #[serde(rename_all = "camelCase")]
+    Completed {
+        accepted: u64,
+        rejected: u64,
+        written: u64,
+        failed: u64,
+    },
+}
+
+/// Totals reported by the batch writer
+#[derive(Debug, Clone, Copy, Default)]
+struct WriteStats {
+    written: u64,
+    failed: u64,
+}
+
+/// Batching writer fed through a bounded queue
+pub struct SensorIngestor {
+    queue: mpsc::Sender<SensorReading>,
+    writer: JoinHandle<WriteStats>,
+}
+
+impl SensorIngestor {
+    /// Start the background writer for one stream
+    pub fn spawn(
+        database: SharedDatabase,
+        config: IngestionConfig,
+        events: mpsc::Sender<IngestEvent>,
+    ) -> Self {
+        let (queue, receiver) = mpsc::channel(config.queue_capacity.max(1));
+        let writer = tokio::spawn(run_writer(database, config, receiver, events));
+        Self { queue, writer }
+    }
+
+    /// Queue a reading, waiting while the queue is full
+    pub async fn submit(&self, reading: SensorReading) -> Result<(), String> {
+        self.queue
+            .send(reading)
+            .await
+            .map_err(|_| "Ingestion writer stopped".to_string())
+    }
+
+    /// Close the queue and wait until every queued reading has been written
+    async fn finish(self) -> WriteStats {
+        drop(self.queue);
+        self.writer.await.unwrap_or_else(|e| {
+            warn!("Sensor ingestion writer failed: {}", e);
+            WriteStats::default()
+        })
+    }
+}
+
+async fn run_writer(
+    database: SharedDatabase,
+    config: IngestionConfig,
+    mut receiver: mpsc::Receiver<SensorReading>,
+    events: mpsc::Sender<IngestEvent>,
+) -> WriteStats {
+    let batch_size = config.batch_size.max(1);
+    let mut stats = WriteStats::default();
+    let mut buffer = Vec::with_capacity(batch_size);
+    let mut ticker = tokio::time::interval(config.flush_interval);
+
+    loop {
+        tokio::select! {
+            reading = receiver.recv() => match reading {
+                Some(reading) => {
+                    buffer.push(reading);
+                    if buffer.len() >= batch_size {
+                        flush(&database, &config, &mut buffer, &mut stats, &events).await;
+                    }
+                }
+                None => break,
+            },
+            _ = ticker.tick() => {
+                if !buffer.is_empty() {
+                    flush(&database, &config, &mut buffer, &mut stats, &events).await;
+                }
+            }
+        }
+    }
+
+    if !buffer.is_empty() {
+        flush(&database, &config, &mut buffer, &mut stats, &events).await;
+    }
+    stats
+}
+
+async fn flush(
+    database: &SharedDatabase,
+    config: &IngestionConfig,
+    buffer: &mut Vec<SensorReading>,
+    stats: &mut WriteStats,
+    events: &mpsc::Sender<IngestEvent>,
+) {
+    let batch = SensorBatch {
+        readings: std::mem::take(buffer),
+        batch_id: uuid::Uuid::new_v4().to_string(),
+        created_at: chrono::Utc::now(),
+        source: config.source.clone(),
+    };
+    let count = batch.readings.len();
+
+    let result = database.write().await.store_batch(&batch).await;
+    match result {
+        Ok(()) => {
+            debug!("Wrote sensor batch {} ({} readings)", batch.batch_id, count);
+            stats.written += count as u64;
+        }
+        Err(e) => {
+            warn!("Failed to write sensor batch {}: {}", batch.batch_id, e);
+            stats.failed += count as u64;
+            let _ = events
+                .send(IngestEvent::BatchFailed {
+                    count,
+                    error: e.to_string(),
+                })
+                .await;
+        }
+    }
+}
+
+/// Per-stream record counters and the writer they feed
+struct LineIngest {
+    ingestor: SensorIngestor,
+    events: mpsc::Sender<IngestEvent>,
+    line_number: u64,
+    accepted: u64,
+    rejected: u64,
+}
+
+impl LineIngest {
+    async fn handle_line(&mut self, line: &[u8]) {
+        self.line_number += 1;
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return;
+        }
+
+        let reading = serde_json::from_slice::<IngestRecord>(line)
+            .map_err(|e| format!("Invalid record: {e}"))
+            .and_then(IngestRecord::into_reading);
+        let outcome = match reading {
+            Ok(reading) => self.ingestor.submit(reading).await,
+            Err(error) => Err(error),
+        };
+
+        match outcome {
+            Ok(()) => self.accepted += 1,
+            Err(error) => {
+                self.rejected += 1;
+                let _ = self
+                    .events
+                    .send(IngestEvent::Rejected {
+                        line: self.line_number,
+                        error,
+                    })
+                    .await;
+            }
+        }
+    }
+}
+
+/// Ingest a newline-delimited JSON byte stream.
+///
+/// Records may be split across chunks. Blank lines are ignored. A
+/// [`IngestEvent::Completed`] summary is always sent last.
+pub async fn ingest_ndjson<S, B, E>(
+    mut stream: S,
+    database: SharedDatabase,
+    config: IngestionConfig,
+    events: mpsc::Sender<IngestEvent>,
+) where
+    S: futures::Stream<Item = Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+    E: std::fmt::Display,
+{
+    use futures::StreamExt;
+
+    let mut state = LineIngest {
+        ingestor: SensorIngestor::spawn(database, config, events.clone()),
+        events,
+        line_number: 0,
+        accepted: 0,
+        rejected: 0,
+    };
+    let mut pending: Vec<u8> = Vec::new();
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                let _ = state
+                    .events
+                    .send(IngestEvent::StreamError {
+                        error: e.to_string(),
+                    })
+                    .await;
+                break;
+            }
+        };
+
+        pending.extend_from_slice(chunk.as_ref());
+        while let Some(end) = pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            state.handle_line(&line[..end]).await;
+        }
+    }
+
+    if !pending.is_empty() {
+        state.handle_line(&pending).await;
+    }
+
+    let LineIngest {
+        ingestor,
+        events,
+        accepted,
+        rejected,
+        ..
+    } = state;
+    let stats = ingestor.finish().await;
+    let _ = events
+        .send(IngestEvent::Completed {
+            accepted,
+            rejected,
+            written: stats.written,
+            failed: stats.failed,
+        })
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{DatabaseFactory, SensorQuery};
+
+    #[tokio::test]
+    async fn test_ingest_ndjson_reports_rejected_records() {
+        let database: SharedDatabase =
+            Arc::new(RwLock::new(DatabaseFactory::mock().await.unwrap()));
+        let chunks: Vec<Result<&[u8], String>> = vec![
+            Ok(b"{\"sensor_id\": \"radar\", \"timestamp\": 10, \"val"),
+            Ok(b"ue\": 1.5}\n{\"sensor_id\": \"\", \"timestamp\": 20, \"value\": 2.0}\n"),
+            Ok(b"not json\n\n{\"sensorId\": \"lidar\", \"timestamp\": 30, \"value\": 3.0}"),
+        ];
+        let (events_tx, mut events_rx) = mpsc::channel(16);
+
+        ingest_ndjson(
+            futures::stream::iter(chunks),
+            database.clone(),
+            IngestionConfig {
+                batch_size: 1,
+                ..Default::default()
+            },
+            events_tx,
+        )
+        .await;
+
+        let mut events = Vec::new();
+        while let Some(event) = events_rx.recv().await {
+            events.push(event);
+        }
+
+        let rejected_lines: Vec<u64> = events
+            .iter()
+            .filter_map(|e| match e {
+                IngestEvent::Rejected { line, .. } => Some(*line),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(rejected_lines, vec![2, 3]);
+        assert_eq!(
+            events.last(),
+            Some(&IngestEvent::Completed {
+                accepted: 2,
+                rejected: 2,
+                written: 2,
+                failed: 0,
+            })
+        );
+
+        let stored = database
+            .read()
+            .await
+            .query_readings(&SensorQuery::time_range(0, 100))
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[0].payload, 1.5f64.to_le_bytes().to_vec());
+    }
+
+    #[test]
+    fn test_ingest_record_validation() {
+        let record = IngestRecord {
+            sensor_id: "imu".to_string(),
+            timestamp: 5,
+            value: f64::NAN,
+        };
+        assert!(record.into_reading().is_err());
+    }
+}
@@ -6,8 +6,11 @@
 pub mod config;
 pub mod dataset;
 pub mod error;
+pub mod export;
 pub mod factory;
+pub mod ingestion;
 pub mod models;
+pub mod storage;
 pub mod traits;
 
 #[cfg(test)]
@@ -86,8 +89,12 @@ pub mod redis {
 pub use config::DatabaseConfig;
 pub use dataset::*;
 pub use error::{DatabaseError, DatabaseResult};
+pub use export::{
+    export_sensor_data, export_sensor_data_with, ExportFormat, ExportSummary, EXPORT_WINDOW_US,
+};
 pub use factory::DatabaseFactory;
 pub use models::*;
+pub use storage::{DatabaseStorage, StorageBackend, StorageFactory, StorageRegistry};
 pub use traits::*;
 
 /// Version of the database schema/API
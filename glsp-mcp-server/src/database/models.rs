@@ -1,8 +1,9 @@
 //! Sensor data models and types
 
+use crate::database::{DatabaseError, DatabaseResult};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Sensor reading with metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -208,6 +209,65 @@ pub struct TimeRange {
     pub data_size_bytes: u64,
 }
 
+/// Parse a timestamp given as an RFC 3339 string or as integer milliseconds
+/// since the Unix epoch (a JSON number or a string of digits)
+///
+/// Returns microseconds since the Unix epoch, the unit used internally.
+/// `name` labels the value in the `TimeRangeError` for missing or
+/// unparseable input.
+pub fn parse_timestamp(name: &str, value: &serde_json::Value) -> DatabaseResult<i64> {
+    let invalid = |detail: String| {
+        DatabaseError::TimeRangeError(format!(
+            "{name} {detail}; expected an RFC 3339 timestamp such as \
+             '2024-05-01T12:00:00Z' or integer epoch milliseconds"
+        ))
+    };
+    let millis = match value {
+        serde_json::Value::Null => return Err(invalid("is missing".to_string())),
+        serde_json::Value::Number(number) => number
+            .as_i64()
+            .ok_or_else(|| invalid(format!("{number} is not an integer")))?,
+        serde_json::Value::String(text) => {
+            let text = text.trim();
+            if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+                return Ok(time.with_timezone(&Utc).timestamp_micros());
+            }
+            text.parse::<i64>()
+                .map_err(|_| invalid(format!("'{text}' is not a timestamp")))?
+        }
+        other => return Err(invalid(format!("{other} is not a timestamp"))),
+    };
+    millis
+        .checked_mul(1000)
+        .ok_or_else(|| invalid(format!("{millis} is out of range")))
+}
+
+/// Parse the start and end of a range with [`parse_timestamp`]; fails with
+/// `TimeRangeError` when the start is after the end
+pub fn parse_time_range(
+    start: &serde_json::Value,
+    end: &serde_json::Value,
+) -> DatabaseResult<(i64, i64)> {
+    let start_us = parse_timestamp("startTime", start)?;
+    let end_us = parse_timestamp("endTime", end)?;
+    if start_us > end_us {
+        return Err(DatabaseError::TimeRangeError(format!(
+            "startTime {} is after endTime {}",
+            format_timestamp(start_us),
+            format_timestamp(end_us)
+        )));
+    }
+    Ok((start_us, end_us))
+}
+
+/// Canonical form of timestamps in results: RFC 3339 in UTC with
+/// microsecond precision, e.g. `2024-05-01T12:00:00.000000Z`
+pub fn format_timestamp(timestamp_us: i64) -> String {
+    DateTime::from_timestamp_micros(timestamp_us)
+        .map(|time| time.to_rfc3339_opts(chrono::SecondsFormat::Micros, true))
+        .unwrap_or_else(|| timestamp_us.to_string())
+}
+
 /// Statistics about sensor data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SensorStatistics {
@@ -230,6 +290,61 @@ pub struct SensorStatistics {
     pub total_size_bytes: u64,
 }
 
+/// Summary of a sensor's scalar values over a time range
+///
+/// `count` covers every reading in the range; the value statistics only
+/// consider readings that carry a scalar value. All value fields are `None`
+/// when the range holds no such readings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SensorValueStats {
+    pub sensor_id: String,
+    pub start_time_us: i64,
+    pub end_time_us: i64,
+    pub count: u64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    pub last: Option<f64>,
+    pub last_timestamp_us: Option<i64>,
+}
+
+/// Newest reading of a sensor
+///
+/// `value` is `None` when the newest reading carries no scalar value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatestReading {
+    pub sensor_id: String,
+    pub timestamp_us: i64,
+    pub value: Option<f64>,
+}
+
+/// A stretch of missing data between two consecutive readings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SensorGap {
+    /// Timestamp of the reading before the gap
+    pub start_time_us: i64,
+    /// Timestamp of the reading after the gap
+    pub end_time_us: i64,
+    pub duration_us: i64,
+    /// Readings the expected interval would have put inside the gap
+    pub missing_readings: u64,
+}
+
+/// Gaps in a sensor's readings over a time range
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SensorGapReport {
+    pub sensor_id: String,
+    pub start_time_us: i64,
+    pub end_time_us: i64,
+    pub expected_interval_us: i64,
+    pub reading_count: u64,
+    pub gaps: Vec<SensorGap>,
+}
+
 /// Health status of a database connection
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseHealth {
@@ -284,6 +399,23 @@ impl SensorReading {
         self.timestamp_us >= start_us && self.timestamp_us <= end_us
     }
 
+    /// Scalar value of the reading, if it has one.
+    ///
+    /// Uses the `value` metadata entry, falling back to an 8-byte
+    /// little-endian `f64` payload of a generic scalar sensor.
+    pub fn scalar_value(&self) -> Option<f64> {
+        if let Some(value) = self.metadata.get("value").and_then(|v| v.as_f64()) {
+            return Some(value);
+        }
+        match &self.data_type {
+            SensorDataType::Generic { sensor_type, .. } if sensor_type == "scalar" => {
+                let bytes: [u8; 8] = self.payload.as_slice().try_into().ok()?;
+                Some(f64::from_le_bytes(bytes))
+            }
+            _ => None,
+        }
+    }
+
     /// Convert timestamp to DateTime
     pub fn timestamp(&self) -> DateTime<Utc> {
         DateTime::from_timestamp(
@@ -294,7 +426,164 @@ impl SensorReading {
     }
 }
 
+impl SensorValueStats {
+    /// Compute the statistics from readings of a single sensor
+    pub fn from_readings(
+        sensor_id: &str,
+        start_time_us: i64,
+        end_time_us: i64,
+        readings: &[SensorReading],
+    ) -> Self {
+        let mut stats = Self {
+            sensor_id: sensor_id.to_string(),
+            start_time_us,
+            end_time_us,
+            count: readings.len() as u64,
+            min: None,
+            max: None,
+            mean: None,
+            last: None,
+            last_timestamp_us: None,
+        };
+
+        let mut sum = 0.0;
+        let mut values = 0u64;
+        for reading in readings {
+            let Some(value) = reading.scalar_value() else {
+                continue;
+            };
+            stats.min = Some(stats.min.map_or(value, |min| min.min(value)));
+            stats.max = Some(stats.max.map_or(value, |max| max.max(value)));
+            sum += value;
+            values += 1;
+            if stats
+                .last_timestamp_us
+                .is_none_or(|last| reading.timestamp_us >= last)
+            {
+                stats.last = Some(value);
+                stats.last_timestamp_us = Some(reading.timestamp_us);
+            }
+        }
+        if values > 0 {
+            stats.mean = Some(sum / values as f64);
+        }
+
+        stats
+    }
+}
+
+/// How far past the expected interval, as a fraction of it, two readings may
+/// be spaced before they count as a gap; closer readings are jitter
+pub const GAP_TOLERANCE: f64 = 0.5;
+
+impl SensorGapReport {
+    /// Find the gaps in readings of a single sensor, in any order.
+    ///
+    /// Readings count as a gap once they are more than [`GAP_TOLERANCE`]
+    /// intervals late, so every gap misses at least one reading. Only the spacing between consecutive readings counts: the range
+    /// boundaries are not readings, so a recording that starts late or ends
+    /// early has no gap at either end.
+    pub fn from_readings(
+        sensor_id: &str,
+        start_time_us: i64,
+        end_time_us: i64,
+        expected_interval_us: i64,
+        readings: &[SensorReading],
+    ) -> Self {
+        let mut timestamps: Vec<i64> = readings.iter().map(|r| r.timestamp_us).collect();
+        timestamps.sort_unstable();
+
+        let threshold = expected_interval_us as f64 * (1.0 + GAP_TOLERANCE);
+        let gaps = timestamps
+            .windows(2)
+            .filter(|pair| (pair[1] - pair[0]) as f64 > threshold)
+            .map(|pair| {
+                let duration_us = pair[1] - pair[0];
+                SensorGap {
+                    start_time_us: pair[0],
+                    end_time_us: pair[1],
+                    duration_us,
+                    // Rounded, so that jitter does not add a missed reading
+                    missing_readings: ((duration_us as f64 / expected_interval_us as f64).round()
+                        as u64)
+                        .saturating_sub(1),
+                }
+            })
+            .collect();
+
+        Self {
+            sensor_id: sensor_id.to_string(),
+            start_time_us,
+            end_time_us,
+            expected_interval_us,
+            reading_count: timestamps.len() as u64,
+            gaps,
+        }
+    }
+}
+
+impl From<&SensorReading> for LatestReading {
+    fn from(reading: &SensorReading) -> Self {
+        Self {
+            sensor_id: reading.sensor_id.clone(),
+            timestamp_us: reading.timestamp_us,
+            value: reading.scalar_value(),
+        }
+    }
+}
+
+impl LatestReading {
+    /// Pick the newest of `readings` for each of `sensor_ids`.
+    ///
+    /// The result follows the order of `sensor_ids` with duplicates removed;
+    /// sensors without readings are left out. Of several readings sharing the
+    /// newest timestamp, the last one wins.
+    pub fn select<'a>(
+        sensor_ids: &[String],
+        readings: impl IntoIterator<Item = &'a SensorReading>,
+    ) -> Vec<Self> {
+        let mut newest: HashMap<&str, &SensorReading> = HashMap::new();
+        for reading in readings {
+            if !sensor_ids.contains(&reading.sensor_id) {
+                continue;
+            }
+            let entry = newest.entry(reading.sensor_id.as_str()).or_insert(reading);
+            if reading.timestamp_us >= entry.timestamp_us {
+                *entry = reading;
+            }
+        }
+
+        let mut seen = HashSet::new();
+        sensor_ids
+            .iter()
+            .filter(|id| seen.insert(id.as_str()))
+            .filter_map(|id| newest.get(id.as_str()).map(|r| Self::from(*r)))
+            .collect()
+    }
+}
+
 impl SensorQuery {
+    /// Check the time range and downsample interval before running the query
+    ///
+    /// Fails with `TimeRangeError` for an inverted range or an interval that
+    /// is not positive.
+    pub fn validate(&self) -> DatabaseResult<()> {
+        if self.start_time_us > self.end_time_us {
+            return Err(DatabaseError::TimeRangeError(format!(
+                "start {} is after end {}",
+                self.start_time_us, self.end_time_us
+            )));
+        }
+        if let Some(interval_us) = self.downsample_interval_us {
+            if interval_us <= 0 {
+                return Err(DatabaseError::TimeRangeError(format!(
+                    "interval {interval_us}us is not positive"
+                )));
+            }
+        }
+        Ok(())
+    }
+
     /// Create a simple time range query
     pub fn time_range(start_us: i64, end_us: i64) -> Self {
         Self {
@@ -325,6 +614,12 @@ impl SensorQuery {
         self.min_quality = Some(quality);
         self
     }
+
+    /// Add downsample interval
+    pub fn with_downsample_interval(mut self, interval_us: i64) -> Self {
+        self.downsample_interval_us = Some(interval_us);
+        self
+    }
 }
 
 impl Vec3 {
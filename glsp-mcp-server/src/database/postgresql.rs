@@ -938,6 +938,123 @@ impl SensorDataRepository for PostgreSQLBackend {
 
         Ok(result.rows_affected())
     }
+    async fn sensor_stats(
+        &self,
+        sensor_id: &str,
+        start_time_us: i64,
+        end_time_us: i64,
+    ) -> DatabaseResult<SensorValueStats> {
+        let pool = self.pool.as_ref().ok_or_else(|| {
+            DatabaseError::ConnectionFailed("Not connected to database".to_string())
+        })?;
+
+        let known: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM sensor_readings WHERE sensor_id = $1)")
+                .bind(sensor_id)
+                .fetch_one(pool)
+                .await
+                .map_err(|e| {
+                    DatabaseError::QueryFailed(format!("Failed to look up sensor: {e}"))
+                })?;
+        if !known {
+            return Err(DatabaseError::SensorNotFound(sensor_id.to_string()));
+        }
+
+        // Scalar values live in the `value` metadata entry (see ingestion)
+        let row = sqlx::query(
+            r#"
+            WITH readings AS (
+                SELECT
+                    CASE WHEN jsonb_typeof(metadata->'value') = 'number'
+                        THEN (metadata->>'value')::float8
+                    END AS value
+                FROM sensor_readings
+                WHERE sensor_id = $1 AND timestamp_us >= $2 AND timestamp_us <= $3
+            )
+            SELECT
+                COUNT(*) AS reading_count,
+                MIN(value) AS min_value,
+                MAX(value) AS max_value,
+                AVG(value) AS mean_value
+            FROM readings
+            "#,
+        )
+        .bind(sensor_id)
+        .bind(start_time_us)
+        .bind(end_time_us)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| DatabaseError::QueryFailed(format!("Failed to aggregate readings: {e}")))?;
+
+        let last = sqlx::query(
+            r#"
+            SELECT timestamp_us, (metadata->>'value')::float8 AS value
+            FROM sensor_readings
+            WHERE sensor_id = $1 AND timestamp_us >= $2 AND timestamp_us <= $3
+                AND jsonb_typeof(metadata->'value') = 'number'
+            ORDER BY timestamp_us DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(sensor_id)
+        .bind(start_time_us)
+        .bind(end_time_us)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| DatabaseError::QueryFailed(format!("Failed to get last reading: {e}")))?;
+
+        let reading_count: i64 = row.get("reading_count");
+        Ok(SensorValueStats {
+            sensor_id: sensor_id.to_string(),
+            start_time_us,
+            end_time_us,
+            count: reading_count as u64,
+            min: row.get("min_value"),
+            max: row.get("max_value"),
+            mean: row.get("mean_value"),
+            last: last.as_ref().map(|r| r.get("value")),
+            last_timestamp_us: last.as_ref().map(|r| r.get("timestamp_us")),
+        })
+    }
+
+    async fn latest_readings(&self, sensor_ids: &[String]) -> DatabaseResult<Vec<LatestReading>> {
+        let pool = self.pool.as_ref().ok_or_else(|| {
+            DatabaseError::ConnectionFailed("Not connected to database".to_string())
+        })?;
+
+        // One row per sensor: the newest, picked by the (sensor_id, timestamp_us) index
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT ON (sensor_id)
+                sensor_id, timestamp_us, data_type, payload, quality, metadata, checksum
+            FROM sensor_readings
+            WHERE sensor_id = ANY($1)
+            ORDER BY sensor_id, timestamp_us DESC
+            "#,
+        )
+        .bind(sensor_ids)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| DatabaseError::QueryFailed(format!("Failed to get latest readings: {e}")))?;
+
+        let mut readings = Vec::with_capacity(rows.len());
+        for row in rows {
+            let data_type: serde_json::Value = row.get("data_type");
+            let metadata: serde_json::Value = row.get("metadata");
+            readings.push(SensorReading {
+                sensor_id: row.get("sensor_id"),
+                timestamp_us: row.get("timestamp_us"),
+                data_type: serde_json::from_value(data_type)
+                    .map_err(|e| DatabaseError::SerializationError(e.to_string()))?,
+                payload: row.get("payload"),
+                quality: row.get("quality"),
+                metadata: serde_json::from_value(metadata)
+                    .map_err(|e| DatabaseError::SerializationError(e.to_string()))?,
+                checksum: row.get("checksum"),
+            });
+        }
+        Ok(LatestReading::select(sensor_ids, &readings))
+    }
 }
 
 #[async_trait]
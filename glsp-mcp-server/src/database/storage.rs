@@ -0,0 +1,303 @@
+//! Storage backends selected by name
+//!
+//! [`StorageBackend`] is everything the server needs from a store: sensor
+//! writes, reads and statistics, plus diagram persistence. A
+//! [`StorageRegistry`] maps backend names, as given by `--database-backend`,
+//! to factories producing one, so a new store (SQLite, Postgres, ...) becomes
+//! available by registering a factory under its name. Unknown names are
+//! rejected with [`DatabaseError::ConfigurationError`].
+//!
+//! The built-in names wrap the [`DatabaseFactory`] backends in a
+//! [`DatabaseStorage`], which keeps each diagram as JSON under a
+//! `diagram:{id}` configuration key.
+
+use crate::database::{
+    config::{DatabaseBackend, DatabaseConfig},
+    DatabaseError, DatabaseFactory, DatabaseInterface, DatabaseResult, SensorBatch, SensorQuery,
+    SensorReading, SensorStatistics,
+};
+use crate::model::DiagramModel;
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use std::collections::BTreeMap;
+
+/// Configuration key prefix under which [`DatabaseStorage`] keeps diagrams
+pub const DIAGRAM_KEY_PREFIX: &str = "diagram:";
+
+/// A store for sensor data and diagrams
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// Name the backend was created under
+    fn name(&self) -> &str;
+
+    /// Store sensor readings
+    async fn write_readings(&mut self, readings: &[SensorReading]) -> DatabaseResult<()>;
+
+    /// Query sensor readings
+    async fn read_readings(&self, query: &SensorQuery) -> DatabaseResult<Vec<SensorReading>>;
+
+    /// IDs of all sensors with stored readings
+    async fn sensors(&self) -> DatabaseResult<Vec<String>>;
+
+    /// Statistics for one sensor
+    async fn sensor_statistics(&self, sensor_id: &str) -> DatabaseResult<SensorStatistics>;
+
+    /// Save a diagram, replacing any earlier version
+    async fn save_diagram(&mut self, diagram: &DiagramModel) -> DatabaseResult<()>;
+
+    /// Load a diagram, or `None` if it was never saved or has been deleted
+    async fn load_diagram(&self, diagram_id: &str) -> DatabaseResult<Option<DiagramModel>>;
+
+    /// IDs of all saved diagrams, sorted
+    async fn diagram_ids(&self) -> DatabaseResult<Vec<String>>;
+
+    /// Delete a diagram; returns whether it existed
+    async fn delete_diagram(&mut self, diagram_id: &str) -> DatabaseResult<bool>;
+}
+
+/// A [`StorageBackend`] over any [`DatabaseInterface`]
+pub struct DatabaseStorage {
+    name: String,
+    database: Box<dyn DatabaseInterface>,
+}
+
+impl DatabaseStorage {
+    pub fn new(name: impl Into<String>, database: Box<dyn DatabaseInterface>) -> Self {
+        Self {
+            name: name.into(),
+            database,
+        }
+    }
+
+    /// The wrapped database, for capabilities beyond [`StorageBackend`]
+    pub fn database(&self) -> &dyn DatabaseInterface {
+        self.database.as_ref()
+    }
+}
+
+#[async_trait]
+impl StorageBackend for DatabaseStorage {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn write_readings(&mut self, readings: &[SensorReading]) -> DatabaseResult<()> {
+        match readings {
+            [] => Ok(()),
+            [reading] => self.database.store_reading(reading).await,
+            _ => {
+                let batch = SensorBatch {
+                    readings: readings.to_vec(),
+                    batch_id: crate::model::generate_id(),
+                    created_at: chrono::Utc::now(),
+                    source: self.name.clone(),
+                };
+                self.database.store_batch(&batch).await
+            }
+        }
+    }
+
+    async fn read_readings(&self, query: &SensorQuery) -> DatabaseResult<Vec<SensorReading>> {
+        self.database.query_readings(query).await
+    }
+
+    async fn sensors(&self) -> DatabaseResult<Vec<String>> {
+        self.database.list_sensors().await
+    }
+
+    async fn sensor_statistics(&self, sensor_id: &str) -> DatabaseResult<SensorStatistics> {
+        self.database.get_sensor_statistics(sensor_id).await
+    }
+
+    async fn save_diagram(&mut self, diagram: &DiagramModel) -> DatabaseResult<()> {
+        let value = serde_json::to_value(diagram)
+            .map_err(|e| DatabaseError::SerializationError(e.to_string()))?;
+        self.database
+            .store_config(&format!("{DIAGRAM_KEY_PREFIX}{}", diagram.id), &value)
+            .await
+    }
+
+    async fn load_diagram(&self, diagram_id: &str) -> DatabaseResult<Option<DiagramModel>> {
+        // Deleted diagrams are stored as null, as there is no way to remove
+        // a configuration key
+        match self
+            .database
+            .get_config(&format!("{DIAGRAM_KEY_PREFIX}{diagram_id}"))
+            .await?
+        {
+            None | Some(serde_json::Value::Null) => Ok(None),
+            Some(value) => serde_json::from_value(value)
+                .map(Some)
+                .map_err(|e| DatabaseError::SerializationError(e.to_string())),
+        }
+    }
+
+    async fn diagram_ids(&self) -> DatabaseResult<Vec<String>> {
+        let mut ids = Vec::new();
+        for key in self.database.list_config_keys().await? {
+            if let Some(id) = key.strip_prefix(DIAGRAM_KEY_PREFIX) {
+                if self.load_diagram(id).await?.is_some() {
+                    ids.push(id.to_string());
+                }
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    async fn delete_diagram(&mut self, diagram_id: &str) -> DatabaseResult<bool> {
+        let existed = self.load_diagram(diagram_id).await?.is_some();
+        if existed {
+            self.database
+                .store_config(
+                    &format!("{DIAGRAM_KEY_PREFIX}{diagram_id}"),
+                    &serde_json::Value::Null,
+                )
+                .await?;
+        }
+        Ok(existed)
+    }
+}
+
+/// Creates a storage backend from the database configuration
+pub type StorageFactory =
+    fn(DatabaseConfig) -> BoxFuture<'static, DatabaseResult<Box<dyn StorageBackend>>>;
+
+/// Storage backends by name
+#[derive(Clone)]
+pub struct StorageRegistry {
+    factories: BTreeMap<String, StorageFactory>,
+}
+
+impl StorageRegistry {
+    /// A registry without any backends
+    pub fn empty() -> Self {
+        Self {
+            factories: BTreeMap::new(),
+        }
+    }
+
+    /// Register a backend under a name, replacing any earlier registration.
+    /// Names are matched case-insensitively.
+    pub fn register(&mut self, name: &str, factory: StorageFactory) -> &mut Self {
+        self.factories.insert(name.to_ascii_lowercase(), factory);
+        self
+    }
+
+    /// Registered backend names, sorted
+    pub fn names(&self) -> Vec<&str> {
+        self.factories.keys().map(String::as_str).collect()
+    }
+
+    /// Look up a backend's factory, failing with a configuration error for
+    /// unknown names
+    pub fn resolve(&self, name: &str) -> DatabaseResult<StorageFactory> {
+        self.factories
+            .get(&name.trim().to_ascii_lowercase())
+            .copied()
+            .ok_or_else(|| {
+                DatabaseError::ConfigurationError(format!(
+                    "Unknown storage backend '{name}' (available: {})",
+                    self.names().join(", ")
+                ))
+            })
+    }
+
+    /// Create the backend registered under `name`
+    pub async fn create(
+        &self,
+        name: &str,
+        config: DatabaseConfig,
+    ) -> DatabaseResult<Box<dyn StorageBackend>> {
+        let factory = self.resolve(name)?;
+        factory(config).await
+    }
+}
+
+impl Default for StorageRegistry {
+    /// The built-in backends: `postgresql` (or `postgres`), `influxdb` (or
+    /// `influx`), `redis`, `sqlite`, and `mock` (or `memory`)
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry
+            .register("postgresql", |config| {
+                Box::pin(database_storage(
+                    "postgresql",
+                    DatabaseBackend::PostgreSQL,
+                    config,
+                ))
+            })
+            .register("postgres", |config| {
+                Box::pin(database_storage(
+                    "postgresql",
+                    DatabaseBackend::PostgreSQL,
+                    config,
+                ))
+            })
+            .register("influxdb", |config| {
+                Box::pin(database_storage(
+                    "influxdb",
+                    DatabaseBackend::InfluxDB,
+                    config,
+                ))
+            })
+            .register("influx", |config| {
+                Box::pin(database_storage(
+                    "influxdb",
+                    DatabaseBackend::InfluxDB,
+                    config,
+                ))
+            })
+            .register("redis", |config| {
+                Box::pin(database_storage("redis", DatabaseBackend::Redis, config))
+            })
+            .register("sqlite", |config| {
+                Box::pin(database_storage("sqlite", DatabaseBackend::SQLite, config))
+            })
+            .register("mock", |config| {
+                Box::pin(database_storage("mock", DatabaseBackend::Mock, config))
+            })
+            .register("memory", |config| {
+                Box::pin(database_storage("memory", DatabaseBackend::Mock, config))
+            });
+        registry
+    }
+}
+
+async fn database_storage(
+    name: &'static str,
+    backend: DatabaseBackend,
+    config: DatabaseConfig,
+) -> DatabaseResult<Box<dyn StorageBackend>> {
+    let database = DatabaseFactory::create(DatabaseConfig { backend, ..config }).await?;
+    Ok(Box::new(DatabaseStorage::new(name, database)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_registry_selects_backend_by_name() -> DatabaseResult<()> {
+        let registry = StorageRegistry::default();
+        assert!(matches!(
+            registry.resolve("cassandra"),
+            Err(DatabaseError::ConfigurationError(_))
+        ));
+
+        let mut storage = registry.create("Memory", DatabaseConfig::mock()).await?;
+        assert_eq!(storage.name(), "memory");
+
+        let diagram = DiagramModel::new("workflow");
+        storage.save_diagram(&diagram).await?;
+        assert_eq!(storage.diagram_ids().await?, vec![diagram.id.clone()]);
+        let loaded = storage.load_diagram(&diagram.id).await?.unwrap();
+        assert_eq!(loaded.root.id, diagram.root.id);
+
+        assert!(storage.delete_diagram(&diagram.id).await?);
+        assert!(!storage.delete_diagram(&diagram.id).await?);
+        assert!(storage.load_diagram(&diagram.id).await?.is_none());
+        assert!(storage.diagram_ids().await?.is_empty());
+        Ok(())
+    }
+}
@@ -464,3 +464,300 @@ async fn test_dataset_source_types() {
         // Note: Can't directly compare due to Custom variant, but serialization should work
     }
 }
+
+#[tokio::test]
+async fn test_sensor_stats_over_range() -> DatabaseResult<()> {
+    let mut backend = factory::MockDatabaseBackend::new(DatabaseConfig::mock()).await?;
+    let readings: Vec<SensorReading> = [(10, 4.0), (20, -1.0), (30, 3.0), (40, 9.0)]
+        .into_iter()
+        .map(|(timestamp_us, value)| {
+            let mut reading = SensorReading::new(
+                "speed".to_string(),
+                timestamp_us,
+                SensorDataType::Generic {
+                    sensor_type: "scalar".to_string(),
+                    data_size: 8,
+                },
+                f64::to_le_bytes(value).to_vec(),
+            );
+            if timestamp_us == 40 {
+                // Values may also be carried in metadata
+                reading.payload.clear();
+                reading
+                    .metadata
+                    .insert("value".to_string(), serde_json::json!(value));
+            }
+            reading
+        })
+        .collect();
+    backend
+        .store_batch(&SensorBatch {
+            readings,
+            batch_id: "stats".to_string(),
+            created_at: Utc::now(),
+            source: "test".to_string(),
+        })
+        .await?;
+
+    let stats = backend.sensor_stats("speed", 0, 30).await?;
+    assert_eq!(stats.count, 3);
+    assert_eq!(stats.min, Some(-1.0));
+    assert_eq!(stats.max, Some(4.0));
+    assert_eq!(stats.mean, Some(2.0));
+    assert_eq!(stats.last, Some(3.0));
+
+    let stats = backend.sensor_stats("speed", 15, 45).await?;
+    assert_eq!(stats.last, Some(9.0));
+    assert_eq!(stats.last_timestamp_us, Some(40));
+
+    // Known sensor, empty range: nulls rather than an error
+    let empty = backend.sensor_stats("speed", 1000, 2000).await?;
+    assert_eq!(empty.count, 0);
+    assert_eq!(empty.min, None);
+    assert_eq!(empty.mean, None);
+
+    assert!(matches!(
+        backend.sensor_stats("unknown", 0, 100).await,
+        Err(DatabaseError::SensorNotFound(_))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_latest_readings_skips_sensors_without_data() -> DatabaseResult<()> {
+    let mut backend = factory::MockDatabaseBackend::new(DatabaseConfig::mock()).await?;
+    let readings: Vec<SensorReading> = [("speed", 30, 3.0), ("brake", 5, 0.5), ("speed", 10, 1.0)]
+        .into_iter()
+        .map(|(sensor_id, timestamp_us, value)| {
+            SensorReading::new(
+                sensor_id.to_string(),
+                timestamp_us,
+                SensorDataType::Generic {
+                    sensor_type: "scalar".to_string(),
+                    data_size: 8,
+                },
+                f64::to_le_bytes(value).to_vec(),
+            )
+        })
+        .collect();
+    backend
+        .store_batch(&SensorBatch {
+            readings,
+            batch_id: "latest".to_string(),
+            created_at: Utc::now(),
+            source: "test".to_string(),
+        })
+        .await?;
+
+    let sensor_ids = ["brake", "speed", "lidar", "speed"].map(str::to_string);
+    let latest = backend.latest_readings(&sensor_ids).await?;
+    assert_eq!(
+        latest,
+        vec![
+            LatestReading {
+                sensor_id: "brake".to_string(),
+                timestamp_us: 5,
+                value: Some(0.5),
+            },
+            LatestReading {
+                sensor_id: "speed".to_string(),
+                timestamp_us: 30,
+                value: Some(3.0),
+            },
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_export_sensor_data_to_csv() -> DatabaseResult<()> {
+    let mut backend = factory::MockDatabaseBackend::new(DatabaseConfig::mock()).await?;
+    // Two readings in separate export windows, stored out of order
+    let readings = [(EXPORT_WINDOW_US + 5, 2.5), (1_000_000, 1.0)]
+        .into_iter()
+        .map(|(timestamp_us, value)| {
+            SensorReading::new(
+                "speed".to_string(),
+                timestamp_us,
+                SensorDataType::Generic {
+                    sensor_type: "scalar".to_string(),
+                    data_size: 8,
+                },
+                f64::to_le_bytes(value).to_vec(),
+            )
+        })
+        .collect();
+    backend
+        .store_batch(&SensorBatch {
+            readings,
+            batch_id: "export".to_string(),
+            created_at: Utc::now(),
+            source: "test".to_string(),
+        })
+        .await?;
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("speed.csv");
+    let summary = export_sensor_data(
+        &backend,
+        "speed",
+        0,
+        2 * EXPORT_WINDOW_US,
+        ExportFormat::Csv,
+        &path,
+    )
+    .await?;
+    assert_eq!(summary.rows, 2);
+    assert_eq!(
+        std::fs::read_to_string(&path)?,
+        "timestamp,value\n\
+         1970-01-01T00:00:01.000000Z,1\n\
+         1970-01-01T00:01:00.000005Z,2.5\n"
+    );
+
+    // Each written window is handed on as it is written
+    let mut windows = Vec::new();
+    export_sensor_data_with(
+        &backend,
+        "speed",
+        0,
+        2 * EXPORT_WINDOW_US,
+        ExportFormat::Csv,
+        &path,
+        |readings| windows.push(readings.iter().map(|r| r.timestamp_us).collect::<Vec<_>>()),
+    )
+    .await?;
+    assert_eq!(windows, vec![vec![1_000_000], vec![EXPORT_WINDOW_US + 5]]);
+
+    assert!(matches!(
+        export_sensor_data(&backend, "unknown", 0, 10, ExportFormat::Csv, &path).await,
+        Err(DatabaseError::SensorNotFound(_))
+    ));
+    assert!(matches!(
+        export_sensor_data(&backend, "speed", 10, 0, ExportFormat::Csv, &path).await,
+        Err(DatabaseError::TimeRangeError(_))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_detect_gaps_finds_dropped_frames() -> DatabaseResult<()> {
+    let mut backend = factory::MockDatabaseBackend::new(DatabaseConfig::mock()).await?;
+    // 10 Hz with one late frame, which is jitter rather than a gap,, then two and one dropped frames
+    let readings = [0, 100_000, 200_500, 300_000, 600_000, 700_000, 900_000]
+        .into_iter()
+        .map(|timestamp_us| {
+            SensorReading::new(
+                "camera_front".to_string(),
+                timestamp_us,
+                SensorDataType::Generic {
+                    sensor_type: "frame".to_string(),
+                    data_size: 1,
+                },
+                vec![0],
+            )
+        })
+        .collect();
+    backend
+        .store_batch(&SensorBatch {
+            readings,
+            batch_id: "gaps".to_string(),
+            created_at: Utc::now(),
+            source: "test".to_string(),
+        })
+        .await?;
+
+    let report = backend
+        .gap_report("camera_front", 0, 1_000_000, 100_000)
+        .await?;
+    assert_eq!(report.reading_count, 7);
+    let gaps: Vec<_> = report
+        .gaps
+        .iter()
+        .map(|g| (g.start_time_us, g.end_time_us, g.missing_readings))
+        .collect();
+    assert_eq!(gaps, vec![(300_000, 600_000, 2), (700_000, 900_000, 1)]);
+
+    assert!(matches!(
+        backend.gap_report("camera_front", 10, 0, 100_000).await,
+        Err(DatabaseError::TimeRangeError(_))
+    ));
+    assert!(matches!(
+        backend.gap_report("camera_front", 0, 10, 0).await,
+        Err(DatabaseError::TimeRangeError(_))
+    ));
+    assert!(matches!(
+        backend.gap_report("lidar", 0, 10, 100_000).await,
+        Err(DatabaseError::SensorNotFound(_))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_with_transaction_commits_only_successful_work() -> DatabaseResult<()> {
+    let mut backend: Box<dyn DatabaseInterface> =
+        Box::new(factory::MockDatabaseBackend::new(DatabaseConfig::mock()).await?);
+    let readings = create_test_readings();
+
+    // Failing work leaves nothing behind, even what it staged first
+    let staged = readings.clone();
+    let result: DatabaseResult<()> = backend
+        .with_transaction(move |transaction| {
+            Box::pin(async move {
+                transaction.store_reading(&staged[0]).await?;
+                Err(DatabaseError::QueryFailed("second step failed".to_string()))
+            })
+        })
+        .await;
+    assert!(matches!(result, Err(DatabaseError::QueryFailed(_))));
+    assert!(backend.list_sensors().await?.is_empty());
+
+    let staged = readings.clone();
+    let stored = backend
+        .with_transaction(move |transaction| {
+            Box::pin(async move {
+                for reading in &staged {
+                    transaction.store_reading(reading).await?;
+                }
+                Ok(staged.len())
+            })
+        })
+        .await?;
+    assert_eq!(stored, readings.len());
+    let mut sensors = backend.list_sensors().await?;
+    sensors.sort();
+    let mut expected: Vec<String> = readings.iter().map(|r| r.sensor_id.clone()).collect();
+    expected.sort();
+    expected.dedup();
+    assert_eq!(sensors, expected);
+    Ok(())
+}
+
+#[test]
+fn test_time_ranges_accept_rfc3339_and_epoch_millis() -> DatabaseResult<()> {
+    let rfc3339 = serde_json::json!("2024-05-01T12:00:00+02:00");
+    let millis = serde_json::json!(1_714_557_600_000_i64);
+    let digits = serde_json::json!("1714557600000");
+    let expected = 1_714_557_600_000_000;
+    for value in [&rfc3339, &millis, &digits] {
+        assert_eq!(parse_timestamp("startTime", value)?, expected);
+    }
+    assert_eq!(format_timestamp(expected), "2024-05-01T10:00:00.000000Z");
+
+    assert_eq!(
+        parse_time_range(&rfc3339, &serde_json::json!(1_714_557_601_000_i64))?,
+        (expected, expected + 1_000_000)
+    );
+    for (start, end) in [
+        (serde_json::json!("yesterday"), millis.clone()),
+        (serde_json::Value::Null, millis.clone()),
+        (serde_json::json!(1.5), millis.clone()),
+        (serde_json::json!(1_714_557_601_000_i64), rfc3339.clone()),
+    ] {
+        assert!(matches!(
+            parse_time_range(&start, &end),
+            Err(DatabaseError::TimeRangeError(_))
+        ));
+    }
+    Ok(())
+}
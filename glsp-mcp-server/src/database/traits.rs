@@ -1,10 +1,12 @@
 //! Database abstraction traits for exchangeable backends
 
 use crate::database::{
-    DatabaseHealth, DatabaseResult, SensorBatch, SensorMetadata, SensorQuery, SensorReading,
-    SensorStatistics, TimeRange,
+    DatabaseError, DatabaseHealth, DatabaseResult, LatestReading, SensorBatch, SensorGapReport,
+    SensorMetadata, SensorQuery, SensorReading, SensorStatistics, SensorValueStats, TimeRange,
 };
 use async_trait::async_trait;
+use futures::future::BoxFuture;
+use tracing::warn;
 
 /// Core database provider trait
 ///
@@ -72,6 +74,86 @@ pub trait SensorDataRepository: DatabaseProvider {
         start_time_us: i64,
         end_time_us: i64,
     ) -> DatabaseResult<u64>;
+
+    /// Get count, min, max, mean and last value of a sensor over a time range
+    ///
+    /// Returns `SensorNotFound` only for unknown sensors; an empty range
+    /// yields a zero count and no values. The default implementation fetches
+    /// the readings and aggregates them in Rust; backends that can aggregate
+    /// natively should override it.
+    async fn sensor_stats(
+        &self,
+        sensor_id: &str,
+        start_time_us: i64,
+        end_time_us: i64,
+    ) -> DatabaseResult<SensorValueStats> {
+        if !self.list_sensors().await?.iter().any(|s| s == sensor_id) {
+            return Err(DatabaseError::SensorNotFound(sensor_id.to_string()));
+        }
+
+        let query = SensorQuery::time_range(start_time_us, end_time_us)
+            .with_sensors(vec![sensor_id.to_string()]);
+        let readings = self.query_readings(&query).await?;
+        Ok(SensorValueStats::from_readings(
+            sensor_id,
+            start_time_us,
+            end_time_us,
+            &readings,
+        ))
+    }
+
+    /// Find stretches of missing data in a sensor's readings over a time range
+    ///
+    /// A gap is a pair of consecutive readings spaced wider than
+    /// `expected_interval_us` by more than
+    /// [`GAP_TOLERANCE`](crate::database::models::GAP_TOLERANCE) of it. Fails
+    /// with `TimeRangeError` for an inverted range or an interval that is not
+    /// positive, and with `SensorNotFound` for unknown sensors.
+    async fn gap_report(
+        &self,
+        sensor_id: &str,
+        start_time_us: i64,
+        end_time_us: i64,
+        expected_interval_us: i64,
+    ) -> DatabaseResult<SensorGapReport> {
+        SensorQuery::time_range(start_time_us, end_time_us)
+            .with_downsample_interval(expected_interval_us)
+            .validate()?;
+        if !self.list_sensors().await?.iter().any(|s| s == sensor_id) {
+            return Err(DatabaseError::SensorNotFound(sensor_id.to_string()));
+        }
+
+        let query = SensorQuery::time_range(start_time_us, end_time_us)
+            .with_sensors(vec![sensor_id.to_string()]);
+        let readings = self.query_readings(&query).await?;
+        Ok(SensorGapReport::from_readings(
+            sensor_id,
+            start_time_us,
+            end_time_us,
+            expected_interval_us,
+            &readings,
+        ))
+    }
+
+    /// Get the newest reading of each sensor
+    ///
+    /// Results follow the order of `sensor_ids`; sensors without readings,
+    /// unknown ones included, are skipped rather than reported as errors. The
+    /// default implementation looks up each sensor's time range and fetches
+    /// the readings at its end; backends that can select the last row per
+    /// sensor in one query should override it.
+    async fn latest_readings(&self, sensor_ids: &[String]) -> DatabaseResult<Vec<LatestReading>> {
+        let mut newest = Vec::new();
+        for sensor_id in sensor_ids {
+            let Some(range) = self.get_time_range(sensor_id).await? else {
+                continue;
+            };
+            let query = SensorQuery::time_range(range.end_time_us, range.end_time_us)
+                .with_sensors(vec![sensor_id.clone()]);
+            newest.extend(self.query_readings(&query).await?);
+        }
+        Ok(LatestReading::select(sensor_ids, &newest))
+    }
 }
 
 /// Time-series specific storage operations
@@ -204,6 +286,64 @@ pub trait DatabaseTransaction: Send + Sync {
     async fn rollback(self: Box<Self>) -> DatabaseResult<()>;
 }
 
+/// Transaction emulated by staging writes in memory
+///
+/// Used by [`DatabaseInterface::with_transaction`] for backends without
+/// transactions: nothing reaches the backend until the work succeeds, and
+/// the staged readings are then written as a single batch.
+#[derive(Debug, Default)]
+pub struct StagedTransaction {
+    readings: Vec<SensorReading>,
+}
+
+impl StagedTransaction {
+    /// Readings staged so far
+    pub fn readings(&self) -> &[SensorReading] {
+        &self.readings
+    }
+
+    fn into_batch(self) -> SensorBatch {
+        SensorBatch {
+            readings: self.readings,
+            batch_id: crate::model::generate_id(),
+            created_at: chrono::Utc::now(),
+            source: "transaction".to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl DatabaseTransaction for StagedTransaction {
+    async fn store_reading(&mut self, reading: &SensorReading) -> DatabaseResult<()> {
+        self.readings.push(reading.clone());
+        Ok(())
+    }
+
+    async fn store_batch(&mut self, batch: &SensorBatch) -> DatabaseResult<()> {
+        self.readings.extend(batch.readings.iter().cloned());
+        Ok(())
+    }
+
+    async fn commit(self: Box<Self>) -> DatabaseResult<()> {
+        Ok(())
+    }
+
+    async fn rollback(self: Box<Self>) -> DatabaseResult<()> {
+        Ok(())
+    }
+}
+
+/// Report a failure of the transaction itself, rather than of the work
+/// done in it, as [`DatabaseError::TransactionFailed`]
+fn transaction_failed(stage: &str, error: DatabaseError) -> DatabaseError {
+    match error {
+        DatabaseError::TransactionFailed(_) => error,
+        other => {
+            DatabaseError::TransactionFailed(format!("Failed to {stage} transaction: {other}"))
+        }
+    }
+}
+
 /// Comprehensive database interface combining all capabilities
 ///
 /// This is the main interface that provides access to all database
@@ -222,6 +362,51 @@ pub trait DatabaseInterface:
         None
     }
 
+    /// Run `work` in a transaction: commit when it succeeds and roll back
+    /// when it fails, returning its error.
+    ///
+    /// Backends without a [`TransactionProvider`] get a
+    /// [`StagedTransaction`]. Failures to begin, commit or flush the
+    /// transaction are reported as [`DatabaseError::TransactionFailed`].
+    async fn with_transaction<T, F>(&mut self, work: F) -> DatabaseResult<T>
+    where
+        Self: Sized,
+        T: Send,
+        F: for<'t> FnOnce(&'t mut dyn DatabaseTransaction) -> BoxFuture<'t, DatabaseResult<T>>
+            + Send,
+    {
+        if let Some(provider) = self.transaction_provider() {
+            let mut transaction = provider
+                .begin_transaction()
+                .await
+                .map_err(|e| transaction_failed("begin", e))?;
+            match work(transaction.as_mut()).await {
+                Ok(value) => {
+                    transaction
+                        .commit()
+                        .await
+                        .map_err(|e| transaction_failed("commit", e))?;
+                    Ok(value)
+                }
+                Err(e) => {
+                    if let Err(rollback) = transaction.rollback().await {
+                        warn!("Failed to roll back transaction: {}", rollback);
+                    }
+                    Err(e)
+                }
+            }
+        } else {
+            let mut staged = StagedTransaction::default();
+            let value = work(&mut staged).await?;
+            if !staged.readings.is_empty() {
+                self.store_batch(&staged.into_batch())
+                    .await
+                    .map_err(|e| transaction_failed("commit", e))?;
+            }
+            Ok(value)
+        }
+    }
+
     /// Get supported features
     fn supported_features(&self) -> DatabaseFeatures;
 
@@ -384,6 +569,32 @@ impl SensorDataRepository for Box<dyn DatabaseInterface> {
             .delete_readings(sensor_id, start_time_us, end_time_us)
             .await
     }
+    async fn sensor_stats(
+        &self,
+        sensor_id: &str,
+        start_time_us: i64,
+        end_time_us: i64,
+    ) -> DatabaseResult<SensorValueStats> {
+        self.as_ref()
+            .sensor_stats(sensor_id, start_time_us, end_time_us)
+            .await
+    }
+
+    async fn gap_report(
+        &self,
+        sensor_id: &str,
+        start_time_us: i64,
+        end_time_us: i64,
+        expected_interval_us: i64,
+    ) -> DatabaseResult<SensorGapReport> {
+        self.as_ref()
+            .gap_report(sensor_id, start_time_us, end_time_us, expected_interval_us)
+            .await
+    }
+
+    async fn latest_readings(&self, sensor_ids: &[String]) -> DatabaseResult<Vec<LatestReading>> {
+        self.as_ref().latest_readings(sensor_ids).await
+    }
 }
 
 #[async_trait]
@@ -470,6 +681,10 @@ impl MetadataStore for Box<dyn DatabaseInterface> {
 
 #[async_trait]
 impl DatabaseInterface for Box<dyn DatabaseInterface> {
+    fn transaction_provider(&mut self) -> Option<&mut dyn TransactionProvider> {
+        self.as_mut().transaction_provider()
+    }
+
     fn supported_features(&self) -> DatabaseFeatures {
         self.as_ref().supported_features()
     }
@@ -0,0 +1,520 @@
+//! Server-sent diagram events
+//!
+//! Mutating tool calls publish events on a broadcast bus that `/events`
+//! subscribers consume. Each connection may fall at most `buffer_size`
+//! events behind; a slower consumer overflows instead of growing memory.
+//! Depending on the [`OverflowPolicy`] the overflow is either coalesced into
+//! a single `resync` event, telling the client to refetch, or the connection
+//! is dropped. Both outcomes are counted in [`EventStreamMetrics`].
+//!
+//! Every subscription carries a [`DiagramFilter`]. Diagram events for other
+//! diagrams are skipped before they reach the connection; `resync` is always
+//! delivered. The filter can be replaced while the connection stays open by
+//! addressing the subscription by its ID; a subscription opened with an
+//! owner, such as the bearer token of the connection, only accepts updates
+//! from that owner.
+//!
+//! Updates that edit an element in place carry [`PropertyChange`]s with the
+//! old and new value of each field, so clients can animate the transition
+//! and audit logs can record exactly what changed.
+
+use crate::model::ModelElement;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tracing::warn;
+
+/// Default number of events a connection may lag behind
+pub const DEFAULT_EVENT_BUFFER_SIZE: usize = 256;
+
+/// An event delivered to `/events` subscribers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ServerEvent {
+    /// A diagram was modified by a tool call
+    #[serde(rename_all = "camelCase")]
+    DiagramUpdate {
+        diagram_id: String,
+        /// Revision after the change; absent if the diagram was deleted
+        revision: Option<u32>,
+        tool: String,
+        /// Fields the update changed, for tools that edit elements in place
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        changes: Vec<PropertyChange>,
+    },
+    /// Events were dropped for this connection; refetch any cached state
+    #[serde(rename_all = "camelCase")]
+    Resync { missed: u64 },
+    /// First event of a connection, naming its subscription
+    #[serde(rename_all = "camelCase")]
+    Subscribed {
+        subscription_id: String,
+        filter: DiagramFilter,
+    },
+}
+
+impl ServerEvent {
+    /// SSE event name
+    pub fn name(&self) -> &'static str {
+        match self {
+            ServerEvent::DiagramUpdate { .. } => "diagram-update",
+            ServerEvent::Resync { .. } => "resync",
+            ServerEvent::Subscribed { .. } => "subscribed",
+        }
+    }
+}
+
+/// One field of an element changed by an update.
+///
+/// Entries of `properties` and `style` are fields of their own, named
+/// `properties.<key>` and `style.<key>`. A field that did not exist before,
+/// or was removed, has a null old or new value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PropertyChange {
+    pub element_id: String,
+    pub field: String,
+    pub old_value: Value,
+    pub new_value: Value,
+}
+
+/// Fields that differ between two versions of an element, sorted by name.
+/// Timestamps are left out; they change with every edit.
+pub fn property_changes(before: &ModelElement, after: &ModelElement) -> Vec<PropertyChange> {
+    let fields = |element: &ModelElement| -> BTreeMap<String, Value> {
+        let Ok(Value::Object(object)) = serde_json::to_value(element) else {
+            return BTreeMap::new();
+        };
+        let mut fields = BTreeMap::new();
+        for (name, value) in object {
+            match (name.as_str(), value) {
+                ("created_at" | "updated_at", _) => {}
+                ("properties" | "style", Value::Object(entries)) => {
+                    fields.extend(
+                        entries
+                            .into_iter()
+                            .map(|(key, value)| (format!("{name}.{key}"), value)),
+                    );
+                }
+                (_, value) => {
+                    fields.insert(name, value);
+                }
+            }
+        }
+        fields
+    };
+    let (old, mut new) = (fields(before), fields(after));
+
+    let mut changes = Vec::new();
+    for (field, old_value) in old {
+        let new_value = new.remove(&field).unwrap_or(Value::Null);
+        if old_value != new_value {
+            changes.push(PropertyChange {
+                element_id: after.id.clone(),
+                field,
+                old_value,
+                new_value,
+            });
+        }
+    }
+    changes.extend(new.into_iter().map(|(field, new_value)| PropertyChange {
+        element_id: after.id.clone(),
+        field,
+        old_value: Value::Null,
+        new_value,
+    }));
+    changes.sort_by(|a, b| a.field.cmp(&b.field));
+    changes
+}
+
+/// Which diagrams a subscription receives events for
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagramFilter {
+    /// `None` means every diagram
+    pub diagram_ids: Option<BTreeSet<String>>,
+}
+
+impl DiagramFilter {
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    pub fn diagrams<I: IntoIterator<Item = String>>(ids: I) -> Self {
+        Self {
+            diagram_ids: Some(ids.into_iter().collect()),
+        }
+    }
+
+    /// Parse `all` or a comma-separated list of diagram IDs
+    pub fn parse_list(list: &str) -> Self {
+        if list.trim() == "all" {
+            return Self::all();
+        }
+        Self::diagrams(
+            list.split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(str::to_string),
+        )
+    }
+
+    /// Parse `"all"` or an array of diagram IDs
+    pub fn from_json(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::String(s) if s == "all" => Ok(Self::all()),
+            Value::Array(ids) => ids
+                .iter()
+                .map(|id| {
+                    id.as_str()
+                        .map(str::to_string)
+                        .ok_or_else(|| format!("Diagram IDs must be strings, got {id}"))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(Self::diagrams),
+            other => Err(format!(
+                "Expected \"all\" or an array of diagram IDs, got {other}"
+            )),
+        }
+    }
+
+    /// Whether events of the diagram pass the filter
+    pub fn includes(&self, diagram_id: &str) -> bool {
+        self.diagram_ids
+            .as_ref()
+            .is_none_or(|ids| ids.contains(diagram_id))
+    }
+
+    pub fn matches(&self, event: &ServerEvent) -> bool {
+        match event {
+            ServerEvent::DiagramUpdate { diagram_id, .. } => self.includes(diagram_id),
+            ServerEvent::Resync { .. } | ServerEvent::Subscribed { .. } => true,
+        }
+    }
+}
+
+/// What to do when a connection's buffer overflows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Replace the dropped events with a single `resync` event
+    #[default]
+    Resync,
+    /// Close the connection
+    Disconnect,
+}
+
+impl FromStr for OverflowPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "resync" => Ok(OverflowPolicy::Resync),
+            "disconnect" => Ok(OverflowPolicy::Disconnect),
+            other => Err(format!(
+                "Unknown SSE overflow policy '{other}' (expected resync or disconnect)"
+            )),
+        }
+    }
+}
+
+/// Counters describing the event stream
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventStreamMetrics {
+    pub connected_clients: usize,
+    pub published_events: u64,
+    pub resyncs: u64,
+    pub dropped_clients: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    connected_clients: AtomicUsize,
+    published_events: AtomicU64,
+    resyncs: AtomicU64,
+    dropped_clients: AtomicU64,
+}
+
+/// An open subscription's filter and the owner that may replace it
+#[derive(Debug)]
+struct Registration {
+    owner: Option<String>,
+    filter: DiagramFilter,
+}
+
+/// Registrations of the open subscriptions, by subscription ID
+type Filters = Arc<Mutex<HashMap<String, Registration>>>;
+
+/// Broadcast bus for server events
+#[derive(Debug)]
+pub struct EventBus {
+    sender: broadcast::Sender<ServerEvent>,
+    policy: OverflowPolicy,
+    counters: Arc<Counters>,
+    filters: Filters,
+}
+
+impl EventBus {
+    pub fn new(buffer_size: usize, policy: OverflowPolicy) -> Self {
+        let (sender, _) = broadcast::channel(buffer_size.max(1));
+        Self {
+            sender,
+            policy,
+            counters: Arc::new(Counters::default()),
+            filters: Arc::default(),
+        }
+    }
+
+    /// Publish an event to every connected subscriber
+    pub fn publish(&self, event: ServerEvent) {
+        self.counters
+            .published_events
+            .fetch_add(1, Ordering::Relaxed);
+        // Sending only fails when nobody is subscribed
+        let _ = self.sender.send(event);
+    }
+
+    /// Subscribe to every diagram's events
+    pub fn subscribe(&self) -> EventSubscription {
+        self.subscribe_filtered(DiagramFilter::all(), None)
+    }
+
+    /// Subscribe to the events `filter` lets through; only `owner` may
+    /// replace the filter later
+    pub fn subscribe_filtered(
+        &self,
+        filter: DiagramFilter,
+        owner: Option<String>,
+    ) -> EventSubscription {
+        self.counters
+            .connected_clients
+            .fetch_add(1, Ordering::Relaxed);
+        let id = uuid::Uuid::new_v4().to_string();
+        self.filters
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id.clone(), Registration { owner, filter });
+        EventSubscription {
+            id,
+            receiver: self.sender.subscribe(),
+            policy: self.policy,
+            counters: self.counters.clone(),
+            filters: self.filters.clone(),
+        }
+    }
+
+    /// Replace the filter of an open subscription; false if it is not open
+    /// or `owner` did not open it
+    pub fn update_subscription(
+        &self,
+        subscription_id: &str,
+        owner: Option<&str>,
+        filter: DiagramFilter,
+    ) -> bool {
+        match self
+            .filters
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_mut(subscription_id)
+        {
+            Some(current) if current.owner.as_deref() == owner => {
+                current.filter = filter;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn metrics(&self) -> EventStreamMetrics {
+        EventStreamMetrics {
+            connected_clients: self.counters.connected_clients.load(Ordering::Relaxed),
+            published_events: self.counters.published_events.load(Ordering::Relaxed),
+            resyncs: self.counters.resyncs.load(Ordering::Relaxed),
+            dropped_clients: self.counters.dropped_clients.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// One connection's view of the bus
+pub struct EventSubscription {
+    id: String,
+    receiver: broadcast::Receiver<ServerEvent>,
+    policy: OverflowPolicy,
+    counters: Arc<Counters>,
+    filters: Filters,
+}
+
+impl EventSubscription {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn filter(&self) -> DiagramFilter {
+        self.filters
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&self.id)
+            .map(|registration| registration.filter.clone())
+            .unwrap_or_default()
+    }
+
+    /// Next event for this connection, or `None` when the stream should end
+    pub async fn next(&mut self) -> Option<ServerEvent> {
+        loop {
+            let event = match self.receiver.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(missed)) => match self.policy {
+                    OverflowPolicy::Resync => {
+                        self.counters.resyncs.fetch_add(1, Ordering::Relaxed);
+                        return Some(ServerEvent::Resync { missed });
+                    }
+                    OverflowPolicy::Disconnect => {
+                        warn!("Dropping slow event stream client ({missed} events behind)");
+                        self.counters
+                            .dropped_clients
+                            .fetch_add(1, Ordering::Relaxed);
+                        return None;
+                    }
+                },
+                Err(broadcast::error::RecvError::Closed) => return None,
+            };
+            if self.filter().matches(&event) {
+                return Some(event);
+            }
+        }
+    }
+}
+
+impl Drop for EventSubscription {
+    fn drop(&mut self) {
+        self.filters
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.id);
+        self.counters
+            .connected_clients
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(revision: u32) -> ServerEvent {
+        update_of("d1", revision)
+    }
+
+    fn update_of(diagram_id: &str, revision: u32) -> ServerEvent {
+        ServerEvent::DiagramUpdate {
+            diagram_id: diagram_id.to_string(),
+            revision: Some(revision),
+            tool: "create_node".to_string(),
+            changes: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_client_gets_resync() {
+        let bus = EventBus::new(2, OverflowPolicy::Resync);
+        let mut subscription = bus.subscribe();
+        for revision in 1..=5 {
+            bus.publish(update(revision));
+        }
+
+        assert_eq!(
+            subscription.next().await,
+            Some(ServerEvent::Resync { missed: 3 })
+        );
+        assert_eq!(subscription.next().await, Some(update(4)));
+        assert_eq!(bus.metrics().resyncs, 1);
+    }
+
+    #[tokio::test]
+    async fn test_slow_client_dropped() {
+        let bus = EventBus::new(2, OverflowPolicy::Disconnect);
+        let mut subscription = bus.subscribe();
+        for revision in 1..=3 {
+            bus.publish(update(revision));
+        }
+
+        assert_eq!(subscription.next().await, None);
+        drop(subscription);
+        let metrics = bus.metrics();
+        assert_eq!(metrics.dropped_clients, 1);
+        assert_eq!(metrics.connected_clients, 0);
+    }
+
+    #[tokio::test]
+    async fn test_subscription_filter_can_be_updated() {
+        let bus = EventBus::new(16, OverflowPolicy::Resync);
+        let mut subscription = bus.subscribe_filtered(DiagramFilter::parse_list("d2"), None);
+        bus.publish(update_of("d1", 1));
+        bus.publish(update_of("d2", 1));
+        assert_eq!(subscription.next().await, Some(update_of("d2", 1)));
+
+        let all = DiagramFilter::from_json(&serde_json::json!("all")).unwrap();
+        assert!(bus.update_subscription(subscription.id(), None, all));
+        bus.publish(update_of("d1", 2));
+        assert_eq!(subscription.next().await, Some(update_of("d1", 2)));
+
+        let id = subscription.id().to_string();
+        drop(subscription);
+        assert!(!bus.update_subscription(&id, None, DiagramFilter::all()));
+    }
+
+    #[test]
+    fn test_only_the_owner_updates_a_subscription() {
+        let bus = EventBus::new(16, OverflowPolicy::Resync);
+        let subscription =
+            bus.subscribe_filtered(DiagramFilter::parse_list("d1"), Some("token-1".to_string()));
+        let id = subscription.id();
+
+        assert!(!bus.update_subscription(id, Some("token-2"), DiagramFilter::all()));
+        assert!(!bus.update_subscription(id, None, DiagramFilter::all()));
+        assert_eq!(subscription.filter(), DiagramFilter::parse_list("d1"));
+        assert!(bus.update_subscription(id, Some("token-1"), DiagramFilter::all()));
+        assert_eq!(subscription.filter(), DiagramFilter::all());
+    }
+
+    #[test]
+    fn test_property_changes_carry_old_and_new_values() {
+        use crate::model::{Node, Position};
+
+        let before = Node::new(
+            "task",
+            Position { x: 0.0, y: 0.0 },
+            Some("Brake".to_string()),
+        )
+        .base;
+        let mut after = before.clone();
+        after.label = Some("Emergency brake".to_string());
+        after
+            .properties
+            .insert("priority".to_string(), serde_json::json!(3));
+        after.touch();
+
+        let changes = property_changes(&before, &after);
+        let fields: Vec<(&str, &Value, &Value)> = changes
+            .iter()
+            .map(|c| (c.field.as_str(), &c.old_value, &c.new_value))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                (
+                    "label",
+                    &serde_json::json!("Brake"),
+                    &serde_json::json!("Emergency brake")
+                ),
+                ("properties.priority", &Value::Null, &serde_json::json!(3)),
+            ]
+        );
+        assert!(changes.iter().all(|c| c.element_id == before.id));
+        assert!(property_changes(&before, &before).is_empty());
+    }
+}
@@ -0,0 +1,221 @@
+//! Per-diagram audit trail of recent operations
+//!
+//! Every successful mutating tool call is recorded with when it happened, who
+//! made it (the call's `clientId`), which tool it was and which elements it
+//! created, changed or deleted. Handlers of frequent edits report the
+//! elements they touch with [`touched`] while the call runs inside
+//! [`collect`], which costs nothing per element they leave alone. Bulk
+//! operations such as layouts instead have their elements found by the same
+//! [`diff`](crate::oplog::diff) the operation log writes. Unlike the log, the
+//! trail is meant for people answering "who moved this node and when"; it is
+//! kept in memory and holds the most recent entries of each diagram only.
+
+use crate::oplog::LogOp;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+tokio::task_local! {
+    static TOUCHED: RefCell<HashMap<String, BTreeSet<String>>>;
+}
+
+/// Entries kept per diagram by default
+pub const DEFAULT_HISTORY_SIZE: usize = 200;
+
+/// One recorded operation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub timestamp: DateTime<Utc>,
+    /// `clientId` of the call, if it named one
+    pub actor: Option<String>,
+    /// Name of the tool that made the change
+    pub operation: String,
+    /// Diagram revision after the change
+    pub revision: Option<u32>,
+    /// Elements created, changed or deleted, sorted
+    pub element_ids: Vec<String>,
+}
+
+/// Which entries [`HistoryLog::recent`] returns
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub operation: Option<String>,
+    pub actor: Option<String>,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        self.operation
+            .as_ref()
+            .is_none_or(|operation| entry.operation == *operation)
+            && self
+                .actor
+                .as_ref()
+                .is_none_or(|actor| entry.actor.as_ref() == Some(actor))
+    }
+}
+
+/// Recent operations of every diagram, at most `capacity` per diagram
+#[derive(Debug)]
+pub struct HistoryLog {
+    capacity: usize,
+    entries: HashMap<String, VecDeque<HistoryEntry>>,
+}
+
+impl HistoryLog {
+    /// A log keeping `capacity` entries per diagram; 0 records nothing
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Record an operation, dropping the diagram's oldest entry when full
+    pub fn record(&mut self, diagram_id: &str, entry: HistoryEntry) {
+        if !self.is_enabled() {
+            return;
+        }
+        let entries = self.entries.entry(diagram_id.to_string()).or_default();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Up to `limit` entries matching `filter`, newest first
+    pub fn recent(
+        &self,
+        diagram_id: &str,
+        filter: &HistoryFilter,
+        limit: usize,
+    ) -> Vec<HistoryEntry> {
+        self.entries
+            .get(diagram_id)
+            .into_iter()
+            .flat_map(|entries| entries.iter().rev())
+            .filter(|entry| filter.matches(entry))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Run a tool call, collecting the elements it reports with [`touched`] by
+/// diagram, each list sorted
+pub async fn collect<F: std::future::Future>(
+    future: F,
+) -> (F::Output, HashMap<String, Vec<String>>) {
+    TOUCHED
+        .scope(RefCell::default(), async move {
+            let output = future.await;
+            let touched = TOUCHED.with(|touched| touched.take());
+            let touched = touched
+                .into_iter()
+                .map(|(diagram_id, ids)| (diagram_id, ids.into_iter().collect()))
+                .collect();
+            (output, touched)
+        })
+        .await
+}
+
+/// Report elements the current tool call created, changed or deleted in a
+/// diagram; outside [`collect`] this does nothing
+pub fn touched<S: Into<String>>(diagram_id: &str, element_ids: impl IntoIterator<Item = S>) {
+    let _ = TOUCHED.try_with(|touched| {
+        touched
+            .borrow_mut()
+            .entry(diagram_id.to_string())
+            .or_default()
+            .extend(element_ids.into_iter().map(Into::into));
+    });
+}
+
+/// IDs of the elements `ops` create, change or delete, sorted
+pub fn affected_elements(ops: &[LogOp]) -> Vec<String> {
+    let ids: BTreeSet<&str> = ops
+        .iter()
+        .filter_map(|op| match op {
+            LogOp::Create { element } | LogOp::Update { element } => Some(element.id.as_str()),
+            LogOp::Move { id, .. } | LogOp::Delete { id } => Some(id.as_str()),
+            LogOp::Diagram { .. } => None,
+        })
+        .collect();
+    ids.into_iter().map(str::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_collect_gathers_touched_elements_by_diagram() {
+        // Outside a collection reporting is a no-op
+        touched("d1", ["ignored"]);
+
+        let ((), found) = collect(async {
+            touched("d1", ["b", "a"]);
+            tokio::task::yield_now().await;
+            touched("d1", ["a"]);
+            touched("d2", [String::from("c")]);
+        })
+        .await;
+        assert_eq!(found["d1"], vec!["a", "b"]);
+        assert_eq!(found["d2"], vec!["c"]);
+    }
+
+    fn entry(actor: &str, operation: &str, element: &str) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: Utc::now(),
+            actor: Some(actor.to_string()),
+            operation: operation.to_string(),
+            revision: None,
+            element_ids: vec![element.to_string()],
+        }
+    }
+
+    #[test]
+    fn test_history_keeps_recent_entries_per_diagram() {
+        let mut log = HistoryLog::new(3);
+        log.record("d1", entry("alice", "create_node", "a"));
+        log.record("d1", entry("bob", "update_element", "a"));
+        log.record("d1", entry("alice", "update_element", "b"));
+        log.record("d1", entry("bob", "delete_element", "b"));
+        log.record("d2", entry("carol", "create_node", "c"));
+
+        let all = log.recent("d1", &HistoryFilter::default(), 10);
+        let operations: Vec<&str> = all.iter().map(|e| e.operation.as_str()).collect();
+        // Newest first, and the oldest entry fell out
+        assert_eq!(
+            operations,
+            vec!["delete_element", "update_element", "update_element"]
+        );
+
+        let by_bob = HistoryFilter {
+            actor: Some("bob".to_string()),
+            ..HistoryFilter::default()
+        };
+        assert_eq!(log.recent("d1", &by_bob, 10).len(), 2);
+        let updates = HistoryFilter {
+            operation: Some("update_element".to_string()),
+            actor: Some("alice".to_string()),
+        };
+        assert_eq!(log.recent("d1", &updates, 10)[0].element_ids, vec!["b"]);
+        assert_eq!(log.recent("d1", &HistoryFilter::default(), 1).len(), 1);
+        assert!(log
+            .recent("missing", &HistoryFilter::default(), 10)
+            .is_empty());
+
+        let mut disabled = HistoryLog::new(0);
+        disabled.record("d1", entry("alice", "create_node", "a"));
+        assert!(disabled
+            .recent("d1", &HistoryFilter::default(), 10)
+            .is_empty());
+    }
+}
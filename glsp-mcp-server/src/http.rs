@@ -0,0 +1,962 @@
+//! Direct HTTP transport
+//!
+//! A minimal JSON-RPC over HTTP front end built on axum that dispatches
+//! straight to [`GlspBackend`]. Unlike the framework transports it exposes
+//! its HTTP layer, which lets us configure concerns such as CORS that browser
+//! based editors depend on.
+//!
+//! Endpoints:
+//! - `POST /messages` - JSON-RPC 2.0 requests (`initialize`, `server_info`, `tools/*`, `resources/*`, `prompts/list`, `ping`).
+//!   Batches are supported; notifications (requests without an `id`) are
+//!   processed but answered with `202 Accepted` and no body. Bodies over the
+//!   configured size limit, batches included, are rejected with `413`; a body
+//!   not received within the request timeout is rejected with `408`. A single
+//!   `tools/call` with `_meta.progressToken` from a client accepting
+//!   `text/event-stream` is answered with an SSE stream of
+//!   `notifications/progress` messages followed by the response (see
+//!   [`crate::progress`]). A single `tools/call` from a client accepting
+//!   `application/x-ndjson` is answered with a chunked NDJSON stream (see
+//!   [`crate::streaming`]): for tools that stream their results, a first line
+//!   `{"jsonrpc", "id", "meta"}` is followed by one line per item as the tool
+//!   produces it. Every call ends with its ordinary JSON-RPC response as a
+//!   line, which for streamed results carries totals such as the item count.
+//!   Tool calls refused by a diagram lock, an element lock or a read-only
+//!   diagram fail with their own error codes ([`DIAGRAM_LOCKED_CODE`],
+//!   [`ELEMENT_LOCKED_CODE`], [`DIAGRAM_READ_ONLY_CODE`]) rather than
+//!   `-32603`, with the lock holder or diagram in `data`
+//! - `GET /health` - backend health check
+//! - `GET /ready` - health check plus the outcome of component preloading and
+//!   of the opt-in component instantiation check
+//! - `GET /events` - server-sent diagram events (see [`crate::events`]).
+//!   `?diagrams=id1,id2` limits the stream to those diagrams (default `all`);
+//!   the first event, `subscribed`, carries the subscription ID
+//! - `PUT /events/subscriptions/{id}` - replace a subscription's diagrams with
+//!   `{"diagrams": "all"}` or `{"diagrams": ["id1", ...]}`. Only the token
+//!   that opened the subscription may; for any other it answers `404`
+//! - `GET /metrics` - event stream counters, including dropped slow clients,
+//!   component executions in flight and queued against their limit, and
+//!   execution result cache hits and misses when the cache is enabled
+//! - `GET /diagrams/{id}/export` - the diagram rendered as `?format=` or, if
+//!   absent, as the `Accept` header prefers (see [`DiagramFormat::negotiate`])
+//! - `POST /sensors/stream` - chunked NDJSON sensor readings; per-record
+//!   results are streamed back as NDJSON events (see [`crate::database::ingestion`])
+//!
+//! When bearer tokens are configured (see [`crate::auth`]) every endpoint but
+//! `/health` and `/ready` requires `Authorization: Bearer <token>`; requests
+//! without a known token get `401`. `tools/list` only shows the tools the
+//! token may call, and other tool calls fail with `-32001 Unauthorized`.
+//! Resources and the other endpoints need the `read` scope, except
+//! `/sensors/stream` (`write`) and `/metrics` (`admin`), which answer `403`
+//! otherwise.
+
+use crate::auth::{as_client, unauthorized, Grants, Scope, TokenAuth};
+use crate::backend::{GlspBackend, GlspConfig, GlspError};
+use crate::database::ingestion::{ingest_ndjson, IngestEvent, IngestionConfig};
+use crate::events::{DiagramFilter, ServerEvent};
+use crate::mcp::protocol::{JsonRpcError, JsonRpcRequest, JsonRpcResponse, RequestId};
+use crate::operations::DiagramFormat;
+use crate::progress::{self, progress_token, ProgressReporter};
+use crate::streaming::{self, ResultStream, StreamLine};
+use axum::body::{Body, Bytes};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Extension, Json, Router};
+use futures::StreamExt;
+use serde_json::json;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tracing::{debug, info, warn};
+
+/// Number of ingestion events buffered before the stream waits for the client
+const INGEST_EVENT_BUFFER: usize = 256;
+
+/// JSON-RPC error code for a tool call refused by another client's diagram lock
+pub const DIAGRAM_LOCKED_CODE: i32 = -32010;
+
+/// JSON-RPC error code for a tool call refused by another client's element lock
+pub const ELEMENT_LOCKED_CODE: i32 = -32011;
+
+/// JSON-RPC error code for a tool call that would change a read-only diagram
+pub const DIAGRAM_READ_ONLY_CODE: i32 = -32012;
+
+/// CORS policy for the direct HTTP transport
+///
+/// The default policy is locked down: no origins are allowed, so no CORS
+/// headers are emitted and browsers reject cross-origin calls.
+/// The framework transports (stdio, http, streaming, websocket) do not
+/// expose their HTTP layer, so browser clients must use this transport.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorsConfig {
+    /// Allowed origins; `*` allows any origin (development only)
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    pub allow_credentials: bool,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_methods: vec![
+                "GET".to_string(),
+                "POST".to_string(),
+                "PUT".to_string(),
+                "OPTIONS".to_string(),
+            ],
+            allowed_headers: vec!["content-type".to_string(), "authorization".to_string()],
+            allow_credentials: false,
+        }
+    }
+}
+
+impl CorsConfig {
+    /// Build the policy from the comma-separated CLI options
+    pub fn from_config(config: &GlspConfig) -> Self {
+        Self {
+            allowed_origins: split_list(&config.cors_allowed_origins),
+            allowed_methods: split_list(&config.cors_allowed_methods),
+            allowed_headers: split_list(&config.cors_allowed_headers),
+            allow_credentials: config.cors_allow_credentials,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.allowed_origins.is_empty()
+    }
+
+    fn allows_any_origin(&self) -> bool {
+        self.allowed_origins.iter().any(|o| o == "*")
+    }
+
+    /// Build the tower-http layer, or `None` when CORS is disabled
+    pub fn to_layer(&self) -> Result<Option<CorsLayer>, String> {
+        if !self.is_enabled() {
+            return Ok(None);
+        }
+
+        // Browsers refuse credentialed requests with a wildcard origin
+        if self.allows_any_origin() && self.allow_credentials {
+            return Err(
+                "CORS credentials cannot be combined with a wildcard origin; list origins explicitly"
+                    .to_string(),
+            );
+        }
+
+        let origin = if self.allows_any_origin() {
+            AllowOrigin::from(Any)
+        } else {
+            let origins = self
+                .allowed_origins
+                .iter()
+                .map(|o| {
+                    HeaderValue::from_str(o).map_err(|e| format!("Invalid CORS origin '{o}': {e}"))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            AllowOrigin::list(origins)
+        };
+
+        let methods = self
+            .allowed_methods
+            .iter()
+            .map(|m| {
+                Method::from_bytes(m.to_uppercase().as_bytes())
+                    .map_err(|e| format!("Invalid CORS method '{m}': {e}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let headers = self
+            .allowed_headers
+            .iter()
+            .map(|h| {
+                HeaderName::from_bytes(h.to_lowercase().as_bytes())
+                    .map_err(|e| format!("Invalid CORS header '{h}': {e}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Some(
+            CorsLayer::new()
+                .allow_origin(origin)
+                .allow_methods(methods)
+                .allow_headers(headers)
+                .allow_credentials(self.allow_credentials),
+        ))
+    }
+}
+
+/// Limits applied to `/messages` requests
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HttpLimits {
+    /// Largest accepted request body, for single requests and batches alike
+    pub max_body_bytes: usize,
+    /// Time allowed for the client to send the whole body
+    pub request_timeout: Duration,
+}
+
+impl Default for HttpLimits {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: 8 * 1024 * 1024,
+            request_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl HttpLimits {
+    pub fn from_config(config: &GlspConfig) -> Self {
+        Self {
+            max_body_bytes: config.http_max_body_bytes,
+            request_timeout: Duration::from_secs(config.http_request_timeout_secs),
+        }
+    }
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Build the router for the direct HTTP transport
+pub fn router(
+    backend: GlspBackend,
+    cors: &CorsConfig,
+    limits: HttpLimits,
+    auth: TokenAuth,
+) -> Result<Router, String> {
+    if auth.is_enabled() {
+        info!("Bearer token authentication enabled");
+    }
+    let router = Router::new()
+        .route("/messages", post(handle_message).layer(Extension(limits)))
+        .route("/events", get(handle_events))
+        .route("/events/subscriptions/:id", put(handle_update_subscription))
+        .route("/metrics", get(handle_metrics))
+        .route("/diagrams/:id/export", get(handle_export))
+        .route("/sensors/stream", post(handle_sensor_stream))
+        .layer(middleware::from_fn_with_state(
+            std::sync::Arc::new(auth),
+            authenticate,
+        ))
+        .route("/health", get(handle_health))
+        .route("/ready", get(handle_ready))
+        .with_state(backend);
+
+    Ok(match cors.to_layer()? {
+        Some(layer) => {
+            info!("CORS enabled for origins: {:?}", cors.allowed_origins);
+            if cors.allows_any_origin() {
+                warn!("CORS allows any origin; use an explicit allowlist in production");
+            }
+            router.layer(layer)
+        }
+        None => router,
+    })
+}
+
+/// Serve the direct HTTP transport until the process is stopped
+pub async fn serve(
+    backend: GlspBackend,
+    config: &GlspConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let app = router(
+        backend,
+        &CorsConfig::from_config(config),
+        HttpLimits::from_config(config),
+        TokenAuth::parse(&config.auth_tokens)?,
+    )?;
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", config.port)).await?;
+
+    info!(
+        "GLSP MCP Server (direct HTTP) listening on port {}",
+        config.port
+    );
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+/// Resolve the bearer token into the [`Grants`] later handlers check
+async fn authenticate(
+    State(auth): State<std::sync::Arc<TokenAuth>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let authorization = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    match auth.authenticate(authorization) {
+        Ok(grants) => {
+            request.extensions_mut().insert(grants);
+            next.run(request).await
+        }
+        Err(e) => {
+            warn!("Rejected {} request: {}", request.uri().path(), e);
+            (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Bearer")],
+                Json(json!({"error": e.to_string()})),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// `403` unless the token holds `scope`
+fn require_scope(grants: &Grants, scope: Scope) -> Result<(), HttpError> {
+    if grants.allows(scope) {
+        Ok(())
+    } else {
+        Err(HttpError::new(
+            StatusCode::FORBIDDEN,
+            format!("Token lacks the '{scope}' scope"),
+        ))
+    }
+}
+
+async fn handle_health(
+    State(backend): State<GlspBackend>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    match backend.health_check().await {
+        Ok(()) => (StatusCode::OK, Json(json!({"status": "healthy"}))),
+        Err(e) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"status": "unhealthy", "error": e.to_string()})),
+        ),
+    }
+}
+
+async fn handle_ready(State(backend): State<GlspBackend>) -> (StatusCode, Json<serde_json::Value>) {
+    match backend.readiness_check().await {
+        Ok(()) => (StatusCode::OK, Json(json!({"status": "ready"}))),
+        Err(e) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"status": "not ready", "error": e.to_string()})),
+        ),
+    }
+}
+
+fn sse_event(event: &ServerEvent) -> Event {
+    Event::default()
+        .event(event.name())
+        .json_data(event)
+        .unwrap_or_else(|e| Event::default().event("error").data(e.to_string()))
+}
+
+async fn handle_events(
+    State(backend): State<GlspBackend>,
+    Extension(grants): Extension<Grants>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    if let Err(response) = require_scope(&grants, Scope::Read) {
+        return response.into_response();
+    }
+    let filter = params
+        .get("diagrams")
+        .map(|list| DiagramFilter::parse_list(list))
+        .unwrap_or_default();
+    let subscription = backend
+        .events()
+        .subscribe_filtered(filter, grants.token_id.clone());
+    let subscribed = ServerEvent::Subscribed {
+        subscription_id: subscription.id().to_string(),
+        filter: subscription.filter(),
+    };
+
+    let stream =
+        futures::stream::once(async move { Ok::<_, Infallible>(sse_event(&subscribed)) }).chain(
+            futures::stream::unfold(subscription, |mut subscription| async move {
+                let event = subscription.next().await?;
+                Some((Ok(sse_event(&event)), subscription))
+            }),
+        );
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+async fn handle_update_subscription(
+    State(backend): State<GlspBackend>,
+    Extension(grants): Extension<Grants>,
+    Path(subscription_id): Path<String>,
+    Json(body): Json<serde_json::Value>,
+) -> Response {
+    if let Err(response) = require_scope(&grants, Scope::Read) {
+        return response.into_response();
+    }
+    let filter = match DiagramFilter::from_json(&body["diagrams"]) {
+        Ok(filter) => filter,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({"error": e}))).into_response(),
+    };
+    if backend.events().update_subscription(
+        &subscription_id,
+        grants.token_id.as_deref(),
+        filter.clone(),
+    ) {
+        debug!("Subscription {} now receives {:?}", subscription_id, filter);
+        Json(json!({"subscriptionId": subscription_id, "filter": filter})).into_response()
+    } else {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": format!("No open subscription {subscription_id}")})),
+        )
+            .into_response()
+    }
+}
+
+async fn handle_metrics(
+    State(backend): State<GlspBackend>,
+    Extension(grants): Extension<Grants>,
+) -> Response {
+    if let Err(response) = require_scope(&grants, Scope::Admin) {
+        return response.into_response();
+    }
+    Json(json!({
+        "events": backend.events().metrics(),
+        "executions": backend.execution_concurrency(),
+        "resultCache": backend.result_cache_stats().await,
+    }))
+    .into_response()
+}
+
+async fn handle_export(
+    State(backend): State<GlspBackend>,
+    Extension(grants): Extension<Grants>,
+    Path(diagram_id): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(response) = require_scope(&grants, Scope::Read) {
+        return response.into_response();
+    }
+    let accept = headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok());
+    let format = match DiagramFormat::resolve(params.get("format").map(String::as_str), accept) {
+        Ok(format) => format,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({"error": e}))).into_response(),
+    };
+
+    match backend.render_diagram(&diagram_id, format).await {
+        Ok(body) => (
+            [
+                (header::CONTENT_TYPE, format.media_type()),
+                (header::VARY, "Accept"),
+            ],
+            body,
+        )
+            .into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, Json(json!({"error": e.to_string()}))).into_response(),
+    }
+}
+
+async fn handle_sensor_stream(
+    State(backend): State<GlspBackend>,
+    Extension(grants): Extension<Grants>,
+    body: Body,
+) -> Response {
+    if let Err(response) = require_scope(&grants, Scope::Write) {
+        return response.into_response();
+    }
+    let Some(database_manager) = backend.database_manager() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "Database not enabled"})),
+        )
+            .into_response();
+    };
+    let database = database_manager.backend().await;
+
+    let (events_tx, events_rx) = mpsc::channel(INGEST_EVENT_BUFFER);
+    tokio::spawn(ingest_ndjson(
+        body.into_data_stream(),
+        database,
+        IngestionConfig::default(),
+        events_tx,
+    ));
+
+    let events = futures::stream::unfold(events_rx, |mut rx| async move {
+        rx.recv()
+            .await
+            .map(|event| (Ok::<_, std::convert::Infallible>(ndjson_line(&event)), rx))
+    });
+
+    (
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(events),
+    )
+        .into_response()
+}
+
+fn ndjson_line(event: &IngestEvent) -> String {
+    let mut line = serde_json::to_string(event)
+        .unwrap_or_else(|e| json!({"type": "streamError", "error": e.to_string()}).to_string());
+    line.push('\n');
+    line
+}
+
+/// A rejected request, answered with `{"error": message}`
+#[derive(Debug)]
+struct HttpError {
+    status: StatusCode,
+    message: String,
+}
+
+impl HttpError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl IntoResponse for HttpError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({"error": self.message}))).into_response()
+    }
+}
+
+/// Read a request body, enforcing the size limit and the read timeout
+async fn read_body(body: Body, limits: &HttpLimits) -> Result<Bytes, HttpError> {
+    let read = async {
+        let mut buffer = Vec::new();
+        let mut chunks = body.into_data_stream();
+        while let Some(chunk) = chunks.next().await {
+            let chunk =
+                chunk.map_err(|e| HttpError::new(StatusCode::BAD_REQUEST, e.to_string()))?;
+            if buffer.len() + chunk.len() > limits.max_body_bytes {
+                return Err(HttpError::new(
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    format!(
+                        "Request body exceeds the limit of {} bytes",
+                        limits.max_body_bytes
+                    ),
+                ));
+            }
+            buffer.extend_from_slice(&chunk);
+        }
+        Ok(Bytes::from(buffer))
+    };
+
+    tokio::time::timeout(limits.request_timeout, read)
+        .await
+        .unwrap_or_else(|_| {
+            Err(HttpError::new(
+                StatusCode::REQUEST_TIMEOUT,
+                "Request body not received in time",
+            ))
+        })
+}
+
+async fn handle_message(
+    State(backend): State<GlspBackend>,
+    Extension(limits): Extension<HttpLimits>,
+    Extension(grants): Extension<Grants>,
+    headers: HeaderMap,
+    body: Body,
+) -> Response {
+    let body = match read_body(body, &limits).await {
+        Ok(body) => body,
+        Err(rejection) => {
+            warn!(
+                "Rejected /messages request: {}: {}",
+                rejection.status, rejection.message
+            );
+            return rejection.into_response();
+        }
+    };
+
+    let message: serde_json::Value = match serde_json::from_slice(&body) {
+        Ok(message) => message,
+        Err(e) => {
+            let error = JsonRpcError {
+                data: Some(json!(e.to_string())),
+                ..JsonRpcError::parse_error()
+            };
+            return Json(JsonRpcResponse::error(RequestId::Null, error)).into_response();
+        }
+    };
+
+    match message {
+        serde_json::Value::Array(batch) if !batch.is_empty() => {
+            let mut responses = Vec::new();
+            for message in batch {
+                if let Some(response) = handle_single(&backend, &grants, message).await {
+                    responses.push(response);
+                }
+            }
+            if responses.is_empty() {
+                StatusCode::ACCEPTED.into_response()
+            } else {
+                Json(responses).into_response()
+            }
+        }
+        message => {
+            if let Some(token) = streamed_progress_token(&message, &headers) {
+                return stream_with_progress(backend, grants, message, token);
+            }
+            if wants_ndjson(&message, &headers) {
+                return stream_ndjson(backend, grants, message);
+            }
+            match handle_single(&backend, &grants, message).await {
+                Some(response) => Json(response).into_response(),
+                None => StatusCode::ACCEPTED.into_response(),
+            }
+        }
+    }
+}
+
+/// The progress token of a `tools/call` request whose client can receive
+/// progress over SSE
+fn streamed_progress_token(
+    message: &serde_json::Value,
+    headers: &HeaderMap,
+) -> Option<serde_json::Value> {
+    let accepts_stream = headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/event-stream"));
+    if !accepts_stream || message["method"] != "tools/call" || message.get("id").is_none() {
+        return None;
+    }
+    progress_token(&message["params"])
+}
+
+/// Answer a request with its progress notifications, then its response, as
+/// SSE `message` events
+fn stream_with_progress(
+    backend: GlspBackend,
+    grants: Grants,
+    message: serde_json::Value,
+    token: serde_json::Value,
+) -> Response {
+    let (reporter, notifications) = ProgressReporter::new(token);
+    let call = tokio::spawn(progress::scope(reporter, async move {
+        handle_single(&backend, &grants, message).await
+    }));
+
+    // The notification stream ends when the call finishes and drops its
+    // reporter, so the response always comes last
+    let notifications = futures::stream::unfold(notifications, |mut rx| async move {
+        rx.recv().await.map(|notification| (notification, rx))
+    });
+    let response = futures::stream::once(async move {
+        match call.await {
+            Ok(response) => response.and_then(|r| serde_json::to_value(r).ok()),
+            Err(e) => Some(json!(JsonRpcResponse::error(
+                RequestId::Null,
+                internal_error(e)
+            ))),
+        }
+    })
+    .filter_map(futures::future::ready);
+
+    let events = notifications.chain(response).map(|value| {
+        Ok::<_, Infallible>(
+            Event::default()
+                .event("message")
+                .json_data(value)
+                .unwrap_or_else(|e| Event::default().event("error").data(e.to_string())),
+        )
+    });
+    Sse::new(events).into_response()
+}
+
+/// Whether a request is a `tools/call` whose client asked for NDJSON results
+fn wants_ndjson(message: &serde_json::Value, headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains("application/x-ndjson"))
+        && message["method"] == "tools/call"
+        && message.get("id").is_some()
+}
+
+/// Answer a tool call with its result as NDJSON: the metadata line and the
+/// items as the tool streams them, then the response
+fn stream_ndjson(backend: GlspBackend, grants: Grants, message: serde_json::Value) -> Response {
+    let id = message["id"].clone();
+    let (stream, lines) = ResultStream::channel();
+    let call = tokio::spawn(streaming::scope(stream, async move {
+        handle_single(&backend, &grants, message).await
+    }));
+
+    let lines = futures::stream::unfold(lines, |mut rx| async move {
+        rx.recv().await.map(|line| (line, rx))
+    })
+    .map(move |line| match line {
+        StreamLine::Meta(meta) => json!({"jsonrpc": "2.0", "id": id, "meta": meta}),
+        StreamLine::Item(item) => item,
+    });
+    // The line stream ends when the call finishes and drops its stream, so
+    // the response always comes last
+    let response = futures::stream::once(async move {
+        match call.await {
+            Ok(response) => response.and_then(|r| serde_json::to_value(r).ok()),
+            Err(e) => Some(json!(JsonRpcResponse::error(
+                RequestId::Null,
+                internal_error(e)
+            ))),
+        }
+    })
+    .filter_map(futures::future::ready);
+
+    let body = lines.chain(response).map(|value| {
+        let mut line = value.to_string();
+        line.push('\n');
+        Ok::<_, Infallible>(line)
+    });
+    (
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(body),
+    )
+        .into_response()
+}
+
+/// Handle one JSON-RPC message; notifications produce no response
+async fn handle_single(
+    backend: &GlspBackend,
+    grants: &Grants,
+    message: serde_json::Value,
+) -> Option<JsonRpcResponse> {
+    let request: JsonRpcRequest = match serde_json::from_value(message.clone()) {
+        Ok(request) => request,
+        Err(e) => {
+            // Echo the id when it is usable; otherwise the spec requires null
+            let id = message
+                .get("id")
+                .and_then(|id| serde_json::from_value(id.clone()).ok())
+                .unwrap_or(RequestId::Null);
+            return Some(JsonRpcResponse::error(id, invalid_request(e)));
+        }
+    };
+
+    if request.jsonrpc != "2.0" {
+        let error = invalid_request(format!("Unsupported jsonrpc version '{}'", request.jsonrpc));
+        return request.id.map(|id| JsonRpcResponse::error(id, error));
+    }
+
+    let Some(id) = request.id.clone() else {
+        let method = request.method.clone();
+        if let Err(error) = dispatch(backend, grants, request).await {
+            debug!("Notification '{}' failed: {}", method, error.message);
+        }
+        return None;
+    };
+
+    Some(match dispatch(backend, grants, request).await {
+        Ok(result) => JsonRpcResponse::success(id, result),
+        Err(error) => JsonRpcResponse::error(id, error),
+    })
+}
+
+async fn dispatch(
+    backend: &GlspBackend,
+    grants: &Grants,
+    request: JsonRpcRequest,
+) -> Result<serde_json::Value, JsonRpcError> {
+    let params = request.params.unwrap_or_else(|| json!({}));
+
+    if request.method.starts_with("resources/") && !grants.allows(Scope::Read) {
+        return Err(unauthorized("Token lacks the 'read' scope"));
+    }
+
+    let result = match request.method.as_str() {
+        "initialize" => to_value(backend.get_server_info())?,
+        "server_info" => backend.server_description(),
+        "ping" => json!({}),
+        "tools/list" => {
+            let mut tools = backend
+                .list_tools(parse_params(params)?)
+                .await
+                .map_err(internal_error)?;
+            tools
+                .tools
+                .retain(|tool| grants.allows_tool(&tool.name, None));
+            to_value(tools)?
+        }
+        "tools/call" => {
+            let mut call: crate::CallToolRequestParam = parse_params(params)?;
+            if !grants.allows_tool(&call.name, call.arguments.as_ref()) {
+                return Err(unauthorized(format!(
+                    "Tool '{}' requires the '{}' scope",
+                    call.name,
+                    crate::auth::required_scope(&call.name, call.arguments.as_ref())
+                )));
+            }
+            grants
+                .bind_client(&mut call.arguments)
+                .map_err(unauthorized)?;
+            let outcome = as_client(grants.client.clone(), backend.call_tool(call));
+            to_value(outcome.await.map_err(tool_error)?)?
+        }
+        "resources/list" => to_value(
+            backend
+                .list_resources(parse_params(params)?)
+                .await
+                .map_err(internal_error)?,
+        )?,
+        "resources/read" => to_value(
+            backend
+                .read_resource(parse_params(params)?)
+                .await
+                .map_err(internal_error)?,
+        )?,
+        "prompts/list" => to_value(
+            backend
+                .list_prompts(parse_params(params)?)
+                .await
+                .map_err(internal_error)?,
+        )?,
+        _ => return Err(JsonRpcError::method_not_found()),
+    };
+
+    Ok(result)
+}
+
+fn parse_params<T: serde::de::DeserializeOwned>(
+    params: serde_json::Value,
+) -> Result<T, JsonRpcError> {
+    serde_json::from_value(params).map_err(|e| JsonRpcError {
+        data: Some(json!(e.to_string())),
+        ..JsonRpcError::invalid_params()
+    })
+}
+
+fn to_value<T: serde::Serialize>(value: T) -> Result<serde_json::Value, JsonRpcError> {
+    serde_json::to_value(value).map_err(internal_error)
+}
+
+fn invalid_request(e: impl std::fmt::Display) -> JsonRpcError {
+    JsonRpcError {
+        data: Some(json!(e.to_string())),
+        ..JsonRpcError::invalid_request()
+    }
+}
+
+fn internal_error(e: impl std::fmt::Display) -> JsonRpcError {
+    JsonRpcError {
+        data: Some(json!(e.to_string())),
+        ..JsonRpcError::internal_error()
+    }
+}
+
+/// The error of a failed tool call; refusals a client can wait out or fix
+/// get their own codes, anything else is an internal error
+fn tool_error(error: GlspError) -> JsonRpcError {
+    let (code, message, details) = match &error {
+        GlspError::DiagramLocked { holder } => (
+            DIAGRAM_LOCKED_CODE,
+            "DiagramLocked",
+            json!({"holder": holder}),
+        ),
+        GlspError::ElementLocked { element_id, holder } => (
+            ELEMENT_LOCKED_CODE,
+            "ElementLocked",
+            json!({"elementId": element_id, "holder": holder}),
+        ),
+        GlspError::DiagramReadOnly { diagram_id } => (
+            DIAGRAM_READ_ONLY_CODE,
+            "DiagramReadOnly",
+            json!({"diagramId": diagram_id}),
+        ),
+        _ => return internal_error(error),
+    };
+    let mut data = details;
+    data["error"] = json!(error.to_string());
+    JsonRpcError {
+        code,
+        message: message.to_string(),
+        data: Some(data),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cors_disabled_by_default() {
+        let cors = CorsConfig::default();
+        assert!(!cors.is_enabled());
+        assert!(cors.to_layer().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_cors_allowlist_and_wildcard() {
+        let allowlist = CorsConfig {
+            allowed_origins: split_list("https://editor.example.com, http://localhost:5173"),
+            allow_credentials: true,
+            ..Default::default()
+        };
+        assert_eq!(allowlist.allowed_origins.len(), 2);
+        assert!(allowlist.to_layer().unwrap().is_some());
+
+        let wildcard = CorsConfig {
+            allowed_origins: vec!["*".to_string()],
+            ..Default::default()
+        };
+        assert!(wildcard.to_layer().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_cors_rejects_credentials_with_wildcard() {
+        let cors = CorsConfig {
+            allowed_origins: vec!["*".to_string()],
+            allow_credentials: true,
+            ..Default::default()
+        };
+        assert!(cors.to_layer().is_err());
+    }
+
+    #[test]
+    fn test_lock_and_read_only_refusals_have_their_own_codes() {
+        let locked = tool_error(GlspError::ElementLocked {
+            element_id: "n1".to_string(),
+            holder: "alice".to_string(),
+        });
+        assert_eq!(locked.code, ELEMENT_LOCKED_CODE);
+        assert_eq!(locked.message, "ElementLocked");
+        let data = locked.data.unwrap();
+        assert_eq!(data["elementId"], "n1");
+        assert_eq!(data["holder"], "alice");
+
+        let diagram_locked = tool_error(GlspError::DiagramLocked {
+            holder: "bob".to_string(),
+        });
+        assert_eq!(diagram_locked.code, DIAGRAM_LOCKED_CODE);
+        let read_only = tool_error(GlspError::DiagramReadOnly {
+            diagram_id: "d1".to_string(),
+        });
+        assert_eq!(read_only.code, DIAGRAM_READ_ONLY_CODE);
+        assert_eq!(read_only.data.unwrap()["diagramId"], "d1");
+
+        let failed = tool_error(GlspError::ToolExecution("boom".to_string()));
+        assert_eq!(failed.code, JsonRpcError::internal_error().code);
+    }
+
+    #[tokio::test]
+    async fn test_read_body_enforces_size_limit() {
+        let limits = HttpLimits {
+            max_body_bytes: 16,
+            ..Default::default()
+        };
+        let small = read_body(Body::from(r#"{"id":1}"#), &limits).await;
+        assert_eq!(small.unwrap().len(), 8);
+
+        let batch = r#"[{"id":1},{"id":2},{"id":3}]"#;
+        let rejected = read_body(Body::from(batch), &limits).await.unwrap_err();
+        assert_eq!(rejected.status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -0,0 +1,117 @@
+//! Idempotency keys for create tools
+//!
+//! A client that loses the response to a create call cannot tell whether the
+//! element was created. Create calls may therefore carry an
+//! `idempotencyKey`; the first successful result for a key is remembered and
+//! a retry with the same key and arguments replays it instead of creating a
+//! second element. Reusing a key with different arguments is rejected.
+//! Entries expire after a TTL and are pruned lazily.
+
+use chrono::{DateTime, Duration, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Argument carrying the client's idempotency key
+pub const IDEMPOTENCY_KEY_ARG: &str = "idempotencyKey";
+
+/// How long a key is remembered
+pub const IDEMPOTENCY_KEY_TTL_SECS: i64 = 600;
+
+/// Tools whose calls can be deduplicated by key
+pub const IDEMPOTENT_TOOLS: &[&str] = &["create_diagram", "create_node", "create_edge"];
+
+/// Marker added to replayed results so clients can tell them apart
+pub const REPLAY_MARKER: &str = "idempotentReplay";
+
+#[derive(Debug, Clone)]
+struct Entry {
+    tool: String,
+    arguments: Value,
+    result: Value,
+    stored_at: DateTime<Utc>,
+}
+
+/// Outcome of looking up a key
+#[derive(Debug, Clone, PartialEq)]
+pub enum Lookup {
+    /// First use of the key
+    Miss,
+    /// The key was used before with the same call; the stored result
+    Replay(Value),
+    /// The key was used before for a different call
+    Conflict,
+}
+
+/// Remembered results keyed by idempotency key
+#[derive(Debug, Default)]
+pub struct IdempotencyCache {
+    entries: HashMap<String, Entry>,
+}
+
+impl IdempotencyCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn prune(&mut self) {
+        let cutoff = Utc::now() - Duration::seconds(IDEMPOTENCY_KEY_TTL_SECS);
+        self.entries.retain(|_, entry| entry.stored_at > cutoff);
+    }
+
+    /// Arguments as compared between calls: everything except the key itself
+    fn comparable(arguments: &Value) -> Value {
+        let mut arguments = arguments.clone();
+        if let Some(map) = arguments.as_object_mut() {
+            map.remove(IDEMPOTENCY_KEY_ARG);
+        }
+        arguments
+    }
+
+    pub fn lookup(&mut self, key: &str, tool: &str, arguments: &Value) -> Lookup {
+        self.prune();
+        match self.entries.get(key) {
+            None => Lookup::Miss,
+            Some(entry) if entry.tool == tool && entry.arguments == Self::comparable(arguments) => {
+                Lookup::Replay(entry.result.clone())
+            }
+            Some(_) => Lookup::Conflict,
+        }
+    }
+
+    /// Remember the serialized result of a successful call
+    pub fn store(&mut self, key: &str, tool: &str, arguments: &Value, result: Value) {
+        self.entries.insert(
+            key.to_string(),
+            Entry {
+                tool: tool.to_string(),
+                arguments: Self::comparable(arguments),
+                result,
+                stored_at: Utc::now(),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_replay_and_conflict() {
+        let mut cache = IdempotencyCache::new();
+        let args = json!({"diagramId": "d1", "nodeType": "task", "idempotencyKey": "k1"});
+        assert_eq!(cache.lookup("k1", "create_node", &args), Lookup::Miss);
+
+        cache.store("k1", "create_node", &args, json!({"id": "n1"}));
+        let retry = json!({"idempotencyKey": "k1", "nodeType": "task", "diagramId": "d1"});
+        assert_eq!(
+            cache.lookup("k1", "create_node", &retry),
+            Lookup::Replay(json!({"id": "n1"}))
+        );
+
+        let other = json!({"diagramId": "d1", "nodeType": "class", "idempotencyKey": "k1"});
+        assert_eq!(cache.lookup("k1", "create_node", &other), Lookup::Conflict);
+        assert_eq!(cache.lookup("k1", "create_edge", &args), Lookup::Conflict);
+    }
+}
@@ -28,24 +28,50 @@
 //! }
 //! ```
 
+/// Bearer tokens with per-tool authorization scopes
+pub mod auth;
 /// Backend implementation and configuration
 pub mod backend;
+/// Bounded thread pool for CPU-bound tool work
+pub mod cpu_pool;
 /// Database integration and sensor data management
 pub mod database;
+/// Server-sent diagram events with bounded per-connection buffers
+pub mod events;
+/// Per-diagram audit trail of recent operations
+pub mod history;
+/// Direct HTTP transport with configurable CORS
+pub mod http;
+/// Idempotency keys that make create tools safe to retry
+pub mod idempotency;
+/// Exclusive edit locks for diagrams
+pub mod locking;
 /// Model Context Protocol implementation
 pub mod mcp;
 /// Diagram model types and element definitions
 pub mod model;
 /// Diagram operations and transformations
 pub mod operations;
+/// Append-only log of the changes between diagram saves
+pub mod oplog;
 /// Diagram persistence and file management
 pub mod persistence;
+/// Progress notifications for long-running tool calls
+pub mod progress;
 /// Element selection and interaction management
 pub mod selection;
+/// Tool results streamed as newline-delimited JSON
+pub mod streaming;
+/// Runtime feature flags that disable individual tools
+pub mod tool_flags;
 /// Diagram validation and error checking
 pub mod validation;
+/// Machine-readable warnings attached to tool results
+pub mod warnings;
 /// WebAssembly component execution and management
 pub mod wasm;
+/// Outbound webhooks notified of diagram changes
+pub mod webhooks;
 
 // Re-export local MCP modules for easy access
 pub use mcp::{prompts, protocol, resources, tools};
@@ -64,7 +90,7 @@ pub use tools::DiagramTools;
 use pulseengine_mcp_auth::config::AuthConfig;
 use pulseengine_mcp_server::{McpServer, ServerConfig};
 use pulseengine_mcp_transport::TransportConfig;
-use tracing::info;
+use tracing::{info, warn};
 
 /// Run the MCP server with the given configuration
 /// This is useful for embedding the server in other applications like Tauri
@@ -72,6 +98,12 @@ pub async fn run_server(config: GlspConfig) -> Result<(), Box<dyn std::error::Er
     info!("Initializing GLSP backend...");
     let backend = GlspBackend::initialize(config.clone()).await?;
 
+    if config.transport == "http-direct" {
+        http::serve(backend, &config).await?;
+        info!("GLSP MCP Server shutdown complete");
+        return Ok(());
+    }
+
     // Create server config with memory auth
     let server_config = ServerConfig {
         auth_config: AuthConfig::memory(),
@@ -81,7 +113,7 @@ pub async fn run_server(config: GlspConfig) -> Result<(), Box<dyn std::error::Er
             "websocket" => TransportConfig::websocket(config.port),
             "stdio" => TransportConfig::stdio(),
             _ => {
-                info!(
+                warn!(
                     "Unknown transport type: {}, defaulting to HTTP streaming",
                     config.transport
                 );
@@ -0,0 +1,308 @@
+//! Explicit edit locks for diagrams and their elements
+//!
+//! Locks are an alternative to optimistic revision checks for workflows that
+//! want a single editor at a time. A diagram lock covers the whole diagram;
+//! element locks cover single nodes or edges, so that collaborators can
+//! work on different regions of a large diagram side by side. Every lock
+//! carries a TTL so that a crashed client can never hold a diagram forever;
+//! expired locks are pruned lazily whenever the lock table is consulted.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Default lock lifetime when the caller does not specify one
+pub const DEFAULT_LOCK_TTL_SECS: u64 = 300;
+
+/// Longest lock lifetime; longer requests are shortened to it
+pub const MAX_LOCK_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// An exclusive edit lock held by a single client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditLock {
+    pub holder: String,
+    pub acquired_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl EditLock {
+    fn new(holder: &str, ttl_secs: u64) -> Self {
+        let now = Utc::now();
+        let ttl = Duration::seconds(ttl_secs.min(MAX_LOCK_TTL_SECS) as i64);
+        Self {
+            holder: holder.to_string(),
+            acquired_at: now,
+            expires_at: now
+                .checked_add_signed(ttl)
+                .unwrap_or(DateTime::<Utc>::MAX_UTC),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Utc::now() >= self.expires_at
+    }
+}
+
+/// Lock tables keyed by diagram ID
+#[derive(Debug, Default)]
+pub struct LockManager {
+    diagram_locks: HashMap<String, EditLock>,
+    /// Element locks by diagram, then element ID
+    element_locks: HashMap<String, HashMap<String, EditLock>>,
+}
+
+impl LockManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Acquire (or renew) the lock on a diagram.
+    ///
+    /// Returns the current holder as the error if another client holds an
+    /// unexpired lock. Re-acquiring an owned lock extends its TTL.
+    pub fn acquire(
+        &mut self,
+        diagram_id: &str,
+        client_id: &str,
+        ttl_secs: u64,
+    ) -> Result<EditLock, String> {
+        self.prune_expired();
+
+        if let Some(existing) = self.diagram_locks.get(diagram_id) {
+            if existing.holder != client_id {
+                return Err(existing.holder.clone());
+            }
+        }
+
+        let lock = EditLock::new(client_id, ttl_secs);
+        self.diagram_locks
+            .insert(diagram_id.to_string(), lock.clone());
+        Ok(lock)
+    }
+
+    /// Release a lock held by `client_id`.
+    ///
+    /// Returns `Ok(true)` if a lock was released, `Ok(false)` if the diagram
+    /// was not locked, and the holder as the error if someone else owns it.
+    pub fn release(&mut self, diagram_id: &str, client_id: &str) -> Result<bool, String> {
+        self.prune_expired();
+
+        match self.diagram_locks.get(diagram_id) {
+            Some(lock) if lock.holder != client_id => Err(lock.holder.clone()),
+            Some(_) => {
+                self.diagram_locks.remove(diagram_id);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Check whether `client_id` may mutate the diagram.
+    ///
+    /// Anonymous callers (`None`) are only allowed when no lock is held.
+    pub fn check(&mut self, diagram_id: &str, client_id: Option<&str>) -> Result<(), String> {
+        self.prune_expired();
+
+        match self.diagram_locks.get(diagram_id) {
+            Some(lock) if Some(lock.holder.as_str()) != client_id => Err(lock.holder.clone()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Current (unexpired) lock on a diagram, if any
+    pub fn get(&mut self, diagram_id: &str) -> Option<EditLock> {
+        self.prune_expired();
+        self.diagram_locks.get(diagram_id).cloned()
+    }
+
+    /// Drop any lock on a diagram or its elements regardless of holder (used
+    /// when a diagram is deleted)
+    pub fn clear(&mut self, diagram_id: &str) {
+        self.diagram_locks.remove(diagram_id);
+        self.element_locks.remove(diagram_id);
+    }
+
+    /// Acquire (or renew) the lock on one element of a diagram.
+    ///
+    /// Same rules as [`LockManager::acquire`], per element.
+    pub fn acquire_element(
+        &mut self,
+        diagram_id: &str,
+        element_id: &str,
+        client_id: &str,
+        ttl_secs: u64,
+    ) -> Result<EditLock, String> {
+        self.prune_expired();
+
+        let locks = self
+            .element_locks
+            .entry(diagram_id.to_string())
+            .or_default();
+        if let Some(existing) = locks.get(element_id) {
+            if existing.holder != client_id {
+                return Err(existing.holder.clone());
+            }
+        }
+
+        let lock = EditLock::new(client_id, ttl_secs);
+        locks.insert(element_id.to_string(), lock.clone());
+        Ok(lock)
+    }
+
+    /// Release an element lock held by `client_id`; see [`LockManager::release`]
+    pub fn release_element(
+        &mut self,
+        diagram_id: &str,
+        element_id: &str,
+        client_id: &str,
+    ) -> Result<bool, String> {
+        self.prune_expired();
+
+        let Some(locks) = self.element_locks.get_mut(diagram_id) else {
+            return Ok(false);
+        };
+        match locks.get(element_id) {
+            Some(lock) if lock.holder != client_id => Err(lock.holder.clone()),
+            Some(_) => {
+                locks.remove(element_id);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Check whether `client_id` may mutate the given elements.
+    ///
+    /// Returns the first element locked by someone else together with its
+    /// holder. Anonymous callers are only allowed on unlocked elements.
+    pub fn check_elements<'a>(
+        &mut self,
+        diagram_id: &str,
+        element_ids: impl IntoIterator<Item = &'a str>,
+        client_id: Option<&str>,
+    ) -> Result<(), (String, String)> {
+        self.prune_expired();
+
+        let Some(locks) = self.element_locks.get(diagram_id) else {
+            return Ok(());
+        };
+        for element_id in element_ids {
+            if let Some(lock) = locks.get(element_id) {
+                if Some(lock.holder.as_str()) != client_id {
+                    return Err((element_id.to_string(), lock.holder.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Current (unexpired) element locks of a diagram, by element ID
+    pub fn element_locks(&mut self, diagram_id: &str) -> BTreeMap<String, EditLock> {
+        self.prune_expired();
+        self.element_locks
+            .get(diagram_id)
+            .map(|locks| {
+                locks
+                    .iter()
+                    .map(|(id, lock)| (id.clone(), lock.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Drop the lock on an element regardless of holder (used when the
+    /// element is deleted)
+    pub fn clear_element(&mut self, diagram_id: &str, element_id: &str) {
+        if let Some(locks) = self.element_locks.get_mut(diagram_id) {
+            locks.remove(element_id);
+        }
+    }
+
+    fn prune_expired(&mut self) {
+        self.diagram_locks.retain(|_, lock| !lock.is_expired());
+        self.element_locks.retain(|_, locks| {
+            locks.retain(|_, lock| !lock.is_expired());
+            !locks.is_empty()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_excludes_other_clients() {
+        let mut locks = LockManager::new();
+
+        assert!(locks.acquire("d1", "alice", 60).is_ok());
+        assert_eq!(locks.acquire("d1", "bob", 60).unwrap_err(), "alice");
+        assert_eq!(locks.check("d1", Some("bob")).unwrap_err(), "alice");
+        assert_eq!(locks.check("d1", None).unwrap_err(), "alice");
+        assert!(locks.check("d1", Some("alice")).is_ok());
+        assert!(locks.check("d2", Some("bob")).is_ok());
+    }
+
+    #[test]
+    fn test_release_requires_holder() {
+        let mut locks = LockManager::new();
+        locks.acquire("d1", "alice", 60).unwrap();
+
+        assert_eq!(locks.release("d1", "bob").unwrap_err(), "alice");
+        assert!(locks.release("d1", "alice").unwrap());
+        assert!(!locks.release("d1", "alice").unwrap());
+        assert!(locks.acquire("d1", "bob", 60).is_ok());
+    }
+
+    #[test]
+    fn test_element_locks_only_cover_their_element() {
+        let mut locks = LockManager::new();
+        locks.acquire_element("d1", "n1", "alice", 60).unwrap();
+
+        assert_eq!(
+            locks.acquire_element("d1", "n1", "bob", 60).unwrap_err(),
+            "alice"
+        );
+        assert!(locks.acquire_element("d1", "n2", "bob", 60).is_ok());
+        assert_eq!(
+            locks
+                .check_elements("d1", ["n2", "n1"], Some("bob"))
+                .unwrap_err(),
+            ("n1".to_string(), "alice".to_string())
+        );
+        assert!(locks.check_elements("d1", ["n1"], Some("alice")).is_ok());
+        assert!(locks.check_elements("d2", ["n1"], None).is_ok());
+        assert_eq!(locks.element_locks("d1").len(), 2);
+
+        assert_eq!(
+            locks.release_element("d1", "n1", "bob").unwrap_err(),
+            "alice"
+        );
+        assert!(locks.release_element("d1", "n1", "alice").unwrap());
+        assert!(locks.check_elements("d1", ["n1"], None).is_ok());
+        locks.clear("d1");
+        assert!(locks.element_locks("d1").is_empty());
+    }
+
+    #[test]
+    fn test_huge_ttl_is_capped() {
+        let mut locks = LockManager::new();
+        locks.acquire("d1", "alice", u64::MAX).unwrap();
+        let lock = locks.get("d1").unwrap();
+        assert_eq!(
+            (lock.expires_at - lock.acquired_at).num_seconds(),
+            MAX_LOCK_TTL_SECS as i64
+        );
+    }
+
+    #[test]
+    fn test_expired_lock_is_ignored() {
+        let mut locks = LockManager::new();
+        locks.acquire("d1", "alice", 0).unwrap();
+
+        assert!(locks.check("d1", Some("bob")).is_ok());
+        assert!(locks.get("d1").is_none());
+        assert!(locks.acquire("d1", "bob", 60).is_ok());
+    }
+}
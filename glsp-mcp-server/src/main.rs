@@ -1,9 +1,7 @@
 //! Main entry point for GLSP MCP Server using the PulseEngine MCP framework 0.3.0
 
 use clap::Parser;
-use glsp_mcp_server::{GlspBackend, GlspConfig};
-use pulseengine_mcp_auth::config::AuthConfig;
-use pulseengine_mcp_server::{McpServer, ServerConfig};
+use glsp_mcp_server::GlspConfig;
 use std::fs;
 use std::path::Path;
 use tracing::{info, warn};
@@ -56,37 +54,5 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
         config.transport, config.port
     );
 
-    // Initialize backend
-    info!("Initializing GLSP backend...");
-    let backend = GlspBackend::initialize(config.clone()).await?;
-
-    // Configure server with framework based on our config
-    // Use memory-only authentication (no persistent storage)
-    use pulseengine_mcp_transport::TransportConfig;
-    let server_config = ServerConfig {
-        auth_config: AuthConfig::memory(),
-        transport_config: match config.transport.as_str() {
-            "http" => TransportConfig::http(config.port),
-            "http-streaming" | "streaming" => TransportConfig::streamable_http(config.port),
-            "websocket" => TransportConfig::websocket(config.port),
-            "stdio" => TransportConfig::stdio(),
-            _ => {
-                warn!(
-                    "Unknown transport type: {}, defaulting to HTTP streaming",
-                    config.transport
-                );
-                TransportConfig::streamable_http(config.port)
-            }
-        },
-        ..Default::default()
-    };
-
-    // Create and run server using framework
-    let mut server = McpServer::new(backend, server_config).await?;
-
-    info!("GLSP MCP Server listening on port {}", config.port);
-    server.run().await?;
-
-    info!("GLSP MCP Server shutdown complete");
-    Ok(())
+    glsp_mcp_server::run_server(config).await
 }
@@ -1,13 +1,46 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// JSON-RPC 2.0 request id
+///
+/// Ids are echoed back exactly as received: numbers keep their original
+/// representation (`1`, `0.5`) and strings are never coerced to numbers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RequestId {
+    Number(serde_json::Number),
+    String(String),
+    Null,
+}
+
 /// JSON-RPC 2.0 request structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonRpcRequest {
     pub jsonrpc: String,
     pub method: String,
     pub params: Option<serde_json::Value>,
-    pub id: Option<serde_json::Value>,
+    /// `None` when the `id` member is absent, which makes the request a
+    /// notification. An explicit `"id": null` is `Some(RequestId::Null)`.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_present_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub id: Option<RequestId>,
+}
+
+impl JsonRpcRequest {
+    /// Notifications carry no id and must not be answered
+    pub fn is_notification(&self) -> bool {
+        self.id.is_none()
+    }
+}
+
+fn deserialize_present_id<'de, D>(deserializer: D) -> Result<Option<RequestId>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    RequestId::deserialize(deserializer).map(Some)
 }
 
 /// JSON-RPC 2.0 response structure
@@ -18,7 +51,8 @@ pub struct JsonRpcResponse {
     pub result: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<JsonRpcError>,
-    pub id: Option<serde_json::Value>,
+    /// Id of the request; `null` when it could not be determined
+    pub id: RequestId,
 }
 
 /// JSON-RPC error structure
@@ -211,7 +245,7 @@ pub struct PromptMessage {
 }
 
 impl JsonRpcResponse {
-    pub fn success(id: Option<serde_json::Value>, result: serde_json::Value) -> Self {
+    pub fn success(id: RequestId, result: serde_json::Value) -> Self {
         Self {
             jsonrpc: "2.0".to_string(),
             result: Some(result),
@@ -220,7 +254,7 @@ impl JsonRpcResponse {
         }
     }
 
-    pub fn error(id: Option<serde_json::Value>, error: JsonRpcError) -> Self {
+    pub fn error(id: RequestId, error: JsonRpcError) -> Self {
         Self {
             jsonrpc: "2.0".to_string(),
             result: None,
@@ -271,3 +305,39 @@ impl JsonRpcError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(message: serde_json::Value) -> JsonRpcRequest {
+        serde_json::from_value(message).unwrap()
+    }
+
+    #[test]
+    fn test_request_ids_round_trip_exactly() {
+        for id in [json!(7), json!(0.5), json!("7"), json!(null)] {
+            let request = parse(json!({"jsonrpc": "2.0", "method": "ping", "id": id}));
+            assert!(!request.is_notification());
+
+            let response = JsonRpcResponse::success(request.id.unwrap(), json!({}));
+            assert_eq!(serde_json::to_value(&response).unwrap()["id"], id);
+        }
+    }
+
+    #[test]
+    fn test_missing_id_is_notification() {
+        let request = parse(json!({"jsonrpc": "2.0", "method": "notifications/initialized"}));
+        assert!(request.is_notification());
+        assert!(serde_json::to_value(&request).unwrap().get("id").is_none());
+    }
+
+    #[test]
+    fn test_invalid_id_type_is_rejected() {
+        let result = serde_json::from_value::<JsonRpcRequest>(
+            json!({"jsonrpc": "2.0", "method": "ping", "id": {"nested": true}}),
+        );
+        assert!(result.is_err());
+    }
+}
@@ -564,9 +564,9 @@ impl DiagramResources {
                     let name = file_name.to_string_lossy();
                     if name.ends_with(".glsp.json") {
                         // Try to read just the basic info
-                        if let Ok(content) = std::fs::read_to_string(&path) {
+                        if let Ok(bytes) = std::fs::read(&path) {
                             if let Ok(json_value) =
-                                serde_json::from_str::<serde_json::Value>(&content)
+                                crate::persistence::decode::<serde_json::Value>(&bytes)
                             {
                                 let id = json_value["id"].as_str().unwrap_or("");
                                 let diagram_name = json_value["name"].as_str().unwrap_or("");
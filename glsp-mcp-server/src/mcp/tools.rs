@@ -4,8 +4,8 @@ use crate::mcp::protocol::{CallToolParams, CallToolResult, TextContent, Tool};
 use crate::model::{DiagramModel, Edge, ElementType, ModelElement, Node, Position};
 use crate::selection::SelectionMode;
 use crate::wasm::{
-    ComponentGroup, ComponentGroupInfo, ConnectionType, ExternalInterface, InterfaceConnection,
-    WasmComponent, WasmComponentChange, WasmFileWatcher,
+    CancelOutcome, ComponentGroup, ComponentGroupInfo, ConnectionType, EngineOptions,
+    ExternalInterface, InterfaceConnection, WasmComponent, WasmComponentChange, WasmFileWatcher,
 };
 use anyhow::Result;
 use chrono::{DateTime, Utc};
@@ -67,7 +67,7 @@ impl DiagramTools {
 
         // Initialize with execution engine (max 3 concurrent executions)
         let wasm_watcher = WasmFileWatcher::new(wasm_path)
-            .with_execution_engine(3)
+            .with_execution_engine(3, EngineOptions::default())
             .expect("Failed to initialize WASM execution engine");
 
         Self {
@@ -1910,7 +1910,7 @@ impl DiagramTools {
             .map(|(id, element)| (id, element, element.z_index.unwrap_or(0)))
             .collect();
 
-        elements_with_z.sort_by(|a, b| b.2.cmp(&a.2));
+        elements_with_z.sort_by_key(|e| std::cmp::Reverse(e.2));
 
         for (id, element, _) in elements_with_z {
             if let Some(bounds) = &element.bounds {
@@ -2211,6 +2211,7 @@ impl DiagramTools {
         let timeout_ms = args["timeout_ms"].as_u64().unwrap_or(30000);
         let max_memory_mb = args["max_memory_mb"].as_u64().unwrap_or(64) as u32;
         let method_args = args.get("args").cloned().unwrap_or(json!({}));
+        let profile = args["profile"].as_bool().unwrap_or(false);
 
         match self
             .wasm_watcher
@@ -2220,6 +2221,9 @@ impl DiagramTools {
                 method_args,
                 timeout_ms,
                 max_memory_mb,
+                profile,
+                false,
+                false,
             )
             .await
         {
@@ -2323,25 +2327,22 @@ impl DiagramTools {
             .as_str()
             .ok_or_else(|| anyhow::anyhow!("Missing executionId"))?;
 
-        let cancelled = self.wasm_watcher.cancel_execution(execution_id);
+        let outcome = self.wasm_watcher.cancel_execution(execution_id);
 
-        if cancelled {
-            Ok(CallToolResult {
-                content: vec![TextContent {
-                    content_type: "text".to_string(),
-                    text: format!("Cancelled execution {execution_id}"),
-                }],
-                is_error: None,
-            })
-        } else {
-            Ok(CallToolResult {
-                content: vec![TextContent {
-                    content_type: "text".to_string(),
-                    text: format!("Execution {execution_id} not found or could not be cancelled"),
-                }],
-                is_error: Some(true),
-            })
-        }
+        let text = match outcome {
+            CancelOutcome::Cancelled => format!("Cancelled execution {execution_id}"),
+            CancelOutcome::AlreadyFinished => {
+                format!("Execution {execution_id} already finished; nothing to cancel")
+            }
+            CancelOutcome::NotFound => format!("Execution {execution_id} not found"),
+        };
+        Ok(CallToolResult {
+            content: vec![TextContent {
+                content_type: "text".to_string(),
+                text,
+            }],
+            is_error: (outcome == CancelOutcome::NotFound).then_some(true),
+        })
     }
 
     // Sensor data tool implementations
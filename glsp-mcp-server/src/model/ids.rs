@@ -0,0 +1,187 @@
+//! Canonical element identifiers
+//!
+//! Every element created by the server gets a lowercase, hyphenated UUID v4.
+//! Client-supplied IDs are normalized and validated against the same format
+//! so that client and server agree on what an ID looks like.
+//!
+//! Diagram types listed in [`IdPrefixes`] give their elements a readable
+//! type prefix instead, e.g. `task-3f2504e0-...` or `edge-3f2504e0-...`. The
+//! UUID is still the last 36 characters, so prefixed IDs stay unique.
+
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Prefix of edge IDs in diagram types with prefixed IDs
+pub const EDGE_ID_PREFIX: &str = "edge";
+
+const UUID_LEN: usize = 36;
+
+/// Rejected identifier with the reason it failed validation
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid ID '{id}': {reason}")]
+pub struct InvalidId {
+    pub id: String,
+    pub reason: String,
+}
+
+/// Generate a new canonical element ID
+pub fn generate_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+/// Generate a new element ID carrying a type prefix, e.g. `task-<uuid>`.
+///
+/// Characters other than ASCII letters, digits, `-` and `_` are replaced
+/// with `-` so the result always passes [`validate_id`].
+pub fn generate_prefixed_id(prefix: &str) -> String {
+    let prefix: String = prefix
+        .trim()
+        .to_ascii_lowercase()
+        .chars()
+        .map(|c| if is_prefix_char(c) { c } else { '-' })
+        .collect();
+    if prefix.is_empty() {
+        return generate_id();
+    }
+    format!("{prefix}-{}", generate_id())
+}
+
+fn is_prefix_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+/// Normalize a client-supplied ID to canonical form.
+///
+/// Surrounding whitespace is trimmed and hex digits are lowercased; anything
+/// other than a hyphenated UUID v4, optionally preceded by a `{prefix}-` type
+/// prefix, is rejected.
+pub fn normalize_id(id: &str) -> Result<String, InvalidId> {
+    let invalid = |reason: &str| InvalidId {
+        id: id.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let candidate = id.trim().to_ascii_lowercase();
+    if candidate.len() < UUID_LEN {
+        return Err(invalid("expected a 36 character hyphenated UUID"));
+    }
+
+    let (prefix, uuid) = candidate.split_at(candidate.len() - UUID_LEN);
+    if !prefix.is_empty() {
+        let name = prefix
+            .strip_suffix('-')
+            .ok_or_else(|| invalid("expected a 36 character hyphenated UUID"))?;
+        if name.is_empty() || !name.chars().all(is_prefix_char) {
+            return Err(invalid(
+                "prefix may only contain letters, digits, '-' and '_'",
+            ));
+        }
+    }
+
+    let uuid = Uuid::parse_str(uuid).map_err(|_| invalid("not a valid UUID"))?;
+    if uuid.get_version_num() != 4 {
+        return Err(invalid("expected a version 4 UUID"));
+    }
+
+    Ok(format!("{prefix}{}", uuid.hyphenated()))
+}
+
+/// Check that an ID is already in canonical form
+pub fn validate_id(id: &str) -> Result<(), InvalidId> {
+    let normalized = normalize_id(id)?;
+    if normalized != id {
+        return Err(InvalidId {
+            id: id.to_string(),
+            reason: format!("not in canonical form (expected '{normalized}')"),
+        });
+    }
+    Ok(())
+}
+
+/// Diagram types whose new nodes and edges get type-prefixed IDs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdPrefixes {
+    all: bool,
+    diagram_types: HashSet<String>,
+}
+
+impl IdPrefixes {
+    /// Parse a comma-separated list of diagram types; `*` enables prefixes
+    /// for every diagram type
+    pub fn parse(list: &str) -> Self {
+        let mut prefixes = Self::default();
+        for diagram_type in list.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            if diagram_type == "*" {
+                prefixes.all = true;
+            } else {
+                prefixes.diagram_types.insert(diagram_type.to_string());
+            }
+        }
+        prefixes
+    }
+
+    pub fn enabled_for(&self, diagram_type: &str) -> bool {
+        self.all || self.diagram_types.contains(diagram_type)
+    }
+
+    /// A new ID for an element of a diagram: prefixed with `prefix` (the node
+    /// type, or [`EDGE_ID_PREFIX`]) when the diagram type has prefixes
+    /// enabled, otherwise a bare UUID
+    pub fn generate(&self, diagram_type: &str, prefix: &str) -> String {
+        if self.enabled_for(diagram_type) {
+            generate_prefixed_id(prefix)
+        } else {
+            generate_id()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_ids_are_canonical() {
+        let id = generate_id();
+        assert!(validate_id(&id).is_ok());
+    }
+
+    #[test]
+    fn test_normalize_id() {
+        let id = "  3F2504E0-4F89-41D3-9A0C-0305E82C3301 ";
+        assert_eq!(
+            normalize_id(id).unwrap(),
+            "3f2504e0-4f89-41d3-9a0c-0305e82c3301"
+        );
+        assert!(validate_id(id).is_err());
+    }
+
+    #[test]
+    fn test_rejects_malformed_ids() {
+        assert!(normalize_id("abc-123").is_err());
+        assert!(normalize_id("3f2504e04f8941d39a0c0305e82c3301").is_err());
+        // Version 1 UUID
+        assert!(normalize_id("6ba7b810-9dad-11d1-80b4-00c04fd430c8").is_err());
+        assert!(normalize_id("zzzzzzzz-4f89-41d3-9a0c-0305e82c3301").is_err());
+        assert!(normalize_id("task3f2504e0-4f89-41d3-9a0c-0305e82c3301").is_err());
+        assert!(normalize_id("-3f2504e0-4f89-41d3-9a0c-0305e82c3301").is_err());
+        assert!(normalize_id("a b-3f2504e0-4f89-41d3-9a0c-0305e82c3301").is_err());
+    }
+
+    #[test]
+    fn test_prefixed_ids() {
+        let prefixes = IdPrefixes::parse("workflow, uml-class");
+        let id = prefixes.generate("workflow", "start-event");
+        assert!(id.starts_with("start-event-"));
+        assert!(validate_id(&id).is_ok());
+        assert!(validate_id(&prefixes.generate("bpmn", "task")).is_ok());
+        assert_eq!(prefixes.generate("bpmn", "task").len(), 36);
+        assert!(IdPrefixes::parse("*").enabled_for("bpmn"));
+
+        assert_eq!(
+            normalize_id(" Edge-3F2504E0-4F89-41D3-9A0C-0305E82C3301").unwrap(),
+            "edge-3f2504e0-4f89-41d3-9a0c-0305e82c3301"
+        );
+        assert!(generate_prefixed_id("UML Class").starts_with("uml-class-"));
+    }
+}
@@ -1,10 +1,21 @@
+pub mod ids;
+pub mod uml;
+
+pub use ids::{
+    generate_id, generate_prefixed_id, normalize_id, validate_id, IdPrefixes, InvalidId,
+    EDGE_ID_PREFIX,
+};
+pub use uml::{EdgeType, UnknownVariant, Visibility};
+
 use crate::selection::SelectionState;
 use crate::wasm::ComponentGroup;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
 use std::str::FromStr;
-use uuid::Uuid;
+
+/// Metadata key marking a diagram as read-only
+pub const READ_ONLY_KEY: &str = "readOnly";
 
 /// Core diagram model
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,7 +37,12 @@ pub struct DiagramModel {
     #[serde(default)]
     pub metadata: HashMap<String, serde_json::Value>,
     #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
     pub component_groups: HashMap<String, ComponentGroup>,
+    /// The user's last pan and zoom, restored when the diagram is reopened
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub viewport: Option<Viewport>,
 }
 
 fn default_name() -> String {
@@ -48,10 +64,15 @@ fn default_name() -> String {
 /// - `properties`: Key-value pairs for element-specific data
 /// - `label`: Optional text label for the element
 /// - `source_id`/`target_id`: Connection points for edges
+/// - `sources`/`targets`: Connection points for hyperedges, which join several nodes at once
 /// - `route`: Path points for edge routing
 /// - `visible`: Whether the element should be displayed
 /// - `z_index`: Layering order for overlapping elements
 /// - `style`: Visual styling properties
+/// - `created_at`/`updated_at`: Audit timestamps, set when the element is created or modified
+/// - `created_by`: Optional ID of the client that created the element. Only
+///   an identity a token's `client:<id>` grant vouches for is recorded; a bare
+///   `clientId` argument leaves it unset (see [`crate::auth`])
 ///
 /// # Examples
 ///
@@ -69,10 +90,15 @@ fn default_name() -> String {
 ///     label: Some("Process Step".to_string()),
 ///     source_id: None,
 ///     target_id: None,
+///     sources: None,
+///     targets: None,
 ///     route: None,
 ///     visible: true,
 ///     z_index: Some(1),
 ///     style: HashMap::new(),
+///     created_at: None,
+///     updated_at: None,
+///     created_by: None,
 /// };
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -94,6 +120,10 @@ pub struct ModelElement {
     pub source_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub target_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sources: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub targets: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub route: Option<Vec<Position>>,
     #[serde(default = "default_true")]
@@ -102,6 +132,19 @@ pub struct ModelElement {
     pub z_index: Option<i32>,
     #[serde(default)]
     pub style: HashMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+}
+
+impl ModelElement {
+    /// Record a modification of this element
+    pub fn touch(&mut self) {
+        self.updated_at = Some(chrono::Utc::now());
+    }
 }
 
 fn default_true() -> bool {
@@ -117,6 +160,29 @@ pub struct Bounds {
     pub height: f64,
 }
 
+/// Visible area of the canvas: the top-left corner in diagram coordinates and the zoom factor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Viewport {
+    pub x: f64,
+    pub y: f64,
+    pub zoom: f64,
+}
+
+impl Viewport {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.x.is_finite() || !self.y.is_finite() {
+            return Err("Viewport x and y must be finite numbers".to_string());
+        }
+        if !self.zoom.is_finite() || self.zoom <= 0.0 {
+            return Err(format!(
+                "Viewport zoom must be a positive number, got {}",
+                self.zoom
+            ));
+        }
+        Ok(())
+    }
+}
+
 /// Position coordinate
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Position {
@@ -278,7 +344,7 @@ mod element_type_serde {
 
 impl DiagramModel {
     pub fn new(diagram_type: &str) -> Self {
-        let id = Uuid::new_v4().to_string();
+        let id = generate_id();
         let root_id = format!("{id}_root");
 
         let root = ModelElement {
@@ -291,10 +357,15 @@ impl DiagramModel {
             label: None,
             source_id: None,
             target_id: None,
+            sources: None,
+            targets: None,
             route: None,
             visible: true,
             z_index: None,
             style: HashMap::new(),
+            created_at: None,
+            updated_at: None,
+            created_by: None,
         };
 
         let mut elements = HashMap::new();
@@ -311,19 +382,23 @@ impl DiagramModel {
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
             metadata: HashMap::new(),
+            tags: Vec::new(),
             component_groups: HashMap::new(),
+            viewport: None,
         }
     }
 
     pub fn add_element(&mut self, element: ModelElement) {
         self.elements.insert(element.id.clone(), element);
         self.revision += 1;
+        self.updated_at = chrono::Utc::now();
     }
 
     pub fn remove_element(&mut self, element_id: &str) -> Option<ModelElement> {
         let removed = self.elements.remove(element_id);
         if removed.is_some() {
             self.revision += 1;
+            self.updated_at = chrono::Utc::now();
         }
         removed
     }
@@ -347,6 +422,25 @@ impl DiagramModel {
         self.revision += 1;
     }
 
+    /// Add elements as children of the root in one change, bumping the
+    /// revision once; returns how many were added
+    pub fn add_root_elements(&mut self, elements: impl IntoIterator<Item = ModelElement>) -> usize {
+        let mut added = 0;
+        for element in elements {
+            let children = self.root.children.get_or_insert_with(Vec::new);
+            if !children.contains(&element.id) {
+                children.push(element.id.clone());
+            }
+            self.elements.insert(element.id.clone(), element);
+            added += 1;
+        }
+        if added > 0 {
+            self.revision += 1;
+            self.updated_at = chrono::Utc::now();
+        }
+        added
+    }
+
     pub fn get_all_element_ids(&self) -> Vec<String> {
         self.elements
             .keys()
@@ -370,6 +464,45 @@ impl DiagramModel {
         None
     }
 
+    /// Merge metadata entries into the diagram; a `null` value removes the key
+    pub fn merge_metadata(&mut self, entries: &serde_json::Map<String, serde_json::Value>) {
+        for (key, value) in entries {
+            if value.is_null() {
+                self.metadata.remove(key);
+            } else {
+                self.metadata.insert(key.clone(), value.clone());
+            }
+        }
+        self.updated_at = chrono::Utc::now();
+    }
+
+    /// Add tags, ignoring blanks and tags already present. Returns the tags that were added
+    pub fn add_tags<'a>(&mut self, tags: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        let mut added = Vec::new();
+        for tag in tags.into_iter().map(str::trim) {
+            if !tag.is_empty() && !self.tags.iter().any(|t| t == tag) {
+                self.tags.push(tag.to_string());
+                added.push(tag.to_string());
+            }
+        }
+        if !added.is_empty() {
+            self.updated_at = chrono::Utc::now();
+        }
+        added
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Whether the diagram is frozen against changes
+    pub fn is_read_only(&self) -> bool {
+        self.metadata
+            .get(READ_ONLY_KEY)
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false)
+    }
+
     pub fn add_component_group(&mut self, group: ComponentGroup) {
         self.component_groups.insert(group.id.clone(), group);
         self.revision += 1;
@@ -400,7 +533,8 @@ impl DiagramModel {
 
 impl Node {
     pub fn new(node_type: &str, position: Position, label: Option<String>) -> Self {
-        let id = Uuid::new_v4().to_string();
+        let id = generate_id();
+        let now = chrono::Utc::now();
         let mut properties = HashMap::new();
 
         if let Some(ref label_text) = label {
@@ -426,10 +560,15 @@ impl Node {
                 label: label.clone(),
                 source_id: None,
                 target_id: None,
+                sources: None,
+                targets: None,
                 route: None,
                 visible: true,
                 z_index: None,
                 style: HashMap::new(),
+                created_at: Some(now),
+                updated_at: Some(now),
+                created_by: None,
             },
             position,
             size: Some(Size {
@@ -448,7 +587,8 @@ impl Edge {
         target_id: String,
         label: Option<String>,
     ) -> Self {
-        let id = Uuid::new_v4().to_string();
+        let id = generate_id();
+        let now = chrono::Utc::now();
         let mut properties = HashMap::new();
 
         if let Some(ref label_text) = label {
@@ -469,10 +609,15 @@ impl Edge {
                 label: label.clone(),
                 source_id: Some(source_id.clone()),
                 target_id: Some(target_id.clone()),
+                sources: None,
+                targets: None,
                 route: None,
                 visible: true,
                 z_index: None,
                 style: HashMap::new(),
+                created_at: Some(now),
+                updated_at: Some(now),
+                created_by: None,
             },
             source_id,
             target_id,
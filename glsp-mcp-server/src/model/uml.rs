@@ -0,0 +1,161 @@
+//! Typed UML vocabulary
+//!
+//! Member visibilities and class relation types travel as lowercase strings
+//! (`"private"`, `"association"`). Parsing them into these enums, instead of
+//! matching strings where they are used, turns a typo into an error that
+//! lists the accepted values.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A string that is not one of an enum's accepted values
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown {kind} '{value}' (expected one of: {})", expected.join(", "))]
+pub struct UnknownVariant {
+    pub kind: &'static str,
+    pub value: String,
+    pub expected: &'static [&'static str],
+}
+
+/// Visibility of a class member
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    #[default]
+    Public,
+    Private,
+    Protected,
+    Package,
+}
+
+impl Visibility {
+    pub const ALL: &'static [&'static str] = &["public", "private", "protected", "package"];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Visibility::Public => "public",
+            Visibility::Private => "private",
+            Visibility::Protected => "protected",
+            Visibility::Package => "package",
+        }
+    }
+
+    /// UML notation prefix (`+`, `-`, `#`, `~`)
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Visibility::Public => "+",
+            Visibility::Private => "-",
+            Visibility::Protected => "#",
+            Visibility::Package => "~",
+        }
+    }
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Visibility {
+    type Err = UnknownVariant;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "public" => Ok(Visibility::Public),
+            "private" => Ok(Visibility::Private),
+            "protected" => Ok(Visibility::Protected),
+            "package" => Ok(Visibility::Package),
+            other => Err(UnknownVariant {
+                kind: "visibility",
+                value: other.to_string(),
+                expected: Self::ALL,
+            }),
+        }
+    }
+}
+
+/// Relation between UML classifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeType {
+    Association,
+    Aggregation,
+    Composition,
+    #[serde(alias = "generalization")]
+    Inheritance,
+    Realization,
+    Dependency,
+}
+
+impl EdgeType {
+    pub const ALL: &'static [&'static str] = &[
+        "association",
+        "aggregation",
+        "composition",
+        "inheritance",
+        "realization",
+        "dependency",
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeType::Association => "association",
+            EdgeType::Aggregation => "aggregation",
+            EdgeType::Composition => "composition",
+            EdgeType::Inheritance => "inheritance",
+            EdgeType::Realization => "realization",
+            EdgeType::Dependency => "dependency",
+        }
+    }
+}
+
+impl fmt::Display for EdgeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EdgeType {
+    type Err = UnknownVariant;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "association" => Ok(EdgeType::Association),
+            "aggregation" => Ok(EdgeType::Aggregation),
+            "composition" => Ok(EdgeType::Composition),
+            "inheritance" | "generalization" => Ok(EdgeType::Inheritance),
+            "realization" => Ok(EdgeType::Realization),
+            "dependency" => Ok(EdgeType::Dependency),
+            other => Err(UnknownVariant {
+                kind: "edge type",
+                value: other.to_string(),
+                expected: Self::ALL,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_and_reject_typos() {
+        assert_eq!("private".parse::<Visibility>(), Ok(Visibility::Private));
+        assert_eq!(
+            serde_json::from_value::<EdgeType>(json!("generalization")).unwrap(),
+            EdgeType::Inheritance
+        );
+        assert_eq!(json!(EdgeType::Association), json!("association"));
+
+        let error = "privat".parse::<Visibility>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unknown visibility 'privat' (expected one of: public, private, protected, package)"
+        );
+        assert!(serde_json::from_value::<EdgeType>(json!("asociation")).is_err());
+    }
+}
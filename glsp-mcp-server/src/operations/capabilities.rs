@@ -0,0 +1,431 @@
+//! Node and edge types allowed per diagram type
+//!
+//! This is the server-side source of truth for palettes: `create_node` and
+//! `create_edge` validate against it and `get_diagram_type_capabilities`
+//! publishes it. Diagram types without a declaration accept any node or edge
+//! type so that custom notations keep working. Notes (see
+//! [`crate::operations::conversion::NOTE_TYPE`]) are allowed everywhere.
+
+use crate::operations::conversion::NOTE_TYPE;
+use serde_json::{json, Value};
+
+/// A property a node type understands
+pub struct PropertySpec {
+    pub name: &'static str,
+    /// JSON Schema type of the value
+    pub kind: &'static str,
+    pub description: &'static str,
+}
+
+pub struct NodeTypeSpec {
+    pub node_type: &'static str,
+    pub label: &'static str,
+    pub properties: &'static [PropertySpec],
+}
+
+/// An edge type and the node types it may connect; an empty list allows any
+/// node type of the diagram
+pub struct EdgeTypeSpec {
+    pub edge_type: &'static str,
+    pub label: &'static str,
+    pub sources: &'static [&'static str],
+    pub targets: &'static [&'static str],
+}
+
+pub struct DiagramTypeSpec {
+    pub diagram_type: &'static str,
+    pub node_types: &'static [NodeTypeSpec],
+    pub edge_types: &'static [EdgeTypeSpec],
+}
+
+const fn node(node_type: &'static str, label: &'static str) -> NodeTypeSpec {
+    NodeTypeSpec {
+        node_type,
+        label,
+        properties: &[],
+    }
+}
+
+const fn any_edge(edge_type: &'static str, label: &'static str) -> EdgeTypeSpec {
+    EdgeTypeSpec {
+        edge_type,
+        label,
+        sources: &[],
+        targets: &[],
+    }
+}
+
+const DESCRIPTION: PropertySpec = PropertySpec {
+    name: "description",
+    kind: "string",
+    description: "Free-form description",
+};
+
+const WORKFLOW_NODES: &[NodeTypeSpec] = &[
+    NodeTypeSpec {
+        node_type: "task",
+        label: "Task",
+        properties: &[
+            DESCRIPTION,
+            PropertySpec {
+                name: "assignee",
+                kind: "string",
+                description: "Who performs the task",
+            },
+        ],
+    },
+    node("start-event", "Start Event"),
+    node("end-event", "End Event"),
+    NodeTypeSpec {
+        node_type: "gateway",
+        label: "Gateway",
+        properties: &[PropertySpec {
+            name: "gatewayType",
+            kind: "string",
+            description: "exclusive, parallel or inclusive",
+        }],
+    },
+    NodeTypeSpec {
+        node_type: "decision",
+        label: "Decision",
+        properties: &[PropertySpec {
+            name: "condition",
+            kind: "string",
+            description: "Condition evaluated by the decision",
+        }],
+    },
+    NodeTypeSpec {
+        node_type: "subprocess",
+        label: "Subprocess",
+        properties: &[DESCRIPTION],
+    },
+];
+
+const WORKFLOW_EDGES: &[EdgeTypeSpec] = &[
+    EdgeTypeSpec {
+        edge_type: "flow",
+        label: "Flow",
+        sources: &["start-event", "task", "gateway", "decision", "subprocess"],
+        targets: &["end-event", "task", "gateway", "decision", "subprocess"],
+    },
+    any_edge("association", "Association"),
+    any_edge("dependency", "Dependency"),
+];
+
+const MEMBERS: &[PropertySpec] = &[
+    PropertySpec {
+        name: "attributes",
+        kind: "array",
+        description: "Attribute strings or {name, type, visibility} objects",
+    },
+    PropertySpec {
+        name: "methods",
+        kind: "array",
+        description: "Method strings or {name, type, visibility} objects",
+    },
+];
+
+pub(crate) const CLASSIFIERS: &[&str] = &["class", "interface", "enum"];
+
+pub const DIAGRAM_TYPES: &[DiagramTypeSpec] = &[
+    DiagramTypeSpec {
+        diagram_type: "workflow",
+        node_types: WORKFLOW_NODES,
+        edge_types: WORKFLOW_EDGES,
+    },
+    DiagramTypeSpec {
+        diagram_type: "bpmn",
+        node_types: WORKFLOW_NODES,
+        edge_types: WORKFLOW_EDGES,
+    },
+    DiagramTypeSpec {
+        diagram_type: "uml-class",
+        node_types: &[
+            NodeTypeSpec {
+                node_type: "class",
+                label: "Class",
+                properties: MEMBERS,
+            },
+            NodeTypeSpec {
+                node_type: "interface",
+                label: "Interface",
+                properties: MEMBERS,
+            },
+            NodeTypeSpec {
+                node_type: "enum",
+                label: "Enum",
+                properties: &[PropertySpec {
+                    name: "attributes",
+                    kind: "array",
+                    description: "Enum literals",
+                }],
+            },
+            node("package", "Package"),
+        ],
+        edge_types: &[
+            EdgeTypeSpec {
+                edge_type: "inheritance",
+                label: "Inheritance",
+                sources: &["class", "interface"],
+                targets: &["class", "interface"],
+            },
+            EdgeTypeSpec {
+                edge_type: "realization",
+                label: "Realization",
+                sources: &["class"],
+                targets: &["interface"],
+            },
+            EdgeTypeSpec {
+                edge_type: "composition",
+                label: "Composition",
+                sources: &["class"],
+                targets: CLASSIFIERS,
+            },
+            EdgeTypeSpec {
+                edge_type: "aggregation",
+                label: "Aggregation",
+                sources: &["class"],
+                targets: CLASSIFIERS,
+            },
+            EdgeTypeSpec {
+                edge_type: "association",
+                label: "Association",
+                sources: CLASSIFIERS,
+                targets: CLASSIFIERS,
+            },
+            any_edge("dependency", "Dependency"),
+        ],
+    },
+    DiagramTypeSpec {
+        diagram_type: "uml-activity",
+        node_types: &[
+            node("action", "Action"),
+            node("initial-node", "Initial Node"),
+            node("final-node", "Final Node"),
+            node("fork-node", "Fork Node"),
+            node("decision-node", "Decision Node"),
+            node("call-behavior-action", "Call Behavior Action"),
+        ],
+        edge_types: &[
+            EdgeTypeSpec {
+                edge_type: "control-flow",
+                label: "Control Flow",
+                sources: &[
+                    "initial-node",
+                    "action",
+                    "fork-node",
+                    "decision-node",
+                    "call-behavior-action",
+                ],
+                targets: &[
+                    "final-node",
+                    "action",
+                    "fork-node",
+                    "decision-node",
+                    "call-behavior-action",
+                ],
+            },
+            any_edge("object-flow", "Object Flow"),
+        ],
+    },
+    DiagramTypeSpec {
+        diagram_type: "wasm-component",
+        node_types: &[
+            NodeTypeSpec {
+                node_type: "wasm-component",
+                label: "WASM Component",
+                properties: &[PropertySpec {
+                    name: "componentName",
+                    kind: "string",
+                    description: "Name of the loaded component",
+                }],
+            },
+            node("host-component", "Host Component"),
+            node("import-interface", "Import Interface"),
+            node("export-interface", "Export Interface"),
+            node("composition-root", "Composition Root"),
+        ],
+        edge_types: &[
+            any_edge("interface-connection", "Interface Connection"),
+            any_edge("dependency", "Dependency"),
+            EdgeTypeSpec {
+                edge_type: "composition-contains",
+                label: "Contains",
+                sources: &["composition-root"],
+                targets: &["wasm-component", "host-component"],
+            },
+        ],
+    },
+    DiagramTypeSpec {
+        diagram_type: "system-architecture",
+        node_types: &[
+            node("service", "Service"),
+            node("database", "Database"),
+            node("queue", "Message Queue"),
+            node("cache", "Cache"),
+            node("load-balancer", "Load Balancer"),
+            node("api-gateway", "API Gateway"),
+        ],
+        edge_types: &[
+            any_edge("http-api", "HTTP API"),
+            any_edge("async-message", "Async Message"),
+            any_edge("data-flow", "Data Flow"),
+        ],
+    },
+];
+
+/// Edge types that express a hierarchy and so must not form a cycle, in any
+/// diagram type
+pub const ACYCLIC_EDGE_TYPES: &[&str] = &[
+    "inheritance",
+    "realization",
+    "composition",
+    "composition-contains",
+];
+
+/// The declaration for a diagram type, if it has one
+pub fn diagram_type_spec(diagram_type: &str) -> Option<&'static DiagramTypeSpec> {
+    DIAGRAM_TYPES
+        .iter()
+        .find(|d| d.diagram_type == diagram_type)
+}
+
+impl DiagramTypeSpec {
+    fn has_node_type(&self, node_type: &str) -> bool {
+        node_type == NOTE_TYPE || self.node_types.iter().any(|n| n.node_type == node_type)
+    }
+
+    /// Check that a node type may be created in this diagram type
+    pub fn check_node_type(&self, node_type: &str) -> Result<(), String> {
+        if self.has_node_type(node_type) {
+            return Ok(());
+        }
+        Err(format!(
+            "Node type '{node_type}' is not allowed in {} diagrams (allowed: {})",
+            self.diagram_type,
+            self.node_types
+                .iter()
+                .map(|n| n.node_type)
+                .chain(std::iter::once(NOTE_TYPE))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    }
+
+    /// Check that an edge type may connect nodes of the given types
+    pub fn check_edge(
+        &self,
+        edge_type: &str,
+        source_type: &str,
+        target_type: &str,
+    ) -> Result<(), String> {
+        let Some(spec) = self.edge_types.iter().find(|e| e.edge_type == edge_type) else {
+            return Err(format!(
+                "Edge type '{edge_type}' is not allowed in {} diagrams (allowed: {})",
+                self.diagram_type,
+                self.edge_types
+                    .iter()
+                    .map(|e| e.edge_type)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        };
+
+        let allows = |allowed: &[&str], node_type: &str| {
+            node_type == NOTE_TYPE || allowed.is_empty() || allowed.contains(&node_type)
+        };
+        if !allows(spec.sources, source_type) || !allows(spec.targets, target_type) {
+            return Err(format!(
+                "A {edge_type} edge cannot connect {source_type} to {target_type} in {} diagrams",
+                self.diagram_type
+            ));
+        }
+        Ok(())
+    }
+
+    /// Capabilities as returned by `get_diagram_type_capabilities`
+    pub fn to_json(&self) -> Value {
+        let node_types: Vec<Value> = self
+            .node_types
+            .iter()
+            .map(|n| {
+                let properties: serde_json::Map<String, Value> = n
+                    .properties
+                    .iter()
+                    .map(|p| {
+                        (
+                            p.name.to_string(),
+                            json!({"type": p.kind, "description": p.description}),
+                        )
+                    })
+                    .collect();
+                json!({
+                    "type": n.node_type,
+                    "label": n.label,
+                    "propertySchema": {"type": "object", "properties": properties},
+                })
+            })
+            .collect();
+
+        let edge_types: Vec<Value> = self
+            .edge_types
+            .iter()
+            .map(|e| {
+                let or_any = |types: &[&'static str]| {
+                    if types.is_empty() {
+                        vec!["*"]
+                    } else {
+                        types.to_vec()
+                    }
+                };
+                let connections: Vec<Value> = or_any(e.sources)
+                    .iter()
+                    .flat_map(|source| {
+                        or_any(e.targets)
+                            .into_iter()
+                            .map(move |target| json!({"source": source, "target": target}))
+                    })
+                    .collect();
+                json!({
+                    "type": e.edge_type,
+                    "label": e.label,
+                    "connections": connections,
+                })
+            })
+            .collect();
+
+        json!({
+            "diagramType": self.diagram_type,
+            "nodeTypes": node_types,
+            "edgeTypes": edge_types,
+            "alwaysAllowedNodeTypes": [NOTE_TYPE],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uml_class_capabilities() {
+        let spec = diagram_type_spec("uml-class").unwrap();
+        assert!(spec.check_node_type("class").is_ok());
+        assert!(spec.check_node_type(NOTE_TYPE).is_ok());
+        assert!(spec.check_node_type("task").is_err());
+
+        assert!(spec.check_edge("realization", "class", "interface").is_ok());
+        assert!(spec
+            .check_edge("realization", "interface", "class")
+            .is_err());
+        assert!(spec.check_edge("dependency", "enum", "package").is_ok());
+        assert!(spec.check_edge("flow", "class", "class").is_err());
+
+        let json = spec.to_json();
+        assert_eq!(
+            json["nodeTypes"][0]["propertySchema"]["properties"]["methods"]["type"],
+            "array"
+        );
+        assert!(diagram_type_spec("my-notation").is_none());
+    }
+}
@@ -0,0 +1,358 @@
+//! Compare two separate diagrams
+//!
+//! Unlike the operation log, which diffs two versions of one diagram, the
+//! diagrams compared here may have been forked, duplicated or imported, so
+//! their elements do not necessarily share IDs. Elements are matched in
+//! three passes, each reporting how confident the match is:
+//!
+//! 1. the same ID on both sides (`id`, confidence 1.0)
+//! 2. nodes with the same type and label (`label`, 0.8, or 0.6 when several
+//!    candidates share the label and the nearest one is taken)
+//! 3. edges of the same type between matched nodes (`endpoints`, 0.9 times
+//!    the weaker endpoint match)
+//!
+//! Matched elements are compared field by field, with `properties` and
+//! `style` entries compared individually. References to other elements are
+//! translated to the left diagram's IDs first, so a duplicate with fresh IDs
+//! compares as unchanged. Timestamps and authorship are ignored.
+
+use super::graph::is_edge;
+use crate::model::{DiagramModel, ModelElement};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+const LABEL_CONFIDENCE: f64 = 0.8;
+const AMBIGUOUS_LABEL_CONFIDENCE: f64 = 0.6;
+const ENDPOINT_CONFIDENCE: f64 = 0.9;
+
+/// Fields left out of the comparison
+const IGNORED_FIELDS: &[&str] = &["id", "created_at", "updated_at", "created_by"];
+
+/// Fields whose entries are compared one by one
+const NESTED_FIELDS: &[&str] = &["properties", "style"];
+
+/// An element present in only one of the diagrams
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementSummary {
+    pub id: String,
+    pub element_type: String,
+    pub label: Option<String>,
+}
+
+impl ElementSummary {
+    fn of(element: &ModelElement) -> Self {
+        Self {
+            id: element.id.clone(),
+            element_type: element.element_type.as_str().to_string(),
+            label: element.label.clone(),
+        }
+    }
+}
+
+/// One field that differs between matched elements; a missing field is null
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    /// Field name, `properties.<key>` or `style.<key>` for nested entries
+    pub field: String,
+    pub left: Value,
+    pub right: Value,
+}
+
+/// A pair of elements matched across the diagrams
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementMatch {
+    pub left_id: String,
+    pub right_id: String,
+    pub element_type: String,
+    pub label: Option<String>,
+    /// `id`, `label` or `endpoints`
+    pub method: &'static str,
+    pub confidence: f64,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Outcome of [`compare_diagrams`]
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagramComparison {
+    /// Only in the right diagram
+    pub added: Vec<ElementSummary>,
+    /// Only in the left diagram
+    pub removed: Vec<ElementSummary>,
+    /// Matched with at least one differing field
+    pub changed: Vec<ElementMatch>,
+    /// Matched without differences
+    pub unchanged: usize,
+    /// Mean confidence over all matches; 1.0 when nothing had to be matched
+    pub confidence: f64,
+}
+
+/// Compare every element of `left` with `right` (the diagrams' roots are
+/// skipped)
+pub fn compare_diagrams(left: &DiagramModel, right: &DiagramModel) -> DiagramComparison {
+    let left_elements = elements(left);
+    let right_elements = elements(right);
+
+    // right ID -> (left ID, method, confidence)
+    let mut matches: BTreeMap<&str, (&str, &'static str, f64)> = BTreeMap::new();
+    let mut matched_left: BTreeSet<&str> = BTreeSet::new();
+
+    for (id, element) in &right_elements {
+        if let Some(other) = left_elements.get(id) {
+            if other.element_type == element.element_type {
+                matches.insert(*id, (*id, "id", 1.0));
+                matched_left.insert(*id);
+            }
+        }
+    }
+
+    for (right_id, element) in right_elements.iter().filter(|(_, e)| !is_edge(e)) {
+        if matches.contains_key(right_id) || element.label.is_none() {
+            continue;
+        }
+        let candidates: Vec<&ModelElement> = left_elements
+            .values()
+            .filter(|candidate| {
+                !is_edge(candidate)
+                    && !matched_left.contains(candidate.id.as_str())
+                    && candidate.element_type == element.element_type
+                    && candidate.label == element.label
+            })
+            .copied()
+            .collect();
+        let (best, confidence) = match candidates.as_slice() {
+            [] => continue,
+            [only] => (*only, LABEL_CONFIDENCE),
+            many => {
+                let nearest = many
+                    .iter()
+                    .min_by(|a, b| distance(a, element).total_cmp(&distance(b, element)))
+                    .copied()
+                    .unwrap_or(many[0]);
+                (nearest, AMBIGUOUS_LABEL_CONFIDENCE)
+            }
+        };
+        matches.insert(*right_id, (best.id.as_str(), "label", confidence));
+        matched_left.insert(best.id.as_str());
+    }
+
+    for (right_id, element) in right_elements.iter().filter(|(_, e)| is_edge(e)) {
+        if matches.contains_key(right_id) {
+            continue;
+        }
+        let endpoint = |id: &Option<String>| {
+            id.as_deref()
+                .and_then(|id| matches.get(id))
+                .map(|(left_id, _, confidence)| (*left_id, *confidence))
+        };
+        let (Some((source, source_confidence)), Some((target, target_confidence))) =
+            (endpoint(&element.source_id), endpoint(&element.target_id))
+        else {
+            continue;
+        };
+        let candidate = left_elements.values().find(|candidate| {
+            is_edge(candidate)
+                && !matched_left.contains(candidate.id.as_str())
+                && candidate.element_type == element.element_type
+                && candidate.source_id.as_deref() == Some(source)
+                && candidate.target_id.as_deref() == Some(target)
+        });
+        if let Some(candidate) = candidate {
+            let confidence = ENDPOINT_CONFIDENCE * source_confidence.min(target_confidence);
+            matches.insert(*right_id, (candidate.id.as_str(), "endpoints", confidence));
+            matched_left.insert(candidate.id.as_str());
+        }
+    }
+
+    // Translate references in the right diagram into left IDs
+    let mut id_map: HashMap<String, String> = matches
+        .iter()
+        .map(|(right_id, (left_id, _, _))| (right_id.to_string(), left_id.to_string()))
+        .collect();
+    id_map.insert(right.root.id.clone(), left.root.id.clone());
+
+    let mut comparison = DiagramComparison::default();
+    for (right_id, &(left_id, method, confidence)) in &matches {
+        let left_element = left_elements[left_id];
+        let right_element = right_elements[right_id];
+        let changes = field_changes(left_element, right_element, &id_map);
+        if changes.is_empty() {
+            comparison.unchanged += 1;
+        } else {
+            comparison.changed.push(ElementMatch {
+                left_id: left_id.to_string(),
+                right_id: right_id.to_string(),
+                element_type: left_element.element_type.as_str().to_string(),
+                label: left_element.label.clone(),
+                method,
+                confidence,
+                changes,
+            });
+        }
+    }
+    comparison.added = right_elements
+        .iter()
+        .filter(|(id, _)| !matches.contains_key(*id))
+        .map(|(_, e)| ElementSummary::of(e))
+        .collect();
+    comparison.removed = left_elements
+        .iter()
+        .filter(|(id, _)| !matched_left.contains(*id))
+        .map(|(_, e)| ElementSummary::of(e))
+        .collect();
+    comparison.confidence = if matches.is_empty() {
+        1.0
+    } else {
+        matches.values().map(|(_, _, c)| c).sum::<f64>() / matches.len() as f64
+    };
+    comparison
+}
+
+/// Non-root elements by ID, sorted for stable output
+fn elements(diagram: &DiagramModel) -> BTreeMap<&str, &ModelElement> {
+    diagram
+        .elements
+        .iter()
+        .filter(|(id, _)| **id != diagram.root.id)
+        .map(|(id, e)| (id.as_str(), e))
+        .collect()
+}
+
+/// Distance between the top-left corners of two elements; elements without
+/// bounds are infinitely far away
+fn distance(a: &ModelElement, b: &ModelElement) -> f64 {
+    match (&a.bounds, &b.bounds) {
+        (Some(a), Some(b)) => (a.x - b.x).hypot(a.y - b.y),
+        _ => f64::INFINITY,
+    }
+}
+
+fn field_changes(
+    left: &ModelElement,
+    right: &ModelElement,
+    id_map: &HashMap<String, String>,
+) -> Vec<FieldChange> {
+    let mut right = right.clone();
+    let translate = |id: &mut String| {
+        if let Some(left_id) = id_map.get(id.as_str()) {
+            *id = left_id.clone();
+        }
+    };
+    right.source_id.iter_mut().for_each(translate);
+    right.target_id.iter_mut().for_each(translate);
+    for ids in [&mut right.sources, &mut right.targets, &mut right.children] {
+        ids.iter_mut().flatten().for_each(translate);
+    }
+
+    let left = object(left);
+    let right = object(&right);
+    let mut changes = Vec::new();
+    let fields: BTreeSet<&String> = left.keys().chain(right.keys()).collect();
+    for field in fields {
+        if IGNORED_FIELDS.contains(&field.as_str()) {
+            continue;
+        }
+        let (l, r) = (
+            left.get(field).unwrap_or(&Value::Null),
+            right.get(field).unwrap_or(&Value::Null),
+        );
+        if l == r {
+            continue;
+        }
+        match (l, r) {
+            (Value::Object(l), Value::Object(r)) if NESTED_FIELDS.contains(&field.as_str()) => {
+                let keys: BTreeSet<&String> = l.keys().chain(r.keys()).collect();
+                for key in keys {
+                    let (lv, rv) = (
+                        l.get(key).unwrap_or(&Value::Null),
+                        r.get(key).unwrap_or(&Value::Null),
+                    );
+                    if lv != rv {
+                        changes.push(FieldChange {
+                            field: format!("{field}.{key}"),
+                            left: lv.clone(),
+                            right: rv.clone(),
+                        });
+                    }
+                }
+            }
+            _ => changes.push(FieldChange {
+                field: field.clone(),
+                left: l.clone(),
+                right: r.clone(),
+            }),
+        }
+    }
+    changes
+}
+
+fn object(element: &ModelElement) -> Map<String, Value> {
+    match serde_json::to_value(element) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Edge, Node, Position};
+    use serde_json::json;
+
+    fn add_node(diagram: &mut DiagramModel, label: &str, x: f64) -> String {
+        let node = Node::new("task", Position { x, y: 0.0 }, Some(label.to_string()));
+        let id = node.base.id.clone();
+        diagram.add_element(node.base);
+        diagram.add_child_to_root(&id);
+        id
+    }
+
+    #[test]
+    fn test_compare_matches_by_id_then_label() {
+        let mut left = DiagramModel::new("workflow");
+        let a = add_node(&mut left, "Start", 0.0);
+        let b = add_node(&mut left, "Review", 200.0);
+        let gone = add_node(&mut left, "Archive", 400.0);
+        let edge = Edge::new("flow", a.clone(), b.clone(), None);
+        left.add_element(edge.base);
+
+        // The right side keeps Start's ID, recreates Review and its edge under
+        // new IDs, drops Archive and adds Publish
+        let mut right = DiagramModel::new("workflow");
+        right.add_element(left.elements[&a].clone());
+        right.add_child_to_root(&a);
+        let review = add_node(&mut right, "Review", 250.0);
+        right
+            .elements
+            .get_mut(&review)
+            .unwrap()
+            .properties
+            .insert("owner".to_string(), json!("sam"));
+        let added = add_node(&mut right, "Publish", 600.0);
+        let edge = Edge::new("flow", a.clone(), review.clone(), None);
+        right.add_element(edge.base);
+
+        let comparison = compare_diagrams(&left, &right);
+        assert_eq!(comparison.unchanged, 2, "{comparison:?}");
+        assert_eq!(comparison.added.len(), 1);
+        assert_eq!(comparison.added[0].id, added);
+        assert_eq!(comparison.removed.len(), 1);
+        assert_eq!(comparison.removed[0].id, gone);
+
+        let [changed] = comparison.changed.as_slice() else {
+            panic!("expected one changed element: {comparison:?}");
+        };
+        assert_eq!(
+            (changed.left_id.as_str(), changed.method),
+            (b.as_str(), "label")
+        );
+        assert_eq!(changed.confidence, LABEL_CONFIDENCE);
+        let fields: Vec<&str> = changed.changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["bounds", "properties.owner"]);
+        // (1.0 + 0.8 + 0.72) / 3 for Start, Review and the edge
+        assert!((comparison.confidence - 0.84).abs() < 1e-9);
+    }
+}
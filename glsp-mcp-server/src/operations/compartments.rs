@@ -0,0 +1,287 @@
+//! UML class compartments
+//!
+//! Class nodes keep their members in the `attributes` and `methods`
+//! properties. Entries are either plain strings or objects with `name`,
+//! `type` and optional `visibility` (see [`Visibility`]). Which compartments are shown is stored in
+//! the `compartments` property so that exporters and the client agree on the
+//! collapsed state.
+
+use crate::model::{Bounds, ModelElement, Visibility};
+use crate::operations::TypeStyle;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Property holding the compartment visibility flags
+pub const COMPARTMENTS_PROPERTY: &str = "compartments";
+
+const HEADER_HEIGHT: f64 = 30.0;
+const LINE_HEIGHT: f64 = 18.0;
+const COMPARTMENT_PADDING: f64 = 8.0;
+const COLLAPSED_HEIGHT: f64 = 10.0;
+const CHAR_WIDTH: f64 = 7.0;
+const MIN_WIDTH: f64 = 100.0;
+
+/// Which member compartments of a class node are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompartmentVisibility {
+    pub attributes: bool,
+    pub methods: bool,
+}
+
+impl Default for CompartmentVisibility {
+    fn default() -> Self {
+        Self {
+            attributes: true,
+            methods: true,
+        }
+    }
+}
+
+impl CompartmentVisibility {
+    /// Read the flags stored on an element; missing flags mean visible
+    pub fn of(element: &ModelElement) -> Self {
+        let flags = element.properties.get(COMPARTMENTS_PROPERTY);
+        let flag = |name: &str| {
+            flags
+                .and_then(|f| f.get(name))
+                .and_then(Value::as_bool)
+                .unwrap_or(true)
+        };
+        Self {
+            attributes: flag("attributes"),
+            methods: flag("methods"),
+        }
+    }
+}
+
+/// Whether an element has member compartments at all
+pub fn has_compartments(element: &ModelElement) -> bool {
+    element.properties.contains_key("attributes") || element.properties.contains_key("methods")
+}
+
+/// Render the members of one compartment (`attributes` or `methods`) as text lines
+pub fn member_lines(element: &ModelElement, compartment: &str) -> Vec<String> {
+    let Some(members) = element
+        .properties
+        .get(compartment)
+        .and_then(Value::as_array)
+    else {
+        return Vec::new();
+    };
+
+    members
+        .iter()
+        .filter_map(|member| member_line(compartment, member))
+        .collect()
+}
+
+/// Render one entry of the `attributes` or `methods` compartment as a text line
+pub fn member_line(compartment: &str, member: &Value) -> Option<String> {
+    match member {
+        Value::String(text) => Some(text.clone()),
+        Value::Object(fields) => {
+            let name = fields.get("name")?.as_str()?;
+            let symbol = fields
+                .get("visibility")
+                .and_then(Value::as_str)
+                .and_then(|v| v.parse::<Visibility>().ok())
+                .unwrap_or_default()
+                .symbol();
+            let suffix = if compartment == "methods" { "()" } else { "" };
+            Some(match fields.get("type").and_then(Value::as_str) {
+                Some(ty) => format!("{symbol}{name}{suffix}: {ty}"),
+                None => format!("{symbol}{name}{suffix}"),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Check the member entries in `attributes` and `methods` properties.
+///
+/// Object entries need a string `name`, and their `visibility`, when given,
+/// must be a known [`Visibility`].
+pub fn check_members(properties: &serde_json::Map<String, Value>) -> Result<(), String> {
+    for compartment in ["attributes", "methods"] {
+        let Some(members) = properties.get(compartment).and_then(Value::as_array) else {
+            continue;
+        };
+        for (i, member) in members.iter().enumerate() {
+            let Value::Object(fields) = member else {
+                continue;
+            };
+            if fields.get("name").and_then(Value::as_str).is_none() {
+                return Err(format!("{compartment}[{i}] needs a string name"));
+            }
+            if let Some(visibility) = fields.get("visibility") {
+                visibility
+                    .as_str()
+                    .ok_or_else(|| format!("{compartment}[{i}].visibility must be a string"))?
+                    .parse::<Visibility>()
+                    .map_err(|e| format!("{compartment}[{i}]: {e}"))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Height of a compartment showing `lines` members, or its collapsed height
+fn compartment_height(lines: usize, visible: bool) -> f64 {
+    if visible {
+        COMPARTMENT_PADDING * 2.0 + LINE_HEIGHT * lines.max(1) as f64
+    } else {
+        COLLAPSED_HEIGHT
+    }
+}
+
+/// Set compartment visibility and resize the node to fit what is shown.
+///
+/// The node keeps its position; the new bounds are returned.
+pub fn set_compartment_visibility(
+    element: &mut ModelElement,
+    visibility: CompartmentVisibility,
+) -> Bounds {
+    element.properties.insert(
+        COMPARTMENTS_PROPERTY.to_string(),
+        serde_json::to_value(visibility).unwrap_or_default(),
+    );
+
+    let attributes = member_lines(element, "attributes");
+    let methods = member_lines(element, "methods");
+
+    let title_len = element.label.as_deref().map_or(0, str::len);
+    let widest = attributes
+        .iter()
+        .filter(|_| visibility.attributes)
+        .chain(methods.iter().filter(|_| visibility.methods))
+        .map(String::len)
+        .chain(std::iter::once(title_len))
+        .max()
+        .unwrap_or_default();
+
+    let width = (widest as f64 * CHAR_WIDTH + COMPARTMENT_PADDING * 2.0).max(MIN_WIDTH);
+    let height = HEADER_HEIGHT
+        + compartment_height(attributes.len(), visibility.attributes)
+        + compartment_height(methods.len(), visibility.methods);
+
+    let (x, y) = element.bounds.as_ref().map_or((0.0, 0.0), |b| (b.x, b.y));
+    let bounds = Bounds {
+        x,
+        y,
+        width,
+        height,
+    };
+    element.bounds = Some(bounds.clone());
+    bounds
+}
+
+/// Draw a class node with its compartments as SVG
+pub fn class_svg(element: &ModelElement, bounds: &Bounds, style: &TypeStyle) -> String {
+    let visibility = CompartmentVisibility::of(element);
+    let label = element.label.as_deref().unwrap_or_default();
+    let mut svg = format!(
+        r#"<rect x="{}" y="{}" width="{}" height="{}"{}/>"#,
+        bounds.x,
+        bounds.y,
+        bounds.width,
+        bounds.height,
+        style.shape_attributes("lightyellow")
+    );
+    svg.push_str(&format!(
+        r#"<text x="{}" y="{}" text-anchor="middle" font-weight="bold"{}>{}</text>"#,
+        bounds.x + bounds.width / 2.0,
+        bounds.y + HEADER_HEIGHT / 2.0 + 5.0,
+        style.text_attributes(),
+        label
+    ));
+
+    let mut top = bounds.y + HEADER_HEIGHT;
+    for (compartment, visible) in [
+        ("attributes", visibility.attributes),
+        ("methods", visibility.methods),
+    ] {
+        svg.push_str(&format!(
+            r#"<line x1="{}" y1="{top}" x2="{}" y2="{top}"{}/>"#,
+            bounds.x,
+            bounds.x + bounds.width,
+            style.line_attributes()
+        ));
+
+        let lines = member_lines(element, compartment);
+        if visible {
+            for (i, line) in lines.iter().enumerate() {
+                svg.push_str(&format!(
+                    r#"<text x="{}" y="{}" font-size="12">{}</text>"#,
+                    bounds.x + COMPARTMENT_PADDING,
+                    top + COMPARTMENT_PADDING + LINE_HEIGHT * (i as f64 + 0.75),
+                    line
+                ));
+            }
+        }
+        top += compartment_height(lines.len(), visible);
+    }
+
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Node, Position};
+    use serde_json::json;
+
+    fn class_node() -> ModelElement {
+        let mut node = Node::new(
+            "class",
+            Position { x: 10.0, y: 20.0 },
+            Some("Sensor".to_string()),
+        )
+        .base;
+        node.properties.insert(
+            "attributes".to_string(),
+            json!([{"name": "id", "type": "String", "visibility": "private"}, "+rate: f32"]),
+        );
+        node.properties.insert(
+            "methods".to_string(),
+            json!([{"name": "read", "type": "f64"}]),
+        );
+        node
+    }
+
+    #[test]
+    fn test_collapse_shrinks_node() {
+        let mut node = class_node();
+        assert_eq!(
+            member_lines(&node, "attributes"),
+            vec!["-id: String", "+rate: f32"]
+        );
+
+        let expanded = set_compartment_visibility(&mut node, CompartmentVisibility::default());
+        let collapsed = set_compartment_visibility(
+            &mut node,
+            CompartmentVisibility {
+                attributes: false,
+                methods: true,
+            },
+        );
+
+        assert_eq!((collapsed.x, collapsed.y), (10.0, 20.0));
+        assert!(collapsed.height < expanded.height);
+        assert_eq!(
+            CompartmentVisibility::of(&node),
+            CompartmentVisibility {
+                attributes: false,
+                methods: true
+            }
+        );
+
+        let svg = class_svg(&node, &collapsed, &TypeStyle::default());
+        assert!(!svg.contains("-id: String"));
+        assert!(svg.contains("+read(): f64"));
+
+        let invalid = json!({"methods": [{"name": "read", "visibility": "privat"}]});
+        assert!(check_members(invalid.as_object().unwrap())
+            .unwrap_err()
+            .starts_with("methods[0]: unknown visibility 'privat'"));
+    }
+}
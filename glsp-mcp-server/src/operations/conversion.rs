@@ -0,0 +1,316 @@
+//! Conversion between diagram types
+//!
+//! Node and edge types are translated through a declared mapping table.
+//! Only diagram kinds listed in [`TYPE_MAPPINGS`] can be converted; elements
+//! whose type has no counterpart in the target notation are replaced by
+//! `note` nodes so that no information is silently dropped.
+
+use crate::model::{DiagramModel, ElementType, ModelElement};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Element type used for elements that could not be mapped
+pub const NOTE_TYPE: &str = "note";
+
+/// A declared mapping between two diagram kinds.
+///
+/// Each pair maps a source element type to a target element type and is
+/// applied in reverse when converting from `to` back to `from`.
+pub struct TypeMapping {
+    pub from: &'static str,
+    pub to: &'static str,
+    pub nodes: &'static [(&'static str, &'static str)],
+    pub edges: &'static [(&'static str, &'static str)],
+}
+
+const WORKFLOW_NODES: &[(&str, &str)] = &[
+    ("task", "task"),
+    ("start-event", "start-event"),
+    ("end-event", "end-event"),
+    ("gateway", "gateway"),
+    ("decision", "decision"),
+    ("subprocess", "subprocess"),
+];
+
+const WORKFLOW_EDGES: &[(&str, &str)] = &[
+    ("flow", "flow"),
+    ("association", "association"),
+    ("dependency", "dependency"),
+];
+
+pub const TYPE_MAPPINGS: &[TypeMapping] = &[
+    TypeMapping {
+        from: "workflow",
+        to: "bpmn",
+        nodes: WORKFLOW_NODES,
+        edges: WORKFLOW_EDGES,
+    },
+    TypeMapping {
+        from: "workflow",
+        to: "uml-activity",
+        nodes: &[
+            ("task", "action"),
+            ("start-event", "initial-node"),
+            ("end-event", "final-node"),
+            ("gateway", "fork-node"),
+            ("decision", "decision-node"),
+            ("subprocess", "call-behavior-action"),
+        ],
+        edges: &[("flow", "control-flow"), ("dependency", "object-flow")],
+    },
+    TypeMapping {
+        from: "bpmn",
+        to: "uml-activity",
+        nodes: &[
+            ("task", "action"),
+            ("start-event", "initial-node"),
+            ("end-event", "final-node"),
+            ("gateway", "fork-node"),
+            ("decision", "decision-node"),
+            ("subprocess", "call-behavior-action"),
+        ],
+        edges: &[("flow", "control-flow"), ("dependency", "object-flow")],
+    },
+    TypeMapping {
+        from: "uml-class",
+        to: "wit-schema",
+        nodes: &[
+            ("interface", "interface"),
+            ("class", "record"),
+            ("enum", "enum"),
+        ],
+        edges: &[("dependency", "uses"), ("association", "uses")],
+    },
+];
+
+/// Resolved node and edge lookup tables for one conversion direction
+struct ResolvedMapping {
+    nodes: HashMap<&'static str, &'static str>,
+    edges: HashMap<&'static str, &'static str>,
+}
+
+fn resolve_mapping(from: &str, to: &str) -> Option<ResolvedMapping> {
+    TYPE_MAPPINGS.iter().find_map(|m| {
+        if m.from == from && m.to == to {
+            Some(ResolvedMapping {
+                nodes: m.nodes.iter().copied().collect(),
+                edges: m.edges.iter().copied().collect(),
+            })
+        } else if m.from == to && m.to == from {
+            Some(ResolvedMapping {
+                nodes: m.nodes.iter().map(|(a, b)| (*b, *a)).collect(),
+                edges: m.edges.iter().map(|(a, b)| (*b, *a)).collect(),
+            })
+        } else {
+            None
+        }
+    })
+}
+
+/// Diagram kinds that `diagram_type` can be converted to
+pub fn conversion_targets(diagram_type: &str) -> Vec<&'static str> {
+    TYPE_MAPPINGS
+        .iter()
+        .filter_map(|m| {
+            if m.from == diagram_type {
+                Some(m.to)
+            } else if m.to == diagram_type {
+                Some(m.from)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// An element that had no counterpart in the target notation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmappedElement {
+    pub element_id: String,
+    pub element_type: String,
+    /// ID of the note that replaces the element in the converted diagram
+    pub note_id: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConversionResult {
+    pub diagram: DiagramModel,
+    pub mapped: usize,
+    pub unmapped: Vec<UnmappedElement>,
+}
+
+/// Convert a diagram into a new diagram of `target_type`.
+///
+/// The source diagram is left untouched. Element IDs are preserved so that
+/// edges keep pointing at their converted endpoints.
+pub fn convert_diagram(
+    source: &DiagramModel,
+    target_type: &str,
+) -> Result<ConversionResult, String> {
+    let mapping = resolve_mapping(&source.diagram_type, target_type).ok_or_else(|| {
+        format!(
+            "No mapping from '{}' to '{target_type}'. Supported targets: {:?}",
+            source.diagram_type,
+            conversion_targets(&source.diagram_type)
+        )
+    })?;
+
+    let mut diagram = DiagramModel::new(target_type);
+    diagram.name = format!("{} ({target_type})", source.name);
+    diagram.metadata = source.metadata.clone();
+    diagram.metadata.insert(
+        "convertedFrom".to_string(),
+        serde_json::json!({"diagramId": source.id, "diagramType": source.diagram_type}),
+    );
+
+    let mut mapped = 0;
+    let mut unmapped = Vec::new();
+
+    for (id, element) in &source.elements {
+        if id == &source.root.id {
+            continue;
+        }
+
+        let is_edge = element.source_id.is_some() && element.target_id.is_some();
+        let table = if is_edge {
+            &mapping.edges
+        } else {
+            &mapping.nodes
+        };
+
+        match table.get(element.element_type.as_str()) {
+            Some(target) => {
+                let mut converted = element.clone();
+                converted.element_type = ElementType::from(*target);
+                diagram.add_element(converted);
+                mapped += 1;
+            }
+            None => {
+                let note = note_for(element, is_edge);
+                unmapped.push(UnmappedElement {
+                    element_id: element.id.clone(),
+                    element_type: element.element_type.as_str().to_string(),
+                    note_id: note.id.clone(),
+                });
+                diagram.add_element(note);
+            }
+        }
+    }
+
+    // Root children keep their order; replaced edges are swapped for their notes
+    for child_id in source.root.children.iter().flatten() {
+        let id = unmapped
+            .iter()
+            .find(|u| &u.element_id == child_id)
+            .map_or(child_id.as_str(), |u| u.note_id.as_str());
+        diagram.add_child_to_root(id);
+    }
+
+    unmapped.sort_by(|a, b| a.element_id.cmp(&b.element_id));
+
+    Ok(ConversionResult {
+        diagram,
+        mapped,
+        unmapped,
+    })
+}
+
+/// Build the note that stands in for an unmappable element.
+///
+/// Nodes keep their ID and position; edges become a free-standing note with a
+/// fresh ID because the connection itself cannot be represented.
+fn note_for(element: &ModelElement, is_edge: bool) -> ModelElement {
+    let original_type = element.element_type.as_str().to_string();
+    let mut note = element.clone();
+
+    if is_edge {
+        note.id = uuid::Uuid::new_v4().to_string();
+        note.source_id = None;
+        note.target_id = None;
+        note.route = None;
+        note.properties.insert(
+            "originalSourceId".to_string(),
+            serde_json::json!(element.source_id),
+        );
+        note.properties.insert(
+            "originalTargetId".to_string(),
+            serde_json::json!(element.target_id),
+        );
+    }
+
+    note.element_type = ElementType::from(NOTE_TYPE);
+    note.label = Some(match &element.label {
+        Some(label) => format!("{original_type}: {label}"),
+        None => format!("Unmapped {original_type}"),
+    });
+    note.properties
+        .insert("originalType".to_string(), serde_json::json!(original_type));
+    note.properties
+        .insert("originalId".to_string(), serde_json::json!(element.id));
+    note
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Edge, Node, Position};
+
+    #[test]
+    fn test_convert_workflow_to_uml_activity() {
+        let mut source = DiagramModel::new("workflow");
+        let start = Node::new("start-event", Position { x: 0.0, y: 0.0 }, None);
+        let task = Node::new("task", Position { x: 100.0, y: 0.0 }, Some("Work".into()));
+        let flow = Edge::new("flow", start.base.id.clone(), task.base.id.clone(), None);
+        let (start_id, task_id, flow_id) = (
+            start.base.id.clone(),
+            task.base.id.clone(),
+            flow.base.id.clone(),
+        );
+        for element in [start.base, task.base, flow.base] {
+            let id = element.id.clone();
+            source.add_element(element);
+            source.add_child_to_root(&id);
+        }
+
+        let result = convert_diagram(&source, "uml-activity").unwrap();
+
+        assert_eq!(result.mapped, 3);
+        assert!(result.unmapped.is_empty());
+        assert_eq!(result.diagram.diagram_type, "uml-activity");
+        assert_eq!(
+            result.diagram.elements[&start_id].element_type.as_str(),
+            "initial-node"
+        );
+        assert_eq!(
+            result.diagram.elements[&task_id].element_type.as_str(),
+            "action"
+        );
+        assert_eq!(
+            result.diagram.elements[&flow_id].element_type.as_str(),
+            "control-flow"
+        );
+    }
+
+    #[test]
+    fn test_unmappable_elements_become_notes() {
+        let mut source = DiagramModel::new("uml-activity");
+        let swimlane = Node::new("activity-partition", Position { x: 0.0, y: 0.0 }, None);
+        let swimlane_id = swimlane.base.id.clone();
+        source.add_element(swimlane.base);
+        source.add_child_to_root(&swimlane_id);
+
+        let result = convert_diagram(&source, "workflow").unwrap();
+
+        assert_eq!(result.unmapped.len(), 1);
+        assert_eq!(result.unmapped[0].element_type, "activity-partition");
+        let note = &result.diagram.elements[&result.unmapped[0].note_id];
+        assert_eq!(note.element_type.as_str(), NOTE_TYPE);
+    }
+
+    #[test]
+    fn test_unsupported_conversion_is_rejected() {
+        let source = DiagramModel::new("wasm-component");
+        assert!(convert_diagram(&source, "uml-class").is_err());
+    }
+}
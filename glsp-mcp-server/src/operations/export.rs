@@ -0,0 +1,134 @@
+//! Export formats and content negotiation
+//!
+//! `export_diagram` takes a `format` name, but an HTTP client can instead
+//! name the rendering it wants in an `Accept` header, such as
+//! `image/svg+xml` or `text/vnd.plantuml`. An explicit format always wins;
+//! an `Accept` header naming nothing we can produce falls back to JSON.
+
+use std::str::FromStr;
+
+/// A rendering `export_diagram` can produce
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiagramFormat {
+    #[default]
+    Json,
+    Svg,
+    Png,
+    PlantUml,
+    Mermaid,
+}
+
+impl DiagramFormat {
+    pub const ALL: &'static [DiagramFormat] = &[
+        DiagramFormat::Json,
+        DiagramFormat::Svg,
+        DiagramFormat::Png,
+        DiagramFormat::PlantUml,
+        DiagramFormat::Mermaid,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagramFormat::Json => "json",
+            DiagramFormat::Svg => "svg",
+            DiagramFormat::Png => "png",
+            DiagramFormat::PlantUml => "plantuml",
+            DiagramFormat::Mermaid => "mermaid",
+        }
+    }
+
+    pub fn media_type(&self) -> &'static str {
+        match self {
+            DiagramFormat::Json => "application/json",
+            DiagramFormat::Svg => "image/svg+xml",
+            DiagramFormat::Png => "image/png",
+            DiagramFormat::PlantUml => "text/vnd.plantuml",
+            DiagramFormat::Mermaid => "text/vnd.mermaid",
+        }
+    }
+
+    pub fn from_media_type(media_type: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|f| f.media_type().eq_ignore_ascii_case(media_type.trim()))
+    }
+
+    /// Pick the format an `Accept` header prefers.
+    ///
+    /// Media ranges are ranked by their `q` parameter (default 1), keeping
+    /// header order among equals; ranges with `q=0`, wildcards and unknown
+    /// types are skipped. Falls back to JSON.
+    pub fn negotiate(accept: &str) -> Self {
+        let mut ranges: Vec<(f32, &str)> = accept
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let media_type = parts.next()?.trim();
+                let quality = parts
+                    .filter_map(|p| p.trim().strip_prefix("q="))
+                    .find_map(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (quality > 0.0).then_some((quality, media_type))
+            })
+            .collect();
+        ranges.sort_by(|a, b| b.0.total_cmp(&a.0));
+        ranges
+            .into_iter()
+            .find_map(|(_, media_type)| Self::from_media_type(media_type))
+            .unwrap_or_default()
+    }
+
+    /// The explicit `format` if given, otherwise the `Accept` header's choice
+    pub fn resolve(format: Option<&str>, accept: Option<&str>) -> Result<Self, String> {
+        match (format, accept) {
+            (Some(format), _) => format.parse(),
+            (None, Some(accept)) => Ok(Self::negotiate(accept)),
+            (None, None) => Ok(Self::default()),
+        }
+    }
+}
+
+impl FromStr for DiagramFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|f| f.as_str() == s)
+            .ok_or_else(|| format!("Export format '{s}' not supported yet"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_accept_header() {
+        assert_eq!(
+            DiagramFormat::negotiate("image/svg+xml"),
+            DiagramFormat::Svg
+        );
+        assert_eq!(
+            DiagramFormat::negotiate("text/html, text/vnd.mermaid;q=0.5, image/png;q=0.9"),
+            DiagramFormat::Png
+        );
+        assert_eq!(
+            DiagramFormat::negotiate("image/png;q=0, text/vnd.plantuml"),
+            DiagramFormat::PlantUml
+        );
+        assert_eq!(
+            DiagramFormat::negotiate("text/html, */*"),
+            DiagramFormat::Json
+        );
+
+        // An explicit format wins over the header
+        assert_eq!(
+            DiagramFormat::resolve(Some("svg"), Some("image/png")),
+            Ok(DiagramFormat::Svg)
+        );
+        assert!(DiagramFormat::resolve(Some("dot"), None).is_err());
+    }
+}
@@ -0,0 +1,173 @@
+//! Force-directed layout
+//!
+//! A Fruchterman-Reingold layout seeded from a small deterministic PRNG.
+//! Nodes are processed in id order, so the same seed applied to the same
+//! graph (same node ids and edges) always produces the same positions.
+
+use crate::model::DiagramModel;
+use crate::operations::graph::{connections, is_edge};
+use std::collections::HashMap;
+
+const AREA_WIDTH: f64 = 800.0;
+const AREA_HEIGHT: f64 = 600.0;
+const MARGIN: f64 = 50.0;
+const ITERATIONS: usize = 200;
+
+/// SplitMix64, enough for reproducible initial placement
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `[0, 1)`
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Lay out every node with bounds using a force-directed simulation.
+///
+/// Identical seed and identical graph produce identical output. Returns the
+/// number of nodes moved.
+pub fn apply_force_layout(diagram: &mut DiagramModel, seed: u64) -> usize {
+    let root_id = diagram.root.id.clone();
+    let mut ids: Vec<String> = diagram
+        .elements
+        .values()
+        .filter(|e| e.id != root_id && !is_edge(e) && e.bounds.is_some())
+        .map(|e| e.id.clone())
+        .collect();
+    ids.sort();
+    if ids.is_empty() {
+        return 0;
+    }
+
+    let index: HashMap<&str, usize> = ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (id.as_str(), i))
+        .collect();
+    let mut links: Vec<(usize, usize)> = diagram
+        .elements
+        .values()
+        .flat_map(connections)
+        .filter_map(|(source, target)| {
+            let source = *index.get(source)?;
+            let target = *index.get(target)?;
+            (source != target).then_some((source, target))
+        })
+        .collect();
+    links.sort_unstable();
+
+    let mut rng = SplitMix64(seed);
+    let mut positions: Vec<(f64, f64)> = ids
+        .iter()
+        .map(|_| (rng.next_f64() * AREA_WIDTH, rng.next_f64() * AREA_HEIGHT))
+        .collect();
+
+    let n = positions.len();
+    let k = (AREA_WIDTH * AREA_HEIGHT / n as f64).sqrt();
+    let mut temperature = AREA_WIDTH / 10.0;
+    let cooling = temperature / ITERATIONS as f64;
+
+    for _ in 0..ITERATIONS {
+        let mut displacement = vec![(0.0, 0.0); n];
+
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let dx = positions[i].0 - positions[j].0;
+                let dy = positions[i].1 - positions[j].1;
+                let distance = (dx * dx + dy * dy).sqrt().max(0.01);
+                let force = k * k / distance;
+                let (fx, fy) = (dx / distance * force, dy / distance * force);
+                displacement[i].0 += fx;
+                displacement[i].1 += fy;
+                displacement[j].0 -= fx;
+                displacement[j].1 -= fy;
+            }
+        }
+
+        for &(source, target) in &links {
+            let dx = positions[source].0 - positions[target].0;
+            let dy = positions[source].1 - positions[target].1;
+            let distance = (dx * dx + dy * dy).sqrt().max(0.01);
+            let force = distance * distance / k;
+            let (fx, fy) = (dx / distance * force, dy / distance * force);
+            displacement[source].0 -= fx;
+            displacement[source].1 -= fy;
+            displacement[target].0 += fx;
+            displacement[target].1 += fy;
+        }
+
+        for (position, (dx, dy)) in positions.iter_mut().zip(&displacement) {
+            let length = (dx * dx + dy * dy).sqrt().max(0.01);
+            let step = length.min(temperature);
+            position.0 = (position.0 + dx / length * step).clamp(0.0, AREA_WIDTH);
+            position.1 = (position.1 + dy / length * step).clamp(0.0, AREA_HEIGHT);
+        }
+        temperature = (temperature - cooling).max(1.0);
+    }
+
+    for (id, (x, y)) in ids.iter().zip(positions) {
+        if let Some(bounds) = diagram.elements.get_mut(id).and_then(|e| e.bounds.as_mut()) {
+            bounds.x = (MARGIN + x).round();
+            bounds.y = (MARGIN + y).round();
+        }
+    }
+    diagram.revision += 1;
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Edge, Node, Position};
+
+    fn diagram() -> DiagramModel {
+        let mut diagram = DiagramModel::new("workflow");
+        let mut ids = Vec::new();
+        for i in 0..5 {
+            let node = Node::new("task", Position { x: 0.0, y: 0.0 }, None);
+            let mut base = node.base;
+            base.id = format!("node-{i}");
+            ids.push(base.id.clone());
+            diagram.add_element(base);
+        }
+        for pair in ids.windows(2) {
+            let edge = Edge::new("flow", pair[0].clone(), pair[1].clone(), None);
+            let mut base = edge.base;
+            base.id = format!("edge-{}", pair[0]);
+            diagram.add_element(base);
+        }
+        diagram
+    }
+
+    fn positions(diagram: &DiagramModel) -> Vec<(String, f64, f64)> {
+        let mut positions: Vec<_> = diagram
+            .elements
+            .values()
+            .filter_map(|e| e.bounds.as_ref().map(|b| (e.id.clone(), b.x, b.y)))
+            .collect();
+        positions.sort_by(|a, b| a.0.cmp(&b.0));
+        positions
+    }
+
+    #[test]
+    fn test_same_seed_same_layout() {
+        let mut first = diagram();
+        let mut second = diagram();
+        assert_eq!(apply_force_layout(&mut first, 42), 5);
+        apply_force_layout(&mut second, 42);
+        assert_eq!(positions(&first), positions(&second));
+
+        let mut other = diagram();
+        apply_force_layout(&mut other, 7);
+        assert_ne!(positions(&first), positions(&other));
+    }
+}
@@ -0,0 +1,1003 @@
+//! Graph queries over diagram elements
+//!
+//! Edges are recognised structurally: any element with both a source and a
+//! target is treated as an edge, regardless of its element type. This matches
+//! how `create_edge` stores custom edge types such as `flow` or `association`.
+//!
+//! Hyperedges join several sources to several targets, as in a fork or join.
+//! They count as edges, so node filters skip them, but carry their endpoints
+//! in `sources`/`targets` rather than `source_id`/`target_id`. Path, cycle and
+//! layering queries see a hyperedge as one connection from each of its
+//! sources to each of its targets.
+
+use crate::model::{generate_id, DiagramModel, Edge, ElementType, ModelElement};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, VecDeque};
+
+/// Edge property recording whether the edge has a direction
+pub const DIRECTED_PROPERTY: &str = "directed";
+
+/// Diagram metadata flag: a change that closes a cycle of directed edges is
+/// rejected
+pub const ACYCLIC_KEY: &str = "mustBeAcyclic";
+
+/// Edge types that have no direction unless the edge says otherwise
+const UNDIRECTED_EDGE_TYPES: &[&str] = &["association", "link"];
+
+/// Whether an element connects other elements: an edge or a hyperedge
+pub fn is_edge(element: &ModelElement) -> bool {
+    (element.source_id.is_some() && element.target_id.is_some()) || is_hyperedge(element)
+}
+
+/// Element type of edges with several sources and targets
+pub const HYPEREDGE_TYPE: &str = "hyperedge";
+
+/// Whether an element joins lists of sources and targets
+pub fn is_hyperedge(element: &ModelElement) -> bool {
+    element.sources.is_some() && element.targets.is_some()
+}
+
+/// The `(source, target)` pairs an edge or hyperedge connects
+pub fn connections(element: &ModelElement) -> Vec<(&str, &str)> {
+    if let (Some(source), Some(target)) =
+        (element.source_id.as_deref(), element.target_id.as_deref())
+    {
+        return vec![(source, target)];
+    }
+    let (Some(sources), Some(targets)) = (&element.sources, &element.targets) else {
+        return Vec::new();
+    };
+    sources
+        .iter()
+        .flat_map(|source| {
+            targets
+                .iter()
+                .map(move |target| (source.as_str(), target.as_str()))
+        })
+        .collect()
+}
+
+/// Every endpoint of an edge or hyperedge, labelled `"source"` or `"target"`
+pub fn endpoints(element: &ModelElement) -> Vec<(&'static str, &str)> {
+    let sources = element
+        .source_id
+        .iter()
+        .chain(element.sources.iter().flatten());
+    let targets = element
+        .target_id
+        .iter()
+        .chain(element.targets.iter().flatten());
+    sources
+        .map(|id| ("source", id.as_str()))
+        .chain(targets.map(|id| ("target", id.as_str())))
+        .collect()
+}
+
+/// Every pairwise connection in a diagram, with the edge or hyperedge it belongs to
+pub fn links(diagram: &DiagramModel) -> impl Iterator<Item = (&ModelElement, &str, &str)> {
+    diagram
+        .elements
+        .values()
+        .flat_map(|e| connections(e).into_iter().map(move |(s, t)| (e, s, t)))
+}
+
+/// Build an edge the way `create_edge` stores it: with the `sourceId` and
+/// `targetId` property mirrors the editor reads and an explicit `directed`
+/// flag. The caller adds it to the diagram and the root's children.
+pub fn edge_element(
+    edge_type: &str,
+    source_id: &str,
+    target_id: &str,
+    label: Option<String>,
+    directed: bool,
+) -> ModelElement {
+    let mut edge = Edge::new(
+        edge_type,
+        source_id.to_string(),
+        target_id.to_string(),
+        label,
+    )
+    .base;
+    edge.properties
+        .insert("sourceId".to_string(), json!(source_id));
+    edge.properties
+        .insert("targetId".to_string(), json!(target_id));
+    edge.properties
+        .insert(DIRECTED_PROPERTY.to_string(), json!(directed));
+    edge
+}
+
+/// Direction of edges of a type that do not set `directed` themselves
+pub fn default_directed(edge_type: &str) -> bool {
+    !UNDIRECTED_EDGE_TYPES.contains(&edge_type)
+}
+
+/// Whether an edge points from its source to its target.
+///
+/// The `directed` property wins; otherwise the edge type decides.
+pub fn is_directed(edge: &ModelElement) -> bool {
+    edge.properties
+        .get(DIRECTED_PROPERTY)
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or_else(|| default_directed(edge.element_type.as_str()))
+}
+
+/// Adjacency over directed edges only, with deterministic neighbour order
+fn directed_adjacency(diagram: &DiagramModel) -> BTreeMap<&str, Vec<&str>> {
+    adjacency_of(
+        links(diagram)
+            .filter(|(e, _, _)| is_directed(e))
+            .map(|(_, source, target)| (source, target)),
+    )
+}
+
+/// Adjacency over `(source, target)` pairs, with deterministic neighbour order
+fn adjacency_of<'a>(
+    pairs: impl Iterator<Item = (&'a str, &'a str)>,
+) -> BTreeMap<&'a str, Vec<&'a str>> {
+    let mut adjacency: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (source, target) in pairs {
+        adjacency.entry(source).or_default().push(target);
+        adjacency.entry(target).or_default();
+    }
+    for targets in adjacency.values_mut() {
+        targets.sort_unstable();
+        targets.dedup();
+    }
+    adjacency
+}
+
+/// Adjacency for traversal: directed edges one way, undirected edges both ways
+fn traversal_adjacency(diagram: &DiagramModel) -> BTreeMap<&str, Vec<&str>> {
+    let mut adjacency: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (edge, source, target) in links(diagram) {
+        adjacency.entry(source).or_default().push(target);
+        if !is_directed(edge) {
+            adjacency.entry(target).or_default().push(source);
+        }
+    }
+    for targets in adjacency.values_mut() {
+        targets.sort_unstable();
+        targets.dedup();
+    }
+    adjacency
+}
+
+/// Find a path from one node to another along edges.
+///
+/// Directed edges are only followed from source to target; undirected edges
+/// are followed either way. Returns the node IDs of a shortest path
+/// including both ends, or `None` if `to` cannot be reached. A node always
+/// reaches itself.
+pub fn find_path(diagram: &DiagramModel, from: &str, to: &str) -> Option<Vec<String>> {
+    if from == to {
+        return Some(vec![from.to_string()]);
+    }
+
+    let adjacency = traversal_adjacency(diagram);
+    let mut previous: HashMap<&str, &str> = HashMap::new();
+    let mut queue: VecDeque<&str> = VecDeque::from([from]);
+
+    while let Some(node) = queue.pop_front() {
+        for &neighbour in adjacency.get(node).into_iter().flatten() {
+            if neighbour == from || previous.contains_key(neighbour) {
+                continue;
+            }
+            previous.insert(neighbour, node);
+            if neighbour == to {
+                let mut path = vec![to.to_string()];
+                let mut current = to;
+                while let Some(&prev) = previous.get(current) {
+                    path.push(prev.to_string());
+                    current = prev;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            queue.push_back(neighbour);
+        }
+    }
+
+    None
+}
+
+/// A path found by [`shortest_path`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeightedPath {
+    /// Node IDs from start to end, including both
+    pub nodes: Vec<String>,
+    /// IDs of the edges taken, in order
+    pub edges: Vec<String>,
+    /// Sum of the edge costs; the edge count when unweighted
+    pub cost: f64,
+}
+
+/// Cost of traversing an edge: the numeric `property`, or 1 when the edge
+/// does not have it. Negative and non-numeric costs are rejected.
+pub fn edge_cost(edge: &ModelElement, property: &str) -> Result<f64, String> {
+    let Some(value) = edge.properties.get(property) else {
+        return Ok(1.0);
+    };
+    let cost = value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+        .ok_or_else(|| format!("Edge {} has a non-numeric {property}: {value}", edge.id))?;
+    if !cost.is_finite() || cost < 0.0 {
+        return Err(format!(
+            "Edge {} has an invalid {property}: {cost} (costs must be finite and non-negative)",
+            edge.id
+        ));
+    }
+    Ok(cost)
+}
+
+/// Queue entry for [`shortest_path`], ordered so the cheapest pops first
+struct Visit<'a> {
+    cost: f64,
+    node: &'a str,
+}
+
+impl PartialEq for Visit<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Visit<'_> {}
+
+impl PartialOrd for Visit<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Visit<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.node.cmp(self.node))
+    }
+}
+
+/// Find the cheapest path from one node to another.
+///
+/// Edges are followed as in [`find_path`]. Without a `weight_property` every
+/// edge costs 1, giving the path with the fewest edges; with one, edges cost
+/// the value of that property (see [`edge_cost`]) and Dijkstra's algorithm
+/// picks the cheapest path. Ties are broken by node and edge ID so results
+/// are stable. Returns `Ok(None)` when `to` cannot be reached.
+pub fn shortest_path(
+    diagram: &DiagramModel,
+    from: &str,
+    to: &str,
+    weight_property: Option<&str>,
+) -> Result<Option<WeightedPath>, String> {
+    let mut adjacency: BTreeMap<&str, Vec<(&str, &str, f64)>> = BTreeMap::new();
+    for (edge, source, target) in links(diagram) {
+        let cost = match weight_property {
+            Some(property) => edge_cost(edge, property)?,
+            None => 1.0,
+        };
+        adjacency
+            .entry(source)
+            .or_default()
+            .push((target, edge.id.as_str(), cost));
+        if !is_directed(edge) {
+            adjacency
+                .entry(target)
+                .or_default()
+                .push((source, edge.id.as_str(), cost));
+        }
+    }
+    for steps in adjacency.values_mut() {
+        steps.sort_by(|a, b| a.0.cmp(b.0).then_with(|| a.1.cmp(b.1)));
+    }
+
+    let mut best: HashMap<&str, f64> = HashMap::from([(from, 0.0)]);
+    let mut previous: HashMap<&str, (&str, &str)> = HashMap::new();
+    let mut queue = BinaryHeap::from([Visit {
+        cost: 0.0,
+        node: from,
+    }]);
+
+    while let Some(Visit { cost, node }) = queue.pop() {
+        if node == to {
+            let mut nodes = vec![to.to_string()];
+            let mut edges = Vec::new();
+            let mut current = to;
+            while let Some(&(prev, edge_id)) = previous.get(current) {
+                nodes.push(prev.to_string());
+                edges.push(edge_id.to_string());
+                current = prev;
+            }
+            nodes.reverse();
+            edges.reverse();
+            return Ok(Some(WeightedPath { nodes, edges, cost }));
+        }
+        if best.get(node).is_some_and(|&known| cost > known) {
+            continue;
+        }
+
+        for &(neighbour, edge_id, step) in adjacency.get(node).into_iter().flatten() {
+            let next = cost + step;
+            if !best.get(neighbour).is_some_and(|&known| known <= next) {
+                best.insert(neighbour, next);
+                previous.insert(neighbour, (node, edge_id));
+                queue.push(Visit {
+                    cost: next,
+                    node: neighbour,
+                });
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Find cycles formed by directed edges.
+///
+/// Undirected edges are ignored, so an association between two nodes never
+/// counts as a cycle. Each back edge found by a depth-first search yields one
+/// cycle, reported as the node IDs along it.
+pub fn find_cycles(diagram: &DiagramModel) -> Vec<Vec<String>> {
+    cycles_in(&directed_adjacency(diagram))
+}
+
+/// The cycle a change would close in a diagram that must stay acyclic.
+///
+/// The change adds the directed `(source, target)` connections in `added`
+/// and drops the edges whose IDs are in `removed`, as reconnecting or
+/// reversing an edge does. Only diagrams that are acyclic now are guarded,
+/// so a diagram marked while it had cycles can still be edited to remove
+/// them. Returns `None` when the change is allowed.
+pub fn cycle_closed_by<'a>(
+    diagram: &'a DiagramModel,
+    added: &[(&'a str, &'a str)],
+    removed: &[&str],
+) -> Option<Vec<String>> {
+    if added.is_empty() || !must_be_acyclic(diagram) || !find_cycles(diagram).is_empty() {
+        return None;
+    }
+    let kept = links(diagram)
+        .filter(|(e, _, _)| is_directed(e) && !removed.contains(&e.id.as_str()))
+        .map(|(_, source, target)| (source, target));
+    let adjacency = adjacency_of(kept.chain(added.iter().copied()));
+    cycles_in(&adjacency).into_iter().next()
+}
+
+/// Apply a change that may rewire many edges at once, such as a merge.
+///
+/// In a diagram guarded as by [`cycle_closed_by`] the change is made on a
+/// copy, which replaces the diagram only if it stayed acyclic; otherwise the
+/// diagram is left untouched and the cycle is returned.
+pub fn guarded_change<T>(
+    diagram: &mut DiagramModel,
+    change: impl FnOnce(&mut DiagramModel) -> T,
+) -> Result<T, Vec<String>> {
+    if !must_be_acyclic(diagram) || !find_cycles(diagram).is_empty() {
+        return Ok(change(diagram));
+    }
+    let mut candidate = diagram.clone();
+    let outcome = change(&mut candidate);
+    if let Some(cycle) = find_cycles(&candidate).into_iter().next() {
+        return Err(cycle);
+    }
+    *diagram = candidate;
+    Ok(outcome)
+}
+
+/// Cycles found by a depth-first search over `adjacency`, one per back edge
+fn cycles_in(adjacency: &BTreeMap<&str, Vec<&str>>) -> Vec<Vec<String>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        Unvisited,
+        OnStack,
+        Done,
+    }
+
+    let mut marks: HashMap<&str, Mark> =
+        adjacency.keys().map(|id| (*id, Mark::Unvisited)).collect();
+    let mut cycles = Vec::new();
+
+    for &start in adjacency.keys() {
+        if marks[start] != Mark::Unvisited {
+            continue;
+        }
+        // Iterative DFS: (node, index of the next neighbour to visit)
+        let mut stack: Vec<(&str, usize)> = vec![(start, 0)];
+        marks.insert(start, Mark::OnStack);
+
+        while let Some((node, next)) = stack.last_mut() {
+            let node = *node;
+            match adjacency[node].get(*next) {
+                Some(&neighbour) => {
+                    *next += 1;
+                    match marks[neighbour] {
+                        Mark::Unvisited => {
+                            marks.insert(neighbour, Mark::OnStack);
+                            stack.push((neighbour, 0));
+                        }
+                        Mark::OnStack => {
+                            let from = stack.iter().position(|(n, _)| *n == neighbour).unwrap_or(0);
+                            cycles.push(stack[from..].iter().map(|(n, _)| n.to_string()).collect());
+                        }
+                        Mark::Done => {}
+                    }
+                }
+                None => {
+                    marks.insert(node, Mark::Done);
+                    stack.pop();
+                }
+            }
+        }
+    }
+
+    cycles
+}
+
+/// Whether the diagram is marked as one that must stay acyclic
+pub fn must_be_acyclic(diagram: &DiagramModel) -> bool {
+    diagram
+        .metadata
+        .get(ACYCLIC_KEY)
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// Layer index of every node reached by a directed edge.
+///
+/// Sources are layer 0 and every node sits one layer below its deepest
+/// predecessor. Nodes on cycles are placed one layer below the deepest
+/// acyclic node.
+pub fn directed_layers(diagram: &DiagramModel) -> HashMap<String, usize> {
+    let adjacency = directed_adjacency(diagram);
+    let mut in_degree: HashMap<&str, usize> = adjacency.keys().map(|id| (*id, 0)).collect();
+    for targets in adjacency.values() {
+        for &target in targets {
+            *in_degree.entry(target).or_default() += 1;
+        }
+    }
+
+    let mut layers: HashMap<String, usize> = HashMap::new();
+    let mut queue: VecDeque<&str> = adjacency
+        .keys()
+        .copied()
+        .filter(|id| in_degree[id] == 0)
+        .collect();
+    while let Some(node) = queue.pop_front() {
+        let layer = *layers.entry(node.to_string()).or_insert(0);
+        for &target in &adjacency[node] {
+            let entry = layers.entry(target.to_string()).or_insert(0);
+            *entry = (*entry).max(layer + 1);
+            let degree = in_degree.get_mut(target).expect("target is in adjacency");
+            *degree -= 1;
+            if *degree == 0 {
+                queue.push_back(target);
+            }
+        }
+    }
+
+    let cyclic_layer = layers.values().max().map_or(0, |max| max + 1);
+    for (node, degree) in in_degree {
+        if degree > 0 {
+            layers.insert(node.to_string(), cyclic_layer);
+        }
+    }
+    layers
+}
+
+/// Move one or both endpoints of an edge, keeping its label, route and properties.
+///
+/// New endpoints must be existing nodes: neither the root nor another edge.
+/// The `sourceId`/`targetId` property mirrors written by `create_edge` are
+/// kept in sync. Returns the updated edge.
+pub fn reconnect_edge<'a>(
+    diagram: &'a mut DiagramModel,
+    edge_id: &str,
+    source_id: Option<&str>,
+    target_id: Option<&str>,
+) -> Result<&'a ModelElement, String> {
+    if source_id.is_none() && target_id.is_none() {
+        return Err("Nothing to reconnect: give sourceId and/or targetId".to_string());
+    }
+    match diagram.elements.get(edge_id) {
+        Some(edge) if is_hyperedge(edge) => {
+            return Err(format!(
+                "Hyperedge {edge_id} has several endpoints and cannot be reconnected"
+            ))
+        }
+        Some(edge) if is_edge(edge) => {}
+        Some(_) => return Err(format!("Element {edge_id} is not an edge")),
+        None => return Err(format!("Edge {edge_id} not found")),
+    }
+    for (role, id) in [("Source", source_id), ("Target", target_id)] {
+        let Some(id) = id else { continue };
+        match diagram.elements.get(id) {
+            None => return Err(format!("{role} element {id} not found")),
+            Some(element) if id == diagram.root.id || is_edge(element) => {
+                return Err(format!("{role} element {id} is not a node"))
+            }
+            Some(_) => {}
+        }
+    }
+
+    let edge = diagram
+        .elements
+        .get_mut(edge_id)
+        .expect("edge existence checked above");
+    for (field, property, id) in [
+        (&mut edge.source_id, "sourceId", source_id),
+        (&mut edge.target_id, "targetId", target_id),
+    ] {
+        let Some(id) = id else { continue };
+        *field = Some(id.to_string());
+        if edge.properties.contains_key(property) {
+            edge.properties.insert(
+                property.to_string(),
+                serde_json::Value::String(id.to_string()),
+            );
+        }
+    }
+    edge.touch();
+    diagram.revision += 1;
+    diagram.updated_at = chrono::Utc::now();
+    Ok(&diagram.elements[edge_id])
+}
+
+/// Swap an edge's source and target.
+///
+/// Endpoint-specific properties come in `source*`/`target*` pairs, such as
+/// the `sourceId`/`targetId` mirrors or `sourceInterface`/`targetInterface`
+/// port anchors; each pair is swapped along with the endpoints. The route is
+/// reversed so its waypoints still run from the new source to the new target.
+/// A hyperedge swaps its source and target lists.
+pub fn reverse_edge<'a>(
+    diagram: &'a mut DiagramModel,
+    edge_id: &str,
+) -> Result<&'a ModelElement, String> {
+    let edge = match diagram.elements.get_mut(edge_id) {
+        Some(edge) if is_edge(edge) => edge,
+        Some(_) => return Err(format!("Element {edge_id} is not an edge")),
+        None => return Err(format!("Edge {edge_id} not found")),
+    };
+
+    std::mem::swap(&mut edge.source_id, &mut edge.target_id);
+    std::mem::swap(&mut edge.sources, &mut edge.targets);
+    let suffixes: BTreeSet<String> = edge
+        .properties
+        .keys()
+        .filter_map(|key| {
+            key.strip_prefix("source")
+                .or_else(|| key.strip_prefix("target"))
+        })
+        .filter(|suffix| suffix.starts_with(|c: char| c.is_ascii_uppercase()))
+        .map(str::to_string)
+        .collect();
+    for suffix in suffixes {
+        let source_key = format!("source{suffix}");
+        let target_key = format!("target{suffix}");
+        let source = edge.properties.remove(&source_key);
+        let target = edge.properties.remove(&target_key);
+        if let Some(value) = target {
+            edge.properties.insert(source_key, value);
+        }
+        if let Some(value) = source {
+            edge.properties.insert(target_key, value);
+        }
+    }
+    if let Some(route) = edge.route.as_mut() {
+        route.reverse();
+    }
+    edge.touch();
+    diagram.revision += 1;
+    diagram.updated_at = chrono::Utc::now();
+    Ok(&diagram.elements[edge_id])
+}
+
+/// Add a hyperedge joining every one of `sources` to every one of `targets`.
+///
+/// All endpoints must be existing nodes. Returns the new hyperedge's ID.
+pub fn create_hyperedge(
+    diagram: &mut DiagramModel,
+    sources: &[String],
+    targets: &[String],
+    label: Option<String>,
+) -> Result<String, String> {
+    if sources.is_empty() || targets.is_empty() {
+        return Err("A hyperedge needs at least one source and one target".to_string());
+    }
+    for (role, id) in sources
+        .iter()
+        .map(|id| ("Source", id))
+        .chain(targets.iter().map(|id| ("Target", id)))
+    {
+        match diagram.elements.get(id) {
+            None => return Err(format!("{role} element {id} not found")),
+            Some(element) if *id == diagram.root.id || is_edge(element) => {
+                return Err(format!("{role} element {id} is not a node"))
+            }
+            Some(_) => {}
+        }
+    }
+
+    let now = chrono::Utc::now();
+    let element = ModelElement {
+        id: generate_id(),
+        element_type: ElementType::from(HYPEREDGE_TYPE),
+        children: None,
+        bounds: None,
+        layout_options: None,
+        properties: HashMap::new(),
+        label,
+        source_id: None,
+        target_id: None,
+        sources: Some(sources.to_vec()),
+        targets: Some(targets.to_vec()),
+        route: None,
+        visible: true,
+        z_index: None,
+        style: HashMap::new(),
+        created_at: Some(now),
+        updated_at: Some(now),
+        created_by: None,
+    };
+    let id = element.id.clone();
+    diagram.add_element(element);
+    Ok(id)
+}
+
+/// Iterate over all edges in a diagram
+pub fn edges(diagram: &DiagramModel) -> impl Iterator<Item = &ModelElement> {
+    diagram.elements.values().filter(|e| is_edge(e))
+}
+
+/// An edge as seen from one of its endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeRef {
+    pub edge_id: String,
+    pub edge_type: String,
+    pub label: Option<String>,
+    /// The endpoint on the other side of the edge
+    pub other_id: String,
+}
+
+/// Edges touching a node, split by direction
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeEdges {
+    pub incoming: Vec<EdgeRef>,
+    pub outgoing: Vec<EdgeRef>,
+}
+
+/// Collect incoming and outgoing edges for a node.
+///
+/// A self-loop is reported in both lists.
+pub fn edges_for_node(diagram: &DiagramModel, node_id: &str) -> NodeEdges {
+    let mut result = NodeEdges::default();
+
+    for (edge, source, target) in links(diagram) {
+        let edge_ref = |other_id: &str| EdgeRef {
+            edge_id: edge.id.clone(),
+            edge_type: edge.element_type.as_str().to_string(),
+            label: edge.label.clone(),
+            other_id: other_id.to_string(),
+        };
+
+        if target == node_id {
+            result.incoming.push(edge_ref(source));
+        }
+        if source == node_id {
+            result.outgoing.push(edge_ref(target));
+        }
+    }
+
+    result.incoming.sort_by(|a, b| a.edge_id.cmp(&b.edge_id));
+    result.outgoing.sort_by(|a, b| a.edge_id.cmp(&b.edge_id));
+    result
+}
+
+/// IDs of the edges and hyperedges with an endpoint at a node, sorted.
+///
+/// These are the edges left dangling when the node is deleted.
+pub fn incident_edges(diagram: &DiagramModel, node_id: &str) -> Vec<String> {
+    links(diagram)
+        .filter(|(_, source, target)| *source == node_id || *target == node_id)
+        .map(|(edge, _, _)| edge.id.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Edge, Node, Position};
+
+    fn node(diagram: &mut DiagramModel) -> String {
+        let node = Node::new("task", Position { x: 0.0, y: 0.0 }, None);
+        let id = node.base.id.clone();
+        diagram.add_element(node.base);
+        id
+    }
+
+    fn edge(diagram: &mut DiagramModel, source: &str, target: &str) -> String {
+        typed_edge(diagram, "flow", source, target)
+    }
+
+    fn typed_edge(
+        diagram: &mut DiagramModel,
+        edge_type: &str,
+        source: &str,
+        target: &str,
+    ) -> String {
+        let edge = Edge::new(edge_type, source.to_string(), target.to_string(), None);
+        let id = edge.base.id.clone();
+        diagram.add_element(edge.base);
+        id
+    }
+
+    #[test]
+    fn test_edges_for_node() {
+        let mut diagram = DiagramModel::new("workflow");
+        let a = node(&mut diagram);
+        let b = node(&mut diagram);
+        let c = node(&mut diagram);
+        let ab = edge(&mut diagram, &a, &b);
+        let bc = edge(&mut diagram, &b, &c);
+        edge(&mut diagram, &a, &c);
+
+        let result = edges_for_node(&diagram, &b);
+        assert_eq!(result.incoming.len(), 1);
+        assert_eq!(result.incoming[0].edge_id, ab);
+        assert_eq!(result.incoming[0].other_id, a);
+        assert_eq!(result.outgoing.len(), 1);
+        assert_eq!(result.outgoing[0].edge_id, bc);
+        assert_eq!(result.outgoing[0].edge_type, "flow");
+    }
+
+    #[test]
+    fn test_self_loop_is_incoming_and_outgoing() {
+        let mut diagram = DiagramModel::new("workflow");
+        let a = node(&mut diagram);
+        edge(&mut diagram, &a, &a);
+
+        let result = edges_for_node(&diagram, &a);
+        assert_eq!(result.incoming.len(), 1);
+        assert_eq!(result.outgoing.len(), 1);
+    }
+
+    #[test]
+    fn test_incident_edges() {
+        let mut diagram = DiagramModel::new("workflow");
+        let a = node(&mut diagram);
+        let b = node(&mut diagram);
+        let c = node(&mut diagram);
+        let ab = edge(&mut diagram, &a, &b);
+        let bc = edge(&mut diagram, &b, &c);
+        let bb = edge(&mut diagram, &b, &b);
+        edge(&mut diagram, &a, &c);
+        let d = node(&mut diagram);
+
+        let mut expected = vec![ab, bc, bb];
+        expected.sort();
+        assert_eq!(incident_edges(&diagram, &b), expected);
+        assert!(incident_edges(&diagram, &d).is_empty());
+    }
+
+    #[test]
+    fn test_undirected_edges_do_not_form_cycles() {
+        let mut diagram = DiagramModel::new("uml-class");
+        let a = node(&mut diagram);
+        let b = node(&mut diagram);
+        let c = node(&mut diagram);
+        typed_edge(&mut diagram, "association", &a, &b);
+        typed_edge(&mut diagram, "association", &b, &a);
+        assert!(find_cycles(&diagram).is_empty());
+
+        edge(&mut diagram, &b, &c);
+        edge(&mut diagram, &c, &b);
+        let cycles = find_cycles(&diagram);
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].len(), 2);
+
+        let layers = directed_layers(&diagram);
+        assert!(!layers.contains_key(&a));
+        assert_eq!(layers[&b], 0);
+    }
+
+    #[test]
+    fn test_cycle_closed_by_guards_acyclic_diagrams() {
+        let mut diagram = DiagramModel::new("workflow");
+        let a = node(&mut diagram);
+        let b = node(&mut diagram);
+        let c = node(&mut diagram);
+        let ab = edge(&mut diagram, &a, &b);
+        edge(&mut diagram, &b, &c);
+
+        // Unmarked diagrams accept any edge
+        assert!(cycle_closed_by(&diagram, &[(c.as_str(), a.as_str())], &[]).is_none());
+
+        diagram
+            .metadata
+            .insert(ACYCLIC_KEY.to_string(), json!(true));
+        let cycle = cycle_closed_by(&diagram, &[(c.as_str(), a.as_str())], &[]).unwrap();
+        assert_eq!(cycle.len(), 3);
+        // Without the edge it replaces, the new connection closes nothing
+        assert!(cycle_closed_by(&diagram, &[(c.as_str(), a.as_str())], &[ab.as_str()]).is_none());
+
+        // A rejected change leaves the diagram as it was
+        let count = diagram.elements.len();
+        let rejected = guarded_change(&mut diagram, |d| edge(d, &c, &a));
+        assert_eq!(rejected.unwrap_err().len(), 3);
+        assert_eq!(diagram.elements.len(), count);
+        assert!(guarded_change(&mut diagram, |d| edge(d, &a, &c)).is_ok());
+        assert_eq!(diagram.elements.len(), count + 1);
+    }
+
+    #[test]
+    fn test_find_path_respects_direction() {
+        let mut diagram = DiagramModel::new("workflow");
+        let a = node(&mut diagram);
+        let b = node(&mut diagram);
+        let c = node(&mut diagram);
+        edge(&mut diagram, &a, &b);
+        typed_edge(&mut diagram, "association", &c, &b);
+
+        assert_eq!(
+            find_path(&diagram, &a, &c),
+            Some(vec![a.clone(), b.clone(), c.clone()])
+        );
+        assert_eq!(find_path(&diagram, &b, &a), None);
+        assert_eq!(find_path(&diagram, &a, &a), Some(vec![a.clone()]));
+    }
+
+    #[test]
+    fn test_shortest_path_by_count_and_cost() {
+        let mut diagram = DiagramModel::new("workflow");
+        let a = node(&mut diagram);
+        let b = node(&mut diagram);
+        let c = node(&mut diagram);
+        let direct = edge(&mut diagram, &a, &c);
+        let ab = edge(&mut diagram, &a, &b);
+        let bc = edge(&mut diagram, &b, &c);
+        let costs = [(&direct, 10.0), (&ab, 2.0), (&bc, 3.0)];
+        for (id, cost) in costs {
+            diagram
+                .elements
+                .get_mut(id)
+                .unwrap()
+                .properties
+                .insert("cost".to_string(), serde_json::json!(cost));
+        }
+
+        let hops = shortest_path(&diagram, &a, &c, None).unwrap().unwrap();
+        assert_eq!(hops.edges, vec![direct.clone()]);
+        assert_eq!(hops.cost, 1.0);
+
+        let cheapest = shortest_path(&diagram, &a, &c, Some("cost"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(cheapest.nodes, vec![a.clone(), b.clone(), c.clone()]);
+        assert_eq!(cheapest.edges, vec![ab, bc]);
+        assert_eq!(cheapest.cost, 5.0);
+
+        assert_eq!(shortest_path(&diagram, &c, &a, None).unwrap(), None);
+
+        diagram
+            .elements
+            .get_mut(&direct)
+            .unwrap()
+            .properties
+            .insert("cost".to_string(), serde_json::json!(-1));
+        assert!(shortest_path(&diagram, &a, &c, Some("cost")).is_err());
+    }
+
+    #[test]
+    fn test_reconnect_edge_keeps_label() {
+        let mut diagram = DiagramModel::new("workflow");
+        let a = node(&mut diagram);
+        let b = node(&mut diagram);
+        let c = node(&mut diagram);
+        let ab = edge(&mut diagram, &a, &b);
+        diagram.elements.get_mut(&ab).unwrap().label = Some("data".to_string());
+
+        let updated = reconnect_edge(&mut diagram, &ab, None, Some(&c)).unwrap();
+        assert_eq!(updated.source_id.as_deref(), Some(a.as_str()));
+        assert_eq!(updated.target_id.as_deref(), Some(c.as_str()));
+        assert_eq!(updated.label.as_deref(), Some("data"));
+
+        let root = diagram.root.id.clone();
+        assert!(reconnect_edge(&mut diagram, &ab, Some(&root), None).is_err());
+        assert!(reconnect_edge(&mut diagram, &ab, Some(&ab), None).is_err());
+        assert!(reconnect_edge(&mut diagram, &ab, Some("missing"), None).is_err());
+    }
+
+    #[test]
+    fn test_reverse_edge_swaps_anchors_and_route() {
+        let mut diagram = DiagramModel::new("workflow");
+        let a = node(&mut diagram);
+        let b = node(&mut diagram);
+        let ab = edge(&mut diagram, &a, &b);
+        {
+            let element = diagram.elements.get_mut(&ab).unwrap();
+            element
+                .properties
+                .insert("sourceInterface".to_string(), serde_json::json!("out"));
+            element.route = Some(vec![
+                Position { x: 1.0, y: 1.0 },
+                Position { x: 2.0, y: 2.0 },
+            ]);
+        }
+
+        let reversed = reverse_edge(&mut diagram, &ab).unwrap();
+        assert_eq!(reversed.source_id.as_deref(), Some(b.as_str()));
+        assert_eq!(reversed.target_id.as_deref(), Some(a.as_str()));
+        assert!(!reversed.properties.contains_key("sourceInterface"));
+        assert_eq!(reversed.properties["targetInterface"], "out");
+        assert_eq!(reversed.route.as_ref().unwrap()[0].x, 2.0);
+
+        assert!(reverse_edge(&mut diagram, &a).is_err());
+        assert!(reverse_edge(&mut diagram, "missing").is_err());
+    }
+
+    #[test]
+    fn test_hyperedge_expands_to_pairwise_connections() {
+        let mut diagram = DiagramModel::new("workflow");
+        let a = node(&mut diagram);
+        let b = node(&mut diagram);
+        let c = node(&mut diagram);
+        let d = node(&mut diagram);
+        let fork = create_hyperedge(
+            &mut diagram,
+            std::slice::from_ref(&a),
+            &[b.clone(), c.clone()],
+            None,
+        )
+        .unwrap();
+        create_hyperedge(
+            &mut diagram,
+            &[b.clone(), c.clone()],
+            std::slice::from_ref(&d),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(connections(&diagram.elements[&fork]).len(), 2);
+        let layers = directed_layers(&diagram);
+        assert_eq!((layers[&a], layers[&c], layers[&d]), (0, 1, 2));
+        assert_eq!(edges_for_node(&diagram, &d).incoming.len(), 2);
+        assert!(find_cycles(&diagram).is_empty());
+
+        create_hyperedge(
+            &mut diagram,
+            std::slice::from_ref(&d),
+            std::slice::from_ref(&a),
+            None,
+        )
+        .unwrap();
+        assert!(!find_cycles(&diagram).is_empty());
+
+        assert!(create_hyperedge(&mut diagram, std::slice::from_ref(&a), &[], None).is_err());
+        assert!(create_hyperedge(&mut diagram, &[a], &[fork], None).is_err());
+        assert!(create_hyperedge(&mut diagram, &[b], &["missing".to_string()], None).is_err());
+    }
+
+    #[test]
+    fn test_directed_layers() {
+        let mut diagram = DiagramModel::new("workflow");
+        let a = node(&mut diagram);
+        let b = node(&mut diagram);
+        let c = node(&mut diagram);
+        edge(&mut diagram, &a, &b);
+        edge(&mut diagram, &b, &c);
+        edge(&mut diagram, &a, &c);
+
+        let layers = directed_layers(&diagram);
+        assert_eq!((layers[&a], layers[&b], layers[&c]), (0, 1, 2));
+    }
+}
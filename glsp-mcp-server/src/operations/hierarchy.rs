@@ -0,0 +1,484 @@
+//! Hierarchical tasks
+//!
+//! A composite task contains subtasks. The parent lists its subtasks in its
+//! `children`, as containers do, and each subtask names its parent in the
+//! [`PARENT_TASK_PROPERTY`] property, so either side can be read without a
+//! search. A nested subtask is no longer a child of the root. Collapsing a
+//! parent ([`COLLAPSED_PROPERTY`]) hides every subtask below it; expanding it
+//! shows them again, except those below a subtask that is still collapsed.
+//!
+//! The hierarchy is containment, not flow: child lists are never edges for
+//! [`find_cycles`](super::graph::find_cycles). Containment must stay a tree,
+//! so neither a task nor a node moved with [`add_to_container`] can become its
+//! own ancestor; such a move fails with [`HierarchyError::ContainmentCycle`].
+//! [`containment_cycles`] finds cycles that got into a diagram some other way,
+//! e.g. through an import.
+
+use crate::model::{DiagramModel, ModelElement};
+use crate::operations::graph::is_edge;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+/// Property of a subtask naming its parent task
+pub const PARENT_TASK_PROPERTY: &str = "parentTask";
+
+/// Property of a parent task whose subtasks are hidden
+pub const COLLAPSED_PROPERTY: &str = "collapsed";
+
+/// Parent task named by a subtask
+pub fn parent_task(element: &ModelElement) -> Option<&str> {
+    element
+        .properties
+        .get(PARENT_TASK_PROPERTY)
+        .and_then(Value::as_str)
+}
+
+/// Whether a parent task hides its subtasks
+pub fn is_collapsed(element: &ModelElement) -> bool {
+    element
+        .properties
+        .get(COLLAPSED_PROPERTY)
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// Why a change to the hierarchy was refused
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HierarchyError {
+    /// The change does not apply, e.g. an element is missing
+    Invalid(String),
+    /// The change would make an element contain itself. Lists the cycle of
+    /// element IDs, each containing the next and the last the first.
+    ContainmentCycle(Vec<String>),
+}
+
+impl std::fmt::Display for HierarchyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HierarchyError::Invalid(message) => f.write_str(message),
+            HierarchyError::ContainmentCycle(cycle) => write!(
+                f,
+                "Containment cycle: {} -> {}",
+                cycle.join(" -> "),
+                cycle[0]
+            ),
+        }
+    }
+}
+
+impl From<String> for HierarchyError {
+    fn from(message: String) -> Self {
+        HierarchyError::Invalid(message)
+    }
+}
+
+fn children_of<'a>(diagram: &'a DiagramModel, id: &str) -> &'a [String] {
+    diagram
+        .elements
+        .get(id)
+        .and_then(|e| e.children.as_deref())
+        .unwrap_or_default()
+}
+
+/// The cycle that putting `child_id` into `container_id` would close, if any:
+/// the container followed by the path from the child down to it
+fn cycle_if_contained(
+    diagram: &DiagramModel,
+    container_id: &str,
+    child_id: &str,
+) -> Option<Vec<String>> {
+    if container_id == child_id {
+        return Some(vec![child_id.to_string()]);
+    }
+    // Breadth-first from the child, remembering how each element was reached
+    let mut reached_from: HashMap<&str, &str> = HashMap::from([(child_id, child_id)]);
+    let mut pending = VecDeque::from([child_id]);
+    while let Some(id) = pending.pop_front() {
+        for child in children_of(diagram, id) {
+            if reached_from.contains_key(child.as_str()) {
+                continue;
+            }
+            reached_from.insert(child, id);
+            if child == container_id {
+                let mut path = vec![container_id.to_string()];
+                let mut step = id;
+                while step != child_id {
+                    path.push(step.to_string());
+                    step = reached_from[step];
+                }
+                path.push(child_id.to_string());
+                path[1..].reverse();
+                return Some(path);
+            }
+            pending.push_back(child);
+        }
+    }
+    None
+}
+
+/// Every containment cycle in the diagram, each starting at its smallest
+/// element ID; child references to missing elements are ignored
+pub fn containment_cycles(diagram: &DiagramModel) -> Vec<Vec<String>> {
+    // false while an element is on the current path, true once finished
+    let mut finished: HashMap<&str, bool> = HashMap::new();
+    let mut cycles = BTreeSet::new();
+    let mut ids: Vec<&str> = diagram.elements.keys().map(String::as_str).collect();
+    ids.sort_unstable();
+
+    for start in ids {
+        if finished.contains_key(start) {
+            continue;
+        }
+        finished.insert(start, false);
+        let mut path = vec![start];
+        let mut remaining = vec![children_of(diagram, start).iter()];
+        while let Some(children) = remaining.last_mut() {
+            let Some(child) = children.next() else {
+                remaining.pop();
+                if let Some(done) = path.pop() {
+                    finished.insert(done, true);
+                }
+                continue;
+            };
+            let child = child.as_str();
+            if !diagram.elements.contains_key(child) {
+                continue;
+            }
+            match finished.get(child) {
+                None => {
+                    finished.insert(child, false);
+                    path.push(child);
+                    remaining.push(children_of(diagram, child).iter());
+                }
+                Some(false) => {
+                    let from = path.iter().position(|id| *id == child).unwrap_or(0);
+                    let mut cycle: Vec<String> =
+                        path[from..].iter().map(|id| id.to_string()).collect();
+                    let smallest = (0..cycle.len()).min_by_key(|&i| &cycle[i]).unwrap_or(0);
+                    cycle.rotate_left(smallest);
+                    cycles.insert(cycle);
+                }
+                Some(true) => {}
+            }
+        }
+    }
+    cycles.into_iter().collect()
+}
+
+/// Subtasks below `task_id` at any depth, parents before their subtasks
+pub fn descendants(diagram: &DiagramModel, task_id: &str) -> Vec<String> {
+    let mut seen = HashSet::from([task_id.to_string()]);
+    let mut found = Vec::new();
+    let mut pending = vec![task_id.to_string()];
+    while let Some(id) = pending.pop() {
+        let children = diagram
+            .elements
+            .get(&id)
+            .and_then(|e| e.children.as_deref())
+            .unwrap_or_default();
+        for child in children.iter().rev() {
+            if seen.insert(child.clone()) {
+                found.push(child.clone());
+                pending.push(child.clone());
+            }
+        }
+    }
+    found
+}
+
+/// Make `child_id` a subtask of `parent_id` and return its previous parent.
+///
+/// Fails if either task is missing or an edge, or if the parent is the task
+/// itself or one of its subtasks.
+pub fn add_subtask(
+    diagram: &mut DiagramModel,
+    parent_id: &str,
+    child_id: &str,
+) -> Result<Option<String>, HierarchyError> {
+    check_containable(diagram, parent_id, child_id, "Task")?;
+
+    let previous = detach(diagram, child_id);
+    let parent = diagram.elements.get_mut(parent_id).expect("checked above");
+    parent
+        .children
+        .get_or_insert_with(Vec::new)
+        .push(child_id.to_string());
+    parent.touch();
+    let hidden = is_collapsed(parent) || !parent.visible;
+
+    let child = diagram.elements.get_mut(child_id).expect("checked above");
+    child.properties.insert(
+        PARENT_TASK_PROPERTY.to_string(),
+        Value::String(parent_id.to_string()),
+    );
+    child.touch();
+    show_subtree(diagram, child_id, !hidden);
+    Ok(previous)
+}
+
+/// Move a node into a container's child list and return the container that
+/// held it before (`None` for the root).
+///
+/// The node leaves every other child list, so it keeps a single container,
+/// and stops being a subtask. It takes the visibility of its new container.
+/// Fails if either element is missing or an edge, or if the container is the
+/// node itself or lies inside it.
+pub fn add_to_container(
+    diagram: &mut DiagramModel,
+    container_id: &str,
+    child_id: &str,
+) -> Result<Option<String>, HierarchyError> {
+    check_containable(diagram, container_id, child_id, "Element")?;
+
+    let mut holders: Vec<&str> = diagram
+        .elements
+        .values()
+        .filter(|e| {
+            e.children
+                .as_deref()
+                .unwrap_or_default()
+                .iter()
+                .any(|c| c == child_id)
+        })
+        .map(|e| e.id.as_str())
+        .collect();
+    holders.sort_unstable();
+    let previous = holders.first().map(|id| id.to_string());
+    for element in diagram
+        .elements
+        .values_mut()
+        .chain(std::iter::once(&mut diagram.root))
+    {
+        if let Some(children) = &mut element.children {
+            children.retain(|id| id != child_id);
+        }
+    }
+
+    let container = diagram
+        .elements
+        .get_mut(container_id)
+        .expect("checked above");
+    container
+        .children
+        .get_or_insert_with(Vec::new)
+        .push(child_id.to_string());
+    container.touch();
+    let shown = container.visible && !is_collapsed(container);
+
+    let child = diagram.elements.get_mut(child_id).expect("checked above");
+    child.properties.remove(PARENT_TASK_PROPERTY);
+    child.touch();
+    show_subtree(diagram, child_id, shown);
+    Ok(previous)
+}
+
+/// Both elements exist, neither is an edge and the move closes no cycle
+fn check_containable(
+    diagram: &DiagramModel,
+    container_id: &str,
+    child_id: &str,
+    kind: &str,
+) -> Result<(), HierarchyError> {
+    for id in [container_id, child_id] {
+        match diagram.elements.get(id) {
+            None => return Err(format!("{kind} {id} not found").into()),
+            Some(element) if is_edge(element) => {
+                return Err(format!("{id} is an edge and cannot be nested").into())
+            }
+            Some(_) => {}
+        }
+    }
+    match cycle_if_contained(diagram, container_id, child_id) {
+        Some(cycle) => Err(HierarchyError::ContainmentCycle(cycle)),
+        None => Ok(()),
+    }
+}
+
+/// Move a subtask back to the top level and return its former parent
+pub fn remove_subtask(diagram: &mut DiagramModel, child_id: &str) -> Result<String, String> {
+    let parent = diagram
+        .elements
+        .get(child_id)
+        .ok_or_else(|| format!("Task {child_id} not found"))?;
+    if parent_task(parent).is_none() {
+        return Err(format!("Task {child_id} is not a subtask"));
+    }
+
+    let previous = detach(diagram, child_id).unwrap_or_default();
+    let root_children = diagram.root.children.get_or_insert_with(Vec::new);
+    if !root_children.iter().any(|id| id == child_id) {
+        root_children.push(child_id.to_string());
+    }
+    show_subtree(diagram, child_id, true);
+    Ok(previous)
+}
+
+/// Collapse or expand a parent task and return the subtasks whose
+/// visibility changed
+pub fn set_collapsed(
+    diagram: &mut DiagramModel,
+    task_id: &str,
+    collapsed: bool,
+) -> Result<Vec<String>, String> {
+    let task = diagram
+        .elements
+        .get_mut(task_id)
+        .ok_or_else(|| format!("Task {task_id} not found"))?;
+    task.properties
+        .insert(COLLAPSED_PROPERTY.to_string(), Value::Bool(collapsed));
+    task.touch();
+    let visible = task.visible;
+
+    let before: Vec<(String, bool)> = descendants(diagram, task_id)
+        .into_iter()
+        .filter_map(|id| diagram.elements.get(&id).map(|e| (id, e.visible)))
+        .collect();
+    show_subtree(diagram, task_id, visible);
+    Ok(before
+        .into_iter()
+        .filter(|(id, was)| diagram.elements.get(id).is_some_and(|e| e.visible != *was))
+        .map(|(id, _)| id)
+        .collect())
+}
+
+/// Take a task out of its parent's (or the root's) child list and clear its
+/// parent reference; returns the parent it had
+fn detach(diagram: &mut DiagramModel, child_id: &str) -> Option<String> {
+    let previous = diagram
+        .elements
+        .get_mut(child_id)
+        .and_then(|child| child.properties.remove(PARENT_TASK_PROPERTY))
+        .and_then(|parent| parent.as_str().map(str::to_string));
+    let holder = match &previous {
+        Some(parent_id) => diagram.elements.get_mut(parent_id),
+        None => Some(&mut diagram.root),
+    };
+    if let Some(holder) = holder {
+        if let Some(children) = &mut holder.children {
+            children.retain(|id| id != child_id);
+        }
+    }
+    previous
+}
+
+/// Set `task_id` visible or hidden and its subtasks to match, keeping those
+/// below a collapsed subtask hidden
+fn show_subtree(diagram: &mut DiagramModel, task_id: &str, visible: bool) {
+    let mut pending = vec![(task_id.to_string(), visible)];
+    let mut seen = HashSet::new();
+    while let Some((id, visible)) = pending.pop() {
+        if !seen.insert(id.clone()) {
+            continue;
+        }
+        let Some(task) = diagram.elements.get_mut(&id) else {
+            continue;
+        };
+        task.visible = visible;
+        let shown = visible && !is_collapsed(task);
+        for child in task.children.as_deref().unwrap_or_default() {
+            pending.push((child.clone(), shown));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Node, Position};
+
+    fn task(diagram: &mut DiagramModel) -> String {
+        let node = Node::new("task", Position { x: 0.0, y: 0.0 }, None).base;
+        let id = node.id.clone();
+        diagram.add_element(node);
+        diagram.add_child_to_root(&id);
+        id
+    }
+
+    #[test]
+    fn test_subtasks_nest_collapse_and_reject_cycles() {
+        let mut diagram = DiagramModel::new("workflow");
+        let (braking, sensing, fusion) =
+            (task(&mut diagram), task(&mut diagram), task(&mut diagram));
+
+        assert_eq!(add_subtask(&mut diagram, &braking, &sensing), Ok(None));
+        assert_eq!(add_subtask(&mut diagram, &sensing, &fusion), Ok(None));
+        assert_eq!(
+            descendants(&diagram, &braking),
+            vec![sensing.clone(), fusion.clone()]
+        );
+        assert_eq!(diagram.root.children, Some(vec![braking.clone()]));
+        assert_eq!(
+            parent_task(&diagram.elements[&fusion]),
+            Some(sensing.as_str())
+        );
+
+        // A task cannot contain its own ancestor
+        assert!(add_subtask(&mut diagram, &fusion, &braking).is_err());
+        assert!(add_subtask(&mut diagram, &braking, &braking).is_err());
+
+        assert_eq!(
+            set_collapsed(&mut diagram, &sensing, true),
+            Ok(vec![fusion.clone()])
+        );
+        assert_eq!(
+            set_collapsed(&mut diagram, &braking, true),
+            Ok(vec![sensing.clone()])
+        );
+        // Expanding the outer task leaves the inner one collapsed
+        assert_eq!(
+            set_collapsed(&mut diagram, &braking, false),
+            Ok(vec![sensing.clone()])
+        );
+        assert!(!diagram.elements[&fusion].visible);
+
+        assert_eq!(remove_subtask(&mut diagram, &fusion), Ok(sensing.clone()));
+        assert!(diagram.elements[&fusion].visible);
+        assert!(parent_task(&diagram.elements[&fusion]).is_none());
+        assert_eq!(diagram.root.children, Some(vec![braking, fusion.clone()]));
+        assert!(remove_subtask(&mut diagram, &fusion).is_err());
+    }
+
+    #[test]
+    fn test_containers_stay_a_tree() {
+        let mut diagram = DiagramModel::new("workflow");
+        let (outer, inner, node) = (task(&mut diagram), task(&mut diagram), task(&mut diagram));
+
+        assert_eq!(add_to_container(&mut diagram, &outer, &inner), Ok(None));
+        assert_eq!(add_to_container(&mut diagram, &inner, &node), Ok(None));
+        assert_eq!(diagram.root.children, Some(vec![outer.clone()]));
+        assert_eq!(
+            add_to_container(&mut diagram, &node, &outer),
+            Err(HierarchyError::ContainmentCycle(vec![
+                node.clone(),
+                outer.clone(),
+                inner.clone()
+            ]))
+        );
+        assert!(matches!(
+            add_subtask(&mut diagram, &outer, &outer),
+            Err(HierarchyError::ContainmentCycle(_))
+        ));
+        assert!(containment_cycles(&diagram).is_empty());
+
+        // Moving keeps a single container
+        assert_eq!(
+            add_to_container(&mut diagram, &outer, &node),
+            Ok(Some(inner.clone()))
+        );
+        assert_eq!(diagram.elements[&inner].children, Some(vec![]));
+
+        // A cycle written directly into the child lists is still found
+        diagram
+            .elements
+            .get_mut(&node)
+            .unwrap()
+            .children
+            .get_or_insert_with(Vec::new)
+            .push(outer.clone());
+        let mut expected = vec![outer.clone(), node.clone()];
+        let smallest = if node < outer { 1 } else { 0 };
+        expected.rotate_left(smallest);
+        assert_eq!(containment_cycles(&diagram), vec![expected]);
+        assert_eq!(descendants(&diagram, &outer).len(), 2);
+    }
+}
@@ -0,0 +1,175 @@
+//! Length limits for node labels
+//!
+//! Very long labels break layout and rendering, so the server caps them.
+//! UML classifiers have their own limits: class names are usually shorter
+//! than free-form labels, attributes are limited by name and methods by their
+//! whole signature as shown in the class box (see
+//! [`member_line`](crate::operations::compartments::member_line)). Lengths
+//! are counted in characters, not bytes.
+
+use crate::operations::capabilities::CLASSIFIERS;
+use crate::operations::compartments::member_line;
+use serde_json::Value;
+use std::fmt;
+
+/// Longest label a node may have
+pub const DEFAULT_MAX_LABEL_LENGTH: usize = 200;
+
+/// Longest name of a class, interface or enum
+pub const DEFAULT_MAX_CLASS_NAME_LENGTH: usize = 100;
+
+/// Longest name of a class attribute
+pub const DEFAULT_MAX_ATTRIBUTE_NAME_LENGTH: usize = 100;
+
+/// Longest method signature, e.g. `+area(): f64`
+pub const DEFAULT_MAX_METHOD_SIGNATURE_LENGTH: usize = 200;
+
+/// Label length limits, in characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LabelLimits {
+    pub label: usize,
+    pub class_name: usize,
+    pub attribute_name: usize,
+    pub method_signature: usize,
+}
+
+impl Default for LabelLimits {
+    fn default() -> Self {
+        Self {
+            label: DEFAULT_MAX_LABEL_LENGTH,
+            class_name: DEFAULT_MAX_CLASS_NAME_LENGTH,
+            attribute_name: DEFAULT_MAX_ATTRIBUTE_NAME_LENGTH,
+            method_signature: DEFAULT_MAX_METHOD_SIGNATURE_LENGTH,
+        }
+    }
+}
+
+/// A label longer than its limit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelTooLong {
+    /// What was too long, e.g. `label`, `className` or `methods[2]`
+    pub field: String,
+    pub length: usize,
+    pub limit: usize,
+}
+
+impl fmt::Display for LabelTooLong {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is {} characters long, the limit is {}",
+            self.field, self.length, self.limit
+        )
+    }
+}
+
+impl LabelLimits {
+    /// Check the label of a node of `node_type`; classifiers use the class name limit
+    pub fn check_label(&self, node_type: &str, label: &str) -> Result<(), LabelTooLong> {
+        let (field, limit) = if CLASSIFIERS.contains(&node_type) {
+            ("className", self.class_name)
+        } else {
+            ("label", self.label)
+        };
+        within(field.to_string(), label.chars().count(), limit)
+    }
+
+    /// Check the `attributes` and `methods` entries in `properties`
+    pub fn check_members(
+        &self,
+        properties: &serde_json::Map<String, Value>,
+    ) -> Result<(), LabelTooLong> {
+        let entries = |compartment| {
+            properties
+                .get(compartment)
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .enumerate()
+        };
+        for (i, attribute) in entries("attributes") {
+            let name = match attribute {
+                Value::Object(fields) => fields.get("name").and_then(Value::as_str),
+                other => other.as_str(),
+            };
+            if let Some(name) = name {
+                within(
+                    format!("attributes[{i}]"),
+                    name.chars().count(),
+                    self.attribute_name,
+                )?;
+            }
+        }
+        for (i, method) in entries("methods") {
+            if let Some(signature) = member_line("methods", method) {
+                within(
+                    format!("methods[{i}]"),
+                    signature.chars().count(),
+                    self.method_signature,
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn within(field: String, length: usize, limit: usize) -> Result<(), LabelTooLong> {
+    if length > limit {
+        return Err(LabelTooLong {
+            field,
+            length,
+            limit,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_label_limits_per_kind() {
+        let limits = LabelLimits {
+            label: 10,
+            class_name: 5,
+            attribute_name: 4,
+            method_signature: 12,
+        };
+        assert!(limits.check_label("task", "Ten chars!").is_ok());
+        // Characters, not bytes
+        assert!(limits.check_label("task", "ääääääääää").is_ok());
+        let err = limits.check_label("class", "Shape2D").unwrap_err();
+        assert_eq!(
+            err,
+            LabelTooLong {
+                field: "className".to_string(),
+                length: 7,
+                limit: 5,
+            }
+        );
+
+        let ok = json!({
+            "attributes": ["size", {"name": "area", "type": "a very long type"}],
+            "methods": [{"name": "area", "type": "f64", "visibility": "public"}],
+        });
+        assert!(limits.check_members(ok.as_object().unwrap()).is_ok());
+
+        let long_attribute = json!({"attributes": ["size", "width"]});
+        let err = limits
+            .check_members(long_attribute.as_object().unwrap())
+            .unwrap_err();
+        assert_eq!((err.field.as_str(), err.length), ("attributes[1]", 5));
+
+        // "+area(): f64" fits, the return type pushes this one over
+        let long_method = json!({"methods": [{"name": "area", "type": "double"}]});
+        let err = limits
+            .check_members(long_method.as_object().unwrap())
+            .unwrap_err();
+        assert_eq!(
+            (err.field.as_str(), err.length, err.limit),
+            ("methods[0]", 15, 12)
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! Layout structure without positions
+//!
+//! Clients that render diagrams themselves may still want the server to work
+//! out the structure of a layout. [`layout_hints`] assigns every node a rank
+//! (its layer or row) and an order within that rank, and leaves positions
+//! alone; the client places nodes from the hints.
+//!
+//! The hierarchical hints use the same layering as `apply_layout`: directed
+//! edges point from lower to higher ranks. Nodes within a rank are ordered by
+//! a few barycenter sweeps, each moving a node towards the average order of
+//! its predecessors, which reduces edge crossings between adjacent ranks.
+
+use crate::model::{DiagramModel, ElementType};
+use crate::operations::graph::{directed_layers, is_directed, is_edge, links};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Algorithms [`layout_hints`] supports
+pub const LAYOUT_HINT_ALGORITHMS: &[&str] = &["hierarchical", "grid"];
+
+/// Columns per row of the grid hints, as `apply_layout` uses
+const GRID_COLUMNS: usize = 4;
+
+/// Barycenter sweeps over the ranks; later sweeps rarely change the order
+const ORDERING_SWEEPS: usize = 4;
+
+/// Suggested placement of one node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutHint {
+    pub node_id: String,
+    /// Layer (hierarchical) or row (grid), from 0
+    pub rank: usize,
+    /// Position within the rank, from 0
+    pub order: usize,
+}
+
+/// Rank and order of every node for `algorithm`, sorted by rank and order
+pub fn layout_hints(diagram: &DiagramModel, algorithm: &str) -> Result<Vec<LayoutHint>, String> {
+    let mut nodes: Vec<&str> = diagram
+        .elements
+        .values()
+        .filter(|e| !is_edge(e) && e.element_type != ElementType::Graph)
+        .map(|e| e.id.as_str())
+        .collect();
+    nodes.sort_unstable();
+
+    let mut hints = match algorithm {
+        "hierarchical" => hierarchical_hints(diagram, &nodes),
+        "grid" => nodes
+            .iter()
+            .enumerate()
+            .map(|(index, id)| LayoutHint {
+                node_id: id.to_string(),
+                rank: index / GRID_COLUMNS,
+                order: index % GRID_COLUMNS,
+            })
+            .collect(),
+        other => {
+            return Err(format!(
+                "Layout algorithm '{other}' has no hints (expected one of: {})",
+                LAYOUT_HINT_ALGORITHMS.join(", ")
+            ))
+        }
+    };
+    hints.sort_by_key(|hint| (hint.rank, hint.order));
+    Ok(hints)
+}
+
+fn hierarchical_hints(diagram: &DiagramModel, nodes: &[&str]) -> Vec<LayoutHint> {
+    let layers = directed_layers(diagram);
+    let rank_of = |id: &str| layers.get(id).copied().unwrap_or(0);
+
+    // Nodes start in ID order within their rank
+    let mut ranks: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+    for &id in nodes {
+        ranks.entry(rank_of(id)).or_default().push(id);
+    }
+
+    let mut predecessors: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (edge, source, target) in links(diagram) {
+        if is_directed(edge) && rank_of(source) < rank_of(target) {
+            predecessors.entry(target).or_default().push(source);
+        }
+    }
+
+    let mut order: HashMap<&str, usize> = HashMap::new();
+    for members in ranks.values() {
+        order.extend(members.iter().enumerate().map(|(i, id)| (*id, i)));
+    }
+    for _ in 0..ORDERING_SWEEPS {
+        for members in ranks.values_mut() {
+            let barycenter = |id: &str| -> f64 {
+                match predecessors.get(id) {
+                    Some(preds) if !preds.is_empty() => {
+                        preds.iter().map(|p| order[p] as f64).sum::<f64>() / preds.len() as f64
+                    }
+                    _ => order[id] as f64,
+                }
+            };
+            let mut keyed: Vec<(f64, &str)> =
+                members.iter().map(|id| (barycenter(id), *id)).collect();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            *members = keyed.into_iter().map(|(_, id)| id).collect();
+            order.extend(members.iter().enumerate().map(|(i, id)| (*id, i)));
+        }
+    }
+
+    ranks
+        .into_iter()
+        .flat_map(|(rank, members)| {
+            members
+                .into_iter()
+                .enumerate()
+                .map(move |(order, id)| LayoutHint {
+                    node_id: id.to_string(),
+                    rank,
+                    order,
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Edge, Node, Position};
+
+    fn add_node(diagram: &mut DiagramModel, id: &str) {
+        let mut node = Node::new("task", Position { x: 0.0, y: 0.0 }, None).base;
+        node.id = id.to_string();
+        diagram.add_element(node);
+    }
+
+    fn add_edge(diagram: &mut DiagramModel, source: &str, target: &str) {
+        let edge = Edge::new("flow", source.to_string(), target.to_string(), None).base;
+        diagram.add_element(edge);
+    }
+
+    #[test]
+    fn test_hints_rank_and_order_without_moving_nodes() {
+        let mut diagram = DiagramModel::new("workflow");
+        for id in ["a", "b", "x", "y"] {
+            add_node(&mut diagram, id);
+        }
+        // a -> y and b -> x cross when the second rank stays in ID order
+        add_edge(&mut diagram, "a", "y");
+        add_edge(&mut diagram, "b", "x");
+        let before = serde_json::to_value(&diagram).unwrap();
+
+        let hints = layout_hints(&diagram, "hierarchical").unwrap();
+        let placed: Vec<(&str, usize, usize)> = hints
+            .iter()
+            .map(|h| (h.node_id.as_str(), h.rank, h.order))
+            .collect();
+        assert_eq!(
+            placed,
+            vec![("a", 0, 0), ("b", 0, 1), ("y", 1, 0), ("x", 1, 1)]
+        );
+        assert_eq!(serde_json::to_value(&diagram).unwrap(), before);
+
+        let grid = layout_hints(&diagram, "grid").unwrap();
+        assert_eq!((grid[3].rank, grid[3].order), (0, 3));
+        assert!(layout_hints(&diagram, "force").is_err());
+    }
+}
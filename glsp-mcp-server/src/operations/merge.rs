@@ -0,0 +1,225 @@
+//! Merging one diagram into another
+//!
+//! Every element of the source (except its root) is copied into the target
+//! under a fresh ID. Edge endpoints and child lists are rewritten through the
+//! ID map so connections survive the copy, and geometry is shifted by an
+//! offset so merged content does not overlap what the target already holds.
+//!
+//! Duplicating a diagram is a merge into a fresh, empty diagram with no offset.
+
+use crate::model::{generate_id, DiagramModel, ModelElement, Position};
+use std::collections::HashMap;
+
+/// Vertical gap between existing target content and merged content
+const MERGE_GAP: f64 = 100.0;
+
+/// Offset that places the source's content below the target's content
+pub fn default_merge_offset(target: &DiagramModel, source: &DiagramModel) -> Position {
+    let content_bounds = |diagram: &DiagramModel| {
+        diagram
+            .elements
+            .values()
+            .filter(|e| e.id != diagram.root.id)
+            .filter_map(|e| e.bounds.as_ref())
+            .fold(None, |acc: Option<(f64, f64, f64)>, b| {
+                Some(match acc {
+                    Some((min_x, min_y, max_y)) => {
+                        (min_x.min(b.x), min_y.min(b.y), max_y.max(b.y + b.height))
+                    }
+                    None => (b.x, b.y, b.y + b.height),
+                })
+            })
+    };
+
+    match (content_bounds(target), content_bounds(source)) {
+        (Some((target_x, _, target_bottom)), Some((source_x, source_top, _))) => Position {
+            x: target_x - source_x,
+            y: target_bottom + MERGE_GAP - source_top,
+        },
+        _ => Position { x: 0.0, y: 0.0 },
+    }
+}
+
+/// Copy all elements of `source` into `target`, shifted by `offset`.
+///
+/// Returns the map from source element IDs to their new IDs in the target.
+pub fn merge_diagram(
+    target: &mut DiagramModel,
+    source: &DiagramModel,
+    offset: &Position,
+) -> HashMap<String, String> {
+    let id_map: HashMap<String, String> = source
+        .elements
+        .keys()
+        .filter(|id| **id != source.root.id)
+        .map(|id| (id.clone(), generate_id()))
+        .collect();
+    let remap = |id: &String| id_map.get(id).cloned().unwrap_or_else(|| id.clone());
+
+    let mut ids: Vec<&String> = id_map.keys().collect();
+    ids.sort();
+    for old_id in ids {
+        let mut element: ModelElement = source.elements[old_id].clone();
+        element.id = id_map[old_id].clone();
+        element.source_id = element.source_id.as_ref().map(remap);
+        element.target_id = element.target_id.as_ref().map(remap);
+        for ids in [&mut element.sources, &mut element.targets]
+            .into_iter()
+            .flatten()
+        {
+            *ids = ids.iter().map(remap).collect();
+        }
+        if let Some(children) = &mut element.children {
+            *children = children.iter().map(remap).collect();
+        }
+        if let Some(bounds) = &mut element.bounds {
+            bounds.x += offset.x;
+            bounds.y += offset.y;
+        }
+        if let Some(route) = &mut element.route {
+            for point in route {
+                point.x += offset.x;
+                point.y += offset.y;
+            }
+        }
+        element.touch();
+        target.add_element(element);
+    }
+
+    for child in source.root.children.as_deref().unwrap_or_default() {
+        if let Some(new_id) = id_map.get(child) {
+            target.add_child_to_root(new_id);
+        }
+    }
+
+    id_map
+}
+
+/// What `duplicate_diagram` copies besides the elements themselves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DuplicateOptions {
+    /// Keep element properties; when off, copies start with empty properties
+    pub properties: bool,
+    /// Keep diagram metadata and tags, except timestamp entries
+    pub metadata: bool,
+}
+
+impl Default for DuplicateOptions {
+    fn default() -> Self {
+        Self {
+            properties: true,
+            metadata: true,
+        }
+    }
+}
+
+/// Metadata keys holding timestamps, which belong to the original diagram
+fn is_timestamp_key(key: &str) -> bool {
+    key.ends_with("At") || key.ends_with("_at") || key.to_ascii_lowercase().contains("timestamp")
+}
+
+/// Copy `source` into a new diagram of the same type named `name`.
+///
+/// Returns the new diagram and the map from source element IDs to copy IDs.
+pub fn duplicate_diagram(
+    source: &DiagramModel,
+    name: &str,
+    options: DuplicateOptions,
+) -> (DiagramModel, HashMap<String, String>) {
+    let mut copy = DiagramModel::new(&source.diagram_type);
+    copy.name = name.to_string();
+    if options.metadata {
+        copy.metadata = source
+            .metadata
+            .iter()
+            .filter(|(key, _)| !is_timestamp_key(key))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        copy.tags = source.tags.clone();
+    }
+
+    let id_map = merge_diagram(&mut copy, source, &Position { x: 0.0, y: 0.0 });
+    if !options.properties {
+        for new_id in id_map.values() {
+            if let Some(element) = copy.elements.get_mut(new_id) {
+                element.properties.clear();
+            }
+        }
+    }
+    copy.revision = 0;
+
+    (copy, id_map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Edge, Node};
+
+    fn add_node(diagram: &mut DiagramModel, x: f64, y: f64) -> String {
+        let node = Node::new("task", Position { x, y }, None);
+        let id = node.base.id.clone();
+        diagram.add_element(node.base);
+        diagram.add_child_to_root(&id);
+        id
+    }
+
+    #[test]
+    fn test_merge_remaps_edges_and_offsets_nodes() {
+        let mut target = DiagramModel::new("workflow");
+        add_node(&mut target, 50.0, 50.0);
+
+        let mut source = DiagramModel::new("workflow");
+        let a = add_node(&mut source, 0.0, 0.0);
+        let b = add_node(&mut source, 200.0, 0.0);
+        let edge = Edge::new("flow", a.clone(), b.clone(), None);
+        let edge_id = edge.base.id.clone();
+        source.add_element(edge.base);
+        source.add_child_to_root(&edge_id);
+
+        let offset = default_merge_offset(&target, &source);
+        assert_eq!((offset.x, offset.y), (50.0, 200.0));
+
+        let id_map = merge_diagram(&mut target, &source, &offset);
+        assert_eq!(id_map.len(), 3);
+
+        let merged_a = &target.elements[&id_map[&a]];
+        let bounds = merged_a.bounds.as_ref().unwrap();
+        assert_eq!((bounds.x, bounds.y), (50.0, 200.0));
+
+        let merged_edge = &target.elements[&id_map[&edge_id]];
+        assert_eq!(merged_edge.source_id.as_ref(), Some(&id_map[&a]));
+        assert_eq!(merged_edge.target_id.as_ref(), Some(&id_map[&b]));
+        assert_eq!(target.root.children.as_ref().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_duplicate_keeps_structure_under_new_ids() {
+        let mut source = DiagramModel::new("workflow");
+        source
+            .metadata
+            .insert("owner".to_string(), serde_json::json!("adas"));
+        source
+            .metadata
+            .insert("reviewedAt".to_string(), serde_json::json!("2024-01-01"));
+        let a = add_node(&mut source, 0.0, 0.0);
+        let b = add_node(&mut source, 200.0, 0.0);
+        let edge = Edge::new("flow", a.clone(), b.clone(), None);
+        let edge_id = edge.base.id.clone();
+        source.add_element(edge.base);
+
+        let (copy, id_map) = duplicate_diagram(&source, "Copy", DuplicateOptions::default());
+        assert_ne!(copy.id, source.id);
+        assert_eq!((copy.name.as_str(), copy.revision), ("Copy", 0));
+        assert_eq!(copy.elements.len(), source.elements.len());
+        assert!(id_map.iter().all(|(old, new)| old != new));
+
+        let copied_edge = &copy.elements[&id_map[&edge_id]];
+        assert_eq!(copied_edge.source_id.as_ref(), Some(&id_map[&a]));
+        let bounds = copy.elements[&id_map[&b]].bounds.as_ref().unwrap();
+        assert_eq!((bounds.x, bounds.y), (200.0, 0.0));
+
+        assert!(copy.metadata.contains_key("owner"));
+        assert!(!copy.metadata.contains_key("reviewedAt"));
+    }
+}
@@ -0,0 +1,86 @@
+//! Mermaid export
+//!
+//! Diagrams become Mermaid flowcharts: each node a box labelled with its
+//! label or type, each edge an arrow, or a plain line when undirected.
+//! Hyperedges are drawn as one arrow per source and target pair.
+
+use crate::model::{DiagramModel, ModelElement};
+use crate::operations::graph::{is_directed, is_edge, links};
+use std::collections::HashMap;
+
+/// Render a diagram as a Mermaid flowchart
+pub fn to_mermaid(diagram: &DiagramModel) -> String {
+    let mut nodes: Vec<&ModelElement> = diagram
+        .elements
+        .values()
+        .filter(|e| e.id != diagram.root.id && !is_edge(e))
+        .collect();
+    nodes.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let aliases: HashMap<&str, String> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, e)| (e.id.as_str(), format!("N{i}")))
+        .collect();
+
+    let mut out = format!("---\ntitle: {}\n---\nflowchart LR\n", diagram.name);
+    for node in &nodes {
+        let name = node
+            .label
+            .as_deref()
+            .unwrap_or_else(|| node.element_type.as_str())
+            .replace('"', "#quot;");
+        out.push_str(&format!("    {}[\"{name}\"]\n", aliases[node.id.as_str()]));
+    }
+
+    let mut relations: Vec<String> = links(diagram)
+        .filter_map(|(edge, source, target)| {
+            let source = aliases.get(source)?;
+            let target = aliases.get(target)?;
+            let arrow = if is_directed(edge) { "-->" } else { "---" };
+            Some(match &edge.label {
+                Some(label) => format!(
+                    "    {source} {arrow}|\"{}\"| {target}\n",
+                    label.replace('"', "#quot;")
+                ),
+                None => format!("    {source} {arrow} {target}\n"),
+            })
+        })
+        .collect();
+    relations.sort();
+    for relation in relations {
+        out.push_str(&relation);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Edge, Node, Position};
+
+    #[test]
+    fn test_mermaid_flowchart() {
+        let mut diagram = DiagramModel::new("workflow");
+        diagram.name = "Pipeline".to_string();
+        let mut ids = Vec::new();
+        for label in ["Read", "Write \"out\""] {
+            let node = Node::new("task", Position { x: 0.0, y: 0.0 }, Some(label.to_string()));
+            ids.push(node.base.id.clone());
+            diagram.add_element(node.base);
+        }
+        ids.sort();
+        let edge = Edge::new(
+            "flow",
+            ids[0].clone(),
+            ids[1].clone(),
+            Some("data".to_string()),
+        );
+        diagram.add_element(edge.base);
+
+        let mermaid = to_mermaid(&diagram);
+        assert!(mermaid.starts_with("---\ntitle: Pipeline\n---\nflowchart LR\n"));
+        assert!(mermaid.contains("#quot;out#quot;"));
+        assert!(mermaid.ends_with("    N0 -->|\"data\"| N1\n"));
+    }
+}
@@ -1,2 +1,69 @@
-// Placeholder for future operation types and handlers
-// This module can be expanded to include more sophisticated operation processing
+//! Diagram operations and transformations
+//!
+//! This module can be expanded to include more sophisticated operation processing
+
+pub mod capabilities;
+pub mod compare;
+pub mod compartments;
+pub mod conversion;
+pub mod export;
+pub mod force_layout;
+pub mod graph;
+pub mod hierarchy;
+pub mod labels;
+pub mod layout_hints;
+pub mod merge;
+pub mod mermaid;
+pub mod paging;
+pub mod placement;
+pub mod plantuml;
+pub mod projection;
+pub mod raster;
+pub mod rust_types;
+pub mod styles;
+pub mod subgraph;
+pub mod targets;
+pub mod wit_diagram;
+
+pub use capabilities::{diagram_type_spec, DiagramTypeSpec, ACYCLIC_EDGE_TYPES, DIAGRAM_TYPES};
+pub use compare::{compare_diagrams, DiagramComparison, ElementMatch, ElementSummary, FieldChange};
+pub use conversion::{convert_diagram, ConversionResult, UnmappedElement};
+pub use export::DiagramFormat;
+pub use force_layout::apply_force_layout;
+pub use graph::{
+    connections, create_hyperedge, cycle_closed_by, default_directed, directed_layers, edge_cost,
+    edge_element, edges_for_node, find_cycles, find_path, guarded_change, incident_edges,
+    is_directed, is_edge, is_hyperedge, links, must_be_acyclic, reconnect_edge, reverse_edge,
+    shortest_path, EdgeRef, NodeEdges, WeightedPath, ACYCLIC_KEY, DIRECTED_PROPERTY,
+    HYPEREDGE_TYPE,
+};
+pub use hierarchy::{
+    add_subtask, add_to_container, containment_cycles, descendants, is_collapsed, parent_task,
+    remove_subtask, set_collapsed, HierarchyError, COLLAPSED_PROPERTY, PARENT_TASK_PROPERTY,
+};
+pub use labels::{
+    LabelLimits, LabelTooLong, DEFAULT_MAX_ATTRIBUTE_NAME_LENGTH, DEFAULT_MAX_CLASS_NAME_LENGTH,
+    DEFAULT_MAX_LABEL_LENGTH, DEFAULT_MAX_METHOD_SIGNATURE_LENGTH,
+};
+pub use layout_hints::{layout_hints, LayoutHint, LAYOUT_HINT_ALGORITHMS};
+pub use merge::{default_merge_offset, duplicate_diagram, merge_diagram, DuplicateOptions};
+pub use mermaid::to_mermaid;
+pub use paging::{PageCursor, SnapshotCache, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+pub use placement::{
+    content_bounds, content_extent, default_position, fit_to_content, normalize_coordinates,
+    snap_position, snap_to_grid, PlacementStrategy, CANVAS_MARGIN,
+};
+pub use plantuml::to_plantuml;
+pub use projection::{partition_fields, project_diagram, project_element, ELEMENT_FIELDS};
+pub use raster::render_png;
+pub use rust_types::{diagram_from_rust, RustTypesDiagram};
+pub use styles::{resolve_style, set_type_style, type_styles, TypeStyle, TYPE_STYLES_KEY};
+pub use subgraph::{
+    extract_subgraph, subdiagram_link, Extraction, PARENT_DIAGRAM_KEY, SUBDIAGRAM_PROPERTY,
+    SUBDIAGRAM_REFERENCE_TYPE,
+};
+pub use targets::suggest_targets;
+pub use wit_diagram::{
+    diagram_from_dependency_graph, diagram_from_wit, validate_wit, WitDiagram, WitError,
+    WitPackageSummary,
+};
@@ -0,0 +1,224 @@
+//! Cursor-based paging over diagram elements
+//!
+//! The first page of a paged read snapshots the diagram; every following
+//! page is served from that snapshot, so concurrent edits never shift or
+//! duplicate elements between pages. A cursor names the snapshot and the
+//! offset of the next element. Snapshots expire once unused for a TTL and
+//! are pruned lazily; each page served restarts the TTL. The number of
+//! snapshots per diagram and in total is capped, evicting the least
+//! recently used.
+
+use crate::model::{generate_id, DiagramModel};
+use crate::operations::graph::is_edge;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+/// Elements per page when the caller does not say
+pub const DEFAULT_PAGE_SIZE: usize = 500;
+/// Largest page a caller may request
+pub const MAX_PAGE_SIZE: usize = 5000;
+/// How long a snapshot stays available after its last page was served
+pub const SNAPSHOT_TTL_SECS: i64 = 300;
+/// Snapshots kept per diagram
+pub const MAX_SNAPSHOTS_PER_DIAGRAM: usize = 4;
+/// Snapshots kept across all diagrams
+pub const MAX_SNAPSHOTS: usize = 32;
+
+/// A position in a paged read
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageCursor {
+    pub snapshot_id: String,
+    pub offset: usize,
+}
+
+impl PageCursor {
+    pub fn encode(&self) -> String {
+        format!("{}:{}", self.snapshot_id, self.offset)
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, String> {
+        let (snapshot_id, offset) = cursor
+            .rsplit_once(':')
+            .ok_or_else(|| format!("Malformed cursor: {cursor}"))?;
+        let offset = offset
+            .parse()
+            .map_err(|_| format!("Malformed cursor: {cursor}"))?;
+        Ok(Self {
+            snapshot_id: snapshot_id.to_string(),
+            offset,
+        })
+    }
+}
+
+/// A diagram frozen for paging, with its elements in page order
+#[derive(Debug, Clone)]
+pub struct DiagramSnapshot {
+    pub diagram: DiagramModel,
+    /// Nodes first, then edges, each sorted by id; the root is excluded
+    pub order: Vec<String>,
+    last_used: DateTime<Utc>,
+}
+
+impl DiagramSnapshot {
+    pub fn new(diagram: DiagramModel) -> Self {
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        for element in diagram.elements.values() {
+            if element.id == diagram.root.id {
+                continue;
+            }
+            if is_edge(element) {
+                edges.push(element.id.clone());
+            } else {
+                nodes.push(element.id.clone());
+            }
+        }
+        nodes.sort();
+        edges.sort();
+        nodes.extend(edges);
+
+        Self {
+            diagram,
+            order: nodes,
+            last_used: Utc::now(),
+        }
+    }
+
+    /// Element IDs of the page starting at `offset`, and the offset of the next page
+    pub fn page(&self, offset: usize, page_size: usize) -> (&[String], Option<usize>) {
+        let start = offset.min(self.order.len());
+        let end = (start + page_size).min(self.order.len());
+        let next = (end < self.order.len()).then_some(end);
+        (&self.order[start..end], next)
+    }
+}
+
+/// Snapshots of in-progress paged reads, keyed by snapshot id
+#[derive(Debug, Default)]
+pub struct SnapshotCache {
+    snapshots: HashMap<String, DiagramSnapshot>,
+}
+
+impl SnapshotCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn prune(&mut self) {
+        let cutoff = Utc::now() - Duration::seconds(SNAPSHOT_TTL_SECS);
+        self.snapshots.retain(|_, s| s.last_used > cutoff);
+    }
+
+    /// Drop least recently used snapshots matching `filter` until fewer
+    /// than `limit` remain
+    fn evict(&mut self, limit: usize, filter: impl Fn(&DiagramSnapshot) -> bool) {
+        let mut matching: Vec<(DateTime<Utc>, String)> = self
+            .snapshots
+            .iter()
+            .filter(|(_, s)| filter(s))
+            .map(|(id, s)| (s.last_used, id.clone()))
+            .collect();
+        if matching.len() < limit {
+            return;
+        }
+        matching.sort();
+        for (_, id) in &matching[..=matching.len() - limit] {
+            self.snapshots.remove(id);
+        }
+    }
+
+    /// Snapshot a diagram and return the snapshot id
+    pub fn insert(&mut self, diagram: DiagramModel) -> String {
+        self.prune();
+        self.evict(MAX_SNAPSHOTS_PER_DIAGRAM, |s| s.diagram.id == diagram.id);
+        self.evict(MAX_SNAPSHOTS, |_| true);
+        let snapshot_id = generate_id();
+        self.snapshots
+            .insert(snapshot_id.clone(), DiagramSnapshot::new(diagram));
+        snapshot_id
+    }
+
+    /// The snapshot, restarting its TTL
+    pub fn get(&mut self, snapshot_id: &str) -> Option<&DiagramSnapshot> {
+        self.prune();
+        let snapshot = self.snapshots.get_mut(snapshot_id)?;
+        snapshot.last_used = Utc::now();
+        Some(snapshot)
+    }
+
+    /// Drop a snapshot once its last page has been served
+    pub fn remove(&mut self, snapshot_id: &str) {
+        self.snapshots.remove(snapshot_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Edge, Node, Position};
+
+    #[test]
+    fn test_pages_are_stable_against_edits() {
+        let mut diagram = DiagramModel::new("workflow");
+        let mut ids = Vec::new();
+        for i in 0..3 {
+            let node = Node::new(
+                "task",
+                Position {
+                    x: i as f64,
+                    y: 0.0,
+                },
+                None,
+            );
+            ids.push(node.base.id.clone());
+            diagram.add_element(node.base);
+        }
+        let edge = Edge::new("flow", ids[0].clone(), ids[1].clone(), None);
+        let edge_id = edge.base.id.clone();
+        diagram.add_element(edge.base);
+
+        let mut cache = SnapshotCache::new();
+        let snapshot_id = cache.insert(diagram.clone());
+        // Edits after the first page do not affect the snapshot
+        diagram.add_element(Node::new("task", Position { x: 9.0, y: 9.0 }, None).base);
+
+        let snapshot = cache.get(&snapshot_id).unwrap();
+        let (first, next) = snapshot.page(0, 3);
+        assert_eq!(first.len(), 3);
+        assert!(!first.contains(&edge_id));
+        let (second, next) = snapshot.page(next.unwrap(), 3);
+        assert_eq!(second, &[edge_id]);
+        assert_eq!(next, None);
+
+        let cursor = PageCursor {
+            snapshot_id,
+            offset: 3,
+        };
+        assert_eq!(PageCursor::decode(&cursor.encode()), Ok(cursor));
+    }
+
+    #[test]
+    fn test_snapshots_are_capped_and_refreshed_on_use() {
+        let diagram = DiagramModel::new("workflow");
+        let mut cache = SnapshotCache::new();
+        let ids: Vec<String> = (0..MAX_SNAPSHOTS_PER_DIAGRAM + 1)
+            .map(|_| cache.insert(diagram.clone()))
+            .collect();
+        // The oldest snapshot of the diagram made room for the newest
+        assert_eq!(cache.snapshots.len(), MAX_SNAPSHOTS_PER_DIAGRAM);
+        assert!(cache.get(&ids[0]).is_none());
+        assert!(cache.get(&ids[MAX_SNAPSHOTS_PER_DIAGRAM]).is_some());
+
+        for _ in 0..MAX_SNAPSHOTS {
+            cache.insert(DiagramModel::new("workflow"));
+        }
+        assert_eq!(cache.snapshots.len(), MAX_SNAPSHOTS);
+
+        // A snapshot still being paged through outlives its original TTL
+        let snapshot_id = cache.insert(diagram);
+        let stale = Utc::now() - Duration::seconds(SNAPSHOT_TTL_SECS - 1);
+        cache.snapshots.get_mut(&snapshot_id).unwrap().last_used = stale;
+        assert!(cache.get(&snapshot_id).is_some());
+        assert!(cache.snapshots[&snapshot_id].last_used > stale);
+    }
+}
@@ -0,0 +1,402 @@
+//! Default placement for nodes created without coordinates
+//!
+//! The strategy is chosen by server configuration. Every strategy returns a
+//! position whose node bounds do not overlap any existing node.
+//!
+//! Positions given by callers can also be snapped to a coordinate grid so
+//! that float noise does not show up in diffs and exports.
+//!
+//! [`content_bounds`] gives the area all nodes occupy, which clients use to
+//! size the canvas and to fit a diagram into view. [`normalize_coordinates`]
+//! moves that area back to a fixed origin after edits have pushed it to
+//! negative or very large coordinates. [`content_extent`] also covers edge
+//! waypoints, and [`fit_to_content`] pads it into a canvas for exporters.
+
+use crate::model::{Bounds, DiagramModel, Position};
+use crate::operations::graph::is_edge;
+use std::str::FromStr;
+
+/// Default node size, matching `Node::new`
+pub const DEFAULT_NODE_WIDTH: f64 = 100.0;
+pub const DEFAULT_NODE_HEIGHT: f64 = 50.0;
+
+const ORIGIN_X: f64 = 50.0;
+const ORIGIN_Y: f64 = 50.0;
+const GAP: f64 = 50.0;
+const GRID_COLUMNS: usize = 5;
+
+/// How to place a node when the caller gives no position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlacementStrategy {
+    /// The slot after the last occupied grid cell, in row-major order
+    Grid,
+    /// The first grid cell that does not overlap an existing node
+    #[default]
+    NextFreeSlot,
+    /// Directly below the most recently added node
+    BelowLast,
+}
+
+impl PlacementStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlacementStrategy::Grid => "grid",
+            PlacementStrategy::NextFreeSlot => "next-free-slot",
+            PlacementStrategy::BelowLast => "below-last",
+        }
+    }
+}
+
+impl FromStr for PlacementStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "grid" => Ok(PlacementStrategy::Grid),
+            "next-free-slot" => Ok(PlacementStrategy::NextFreeSlot),
+            "below-last" => Ok(PlacementStrategy::BelowLast),
+            other => Err(format!(
+                "Unknown placement strategy '{other}' (expected grid, next-free-slot or below-last)"
+            )),
+        }
+    }
+}
+
+/// Compute a non-overlapping position for a new node of the default size
+pub fn default_position(diagram: &DiagramModel, strategy: PlacementStrategy) -> Position {
+    let occupied = node_bounds(diagram);
+
+    match strategy {
+        PlacementStrategy::Grid => {
+            let last_used = occupied
+                .iter()
+                .filter_map(grid_index_of)
+                .max()
+                .map_or(0, |index| index + 1);
+            first_free_cell(&occupied, last_used)
+        }
+        PlacementStrategy::NextFreeSlot => first_free_cell(&occupied, 0),
+        PlacementStrategy::BelowLast => match last_node_bounds(diagram) {
+            Some(last) => {
+                let mut candidate = Position {
+                    x: last.x,
+                    y: last.y + last.height + GAP,
+                };
+                while overlaps_any(&occupied, &candidate) {
+                    candidate.y += DEFAULT_NODE_HEIGHT + GAP;
+                }
+                candidate
+            }
+            None => first_free_cell(&occupied, 0),
+        },
+    }
+}
+
+/// Round a coordinate to the nearest multiple of `grid`; a grid of 0 keeps it as is
+pub fn snap_to_grid(value: f64, grid: f64) -> f64 {
+    if grid > 0.0 && value.is_finite() {
+        // Adding 0.0 turns -0.0 into 0.0
+        (value / grid).round() * grid + 0.0
+    } else {
+        value
+    }
+}
+
+pub fn snap_position(position: Position, grid: f64) -> Position {
+    Position {
+        x: snap_to_grid(position.x, grid),
+        y: snap_to_grid(position.y, grid),
+    }
+}
+
+/// Default space [`fit_to_content`] leaves around the content
+pub const CANVAS_MARGIN: f64 = 20.0;
+
+/// Smallest rectangle containing every node, or `None` when no node has bounds
+pub fn content_bounds(diagram: &DiagramModel) -> Option<Bounds> {
+    enclosing(&node_bounds(diagram))
+}
+
+/// Smallest rectangle containing every node and every edge waypoint, or
+/// `None` when the diagram has neither
+pub fn content_extent(diagram: &DiagramModel) -> Option<Bounds> {
+    let mut areas = node_bounds(diagram);
+    areas.extend(
+        diagram
+            .elements
+            .values()
+            .filter(|e| is_edge(e))
+            .flat_map(|e| e.route.iter().flatten())
+            .map(|point| Bounds {
+                x: point.x,
+                y: point.y,
+                width: 0.0,
+                height: 0.0,
+            }),
+    );
+    enclosing(&areas)
+}
+
+/// Canvas that shows `content` with `margin` on every side
+pub fn fit_to_content(content: &Bounds, margin: f64) -> Bounds {
+    Bounds {
+        x: content.x - margin,
+        y: content.y - margin,
+        width: content.width + 2.0 * margin,
+        height: content.height + 2.0 * margin,
+    }
+}
+
+fn enclosing(areas: &[Bounds]) -> Option<Bounds> {
+    let first = areas.first()?;
+    let (min_x, min_y, max_x, max_y) = areas.iter().fold(
+        (
+            first.x,
+            first.y,
+            first.x + first.width,
+            first.y + first.height,
+        ),
+        |(min_x, min_y, max_x, max_y), b| {
+            (
+                min_x.min(b.x),
+                min_y.min(b.y),
+                max_x.max(b.x + b.width),
+                max_y.max(b.y + b.height),
+            )
+        },
+    );
+    Some(Bounds {
+        x: min_x,
+        y: min_y,
+        width: max_x - min_x,
+        height: max_y - min_y,
+    })
+}
+
+/// Translate the whole diagram so its content bounds start at `origin`.
+///
+/// Every element's bounds and every edge waypoint move by the same amount,
+/// so relative positions are kept. Returns the translation applied, or
+/// `None` when no node has bounds; a zero translation leaves the diagram
+/// untouched.
+pub fn normalize_coordinates(diagram: &mut DiagramModel, origin: Position) -> Option<Position> {
+    let bounds = content_bounds(diagram)?;
+    let translation = Position {
+        x: origin.x - bounds.x,
+        y: origin.y - bounds.y,
+    };
+    if translation.x == 0.0 && translation.y == 0.0 {
+        return Some(translation);
+    }
+
+    let root_id = diagram.root.id.clone();
+    for element in diagram.elements.values_mut() {
+        if element.id == root_id {
+            continue;
+        }
+        let mut moved = false;
+        if let Some(bounds) = &mut element.bounds {
+            bounds.x += translation.x;
+            bounds.y += translation.y;
+            moved = true;
+        }
+        for point in element.route.iter_mut().flatten() {
+            point.x += translation.x;
+            point.y += translation.y;
+            moved = true;
+        }
+        if moved {
+            element.touch();
+        }
+    }
+    diagram.revision += 1;
+    diagram.updated_at = chrono::Utc::now();
+    Some(translation)
+}
+
+/// Bounds of every node in the diagram (edges and the root are skipped)
+fn node_bounds(diagram: &DiagramModel) -> Vec<Bounds> {
+    diagram
+        .elements
+        .values()
+        .filter(|e| e.id != diagram.root.id && !is_edge(e))
+        .filter_map(|e| e.bounds.clone())
+        .collect()
+}
+
+/// The most recently added node, following the root's child order
+fn last_node_bounds(diagram: &DiagramModel) -> Option<Bounds> {
+    diagram
+        .root
+        .children
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .rev()
+        .filter_map(|id| diagram.elements.get(id))
+        .find(|e| !is_edge(e))
+        .and_then(|e| e.bounds.clone())
+}
+
+fn cell_position(index: usize) -> Position {
+    Position {
+        x: ORIGIN_X + (index % GRID_COLUMNS) as f64 * (DEFAULT_NODE_WIDTH + GAP),
+        y: ORIGIN_Y + (index / GRID_COLUMNS) as f64 * (DEFAULT_NODE_HEIGHT + GAP),
+    }
+}
+
+/// Grid cell whose origin a node sits exactly on, if any
+fn grid_index_of(bounds: &Bounds) -> Option<usize> {
+    let column = (bounds.x - ORIGIN_X) / (DEFAULT_NODE_WIDTH + GAP);
+    let row = (bounds.y - ORIGIN_Y) / (DEFAULT_NODE_HEIGHT + GAP);
+    let on_grid = column >= 0.0
+        && row >= 0.0
+        && column.fract() == 0.0
+        && row.fract() == 0.0
+        && (column as usize) < GRID_COLUMNS;
+    on_grid.then(|| row as usize * GRID_COLUMNS + column as usize)
+}
+
+fn first_free_cell(occupied: &[Bounds], start: usize) -> Position {
+    (start..)
+        .map(cell_position)
+        .find(|candidate| !overlaps_any(occupied, candidate))
+        .expect("grid has unbounded free cells")
+}
+
+fn overlaps_any(occupied: &[Bounds], candidate: &Position) -> bool {
+    occupied.iter().any(|b| {
+        candidate.x < b.x + b.width
+            && candidate.x + DEFAULT_NODE_WIDTH > b.x
+            && candidate.y < b.y + b.height
+            && candidate.y + DEFAULT_NODE_HEIGHT > b.y
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Node;
+
+    fn add_node(diagram: &mut DiagramModel, x: f64, y: f64) {
+        let node = Node::new("task", Position { x, y }, None);
+        let id = node.base.id.clone();
+        diagram.add_element(node.base);
+        diagram.add_child_to_root(&id);
+    }
+
+    #[test]
+    fn test_next_free_slot_fills_gaps() {
+        let mut diagram = DiagramModel::new("workflow");
+        assert_eq!(
+            default_position(&diagram, PlacementStrategy::NextFreeSlot).x,
+            ORIGIN_X
+        );
+
+        add_node(&mut diagram, ORIGIN_X, ORIGIN_Y);
+        add_node(&mut diagram, 350.0, ORIGIN_Y);
+        let position = default_position(&diagram, PlacementStrategy::NextFreeSlot);
+        assert_eq!((position.x, position.y), (200.0, ORIGIN_Y));
+
+        let position = default_position(&diagram, PlacementStrategy::Grid);
+        assert_eq!((position.x, position.y), (500.0, ORIGIN_Y));
+    }
+
+    #[test]
+    fn test_below_last_skips_occupied_space() {
+        let mut diagram = DiagramModel::new("workflow");
+        add_node(&mut diagram, 10.0, 300.0);
+        add_node(&mut diagram, 400.0, 20.0);
+        add_node(&mut diagram, 400.0, 120.0);
+        // The last node is (400, 120), but the slot at y=220 is free
+        let position = default_position(&diagram, PlacementStrategy::BelowLast);
+        assert_eq!((position.x, position.y), (400.0, 220.0));
+
+        add_node(&mut diagram, 400.0, 220.0);
+        add_node(&mut diagram, 400.0, 20.0);
+        // Below (400, 20) are nodes at 120 and 220, so the next gap is 320
+        let position = default_position(&diagram, PlacementStrategy::BelowLast);
+        assert_eq!((position.x, position.y), (400.0, 320.0));
+    }
+
+    #[test]
+    fn test_content_bounds_covers_all_nodes() {
+        let mut diagram = DiagramModel::new("workflow");
+        assert!(content_bounds(&diagram).is_none());
+
+        add_node(&mut diagram, 10.0, 300.0);
+        add_node(&mut diagram, -40.0, 20.0);
+        let bounds = content_bounds(&diagram).unwrap();
+        assert_eq!(
+            (bounds.x, bounds.y, bounds.width, bounds.height),
+            (-40.0, 20.0, 150.0, 330.0)
+        );
+    }
+
+    #[test]
+    fn test_content_extent_includes_edge_waypoints() {
+        let mut diagram = DiagramModel::new("workflow");
+        assert!(content_extent(&diagram).is_none());
+
+        add_node(&mut diagram, 10.0, 20.0);
+        let mut edge = crate::model::Edge::new("flow", "a".to_string(), "b".to_string(), None);
+        edge.base.route = Some(vec![Position { x: 300.0, y: -30.0 }]);
+        diagram.add_element(edge.base);
+
+        let extent = content_extent(&diagram).unwrap();
+        assert_eq!(
+            (extent.x, extent.y, extent.width, extent.height),
+            (10.0, -30.0, 290.0, 100.0)
+        );
+        let canvas = fit_to_content(&extent, CANVAS_MARGIN);
+        assert_eq!(
+            (canvas.x, canvas.y, canvas.width, canvas.height),
+            (-10.0, -50.0, 330.0, 140.0)
+        );
+        // Node bounds alone ignore the waypoint
+        assert_eq!(content_bounds(&diagram).unwrap().y, 20.0);
+    }
+
+    #[test]
+    fn test_normalize_coordinates_moves_content_to_origin() {
+        let mut diagram = DiagramModel::new("workflow");
+        assert!(normalize_coordinates(&mut diagram, Position { x: 0.0, y: 0.0 }).is_none());
+
+        add_node(&mut diagram, -40.0, 20.0);
+        add_node(&mut diagram, 5000.0, 300.0);
+        let mut edge = crate::model::Edge::new("flow", "a".to_string(), "b".to_string(), None);
+        edge.base.route = Some(vec![Position { x: 0.0, y: 0.0 }]);
+        let edge_id = edge.base.id.clone();
+        diagram.add_element(edge.base);
+
+        let translation =
+            normalize_coordinates(&mut diagram, Position { x: 20.0, y: 20.0 }).unwrap();
+        assert_eq!((translation.x, translation.y), (60.0, 0.0));
+        let bounds = content_bounds(&diagram).unwrap();
+        assert_eq!((bounds.x, bounds.y, bounds.width), (20.0, 20.0, 5140.0));
+        let route = diagram.elements[&edge_id].route.as_ref().unwrap();
+        assert_eq!((route[0].x, route[0].y), (60.0, 0.0));
+
+        let revision = diagram.revision;
+        let again = normalize_coordinates(&mut diagram, Position { x: 20.0, y: 20.0 }).unwrap();
+        assert_eq!((again.x, again.y), (0.0, 0.0));
+        assert_eq!(diagram.revision, revision);
+    }
+
+    #[test]
+    fn test_snap_to_grid() {
+        assert_eq!(snap_to_grid(10.000000001, 1.0), 10.0);
+        assert_eq!(snap_to_grid(10.3, 0.5), 10.5);
+        assert_eq!(snap_to_grid(-0.2, 1.0).to_bits(), 0.0f64.to_bits());
+        assert_eq!(snap_to_grid(10.3, 0.0), 10.3);
+    }
+
+    #[test]
+    fn test_parse_strategy() {
+        assert_eq!(
+            "below-last".parse::<PlacementStrategy>(),
+            Ok(PlacementStrategy::BelowLast)
+        );
+        assert!("spiral".parse::<PlacementStrategy>().is_err());
+    }
+}
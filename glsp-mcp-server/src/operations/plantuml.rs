@@ -0,0 +1,143 @@
+//! PlantUML export
+//!
+//! Nodes become PlantUML classes with their attribute and method
+//! compartments; collapsed compartments are hidden with `hide <alias> fields`
+//! or `hide <alias> methods`. Edges become relations whose arrow depends on
+//! the edge type; undirected edges are drawn without an arrowhead.
+
+use crate::model::{DiagramModel, EdgeType, ModelElement};
+use crate::operations::compartments::{member_lines, CompartmentVisibility};
+use crate::operations::graph::{is_directed, is_edge, links};
+use std::collections::HashMap;
+
+/// Render a diagram as a PlantUML document
+pub fn to_plantuml(diagram: &DiagramModel) -> String {
+    let mut nodes: Vec<&ModelElement> = diagram
+        .elements
+        .values()
+        .filter(|e| e.id != diagram.root.id && !is_edge(e))
+        .collect();
+    // Stable output: order by position, then id
+    nodes.sort_by(|a, b| {
+        let key = |e: &ModelElement| e.bounds.as_ref().map_or((0.0, 0.0), |b| (b.y, b.x));
+        key(a)
+            .partial_cmp(&key(b))
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+
+    let aliases: HashMap<&str, String> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, e)| (e.id.as_str(), format!("N{i}")))
+        .collect();
+
+    let mut out = format!("@startuml\ntitle {}\n", diagram.name);
+
+    for node in &nodes {
+        let alias = &aliases[node.id.as_str()];
+        let name = node
+            .label
+            .as_deref()
+            .unwrap_or_else(|| node.element_type.as_str())
+            .replace('"', "'");
+        let keyword = match node.element_type.as_str() {
+            "interface" => "interface",
+            "enum" => "enum",
+            _ => "class",
+        };
+
+        out.push_str(&format!("{keyword} \"{name}\" as {alias} {{\n"));
+        let visibility = CompartmentVisibility::of(node);
+        for line in member_lines(node, "attributes") {
+            out.push_str(&format!("  {line}\n"));
+        }
+        for line in member_lines(node, "methods") {
+            out.push_str(&format!("  {line}\n"));
+        }
+        out.push_str("}\n");
+
+        if !visibility.attributes {
+            out.push_str(&format!("hide {alias} fields\n"));
+        }
+        if !visibility.methods {
+            out.push_str(&format!("hide {alias} methods\n"));
+        }
+    }
+
+    let mut relations: Vec<String> = links(diagram)
+        .filter_map(|(edge, source, target)| {
+            let source = aliases.get(source)?;
+            let target = aliases.get(target)?;
+            let edge_type = edge.element_type.as_str().parse::<EdgeType>().ok();
+            let arrow = match (edge_type, is_directed(edge)) {
+                (Some(EdgeType::Inheritance), _) => "--|>",
+                (Some(EdgeType::Realization), _) => "..|>",
+                (Some(EdgeType::Composition), _) => "*--",
+                (Some(EdgeType::Aggregation), _) => "o--",
+                (Some(EdgeType::Dependency), true) => "..>",
+                (Some(EdgeType::Dependency), false) => "..",
+                (_, true) => "-->",
+                (_, false) => "--",
+            };
+            Some(match &edge.label {
+                Some(label) => format!("{source} {arrow} {target} : {label}\n"),
+                None => format!("{source} {arrow} {target}\n"),
+            })
+        })
+        .collect();
+    relations.sort();
+    for relation in relations {
+        out.push_str(&relation);
+    }
+
+    out.push_str("@enduml\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Edge, Node, Position};
+    use crate::operations::compartments::set_compartment_visibility;
+    use serde_json::json;
+
+    #[test]
+    fn test_plantuml_hides_collapsed_compartments() {
+        let mut diagram = DiagramModel::new("uml-class");
+        let mut sensor = Node::new(
+            "class",
+            Position { x: 0.0, y: 0.0 },
+            Some("Sensor".to_string()),
+        )
+        .base;
+        sensor
+            .properties
+            .insert("attributes".to_string(), json!(["-id: String"]));
+        set_compartment_visibility(
+            &mut sensor,
+            CompartmentVisibility {
+                attributes: false,
+                methods: true,
+            },
+        );
+        let radar = Node::new(
+            "class",
+            Position { x: 0.0, y: 200.0 },
+            Some("Radar".to_string()),
+        )
+        .base;
+        let edge = Edge::new("inheritance", radar.id.clone(), sensor.id.clone(), None);
+        let association = Edge::new("association", sensor.id.clone(), radar.id.clone(), None);
+        diagram.add_element(sensor);
+        diagram.add_element(radar);
+        diagram.add_element(edge.base);
+        diagram.add_element(association.base);
+
+        let uml = to_plantuml(&diagram);
+        assert!(uml.contains("class \"Sensor\" as N0 {\n  -id: String\n}\nhide N0 fields\n"));
+        assert!(uml.contains("N1 --|> N0\n"));
+        assert!(uml.contains("N0 -- N1\n"));
+        assert!(!uml.contains("hide N1"));
+    }
+}
@@ -0,0 +1,103 @@
+//! Field projection for diagram elements
+//!
+//! Clients that only need part of each element (a minimap needs geometry,
+//! not properties) can ask for a subset of fields. Besides the serialized
+//! element fields, `position` is accepted as the `{x, y}` of the bounds.
+
+use crate::model::{DiagramModel, ModelElement};
+use serde_json::{json, Map, Value};
+
+/// Element fields that can be requested, as serialized
+pub const ELEMENT_FIELDS: &[&str] = &[
+    "id",
+    "type",
+    "children",
+    "bounds",
+    "position",
+    "layout_options",
+    "properties",
+    "label",
+    "source_id",
+    "target_id",
+    "sources",
+    "targets",
+    "route",
+    "visible",
+    "z_index",
+    "style",
+    "created_at",
+    "updated_at",
+    "created_by",
+];
+
+/// Split requested field names into known fields and unknown ones
+pub fn partition_fields<'a>(requested: &[&'a str]) -> (Vec<&'a str>, Vec<&'a str>) {
+    requested.iter().partition(|f| ELEMENT_FIELDS.contains(f))
+}
+
+/// Serialize an element keeping only `fields`; the id is always included
+pub fn project_element(element: &ModelElement, fields: &[&str]) -> Value {
+    let full = serde_json::to_value(element).unwrap_or_default();
+    let mut projected = Map::new();
+    projected.insert("id".to_string(), json!(element.id));
+
+    for &field in fields {
+        if field == "position" {
+            if let Some(bounds) = &element.bounds {
+                projected.insert(field.to_string(), json!({"x": bounds.x, "y": bounds.y}));
+            }
+        } else if let Some(value) = full.get(field) {
+            projected.insert(field.to_string(), value.clone());
+        }
+    }
+
+    Value::Object(projected)
+}
+
+/// Serialize a diagram with every element, including the root, projected
+pub fn project_diagram(diagram: &DiagramModel, fields: &[&str]) -> Value {
+    let elements: Map<String, Value> = diagram
+        .elements
+        .iter()
+        .map(|(id, element)| (id.clone(), project_element(element, fields)))
+        .collect();
+
+    json!({
+        "id": diagram.id,
+        "diagram_type": diagram.diagram_type,
+        "revision": diagram.revision,
+        "name": diagram.name,
+        "root": project_element(&diagram.root, fields),
+        "elements": elements,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Node, Position};
+
+    #[test]
+    fn test_project_positions_only() {
+        let mut diagram = DiagramModel::new("workflow");
+        let node = Node::new(
+            "task",
+            Position { x: 10.0, y: 20.0 },
+            Some("Read".to_string()),
+        );
+        let id = node.base.id.clone();
+        diagram.add_element(node.base);
+
+        let (known, unknown) = partition_fields(&["position", "colour"]);
+        assert_eq!(unknown, vec!["colour"]);
+
+        let projected = project_diagram(&diagram, &known);
+        let element = &projected["elements"][&id];
+        assert_eq!(
+            element,
+            &json!({"id": id, "position": {"x": 10.0, "y": 20.0}})
+        );
+        assert!(element.get("properties").is_none());
+        assert!(element.get("label").is_none());
+    }
+}
@@ -0,0 +1,235 @@
+//! PNG rendering of diagrams
+//!
+//! A small dependency-free rasterizer: nodes are drawn as filled, outlined
+//! rectangles and edges as lines between node centres, with an arrowhead
+//! when the edge is directed. Labels are not rendered; use the SVG export
+//! when text matters. The PNG is written with stored (uncompressed) deflate
+//! blocks, which keeps the encoder trivial at the cost of file size.
+
+use crate::model::{Bounds, DiagramModel};
+use crate::operations::graph::{is_directed, is_edge, links};
+use crate::operations::placement::{content_bounds, fit_to_content, CANVAS_MARGIN};
+
+/// Largest image dimension produced, in pixels
+pub const MAX_DIMENSION: u32 = 4096;
+
+const BACKGROUND: [u8; 3] = [255, 255, 255];
+const NODE_FILL: [u8; 3] = [173, 216, 230];
+const STROKE: [u8; 3] = [0, 0, 0];
+const ARROW_LENGTH: f64 = 10.0;
+
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Self {
+        let mut pixels = Vec::with_capacity((width * height * 3) as usize);
+        for _ in 0..width * height {
+            pixels.extend_from_slice(&BACKGROUND);
+        }
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    fn set(&mut self, x: i64, y: i64, color: [u8; 3]) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let offset = ((y as u32 * self.width + x as u32) * 3) as usize;
+        self.pixels[offset..offset + 3].copy_from_slice(&color);
+    }
+
+    fn fill_rect(&mut self, x0: i64, y0: i64, x1: i64, y1: i64, color: [u8; 3]) {
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                self.set(x, y, color);
+            }
+        }
+    }
+
+    /// Bresenham line
+    fn line(&mut self, (mut x0, mut y0): (i64, i64), (x1, y1): (i64, i64), color: [u8; 3]) {
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut error = dx + dy;
+        loop {
+            self.set(x0, y0, color);
+            if x0 == x1 && y0 == y1 {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x0 += sx;
+            }
+            if doubled <= dx {
+                error += dx;
+                y0 += sy;
+            }
+        }
+    }
+}
+
+/// Render a diagram as a PNG image
+pub fn render_png(diagram: &DiagramModel) -> Vec<u8> {
+    let nodes: Vec<&Bounds> = diagram
+        .elements
+        .values()
+        .filter(|e| e.id != diagram.root.id && !is_edge(e))
+        .filter_map(|e| e.bounds.as_ref())
+        .collect();
+
+    let canvas_bounds = content_bounds(diagram)
+        .map(|content| fit_to_content(&content, CANVAS_MARGIN))
+        .unwrap_or(Bounds {
+            x: 0.0,
+            y: 0.0,
+            width: 2.0 * CANVAS_MARGIN,
+            height: 2.0 * CANVAS_MARGIN,
+        });
+    let (origin_x, origin_y) = (canvas_bounds.x, canvas_bounds.y);
+    let to_px = |value: f64| value.round() as i64;
+
+    let mut canvas = Canvas::new(
+        (canvas_bounds.width.ceil() as u32).clamp(1, MAX_DIMENSION),
+        (canvas_bounds.height.ceil() as u32).clamp(1, MAX_DIMENSION),
+    );
+
+    for bounds in &nodes {
+        let (x0, y0) = (to_px(bounds.x - origin_x), to_px(bounds.y - origin_y));
+        let (x1, y1) = (x0 + to_px(bounds.width), y0 + to_px(bounds.height));
+        canvas.fill_rect(x0, y0, x1, y1, STROKE);
+        canvas.fill_rect(x0 + 1, y0 + 1, x1 - 1, y1 - 1, NODE_FILL);
+    }
+
+    let center = |id: &str| {
+        let b = diagram.elements.get(id)?.bounds.as_ref()?;
+        Some((
+            b.x + b.width / 2.0 - origin_x,
+            b.y + b.height / 2.0 - origin_y,
+        ))
+    };
+    for (edge, source, target) in links(diagram) {
+        let (Some((sx, sy)), Some((tx, ty))) = (center(source), center(target)) else {
+            continue;
+        };
+        canvas.line((to_px(sx), to_px(sy)), (to_px(tx), to_px(ty)), STROKE);
+
+        let length = ((tx - sx).powi(2) + (ty - sy).powi(2)).sqrt();
+        if is_directed(edge) && length > 0.0 {
+            let (ux, uy) = ((tx - sx) / length, (ty - sy) / length);
+            for side in [-1.0, 1.0] {
+                let wing = (
+                    tx - ARROW_LENGTH * ux - side * ARROW_LENGTH / 2.0 * uy,
+                    ty - ARROW_LENGTH * uy + side * ARROW_LENGTH / 2.0 * ux,
+                );
+                canvas.line(
+                    (to_px(tx), to_px(ty)),
+                    (to_px(wing.0), to_px(wing.1)),
+                    STROKE,
+                );
+            }
+        }
+    }
+
+    encode_png(&canvas)
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wrap raw bytes in a zlib stream of stored deflate blocks
+fn zlib_stored(raw: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 65_535;
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(u8::from(last));
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(raw).to_be_bytes());
+    out
+}
+
+fn encode_png(canvas: &Canvas) -> Vec<u8> {
+    let row_len = canvas.width as usize * 3;
+    let mut raw = Vec::with_capacity((row_len + 1) * canvas.height as usize);
+    for row in canvas.pixels.chunks(row_len) {
+        raw.push(0); // filter type: none
+        raw.extend_from_slice(row);
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&canvas.width.to_be_bytes());
+    header.extend_from_slice(&canvas.height.to_be_bytes());
+    header.extend_from_slice(&[8, 2, 0, 0, 0]); // 8-bit RGB, no interlace
+
+    let mut png = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Node, Position};
+
+    #[test]
+    fn test_render_png_header_and_size() {
+        let mut diagram = DiagramModel::new("workflow");
+        let node = Node::new("task", Position { x: 100.0, y: 100.0 }, None);
+        diagram.add_element(node.base);
+
+        let png = render_png(&diagram);
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        // 100x50 node plus a 20px margin on each side
+        assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 140);
+        assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 90);
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+    }
+}
@@ -0,0 +1,475 @@
+//! Generate `uml-class` diagrams from Rust source
+//!
+//! Structs become `class` nodes with their fields as attributes, enums become
+//! `enum` nodes listing their variants, and traits become `interface` nodes
+//! listing their methods. Methods of inherent `impl` blocks are added to the
+//! type they belong to. `impl Trait for Type` and supertraits become
+//! `inheritance` (generalization) edges when both ends are defined in the
+//! source.
+//!
+//! Inline modules are walked as if their items were at the top level. Items
+//! that have no class diagram counterpart (functions, macros, unions, ...)
+//! are skipped and reported as warnings.
+
+use super::compartments::{set_compartment_visibility, CompartmentVisibility};
+use crate::model::{DiagramModel, Edge, EdgeType, Node, Position, Visibility};
+use quote::ToTokens;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Diagram type produced by [`diagram_from_rust`]
+pub const RUST_DIAGRAM_TYPE: &str = "uml-class";
+
+const GRID_COLUMNS: usize = 4;
+const COLUMN_SPACING: f64 = 300.0;
+const ROW_GAP: f64 = 60.0;
+const ORIGIN: f64 = 50.0;
+
+/// Outcome of generating a diagram from Rust source
+#[derive(Debug, Clone)]
+pub struct RustTypesDiagram {
+    pub diagram: DiagramModel,
+    pub class_count: usize,
+    pub enum_count: usize,
+    pub interface_count: usize,
+    pub edge_count: usize,
+    /// Items that were skipped or could not be connected
+    pub warnings: Vec<String>,
+}
+
+/// Collected while walking the items, before nodes are laid out
+#[derive(Default)]
+struct Collector {
+    nodes: Vec<Node>,
+    /// Node index by type name
+    by_name: HashMap<String, usize>,
+    /// (type, trait) pairs from `impl Trait for Type` and supertraits
+    generalizations: Vec<(String, String)>,
+    /// Inherent methods by type name, attached once all types are known
+    methods: Vec<(String, Vec<Value>)>,
+    warnings: Vec<String>,
+}
+
+/// Parse Rust source and build a class diagram of its types
+pub fn diagram_from_rust(source: &str) -> Result<RustTypesDiagram, String> {
+    let file = syn::parse_file(source).map_err(|e| format!("Failed to parse Rust source: {e}"))?;
+
+    let mut collector = Collector::default();
+    collector.items(&file.items, "");
+
+    let Collector {
+        mut nodes,
+        by_name,
+        generalizations,
+        methods,
+        mut warnings,
+    } = collector;
+
+    for (type_name, members) in methods {
+        match by_name.get(&type_name) {
+            Some(&index) => append_members(&mut nodes[index], "methods", members),
+            None => warnings.push(format!(
+                "impl {type_name}: type is not defined in the source"
+            )),
+        }
+    }
+
+    let mut diagram = DiagramModel::new(RUST_DIAGRAM_TYPE);
+    diagram.name = "Rust types".to_string();
+
+    let mut edges = Vec::new();
+    for (type_name, trait_name) in generalizations {
+        let (Some(&source), Some(&target)) = (by_name.get(&type_name), by_name.get(&trait_name))
+        else {
+            warnings.push(format!(
+                "{type_name}: {trait_name}: both must be defined in the source to draw a generalization"
+            ));
+            continue;
+        };
+        if nodes[source].base.element_type.as_str() == "enum" {
+            warnings.push(format!(
+                "impl {trait_name} for {type_name}: enums cannot be generalizations in uml-class"
+            ));
+            continue;
+        }
+        edges.push(Edge::new(
+            EdgeType::Inheritance.as_str(),
+            nodes[source].base.id.clone(),
+            nodes[target].base.id.clone(),
+            None,
+        ));
+    }
+
+    let count = |node_type: &str| {
+        nodes
+            .iter()
+            .filter(|n| n.base.element_type.as_str() == node_type)
+            .count()
+    };
+    let (class_count, enum_count, interface_count) =
+        (count("class"), count("enum"), count("interface"));
+
+    layout(&mut nodes);
+    for node in nodes {
+        let id = node.base.id.clone();
+        diagram.add_element(node.base);
+        diagram.add_child_to_root(&id);
+    }
+    let edge_count = edges.len();
+    for edge in edges {
+        diagram.add_element(edge.base);
+    }
+
+    Ok(RustTypesDiagram {
+        diagram,
+        class_count,
+        enum_count,
+        interface_count,
+        edge_count,
+        warnings,
+    })
+}
+
+impl Collector {
+    fn items(&mut self, items: &[syn::Item], module: &str) {
+        for item in items {
+            self.item(item, module);
+        }
+    }
+
+    fn item(&mut self, item: &syn::Item, module: &str) {
+        match item {
+            syn::Item::Struct(item) => {
+                let attributes = match &item.fields {
+                    syn::Fields::Named(fields) => fields
+                        .named
+                        .iter()
+                        .map(|f| {
+                            let name = f.ident.as_ref().map(ToString::to_string);
+                            member(&name.unwrap_or_default(), &f.ty, &f.vis)
+                        })
+                        .collect(),
+                    syn::Fields::Unnamed(fields) => fields
+                        .unnamed
+                        .iter()
+                        .enumerate()
+                        .map(|(i, f)| member(&i.to_string(), &f.ty, &f.vis))
+                        .collect(),
+                    syn::Fields::Unit => Vec::new(),
+                };
+                self.add_node("class", &item.ident, &item.generics, attributes);
+            }
+            syn::Item::Enum(item) => {
+                let variants = item
+                    .variants
+                    .iter()
+                    .map(|v| {
+                        let fields = v.fields.to_token_stream();
+                        json!(format!("{}{}", v.ident, tokens_to_string(fields)))
+                    })
+                    .collect();
+                self.add_node("enum", &item.ident, &item.generics, variants);
+            }
+            syn::Item::Trait(item) => {
+                let methods = item
+                    .items
+                    .iter()
+                    .filter_map(|trait_item| match trait_item {
+                        syn::TraitItem::Fn(method) => {
+                            Some(method_member(&method.sig, Visibility::Public))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                let index = self.add_node("interface", &item.ident, &item.generics, Vec::new());
+                append_members(&mut self.nodes[index], "methods", methods);
+                for bound in &item.supertraits {
+                    if let syn::TypeParamBound::Trait(bound) = bound {
+                        if let Some(name) = last_segment(&bound.path) {
+                            self.generalizations.push((item.ident.to_string(), name));
+                        }
+                    }
+                }
+            }
+            syn::Item::Impl(item) => {
+                let syn::Type::Path(self_ty) = item.self_ty.as_ref() else {
+                    self.warnings.push(format!(
+                        "impl for {}: only named types are supported",
+                        type_string(&item.self_ty)
+                    ));
+                    return;
+                };
+                let Some(type_name) = last_segment(&self_ty.path) else {
+                    return;
+                };
+                match &item.trait_ {
+                    Some((_, path, _)) => {
+                        if let Some(trait_name) = last_segment(path) {
+                            self.generalizations.push((type_name, trait_name));
+                        }
+                    }
+                    None => {
+                        let methods = item
+                            .items
+                            .iter()
+                            .filter_map(|impl_item| match impl_item {
+                                syn::ImplItem::Fn(method) => {
+                                    Some(method_member(&method.sig, visibility(&method.vis)))
+                                }
+                                _ => None,
+                            })
+                            .collect();
+                        self.methods.push((type_name, methods));
+                    }
+                }
+            }
+            syn::Item::Mod(item) => match &item.content {
+                Some((_, items)) => self.items(items, &format!("{module}{}::", item.ident)),
+                None => self.warnings.push(format!(
+                    "mod {module}{}: out-of-line modules are not read",
+                    item.ident
+                )),
+            },
+            // Imports carry nothing to draw and are expected in any file
+            syn::Item::Use(_) | syn::Item::ExternCrate(_) => {}
+            other => self.warnings.push(format!(
+                "{}: skipped unsupported item",
+                item_description(other, module)
+            )),
+        }
+    }
+
+    fn add_node(
+        &mut self,
+        node_type: &str,
+        ident: &syn::Ident,
+        generics: &syn::Generics,
+        attributes: Vec<Value>,
+    ) -> usize {
+        let name = ident.to_string();
+        let label = format!("{name}{}", tokens_to_string(generics.to_token_stream()));
+        let mut node = Node::new(node_type, Position { x: 0.0, y: 0.0 }, Some(label));
+        node.base
+            .properties
+            .insert("attributes".to_string(), json!(attributes));
+        node.base
+            .properties
+            .insert("methods".to_string(), json!([]));
+
+        if self.by_name.contains_key(&name) {
+            self.warnings.push(format!(
+                "{name}: defined more than once; members go to the first definition"
+            ));
+        } else {
+            self.by_name.insert(name, self.nodes.len());
+        }
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+}
+
+fn append_members(node: &mut Node, compartment: &str, members: Vec<Value>) {
+    if let Some(Value::Array(existing)) = node.base.properties.get_mut(compartment) {
+        existing.extend(members);
+    }
+}
+
+/// Size every node to its compartments and place them on a grid, each row as
+/// tall as its tallest node
+fn layout(nodes: &mut [Node]) {
+    let mut y = ORIGIN;
+    for row in nodes.chunks_mut(GRID_COLUMNS) {
+        let mut row_height: f64 = 0.0;
+        for (column, node) in row.iter_mut().enumerate() {
+            node.base.bounds = None;
+            let mut bounds =
+                set_compartment_visibility(&mut node.base, CompartmentVisibility::default());
+            bounds.x = ORIGIN + column as f64 * COLUMN_SPACING;
+            bounds.y = y;
+            row_height = row_height.max(bounds.height);
+            node.base.bounds = Some(bounds);
+        }
+        y += row_height + ROW_GAP;
+    }
+}
+
+fn member(name: &str, ty: &syn::Type, vis: &syn::Visibility) -> Value {
+    json!({
+        "name": name,
+        "type": type_string(ty),
+        "visibility": visibility(vis),
+    })
+}
+
+fn method_member(sig: &syn::Signature, visibility: Visibility) -> Value {
+    let parameters: Vec<String> = sig
+        .inputs
+        .iter()
+        .filter_map(|input| match input {
+            syn::FnArg::Typed(arg) => Some(format!(
+                "{}: {}",
+                tokens_to_string(arg.pat.to_token_stream()),
+                type_string(&arg.ty)
+            )),
+            syn::FnArg::Receiver(_) => None,
+        })
+        .collect();
+    let mut member = json!({
+        "name": sig.ident.to_string(),
+        "visibility": visibility,
+        "parameters": parameters,
+    });
+    if let syn::ReturnType::Type(_, ty) = &sig.output {
+        member["type"] = json!(type_string(ty));
+    }
+    member
+}
+
+/// `pub` is public, restricted `pub(...)` is package, anything else private
+fn visibility(vis: &syn::Visibility) -> Visibility {
+    match vis {
+        syn::Visibility::Public(_) => Visibility::Public,
+        syn::Visibility::Restricted(_) => Visibility::Package,
+        syn::Visibility::Inherited => Visibility::Private,
+    }
+}
+
+fn last_segment(path: &syn::Path) -> Option<String> {
+    path.segments.last().map(|s| s.ident.to_string())
+}
+
+fn type_string(ty: &syn::Type) -> String {
+    tokens_to_string(ty.to_token_stream())
+}
+
+/// Render tokens the way the type would be written, without the spaces the
+/// token printer puts between every token
+fn tokens_to_string(tokens: proc_macro2::TokenStream) -> String {
+    let mut text = tokens.to_string();
+    for (from, to) in [
+        (" <", "<"),
+        ("< ", "<"),
+        (" >", ">"),
+        (" ::", "::"),
+        (":: ", "::"),
+        (" ,", ","),
+        ("& ", "&"),
+        ("( ", "("),
+        (" )", ")"),
+        (" (", "("),
+        ("[ ", "["),
+        (" ]", "]"),
+        (" ;", ";"),
+        (" :", ":"),
+    ] {
+        text = text.replace(from, to);
+    }
+    text.replace("->", " -> ").replace("  ", " ")
+}
+
+fn item_description(item: &syn::Item, module: &str) -> String {
+    let (kind, name) = match item {
+        syn::Item::Fn(item) => ("fn", item.sig.ident.to_string()),
+        syn::Item::Const(item) => ("const", item.ident.to_string()),
+        syn::Item::Static(item) => ("static", item.ident.to_string()),
+        syn::Item::Type(item) => ("type", item.ident.to_string()),
+        syn::Item::Union(item) => ("union", item.ident.to_string()),
+        syn::Item::TraitAlias(item) => ("trait alias", item.ident.to_string()),
+        syn::Item::Macro(item) => (
+            "macro",
+            item.ident
+                .as_ref()
+                .map(ToString::to_string)
+                .or_else(|| last_segment(&item.mac.path).map(|name| format!("{name}!")))
+                .unwrap_or_default(),
+        ),
+        syn::Item::ForeignMod(_) => ("extern block", String::new()),
+        _ => ("item", String::new()),
+    };
+    format!("{kind} {module}{name}").trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+        use std::fmt;
+
+        pub trait Shape: Named {
+            fn area(&self) -> f64;
+        }
+
+        pub trait Named {
+            fn name(&self) -> String;
+        }
+
+        pub struct Circle {
+            pub radius: f64,
+            center: (f64, f64),
+            pub(crate) tags: Vec<String>,
+        }
+
+        impl Circle {
+            pub fn new(radius: f64) -> Self {
+                Self { radius, center: (0.0, 0.0), tags: Vec::new() }
+            }
+        }
+
+        impl Shape for Circle {
+            fn area(&self) -> f64 { 3.14 * self.radius * self.radius }
+        }
+
+        impl fmt::Display for Circle {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { Ok(()) }
+        }
+
+        pub enum Color { Red, Rgb(u8, u8, u8) }
+
+        fn helper() {}
+    "#;
+
+    #[test]
+    fn test_rust_types_become_classes() {
+        let generated = diagram_from_rust(SOURCE).unwrap();
+        assert_eq!(generated.class_count, 1);
+        assert_eq!(generated.enum_count, 1);
+        assert_eq!(generated.interface_count, 2);
+        // Circle -> Shape and Shape -> Named; Display is not in the source
+        assert_eq!(generated.edge_count, 2);
+        assert_eq!(generated.warnings.len(), 2, "{:?}", generated.warnings);
+        assert!(generated.warnings.iter().any(|w| w.contains("Display")));
+        assert!(generated.warnings.iter().any(|w| w.contains("fn helper")));
+
+        let diagram = &generated.diagram;
+        let circle = diagram
+            .elements
+            .values()
+            .find(|e| e.label.as_deref() == Some("Circle"))
+            .unwrap();
+        assert_eq!(
+            circle.properties["attributes"],
+            json!([
+                {"name": "radius", "type": "f64", "visibility": "public"},
+                {"name": "center", "type": "(f64, f64)", "visibility": "private"},
+                {"name": "tags", "type": "Vec<String>", "visibility": "package"},
+            ])
+        );
+        assert_eq!(
+            circle.properties["methods"],
+            json!([{"name": "new", "type": "Self", "visibility": "public", "parameters": ["radius: f64"]}])
+        );
+
+        let color = diagram
+            .elements
+            .values()
+            .find(|e| e.label.as_deref() == Some("Color"))
+            .unwrap();
+        assert_eq!(
+            color.properties["attributes"],
+            json!(["Red", "Rgb(u8, u8, u8)"])
+        );
+
+        assert!(diagram_from_rust("struct {").is_err());
+    }
+}
@@ -0,0 +1,222 @@
+//! Default styles per element type
+//!
+//! A diagram can declare how elements of each type look, so that styles need
+//! not be repeated on every element. The defaults live in the
+//! `typeStyles` metadata entry, keyed by element type. An element overrides
+//! them with the same keys in its `style` map or, failing that, its
+//! properties; [`resolve_style`] merges them for exporters.
+
+use crate::model::{DiagramModel, ModelElement};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Metadata key holding the default style of each element type
+pub const TYPE_STYLES_KEY: &str = "typeStyles";
+
+/// Visual style of an element; unset fields fall back to the next level
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TypeStyle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fill: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stroke: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stroke_width: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font_color: Option<String>,
+}
+
+impl TypeStyle {
+    /// Reject values that are out of range or could break out of an SVG
+    /// attribute
+    pub fn validate(&self) -> Result<(), String> {
+        for (field, value) in [
+            ("fill", &self.fill),
+            ("stroke", &self.stroke),
+            ("fontFamily", &self.font_family),
+            ("fontColor", &self.font_color),
+        ] {
+            if let Some(value) = value {
+                if value.trim().is_empty() || value.contains(['"', '<', '>', '&']) {
+                    return Err(format!("Invalid {field} '{value}'"));
+                }
+            }
+        }
+        if self.stroke_width.is_some_and(|w| !w.is_finite() || w < 0.0) {
+            return Err("strokeWidth must not be negative".to_string());
+        }
+        if self.font_size.is_some_and(|s| !s.is_finite() || s <= 0.0) {
+            return Err("fontSize must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    /// Fields set in `over` replace those set here
+    fn overlay(mut self, over: TypeStyle) -> Self {
+        self.fill = over.fill.or(self.fill);
+        self.stroke = over.stroke.or(self.stroke);
+        self.stroke_width = over.stroke_width.or(self.stroke_width);
+        self.font_family = over.font_family.or(self.font_family);
+        self.font_size = over.font_size.or(self.font_size);
+        self.font_color = over.font_color.or(self.font_color);
+        self
+    }
+
+    /// Style overrides of an element; entries that are not valid style
+    /// values are ignored
+    fn of_element(element: &ModelElement) -> Self {
+        let get = |key: &str| -> Option<&Value> {
+            element
+                .style
+                .get(key)
+                .or_else(|| element.properties.get(key))
+        };
+        let text = |key: &str| get(key).and_then(Value::as_str).map(str::to_string);
+        let number = |key: &str| get(key).and_then(Value::as_f64);
+        let style = Self {
+            fill: text("fill"),
+            stroke: text("stroke"),
+            stroke_width: number("strokeWidth"),
+            font_family: text("fontFamily"),
+            font_size: number("fontSize"),
+            font_color: text("fontColor"),
+        };
+        if style.validate().is_ok() {
+            style
+        } else {
+            Self::default()
+        }
+    }
+
+    /// `fill`, `stroke` and `stroke-width` attributes for a shape
+    pub fn shape_attributes(&self, default_fill: &str) -> String {
+        format!(
+            r#" fill="{}" stroke="{}" stroke-width="{}""#,
+            self.fill.as_deref().unwrap_or(default_fill),
+            self.stroke.as_deref().unwrap_or("black"),
+            self.stroke_width.unwrap_or(1.0)
+        )
+    }
+
+    /// `stroke` and `stroke-width` attributes for a line
+    pub fn line_attributes(&self) -> String {
+        format!(
+            r#" stroke="{}" stroke-width="{}""#,
+            self.stroke.as_deref().unwrap_or("black"),
+            self.stroke_width.unwrap_or(1.0)
+        )
+    }
+
+    /// Font attributes for text; empty when no font is styled
+    pub fn text_attributes(&self) -> String {
+        let mut attributes = String::new();
+        if let Some(family) = &self.font_family {
+            attributes.push_str(&format!(r#" font-family="{family}""#));
+        }
+        if let Some(size) = self.font_size {
+            attributes.push_str(&format!(r#" font-size="{size}""#));
+        }
+        if let Some(color) = &self.font_color {
+            attributes.push_str(&format!(r#" fill="{color}""#));
+        }
+        attributes
+    }
+}
+
+/// Default styles declared by a diagram, by element type
+pub fn type_styles(diagram: &DiagramModel) -> BTreeMap<String, TypeStyle> {
+    diagram
+        .metadata
+        .get(TYPE_STYLES_KEY)
+        .and_then(|styles| serde_json::from_value(styles.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Set the default style of an element type; `None` removes it
+pub fn set_type_style(diagram: &mut DiagramModel, element_type: &str, style: Option<TypeStyle>) {
+    let mut styles = type_styles(diagram);
+    match style {
+        Some(style) => {
+            styles.insert(element_type.to_string(), style);
+        }
+        None => {
+            styles.remove(element_type);
+        }
+    }
+    if styles.is_empty() {
+        diagram.metadata.remove(TYPE_STYLES_KEY);
+    } else {
+        diagram.metadata.insert(
+            TYPE_STYLES_KEY.to_string(),
+            serde_json::to_value(styles).expect("styles serialize to JSON"),
+        );
+    }
+}
+
+/// Style of an element: the default of its type in `styles` (see
+/// [`type_styles`]) overlaid with its own overrides
+pub fn resolve_style(styles: &BTreeMap<String, TypeStyle>, element: &ModelElement) -> TypeStyle {
+    styles
+        .get(element.element_type.as_str())
+        .cloned()
+        .unwrap_or_default()
+        .overlay(TypeStyle::of_element(element))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Node, Position};
+    use serde_json::json;
+
+    #[test]
+    fn test_type_styles_apply_unless_overridden() {
+        let mut diagram = DiagramModel::new("workflow");
+        let plain = Node::new("task", Position { x: 0.0, y: 0.0 }, None);
+        let mut custom = Node::new("task", Position { x: 200.0, y: 0.0 }, None);
+        custom
+            .base
+            .properties
+            .insert("fill".to_string(), json!("red"));
+        let (plain, custom) = (plain.base, custom.base);
+
+        set_type_style(
+            &mut diagram,
+            "task",
+            Some(TypeStyle {
+                fill: Some("#eef".to_string()),
+                font_size: Some(14.0),
+                ..Default::default()
+            }),
+        );
+        let styles = type_styles(&diagram);
+        assert_eq!(resolve_style(&styles, &plain).fill.as_deref(), Some("#eef"));
+        let style = resolve_style(&styles, &custom);
+        assert_eq!(style.fill.as_deref(), Some("red"));
+        assert_eq!(style.font_size, Some(14.0));
+        assert_eq!(
+            style.shape_attributes("lightblue"),
+            r#" fill="red" stroke="black" stroke-width="1""#
+        );
+
+        assert!(TypeStyle {
+            fill: Some(r#"red" onload="x"#.to_string()),
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+
+        set_type_style(&mut diagram, "task", None);
+        assert!(!diagram.metadata.contains_key(TYPE_STYLES_KEY));
+        assert_eq!(
+            resolve_style(&type_styles(&diagram), &plain),
+            TypeStyle::default()
+        );
+    }
+}
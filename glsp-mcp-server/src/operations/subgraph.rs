@@ -0,0 +1,227 @@
+//! Extracting part of a diagram into its own, linked diagram
+//!
+//! The selected nodes, their nested children and every edge running between
+//! them move to a new diagram under their existing IDs. In the original they
+//! are replaced by one reference node whose `subdiagramId` property holds the
+//! new diagram's ID; edges that crossed the boundary are reconnected to it.
+//! The new diagram records the original's ID under the `parentDiagramId`
+//! metadata key, so the hierarchy can be walked in both directions.
+
+use crate::model::{DiagramModel, ModelElement, Node, Position};
+use crate::operations::graph::{endpoints, is_edge};
+use crate::operations::placement::content_bounds;
+use std::collections::{BTreeSet, HashSet};
+
+/// Node type of the element left behind by an extraction
+pub const SUBDIAGRAM_REFERENCE_TYPE: &str = "subdiagram-reference";
+
+/// Property of a reference node holding the linked diagram's ID
+pub const SUBDIAGRAM_PROPERTY: &str = "subdiagramId";
+
+/// Metadata key of an extracted diagram holding the diagram it came from
+pub const PARENT_DIAGRAM_KEY: &str = "parentDiagramId";
+
+/// Result of `extract_subgraph`
+#[derive(Debug, Clone)]
+pub struct Extraction {
+    pub diagram: DiagramModel,
+    pub reference_id: String,
+    /// Nodes and edges now in the new diagram, sorted
+    pub moved: Vec<String>,
+    /// Boundary edges that stayed behind and now end at the reference node, sorted
+    pub reconnected: Vec<String>,
+}
+
+/// Move `node_ids` (with their children) out of `source` into a new diagram named `name`.
+///
+/// Fails without changing `source` when an ID is unknown, the root or an edge.
+pub fn extract_subgraph(
+    source: &mut DiagramModel,
+    node_ids: &[String],
+    name: &str,
+) -> Result<Extraction, String> {
+    if node_ids.is_empty() {
+        return Err("No nodes selected".to_string());
+    }
+    for id in node_ids {
+        match source.elements.get(id) {
+            None => return Err(format!("Node {id} not found")),
+            Some(_) if *id == source.root.id => {
+                return Err("The root element cannot be extracted".to_string())
+            }
+            Some(element) if is_edge(element) => {
+                return Err(format!("{id} is an edge; select nodes only"))
+            }
+            Some(_) => {}
+        }
+    }
+
+    // Selected nodes and everything nested in them
+    let mut selected: BTreeSet<String> = BTreeSet::new();
+    let mut pending: Vec<String> = node_ids.to_vec();
+    while let Some(id) = pending.pop() {
+        if selected.insert(id.clone()) {
+            if let Some(children) = source.elements.get(&id).and_then(|e| e.children.as_ref()) {
+                pending.extend(children.iter().cloned());
+            }
+        }
+    }
+
+    let mut internal_edges = BTreeSet::new();
+    let mut boundary_edges = BTreeSet::new();
+    for element in source.elements.values().filter(|e| is_edge(e)) {
+        let ends = endpoints(element);
+        let inside = ends.iter().filter(|(_, id)| selected.contains(*id)).count();
+        if inside == ends.len() {
+            internal_edges.insert(element.id.clone());
+        } else if inside > 0 {
+            boundary_edges.insert(element.id.clone());
+        }
+    }
+
+    let moved: BTreeSet<String> = selected.union(&internal_edges).cloned().collect();
+    let mut extracted = DiagramModel::new(&source.diagram_type);
+    extracted.name = name.to_string();
+    extracted
+        .metadata
+        .insert(PARENT_DIAGRAM_KEY.to_string(), serde_json::json!(source.id));
+
+    // Elements that were not nested in another moved element become top-level
+    let nested: HashSet<&String> = moved
+        .iter()
+        .filter_map(|id| source.elements.get(id))
+        .filter_map(|e| e.children.as_ref())
+        .flatten()
+        .collect();
+    let top_level: Vec<String> = moved
+        .iter()
+        .filter(|id| !nested.contains(id))
+        .cloned()
+        .collect();
+
+    for id in &moved {
+        if let Some(element) = source.remove_element(id) {
+            extracted.add_element(element);
+        }
+    }
+    for id in &top_level {
+        extracted.add_child_to_root(id);
+    }
+    extracted.revision = 0;
+
+    let origin = content_bounds(&extracted)
+        .map_or(Position { x: 0.0, y: 0.0 }, |b| Position { x: b.x, y: b.y });
+    let mut reference = Node::new(SUBDIAGRAM_REFERENCE_TYPE, origin, Some(name.to_string()));
+    reference.base.properties.insert(
+        SUBDIAGRAM_PROPERTY.to_string(),
+        serde_json::json!(extracted.id),
+    );
+    let reference_id = reference.base.id.clone();
+
+    // Drop moved elements from the child lists left behind
+    let detach = |children: &mut Option<Vec<String>>| {
+        if let Some(children) = children {
+            children.retain(|c| !moved.contains(c));
+        }
+    };
+    detach(&mut source.root.children);
+    for element in source.elements.values_mut() {
+        detach(&mut element.children);
+    }
+
+    for id in &boundary_edges {
+        if let Some(edge) = source.elements.get_mut(id) {
+            for endpoint in [&mut edge.source_id, &mut edge.target_id] {
+                if endpoint.as_ref().is_some_and(|e| selected.contains(e)) {
+                    *endpoint = Some(reference_id.clone());
+                }
+            }
+            for ids in [&mut edge.sources, &mut edge.targets].into_iter().flatten() {
+                let mut linked = false;
+                ids.retain(|id| {
+                    let moved = selected.contains(id);
+                    linked |= moved;
+                    !moved
+                });
+                if linked {
+                    ids.push(reference_id.clone());
+                }
+            }
+            edge.touch();
+        }
+    }
+
+    source.add_element(reference.base);
+    source.add_child_to_root(&reference_id);
+
+    Ok(Extraction {
+        diagram: extracted,
+        reference_id,
+        moved: moved.into_iter().collect(),
+        reconnected: boundary_edges.into_iter().collect(),
+    })
+}
+
+/// Linked diagram of a reference node, if the element is one
+pub fn subdiagram_link(element: &ModelElement) -> Option<&str> {
+    element
+        .properties
+        .get(SUBDIAGRAM_PROPERTY)
+        .and_then(|v| v.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Edge;
+
+    fn add_node(diagram: &mut DiagramModel, x: f64, y: f64) -> String {
+        let node = Node::new("task", Position { x, y }, None);
+        let id = node.base.id.clone();
+        diagram.add_element(node.base);
+        diagram.add_child_to_root(&id);
+        id
+    }
+
+    fn add_edge(diagram: &mut DiagramModel, source: &str, target: &str) -> String {
+        let edge = Edge::new("flow", source.to_string(), target.to_string(), None);
+        let id = edge.base.id.clone();
+        diagram.add_element(edge.base);
+        id
+    }
+
+    #[test]
+    fn test_extract_moves_nodes_and_reconnects_boundary_edges() {
+        let mut diagram = DiagramModel::new("workflow");
+        let input = add_node(&mut diagram, 0.0, 0.0);
+        let a = add_node(&mut diagram, 200.0, 100.0);
+        let b = add_node(&mut diagram, 400.0, 100.0);
+        let incoming = add_edge(&mut diagram, &input, &a);
+        let internal = add_edge(&mut diagram, &a, &b);
+
+        let extraction =
+            extract_subgraph(&mut diagram, &[a.clone(), b.clone()], "Perception").unwrap();
+        let extracted = &extraction.diagram;
+        assert_eq!(extracted.name, "Perception");
+        assert_eq!(
+            extracted.metadata[PARENT_DIAGRAM_KEY],
+            serde_json::json!(diagram.id)
+        );
+        assert!(extracted.elements.contains_key(&a) && extracted.elements.contains_key(&internal));
+        assert_eq!(extraction.reconnected, vec![incoming.clone()]);
+
+        assert!(!diagram.elements.contains_key(&a) && !diagram.elements.contains_key(&internal));
+        let reference = &diagram.elements[&extraction.reference_id];
+        assert_eq!(subdiagram_link(reference), Some(extracted.id.as_str()));
+        let bounds = reference.bounds.as_ref().unwrap();
+        assert_eq!((bounds.x, bounds.y), (200.0, 100.0));
+        assert_eq!(
+            diagram.elements[&incoming].target_id.as_ref(),
+            Some(&extraction.reference_id)
+        );
+        let children = diagram.root.children.as_ref().unwrap();
+        assert!(!children.contains(&a) && children.contains(&extraction.reference_id));
+
+        assert!(extract_subgraph(&mut diagram, &[incoming], "Edge").is_err());
+    }
+}
@@ -0,0 +1,150 @@
+//! Valid targets for a new edge
+//!
+//! Editors offer only the nodes an edge could actually connect to. A node is
+//! a valid target when the diagram type allows the edge between the two node
+//! types (see [`crate::operations::capabilities`]), when no edge of the same
+//! type already joins the pair, and, for the hierarchy edge types in
+//! [`ACYCLIC_EDGE_TYPES`], when the edge would not close a cycle.
+
+use crate::model::DiagramModel;
+use crate::operations::capabilities::{diagram_type_spec, ACYCLIC_EDGE_TYPES};
+use crate::operations::graph::{default_directed, is_directed, is_edge, links};
+use std::collections::{BTreeSet, HashSet};
+
+/// Nodes a new `edge_type` edge from `source_id` could connect to, sorted by ID
+pub fn suggest_targets(
+    diagram: &DiagramModel,
+    source_id: &str,
+    edge_type: &str,
+) -> Result<Vec<String>, String> {
+    let source = match diagram.elements.get(source_id) {
+        Some(element) if element.id != diagram.root.id && !is_edge(element) => element,
+        Some(_) => return Err(format!("Element {source_id} is not a node")),
+        None => return Err(format!("Source element {source_id} not found")),
+    };
+    let spec = diagram_type_spec(&diagram.diagram_type);
+    if let Some(spec) = spec {
+        if !spec.edge_types.iter().any(|e| e.edge_type == edge_type) {
+            // Reuse the message listing the allowed edge types
+            spec.check_edge(edge_type, "", "")?;
+        }
+    }
+
+    let same_type: Vec<(&str, &str, bool)> = links(diagram)
+        .filter(|(edge, _, _)| edge.element_type.as_str() == edge_type)
+        .map(|(edge, from, to)| (from, to, is_directed(edge)))
+        .collect();
+    let directed = default_directed(edge_type);
+    let connected: HashSet<&str> = same_type
+        .iter()
+        .filter_map(|&(from, to, edge_directed)| {
+            if from == source_id {
+                Some(to)
+            } else if to == source_id && !(directed && edge_directed) {
+                Some(from)
+            } else {
+                None
+            }
+        })
+        .collect();
+    let ancestors = if ACYCLIC_EDGE_TYPES.contains(&edge_type) {
+        reaching(source_id, &same_type)
+    } else {
+        HashSet::new()
+    };
+
+    let source_type = source.element_type.as_str();
+    let targets: BTreeSet<String> = diagram
+        .elements
+        .values()
+        .filter(|e| e.id != diagram.root.id && !is_edge(e))
+        .filter(|e| {
+            spec.is_none_or(|spec| {
+                spec.check_edge(edge_type, source_type, e.element_type.as_str())
+                    .is_ok()
+            })
+        })
+        .filter(|e| !connected.contains(e.id.as_str()))
+        .filter(|e| !ancestors.contains(e.id.as_str()))
+        .map(|e| e.id.clone())
+        .collect();
+    Ok(targets.into_iter().collect())
+}
+
+/// Nodes from which `node` can be reached over `edges`, `node` included
+fn reaching<'a>(node: &'a str, edges: &[(&'a str, &'a str, bool)]) -> HashSet<&'a str> {
+    let mut reached = HashSet::from([node]);
+    let mut pending = vec![node];
+    while let Some(current) = pending.pop() {
+        for &(from, to, directed) in edges {
+            let predecessor = if to == current {
+                Some(from)
+            } else if from == current && !directed {
+                Some(to)
+            } else {
+                None
+            };
+            if let Some(predecessor) = predecessor {
+                if reached.insert(predecessor) {
+                    pending.push(predecessor);
+                }
+            }
+        }
+    }
+    reached
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Edge, Node, Position};
+
+    fn add_node(diagram: &mut DiagramModel, node_type: &str) -> String {
+        let mut node = Node::new(node_type, Position { x: 0.0, y: 0.0 }, None);
+        node.base.id = format!("{node_type}-{}", diagram.elements.len());
+        let id = node.base.id.clone();
+        diagram.add_element(node.base);
+        diagram.add_child_to_root(&id);
+        id
+    }
+
+    fn add_edge(diagram: &mut DiagramModel, edge_type: &str, source: &str, target: &str) {
+        let edge = Edge::new(edge_type, source.to_string(), target.to_string(), None);
+        diagram.add_element(edge.base);
+    }
+
+    #[test]
+    fn test_suggest_targets_skips_invalid_duplicate_and_cyclic_edges() {
+        let mut diagram = DiagramModel::new("uml-class");
+        let base = add_node(&mut diagram, "class");
+        let derived = add_node(&mut diagram, "class");
+        let leaf = add_node(&mut diagram, "class");
+        let package = add_node(&mut diagram, "package");
+        add_edge(&mut diagram, "inheritance", &derived, &base);
+        add_edge(&mut diagram, "inheritance", &leaf, &derived);
+
+        // Only classifiers can be inherited from, and never a descendant
+        assert_eq!(
+            suggest_targets(&diagram, &derived, "inheritance").unwrap(),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            suggest_targets(&diagram, &base, "inheritance").unwrap(),
+            Vec::<String>::new()
+        );
+        let other = add_node(&mut diagram, "interface");
+        assert_eq!(
+            suggest_targets(&diagram, &leaf, "inheritance").unwrap(),
+            vec![base.clone(), other.clone()]
+        );
+
+        // Dependencies may point anywhere, but only once
+        add_edge(&mut diagram, "dependency", &leaf, &package);
+        let targets = suggest_targets(&diagram, &leaf, "dependency").unwrap();
+        assert!(!targets.contains(&package));
+        assert!(targets.contains(&leaf) && targets.contains(&base));
+
+        assert!(suggest_targets(&diagram, &leaf, "flow").is_err());
+        assert!(suggest_targets(&diagram, "missing", "dependency").is_err());
+    }
+}
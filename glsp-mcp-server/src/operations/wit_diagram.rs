@@ -0,0 +1,529 @@
+//! Generate `wit-schema` diagrams from WIT source
+//!
+//! Every interface in the parsed package becomes an `interface` node whose
+//! functions are listed in its `functions` property, and every world becomes
+//! a `world` node connected to the interfaces it pulls in by `import` and
+//! `export` edges. Interfaces are laid out on a grid below the worlds. Edges
+//! are stored as `create_edge` stores them (see [`edge_element`]).
+//!
+//! The same layout renders a component [`DependencyGraph`], with `component`
+//! nodes in place of worlds.
+//!
+//! [`validate_wit`] runs the same parser without building a diagram, for a
+//! quick check of WIT source before a component is built.
+
+use crate::model::{DiagramModel, ModelElement, Node, Position};
+use crate::operations::graph::{default_directed, edge_element};
+use crate::wasm::DependencyGraph;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use wit_parser::{Function, InterfaceId, Resolve, Results, Type, WorldItem, WorldKey};
+
+/// Diagram type produced by [`diagram_from_wit`]
+pub const WIT_DIAGRAM_TYPE: &str = "wit-schema";
+
+const GRID_COLUMNS: usize = 4;
+const COLUMN_SPACING: f64 = 260.0;
+const ROW_SPACING: f64 = 200.0;
+const WORLD_ROW_Y: f64 = 50.0;
+const INTERFACE_ROW_Y: f64 = 250.0;
+
+/// Outcome of generating a diagram from WIT source
+#[derive(Debug, Clone)]
+pub struct WitDiagram {
+    pub diagram: DiagramModel,
+    pub interface_count: usize,
+    pub world_count: usize,
+    pub edge_count: usize,
+}
+
+/// Parse WIT source and build a diagram of its interfaces and worlds.
+///
+/// The source must describe a single package; foreign packages it refers to
+/// cannot be resolved here and are reported as parse errors.
+pub fn diagram_from_wit(source: &str) -> Result<WitDiagram, String> {
+    let mut resolve = Resolve::new();
+    let package_id = resolve
+        .push_str("input.wit", source)
+        .map_err(|e| format!("Failed to parse WIT: {e:#}"))?;
+    let package = &resolve.packages[package_id];
+
+    let mut diagram = DiagramModel::new(WIT_DIAGRAM_TYPE);
+    diagram.name = package.name.to_string();
+    diagram
+        .metadata
+        .insert("witPackage".to_string(), json!(package.name.to_string()));
+
+    // Interfaces declared by the package, in source order. Inline world
+    // interfaces are added when the worlds are visited below.
+    let mut interface_ids: Vec<(String, InterfaceId)> = package
+        .interfaces
+        .iter()
+        .map(|(name, id)| (name.clone(), *id))
+        .collect();
+
+    let mut edges: Vec<(usize, InterfaceId, &'static str)> = Vec::new();
+    let mut world_nodes: Vec<Node> = Vec::new();
+
+    for (index, world_id) in package.worlds.values().enumerate() {
+        let world = &resolve.worlds[*world_id];
+        let mut functions = Vec::new();
+
+        let items = world
+            .imports
+            .iter()
+            .map(|item| (item, "import"))
+            .chain(world.exports.iter().map(|item| (item, "export")));
+
+        for ((key, item), direction) in items {
+            match item {
+                WorldItem::Interface { id, .. } => {
+                    if !interface_ids.iter().any(|(_, known)| known == id) {
+                        interface_ids.push((interface_name(&resolve, key, *id), *id));
+                    }
+                    edges.push((index, *id, direction));
+                }
+                WorldItem::Function(func) => {
+                    let mut member = function_member(&resolve, func);
+                    member["direction"] = json!(direction);
+                    functions.push(member);
+                }
+                WorldItem::Type(_) => {}
+            }
+        }
+
+        let mut node = Node::new(
+            "world",
+            grid_position(index, WORLD_ROW_Y),
+            Some(world.name.clone()),
+        );
+        node.base
+            .properties
+            .insert("functions".to_string(), json!(functions));
+        world_nodes.push(node);
+    }
+
+    let mut interface_nodes: HashMap<InterfaceId, String> = HashMap::new();
+    for (index, (name, id)) in interface_ids.iter().enumerate() {
+        let interface = &resolve.interfaces[*id];
+        let functions: Vec<_> = interface
+            .functions
+            .values()
+            .map(|func| function_member(&resolve, func))
+            .collect();
+
+        // Only imported interfaces get the import badge; everything else
+        // is shown as provided by the package.
+        let imported_only = edges
+            .iter()
+            .filter(|(_, target, _)| target == id)
+            .all(|(_, _, direction)| *direction == "import")
+            && edges.iter().any(|(_, target, _)| target == id);
+
+        let mut node = Node::new(
+            "interface",
+            grid_position(index, INTERFACE_ROW_Y),
+            Some(name.clone()),
+        );
+        let properties = &mut node.base.properties;
+        properties.insert("functions".to_string(), json!(functions));
+        properties.insert(
+            "types".to_string(),
+            json!(interface.types.keys().collect::<Vec<_>>()),
+        );
+        properties.insert(
+            "interfaceType".to_string(),
+            json!(if imported_only { "import" } else { "export" }),
+        );
+
+        interface_nodes.insert(*id, node.base.id.clone());
+        add_node(&mut diagram, node);
+    }
+
+    let world_ids: Vec<String> = world_nodes.iter().map(|n| n.base.id.clone()).collect();
+    let world_count = world_nodes.len();
+    for node in world_nodes {
+        add_node(&mut diagram, node);
+    }
+
+    let edge_count = edges.len();
+    for (world_index, interface_id, direction) in edges {
+        add_edge(
+            &mut diagram,
+            direction,
+            &world_ids[world_index],
+            &interface_nodes[&interface_id],
+        );
+    }
+
+    Ok(WitDiagram {
+        diagram,
+        interface_count: interface_nodes.len(),
+        world_count,
+        edge_count,
+    })
+}
+
+/// Render a component dependency graph.
+///
+/// Every interface imported or exported by a component becomes an
+/// `interface` node; imports no one provides are marked `unsatisfied`.
+pub fn diagram_from_dependency_graph(graph: &DependencyGraph) -> DiagramModel {
+    let mut diagram = DiagramModel::new(WIT_DIAGRAM_TYPE);
+    diagram.name = "Component dependencies".to_string();
+
+    let mut component_ids = HashMap::new();
+    for (index, component) in graph.components.iter().enumerate() {
+        let mut node = Node::new(
+            "component",
+            grid_position(index, WORLD_ROW_Y),
+            Some(component.component.clone()),
+        );
+        node.base
+            .properties
+            .insert("componentName".to_string(), json!(component.component));
+        component_ids.insert(component.component.as_str(), node.base.id.clone());
+        add_node(&mut diagram, node);
+    }
+
+    let mut interface_ids: Vec<(String, String)> = Vec::new();
+    let mut interface_id = |diagram: &mut DiagramModel, name: &str| -> String {
+        if let Some((_, id)) = interface_ids.iter().find(|(known, _)| known == name) {
+            return id.clone();
+        }
+        let mut node = Node::new(
+            "interface",
+            grid_position(interface_ids.len(), INTERFACE_ROW_Y),
+            Some(name.to_string()),
+        );
+        let unsatisfied = graph.unsatisfied.iter().any(|u| u.interface == name);
+        let properties = &mut node.base.properties;
+        properties.insert("unsatisfied".to_string(), json!(unsatisfied));
+        properties.insert(
+            "interfaceType".to_string(),
+            json!(if unsatisfied { "import" } else { "export" }),
+        );
+        let id = node.base.id.clone();
+        interface_ids.push((name.to_string(), id.clone()));
+        add_node(diagram, node);
+        id
+    };
+
+    let mut edges = Vec::new();
+    for component in &graph.components {
+        let source = &component_ids[component.component.as_str()];
+        for import in &component.imports {
+            let target = interface_id(&mut diagram, &import.interface);
+            edges.push(("import", source.clone(), target));
+        }
+        for export in &component.exports {
+            let target = interface_id(&mut diagram, export);
+            edges.push(("export", source.clone(), target));
+        }
+    }
+
+    for (direction, source, target) in edges {
+        add_edge(&mut diagram, direction, &source, &target);
+    }
+
+    diagram
+}
+
+fn add_node(diagram: &mut DiagramModel, node: Node) {
+    add_to_root(diagram, node.base);
+}
+
+/// Add an `import` or `export` edge labelled `imports` or `exports`
+fn add_edge(diagram: &mut DiagramModel, direction: &str, source: &str, target: &str) {
+    let label = Some(format!("{direction}s"));
+    let edge = edge_element(
+        direction,
+        source,
+        target,
+        label,
+        default_directed(direction),
+    );
+    add_to_root(diagram, edge);
+}
+
+fn add_to_root(diagram: &mut DiagramModel, element: ModelElement) {
+    let id = element.id.clone();
+    diagram.add_element(element);
+    diagram.add_child_to_root(&id);
+}
+
+fn grid_position(index: usize, top: f64) -> Position {
+    Position {
+        x: 50.0 + (index % GRID_COLUMNS) as f64 * COLUMN_SPACING,
+        y: top + (index / GRID_COLUMNS) as f64 * ROW_SPACING,
+    }
+}
+
+fn interface_name(resolve: &Resolve, key: &WorldKey, id: InterfaceId) -> String {
+    match key {
+        WorldKey::Name(name) => name.clone(),
+        WorldKey::Interface(_) => resolve
+            .id_of(id)
+            .or_else(|| resolve.interfaces[id].name.clone())
+            .unwrap_or_else(|| "unnamed".to_string()),
+    }
+}
+
+/// A function as a diagram member: its name and rendered signature
+fn function_member(resolve: &Resolve, func: &Function) -> serde_json::Value {
+    let params = func
+        .params
+        .iter()
+        .map(|(name, ty)| format!("{name}: {}", type_name(resolve, ty)))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let results = match &func.results {
+        Results::Anon(ty) => format!(" -> {}", type_name(resolve, ty)),
+        Results::Named(named) if named.is_empty() => String::new(),
+        Results::Named(named) => format!(
+            " -> ({})",
+            named
+                .iter()
+                .map(|(name, ty)| format!("{name}: {}", type_name(resolve, ty)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+
+    json!({
+        "name": func.name,
+        "signature": format!("func({params}){results}"),
+    })
+}
+
+fn type_name(resolve: &Resolve, ty: &Type) -> String {
+    match ty {
+        Type::Id(id) => resolve.types[*id]
+            .name
+            .clone()
+            .unwrap_or_else(|| "anonymous".to_string()),
+        Type::Bool => "bool".to_string(),
+        Type::U8 => "u8".to_string(),
+        Type::U16 => "u16".to_string(),
+        Type::U32 => "u32".to_string(),
+        Type::U64 => "u64".to_string(),
+        Type::S8 => "s8".to_string(),
+        Type::S16 => "s16".to_string(),
+        Type::S32 => "s32".to_string(),
+        Type::S64 => "s64".to_string(),
+        Type::F32 => "f32".to_string(),
+        Type::F64 => "f64".to_string(),
+        Type::Char => "char".to_string(),
+        Type::String => "string".to_string(),
+    }
+}
+
+/// A parse error with its position in the source, when the parser gave one
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WitError {
+    pub message: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+/// Summary of a world: its name and how many items it imports and exports
+#[derive(Debug, Clone, Serialize)]
+pub struct WitWorldSummary {
+    pub name: String,
+    pub imports: usize,
+    pub exports: usize,
+}
+
+/// Summary of an interface declared by the package
+#[derive(Debug, Clone, Serialize)]
+pub struct WitInterfaceSummary {
+    pub name: String,
+    pub functions: Vec<serde_json::Value>,
+}
+
+/// Summary of a successfully parsed package
+#[derive(Debug, Clone, Serialize)]
+pub struct WitPackageSummary {
+    pub name: String,
+    pub version: Option<String>,
+    pub interfaces: Vec<WitInterfaceSummary>,
+    pub worlds: Vec<WitWorldSummary>,
+}
+
+/// Parse WIT source, returning a summary of its package or the parse errors.
+///
+/// The parser stops at the first error, so the list holds a single entry.
+pub fn validate_wit(source: &str) -> Result<WitPackageSummary, Vec<WitError>> {
+    let mut resolve = Resolve::new();
+    let package_id = resolve
+        .push_str("input.wit", source)
+        .map_err(|e| vec![wit_error(&format!("{e:?}"))])?;
+    let package = &resolve.packages[package_id];
+
+    let interfaces = package
+        .interfaces
+        .iter()
+        .map(|(name, id)| WitInterfaceSummary {
+            name: name.clone(),
+            functions: resolve.interfaces[*id]
+                .functions
+                .values()
+                .map(|func| function_member(&resolve, func))
+                .collect(),
+        })
+        .collect();
+
+    let worlds = package
+        .worlds
+        .values()
+        .map(|id| {
+            let world = &resolve.worlds[*id];
+            WitWorldSummary {
+                name: world.name.clone(),
+                imports: world.imports.len(),
+                exports: world.exports.len(),
+            }
+        })
+        .collect();
+
+    Ok(WitPackageSummary {
+        name: package.name.to_string(),
+        version: package.name.version.as_ref().map(|v| v.to_string()),
+        interfaces,
+        worlds,
+    })
+}
+
+/// Split a rendered parser error into its message and `--> file:line:col`
+/// location. Lines of context (`Caused by:`, source excerpts) are dropped.
+fn wit_error(rendered: &str) -> WitError {
+    let mut message = Vec::new();
+    let mut position = None;
+    for line in rendered.lines().map(str::trim) {
+        if let Some(location) = line.strip_prefix("-->") {
+            let mut parts = location.trim().rsplitn(3, ':');
+            let column = parts.next().and_then(|c| c.parse().ok());
+            let row = parts.next().and_then(|l| l.parse().ok());
+            if let (Some(row), Some(column)) = (row, column) {
+                position.get_or_insert((row, column));
+            }
+        } else if position.is_none()
+            && !line.is_empty()
+            && !line.starts_with('|')
+            && line != "Caused by:"
+        {
+            message.push(line);
+        }
+    }
+
+    WitError {
+        message: message.join(": "),
+        line: position.map(|(line, _)| line),
+        column: position.map(|(_, column)| column),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::graph::edges;
+
+    const SAMPLE_WIT: &str = r#"
+        package example:sensors@0.1.0;
+
+        interface readings {
+            record reading {
+                value: f64,
+                timestamp: u64,
+            }
+            latest: func(sensor: string) -> reading;
+            count: func() -> u32;
+        }
+
+        interface logging {
+            log: func(message: string);
+        }
+
+        world sensor-node {
+            import logging;
+            export readings;
+        }
+    "#;
+
+    #[test]
+    fn test_diagram_from_wit() {
+        let result = diagram_from_wit(SAMPLE_WIT).unwrap();
+        assert_eq!(result.interface_count, 2);
+        assert_eq!(result.world_count, 1);
+        assert_eq!(result.edge_count, 2);
+
+        let diagram = &result.diagram;
+        assert_eq!(diagram.diagram_type, WIT_DIAGRAM_TYPE);
+
+        let readings = diagram
+            .elements
+            .values()
+            .find(|e| e.label.as_deref() == Some("readings"))
+            .unwrap();
+        let functions = readings.properties["functions"].as_array().unwrap();
+        assert_eq!(functions.len(), 2);
+        assert_eq!(
+            functions[0]["signature"],
+            json!("func(sensor: string) -> reading")
+        );
+
+        let logging = diagram
+            .elements
+            .values()
+            .find(|e| e.label.as_deref() == Some("logging"))
+            .unwrap();
+        assert_eq!(logging.properties["interfaceType"], json!("import"));
+
+        let mut kinds: Vec<_> = edges(diagram)
+            .map(|e| (e.element_type.as_str().to_string(), e.target_id.clone()))
+            .collect();
+        kinds.sort();
+        assert_eq!(kinds[0], ("export".to_string(), Some(readings.id.clone())));
+        assert_eq!(kinds[1], ("import".to_string(), Some(logging.id.clone())));
+
+        // Edges are stored like create_edge stores them
+        let children = diagram.root.children.as_deref().unwrap_or_default();
+        for edge in edges(diagram) {
+            assert!(children.contains(&edge.id));
+            assert_eq!(edge.properties["sourceId"], json!(edge.source_id));
+            assert_eq!(edge.properties["targetId"], json!(edge.target_id));
+            assert_eq!(edge.properties["directed"], json!(true));
+        }
+        assert_eq!(
+            functions[1]["signature"],
+            json!("func() -> u32"),
+            "primitive types are shown as WIT keywords"
+        );
+    }
+
+    #[test]
+    fn test_diagram_from_invalid_wit() {
+        assert!(diagram_from_wit("package broken").is_err());
+    }
+
+    #[test]
+    fn test_validate_wit() {
+        let summary = validate_wit(SAMPLE_WIT).unwrap();
+        assert_eq!(summary.name, "example:sensors@0.1.0");
+        assert_eq!(summary.version.as_deref(), Some("0.1.0"));
+        assert_eq!(summary.interfaces.len(), 2);
+        assert_eq!(summary.interfaces[0].functions.len(), 2);
+        assert_eq!(summary.worlds[0].imports, 1);
+        assert_eq!(summary.worlds[0].exports, 1);
+
+        let errors = validate_wit(
+            "package test:component;\n\ninterface calculator {\n    add: func(a: s32) -> s32\n}\n",
+        )
+        .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, Some(5));
+        assert!(!errors[0].message.is_empty());
+    }
+}
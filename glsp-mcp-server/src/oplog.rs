@@ -0,0 +1,268 @@
+//! Append-only operation log for saved diagrams
+//!
+//! Rewriting a diagram's content and layout files on every edit costs time
+//! proportional to the whole diagram, however small the edit. With the log
+//! enabled, a save appends one line to `{name}.glsp.log` instead, describing
+//! what changed since the previous save: elements created, moved, updated or
+//! deleted, and changes to the diagram's own fields. Loading reads the last
+//! snapshot (the content and layout files) and replays the log over it. Once
+//! the log holds enough entries, the next save writes a fresh snapshot and
+//! starts a new log.
+//!
+//! Every entry names the snapshot it applies to by the snapshot's
+//! `updated_at`, so a log left behind by an interrupted compaction is
+//! recognised as stale and skipped rather than replayed over newer data.
+//! Log lines are always JSON, whatever the snapshot format; a malformed line,
+//! such as one cut short by a crash, ends the replay.
+
+use crate::model::{Bounds, DiagramModel, ModelElement, Viewport};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use tracing::warn;
+
+/// File name suffix of operation logs
+pub const LOG_SUFFIX: &str = ".glsp.log";
+
+/// One change to a diagram
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum LogOp {
+    Create {
+        element: ModelElement,
+    },
+    Update {
+        element: ModelElement,
+    },
+    /// An element whose bounds changed and nothing else
+    #[serde(rename_all = "camelCase")]
+    Move {
+        id: String,
+        bounds: Option<Bounds>,
+        updated_at: Option<DateTime<Utc>>,
+    },
+    Delete {
+        id: String,
+    },
+    /// The diagram's own fields, including the root and its children
+    Diagram {
+        name: String,
+        root: ModelElement,
+        metadata: HashMap<String, serde_json::Value>,
+        tags: Vec<String>,
+        viewport: Option<Viewport>,
+    },
+}
+
+/// The changes made by one save
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    /// `updated_at` of the snapshot this entry applies to
+    pub snapshot: DateTime<Utc>,
+    pub revision: u32,
+    pub updated_at: DateTime<Utc>,
+    pub ops: Vec<LogOp>,
+}
+
+impl LogEntry {
+    /// Whether replaying the entry would change nothing
+    pub fn is_empty(&self, previous: &DiagramModel) -> bool {
+        self.ops.is_empty()
+            && self.revision == previous.revision
+            && self.updated_at == previous.updated_at
+    }
+}
+
+fn same<T: Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// Changes turning `previous` into `current`, ordered by element ID
+pub fn diff(snapshot: DateTime<Utc>, previous: &DiagramModel, current: &DiagramModel) -> LogEntry {
+    let mut ops = Vec::new();
+    let ids: BTreeSet<&String> = previous
+        .elements
+        .keys()
+        .chain(current.elements.keys())
+        .filter(|id| **id != current.root.id && **id != previous.root.id)
+        .collect();
+
+    for id in ids {
+        match (previous.elements.get(id), current.elements.get(id)) {
+            (None, Some(element)) => ops.push(LogOp::Create {
+                element: element.clone(),
+            }),
+            (Some(_), None) => ops.push(LogOp::Delete { id: id.clone() }),
+            (Some(old), Some(new)) if !same(old, new) => {
+                let mut moved = old.clone();
+                moved.bounds = new.bounds.clone();
+                moved.updated_at = new.updated_at;
+                if same(&moved, new) {
+                    ops.push(LogOp::Move {
+                        id: id.clone(),
+                        bounds: new.bounds.clone(),
+                        updated_at: new.updated_at,
+                    });
+                } else {
+                    ops.push(LogOp::Update {
+                        element: new.clone(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if previous.name != current.name
+        || !same(&previous.root, &current.root)
+        || !same(&previous.metadata, &current.metadata)
+        || previous.tags != current.tags
+        || previous.viewport != current.viewport
+    {
+        ops.push(LogOp::Diagram {
+            name: current.name.clone(),
+            root: current.root.clone(),
+            metadata: current.metadata.clone(),
+            tags: current.tags.clone(),
+            viewport: current.viewport.clone(),
+        });
+    }
+
+    LogEntry {
+        snapshot,
+        revision: current.revision,
+        updated_at: current.updated_at,
+        ops,
+    }
+}
+
+/// Replay one entry onto a diagram
+pub fn apply(diagram: &mut DiagramModel, entry: LogEntry) {
+    for op in entry.ops {
+        match op {
+            LogOp::Create { element } | LogOp::Update { element } => {
+                diagram.elements.insert(element.id.clone(), element);
+            }
+            LogOp::Move {
+                id,
+                bounds,
+                updated_at,
+            } => {
+                if let Some(element) = diagram.elements.get_mut(&id) {
+                    element.bounds = bounds;
+                    element.updated_at = updated_at;
+                }
+            }
+            LogOp::Delete { id } => {
+                diagram.elements.remove(&id);
+            }
+            LogOp::Diagram {
+                name,
+                root,
+                metadata,
+                tags,
+                viewport,
+            } => {
+                diagram.name = name;
+                if diagram.elements.contains_key(&root.id) {
+                    diagram.elements.insert(root.id.clone(), root.clone());
+                }
+                diagram.root = root;
+                diagram.metadata = metadata;
+                diagram.tags = tags;
+                diagram.viewport = viewport;
+            }
+        }
+    }
+    diagram.revision = entry.revision;
+    diagram.updated_at = entry.updated_at;
+}
+
+/// Entries of a log that apply to the snapshot saved at `snapshot`
+pub fn read_entries(text: &str, snapshot: DateTime<Utc>) -> Vec<LogEntry> {
+    let mut entries = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<LogEntry>(line) {
+            Ok(entry) if entry.snapshot == snapshot => entries.push(entry),
+            Ok(_) => {}
+            Err(e) => {
+                warn!(
+                    "Operation log line {} is malformed ({}); ignoring the rest",
+                    number + 1,
+                    e
+                );
+                break;
+            }
+        }
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Node, Position};
+
+    fn add_node(diagram: &mut DiagramModel, x: f64) -> String {
+        let node = Node::new("task", Position { x, y: 0.0 }, None);
+        let id = node.base.id.clone();
+        diagram.add_element(node.base);
+        diagram.add_child_to_root(&id);
+        id
+    }
+
+    #[test]
+    fn test_diff_and_replay_reproduce_the_diagram() {
+        let mut diagram = DiagramModel::new("workflow");
+        let kept = add_node(&mut diagram, 0.0);
+        let removed = add_node(&mut diagram, 100.0);
+        let snapshot = diagram.updated_at;
+        let saved = diagram.clone();
+
+        diagram
+            .elements
+            .get_mut(&kept)
+            .unwrap()
+            .bounds
+            .as_mut()
+            .unwrap()
+            .x = 50.0;
+        diagram.remove_element(&removed);
+        let added = add_node(&mut diagram, 200.0);
+        diagram.name = "Renamed".to_string();
+
+        let entry = diff(snapshot, &saved, &diagram);
+        assert!(matches!(entry.ops.last(), Some(LogOp::Diagram { .. })));
+        assert!(entry
+            .ops
+            .iter()
+            .any(|op| matches!(op, LogOp::Move { id, .. } if *id == kept)));
+
+        let line = serde_json::to_string(&entry).unwrap();
+        let stale = LogEntry {
+            snapshot: snapshot - chrono::Duration::seconds(1),
+            ..entry.clone()
+        };
+        let log = format!(
+            "{}\n{line}\n{{\"trunc",
+            serde_json::to_string(&stale).unwrap()
+        );
+        let entries = read_entries(&log, snapshot);
+        assert_eq!(entries.len(), 1);
+
+        let mut replayed = saved;
+        for entry in entries {
+            apply(&mut replayed, entry);
+        }
+        assert_eq!(replayed.name, "Renamed");
+        assert_eq!(replayed.revision, diagram.revision);
+        assert!(!replayed.elements.contains_key(&removed));
+        assert!(replayed.elements.contains_key(&added));
+        assert_eq!(replayed.elements[&kept].bounds.as_ref().unwrap().x, 50.0);
+        assert_eq!(replayed.root.children, diagram.root.children);
+    }
+}
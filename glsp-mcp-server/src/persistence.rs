@@ -3,14 +3,105 @@
 //! Implements dual-file storage:
 //! - Content file (.glsp.json): Semantic model (nodes, edges, properties)
 //! - Layout file (.glsp.layout.json): Graphical representation (positions, sizes)
+//!
+//! Both files are written as JSON by default or as MessagePack (see
+//! [`PersistenceFormat`]). MessagePack files start with a short header naming
+//! the format, so loading detects the encoding of each file on its own and a
+//! directory may mix files written under either setting. The file names keep
+//! their `.json` extensions in both formats: switching formats then rewrites
+//! each diagram's files in place on its next save instead of leaving a stale
+//! copy under the other name.
+//!
+//! Diagrams that fail to save are written to a separate dead-letter directory
+//! (see [`DeadLetterStore`]) so the change survives until a retry succeeds.
 
-use crate::model::{Bounds, DiagramModel, ElementType, ModelElement};
+use crate::model::{generate_id, Bounds, DiagramModel, ElementType, ModelElement, Viewport};
+use crate::operations::{is_hyperedge, HYPEREDGE_TYPE, PARENT_DIAGRAM_KEY, SUBDIAGRAM_PROPERTY};
+use crate::oplog;
 use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use tokio::fs;
 
+/// Header that starts every MessagePack diagram file.
+///
+/// JSON documents cannot start with these bytes, so files without the header
+/// are read as JSON.
+pub const MESSAGEPACK_HEADER: &[u8] = b"GLSP-MSGPACK/1\n";
+
+/// Encoding of content and layout files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PersistenceFormat {
+    /// Pretty-printed JSON, readable and diffable by hand
+    #[default]
+    Json,
+    /// Compact binary encoding, smaller and faster to load for large diagrams
+    MessagePack,
+}
+
+impl PersistenceFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PersistenceFormat::Json => "json",
+            PersistenceFormat::MessagePack => "messagepack",
+        }
+    }
+
+    /// Format of an encoded file, detected from its header
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(MESSAGEPACK_HEADER) {
+            PersistenceFormat::MessagePack
+        } else {
+            PersistenceFormat::Json
+        }
+    }
+
+    /// Encode a value in this format, including the header
+    pub fn encode<T: Serialize>(&self, value: &T) -> std::io::Result<Vec<u8>> {
+        match self {
+            PersistenceFormat::Json => Ok(serde_json::to_vec_pretty(value)?),
+            PersistenceFormat::MessagePack => {
+                let mut bytes = MESSAGEPACK_HEADER.to_vec();
+                // Named fields keep files readable after fields are added or reordered
+                rmp_serde::encode::write_named(&mut bytes, value).map_err(invalid_data)?;
+                Ok(bytes)
+            }
+        }
+    }
+}
+
+impl FromStr for PersistenceFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(PersistenceFormat::Json),
+            "messagepack" | "msgpack" => Ok(PersistenceFormat::MessagePack),
+            other => Err(format!(
+                "Unknown persistence format '{other}' (expected json or messagepack)"
+            )),
+        }
+    }
+}
+
+/// Decode a content or layout file written in either format
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> std::io::Result<T> {
+    match PersistenceFormat::detect(bytes) {
+        PersistenceFormat::Json => Ok(serde_json::from_slice(bytes)?),
+        PersistenceFormat::MessagePack => {
+            rmp_serde::from_slice(&bytes[MESSAGEPACK_HEADER.len()..]).map_err(invalid_data)
+        }
+    }
+}
+
+fn invalid_data<E: std::error::Error + Send + Sync + 'static>(e: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+}
+
 /// Content file structure - semantic model only
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DiagramContent {
@@ -22,7 +113,11 @@ pub struct DiagramContent {
     pub updated_at: DateTime<Utc>,
     pub nodes: Vec<NodeContent>,
     pub edges: Vec<EdgeContent>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hyperedges: Vec<HyperedgeContent>,
     pub metadata: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -31,6 +126,12 @@ pub struct NodeContent {
     pub node_type: String,
     pub label: Option<String>,
     pub properties: HashMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -41,6 +142,28 @@ pub struct EdgeContent {
     pub target_id: String,
     pub label: Option<String>,
     pub properties: HashMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+}
+
+/// An edge joining several sources to several targets
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HyperedgeContent {
+    pub id: String,
+    pub sources: Vec<String>,
+    pub targets: Vec<String>,
+    pub label: Option<String>,
+    pub properties: HashMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
 }
 
 /// Layout file structure - graphical representation only
@@ -61,13 +184,6 @@ pub struct ElementLayout {
     pub style: HashMap<String, serde_json::Value>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Viewport {
-    pub x: f64,
-    pub y: f64,
-    pub zoom: f64,
-}
-
 /// Persistence manager for diagram storage and file operations
 ///
 /// Handles saving and loading diagrams to/from the file system, managing both
@@ -79,6 +195,13 @@ pub struct Viewport {
 /// - Content files: `{name}.glsp.json` - Contains diagram structure and data
 /// - Layout files: `{name}.glsp.layout.json` - Contains positioning and visual layout
 ///
+/// The file names are the same for both [`PersistenceFormat`]s; the format
+/// only decides how newly saved files are encoded.
+///
+/// With a compaction threshold set, saves append to an operation log,
+/// `{name}.glsp.log`, and rewrite the content and layout files only once the
+/// log holds that many entries; see [`crate::oplog`].
+///
 /// # Examples
 ///
 /// ```rust,no_run
@@ -95,19 +218,51 @@ pub struct Viewport {
 /// ```
 pub struct PersistenceManager {
     base_path: PathBuf,
+    format: PersistenceFormat,
+    compaction_threshold: usize,
+    /// Last saved or loaded state of each diagram with a log, by file name
+    journals: std::sync::Mutex<HashMap<String, Journal>>,
+}
+
+/// What a diagram's operation log currently extends
+struct Journal {
+    /// `updated_at` of the snapshot the log applies to
+    snapshot: DateTime<Utc>,
+    /// The diagram as the snapshot plus the log reconstruct it
+    last: DiagramModel,
+    entries: usize,
 }
 
 impl PersistenceManager {
     pub fn new(base_path: impl AsRef<Path>) -> Self {
+        Self::with_format(base_path, PersistenceFormat::default())
+    }
+
+    /// Create a manager that saves diagrams in the given format
+    pub fn with_format(base_path: impl AsRef<Path>, format: PersistenceFormat) -> Self {
         Self {
             base_path: base_path.as_ref().to_path_buf(),
+            format,
+            compaction_threshold: 0,
+            journals: std::sync::Mutex::default(),
         }
     }
 
+    /// Append edits to an operation log, compacting it into a fresh snapshot
+    /// after `threshold` entries. Zero rewrites the snapshot on every save.
+    pub fn with_compaction_threshold(mut self, threshold: usize) -> Self {
+        self.compaction_threshold = threshold;
+        self
+    }
+
     pub fn get_base_path(&self) -> &Path {
         &self.base_path
     }
 
+    pub fn format(&self) -> PersistenceFormat {
+        self.format
+    }
+
     /// Ensure the storage directory exists
     pub async fn ensure_storage_dir(&self) -> std::io::Result<()> {
         fs::create_dir_all(&self.base_path).await
@@ -121,10 +276,59 @@ impl PersistenceManager {
         (content_path, layout_path)
     }
 
-    /// Save a diagram to disk (both content and layout)
+    fn get_log_path(&self, diagram_name: &str) -> PathBuf {
+        self.base_path.join(format!(
+            "{}{}",
+            sanitize_filename(diagram_name),
+            oplog::LOG_SUFFIX
+        ))
+    }
+
+    fn journals(&self) -> std::sync::MutexGuard<'_, HashMap<String, Journal>> {
+        self.journals.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Save a diagram to disk.
+    ///
+    /// Appends to the diagram's operation log when it has one with room left;
+    /// otherwise writes both content and layout and starts a new log.
     pub async fn save_diagram(&self, diagram: &DiagramModel) -> std::io::Result<()> {
         self.ensure_storage_dir().await?;
 
+        if self.compaction_threshold > 0 {
+            let key = sanitize_filename(&diagram.name);
+            let pending = self
+                .journals()
+                .get(&key)
+                .filter(|j| j.entries < self.compaction_threshold && j.last.id == diagram.id)
+                .map(|j| {
+                    let entry = oplog::diff(j.snapshot, &j.last, diagram);
+                    let unchanged = entry.is_empty(&j.last);
+                    (entry, j.entries, unchanged)
+                });
+            if let Some((entry, entries, unchanged)) = pending {
+                if unchanged {
+                    return Ok(());
+                }
+                let mut line = serde_json::to_vec(&entry)?;
+                line.push(b'\n');
+                if let Err(e) = self.append_log(&diagram.name, &line).await {
+                    // The log may now end in a partial line; start over from a snapshot
+                    self.journals().remove(&key);
+                    return Err(e);
+                }
+                self.journals().insert(
+                    key,
+                    Journal {
+                        snapshot: entry.snapshot,
+                        last: diagram.clone(),
+                        entries: entries + 1,
+                    },
+                );
+                return Ok(());
+            }
+        }
+
         // Extract content and layout from the diagram model
         let (content, layout) = self.split_diagram(diagram);
 
@@ -132,34 +336,91 @@ impl PersistenceManager {
         let (content_path, layout_path) = self.get_file_paths(&diagram.name);
 
         // Save content file
-        let content_json = serde_json::to_string_pretty(&content)?;
-        fs::write(&content_path, content_json).await?;
+        fs::write(&content_path, self.format.encode(&content)?).await?;
 
         // Save layout file
-        let layout_json = serde_json::to_string_pretty(&layout)?;
-        fs::write(&layout_path, layout_json).await?;
+        fs::write(&layout_path, self.format.encode(&layout)?).await?;
+
+        // The snapshot includes everything the old log recorded
+        let log_path = self.get_log_path(&diagram.name);
+        if log_path.exists() {
+            fs::remove_file(&log_path).await?;
+        }
+        if self.compaction_threshold > 0 {
+            self.journals().insert(
+                sanitize_filename(&diagram.name),
+                Journal {
+                    snapshot: diagram.updated_at,
+                    last: diagram.clone(),
+                    entries: 0,
+                },
+            );
+        }
 
         Ok(())
     }
 
+    /// Bytes of the content and the layout file a full save of the diagram
+    /// writes in this manager's format
+    pub fn encoded_size(&self, diagram: &DiagramModel) -> std::io::Result<(usize, usize)> {
+        let (content, layout) = self.split_diagram(diagram);
+        Ok((
+            self.format.encode(&content)?.len(),
+            self.format.encode(&layout)?.len(),
+        ))
+    }
+
+    async fn append_log(&self, diagram_name: &str, line: &[u8]) -> std::io::Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.get_log_path(diagram_name))
+            .await?;
+        file.write_all(line).await?;
+        file.sync_data().await
+    }
+
     /// Load a diagram from disk
     pub async fn load_diagram(&self, diagram_name: &str) -> std::io::Result<DiagramModel> {
         let (content_path, layout_path) = self.get_file_paths(diagram_name);
 
         // Load content file (required)
-        let content_json = fs::read_to_string(&content_path).await?;
-        let content: DiagramContent = serde_json::from_str(&content_json)?;
+        let content: DiagramContent = decode(&fs::read(&content_path).await?)?;
 
         // Load layout file (optional)
         let layout = if layout_path.exists() {
-            let layout_json = fs::read_to_string(&layout_path).await?;
-            Some(serde_json::from_str::<DiagramLayout>(&layout_json)?)
+            Some(decode::<DiagramLayout>(&fs::read(&layout_path).await?)?)
         } else {
             None
         };
 
         // Merge content and layout into DiagramModel
-        Ok(self.merge_diagram(content, layout))
+        let snapshot = content.updated_at;
+        let mut diagram = self.merge_diagram(content, layout);
+
+        // Replay edits saved since the snapshot
+        let log_path = self.get_log_path(diagram_name);
+        let mut entries = 0;
+        if log_path.exists() {
+            for entry in oplog::read_entries(&fs::read_to_string(&log_path).await?, snapshot) {
+                oplog::apply(&mut diagram, entry);
+                entries += 1;
+            }
+        }
+
+        if self.compaction_threshold > 0 {
+            self.journals().insert(
+                sanitize_filename(diagram_name),
+                Journal {
+                    snapshot,
+                    last: diagram.clone(),
+                    entries,
+                },
+            );
+        }
+        Ok(diagram)
     }
 
     /// List all available diagrams
@@ -178,8 +439,8 @@ impl PersistenceManager {
                     let diagram_name = name.trim_end_matches(".glsp.json");
 
                     // Try to load basic info
-                    if let Ok(content_json) = fs::read_to_string(&path).await {
-                        if let Ok(content) = serde_json::from_str::<DiagramContent>(&content_json) {
+                    if let Ok(bytes) = fs::read(&path).await {
+                        if let Ok(content) = decode::<DiagramContent>(&bytes) {
                             diagrams.push(DiagramInfo {
                                 id: content.id,
                                 name: content.name,
@@ -211,6 +472,12 @@ impl PersistenceManager {
             fs::remove_file(&layout_path).await?;
         }
 
+        let log_path = self.get_log_path(diagram_name);
+        if log_path.exists() {
+            fs::remove_file(&log_path).await?;
+        }
+        self.journals().remove(&sanitize_filename(diagram_name));
+
         Ok(())
     }
 
@@ -218,6 +485,7 @@ impl PersistenceManager {
     fn split_diagram(&self, diagram: &DiagramModel) -> (DiagramContent, DiagramLayout) {
         let mut nodes = Vec::new();
         let mut edges = Vec::new();
+        let mut hyperedges = Vec::new();
         let mut element_layouts = HashMap::new();
 
         // Process all elements
@@ -250,11 +518,26 @@ impl PersistenceManager {
                         target_id: element.target_id.clone().unwrap_or_default(),
                         label: element.label.clone(),
                         properties: element.properties.clone(),
+                        created_at: element.created_at,
+                        updated_at: element.updated_at,
+                        created_by: element.created_by.clone(),
                     });
                 }
                 ElementType::Graph => {
                     // Skip the root graph element
                 }
+                _ if is_hyperedge(element) => {
+                    hyperedges.push(HyperedgeContent {
+                        id: id.clone(),
+                        sources: element.sources.clone().unwrap_or_default(),
+                        targets: element.targets.clone().unwrap_or_default(),
+                        label: element.label.clone(),
+                        properties: element.properties.clone(),
+                        created_at: element.created_at,
+                        updated_at: element.updated_at,
+                        created_by: element.created_by.clone(),
+                    });
+                }
                 _ => {
                     // Everything else is a node
                     nodes.push(NodeContent {
@@ -262,6 +545,9 @@ impl PersistenceManager {
                         node_type: element.element_type.to_string(),
                         label: element.label.clone(),
                         properties: element.properties.clone(),
+                        created_at: element.created_at,
+                        updated_at: element.updated_at,
+                        created_by: element.created_by.clone(),
                     });
                 }
             }
@@ -276,7 +562,9 @@ impl PersistenceManager {
             updated_at: diagram.updated_at,
             nodes,
             edges,
+            hyperedges,
             metadata: diagram.metadata.clone(),
+            tags: diagram.tags.clone(),
         };
 
         let layout = DiagramLayout {
@@ -284,7 +572,7 @@ impl PersistenceManager {
             revision: diagram.revision,
             updated_at: diagram.updated_at,
             elements: element_layouts,
-            viewport: None, // Viewport support not implemented yet
+            viewport: diagram.viewport.clone(),
         };
 
         (content, layout)
@@ -308,10 +596,15 @@ impl PersistenceManager {
             label: None,
             source_id: None,
             target_id: None,
+            sources: None,
+            targets: None,
             route: None,
             visible: true,
             z_index: None,
             style: HashMap::new(),
+            created_at: None,
+            updated_at: None,
+            created_by: None,
         };
 
         let mut elements = HashMap::new();
@@ -328,7 +621,9 @@ impl PersistenceManager {
             elements,
             selection: Some(crate::selection::SelectionState::new()),
             metadata: content.metadata,
+            tags: content.tags,
             component_groups: HashMap::new(),
+            viewport: layout.as_ref().and_then(|l| l.viewport.clone()),
         };
 
         // Add nodes
@@ -343,10 +638,15 @@ impl PersistenceManager {
                 label: node.label,
                 source_id: None,
                 target_id: None,
+                sources: None,
+                targets: None,
                 route: None,
                 visible: true,
                 z_index: None,
                 style: HashMap::new(),
+                created_at: node.created_at,
+                updated_at: node.updated_at,
+                created_by: node.created_by,
             };
 
             // Apply layout if available
@@ -381,10 +681,15 @@ impl PersistenceManager {
                 label: edge.label,
                 source_id: Some(edge.source_id),
                 target_id: Some(edge.target_id),
+                sources: None,
+                targets: None,
                 route: None,
                 visible: true,
                 z_index: None,
                 style: HashMap::new(),
+                created_at: edge.created_at,
+                updated_at: edge.updated_at,
+                created_by: edge.created_by,
             };
 
             // Store edge type in properties
@@ -405,6 +710,31 @@ impl PersistenceManager {
             diagram.elements.insert(edge.id, element);
         }
 
+        // Add hyperedges
+        for hyperedge in content.hyperedges {
+            let element = ModelElement {
+                id: hyperedge.id.clone(),
+                element_type: ElementType::from(HYPEREDGE_TYPE),
+                children: None,
+                bounds: None,
+                layout_options: None,
+                properties: hyperedge.properties,
+                label: hyperedge.label,
+                source_id: None,
+                target_id: None,
+                sources: Some(hyperedge.sources),
+                targets: Some(hyperedge.targets),
+                route: None,
+                visible: true,
+                z_index: None,
+                style: HashMap::new(),
+                created_at: hyperedge.created_at,
+                updated_at: hyperedge.updated_at,
+                created_by: hyperedge.created_by,
+            };
+            diagram.elements.insert(hyperedge.id, element);
+        }
+
         diagram
     }
 
@@ -441,6 +771,569 @@ pub struct DiagramInfo {
     pub file_name: String,
 }
 
+/// Current format version written into workspace archive manifests
+pub const WORKSPACE_ARCHIVE_VERSION: u32 = 1;
+
+/// What importing an archive does with diagrams whose IDs are already taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdCollisionStrategy {
+    /// Give every imported diagram a new ID, colliding or not
+    AlwaysNew,
+    /// Keep free IDs and give colliding diagrams a new one
+    #[default]
+    KeepIfFree,
+    /// Import nothing if any ID is taken
+    ErrorOnCollision,
+}
+
+impl FromStr for IdCollisionStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "always-new" => Ok(Self::AlwaysNew),
+            "keep-if-free" => Ok(Self::KeepIfFree),
+            "error-on-collision" => Ok(Self::ErrorOnCollision),
+            other => Err(format!(
+                "Unknown ID collision strategy '{other}' (expected always-new, keep-if-free or error-on-collision)"
+            )),
+        }
+    }
+}
+
+/// The ID one archived diagram was imported under
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdMapping {
+    pub old_id: String,
+    pub new_id: String,
+}
+
+impl IdMapping {
+    pub fn is_remapped(&self) -> bool {
+        self.old_id != self.new_id
+    }
+}
+
+/// Single-payload bundle of every diagram in a workspace, used for backup and transfer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceArchive {
+    pub manifest: WorkspaceManifest,
+    pub diagrams: Vec<DiagramModel>,
+    /// Files attached to the archived diagrams; absent in older archives
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<ArchivedAttachment>,
+}
+
+/// An attachment carried inside a workspace archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedAttachment {
+    pub diagram_id: String,
+    pub name: String,
+    /// File contents, base64 encoded
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceManifest {
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub diagram_count: usize,
+    pub diagrams: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestEntry {
+    pub id: String,
+    pub name: String,
+    pub diagram_type: String,
+}
+
+impl WorkspaceArchive {
+    /// Bundle the given diagrams together with a manifest describing them
+    pub fn new(diagrams: Vec<DiagramModel>) -> Self {
+        let entries = diagrams
+            .iter()
+            .map(|d| ManifestEntry {
+                id: d.id.clone(),
+                name: d.name.clone(),
+                diagram_type: d.diagram_type.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            manifest: WorkspaceManifest {
+                format_version: WORKSPACE_ARCHIVE_VERSION,
+                exported_at: Utc::now(),
+                diagram_count: entries.len(),
+                diagrams: entries,
+            },
+            diagrams,
+            attachments: Vec::new(),
+        }
+    }
+
+    /// Reject archives whose manifest names a format this server does not know
+    pub fn check_version(&self) -> Result<(), String> {
+        let version = self.manifest.format_version;
+        if !(1..=WORKSPACE_ARCHIVE_VERSION).contains(&version) {
+            return Err(format!(
+                "Unsupported archive format version {version} (this server reads versions 1 to {WORKSPACE_ARCHIVE_VERSION})"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Give every diagram that collides with an existing ID or name a fresh one.
+    ///
+    /// Names are made unique as well because they determine the file name on
+    /// disk. Returns the mapping of every remapped diagram, in archive order.
+    pub fn remap_collisions(
+        &mut self,
+        existing_ids: &HashSet<String>,
+        existing_names: &HashSet<String>,
+    ) -> Vec<IdMapping> {
+        self.resolve_collisions(
+            existing_ids,
+            existing_names,
+            IdCollisionStrategy::KeepIfFree,
+        )
+        .expect("keep-if-free never rejects an import")
+        .into_iter()
+        .filter(IdMapping::is_remapped)
+        .collect()
+    }
+
+    /// Assign the archive's diagrams IDs according to `strategy` and make
+    /// their names unique.
+    ///
+    /// A reassigned ID is a fresh UUID v4 like any diagram ID the server
+    /// creates, so it passes [`crate::model::normalize_id`]. Links between
+    /// the archived diagrams (subdiagram references and parent diagram
+    /// metadata) and attachments follow their diagram's new ID; an ID the
+    /// archive holds twice is kept by its first diagram, and links to it
+    /// follow that one.
+    ///
+    /// Returns one mapping per archived diagram, in archive order, or with
+    /// [`IdCollisionStrategy::ErrorOnCollision`] the IDs that are taken, in
+    /// which case the archive is left unchanged.
+    pub fn resolve_collisions(
+        &mut self,
+        existing_ids: &HashSet<String>,
+        existing_names: &HashSet<String>,
+        strategy: IdCollisionStrategy,
+    ) -> Result<Vec<IdMapping>, Vec<String>> {
+        if strategy == IdCollisionStrategy::ErrorOnCollision {
+            let mut seen = HashSet::new();
+            let mut taken: Vec<String> = self
+                .diagrams
+                .iter()
+                .filter(|d| existing_ids.contains(&d.id) || !seen.insert(&d.id))
+                .map(|d| d.id.clone())
+                .collect();
+            if !taken.is_empty() {
+                taken.sort();
+                taken.dedup();
+                return Err(taken);
+            }
+        }
+
+        // Original IDs stay reserved so a new ID never lands on a diagram
+        // further down the archive
+        let mut taken_ids = existing_ids.clone();
+        taken_ids.extend(self.diagrams.iter().map(|d| d.id.clone()));
+        let mut kept = HashSet::new();
+        let mut id_map = Vec::with_capacity(self.diagrams.len());
+        let mut taken_names = existing_names.clone();
+
+        for diagram in &mut self.diagrams {
+            let old_id = diagram.id.clone();
+            let keep = strategy != IdCollisionStrategy::AlwaysNew
+                && !existing_ids.contains(&old_id)
+                && kept.insert(old_id.clone());
+            if !keep {
+                diagram.id = generate_id();
+                while !taken_ids.insert(diagram.id.clone()) {
+                    diagram.id = generate_id();
+                }
+            }
+            id_map.push(IdMapping {
+                old_id,
+                new_id: diagram.id.clone(),
+            });
+
+            if taken_names.contains(&diagram.name) {
+                let base = diagram.name.clone();
+                let mut suffix = 2;
+                while taken_names.contains(&format!("{base} ({suffix})")) {
+                    suffix += 1;
+                }
+                diagram.name = format!("{base} ({suffix})");
+            }
+            taken_names.insert(diagram.name.clone());
+        }
+
+        // Links follow the first diagram that had the ID
+        let mut links = HashMap::new();
+        for mapping in &id_map {
+            links
+                .entry(mapping.old_id.as_str())
+                .or_insert(mapping.new_id.as_str());
+        }
+        let remapped = |id: &str| {
+            links
+                .get(id)
+                .filter(|new| **new != id)
+                .map(|new| new.to_string())
+        };
+        for diagram in &mut self.diagrams {
+            if let Some(new_id) = diagram
+                .metadata
+                .get(PARENT_DIAGRAM_KEY)
+                .and_then(|v| v.as_str())
+                .and_then(remapped)
+            {
+                diagram
+                    .metadata
+                    .insert(PARENT_DIAGRAM_KEY.to_string(), serde_json::json!(new_id));
+            }
+            for element in diagram.elements.values_mut() {
+                if let Some(new_id) = element
+                    .properties
+                    .get(SUBDIAGRAM_PROPERTY)
+                    .and_then(|v| v.as_str())
+                    .and_then(remapped)
+                {
+                    element
+                        .properties
+                        .insert(SUBDIAGRAM_PROPERTY.to_string(), serde_json::json!(new_id));
+                }
+            }
+        }
+        for attachment in &mut self.attachments {
+            if let Some(new_id) = remapped(&attachment.diagram_id) {
+                attachment.diagram_id = new_id;
+            }
+        }
+
+        Ok(id_map)
+    }
+}
+
+/// A diagram whose save failed, kept until a retry succeeds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetter {
+    pub diagram_id: String,
+    pub diagram_name: String,
+    pub failed_at: DateTime<Utc>,
+    pub error: String,
+    pub diagram: DiagramModel,
+}
+
+/// Recovery copies of diagrams that could not be persisted.
+///
+/// Lives outside the diagrams directory so that a full or read-only data
+/// directory does not also take out the recovery copy. There is one file per
+/// diagram; a newer failure replaces the older copy since it holds the whole
+/// model.
+pub struct DeadLetterStore {
+    dir: PathBuf,
+}
+
+impl DeadLetterStore {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn get_dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path_for(&self, diagram_id: &str) -> PathBuf {
+        self.dir.join(format!(
+            "{}.dead-letter.json",
+            sanitize_filename(diagram_id)
+        ))
+    }
+
+    /// Write a recovery copy of a diagram whose save failed
+    pub async fn record(&self, diagram: &DiagramModel, error: &str) -> std::io::Result<PathBuf> {
+        fs::create_dir_all(&self.dir).await?;
+        let letter = DeadLetter {
+            diagram_id: diagram.id.clone(),
+            diagram_name: diagram.name.clone(),
+            failed_at: Utc::now(),
+            error: error.to_string(),
+            diagram: diagram.clone(),
+        };
+        let path = self.path_for(&diagram.id);
+        fs::write(&path, serde_json::to_string_pretty(&letter)?).await?;
+        Ok(path)
+    }
+
+    /// All pending dead letters, oldest failure first
+    pub async fn pending(&self) -> std::io::Result<Vec<DeadLetter>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut letters = Vec::new();
+        let mut entries = fs::read_dir(&self.dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if !path.to_string_lossy().ends_with(".dead-letter.json") {
+                continue;
+            }
+            let json = fs::read_to_string(&path).await?;
+            letters.push(serde_json::from_str::<DeadLetter>(&json)?);
+        }
+        letters.sort_by_key(|letter| letter.failed_at);
+        Ok(letters)
+    }
+
+    /// Drop the dead letter for a diagram, if there is one
+    pub async fn remove(&self, diagram_id: &str) -> std::io::Result<bool> {
+        let path = self.path_for(diagram_id);
+        if !path.exists() {
+            return Ok(false);
+        }
+        fs::remove_file(&path).await?;
+        Ok(true)
+    }
+}
+
+/// Named checkpoints of the whole server: every diagram with its metadata and
+/// attachments, one [`WorkspaceArchive`] file per checkpoint.
+///
+/// A checkpoint is written to a temporary file and renamed into place, so a
+/// crash mid-write leaves the previous checkpoint of that name intact rather
+/// than a truncated one.
+pub struct StateSnapshotStore {
+    dir: PathBuf,
+}
+
+impl StateSnapshotStore {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn get_dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path_for(&self, name: &str) -> std::io::Result<PathBuf> {
+        let safe_name = sanitize_filename(name);
+        if safe_name.is_empty() || safe_name.starts_with('.') {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid snapshot name '{name}'"),
+            ));
+        }
+        Ok(self.dir.join(format!("{safe_name}.state.json")))
+    }
+
+    /// Write a checkpoint, replacing any earlier one of the same name
+    pub async fn save(&self, name: &str, archive: &WorkspaceArchive) -> std::io::Result<PathBuf> {
+        let path = self.path_for(name)?;
+        fs::create_dir_all(&self.dir).await?;
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_vec(archive)?).await?;
+        fs::rename(&temp_path, &path).await?;
+        Ok(path)
+    }
+
+    /// Read a checkpoint back
+    pub async fn load(&self, name: &str) -> std::io::Result<WorkspaceArchive> {
+        let bytes = fs::read(self.path_for(name)?).await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+}
+
+/// Size limits for diagram attachments, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttachmentLimits {
+    pub max_attachment_bytes: u64,
+    pub max_diagram_bytes: u64,
+}
+
+impl Default for AttachmentLimits {
+    fn default() -> Self {
+        Self {
+            max_attachment_bytes: 10 * 1024 * 1024,
+            max_diagram_bytes: 50 * 1024 * 1024,
+        }
+    }
+}
+
+/// A stored attachment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentInfo {
+    pub name: String,
+    pub size: u64,
+    pub modified_at: DateTime<Utc>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AttachmentError {
+    #[error("Invalid attachment name '{0}'")]
+    InvalidName(String),
+
+    #[error("Attachment '{name}' is {size} bytes, more than the {limit} byte limit")]
+    TooLarge { name: String, size: u64, limit: u64 },
+
+    #[error(
+        "Attachments of diagram {diagram_id} would take {total} bytes, more than the {limit} byte limit"
+    )]
+    DiagramLimitExceeded {
+        diagram_id: String,
+        total: u64,
+        limit: u64,
+    },
+
+    #[error("Attachment '{0}' not found")]
+    NotFound(String),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Files attached to diagrams, such as datasheets or reference images.
+///
+/// Stored under `<diagrams dir>/attachments/<diagram id>/<name>`, next to the
+/// diagrams but in a directory the diagram loader does not scan. Names are
+/// used as file names as-is, so anything that is not a plain file name is
+/// rejected rather than rewritten; otherwise two names could end up in the
+/// same file.
+pub struct AttachmentStore {
+    dir: PathBuf,
+    limits: AttachmentLimits,
+}
+
+impl AttachmentStore {
+    pub fn new(dir: impl AsRef<Path>, limits: AttachmentLimits) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            limits,
+        }
+    }
+
+    pub fn get_dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn limits(&self) -> AttachmentLimits {
+        self.limits
+    }
+
+    fn diagram_dir(&self, diagram_id: &str) -> PathBuf {
+        self.dir.join(sanitize_filename(diagram_id))
+    }
+
+    fn path_for(&self, diagram_id: &str, name: &str) -> Result<PathBuf, AttachmentError> {
+        if name.is_empty() || name.starts_with('.') || sanitize_filename(name) != name {
+            return Err(AttachmentError::InvalidName(name.to_string()));
+        }
+        Ok(self.diagram_dir(diagram_id).join(name))
+    }
+
+    /// Store `bytes` as attachment `name`, replacing an attachment of the
+    /// same name
+    pub async fn attach(
+        &self,
+        diagram_id: &str,
+        name: &str,
+        bytes: &[u8],
+    ) -> Result<AttachmentInfo, AttachmentError> {
+        let path = self.path_for(diagram_id, name)?;
+        let size = bytes.len() as u64;
+        if size > self.limits.max_attachment_bytes {
+            return Err(AttachmentError::TooLarge {
+                name: name.to_string(),
+                size,
+                limit: self.limits.max_attachment_bytes,
+            });
+        }
+
+        // The attachment being replaced does not count against the limit
+        let others: u64 = self
+            .list(diagram_id)
+            .await?
+            .iter()
+            .filter(|a| a.name != name)
+            .map(|a| a.size)
+            .sum();
+        if others + size > self.limits.max_diagram_bytes {
+            return Err(AttachmentError::DiagramLimitExceeded {
+                diagram_id: diagram_id.to_string(),
+                total: others + size,
+                limit: self.limits.max_diagram_bytes,
+            });
+        }
+
+        fs::create_dir_all(self.diagram_dir(diagram_id)).await?;
+        fs::write(&path, bytes).await?;
+        Ok(AttachmentInfo {
+            name: name.to_string(),
+            size,
+            modified_at: Utc::now(),
+        })
+    }
+
+    /// Attachments of a diagram, by name
+    pub async fn list(&self, diagram_id: &str) -> std::io::Result<Vec<AttachmentInfo>> {
+        let dir = self.diagram_dir(diagram_id);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut attachments = Vec::new();
+        let mut entries = fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let metadata = entry.metadata().await?;
+            if !metadata.is_file() {
+                continue;
+            }
+            attachments.push(AttachmentInfo {
+                name: entry.file_name().to_string_lossy().to_string(),
+                size: metadata.len(),
+                modified_at: metadata
+                    .modified()
+                    .map(DateTime::<Utc>::from)
+                    .unwrap_or_else(|_| Utc::now()),
+            });
+        }
+        attachments.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(attachments)
+    }
+
+    pub async fn get(&self, diagram_id: &str, name: &str) -> Result<Vec<u8>, AttachmentError> {
+        let path = self.path_for(diagram_id, name)?;
+        if !path.is_file() {
+            return Err(AttachmentError::NotFound(name.to_string()));
+        }
+        Ok(fs::read(&path).await?)
+    }
+
+    /// Drop every attachment of a diagram
+    pub async fn remove_all(&self, diagram_id: &str) -> std::io::Result<()> {
+        let dir = self.diagram_dir(diagram_id);
+        if dir.exists() {
+            fs::remove_dir_all(&dir).await?;
+        }
+        Ok(())
+    }
+}
+
 /// Sanitize a filename to be safe for the filesystem
 fn sanitize_filename(name: &str) -> String {
     name.chars()
@@ -457,6 +1350,7 @@ fn sanitize_filename(name: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::{normalize_id, Node, Position};
 
     #[test]
     fn test_sanitize_filename() {
@@ -464,4 +1358,301 @@ mod tests {
         assert_eq!(sanitize_filename("test:file*name"), "test_file_name");
         assert_eq!(sanitize_filename("normal_name"), "normal_name");
     }
+
+    #[test]
+    fn test_workspace_archive_remaps_collisions() {
+        let mut first = DiagramModel::new("workflow");
+        first.name = "Pipeline".to_string();
+        let second = DiagramModel::new("workflow");
+        let original_first_id = first.id.clone();
+        let original_second_id = second.id.clone();
+
+        let mut archive = WorkspaceArchive::new(vec![first, second]);
+        assert_eq!(archive.manifest.diagram_count, 2);
+
+        let existing_ids = HashSet::from([original_first_id.clone()]);
+        let existing_names = HashSet::from(["Pipeline".to_string()]);
+        let remapped = archive.remap_collisions(&existing_ids, &existing_names);
+
+        assert_eq!(remapped.len(), 1);
+        assert_eq!(remapped[0].old_id, original_first_id);
+        assert_eq!(remapped[0].new_id, archive.diagrams[0].id);
+        // New IDs are as canonical as any other diagram ID
+        assert_eq!(
+            normalize_id(&remapped[0].new_id).unwrap(),
+            remapped[0].new_id
+        );
+        assert_eq!(archive.diagrams[0].name, "Pipeline (2)");
+        assert_eq!(archive.diagrams[1].id, original_second_id);
+    }
+
+    #[test]
+    fn test_duplicate_ids_in_an_archive_get_one_mapping_each() {
+        let original = DiagramModel::new("workflow");
+        let mut duplicate = original.clone();
+        duplicate.name = "Copy".to_string();
+        let mut archive = WorkspaceArchive::new(vec![original.clone(), duplicate]);
+
+        let id_map = archive
+            .resolve_collisions(
+                &HashSet::new(),
+                &HashSet::new(),
+                IdCollisionStrategy::KeepIfFree,
+            )
+            .unwrap();
+        assert_eq!(id_map.len(), 2);
+        assert_eq!(id_map[0].old_id, original.id);
+        assert_eq!(id_map[0].new_id, original.id);
+        assert_eq!(id_map[1].old_id, original.id);
+        assert_ne!(id_map[1].new_id, original.id);
+        assert!(normalize_id(&id_map[1].new_id).is_ok());
+        let new_ids: Vec<&String> = archive.diagrams.iter().map(|d| &d.id).collect();
+        assert_eq!(new_ids, vec![&id_map[0].new_id, &id_map[1].new_id]);
+    }
+
+    #[test]
+    fn test_archive_rejects_unknown_format_version() {
+        let mut archive = WorkspaceArchive::new(vec![]);
+        assert!(archive.check_version().is_ok());
+        archive.manifest.format_version = WORKSPACE_ARCHIVE_VERSION + 1;
+        assert!(archive.check_version().is_err());
+        archive.manifest.format_version = 0;
+        assert!(archive.check_version().is_err());
+    }
+
+    #[test]
+    fn test_collision_strategies_rewrite_links() {
+        let parent = DiagramModel::new("workflow");
+        let mut child = DiagramModel::new("workflow");
+        child.name = "Child".to_string();
+        child.metadata.insert(
+            PARENT_DIAGRAM_KEY.to_string(),
+            serde_json::json!(parent.id.clone()),
+        );
+        let archive = WorkspaceArchive::new(vec![parent.clone(), child.clone()]);
+        let existing_ids = HashSet::from([parent.id.clone()]);
+        let no_names = HashSet::new();
+
+        let mut rejected = archive.clone();
+        assert_eq!(
+            rejected.resolve_collisions(
+                &existing_ids,
+                &no_names,
+                IdCollisionStrategy::ErrorOnCollision
+            ),
+            Err(vec![parent.id.clone()])
+        );
+        assert_eq!(rejected.diagrams[0].id, parent.id);
+
+        let mut kept = archive.clone();
+        let id_map = kept
+            .resolve_collisions(&existing_ids, &no_names, IdCollisionStrategy::KeepIfFree)
+            .unwrap();
+        let new_parent_id = id_map[0].new_id.clone();
+        assert_eq!(id_map[0].old_id, parent.id);
+        assert_ne!(new_parent_id, parent.id);
+        assert!(normalize_id(&new_parent_id).is_ok());
+        assert!(!id_map[1].is_remapped());
+        assert_eq!(
+            kept.diagrams[1].metadata[PARENT_DIAGRAM_KEY],
+            serde_json::json!(new_parent_id)
+        );
+
+        // Importing again gives every diagram an ID that is still free
+        let mut again = archive.clone();
+        let taken = HashSet::from([parent.id.clone(), new_parent_id, child.id.clone()]);
+        let id_map = again
+            .resolve_collisions(&taken, &no_names, IdCollisionStrategy::AlwaysNew)
+            .unwrap();
+        for mapping in &id_map {
+            assert!(!taken.contains(&mapping.new_id));
+            assert!(normalize_id(&mapping.new_id).is_ok());
+        }
+        assert_eq!(
+            again.diagrams[1].metadata[PARENT_DIAGRAM_KEY],
+            serde_json::json!(id_map[0].new_id)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DeadLetterStore::new(dir.path().join("dead-letter"));
+        assert!(store.pending().await.unwrap().is_empty());
+
+        let diagram = DiagramModel::new("workflow");
+        store
+            .record(&diagram, "No space left on device")
+            .await
+            .unwrap();
+        store.record(&diagram, "Permission denied").await.unwrap();
+
+        let pending = store.pending().await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].diagram_id, diagram.id);
+        assert_eq!(pending[0].error, "Permission denied");
+
+        assert!(store.remove(&diagram.id).await.unwrap());
+        assert!(store.pending().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_state_snapshot_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = StateSnapshotStore::new(dir.path().join("state"));
+        assert!(store.load("nightly").await.is_err());
+        assert!(store
+            .save("..", &WorkspaceArchive::new(vec![]))
+            .await
+            .is_err());
+
+        let diagram = DiagramModel::new("workflow");
+        store
+            .save("nightly", &WorkspaceArchive::new(vec![]))
+            .await
+            .unwrap();
+        let path = store
+            .save("nightly", &WorkspaceArchive::new(vec![diagram.clone()]))
+            .await
+            .unwrap();
+        assert!(!path.with_extension("json.tmp").exists());
+
+        let archive = store.load("nightly").await.unwrap();
+        assert_eq!(archive.manifest.diagram_count, 1);
+        assert_eq!(archive.diagrams[0].id, diagram.id);
+    }
+
+    #[tokio::test]
+    async fn test_attachments_respect_size_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AttachmentStore::new(
+            dir.path().join("attachments"),
+            AttachmentLimits {
+                max_attachment_bytes: 8,
+                max_diagram_bytes: 12,
+            },
+        );
+        assert!(store.list("d1").await.unwrap().is_empty());
+
+        store.attach("d1", "spec.txt", b"abcdef").await.unwrap();
+        // Replacing an attachment only counts its new size
+        store.attach("d1", "spec.txt", b"abcdefgh").await.unwrap();
+        assert!(matches!(
+            store.attach("d1", "big.bin", b"123456789").await,
+            Err(AttachmentError::TooLarge { size: 9, .. })
+        ));
+        assert!(matches!(
+            store.attach("d1", "more.bin", b"12345").await,
+            Err(AttachmentError::DiagramLimitExceeded { total: 13, .. })
+        ));
+        assert!(matches!(
+            store.attach("d1", "../escape", b"x").await,
+            Err(AttachmentError::InvalidName(_))
+        ));
+
+        let listed = store.list("d1").await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].size, 8);
+        assert_eq!(store.get("d1", "spec.txt").await.unwrap(), b"abcdefgh");
+        assert!(matches!(
+            store.get("d2", "spec.txt").await,
+            Err(AttachmentError::NotFound(_))
+        ));
+
+        store.remove_all("d1").await.unwrap();
+        assert!(store.list("d1").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_loader_detects_messagepack_and_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let packed = PersistenceManager::with_format(dir.path(), PersistenceFormat::MessagePack);
+        let plain = PersistenceManager::new(dir.path());
+
+        let mut diagram = DiagramModel::new("workflow");
+        diagram.name = "Packed".to_string();
+        let mut node = Node::new("task", Position { x: 10.0, y: 20.0 }, None);
+        node.base.properties.insert(
+            "config".to_string(),
+            serde_json::json!({"retries": [1, 2.5, null]}),
+        );
+        let node_id = node.base.id.clone();
+        diagram.add_element(node.base);
+        diagram.add_child_to_root(&node_id);
+        packed.save_diagram(&diagram).await.unwrap();
+
+        let mut other = DiagramModel::new("workflow");
+        other.name = "Plain".to_string();
+        plain.save_diagram(&other).await.unwrap();
+
+        let bytes = std::fs::read(dir.path().join("Packed.glsp.json")).unwrap();
+        assert_eq!(
+            PersistenceFormat::detect(&bytes),
+            PersistenceFormat::MessagePack
+        );
+        let layout = std::fs::read(dir.path().join("Packed.glsp.layout.json")).unwrap();
+        assert_eq!(
+            packed.encoded_size(&diagram).unwrap(),
+            (bytes.len(), layout.len())
+        );
+
+        // Either manager reads files of either format
+        let loaded = plain.load_diagram("Packed").await.unwrap();
+        assert_eq!(loaded.id, diagram.id);
+        assert_eq!(
+            loaded.elements[&node_id].properties["config"],
+            serde_json::json!({"retries": [1, 2.5, null]})
+        );
+        assert_eq!(loaded.elements[&node_id].bounds.as_ref().unwrap().y, 20.0);
+        assert_eq!(packed.load_diagram("Plain").await.unwrap().id, other.id);
+        assert_eq!(packed.list_diagrams().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_saves_append_to_log_until_compaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let persistence = PersistenceManager::new(dir.path()).with_compaction_threshold(2);
+        let log_path = dir.path().join("Logged.glsp.log");
+
+        let mut diagram = DiagramModel::new("workflow");
+        diagram.name = "Logged".to_string();
+        persistence.save_diagram(&diagram).await.unwrap();
+        assert!(!log_path.exists());
+
+        let node = Node::new("task", Position { x: 10.0, y: 20.0 }, None);
+        let node_id = node.base.id.clone();
+        diagram.add_element(node.base);
+        diagram.add_child_to_root(&node_id);
+        persistence.save_diagram(&diagram).await.unwrap();
+        diagram
+            .elements
+            .get_mut(&node_id)
+            .unwrap()
+            .bounds
+            .as_mut()
+            .unwrap()
+            .x = 40.0;
+        diagram.revision += 1;
+        persistence.save_diagram(&diagram).await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&log_path).unwrap().lines().count(),
+            2
+        );
+
+        // A fresh manager sees the snapshot plus the log
+        let loaded = PersistenceManager::new(dir.path())
+            .load_diagram("Logged")
+            .await
+            .unwrap();
+        assert_eq!(loaded.revision, diagram.revision);
+        assert_eq!(loaded.elements[&node_id].bounds.as_ref().unwrap().x, 40.0);
+        assert_eq!(loaded.root.children, diagram.root.children);
+
+        // The third edit exceeds the threshold and compacts
+        diagram.remove_element(&node_id);
+        persistence.save_diagram(&diagram).await.unwrap();
+        assert!(!log_path.exists());
+        let loaded = persistence.load_diagram("Logged").await.unwrap();
+        assert!(!loaded.elements.contains_key(&node_id));
+    }
 }
@@ -0,0 +1,98 @@
+//! MCP progress notifications for long-running tool calls
+//!
+//! A client that sends `_meta.progressToken` with `tools/call` receives
+//! `notifications/progress` messages while the tool runs. The transport runs
+//! the call inside [`scope`], and tools report their progress with [`report`],
+//! which does nothing when the caller did not ask for progress. Tools
+//! therefore never need to know whether anyone is listening.
+
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+tokio::task_local! {
+    static CURRENT: ProgressReporter;
+}
+
+/// Sends progress notifications for one tool call
+#[derive(Debug, Clone)]
+pub struct ProgressReporter {
+    token: Value,
+    sender: mpsc::UnboundedSender<Value>,
+}
+
+impl ProgressReporter {
+    /// A reporter for `token` and the receiver its notifications arrive on
+    pub fn new(token: Value) -> (Self, mpsc::UnboundedReceiver<Value>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self { token, sender }, receiver)
+    }
+
+    /// Send one `notifications/progress` message. A closed receiver is
+    /// ignored: the client going away must not fail the tool.
+    pub fn report(&self, progress: f64, total: Option<f64>, message: &str) {
+        let mut params = json!({
+            "progressToken": self.token,
+            "progress": progress,
+            "message": message,
+        });
+        if let Some(total) = total {
+            params["total"] = json!(total);
+        }
+        let _ = self.sender.send(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": params,
+        }));
+    }
+}
+
+/// The progress token of a `tools/call` request, if the client sent one
+pub fn progress_token(params: &Value) -> Option<Value> {
+    params
+        .get("_meta")
+        .and_then(|meta| meta.get("progressToken"))
+        .filter(|token| token.is_string() || token.is_number())
+        .cloned()
+}
+
+/// Run `future` with `reporter` receiving everything passed to [`report`]
+pub async fn scope<F: std::future::Future>(reporter: ProgressReporter, future: F) -> F::Output {
+    CURRENT.scope(reporter, future).await
+}
+
+/// Report progress of the current tool call, if its client asked for it
+pub fn report(progress: f64, total: Option<f64>, message: &str) {
+    let _ = CURRENT.try_with(|reporter| reporter.report(progress, total, message));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_reports_reach_the_scoped_reporter_only() {
+        // Outside a scope reporting is a no-op
+        report(1.0, None, "ignored");
+
+        let params = json!({"name": "import_workspace", "_meta": {"progressToken": "t-1"}});
+        let token = progress_token(&params).unwrap();
+        assert!(progress_token(&json!({"_meta": {"progressToken": {}}})).is_none());
+
+        let (reporter, mut receiver) = ProgressReporter::new(token);
+        scope(reporter, async {
+            report(1.0, Some(2.0), "validating");
+            report(2.0, Some(2.0), "saving");
+        })
+        .await;
+
+        let first = receiver.recv().await.unwrap();
+        assert_eq!(first["method"], "notifications/progress");
+        assert_eq!(first["params"]["progressToken"], "t-1");
+        assert_eq!(first["params"]["total"], 2.0);
+        assert_eq!(
+            receiver.recv().await.unwrap()["params"]["message"],
+            "saving"
+        );
+        assert!(receiver.recv().await.is_none());
+    }
+}
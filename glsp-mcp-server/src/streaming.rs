@@ -0,0 +1,109 @@
+//! Tool results streamed as newline-delimited JSON
+//!
+//! Tools with potentially large result sets can hand their items to the
+//! transport one at a time instead of building a single JSON document. A
+//! client opts in per call; the transport runs the call inside [`scope`] and
+//! writes a line per item as it arrives. A streaming tool checks
+//! [`is_active`], announces its result with [`start`], which becomes the
+//! metadata line, and then passes each item to [`item`] as it reads it from
+//! the diagram or the database, without collecting them first. Totals known
+//! only at the end, such as the item count, go into the result the tool
+//! returns. Outside a scope the tool returns its usual document, so tools
+//! never depend on the client asking for a stream.
+
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+tokio::task_local! {
+    static CURRENT: ResultStream;
+}
+
+/// One line of a streamed result
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamLine {
+    /// Everything about the result except its items, sent first
+    Meta(Value),
+    Item(Value),
+}
+
+/// Collects the streamed result of one tool call
+#[derive(Debug, Clone)]
+pub struct ResultStream {
+    sender: mpsc::UnboundedSender<StreamLine>,
+}
+
+impl ResultStream {
+    /// A stream and the receiver its lines arrive on
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<StreamLine>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self { sender }, receiver)
+    }
+
+    /// Send one line. A closed receiver is ignored: the client going away
+    /// must not fail the tool.
+    fn send(&self, line: StreamLine) {
+        let _ = self.sender.send(line);
+    }
+}
+
+/// Run `future` with `stream` receiving everything passed to [`start`] and
+/// [`item`]
+pub async fn scope<F: std::future::Future>(stream: ResultStream, future: F) -> F::Output {
+    CURRENT.scope(stream, future).await
+}
+
+/// Whether the current tool call streams its result
+pub fn is_active() -> bool {
+    CURRENT.try_with(|_| ()).is_ok()
+}
+
+/// Send the metadata line of the current tool call's result
+pub fn start(meta: Value) {
+    let _ = CURRENT.try_with(|stream| stream.send(StreamLine::Meta(meta)));
+}
+
+/// Send one item of the current tool call's result
+pub fn item<T: Serialize>(item: &T) {
+    let _ = CURRENT.try_with(|stream| {
+        let value = serde_json::to_value(item)
+            .unwrap_or_else(|e| serde_json::json!({"streamError": e.to_string()}));
+        stream.send(StreamLine::Item(value))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_lines_reach_the_scoped_stream_only() {
+        // Outside a scope nothing is collected
+        assert!(!is_active());
+        item(&json!({"id": "ignored"}));
+
+        let (stream, mut receiver) = ResultStream::channel();
+        scope(stream, async {
+            assert!(is_active());
+            start(json!({"count": 2}));
+            item(&json!({"id": "a"}));
+            item(&json!({"id": "b"}));
+        })
+        .await;
+
+        assert_eq!(
+            receiver.recv().await,
+            Some(StreamLine::Meta(json!({"count": 2})))
+        );
+        assert_eq!(
+            receiver.recv().await,
+            Some(StreamLine::Item(json!({"id": "a"})))
+        );
+        assert_eq!(
+            receiver.recv().await,
+            Some(StreamLine::Item(json!({"id": "b"})))
+        );
+        assert!(receiver.recv().await.is_none());
+    }
+}
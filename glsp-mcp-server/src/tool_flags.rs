@@ -0,0 +1,141 @@
+//! Runtime feature flags for tools
+//!
+//! Operators can switch individual tools off, for example expensive or
+//! experimental ones, without rebuilding. Disabled tools are left out of
+//! `tools/list` and calls to them fail as if the method did not exist.
+//!
+//! Tools are disabled through the `--disabled-tools` list, which is fixed at
+//! startup, and through an optional flags file of the form
+//! `{"disabled": ["apply_layout"]}`. The file is re-read whenever its
+//! modification time changes, so its flags take effect without a restart.
+
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::SystemTime;
+use tracing::{info, warn};
+
+#[derive(Debug, Default, Deserialize)]
+struct FlagsFile {
+    #[serde(default)]
+    disabled: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct FileState {
+    modified: Option<SystemTime>,
+    disabled: HashSet<String>,
+}
+
+/// Registry of disabled tools
+#[derive(Debug, Default)]
+pub struct ToolFlags {
+    /// Disabled by configuration for the lifetime of the process
+    configured: HashSet<String>,
+    file: Option<PathBuf>,
+    file_state: RwLock<FileState>,
+}
+
+impl ToolFlags {
+    pub fn new<I: IntoIterator<Item = String>>(disabled: I, file: Option<PathBuf>) -> Self {
+        let flags = Self {
+            configured: disabled.into_iter().collect(),
+            file,
+            file_state: RwLock::default(),
+        };
+        flags.refresh();
+        flags
+    }
+
+    pub fn is_enabled(&self, tool: &str) -> bool {
+        self.refresh();
+        !self.configured.contains(tool)
+            && !self
+                .file_state
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .disabled
+                .contains(tool)
+    }
+
+    /// Every disabled tool, sorted
+    pub fn disabled(&self) -> Vec<String> {
+        self.refresh();
+        let state = self.file_state.read().unwrap_or_else(|e| e.into_inner());
+        let mut disabled: Vec<String> = self.configured.union(&state.disabled).cloned().collect();
+        disabled.sort();
+        disabled
+    }
+
+    /// Re-read the flags file if it changed since it was last read.
+    ///
+    /// A missing file disables nothing; an unreadable or malformed file keeps
+    /// the flags that were last loaded.
+    fn refresh(&self) {
+        let Some(path) = &self.file else {
+            return;
+        };
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        if self
+            .file_state
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .modified
+            == modified
+        {
+            return;
+        }
+
+        let disabled = match modified {
+            None => HashSet::new(),
+            Some(_) => {
+                let parsed = std::fs::read_to_string(path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|text| {
+                        serde_json::from_str::<FlagsFile>(&text).map_err(|e| e.to_string())
+                    });
+                match parsed {
+                    Ok(file) => file.disabled.into_iter().collect(),
+                    Err(e) => {
+                        warn!("Ignoring tool flags file {:?}: {}", path, e);
+                        return;
+                    }
+                }
+            }
+        };
+
+        let mut state = self.file_state.write().unwrap_or_else(|e| e.into_inner());
+        if state.disabled != disabled {
+            info!("Tools disabled by {:?}: {:?}", path, disabled);
+        }
+        *state = FileState { modified, disabled };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_flags_file_is_reloaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tool-flags.json");
+        std::fs::write(&path, r#"{"disabled": ["apply_layout"]}"#).unwrap();
+
+        let flags = ToolFlags::new(vec!["execute_component".to_string()], Some(path.clone()));
+        assert!(!flags.is_enabled("apply_layout"));
+        assert!(!flags.is_enabled("execute_component"));
+        assert!(flags.is_enabled("create_node"));
+
+        std::fs::write(&path, r#"{"disabled": ["create_node"]}"#).unwrap();
+        // Make sure the change is visible even on coarse-grained file systems
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5))
+            .unwrap();
+
+        assert!(flags.is_enabled("apply_layout"));
+        assert_eq!(flags.disabled(), vec!["create_node", "execute_component"]);
+    }
+}
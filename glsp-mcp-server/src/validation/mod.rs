@@ -1,2 +1,399 @@
-// Placeholder for future validation rules and engines
-// This module can be expanded to include domain-specific validation logic
+//! Structural validation of diagrams
+//!
+//! Used to vet diagrams arriving from outside the server, e.g. through
+//! `import_workspace`, before they are committed to the store. The checks
+//! cover referential integrity (edges and child lists pointing at missing
+//! elements), ID uniqueness (every element stored under its own ID) and the
+//! hierarchy (subtasks naming the parent that lists them, and no element
+//! containing itself through child lists).
+//! [`repair_diagram`] fixes what can be fixed by dropping or re-keying the
+//! offending entries and reports each change.
+//!
+//! [`check_integrity`] adds checks that only make sense for diagrams already
+//! in the store, such as nodes no container lists as a child, and backs the
+//! `check_integrity` maintenance tool.
+
+use crate::model::DiagramModel;
+use crate::operations::graph::{endpoints, is_edge};
+use crate::operations::hierarchy::{containment_cycles, parent_task, PARENT_TASK_PROPERTY};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Kind of structural problem found in a diagram
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IssueKind {
+    /// Several entries carry the same element ID
+    DuplicateElementId,
+    /// An element is stored under a key other than its ID
+    ElementIdMismatch,
+    /// An edge's source or target does not exist
+    DanglingEdge,
+    /// A child list references a missing element
+    DanglingChild,
+    /// A node is not a child of the root or of any container
+    OrphanedElement,
+    /// A subtask names a parent task that does not list it as a child
+    ParentMismatch,
+    /// Child lists make an element its own ancestor
+    ContainmentCycle,
+}
+
+/// A single problem, located by diagram and element
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationIssue {
+    pub diagram_id: String,
+    pub element_id: String,
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn new(diagram: &DiagramModel, element_id: &str, kind: IssueKind, message: String) -> Self {
+        Self {
+            diagram_id: diagram.id.clone(),
+            element_id: element_id.to_string(),
+            kind,
+            message,
+        }
+    }
+}
+
+/// Check a diagram without modifying it; an empty result means it is sound
+pub fn validate_diagram(diagram: &DiagramModel) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    // Keys grouped by the ID their element claims
+    let mut by_id: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (key, element) in &diagram.elements {
+        by_id.entry(element.id.as_str()).or_default().push(key);
+    }
+    for (id, mut keys) in by_id {
+        keys.sort_unstable();
+        if keys.len() > 1 {
+            issues.push(ValidationIssue::new(
+                diagram,
+                id,
+                IssueKind::DuplicateElementId,
+                format!("Element ID {id} is used by {} entries", keys.len()),
+            ));
+        } else if keys[0] != id {
+            issues.push(ValidationIssue::new(
+                diagram,
+                id,
+                IssueKind::ElementIdMismatch,
+                format!("Element {id} is stored under key {}", keys[0]),
+            ));
+        }
+    }
+
+    let ids: HashSet<&str> = diagram.elements.values().map(|e| e.id.as_str()).collect();
+
+    let mut elements: Vec<_> = diagram.elements.values().collect();
+    elements.sort_by(|a, b| a.id.cmp(&b.id));
+    for element in elements
+        .iter()
+        .copied()
+        .chain(std::iter::once(&diagram.root))
+    {
+        if is_edge(element) {
+            for (end, endpoint) in endpoints(element) {
+                if !ids.contains(endpoint) {
+                    issues.push(ValidationIssue::new(
+                        diagram,
+                        &element.id,
+                        IssueKind::DanglingEdge,
+                        format!("Edge {} has missing {end} {endpoint}", element.id),
+                    ));
+                }
+            }
+        }
+        for child in element.children.as_deref().unwrap_or_default() {
+            if !ids.contains(child.as_str()) {
+                issues.push(ValidationIssue::new(
+                    diagram,
+                    &element.id,
+                    IssueKind::DanglingChild,
+                    format!("Element {} lists missing child {child}", element.id),
+                ));
+            }
+        }
+    }
+
+    for element in &elements {
+        if let Some(parent) = parent_task(element) {
+            let listed = diagram
+                .elements
+                .get(parent)
+                .and_then(|p| p.children.as_deref())
+                .is_some_and(|children| children.contains(&element.id));
+            if !listed {
+                issues.push(ValidationIssue::new(
+                    diagram,
+                    &element.id,
+                    IssueKind::ParentMismatch,
+                    format!(
+                        "Task {} names parent {parent}, which does not list it",
+                        element.id
+                    ),
+                ));
+            }
+        }
+    }
+
+    for cycle in containment_cycles(diagram) {
+        issues.push(ValidationIssue::new(
+            diagram,
+            &cycle[0],
+            IssueKind::ContainmentCycle,
+            format!(
+                "Element {} contains itself: {} -> {}",
+                cycle[0],
+                cycle.join(" -> "),
+                cycle[0]
+            ),
+        ));
+    }
+
+    issues.dedup();
+    issues
+}
+
+/// Fix the problems [`validate_diagram`] reports and return them.
+///
+/// Duplicate entries keep the one stored under the element's own ID (or the
+/// first by key) and drop the rest; mismatched keys are re-keyed; dangling
+/// edges are removed; dangling child references are dropped; a containment
+/// cycle is broken by its last element no longer listing the first, which
+/// moves to the root if nothing else holds it; a subtask's parent reference
+/// is set to the task that lists it, or removed.
+pub fn repair_diagram(diagram: &mut DiagramModel) -> Vec<ValidationIssue> {
+    let issues = validate_diagram(diagram);
+    if issues.is_empty() {
+        return issues;
+    }
+
+    // Re-key every element under its own ID, keeping one entry per ID
+    let mut entries: Vec<(String, _)> = diagram.elements.drain().collect();
+    entries.sort_by(|(a_key, a), (b_key, b)| {
+        a.id.cmp(&b.id)
+            .then_with(|| (*b_key == b.id).cmp(&(*a_key == a.id)))
+            .then_with(|| a_key.cmp(b_key))
+    });
+    for (_, element) in entries {
+        diagram
+            .elements
+            .entry(element.id.clone())
+            .or_insert(element);
+    }
+
+    // Dangling edges, then child references to anything now missing
+    let ids: HashSet<String> = diagram.elements.keys().cloned().collect();
+    let dangling: Vec<String> = diagram
+        .elements
+        .values()
+        .filter(|e| is_edge(e))
+        .filter(|e| endpoints(e).iter().any(|(_, id)| !ids.contains(*id)))
+        .map(|e| e.id.clone())
+        .collect();
+    for id in &dangling {
+        diagram.elements.remove(id);
+    }
+
+    let ids: HashSet<String> = diagram.elements.keys().cloned().collect();
+    for element in diagram
+        .elements
+        .values_mut()
+        .chain(std::iter::once(&mut diagram.root))
+    {
+        if let Some(children) = &mut element.children {
+            children.retain(|child| ids.contains(child));
+        }
+    }
+
+    for cycle in containment_cycles(diagram) {
+        let (first, last) = (&cycle[0], &cycle[cycle.len() - 1]);
+        if let Some(children) = diagram
+            .elements
+            .get_mut(last)
+            .and_then(|e| e.children.as_mut())
+        {
+            children.retain(|child| child != first);
+        }
+        let held = diagram
+            .elements
+            .values()
+            .chain(std::iter::once(&diagram.root))
+            .any(|e| e.children.as_deref().unwrap_or_default().contains(first));
+        if !held {
+            diagram.add_child_to_root(first);
+        }
+    }
+
+    let listed_by: HashMap<String, String> = diagram
+        .elements
+        .values()
+        .flat_map(|parent| {
+            parent
+                .children
+                .iter()
+                .flatten()
+                .map(|child| (child.clone(), parent.id.clone()))
+        })
+        .collect();
+    for element in diagram.elements.values_mut() {
+        let Some(parent) = parent_task(element) else {
+            continue;
+        };
+        match listed_by.get(&element.id) {
+            Some(listed) if listed == parent => {}
+            Some(listed) => {
+                element.properties.insert(
+                    PARENT_TASK_PROPERTY.to_string(),
+                    serde_json::Value::String(listed.clone()),
+                );
+            }
+            None => {
+                element.properties.remove(PARENT_TASK_PROPERTY);
+            }
+        }
+    }
+
+    issues
+}
+
+/// Nodes that neither the root nor any container lists as a child
+fn orphaned_nodes(diagram: &DiagramModel) -> Vec<String> {
+    let contained: HashSet<&str> = diagram
+        .elements
+        .values()
+        .filter(|e| e.id != diagram.root.id)
+        .chain(std::iter::once(&diagram.root))
+        .flat_map(|e| e.children.as_deref().unwrap_or_default())
+        .map(String::as_str)
+        .collect();
+    let mut orphans: Vec<String> = diagram
+        .elements
+        .values()
+        .filter(|e| e.id != diagram.root.id && !is_edge(e) && !contained.contains(e.id.as_str()))
+        .map(|e| e.id.clone())
+        .collect();
+    orphans.sort();
+    orphans.dedup();
+    orphans
+}
+
+/// [`validate_diagram`] plus the checks for stored diagrams
+pub fn check_integrity(diagram: &DiagramModel) -> Vec<ValidationIssue> {
+    let mut issues = validate_diagram(diagram);
+    for id in orphaned_nodes(diagram) {
+        issues.push(ValidationIssue::new(
+            diagram,
+            &id,
+            IssueKind::OrphanedElement,
+            format!("Node {id} is not a child of the root or of any container"),
+        ));
+    }
+    issues
+}
+
+/// Fix the problems [`check_integrity`] reports and return them.
+///
+/// Repairs as [`repair_diagram`] does, then attaches orphaned nodes to the root.
+pub fn repair_integrity(diagram: &mut DiagramModel) -> Vec<ValidationIssue> {
+    let issues = check_integrity(diagram);
+    if issues.is_empty() {
+        return issues;
+    }
+
+    repair_diagram(diagram);
+    for id in orphaned_nodes(diagram) {
+        diagram.add_child_to_root(&id);
+    }
+    diagram.revision += 1;
+    diagram.updated_at = chrono::Utc::now();
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Edge, Node, Position};
+
+    #[test]
+    fn test_validate_and_repair() {
+        let mut diagram = DiagramModel::new("workflow");
+        let node = Node::new("task", Position { x: 0.0, y: 0.0 }, None).base;
+        let node_id = node.id.clone();
+        diagram.add_element(node.clone());
+        diagram.add_child_to_root(&node_id);
+        // Same node stored again under a foreign key
+        diagram.elements.insert("stray".to_string(), node);
+
+        let edge = Edge::new("flow", node_id.clone(), "missing".to_string(), None).base;
+        let edge_id = edge.id.clone();
+        diagram.add_element(edge);
+        diagram.add_child_to_root(&edge_id);
+
+        let issues = validate_diagram(&diagram);
+        let kinds: Vec<IssueKind> = issues.iter().map(|i| i.kind).collect();
+        assert!(kinds.contains(&IssueKind::DuplicateElementId));
+        assert!(kinds.contains(&IssueKind::DanglingEdge));
+
+        let repaired = repair_diagram(&mut diagram);
+        assert_eq!(repaired, issues);
+        assert!(validate_diagram(&diagram).is_empty());
+        assert!(diagram.elements.contains_key(&node_id));
+        assert!(!diagram.elements.contains_key("stray"));
+        assert!(!diagram.elements.contains_key(&edge_id));
+        assert_eq!(diagram.root.children, Some(vec![node_id]));
+    }
+
+    #[test]
+    fn test_integrity_reattaches_orphans() {
+        let mut diagram = DiagramModel::new("workflow");
+        let node = Node::new("task", Position { x: 0.0, y: 0.0 }, None).base;
+        let node_id = node.id.clone();
+        diagram.add_element(node);
+        // Edges need no container
+        let edge = Edge::new("flow", node_id.clone(), node_id.clone(), None).base;
+        diagram.add_element(edge);
+
+        assert!(validate_diagram(&diagram).is_empty());
+        let issues = check_integrity(&diagram);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, IssueKind::OrphanedElement);
+        assert_eq!(issues[0].element_id, node_id);
+
+        assert_eq!(repair_integrity(&mut diagram), issues);
+        assert!(check_integrity(&diagram).is_empty());
+        assert_eq!(diagram.root.children, Some(vec![node_id]));
+    }
+
+    #[test]
+    fn test_containment_cycles_are_reported_and_broken() {
+        let mut diagram = DiagramModel::new("workflow");
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let node = Node::new("task", Position { x: 0.0, y: 0.0 }, None).base;
+            ids.push(node.id.clone());
+            diagram.add_element(node);
+        }
+        ids.sort();
+        let (first, second) = (ids[0].clone(), ids[1].clone());
+        diagram.elements.get_mut(&first).unwrap().children = Some(vec![second.clone()]);
+        diagram.elements.get_mut(&second).unwrap().children = Some(vec![first.clone()]);
+
+        let issues = validate_diagram(&diagram);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, IssueKind::ContainmentCycle);
+        assert_eq!(issues[0].element_id, first);
+
+        assert_eq!(repair_diagram(&mut diagram), issues);
+        assert!(validate_diagram(&diagram).is_empty());
+        assert_eq!(diagram.elements[&second].children, Some(vec![]));
+        assert_eq!(diagram.root.children, Some(vec![first]));
+    }
+}
@@ -0,0 +1,96 @@
+//! Machine-readable warnings attached to tool results
+//!
+//! Tool results only carry content items, so warnings travel as one extra
+//! text item holding `{"warnings": [{"code", "message"}]}`, appended after
+//! the tool's own content. Clients that do not know about warnings keep
+//! reading the first item as before; clients that do can log them without
+//! matching on prose.
+//!
+//! The main use is announcing response shapes that are going away, such as
+//! results whose IDs clients scrape out of a sentence.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Key of the content item carrying warnings
+pub const WARNINGS_KEY: &str = "warnings";
+
+/// The text result of the tool is deprecated
+pub const DEPRECATED_TEXT_RESULT: &str = "deprecated_text_result";
+
+/// Tools whose result is a sentence with IDs embedded in it
+pub const TEXT_RESULT_TOOLS: &[&str] = &[
+    "create_diagram",
+    "create_node",
+    "create_edge",
+    "create_hyperedge",
+    "delete_element",
+    "update_element",
+    "load_wasm_component",
+];
+
+/// One warning about a tool result
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolWarning {
+    pub code: String,
+    pub message: String,
+}
+
+impl ToolWarning {
+    pub fn new(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            code: code.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Deprecation warnings that apply to every successful call of `tool`
+pub fn deprecations(tool: &str) -> Vec<ToolWarning> {
+    let mut warnings = Vec::new();
+    if TEXT_RESULT_TOOLS.contains(&tool) {
+        warnings.push(ToolWarning::new(
+            DEPRECATED_TEXT_RESULT,
+            format!(
+                "The text result of {tool} is deprecated and its wording may change; \
+                 do not parse IDs out of it but read them from structured content"
+            ),
+        ));
+    }
+    warnings
+}
+
+/// The content item carrying `warnings`, or `None` when there are none
+pub fn warnings_item(warnings: &[ToolWarning]) -> Option<String> {
+    (!warnings.is_empty()).then(|| json!({ WARNINGS_KEY: warnings }).to_string())
+}
+
+/// Warnings carried by the content items of a tool result
+pub fn parse_warnings<'a>(texts: impl IntoIterator<Item = &'a str>) -> Vec<ToolWarning> {
+    texts
+        .into_iter()
+        .filter_map(|text| serde_json::from_str::<Value>(text).ok())
+        .filter_map(|mut value| value.get_mut(WARNINGS_KEY).map(Value::take))
+        .filter_map(|warnings| serde_json::from_value::<Vec<ToolWarning>>(warnings).ok())
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deprecated_tools_carry_a_warning_item() {
+        assert!(deprecations("get_diagram").is_empty());
+        assert!(warnings_item(&[]).is_none());
+
+        let warnings = deprecations("create_node");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, DEPRECATED_TEXT_RESULT);
+
+        let item = warnings_item(&warnings).unwrap();
+        let texts = ["Created task node with ID: n-1 at (0, 0)", item.as_str()];
+        assert_eq!(parse_warnings(texts), warnings);
+    }
+}
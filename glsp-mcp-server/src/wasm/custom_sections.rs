@@ -0,0 +1,147 @@
+//! Custom section metadata of components
+//!
+//! Build tooling can embed metadata such as author, version or build info in
+//! named custom sections. Sections holding UTF-8 text are decoded, as JSON
+//! when they parse as JSON and as a string otherwise. Binary sections, and
+//! well-known sections with a binary encoding such as `name` or `producers`,
+//! are reported by name and size only.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasmparser::{Parser, Payload};
+
+/// Key in `WasmComponent::metadata` holding the sections found at load time
+pub const CUSTOM_SECTIONS_KEY: &str = "customSections";
+
+/// Text sections larger than this are reported like binary ones
+const MAX_DECODED_SIZE: usize = 64 * 1024;
+
+/// Sections whose content is a binary encoding even when it happens to be valid UTF-8
+const BINARY_SECTIONS: &[&str] = &[
+    "name",
+    "component-name",
+    "producers",
+    "target_features",
+    "linking",
+    "dylink.0",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SectionEncoding {
+    Json,
+    Text,
+    Binary,
+}
+
+/// One custom section of a component or of a core module inside it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomSection {
+    pub name: String,
+    pub size: usize,
+    pub encoding: SectionEncoding,
+    /// Decoded content; absent for binary sections
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+}
+
+fn is_binary_section(name: &str) -> bool {
+    BINARY_SECTIONS.contains(&name)
+        || name.starts_with(".debug")
+        || name.starts_with("reloc.")
+        || name.starts_with("component-type")
+}
+
+fn decode(name: &str, data: &[u8]) -> (SectionEncoding, Option<Value>) {
+    if is_binary_section(name) || data.len() > MAX_DECODED_SIZE {
+        return (SectionEncoding::Binary, None);
+    }
+    let Ok(text) = std::str::from_utf8(data) else {
+        return (SectionEncoding::Binary, None);
+    };
+    if text
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+    {
+        return (SectionEncoding::Binary, None);
+    }
+    match serde_json::from_str::<Value>(text) {
+        Ok(json) => (SectionEncoding::Json, Some(json)),
+        Err(_) => (SectionEncoding::Text, Some(Value::String(text.to_string()))),
+    }
+}
+
+/// Every custom section in a component or module binary, in file order
+pub fn read_custom_sections(
+    wasm_bytes: &[u8],
+) -> Result<Vec<CustomSection>, wasmparser::BinaryReaderError> {
+    let mut sections = Vec::new();
+    for payload in Parser::new(0).parse_all(wasm_bytes) {
+        if let Payload::CustomSection(reader) = payload? {
+            let (encoding, value) = decode(reader.name(), reader.data());
+            sections.push(CustomSection {
+                name: reader.name().to_string(),
+                size: reader.data().len(),
+                encoding,
+                value,
+            });
+        }
+    }
+    Ok(sections)
+}
+
+/// Sections keyed by name: decoded content, or `{"size": ..}` for binary ones.
+///
+/// When a name occurs more than once the first section wins.
+pub fn section_metadata(sections: &[CustomSection]) -> serde_json::Map<String, Value> {
+    let mut metadata = serde_json::Map::new();
+    for section in sections {
+        metadata.entry(section.name.clone()).or_insert_with(|| {
+            section
+                .value
+                .clone()
+                .unwrap_or_else(|| serde_json::json!({"size": section.size, "encoding": "binary"}))
+        });
+    }
+    metadata
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn custom_section(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut payload = vec![name.len() as u8];
+        payload.extend_from_slice(name.as_bytes());
+        payload.extend_from_slice(data);
+        let mut section = vec![0, payload.len() as u8];
+        section.extend(payload);
+        section
+    }
+
+    #[test]
+    fn test_reads_text_json_and_binary_sections() {
+        let mut module = b"\0asm\x01\0\0\0".to_vec();
+        module.extend(custom_section(
+            "build-info",
+            br#"{"version": "1.4.2", "commit": "abc123"}"#,
+        ));
+        module.extend(custom_section("author", b"ADAS team"));
+        module.extend(custom_section("calibration", &[0, 159, 146, 150]));
+        module.extend(custom_section("author", b"shadowed"));
+
+        let sections = read_custom_sections(&module).unwrap();
+        assert_eq!(sections.len(), 4);
+        assert_eq!(sections[0].encoding, SectionEncoding::Json);
+        assert_eq!(sections[2].encoding, SectionEncoding::Binary);
+
+        let metadata = section_metadata(&sections);
+        assert_eq!(metadata["build-info"]["version"], "1.4.2");
+        assert_eq!(metadata["author"], "ADAS team");
+        assert_eq!(
+            metadata["calibration"],
+            json!({"size": 4, "encoding": "binary"})
+        );
+    }
+}
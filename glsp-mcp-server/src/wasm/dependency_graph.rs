@@ -0,0 +1,332 @@
+//! Dependency graph between loaded components
+//!
+//! Each import of a component is resolved against the exports of every other
+//! component. Interface names are matched exactly first and then without
+//! their `@version` suffix. Imports in the `wasi:` namespace are provided by
+//! the host runtime and never count as unsatisfied.
+//!
+//! [`find_components_by_interface`] answers the reverse question for
+//! planning compositions: which components provide or need an interface.
+
+use super::WasmComponent;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Namespace of interfaces provided by the host runtime
+const HOST_NAMESPACE: &str = "wasi:";
+
+/// How one import of a component is satisfied
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResolution {
+    pub interface: String,
+    /// Components exporting the interface
+    pub providers: Vec<String>,
+    pub host_provided: bool,
+}
+
+impl ImportResolution {
+    pub fn is_satisfied(&self) -> bool {
+        self.host_provided || !self.providers.is_empty()
+    }
+}
+
+/// Imports and exports of one component
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentDependencies {
+    pub component: String,
+    pub imports: Vec<ImportResolution>,
+    pub exports: Vec<String>,
+}
+
+/// An import no loaded component or the host can provide
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsatisfiedImport {
+    pub component: String,
+    pub interface: String,
+}
+
+/// `component -> required interfaces -> providing components`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyGraph {
+    pub components: Vec<ComponentDependencies>,
+    pub unsatisfied: Vec<UnsatisfiedImport>,
+}
+
+fn unversioned(interface: &str) -> &str {
+    interface.split('@').next().unwrap_or(interface)
+}
+
+/// Interface name without namespace, package and version: `sensor-input`
+/// for `adas:sensors/sensor-input@0.1.0`
+fn bare_name(interface: &str) -> &str {
+    let name = unversioned(interface);
+    name.rsplit(['/', ':']).next().unwrap_or(name)
+}
+
+/// Metadata key holding the world a component targets
+const WORLD_KEY: &str = "wit_world";
+
+/// A component importing or exporting a searched-for interface
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterfaceMatch {
+    pub component: String,
+    /// World the component targets, when its WIT named one
+    pub world: Option<String>,
+    /// Full name of the matching interface
+    pub interface: String,
+    /// `import` or `export`
+    pub direction: String,
+}
+
+/// Components importing or exporting `interface_name`, sorted by component,
+/// exports first.
+///
+/// A fully qualified name (`adas:sensors/sensor-input@0.1.0`) matches exactly
+/// or, failing that, ignoring versions; a bare name (`sensor-input`) matches
+/// the interface in any package. `direction` limits the search to `import`
+/// or `export`. Components whose files are missing are skipped.
+pub fn find_components_by_interface(
+    components: &[&WasmComponent],
+    interface_name: &str,
+    direction: Option<&str>,
+) -> Vec<InterfaceMatch> {
+    let qualified = interface_name.contains(['/', ':']);
+    let candidates = || {
+        components
+            .iter()
+            .filter(|c| c.file_exists)
+            .flat_map(|c| c.interfaces.iter().map(move |i| (*c, i)))
+            .filter(|(_, i)| direction.is_none_or(|d| i.interface_type == d))
+    };
+    let exact: Vec<_> = candidates()
+        .filter(|(_, i)| {
+            if qualified {
+                i.name == interface_name
+            } else {
+                bare_name(&i.name) == interface_name
+            }
+        })
+        .collect();
+    let found = if exact.is_empty() && qualified {
+        candidates()
+            .filter(|(_, i)| unversioned(&i.name) == unversioned(interface_name))
+            .collect()
+    } else {
+        exact
+    };
+
+    let mut matches: Vec<InterfaceMatch> = found
+        .into_iter()
+        .map(|(component, interface)| InterfaceMatch {
+            component: component.name.clone(),
+            world: component
+                .metadata
+                .get(WORLD_KEY)
+                .and_then(|w| w.as_str())
+                .filter(|w| *w != "unknown")
+                .map(str::to_string),
+            interface: interface.name.clone(),
+            direction: interface.interface_type.clone(),
+        })
+        .collect();
+    let key = |m: &InterfaceMatch| {
+        (
+            m.component.clone(),
+            m.direction != "export",
+            m.interface.clone(),
+        )
+    };
+    matches.sort_by_key(key);
+    matches.dedup();
+    matches
+}
+
+/// Build the dependency graph for a set of components.
+///
+/// Components whose files are missing are skipped. Output is sorted by
+/// component and interface name.
+pub fn build_dependency_graph(components: &[&WasmComponent]) -> DependencyGraph {
+    let mut available: Vec<&WasmComponent> = components
+        .iter()
+        .copied()
+        .filter(|c| c.file_exists)
+        .collect();
+    available.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let interfaces_of = |component: &WasmComponent, kind: &str| -> Vec<String> {
+        let mut names: Vec<String> = component
+            .interfaces
+            .iter()
+            .filter(|i| i.interface_type == kind)
+            .map(|i| i.name.clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    };
+
+    // Exported interface name -> exporting components
+    let mut exporters: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for component in &available {
+        for interface in interfaces_of(component, "export") {
+            exporters
+                .entry(interface)
+                .or_default()
+                .push(component.name.clone());
+        }
+    }
+
+    let mut graph = DependencyGraph::default();
+    for component in &available {
+        let imports = interfaces_of(component, "import")
+            .into_iter()
+            .map(|interface| {
+                let mut providers: Vec<String> = match exporters.get(&interface) {
+                    Some(exact) => exact.clone(),
+                    None => exporters
+                        .iter()
+                        .filter(|(name, _)| unversioned(name) == unversioned(&interface))
+                        .flat_map(|(_, providers)| providers.iter().cloned())
+                        .collect(),
+                };
+                providers.retain(|p| *p != component.name);
+                providers.sort();
+                providers.dedup();
+
+                ImportResolution {
+                    host_provided: interface.starts_with(HOST_NAMESPACE),
+                    interface,
+                    providers,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        graph
+            .unsatisfied
+            .extend(
+                imports
+                    .iter()
+                    .filter(|i| !i.is_satisfied())
+                    .map(|i| UnsatisfiedImport {
+                        component: component.name.clone(),
+                        interface: i.interface.clone(),
+                    }),
+            );
+        graph.components.push(ComponentDependencies {
+            component: component.name.clone(),
+            imports,
+            exports: interfaces_of(component, "export"),
+        });
+    }
+
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wasm::WasmInterface;
+    use std::collections::HashMap;
+
+    fn component(name: &str, imports: &[&str], exports: &[&str]) -> WasmComponent {
+        let interface = |name: &&str, kind: &str| WasmInterface {
+            name: name.to_string(),
+            interface_type: kind.to_string(),
+            functions: Vec::new(),
+        };
+        WasmComponent {
+            name: name.to_string(),
+            path: format!("{name}.wasm"),
+            description: String::new(),
+            file_exists: true,
+            last_seen: None,
+            removed_at: None,
+            interfaces: imports
+                .iter()
+                .map(|i| interface(i, "import"))
+                .chain(exports.iter().map(|e| interface(e, "export")))
+                .collect(),
+            metadata: HashMap::new(),
+            wit_interfaces: None,
+            dependencies: Vec::new(),
+            security_analysis: None,
+            last_security_scan: None,
+        }
+    }
+
+    #[test]
+    fn test_dependency_graph_resolves_providers() {
+        let camera = component(
+            "camera",
+            &["wasi:io/streams"],
+            &["adas:sensors/frames@0.1.0"],
+        );
+        let detector = component(
+            "detector",
+            &["adas:sensors/frames@0.2.0", "adas:fusion/tracks"],
+            &["adas:vision/objects"],
+        );
+        let graph = build_dependency_graph(&[&detector, &camera]);
+
+        assert_eq!(graph.components[0].component, "camera");
+        assert!(graph.components[0].imports[0].host_provided);
+
+        let detector_imports = &graph.components[1].imports;
+        assert_eq!(detector_imports[1].interface, "adas:sensors/frames@0.2.0");
+        assert_eq!(detector_imports[1].providers, vec!["camera"]);
+
+        assert_eq!(
+            graph.unsatisfied,
+            vec![UnsatisfiedImport {
+                component: "detector".to_string(),
+                interface: "adas:fusion/tracks".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_find_components_by_interface() {
+        let mut camera = component("camera", &[], &["adas:sensors/sensor-input@0.1.0"]);
+        camera
+            .metadata
+            .insert(WORLD_KEY.to_string(), serde_json::json!("camera-world"));
+        let fusion = component("fusion", &["adas:sensors/sensor-input@0.2.0"], &[]);
+        let mut removed = component("lidar", &[], &["adas:sensors/sensor-input@0.1.0"]);
+        removed.file_exists = false;
+        let components = [&fusion, &removed, &camera];
+
+        let found = find_components_by_interface(&components, "sensor-input", None);
+        assert_eq!(
+            found,
+            vec![
+                InterfaceMatch {
+                    component: "camera".to_string(),
+                    world: Some("camera-world".to_string()),
+                    interface: "adas:sensors/sensor-input@0.1.0".to_string(),
+                    direction: "export".to_string(),
+                },
+                InterfaceMatch {
+                    component: "fusion".to_string(),
+                    world: None,
+                    interface: "adas:sensors/sensor-input@0.2.0".to_string(),
+                    direction: "import".to_string(),
+                },
+            ]
+        );
+
+        // A version nothing has falls back to any version of the interface
+        let providers = find_components_by_interface(
+            &components,
+            "adas:sensors/sensor-input@0.3.0",
+            Some("export"),
+        );
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].component, "camera");
+        assert!(find_components_by_interface(&components, "tracks", None).is_empty());
+    }
+}
@@ -5,16 +5,92 @@
  * Replaces client-side execution for better security and performance.
  */
 
+use crate::cpu_pool::CpuPool;
+use crate::wasm::execution_limiter::{ExecutionConcurrency, ExecutionLimiter, ExecutionPermit};
+use crate::wasm::instance_pool::{InstancePool, PoolOverflow, PooledInstance, Reservation};
+use crate::wasm::module_cache::{EngineOptions, ModuleCache};
+use crate::wasm::result_cache::{ResultCache, ResultCacheStats};
 use crate::wasm::sensor_bridge::{SensorBridgeConfig, SensorDataBridge};
 use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::time::timeout;
-use wasmtime::{Config, Engine, Instance, Module, OptLevel, Store};
+use wasmtime::{Config, Engine, Instance, Module, Store, Trap, UpdateDeadline};
+
+/// Memory granted to the throwaway store used by instantiation checks
+const INSTANTIATION_CHECK_MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+
+/// Shortest epoch tick; anything finer only burns CPU
+const MIN_EPOCH_TICK: Duration = Duration::from_millis(1);
+
+/// Resource exhaustion by a component, returned as the execution's error
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ComponentError {
+    #[error(
+        "stack overflow: the component exceeded the WASM stack limit of {max_wasm_stack} bytes"
+    )]
+    StackOverflow { max_wasm_stack: usize },
+
+    #[error("instance limit exceeded: the component needs more than {max_instances} instances")]
+    InstanceLimitExceeded { max_instances: usize },
+}
+
+impl ComponentError {
+    /// Recognize resource exhaustion in an instantiation or execution error
+    fn classify(error: &anyhow::Error, limits: &ExecutionLimits) -> Option<Self> {
+        if error.downcast_ref::<Trap>() == Some(&Trap::StackOverflow) {
+            return Some(ComponentError::StackOverflow {
+                max_wasm_stack: limits.max_wasm_stack,
+            });
+        }
+        // wasmtime reports the instance limit as a plain message, not a typed error
+        if error
+            .chain()
+            .any(|cause| cause.to_string().contains("instance count too high"))
+        {
+            return Some(ComponentError::InstanceLimitExceeded {
+                max_instances: limits.max_instances,
+            });
+        }
+        None
+    }
+}
+
+/// Error a component run fails with once
+/// [`WasmExecutionEngine::cancel_execution`] has stopped it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("execution cancelled")]
+pub struct ExecutionCancelled;
+
+/// Error a component run fails with once it has run past its timeout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("execution timed out")]
+pub struct ExecutionTimedOut;
+
+/// What [`WasmExecutionEngine::cancel_execution`] did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CancelOutcome {
+    /// The execution was running and has been told to stop
+    Cancelled,
+    /// The execution had already finished; nothing changed
+    AlreadyFinished,
+    NotFound,
+}
+
+/// Limits applied to every store, kept for error reporting
+#[derive(Debug, Clone, Copy)]
+struct ExecutionLimits {
+    max_wasm_stack: usize,
+    max_instances: usize,
+    /// Whether stores get a fuel budget (see [`EngineOptions::fuel_metering`])
+    fuel_metering: bool,
+}
 
 /// Execution context for a WASM component
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +104,16 @@ pub struct ExecutionContext {
     pub created_at: DateTime<Utc>,
     /// Optional sensor bridge configuration for sensor-driven components
     pub sensor_config: Option<SensorBridgeConfig>,
+    /// Record instantiation time, fuel consumption and peak memory
+    #[serde(default)]
+    pub profile: bool,
+    /// Always run, even if the result cache holds a result for these inputs
+    #[serde(default)]
+    pub no_cache: bool,
+    /// Record a span for the exported function call; off by default because
+    /// of the overhead
+    #[serde(default)]
+    pub trace: bool,
 }
 
 /// Progress updates during execution
@@ -39,6 +125,7 @@ pub enum ExecutionStage {
     Processing,
     Complete,
     Error,
+    Cancelled,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,6 +150,76 @@ pub struct ExecutionResult {
     pub output_data: Option<Vec<u8>>, // For binary output (graphics, etc.)
     pub graphics_output: Option<GraphicsOutput>,
     pub completed_at: DateTime<Utc>,
+    /// Profiling data, present when the execution was started with profiling enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<ExecutionProfile>,
+    /// Served from the result cache instead of running the component
+    #[serde(default)]
+    pub cached: bool,
+    /// Exported function calls, present when the execution was traced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Vec<TraceSpan>>,
+}
+
+/// Entry, exit and duration of one exported function call
+///
+/// Components run in a traced pipeline contribute one span per stage, so
+/// the spans of a composite execution form a flat list in call order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceSpan {
+    pub component: String,
+    pub function: String,
+    /// Pipeline stage that made the call, for composite executions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stage_id: Option<String>,
+    pub entered_at: DateTime<Utc>,
+    pub exited_at: DateTime<Utc>,
+    pub duration_us: u64,
+    /// False when the call trapped, failed or timed out
+    pub success: bool,
+}
+
+/// Profiling data for a single execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionProfile {
+    pub instantiation_time_us: u64,
+    pub execution_time_us: u64,
+    /// Fuel the call used; 0 unless the engine meters fuel
+    pub fuel_consumed: u64,
+    pub peak_memory_bytes: u64,
+}
+
+/// Aggregated profiling statistics for a component across profiled executions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComponentProfileStats {
+    pub executions: u64,
+    pub avg_instantiation_time_us: u64,
+    pub max_instantiation_time_us: u64,
+    pub avg_execution_time_us: u64,
+    pub avg_fuel_consumed: u64,
+    pub max_fuel_consumed: u64,
+    pub peak_memory_bytes: u64,
+    total_instantiation_time_us: u64,
+    total_execution_time_us: u64,
+    total_fuel_consumed: u64,
+}
+
+impl ComponentProfileStats {
+    fn record(&mut self, profile: &ExecutionProfile) {
+        self.executions += 1;
+        self.total_instantiation_time_us += profile.instantiation_time_us;
+        self.total_execution_time_us += profile.execution_time_us;
+        self.total_fuel_consumed += profile.fuel_consumed;
+        self.max_instantiation_time_us = self
+            .max_instantiation_time_us
+            .max(profile.instantiation_time_us);
+        self.max_fuel_consumed = self.max_fuel_consumed.max(profile.fuel_consumed);
+        self.peak_memory_bytes = self.peak_memory_bytes.max(profile.peak_memory_bytes);
+
+        self.avg_instantiation_time_us = self.total_instantiation_time_us / self.executions;
+        self.avg_execution_time_us = self.total_execution_time_us / self.executions;
+        self.avg_fuel_consumed = self.total_fuel_consumed / self.executions;
+    }
 }
 
 /// Graphics output from WASM components using wasi-gfx
@@ -96,10 +253,75 @@ pub enum VideoFormat {
 pub struct WasmExecutionEngine {
     engine: Engine,
     executions: Arc<Mutex<HashMap<String, ExecutionInfo>>>,
-    max_concurrent: usize,
-    component_cache: Arc<Mutex<HashMap<String, Module>>>,
+    /// Caps how many executions run at once
+    limiter: Arc<ExecutionLimiter>,
+    component_cache: Arc<ModuleCache>,
     /// Optional dataset manager for sensor data bridge
     dataset_manager: Option<Arc<tokio::sync::Mutex<crate::database::BoxedDatasetManager>>>,
+    /// Aggregated profiling statistics keyed by component name
+    profile_stats: Arc<Mutex<HashMap<String, ComponentProfileStats>>>,
+    /// Reusable instances per component; `None` instantiates on every call
+    instance_pool: Option<Arc<InstancePool<StoreState>>>,
+    /// Results of earlier calls; `None` always runs the component
+    result_cache: Option<Arc<ResultCache>>,
+    limits: ExecutionLimits,
+    /// Runs executions off the async runtime; `None` runs them as tasks
+    cpu_pool: Option<CpuPool>,
+    _epoch_ticker: EpochTicker,
+}
+
+/// Thread advancing the engine epoch at a fixed interval so running calls
+/// check their deadline and cancel flag; stops when dropped
+struct EpochTicker {
+    stop: Arc<AtomicBool>,
+}
+
+impl EpochTicker {
+    fn start(engine: Engine, interval: Duration) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let interval = interval.max(MIN_EPOCH_TICK);
+        let spawned = std::thread::Builder::new()
+            .name("wasm-epoch-ticker".to_string())
+            .spawn(move || {
+                while !stopped.load(Ordering::Relaxed) {
+                    std::thread::sleep(interval);
+                    engine.increment_epoch();
+                }
+            });
+        if let Err(e) = spawned {
+            tracing::error!(
+                "Failed to start epoch ticker; timeouts cannot stop running calls: {e}"
+            );
+        }
+        Self { stop }
+    }
+}
+
+impl Drop for EpochTicker {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Per-store state: resource limits plus profiling measurements
+struct StoreState {
+    limiter: ResourceLimiter,
+    instantiation_time: Option<Duration>,
+    /// Set to stop the running call at its next epoch check
+    cancel: Arc<AtomicBool>,
+    /// When the running call times out, checked on every epoch tick
+    deadline: Option<Instant>,
+}
+
+impl StoreState {
+    /// Prepare a pooled store for its next call
+    fn reset(&mut self, memory_limit: usize, cancel: Arc<AtomicBool>) {
+        self.limiter.memory_limit = memory_limit;
+        self.instantiation_time = None;
+        self.cancel = cancel;
+        self.deadline = None;
+    }
 }
 
 #[derive(Debug)]
@@ -111,11 +333,17 @@ struct ExecutionInfo {
     result: Option<ExecutionResult>,
     /// Optional sensor bridge for this execution
     sensor_bridge: Option<Arc<SensorDataBridge>>,
+    cancel: Arc<AtomicBool>,
 }
 
 impl WasmExecutionEngine {
-    /// Create a new execution engine with security configuration
+    /// Create a new execution engine with security configuration and default compilation settings
     pub fn new(max_concurrent: usize) -> Result<Self> {
+        Self::with_options(max_concurrent, EngineOptions::default())
+    }
+
+    /// Create a new execution engine with the given compilation settings
+    pub fn with_options(max_concurrent: usize, options: EngineOptions) -> Result<Self> {
         // Configure Wasmtime with security restrictions
         let mut config = Config::new();
 
@@ -123,8 +351,8 @@ impl WasmExecutionEngine {
         config.wasm_component_model(true);
 
         // Security settings
-        config.cranelift_opt_level(OptLevel::Speed);
-        config.max_wasm_stack(512 * 1024); // 512KB stack limit
+        config.cranelift_opt_level(options.opt_level.to_cranelift());
+        config.max_wasm_stack(options.max_wasm_stack);
         config.wasm_bulk_memory(true);
         config.wasm_multi_value(true);
         config.wasm_reference_types(true);
@@ -133,24 +361,55 @@ impl WasmExecutionEngine {
         config.wasm_threads(false); // No threading for security
         config.wasm_simd(true); // SIMD is safe
 
+        // Fuel metering lets profiled runs report consumption but slows every
+        // run, so it is opt-in; stores get an effectively unlimited budget
+        config.consume_fuel(options.fuel_metering);
+
+        // Epoch checks let timeouts and cancel_execution stop a running call
+        config.epoch_interruption(true);
+
         // Create engine
         let engine = Engine::new(&config).context("Failed to create Wasmtime engine")?;
+        let epoch_ticker = EpochTicker::start(engine.clone(), options.epoch_tick);
 
         Ok(Self {
             engine,
             executions: Arc::new(Mutex::new(HashMap::new())),
-            max_concurrent,
-            component_cache: Arc::new(Mutex::new(HashMap::new())),
+            limiter: Arc::new(ExecutionLimiter::new(
+                max_concurrent,
+                options.execution_queue,
+            )),
+            component_cache: Arc::new(ModuleCache::new(options.precompile_cache_dir)),
             dataset_manager: None,
+            profile_stats: Arc::new(Mutex::new(HashMap::new())),
+            instance_pool: (options.instance_pool.size > 0)
+                .then(|| Arc::new(InstancePool::new(options.instance_pool))),
+            result_cache: (options.result_cache.max_entries > 0)
+                .then(|| Arc::new(ResultCache::new(options.result_cache))),
+            limits: ExecutionLimits {
+                max_wasm_stack: options.max_wasm_stack,
+                max_instances: options.max_instances,
+                fuel_metering: options.fuel_metering,
+            },
+            cpu_pool: None,
+            _epoch_ticker: epoch_ticker,
         })
     }
 
+    /// Run executions on `pool`, so a long synchronous WASM call holds a pool
+    /// thread instead of an async runtime worker
+    pub fn with_cpu_pool(mut self, pool: CpuPool) -> Self {
+        self.cpu_pool = Some(pool);
+        self
+    }
+
     /// Create a new execution engine with sensor data support
     pub fn with_dataset_manager(
         max_concurrent: usize,
         dataset_manager: Arc<tokio::sync::Mutex<crate::database::BoxedDatasetManager>>,
+        options: EngineOptions,
     ) -> Result<Self> {
-        let mut engine = Self::new(max_concurrent)?;
+        let mut engine = Self::with_options(max_concurrent, options)?;
         engine.dataset_manager = Some(dataset_manager);
         Ok(engine)
     }
@@ -164,14 +423,41 @@ impl WasmExecutionEngine {
         let execution_id = context.execution_id.clone();
         let execution_id_for_spawn = execution_id.clone();
 
-        // Check concurrent execution limit
-        {
-            let executions = self.executions.lock().unwrap();
-            if executions.len() >= self.max_concurrent {
-                return Err(anyhow!("Maximum concurrent executions reached"));
+        // Profiled, traced and sensor-driven runs are never served from the cache
+        let cache_key = match &self.result_cache {
+            Some(_)
+                if !context.no_cache
+                    && !context.profile
+                    && !context.trace
+                    && context.sensor_config.is_none() =>
+            {
+                tokio::fs::read(component_path)
+                    .await
+                    .ok()
+                    .map(|bytes| ResultCache::key(&bytes, &context.method, &context.args))
+            }
+            _ => None,
+        };
+        if let (Some(cache), Some(key)) = (&self.result_cache, &cache_key) {
+            if let Some(cached) = cache.get(key) {
+                self.record_cached(context, cached);
+                return Ok(execution_id);
             }
         }
 
+        // With the reject policies a busy server or component fails before
+        // anything starts; otherwise the execution queues once it is spawned
+        let permit = match self.limiter.overflow() {
+            PoolOverflow::Reject => Some(self.limiter.try_acquire(&context.component_name)?),
+            PoolOverflow::Queue => None,
+        };
+        let reservation = match &self.instance_pool {
+            Some(pool) if pool.overflow() == PoolOverflow::Reject => {
+                Some(pool.try_reserve(&context.component_name)?)
+            }
+            _ => None,
+        };
+
         // Initialize execution tracking
         let progress = ExecutionProgress {
             execution_id: execution_id.clone(),
@@ -209,12 +495,14 @@ impl WasmExecutionEngine {
             None
         };
 
+        let cancel = Arc::new(AtomicBool::new(false));
         let execution_info = ExecutionInfo {
             context: context.clone(),
             start_time: Instant::now(),
             progress: progress.clone(),
             result: None,
             sensor_bridge: sensor_bridge.clone(),
+            cancel: cancel.clone(),
         };
 
         {
@@ -229,14 +517,26 @@ impl WasmExecutionEngine {
         let component_path = component_path.to_path_buf();
 
         let executions_for_cleanup = executions.clone();
-        tokio::spawn(async move {
+        let profile_stats = self.profile_stats.clone();
+        let instance_pool = self.instance_pool.clone();
+        let limits = self.limits;
+        let limiter = self.limiter.clone();
+        let result_cache = self.result_cache.clone();
+        let component_name = context.component_name.clone();
+        let cpu_pool = self.cpu_pool.clone();
+        let execution = async move {
             let result = Self::execute_component_impl(
                 engine,
                 executions.clone(),
                 component_cache,
+                instance_pool,
+                limits,
+                (limiter, permit),
+                reservation,
                 context,
                 component_path,
                 sensor_bridge.clone(),
+                cancel,
             )
             .await;
 
@@ -247,25 +547,51 @@ impl WasmExecutionEngine {
                 }
             }
 
+            if let Some(profile) = &result.profile {
+                let mut stats = profile_stats.lock().unwrap();
+                stats.entry(component_name).or_default().record(profile);
+            }
+            if let (Some(cache), Some(key)) = (result_cache, cache_key) {
+                cache.insert(key, &result);
+            }
+
             {
                 let mut executions = executions_for_cleanup.lock().unwrap();
                 if let Some(exec_info) = executions.get_mut(&execution_id_for_spawn) {
                     exec_info.result = Some(result);
                 }
             }
-        });
+        };
+        match cpu_pool {
+            Some(pool) => {
+                tokio::spawn(async move {
+                    if let Err(e) = pool.run_future(execution).await {
+                        tracing::error!("WASM execution did not complete: {}", e);
+                    }
+                });
+            }
+            None => {
+                tokio::spawn(execution);
+            }
+        }
 
         Ok(execution_id)
     }
 
     /// Internal implementation of component execution
+    #[allow(clippy::too_many_arguments)]
     async fn execute_component_impl(
         engine: Engine,
         executions: Arc<Mutex<HashMap<String, ExecutionInfo>>>,
-        component_cache: Arc<Mutex<HashMap<String, Module>>>,
+        component_cache: Arc<ModuleCache>,
+        instance_pool: Option<Arc<InstancePool<StoreState>>>,
+        limits: ExecutionLimits,
+        (limiter, permit): (Arc<ExecutionLimiter>, Option<ExecutionPermit>),
+        reservation: Option<Reservation<StoreState>>,
         context: ExecutionContext,
         component_path: std::path::PathBuf,
         sensor_bridge: Option<Arc<SensorDataBridge>>,
+        cancel: Arc<AtomicBool>,
     ) -> ExecutionResult {
         let start_time = Instant::now();
         let execution_id = context.execution_id.clone();
@@ -286,6 +612,45 @@ impl WasmExecutionEngine {
                 }
             };
 
+        // Held until the execution finishes
+        let _permit = match permit {
+            Some(permit) => permit,
+            None => {
+                update_progress(
+                    ExecutionStage::Preparing,
+                    0.0,
+                    "Waiting for an execution slot".to_string(),
+                    None,
+                );
+                match limiter.acquire(&context.component_name).await {
+                    Ok(permit) => permit,
+                    Err(busy) => {
+                        let error_msg = busy.to_string();
+                        update_progress(
+                            ExecutionStage::Error,
+                            0.0,
+                            error_msg.clone(),
+                            Some(error_msg.clone()),
+                        );
+                        return ExecutionResult {
+                            execution_id,
+                            success: false,
+                            result: None,
+                            error: Some(error_msg),
+                            execution_time_ms: start_time.elapsed().as_millis() as u64,
+                            memory_usage_mb: 0,
+                            output_data: None,
+                            graphics_output: None,
+                            completed_at: Utc::now(),
+                            profile: None,
+                            cached: false,
+                            trace: None,
+                        };
+                    }
+                }
+            }
+        };
+
         // Load component
         update_progress(
             ExecutionStage::Loading,
@@ -294,7 +659,7 @@ impl WasmExecutionEngine {
             None,
         );
 
-        let module = match Self::load_component(&engine, &component_cache, &component_path).await {
+        let module = match component_cache.load(&engine, &component_path).await {
             Ok(module) => module,
             Err(e) => {
                 let error_msg = format!("Failed to load component: {e}");
@@ -314,6 +679,9 @@ impl WasmExecutionEngine {
                     output_data: None,
                     graphics_output: None,
                     completed_at: Utc::now(),
+                    profile: None,
+                    cached: false,
+                    trace: None,
                 };
             }
         };
@@ -326,12 +694,50 @@ impl WasmExecutionEngine {
             None,
         );
 
-        let mut store = Store::new(&engine, ());
         let memory_limit = context.max_memory_mb as usize * 1024 * 1024; // Convert MB to bytes
-        let table_limit = 1000; // Max table elements
-        store.limiter(move |_| -> &mut dyn wasmtime::ResourceLimiter {
-            Box::leak(Box::new(ResourceLimiter::new(memory_limit, table_limit)))
-        });
+        let mut reservation = match (reservation, &instance_pool) {
+            (Some(reservation), _) => Some(reservation),
+            (None, Some(pool)) => {
+                update_progress(
+                    ExecutionStage::Preparing,
+                    0.2,
+                    "Waiting for a free component instance".to_string(),
+                    None,
+                );
+                Some(pool.reserve(&context.component_name).await)
+            }
+            (None, None) => None,
+        };
+
+        let (mut store, instance) = match reservation.as_mut().and_then(Reservation::take_idle) {
+            Some(PooledInstance {
+                mut store,
+                instance,
+            }) => {
+                store.data_mut().reset(memory_limit, cancel.clone());
+                store.set_epoch_deadline(1);
+                (store, Ok(instance))
+            }
+            None => {
+                let mut store = Self::new_store(&engine, memory_limit, limits.max_instances);
+                store.data_mut().cancel = cancel.clone();
+                let instance = Self::instantiate(&mut store, &module);
+                (store, instance)
+            }
+        };
+        if limits.fuel_metering {
+            if let Err(e) = store.set_fuel(u64::MAX) {
+                tracing::warn!("Failed to set execution fuel: {}", e);
+            }
+        }
+
+        // Cancelled while loading or waiting for an instance
+        if cancel.load(Ordering::SeqCst) {
+            if let (Some(reservation), Ok(instance)) = (reservation, instance) {
+                reservation.give_back(PooledInstance { store, instance });
+            }
+            return Self::cancelled_result(execution_id.clone(), start_time, update_progress);
+        }
 
         // Execute with timeout
         update_progress(
@@ -341,12 +747,47 @@ impl WasmExecutionEngine {
             None,
         );
 
+        // The deadline stops a call stuck in WASM code at the next epoch
+        // tick; the async timeout covers time spent outside it
         let timeout_duration = Duration::from_millis(context.timeout_ms);
-        let execution_future =
-            Self::run_component(&mut store, &module, &context, sensor_bridge.as_ref());
+        store.data_mut().deadline = Some(Instant::now() + timeout_duration);
+        let entered_at = Utc::now();
+        let execution_start = Instant::now();
+        let execution_future = async {
+            let instance = instance?;
+            Self::run_component(&mut store, &instance, &context, sensor_bridge.as_ref())
+                .await
+                .map(|output| (output, instance))
+        };
+        let outcome = timeout(timeout_duration, execution_future).await;
+        let profile = context
+            .profile
+            .then(|| Self::collect_profile(&store, execution_start.elapsed()));
+        // The exported function is the only call across the component
+        // boundary: components are linked without host imports
+        let trace = context.trace.then(|| {
+            vec![TraceSpan {
+                component: context.component_name.clone(),
+                function: context.method.clone(),
+                stage_id: None,
+                entered_at,
+                exited_at: Utc::now(),
+                duration_us: execution_start.elapsed().as_micros() as u64,
+                success: matches!(outcome, Ok(Ok(_))),
+            }]
+        });
+        let memory_usage_mb = Self::get_memory_usage(&store);
+
+        // Only instances that finished cleanly go back to the pool
+        if let (Some(reservation), Ok(Ok((_, instance)))) = (reservation, &outcome) {
+            reservation.give_back(PooledInstance {
+                store,
+                instance: *instance,
+            });
+        }
 
-        match timeout(timeout_duration, execution_future).await {
-            Ok(Ok((result, graphics))) => {
+        match outcome {
+            Ok(Ok(((result, graphics), _))) => {
                 update_progress(
                     ExecutionStage::Complete,
                     1.0,
@@ -360,14 +801,23 @@ impl WasmExecutionEngine {
                     result: Some(result),
                     error: None,
                     execution_time_ms: start_time.elapsed().as_millis() as u64,
-                    memory_usage_mb: Self::get_memory_usage(&store),
+                    memory_usage_mb,
                     output_data: graphics.as_ref().map(|g| g.data.clone()),
                     graphics_output: graphics,
                     completed_at: Utc::now(),
+                    profile,
+                    cached: false,
+                    trace,
                 }
             }
-            Ok(Err(e)) => {
-                let error_msg = format!("Execution failed: {e}");
+            Ok(Err(e)) if e.downcast_ref::<ExecutionCancelled>().is_some() => {
+                Self::cancelled_result(execution_id.clone(), start_time, update_progress)
+            }
+            Ok(Err(e)) if e.downcast_ref::<ExecutionTimedOut>().is_none() => {
+                let error_msg = match ComponentError::classify(&e, &limits) {
+                    Some(resource_error) => format!("Execution failed: {resource_error}"),
+                    None => format!("Execution failed: {e}"),
+                };
                 update_progress(
                     ExecutionStage::Error,
                     0.0,
@@ -381,13 +831,16 @@ impl WasmExecutionEngine {
                     result: None,
                     error: Some(error_msg),
                     execution_time_ms: start_time.elapsed().as_millis() as u64,
-                    memory_usage_mb: Self::get_memory_usage(&store),
+                    memory_usage_mb,
                     output_data: None,
                     graphics_output: None,
                     completed_at: Utc::now(),
+                    profile,
+                    cached: false,
+                    trace,
                 }
             }
-            Err(_) => {
+            _ => {
                 let error_msg = "Execution timed out".to_string();
                 update_progress(
                     ExecutionStage::Error,
@@ -402,59 +855,139 @@ impl WasmExecutionEngine {
                     result: None,
                     error: Some(error_msg),
                     execution_time_ms: start_time.elapsed().as_millis() as u64,
-                    memory_usage_mb: Self::get_memory_usage(&store),
+                    memory_usage_mb,
                     output_data: None,
                     graphics_output: None,
                     completed_at: Utc::now(),
+                    profile,
+                    cached: false,
+                    trace,
                 }
             }
         }
     }
 
-    /// Load a WASM component with caching
-    async fn load_component(
-        engine: &Engine,
-        component_cache: &Arc<Mutex<HashMap<String, Module>>>,
-        component_path: &Path,
-    ) -> Result<Module> {
-        let path_str = component_path.to_string_lossy().to_string();
+    /// Result of an execution stopped by [`Self::cancel_execution`]
+    fn cancelled_result(
+        execution_id: String,
+        start_time: Instant,
+        update_progress: impl Fn(ExecutionStage, f32, String, Option<String>),
+    ) -> ExecutionResult {
+        update_progress(
+            ExecutionStage::Cancelled,
+            0.0,
+            "Execution cancelled".to_string(),
+            Some("Cancelled".to_string()),
+        );
 
-        // Check cache first
-        {
-            let cache = component_cache.lock().unwrap();
-            if let Some(module) = cache.get(&path_str) {
-                return Ok(module.clone());
-            }
+        ExecutionResult {
+            execution_id,
+            success: false,
+            result: None,
+            error: Some("Cancelled".to_string()),
+            execution_time_ms: start_time.elapsed().as_millis() as u64,
+            memory_usage_mb: 0,
+            output_data: None,
+            graphics_output: None,
+            completed_at: Utc::now(),
+            profile: None,
+            cached: false,
+            trace: None,
         }
+    }
 
-        // Read and compile component
-        let wasm_bytes = tokio::fs::read(component_path)
-            .await
-            .with_context(|| format!("Failed to read WASM file: {component_path:?}"))?;
-
-        let module = Module::new(engine, &wasm_bytes)
-            .with_context(|| format!("Failed to compile WASM module: {component_path:?}"))?;
+    /// Compile a component into the module cache without instantiating it.
+    ///
+    /// Later executions of the component skip compilation. Returns how long
+    /// loading took, which is near zero when the component was cached.
+    pub async fn precompile(&self, component_path: &Path) -> Result<Duration> {
+        let start = Instant::now();
+        self.component_cache
+            .load(&self.engine, component_path)
+            .await?;
+        Ok(start.elapsed())
+    }
 
-        // Cache the module
-        {
-            let mut cache = component_cache.lock().unwrap();
-            cache.insert(path_str, module.clone());
+    /// Instantiate a component in a throwaway store without running it.
+    ///
+    /// Catches components that compile but fail to link, e.g. because of
+    /// unresolved imports. Returns how long instantiation took.
+    pub async fn check_instantiation(&self, component_path: &Path) -> Result<Duration> {
+        let module = self
+            .component_cache
+            .load(&self.engine, component_path)
+            .await?;
+
+        let mut store = Self::new_store(
+            &self.engine,
+            INSTANTIATION_CHECK_MEMORY_LIMIT,
+            self.limits.max_instances,
+        );
+        if self.limits.fuel_metering {
+            store.set_fuel(u64::MAX)?;
         }
 
-        Ok(module)
+        let start = Instant::now();
+        Instance::new(&mut store, &module, &[])
+            .map_err(|e| match ComponentError::classify(&e, &self.limits) {
+                Some(resource_error) => resource_error.into(),
+                None => e,
+            })
+            .context("Failed to instantiate WASM module")?;
+        Ok(start.elapsed())
+    }
+
+    /// Create a store with the given memory and instance limits.
+    ///
+    /// The store checks its cancel flag and deadline on every epoch tick: a
+    /// set flag traps the running call with [`ExecutionCancelled`], a passed
+    /// deadline with [`ExecutionTimedOut`]; otherwise the epoch deadline is
+    /// pushed to the next tick.
+    fn new_store(engine: &Engine, memory_limit: usize, instance_limit: usize) -> Store<StoreState> {
+        let table_limit = 1000; // Max table elements
+        let mut store = Store::new(
+            engine,
+            StoreState {
+                limiter: ResourceLimiter::new(memory_limit, table_limit, instance_limit),
+                instantiation_time: None,
+                cancel: Arc::new(AtomicBool::new(false)),
+                deadline: None,
+            },
+        );
+        store.limiter(|state| &mut state.limiter);
+        store.set_epoch_deadline(1);
+        store.epoch_deadline_callback(|store| {
+            if store.data().cancel.load(Ordering::SeqCst) {
+                Err(ExecutionCancelled.into())
+            } else if store
+                .data()
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+            {
+                Err(ExecutionTimedOut.into())
+            } else {
+                Ok(UpdateDeadline::Continue(1))
+            }
+        });
+        store
+    }
+
+    /// Instantiate a module, recording how long it took
+    fn instantiate(store: &mut Store<StoreState>, module: &Module) -> Result<Instance> {
+        let instantiation_start = Instant::now();
+        let instance =
+            Instance::new(&mut *store, module, &[]).context("Failed to instantiate WASM module")?;
+        store.data_mut().instantiation_time = Some(instantiation_start.elapsed());
+        Ok(instance)
     }
 
     /// Run the WASM component with the given arguments and optional sensor data
     async fn run_component(
-        store: &mut Store<()>,
-        module: &Module,
+        store: &mut Store<StoreState>,
+        instance: &Instance,
         context: &ExecutionContext,
         sensor_bridge: Option<&Arc<SensorDataBridge>>,
     ) -> Result<(serde_json::Value, Option<GraphicsOutput>)> {
-        // Create instance
-        let instance =
-            Instance::new(&mut *store, module, &[]).context("Failed to instantiate WASM module")?;
-
         // If sensor bridge is available, provide sensor interface to component
         let sensor_interface = if let Some(bridge) = sensor_bridge {
             Some(bridge.get_wasm_interface().await?)
@@ -512,24 +1045,90 @@ impl WasmExecutionEngine {
         Ok((serde_json::Value::Object(result_json), None))
     }
 
-    /// Get memory usage from the store
-    fn get_memory_usage(_store: &Store<()>) -> u32 {
-        // Actual memory usage calculation not implemented yet
-        0
+    /// Get peak memory usage from the store, rounded up to whole megabytes
+    fn get_memory_usage(store: &Store<StoreState>) -> u32 {
+        store.data().limiter.peak_memory.div_ceil(1024 * 1024) as u32
     }
 
-    /// Cancel an execution
-    pub fn cancel_execution(&self, execution_id: &str) -> bool {
-        let mut executions = self.executions.lock().unwrap();
-        if let Some(info) = executions.get_mut(execution_id) {
-            info.progress.stage = ExecutionStage::Error;
-            info.progress.error = Some("Execution cancelled".to_string());
-            true
-        } else {
-            false
+    /// Build the profile for a finished (or timed out) execution
+    fn collect_profile(store: &Store<StoreState>, elapsed: Duration) -> ExecutionProfile {
+        let state = store.data();
+        let instantiation_time = state.instantiation_time.unwrap_or_default();
+
+        ExecutionProfile {
+            instantiation_time_us: instantiation_time.as_micros() as u64,
+            execution_time_us: elapsed.saturating_sub(instantiation_time).as_micros() as u64,
+            fuel_consumed: u64::MAX - store.get_fuel().unwrap_or(u64::MAX),
+            peak_memory_bytes: state.limiter.peak_memory as u64,
         }
     }
 
+    /// Executions running and waiting for a slot, against the limit
+    pub fn concurrency(&self) -> ExecutionConcurrency {
+        self.limiter.concurrency()
+    }
+
+    /// Result cache counters, when the cache is enabled
+    pub fn result_cache_stats(&self) -> Option<ResultCacheStats> {
+        self.result_cache.as_ref().map(|cache| cache.stats())
+    }
+
+    /// Track an execution answered from the result cache as already complete
+    fn record_cached(&self, context: ExecutionContext, cached: ExecutionResult) {
+        let execution_id = context.execution_id.clone();
+        let now = Utc::now();
+        let result = ExecutionResult {
+            execution_id: execution_id.clone(),
+            execution_time_ms: 0,
+            completed_at: now,
+            cached: true,
+            ..cached
+        };
+        let info = ExecutionInfo {
+            context,
+            start_time: Instant::now(),
+            progress: ExecutionProgress {
+                execution_id: execution_id.clone(),
+                stage: ExecutionStage::Complete,
+                progress: 1.0,
+                message: "Served from the result cache".to_string(),
+                error: None,
+                timestamp: now,
+            },
+            result: Some(result),
+            sensor_bridge: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+        };
+        self.executions.lock().unwrap().insert(execution_id, info);
+    }
+
+    /// Aggregated profiling statistics for all profiled components
+    pub fn get_profile_stats(&self) -> HashMap<String, ComponentProfileStats> {
+        self.profile_stats.lock().unwrap().clone()
+    }
+
+    /// Cancel a running execution.
+    ///
+    /// Sets the execution's cancel flag and advances the engine epoch so the
+    /// running call reaches its deadline and traps; the execution then ends
+    /// with stage `Cancelled` and the error "Cancelled". Other running
+    /// executions see their flag unset and carry on. Finished executions are
+    /// left alone.
+    pub fn cancel_execution(&self, execution_id: &str) -> CancelOutcome {
+        let executions = self.executions.lock().unwrap();
+        let Some(info) = executions.get(execution_id) else {
+            return CancelOutcome::NotFound;
+        };
+        if info.result.is_some() {
+            return CancelOutcome::AlreadyFinished;
+        }
+        info.cancel.store(true, Ordering::SeqCst);
+        drop(executions);
+
+        self.engine.increment_epoch();
+        CancelOutcome::Cancelled
+    }
+
     /// Clean up completed executions older than the specified duration
     pub fn cleanup_executions(&self, max_age: Duration) {
         let mut executions = self.executions.lock().unwrap();
@@ -537,7 +1136,9 @@ impl WasmExecutionEngine {
 
         executions.retain(|_, info| {
             match info.progress.stage {
-                ExecutionStage::Complete | ExecutionStage::Error => info.start_time > cutoff,
+                ExecutionStage::Complete | ExecutionStage::Error | ExecutionStage::Cancelled => {
+                    info.start_time > cutoff
+                }
                 _ => true, // Keep running executions
             }
         });
@@ -622,13 +1223,18 @@ impl WasmExecutionEngine {
 struct ResourceLimiter {
     memory_limit: usize,
     table_limit: usize,
+    instance_limit: usize,
+    /// High-water mark of linear memory granted to the instance
+    peak_memory: usize,
 }
 
 impl ResourceLimiter {
-    fn new(memory_limit: usize, table_limit: usize) -> Self {
+    fn new(memory_limit: usize, table_limit: usize, instance_limit: usize) -> Self {
         Self {
             memory_limit,
             table_limit,
+            instance_limit,
+            peak_memory: 0,
         }
     }
 }
@@ -640,7 +1246,11 @@ impl wasmtime::ResourceLimiter for ResourceLimiter {
         desired: usize,
         _maximum: Option<usize>,
     ) -> anyhow::Result<bool> {
-        Ok(desired <= self.memory_limit)
+        let allowed = desired <= self.memory_limit;
+        if allowed {
+            self.peak_memory = self.peak_memory.max(desired);
+        }
+        Ok(allowed)
     }
 
     fn table_growing(
@@ -651,11 +1261,16 @@ impl wasmtime::ResourceLimiter for ResourceLimiter {
     ) -> anyhow::Result<bool> {
         Ok(desired <= self.table_limit)
     }
+
+    fn instances(&self) -> usize {
+        self.instance_limit
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::wasm::module_cache::DEFAULT_MAX_WASM_STACK;
     // use tempfile::tempdir; // Commented out - dependency issue
 
     #[tokio::test]
@@ -664,11 +1279,147 @@ mod tests {
         // assert!(engine.is_ok());
     }
 
+    #[test]
+    fn test_profile_stats_aggregation() {
+        let mut stats = ComponentProfileStats::default();
+        stats.record(&ExecutionProfile {
+            instantiation_time_us: 100,
+            execution_time_us: 1_000,
+            fuel_consumed: 500,
+            peak_memory_bytes: 65_536,
+        });
+        stats.record(&ExecutionProfile {
+            instantiation_time_us: 300,
+            execution_time_us: 3_000,
+            fuel_consumed: 1_500,
+            peak_memory_bytes: 131_072,
+        });
+
+        assert_eq!(stats.executions, 2);
+        assert_eq!(stats.avg_instantiation_time_us, 200);
+        assert_eq!(stats.max_instantiation_time_us, 300);
+        assert_eq!(stats.avg_execution_time_us, 2_000);
+        assert_eq!(stats.avg_fuel_consumed, 1_000);
+        assert_eq!(stats.max_fuel_consumed, 1_500);
+        assert_eq!(stats.peak_memory_bytes, 131_072);
+    }
+
     #[tokio::test]
     async fn test_execution_limits() {
-        let _engine = WasmExecutionEngine::new(1).unwrap();
+        let engine = WasmExecutionEngine::new(1).unwrap();
+
+        // Unbounded recursion traps instead of overflowing the host stack
+        let recursive = Module::new(
+            &engine.engine,
+            r#"(module (func $run (export "run") (result i32) (call $run)))"#,
+        )
+        .unwrap();
+        let mut store = WasmExecutionEngine::new_store(&engine.engine, 1 << 20, 10);
+        let instance = Instance::new(&mut store, &recursive, &[]).unwrap();
+        let run = instance
+            .get_typed_func::<(), i32>(&mut store, "run")
+            .unwrap();
+        let error = run.call(&mut store, ()).unwrap_err();
+        assert_eq!(
+            ComponentError::classify(&error, &engine.limits),
+            Some(ComponentError::StackOverflow {
+                max_wasm_stack: DEFAULT_MAX_WASM_STACK
+            })
+        );
+
+        let empty = Module::new(&engine.engine, "(module)").unwrap();
+        let mut store = WasmExecutionEngine::new_store(&engine.engine, 1 << 20, 0);
+        let error = Instance::new(&mut store, &empty, &[]).unwrap_err();
+        assert!(matches!(
+            ComponentError::classify(&error, &engine.limits),
+            Some(ComponentError::InstanceLimitExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_fuel_is_metered_only_when_enabled() {
+        let add =
+            r#"(module (func (export "run") (result i32) (i32.add (i32.const 1) (i32.const 2))))"#;
+        let fuel_used = |fuel_metering| {
+            let engine = WasmExecutionEngine::with_options(
+                1,
+                EngineOptions {
+                    fuel_metering,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            let module = Module::new(&engine.engine, add).unwrap();
+            let mut store = WasmExecutionEngine::new_store(&engine.engine, 1 << 20, 10);
+            if fuel_metering {
+                store.set_fuel(u64::MAX).unwrap();
+            }
+            let instance = Instance::new(&mut store, &module, &[]).unwrap();
+            let run = instance
+                .get_typed_func::<(), i32>(&mut store, "run")
+                .unwrap();
+            assert_eq!(run.call(&mut store, ()).unwrap(), 3);
+            WasmExecutionEngine::collect_profile(&store, Duration::ZERO).fuel_consumed
+        };
+        assert!(fuel_used(true) > 0);
+        assert_eq!(fuel_used(false), 0);
+    }
+
+    #[test]
+    fn test_cancel_flag_stops_running_call() {
+        let engine = WasmExecutionEngine::new(1).unwrap();
+        let spin = Module::new(
+            &engine.engine,
+            r#"(module (func (export "run") (result i32) (loop $l (br $l)) (i32.const 0)))"#,
+        )
+        .unwrap();
+        let mut store = WasmExecutionEngine::new_store(&engine.engine, 1 << 20, 10);
+        let cancel = store.data().cancel.clone();
+        let instance = Instance::new(&mut store, &spin, &[]).unwrap();
+        let run = instance
+            .get_typed_func::<(), i32>(&mut store, "run")
+            .unwrap();
+
+        let wasm_engine = engine.engine.clone();
+        let canceller = std::thread::spawn(move || {
+            // An epoch tick without the flag only extends the deadline
+            wasm_engine.increment_epoch();
+            std::thread::sleep(Duration::from_millis(50));
+            cancel.store(true, Ordering::SeqCst);
+            wasm_engine.increment_epoch();
+        });
+
+        let error = run.call(&mut store, ()).unwrap_err();
+        canceller.join().unwrap();
+        assert!(error.downcast_ref::<ExecutionCancelled>().is_some());
+    }
 
-        // Tests with actual WASM components not implemented yet
-        // This would require test WASM files
+    #[test]
+    fn test_ticker_enforces_deadline() {
+        let engine = WasmExecutionEngine::with_options(
+            1,
+            EngineOptions {
+                epoch_tick: Duration::from_millis(5),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let spin = Module::new(
+            &engine.engine,
+            r#"(module (func (export "run") (result i32) (loop $l (br $l)) (i32.const 0)))"#,
+        )
+        .unwrap();
+        let mut store = WasmExecutionEngine::new_store(&engine.engine, 1 << 20, 10);
+        let instance = Instance::new(&mut store, &spin, &[]).unwrap();
+        let run = instance
+            .get_typed_func::<(), i32>(&mut store, "run")
+            .unwrap();
+
+        // Nothing but the ticker advances the epoch here
+        let start = Instant::now();
+        store.data_mut().deadline = Some(start + Duration::from_millis(50));
+        let error = run.call(&mut store, ()).unwrap_err();
+        assert!(error.downcast_ref::<ExecutionTimedOut>().is_some());
+        assert!(start.elapsed() < Duration::from_secs(5));
     }
 }
@@ -0,0 +1,159 @@
+/*!
+ * Concurrent execution limit
+ *
+ * Caps how many component executions run at once, across all components, so
+ * that a burst of calls cannot occupy every core. Executions beyond the
+ * limit either wait for a slot, for at most the configured time, or fail
+ * straight away with [`ComponentBusy`], depending on the overflow policy.
+ */
+
+use crate::wasm::instance_pool::{BusyLimit, ComponentBusy, PoolOverflow};
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// How executions beyond the limit are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutionQueueConfig {
+    pub overflow: PoolOverflow,
+    /// Longest a queued execution waits for a slot before failing
+    pub max_wait: Duration,
+}
+
+impl Default for ExecutionQueueConfig {
+    fn default() -> Self {
+        Self {
+            overflow: PoolOverflow::Queue,
+            max_wait: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Current use of the execution slots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionConcurrency {
+    pub limit: usize,
+    pub in_flight: usize,
+    pub queued: usize,
+    pub overflow: &'static str,
+}
+
+/// A held execution slot, freed on drop
+pub(crate) struct ExecutionPermit {
+    _permit: OwnedSemaphorePermit,
+}
+
+pub(crate) struct ExecutionLimiter {
+    limit: usize,
+    config: ExecutionQueueConfig,
+    permits: Arc<Semaphore>,
+    queued: AtomicUsize,
+}
+
+/// Counts a waiting execution for as long as it waits
+struct Queued<'a>(&'a AtomicUsize);
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl ExecutionLimiter {
+    pub fn new(limit: usize, config: ExecutionQueueConfig) -> Self {
+        Self {
+            limit,
+            config,
+            permits: Arc::new(Semaphore::new(limit)),
+            queued: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn overflow(&self) -> PoolOverflow {
+        self.config.overflow
+    }
+
+    fn busy(&self, component: &str) -> ComponentBusy {
+        ComponentBusy {
+            component: component.to_string(),
+            capacity: self.limit,
+            limit: BusyLimit::ConcurrentExecutions,
+        }
+    }
+
+    /// Take a slot without waiting
+    pub fn try_acquire(&self, component: &str) -> Result<ExecutionPermit, ComponentBusy> {
+        let permit = self
+            .permits
+            .clone()
+            .try_acquire_owned()
+            .map_err(|_| self.busy(component))?;
+        Ok(ExecutionPermit { _permit: permit })
+    }
+
+    /// Take a slot, waiting up to the configured maximum for one to free up
+    pub async fn acquire(&self, component: &str) -> Result<ExecutionPermit, ComponentBusy> {
+        if let Ok(permit) = self.try_acquire(component) {
+            return Ok(permit);
+        }
+        self.queued.fetch_add(1, Ordering::SeqCst);
+        let _queued = Queued(&self.queued);
+        match tokio::time::timeout(self.config.max_wait, self.permits.clone().acquire_owned()).await
+        {
+            Ok(Ok(permit)) => Ok(ExecutionPermit { _permit: permit }),
+            _ => Err(self.busy(component)),
+        }
+    }
+
+    pub fn concurrency(&self) -> ExecutionConcurrency {
+        ExecutionConcurrency {
+            limit: self.limit,
+            in_flight: self.limit - self.permits.available_permits(),
+            queued: self.queued.load(Ordering::SeqCst),
+            overflow: self.config.overflow.as_str(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_limit_queues_then_gives_up() {
+        let limiter = Arc::new(ExecutionLimiter::new(
+            1,
+            ExecutionQueueConfig {
+                overflow: PoolOverflow::Queue,
+                max_wait: Duration::from_millis(50),
+            },
+        ));
+        let held = limiter.try_acquire("fusion").unwrap();
+        assert_eq!(limiter.concurrency().in_flight, 1);
+        assert!(limiter.try_acquire("camera").is_err());
+
+        // A waiter is counted while it waits and gives up after max_wait
+        let waiter = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire("camera").await.map(|_| ()) }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(limiter.concurrency().queued, 1);
+        let busy = waiter.await.unwrap().unwrap_err();
+        assert_eq!(busy.limit, BusyLimit::ConcurrentExecutions);
+        assert_eq!(limiter.concurrency().queued, 0);
+
+        // A freed slot goes to the next waiter
+        let waiter = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire("camera").await.is_ok() }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(held);
+        assert!(waiter.await.unwrap());
+        assert_eq!(limiter.concurrency().in_flight, 0);
+    }
+}
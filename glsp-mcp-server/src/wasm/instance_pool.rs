@@ -0,0 +1,226 @@
+//! Pool of reusable component instances
+//!
+//! Each component gets up to `size` instances. A call reserves a slot, reuses
+//! an idle instance if there is one (instantiating a new one otherwise) and
+//! hands it back afterwards. Core WASM instances cannot be rewound, so an
+//! instance is only returned to the pool after a successful call; one that
+//! trapped, failed or timed out is dropped and replaced on a later call.
+//! Fuel and memory limits are reset on every checkout, but state kept in
+//! linear memory survives between calls.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use wasmtime::{Instance, Store};
+
+/// What a call does when every instance of its component is in use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PoolOverflow {
+    /// Wait until an instance is returned
+    #[default]
+    Queue,
+    /// Fail immediately with [`ComponentBusy`]
+    Reject,
+}
+
+impl PoolOverflow {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PoolOverflow::Queue => "queue",
+            PoolOverflow::Reject => "reject",
+        }
+    }
+}
+
+impl FromStr for PoolOverflow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "queue" => Ok(PoolOverflow::Queue),
+            "reject" => Ok(PoolOverflow::Reject),
+            other => Err(format!(
+                "Unknown pool overflow policy '{other}' (expected queue or reject)"
+            )),
+        }
+    }
+}
+
+/// Instance pool settings; a size of 0 disables pooling
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InstancePoolConfig {
+    /// Instances kept per component
+    pub size: usize,
+    pub overflow: PoolOverflow,
+}
+
+/// Which limit a [`ComponentBusy`] call ran into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusyLimit {
+    /// The component's pooled instances
+    InstancePool,
+    /// The server-wide cap on concurrent executions
+    ConcurrentExecutions,
+}
+
+/// Every pooled instance of a component, or every execution slot, is in use
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentBusy {
+    pub component: String,
+    pub capacity: usize,
+    pub limit: BusyLimit,
+}
+
+impl fmt::Display for ComponentBusy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.limit {
+            BusyLimit::InstancePool => write!(
+                f,
+                "Component '{}' is busy: all {} pooled instances are in use",
+                self.component, self.capacity
+            ),
+            BusyLimit::ConcurrentExecutions => write!(
+                f,
+                "Component '{}' is busy: all {} execution slots are in use",
+                self.component, self.capacity
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ComponentBusy {}
+
+/// An instance together with the store that owns it
+pub(crate) struct PooledInstance<T> {
+    pub store: Store<T>,
+    pub instance: Instance,
+}
+
+struct ComponentSlots<T> {
+    permits: Arc<Semaphore>,
+    idle: Mutex<Vec<PooledInstance<T>>>,
+}
+
+pub(crate) struct InstancePool<T> {
+    config: InstancePoolConfig,
+    components: Mutex<HashMap<String, Arc<ComponentSlots<T>>>>,
+}
+
+impl<T> InstancePool<T> {
+    pub fn new(config: InstancePoolConfig) -> Self {
+        Self {
+            config,
+            components: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn overflow(&self) -> PoolOverflow {
+        self.config.overflow
+    }
+
+    fn slots(&self, component: &str) -> Arc<ComponentSlots<T>> {
+        self.components
+            .lock()
+            .unwrap()
+            .entry(component.to_string())
+            .or_insert_with(|| {
+                Arc::new(ComponentSlots {
+                    permits: Arc::new(Semaphore::new(self.config.size)),
+                    idle: Mutex::new(Vec::new()),
+                })
+            })
+            .clone()
+    }
+
+    /// Reserve a slot without waiting
+    pub fn try_reserve(&self, component: &str) -> Result<Reservation<T>, ComponentBusy> {
+        let slots = self.slots(component);
+        let permit = slots
+            .permits
+            .clone()
+            .try_acquire_owned()
+            .map_err(|_| ComponentBusy {
+                component: component.to_string(),
+                capacity: self.config.size,
+                limit: BusyLimit::InstancePool,
+            })?;
+        Ok(Reservation {
+            slots,
+            _permit: permit,
+        })
+    }
+
+    /// Reserve a slot, waiting for one to be returned if necessary
+    pub async fn reserve(&self, component: &str) -> Reservation<T> {
+        let slots = self.slots(component);
+        let permit = slots
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("instance pool semaphore is never closed");
+        Reservation {
+            slots,
+            _permit: permit,
+        }
+    }
+
+    /// Idle instances currently held for a component
+    #[cfg(test)]
+    pub fn idle_count(&self, component: &str) -> usize {
+        self.slots(component).idle.lock().unwrap().len()
+    }
+}
+
+/// A reserved slot; dropping it without [`Reservation::give_back`] frees the
+/// slot and discards the instance
+pub(crate) struct Reservation<T> {
+    slots: Arc<ComponentSlots<T>>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl<T> Reservation<T> {
+    /// An idle instance to reuse, if the pool has one
+    pub fn take_idle(&mut self) -> Option<PooledInstance<T>> {
+        self.slots.idle.lock().unwrap().pop()
+    }
+
+    /// Return a healthy instance to the pool and free the slot
+    pub fn give_back(self, instance: PooledInstance<T>) {
+        self.slots.idle.lock().unwrap().push(instance);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasmtime::{Engine, Module};
+
+    #[tokio::test]
+    async fn test_pool_reuses_instances_and_rejects_overflow() {
+        let engine = Engine::default();
+        let module = Module::new(&engine, "(module)").unwrap();
+        let pool = InstancePool::new(InstancePoolConfig {
+            size: 1,
+            overflow: PoolOverflow::Reject,
+        });
+
+        let mut reservation = pool.try_reserve("fusion").unwrap();
+        assert!(reservation.take_idle().is_none());
+        let busy = pool.try_reserve("fusion").err().unwrap();
+        assert_eq!(busy.capacity, 1);
+        assert!(pool.try_reserve("camera").is_ok());
+
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[]).unwrap();
+        reservation.give_back(PooledInstance { store, instance });
+        assert_eq!(pool.idle_count("fusion"), 1);
+
+        let mut reservation = pool.reserve("fusion").await;
+        assert!(reservation.take_idle().is_some());
+        drop(reservation);
+        assert_eq!(pool.idle_count("fusion"), 0);
+    }
+}
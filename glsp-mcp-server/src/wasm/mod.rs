@@ -1,24 +1,45 @@
+mod custom_sections;
+mod dependency_graph;
 mod execution_engine;
+mod execution_limiter;
 mod filesystem_watcher;
 mod graphics_renderer;
+mod instance_pool;
+mod module_cache;
 mod pipeline;
+mod result_cache;
 mod security_scanner;
 mod sensor_bridge;
 mod simulation;
 mod wit_analyzer;
 
+pub use custom_sections::{
+    read_custom_sections, section_metadata, CustomSection, SectionEncoding, CUSTOM_SECTIONS_KEY,
+};
+pub use dependency_graph::{
+    build_dependency_graph, find_components_by_interface, ComponentDependencies, DependencyGraph,
+    ImportResolution, InterfaceMatch, UnsatisfiedImport,
+};
 pub use execution_engine::{
-    ExecutionContext, ExecutionProgress, ExecutionResult, ExecutionStage, GraphicsFormat,
-    GraphicsOutput, VideoFormat, WasmExecutionEngine,
+    CancelOutcome, ComponentError, ComponentProfileStats, ExecutionCancelled, ExecutionContext,
+    ExecutionProfile, ExecutionProgress, ExecutionResult, ExecutionStage, ExecutionTimedOut,
+    GraphicsFormat, GraphicsOutput, TraceSpan, VideoFormat, WasmExecutionEngine,
 };
+pub use execution_limiter::{ExecutionConcurrency, ExecutionQueueConfig};
 pub use filesystem_watcher::{FileSystemWatcher, WasmChangeType, WasmComponentChange};
 pub use graphics_renderer::{CanvasCommand, GraphicsConfig, ImageFormat, WasmGraphicsRenderer};
+pub use instance_pool::{BusyLimit, ComponentBusy, InstancePoolConfig, PoolOverflow};
+pub use module_cache::{
+    EngineOptions, WasmOptLevel, DEFAULT_EPOCH_TICK_MS, DEFAULT_MAX_INSTANCES,
+    DEFAULT_MAX_WASM_STACK,
+};
 pub use pipeline::{
     BackoffStrategy, ConnectionType as PipelineConnectionType, DataConnection, DataMapping,
     DataTransform, ExecutionMode, ExecutionStats, PersistenceSettings, PipelineConfig,
     PipelineExecution, PipelineSettings, PipelineStage, PipelineState, RetryConfig,
     StageExecutionSettings, StageResult, StageStats, WasmPipelineEngine,
 };
+pub use result_cache::{ResultCacheConfig, ResultCacheStats, DEFAULT_RESULT_CACHE_TTL_SECS};
 pub use security_scanner::{
     SecurityAnalysis, SecurityIssue, SecurityIssueType, SecurityRiskLevel, WasmSecurityScanner,
 };
@@ -43,9 +64,9 @@ pub use wit_analyzer::{
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tracing::{debug, info, warn};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 // Re-export component grouping types (defined in this module)
@@ -186,6 +207,84 @@ pub struct ValidationStatus {
     pub last_validated: DateTime<Utc>,
 }
 
+/// Errors raised while loading an individual WASM component
+#[derive(Debug, thiserror::Error)]
+pub enum WasmError {
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("invalid WebAssembly binary {path}: {reason}")]
+    InvalidBinary { path: String, reason: String },
+
+    #[error("failed to extract component info from {path}: {reason}")]
+    Extraction { path: String, reason: String },
+}
+
+/// Outcome of loading a single component during a scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ComponentLoadStatus {
+    Loaded,
+    Failed,
+}
+
+/// Per-component load result recorded by `scan_components`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentLoadResult {
+    pub name: String,
+    pub path: String,
+    pub status: ComponentLoadStatus,
+    pub error: Option<String>,
+    pub attempted_at: DateTime<Utc>,
+}
+
+/// Outcome of instantiating a component in a throwaway store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstantiationCheck {
+    pub name: String,
+    pub path: String,
+    pub success: bool,
+    pub error: Option<String>,
+    pub duration_us: Option<u64>,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Outcome of compiling, and optionally instantiating, a component at startup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentPreload {
+    pub name: String,
+    pub path: Option<String>,
+    pub success: bool,
+    pub compiled: bool,
+    pub instantiated: bool,
+    pub error: Option<String>,
+    pub duration_us: Option<u64>,
+    pub preloaded_at: DateTime<Utc>,
+}
+
+/// Summary of the most recent component scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentLoadSummary {
+    pub loaded: usize,
+    pub failed: usize,
+    pub last_scan: DateTime<Utc>,
+    pub results: Vec<ComponentLoadResult>,
+    /// Results of the opt-in instantiation self-test, empty if it never ran
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub instantiation_checks: Vec<InstantiationCheck>,
+    /// Components preloaded at startup, empty if none were configured
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub preloads: Vec<ComponentPreload>,
+}
+
 #[derive(Clone)]
 pub struct WasmFileWatcher {
     watch_path: PathBuf,
@@ -195,6 +294,9 @@ pub struct WasmFileWatcher {
     execution_engine: Option<Arc<WasmExecutionEngine>>,
     recent_changes: Arc<tokio::sync::Mutex<Vec<WasmComponentChange>>>,
     filesystem_watcher: Option<Arc<tokio::sync::RwLock<FileSystemWatcher>>>,
+    load_results: HashMap<String, ComponentLoadResult>,
+    instantiation_checks: HashMap<String, InstantiationCheck>,
+    preloads: HashMap<String, ComponentPreload>,
 }
 
 impl WasmFileWatcher {
@@ -207,6 +309,9 @@ impl WasmFileWatcher {
             execution_engine: None,
             recent_changes: Arc::new(tokio::sync::Mutex::new(Vec::new())),
             filesystem_watcher: None,
+            load_results: HashMap::new(),
+            instantiation_checks: HashMap::new(),
+            preloads: HashMap::new(),
         }
     }
 
@@ -226,6 +331,21 @@ impl WasmFileWatcher {
             .get_execution_progress(execution_id)
     }
 
+    /// Aggregated profiling statistics keyed by component name
+    pub fn get_profile_stats(&self) -> HashMap<String, ComponentProfileStats> {
+        self.execution_engine
+            .as_ref()
+            .map(|engine| engine.get_profile_stats())
+            .unwrap_or_default()
+    }
+
+    /// Result cache counters, when the execution engine caches results
+    pub fn result_cache_stats(&self) -> Option<ResultCacheStats> {
+        self.execution_engine
+            .as_ref()
+            .and_then(|engine| engine.result_cache_stats())
+    }
+
     /// Get execution result by ID
     pub fn get_execution_result(&self, execution_id: &str) -> Option<ExecutionResult> {
         self.execution_engine
@@ -274,8 +394,15 @@ impl WasmFileWatcher {
     }
 
     /// Initialize execution engine with given configuration
-    pub fn with_execution_engine(mut self, max_concurrent: usize) -> Result<Self, anyhow::Error> {
-        self.execution_engine = Some(Arc::new(WasmExecutionEngine::new(max_concurrent)?));
+    pub fn with_execution_engine(
+        mut self,
+        max_concurrent: usize,
+        options: EngineOptions,
+    ) -> Result<Self, anyhow::Error> {
+        self.execution_engine = Some(Arc::new(WasmExecutionEngine::with_options(
+            max_concurrent,
+            options,
+        )?));
         Ok(self)
     }
 
@@ -359,10 +486,18 @@ impl WasmFileWatcher {
                     existing.last_seen = Some(Utc::now());
                     existing.removed_at = None;
                 } else {
-                    // New component discovered
-                    let component = self.extract_component_info(&wasm_path).await?;
-                    self.components
-                        .insert(component_name.to_string(), component);
+                    // New component discovered; a broken file must not abort the scan
+                    match self.load_component(&wasm_path).await {
+                        Ok(component) => {
+                            self.record_load_result(component_name, &wasm_path, None);
+                            self.components
+                                .insert(component_name.to_string(), component);
+                        }
+                        Err(e) => {
+                            error!("Failed to load component {component_name}: {e}");
+                            self.record_load_result(component_name, &wasm_path, Some(e));
+                        }
+                    }
                 }
             }
         }
@@ -384,6 +519,180 @@ impl WasmFileWatcher {
         Ok(())
     }
 
+    /// Validate the binary and extract component info for a newly discovered file
+    async fn load_component(&self, wasm_path: &PathBuf) -> Result<WasmComponent, WasmError> {
+        let path = wasm_path.to_string_lossy().to_string();
+        let wasm_bytes = tokio::fs::read(wasm_path)
+            .await
+            .map_err(|source| WasmError::Io {
+                path: path.clone(),
+                source,
+            })?;
+
+        wasmparser::Validator::new()
+            .validate_all(&wasm_bytes)
+            .map_err(|e| WasmError::InvalidBinary {
+                path: path.clone(),
+                reason: e.to_string(),
+            })?;
+
+        self.extract_component_info(wasm_path)
+            .await
+            .map_err(|e| WasmError::Extraction {
+                path,
+                reason: e.to_string(),
+            })
+    }
+
+    fn record_load_result(&mut self, name: &str, wasm_path: &Path, error: Option<WasmError>) {
+        let status = if error.is_some() {
+            ComponentLoadStatus::Failed
+        } else {
+            ComponentLoadStatus::Loaded
+        };
+
+        self.load_results.insert(
+            name.to_string(),
+            ComponentLoadResult {
+                name: name.to_string(),
+                path: wasm_path.to_string_lossy().to_string(),
+                status,
+                error: error.map(|e| e.to_string()),
+                attempted_at: Utc::now(),
+            },
+        );
+    }
+
+    /// Per-component load results from all scans so far
+    pub fn get_load_summary(&self) -> ComponentLoadSummary {
+        let mut results: Vec<ComponentLoadResult> = self.load_results.values().cloned().collect();
+        results.sort_by(|a, b| a.name.cmp(&b.name));
+        let failed = results
+            .iter()
+            .filter(|r| matches!(r.status, ComponentLoadStatus::Failed))
+            .count();
+
+        let mut instantiation_checks: Vec<InstantiationCheck> =
+            self.instantiation_checks.values().cloned().collect();
+        instantiation_checks.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut preloads: Vec<ComponentPreload> = self.preloads.values().cloned().collect();
+        preloads.sort_by(|a, b| a.name.cmp(&b.name));
+
+        ComponentLoadSummary {
+            loaded: results.len() - failed,
+            failed,
+            last_scan: self.last_scan,
+            results,
+            instantiation_checks,
+            preloads,
+        }
+    }
+
+    /// Instantiate every available component in a throwaway store.
+    ///
+    /// This is an opt-in self-test: compiling and instantiating every
+    /// component can be expensive. Results replace those of the previous run.
+    pub async fn run_instantiation_checks(&mut self) -> Vec<InstantiationCheck> {
+        let Some(engine) = self.execution_engine.clone() else {
+            warn!("Skipping instantiation checks: no execution engine");
+            return Vec::new();
+        };
+
+        let mut targets: Vec<(String, String)> = self
+            .components
+            .values()
+            .filter(|c| c.file_exists)
+            .map(|c| (c.name.clone(), c.path.clone()))
+            .collect();
+        targets.sort();
+
+        let mut checks = Vec::with_capacity(targets.len());
+        for (name, path) in targets {
+            let outcome = engine.check_instantiation(Path::new(&path)).await;
+            if let Err(e) = &outcome {
+                warn!("Component {} failed to instantiate: {:#}", name, e);
+            }
+            checks.push(InstantiationCheck {
+                name,
+                path,
+                success: outcome.is_ok(),
+                duration_us: outcome.as_ref().ok().map(|d| d.as_micros() as u64),
+                error: outcome.err().map(|e| format!("{e:#}")),
+                checked_at: Utc::now(),
+            });
+        }
+
+        self.instantiation_checks = checks.iter().map(|c| (c.name.clone(), c.clone())).collect();
+        checks
+    }
+
+    /// Compile the named components into the module cache, and instantiate
+    /// them in a throwaway store when `instantiate` is set, so that their
+    /// first execution does not pay for it.
+    ///
+    /// Names that match no scanned component fail. Results replace those of
+    /// the previous run.
+    pub async fn preload_components(
+        &mut self,
+        names: &[String],
+        instantiate: bool,
+    ) -> Vec<ComponentPreload> {
+        let engine = self.execution_engine.clone();
+        let mut preloads = Vec::with_capacity(names.len());
+        for name in names {
+            let path = self
+                .components
+                .get(name)
+                .filter(|c| c.file_exists)
+                .map(|c| c.path.clone());
+            let mut preload = ComponentPreload {
+                name: name.clone(),
+                path: path.clone(),
+                success: false,
+                compiled: false,
+                instantiated: false,
+                error: None,
+                duration_us: None,
+                preloaded_at: Utc::now(),
+            };
+            let (Some(engine), Some(path)) = (&engine, path) else {
+                preload.error = Some(if engine.is_none() {
+                    "No execution engine".to_string()
+                } else {
+                    format!("Component '{name}' not found")
+                });
+                preloads.push(preload);
+                continue;
+            };
+
+            let start = std::time::Instant::now();
+            let outcome = match engine.precompile(Path::new(&path)).await {
+                Ok(_) => {
+                    preload.compiled = true;
+                    if instantiate {
+                        engine.check_instantiation(Path::new(&path)).await.map(|_| {
+                            preload.instantiated = true;
+                        })
+                    } else {
+                        Ok(())
+                    }
+                }
+                Err(e) => Err(e),
+            };
+            preload.success = outcome.is_ok();
+            preload.error = outcome.err().map(|e| format!("{e:#}"));
+            preload.duration_us = Some(start.elapsed().as_micros() as u64);
+            preloads.push(preload);
+        }
+
+        self.preloads = preloads
+            .iter()
+            .map(|p| (p.name.clone(), p.clone()))
+            .collect();
+        preloads
+    }
+
     async fn scan_directory_recursive(
         &self,
         dir: &PathBuf,
@@ -414,9 +723,24 @@ impl WasmFileWatcher {
 
         debug!("Extracting component info from: {wasm_path:?}");
 
+        // Custom sections are read even when interface extraction fails
+        let custom_sections = match tokio::fs::read(wasm_path).await {
+            Ok(bytes) => read_custom_sections(&bytes).unwrap_or_else(|e| {
+                warn!("Failed to read custom sections of {component_name}: {e}");
+                Vec::new()
+            }),
+            Err(e) => {
+                warn!("Failed to read {wasm_path:?}: {e}");
+                Vec::new()
+            }
+        };
+        let custom_sections = serde_json::to_value(&custom_sections).unwrap_or_default();
+
         // Try to extract actual metadata using wasm-tools
         match self.extract_wasm_metadata(wasm_path).await {
-            Ok((interfaces, metadata, wit_content, dependencies)) => {
+            Ok((interfaces, mut metadata, wit_content, dependencies)) => {
+                metadata.insert(CUSTOM_SECTIONS_KEY.to_string(), custom_sections);
+
                 let description = metadata
                     .get("description")
                     .and_then(|v| v.as_str())
@@ -517,7 +841,7 @@ impl WasmFileWatcher {
                             }],
                         },
                     ],
-                    metadata: HashMap::new(),
+                    metadata: HashMap::from([(CUSTOM_SECTIONS_KEY.to_string(), custom_sections)]),
                     wit_interfaces: None,
                     dependencies: Vec::new(),
                     security_analysis: None,
@@ -847,6 +1171,7 @@ impl WasmFileWatcher {
     }
 
     /// Execute a WASM component method
+    #[allow(clippy::too_many_arguments)]
     pub async fn execute_component(
         &self,
         component_name: &str,
@@ -854,6 +1179,9 @@ impl WasmFileWatcher {
         args: serde_json::Value,
         timeout_ms: u64,
         max_memory_mb: u32,
+        profile: bool,
+        no_cache: bool,
+        trace: bool,
     ) -> Result<String, anyhow::Error> {
         let execution_engine = self
             .execution_engine
@@ -883,6 +1211,9 @@ impl WasmFileWatcher {
             max_memory_mb,
             created_at: Utc::now(),
             sensor_config: None,
+            profile,
+            no_cache,
+            trace,
         };
 
         let component_path = std::path::Path::new(&component.path);
@@ -892,12 +1223,12 @@ impl WasmFileWatcher {
             .await
     }
 
-    /// Cancel an execution
-    pub fn cancel_execution(&self, execution_id: &str) -> bool {
+    /// Cancel a running execution
+    pub fn cancel_execution(&self, execution_id: &str) -> CancelOutcome {
         self.execution_engine
             .as_ref()
             .map(|engine| engine.cancel_execution(execution_id))
-            .unwrap_or(false)
+            .unwrap_or(CancelOutcome::NotFound)
     }
 
     /// Display comprehensive statistics after component scan
@@ -1075,7 +1406,7 @@ impl WasmFileWatcher {
             info!("Top Components by Interface Count:");
             let mut components_by_interfaces: Vec<_> =
                 self.components.values().filter(|c| c.file_exists).collect();
-            components_by_interfaces.sort_by(|a, b| b.interfaces.len().cmp(&a.interfaces.len()));
+            components_by_interfaces.sort_by_key(|c| std::cmp::Reverse(c.interfaces.len()));
 
             for (i, component) in components_by_interfaces.iter().take(5).enumerate() {
                 let wit_status = if component.wit_interfaces.is_some() {
@@ -1668,3 +1999,55 @@ pub struct DeploymentResult {
     pub errors: Vec<String>,
     pub success: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_scan_continues_past_corrupt_component() {
+        let dir = tempfile::tempdir().unwrap();
+        // Smallest valid core module: magic number and version
+        std::fs::write(dir.path().join("good.wasm"), b"\0asm\x01\0\0\0").unwrap();
+        std::fs::write(dir.path().join("broken.wasm"), b"not wasm at all").unwrap();
+
+        let mut watcher = WasmFileWatcher::new(dir.path().to_path_buf());
+        watcher.scan_components().await.unwrap();
+
+        assert!(watcher.get_component("good").is_some());
+        assert!(watcher.get_component("broken").is_none());
+
+        let summary = watcher.get_load_summary();
+        assert_eq!(summary.loaded, 1);
+        assert_eq!(summary.failed, 1);
+        let broken = summary.results.iter().find(|r| r.name == "broken").unwrap();
+        assert!(matches!(broken.status, ComponentLoadStatus::Failed));
+        assert!(broken
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("invalid WebAssembly binary"));
+    }
+
+    #[tokio::test]
+    async fn test_preload_compiles_listed_components() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("fusion.wasm"), b"\0asm\x01\0\0\0").unwrap();
+
+        let mut watcher = WasmFileWatcher::new(dir.path().to_path_buf())
+            .with_execution_engine(1, EngineOptions::default())
+            .unwrap();
+        watcher.scan_components().await.unwrap();
+
+        let preloads = watcher
+            .preload_components(&["fusion".to_string(), "camera".to_string()], true)
+            .await;
+        assert!(preloads[0].success && preloads[0].compiled && preloads[0].instantiated);
+        assert!(!preloads[1].success);
+        assert_eq!(
+            preloads[1].error.as_deref(),
+            Some("Component 'camera' not found")
+        );
+        assert_eq!(watcher.get_load_summary().preloads.len(), 2);
+    }
+}
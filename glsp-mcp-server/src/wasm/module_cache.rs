@@ -0,0 +1,301 @@
+//! Compilation settings and module caching for the execution engine
+//!
+//! Compiled modules are kept in memory per path. Optionally they are also
+//! precompiled to disk so that a restart does not pay for compilation again.
+//! Artifacts are named after the SHA-256 of the component bytes and the
+//! engine's compatibility hash, which covers the wasmtime version and the
+//! compilation settings; an artifact that no longer matches is recompiled
+//! and overwritten.
+//!
+//! Concurrent loads of components with the same bytes share one compilation:
+//! the first caller compiles and the others await its result, error included.
+
+use super::execution_limiter::ExecutionQueueConfig;
+use super::instance_pool::InstancePoolConfig;
+use super::result_cache::ResultCacheConfig;
+use anyhow::{Context, Result};
+use futures::future::{BoxFuture, FutureExt, Shared};
+use sha2::{Digest, Sha256};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, warn};
+use wasmtime::{Engine, Module, OptLevel};
+
+/// Cranelift optimization level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WasmOptLevel {
+    /// Fastest compilation; for development
+    None,
+    /// Optimized code; for production
+    #[default]
+    Speed,
+    /// Optimized code, also favouring small size
+    SpeedAndSize,
+}
+
+impl WasmOptLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WasmOptLevel::None => "none",
+            WasmOptLevel::Speed => "speed",
+            WasmOptLevel::SpeedAndSize => "speed-and-size",
+        }
+    }
+
+    pub(crate) fn to_cranelift(self) -> OptLevel {
+        match self {
+            WasmOptLevel::None => OptLevel::None,
+            WasmOptLevel::Speed => OptLevel::Speed,
+            WasmOptLevel::SpeedAndSize => OptLevel::SpeedAndSize,
+        }
+    }
+}
+
+impl FromStr for WasmOptLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(WasmOptLevel::None),
+            "speed" => Ok(WasmOptLevel::Speed),
+            "speed-and-size" => Ok(WasmOptLevel::SpeedAndSize),
+            other => Err(format!(
+                "Unknown WASM optimization level '{other}' (expected none, speed or speed-and-size)"
+            )),
+        }
+    }
+}
+
+/// Default WASM stack limit in bytes.
+///
+/// Enough for ordinary recursion in components while staying far below the
+/// native stack of the worker thread, so a runaway component traps instead
+/// of taking the host down.
+pub const DEFAULT_MAX_WASM_STACK: usize = 512 * 1024;
+
+/// Default number of instances a single execution may create.
+///
+/// A component creates one core instance per embedded module plus a few for
+/// adapters, so real components stay well below this.
+pub const DEFAULT_MAX_INSTANCES: usize = 100;
+
+/// Default milliseconds between epoch ticks.
+///
+/// Timeouts and cancellation only take effect when the engine epoch advances,
+/// so the tick interval bounds how late a running call is stopped. Every tick
+/// wakes the ticker thread and makes each running call check its deadline:
+/// shorter intervals stop calls sooner at the cost of more of that overhead.
+/// 10 ms stops calls well within any practical timeout while the checks stay
+/// negligible next to the component's own work.
+pub const DEFAULT_EPOCH_TICK_MS: u64 = 10;
+
+/// Settings for [`super::WasmExecutionEngine`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineOptions {
+    pub opt_level: WasmOptLevel,
+    /// Directory for precompiled artifacts; `None` compiles in memory only
+    pub precompile_cache_dir: Option<PathBuf>,
+    pub instance_pool: InstancePoolConfig,
+    /// Bytes of stack WASM code may use before trapping with a stack overflow
+    pub max_wasm_stack: usize,
+    /// Instances (including core instances inside a component) allowed per store
+    pub max_instances: usize,
+    /// What executions beyond the concurrent execution limit do
+    pub execution_queue: ExecutionQueueConfig,
+    pub result_cache: ResultCacheConfig,
+    /// Interval between epoch ticks, the granularity of timeouts and
+    /// cancellation (see [`DEFAULT_EPOCH_TICK_MS`])
+    pub epoch_tick: Duration,
+    /// Meter fuel so profiled executions can report it. Metering instruments
+    /// the compiled code and slows every execution, profiled or not
+    pub fuel_metering: bool,
+}
+
+impl Default for EngineOptions {
+    fn default() -> Self {
+        Self {
+            opt_level: WasmOptLevel::default(),
+            precompile_cache_dir: None,
+            instance_pool: InstancePoolConfig::default(),
+            max_wasm_stack: DEFAULT_MAX_WASM_STACK,
+            max_instances: DEFAULT_MAX_INSTANCES,
+            execution_queue: ExecutionQueueConfig::default(),
+            result_cache: ResultCacheConfig::default(),
+            epoch_tick: Duration::from_millis(DEFAULT_EPOCH_TICK_MS),
+            fuel_metering: false,
+        }
+    }
+}
+
+type Compilation = Shared<BoxFuture<'static, std::result::Result<Module, Arc<anyhow::Error>>>>;
+
+/// In-memory module cache backed by an optional on-disk artifact cache
+#[derive(Default)]
+pub(crate) struct ModuleCache {
+    modules: Mutex<HashMap<String, Module>>,
+    /// Compilations in progress, keyed by the SHA-256 of the component bytes
+    in_flight: Mutex<HashMap<String, Compilation>>,
+    precompile_dir: Option<PathBuf>,
+}
+
+impl ModuleCache {
+    pub(crate) fn new(precompile_dir: Option<PathBuf>) -> Self {
+        Self {
+            modules: Mutex::new(HashMap::new()),
+            in_flight: Mutex::new(HashMap::new()),
+            precompile_dir,
+        }
+    }
+
+    /// Load a module, compiling it only if no cached copy is usable
+    pub(crate) async fn load(&self, engine: &Engine, component_path: &Path) -> Result<Module> {
+        let path_str = component_path.to_string_lossy().to_string();
+        if let Some(module) = self.modules.lock().unwrap().get(&path_str) {
+            return Ok(module.clone());
+        }
+
+        let wasm_bytes = tokio::fs::read(component_path)
+            .await
+            .with_context(|| format!("Failed to read WASM file: {component_path:?}"))?;
+
+        let module = self
+            .compile_shared(engine, wasm_bytes)
+            .await
+            .map_err(|e| anyhow::anyhow!("{e:#}"))
+            .with_context(|| format!("Failed to compile WASM module: {component_path:?}"))?;
+
+        self.modules
+            .lock()
+            .unwrap()
+            .insert(path_str, module.clone());
+        Ok(module)
+    }
+
+    /// Compile the bytes, or join a compilation of the same bytes already in progress
+    async fn compile_shared(
+        &self,
+        engine: &Engine,
+        wasm_bytes: Vec<u8>,
+    ) -> std::result::Result<Module, Arc<anyhow::Error>> {
+        let hash = format!("{:x}", Sha256::digest(&wasm_bytes));
+        let compilation = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .entry(hash.clone())
+            .or_insert_with(|| {
+                let engine = engine.clone();
+                let precompile_dir = self.precompile_dir.clone();
+                async move {
+                    match &precompile_dir {
+                        Some(dir) => Self::load_precompiled(&engine, dir, &wasm_bytes).await,
+                        None => Module::new(&engine, &wasm_bytes),
+                    }
+                    .map_err(Arc::new)
+                }
+                .boxed()
+                .shared()
+            })
+            .clone();
+
+        let result = compilation.clone().await;
+
+        // Only the finished compilation is removed, never a newer one for the same bytes
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if in_flight
+            .get(&hash)
+            .is_some_and(|current| current.ptr_eq(&compilation))
+        {
+            in_flight.remove(&hash);
+        }
+        result
+    }
+
+    fn artifact_name(engine: &Engine, wasm_bytes: &[u8]) -> String {
+        let mut hasher = DefaultHasher::new();
+        engine.precompile_compatibility_hash().hash(&mut hasher);
+        format!(
+            "{:x}-{:016x}.cwasm",
+            Sha256::digest(wasm_bytes),
+            hasher.finish()
+        )
+    }
+
+    async fn load_precompiled(engine: &Engine, dir: &Path, wasm_bytes: &[u8]) -> Result<Module> {
+        let artifact = dir.join(Self::artifact_name(engine, wasm_bytes));
+
+        if artifact.exists() {
+            // SAFETY: artifacts in the cache directory are only ever written
+            // by `Module::serialize` below, and wasmtime rejects artifacts
+            // built by a different version or configuration.
+            match unsafe { Module::deserialize_file(engine, &artifact) } {
+                Ok(module) => {
+                    debug!("Loaded precompiled module {:?}", artifact);
+                    return Ok(module);
+                }
+                Err(e) => warn!(
+                    "Discarding unusable precompiled module {:?}: {}",
+                    artifact, e
+                ),
+            }
+        }
+
+        let module = Module::new(engine, wasm_bytes)?;
+        let write = async {
+            tokio::fs::create_dir_all(dir).await?;
+            let temp = artifact.with_extension("cwasm.tmp");
+            tokio::fs::write(&temp, module.serialize()?).await?;
+            tokio::fs::rename(&temp, &artifact).await?;
+            anyhow::Ok(())
+        };
+        if let Err(e) = write.await {
+            warn!("Failed to write precompiled module {:?}: {}", artifact, e);
+        }
+        Ok(module)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_opt_level() {
+        assert_eq!("none".parse::<WasmOptLevel>(), Ok(WasmOptLevel::None));
+        assert_eq!(
+            WasmOptLevel::SpeedAndSize.as_str().parse::<WasmOptLevel>(),
+            Ok(WasmOptLevel::SpeedAndSize)
+        );
+        assert!("fast".parse::<WasmOptLevel>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_loads_share_result() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = Engine::default();
+        let cache = ModuleCache::new(None);
+
+        let first = dir.path().join("first.wasm");
+        let second = dir.path().join("second.wasm");
+        // Same bytes under two paths; wasmtime accepts the text format as well
+        let module = r#"(module (func (export "run")))"#;
+        std::fs::write(&first, module).unwrap();
+        std::fs::write(&second, module).unwrap();
+
+        let (a, b) = tokio::join!(cache.load(&engine, &first), cache.load(&engine, &second));
+        assert!(a.unwrap().get_export("run").is_some());
+        assert!(b.unwrap().get_export("run").is_some());
+        assert!(cache.in_flight.lock().unwrap().is_empty());
+
+        let broken = dir.path().join("broken.wasm");
+        std::fs::write(&broken, b"\0asm not really").unwrap();
+        let (a, b) = tokio::join!(cache.load(&engine, &broken), cache.load(&engine, &broken));
+        assert!(a.is_err() && b.is_err());
+        assert!(cache.in_flight.lock().unwrap().is_empty());
+    }
+}
@@ -3,7 +3,9 @@
 //! Enables linking multiple WASM components together into processing pipelines
 //! with support for sequential and parallel execution patterns.
 
-use crate::wasm::execution_engine::{ExecutionContext, ExecutionResult, WasmExecutionEngine};
+use crate::wasm::execution_engine::{
+    ExecutionContext, ExecutionResult, TraceSpan, WasmExecutionEngine,
+};
 use crate::wasm::sensor_bridge::SensorBridgeConfig;
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
@@ -191,6 +193,10 @@ pub struct PipelineSettings {
 
     /// Pipeline execution mode
     pub execution_mode: ExecutionMode,
+
+    /// Record a span for every stage's function call (adds overhead)
+    #[serde(default)]
+    pub trace: bool,
 }
 
 /// Data persistence settings
@@ -251,6 +257,11 @@ pub struct PipelineExecution {
 
     /// Error information (if failed)
     pub error: Option<String>,
+
+    /// Function calls of all stages in the order they were entered, when the
+    /// pipeline is traced
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trace: Vec<TraceSpan>,
 }
 
 /// Pipeline execution state
@@ -401,6 +412,7 @@ impl WasmPipelineEngine {
             started_at: Utc::now(),
             completed_at: None,
             error: None,
+            trace: Vec::new(),
         };
 
         let execution_arc = Arc::new(Mutex::new(execution));
@@ -663,10 +675,13 @@ impl WasmPipelineEngine {
         let input_data =
             Self::prepare_stage_input(execution_arc.clone(), stage, stage_outputs).await?;
 
-        // Get sensor configuration from pipeline
-        let sensor_config = {
+        // Get sensor configuration and tracing from pipeline
+        let (sensor_config, trace) = {
             let execution = execution_arc.lock().await;
-            execution.config.sensor_config.clone()
+            (
+                execution.config.sensor_config.clone(),
+                execution.config.settings.trace,
+            )
         };
 
         // Create execution context
@@ -679,6 +694,9 @@ impl WasmPipelineEngine {
             max_memory_mb: stage.execution_settings.max_memory_mb,
             created_at: Utc::now(),
             sensor_config,
+            profile: false,
+            no_cache: false,
+            trace,
         };
 
         // Execute with retries
@@ -716,6 +734,7 @@ impl WasmPipelineEngine {
                                     .stage_results
                                     .insert(stage_id.to_string(), stage_result.clone());
                                 execution.stats.stages_executed += 1;
+                                Self::record_trace(&mut execution, stage_id, &stage_result.result);
                             }
 
                             return Ok(stage_result);
@@ -759,6 +778,9 @@ impl WasmPipelineEngine {
                                     output_data: None,
                                     graphics_output: None,
                                     completed_at: Utc::now(),
+                                    profile: None,
+                                    cached: false,
+                                    trace: None,
                                 },
                                 input_data: Some(input_data),
                                 output_data: None,
@@ -879,6 +901,21 @@ impl WasmPipelineEngine {
         Ok(current.clone())
     }
 
+    /// Add the spans of a stage's execution to the pipeline's flat trace
+    fn record_trace(execution: &mut PipelineExecution, stage_id: &str, result: &ExecutionResult) {
+        let Some(spans) = &result.trace else {
+            return;
+        };
+        execution
+            .trace
+            .extend(spans.iter().cloned().map(|span| TraceSpan {
+                stage_id: Some(stage_id.to_string()),
+                ..span
+            }));
+        // Parallel stages finish out of order
+        execution.trace.sort_by_key(|span| span.entered_at);
+    }
+
     /// Calculate retry delay based on backoff strategy
     fn calculate_retry_delay(config: &RetryConfig, retry_count: u32) -> u64 {
         match config.backoff_strategy {
@@ -967,6 +1004,7 @@ impl Default for PipelineSettings {
             fail_fast: true,
             persistence: PersistenceSettings::default(),
             execution_mode: ExecutionMode::Single,
+            trace: false,
         }
     }
 }
@@ -0,0 +1,199 @@
+//! Memoized results of component executions
+//!
+//! Deterministic components return the same result for the same inputs, so
+//! a repeated call can reuse an earlier result instead of running again. The
+//! cache key is the SHA-256 of the component bytes, the function and the
+//! serialized arguments; a rebuilt component therefore never sees results of
+//! its previous version. Only successful results are kept. Entries expire
+//! after a TTL, and the oldest entry is evicted once the cache is full.
+//!
+//! Calls to nondeterministic components opt out with `noCache`.
+
+use super::execution_engine::ExecutionResult;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Default time a cached result stays valid
+pub const DEFAULT_RESULT_CACHE_TTL_SECS: u64 = 300;
+
+/// Result cache settings; a size of 0 disables caching
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResultCacheConfig {
+    /// Results kept across all components
+    pub max_entries: usize,
+    pub ttl: Duration,
+}
+
+impl Default for ResultCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 0,
+            ttl: Duration::from_secs(DEFAULT_RESULT_CACHE_TTL_SECS),
+        }
+    }
+}
+
+/// Counters reported in the server metrics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResultCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries dropped to make room, not counting expired ones
+    pub evictions: u64,
+    pub entries: usize,
+    pub max_entries: usize,
+}
+
+struct Entry {
+    result: ExecutionResult,
+    stored_at: Instant,
+}
+
+pub(crate) struct ResultCache {
+    config: ResultCacheConfig,
+    entries: Mutex<HashMap<String, Entry>>,
+    stats: Mutex<ResultCacheStats>,
+}
+
+impl ResultCache {
+    pub(crate) fn new(config: ResultCacheConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(HashMap::new()),
+            stats: Mutex::new(ResultCacheStats {
+                max_entries: config.max_entries,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Key of a call to `method` with `args` on the component in `component_bytes`
+    pub(crate) fn key(component_bytes: &[u8], method: &str, args: &serde_json::Value) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(Sha256::digest(component_bytes));
+        hasher.update(method.as_bytes());
+        hasher.update([0]);
+        hasher.update(args.to_string().as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// The cached result for `key`, if there is one that has not expired
+    pub(crate) fn get(&self, key: &str) -> Option<ExecutionResult> {
+        let mut entries = self.entries.lock().unwrap();
+        let fresh = entries
+            .get(key)
+            .filter(|entry| entry.stored_at.elapsed() < self.config.ttl)
+            .map(|entry| entry.result.clone());
+        if fresh.is_none() {
+            entries.remove(key);
+        }
+
+        let mut stats = self.stats.lock().unwrap();
+        match fresh {
+            Some(_) => stats.hits += 1,
+            None => stats.misses += 1,
+        }
+        stats.entries = entries.len();
+        fresh
+    }
+
+    /// Remember a result; failed executions are not cached
+    pub(crate) fn insert(&self, key: String, result: &ExecutionResult) {
+        if !result.success {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        let ttl = self.config.ttl;
+        entries.retain(|_, entry| entry.stored_at.elapsed() < ttl);
+
+        let mut stats = self.stats.lock().unwrap();
+        while entries.len() >= self.config.max_entries && !entries.contains_key(&key) {
+            let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            entries.remove(&oldest);
+            stats.evictions += 1;
+        }
+        entries.insert(
+            key,
+            Entry {
+                result: result.clone(),
+                stored_at: Instant::now(),
+            },
+        );
+        stats.entries = entries.len();
+    }
+
+    pub(crate) fn stats(&self) -> ResultCacheStats {
+        *self.stats.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn result(success: bool) -> ExecutionResult {
+        ExecutionResult {
+            execution_id: "e-1".to_string(),
+            success,
+            result: Some(json!(42)),
+            error: None,
+            execution_time_ms: 5,
+            memory_usage_mb: 1,
+            output_data: None,
+            graphics_output: None,
+            completed_at: Utc::now(),
+            profile: None,
+            cached: false,
+            trace: None,
+        }
+    }
+
+    #[test]
+    fn test_results_are_keyed_by_inputs_and_bounded() {
+        let cache = ResultCache::new(ResultCacheConfig {
+            max_entries: 2,
+            ..Default::default()
+        });
+        let key = |bytes: &[u8], args| ResultCache::key(bytes, "run", &args);
+        let first = key(b"component", json!({"x": 1}));
+        assert_ne!(first, key(b"component", json!({"x": 2})));
+        assert_ne!(first, key(b"rebuilt component", json!({"x": 1})));
+
+        assert!(cache.get(&first).is_none());
+        cache.insert(first.clone(), &result(true));
+        assert_eq!(cache.get(&first).unwrap().result, Some(json!(42)));
+
+        // Failures are not cached
+        let failed = key(b"component", json!({"x": 3}));
+        cache.insert(failed.clone(), &result(false));
+        assert!(cache.get(&failed).is_none());
+
+        // A full cache evicts its oldest entry
+        cache.insert(key(b"component", json!({"x": 4})), &result(true));
+        cache.insert(key(b"component", json!({"x": 5})), &result(true));
+        assert!(cache.get(&first).is_none());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.evictions), (1, 3, 1));
+        assert_eq!(stats.entries, 2);
+
+        let expiring = ResultCache::new(ResultCacheConfig {
+            max_entries: 2,
+            ttl: Duration::ZERO,
+        });
+        expiring.insert(first.clone(), &result(true));
+        assert!(expiring.get(&first).is_none());
+    }
+}
@@ -730,6 +730,29 @@ impl WitAnalyzer {
         Ok(wit_text)
     }
 
+    /// Reconstruct WIT source from the type information embedded in a
+    /// component binary.
+    ///
+    /// A component decodes to a synthesized package holding its world, with
+    /// every import and export; the packages it refers to are printed as
+    /// nested packages so the result stands on its own. A WIT package
+    /// encoded as a binary prints as that package.
+    pub fn reconstruct_wit(wasm_bytes: &[u8]) -> Result<String> {
+        let decoded = wit_component::decode(wasm_bytes)
+            .context("Not a WebAssembly component or has no component type information")?;
+        let resolve = decoded.resolve();
+        let package = decoded.package();
+        let nested: Vec<PackageId> = resolve
+            .packages
+            .iter()
+            .map(|(id, _)| id)
+            .filter(|id| *id != package)
+            .collect();
+        wit_component::WitPrinter::default()
+            .print(resolve, package, &nested)
+            .context("Failed to print WIT")
+    }
+
     /// Debug method to test interface extraction from a specific component
     pub async fn debug_component_interfaces<P: AsRef<Path>>(path: P) -> Result<()> {
         let analysis = Self::analyze_component(path.as_ref()).await?;
@@ -861,49 +884,43 @@ impl WitAnalyzer {
 
             // Validate specific type definitions
             match &wit_type.type_def {
-                WitTypeDefinition::Record { fields } => {
-                    if fields.is_empty() {
-                        validation_results.push(WitValidationIssue {
-                            issue_type: WitValidationIssueType::InvalidSignature {
-                                function: wit_type.name.clone(),
-                                issue: "Empty record type".to_string(),
-                            },
-                            severity: WitValidationSeverity::Warning,
-                            message: format!("Record type '{}' has no fields", wit_type.name),
-                            suggestion: Some(
-                                "Consider if this empty record is intentional".to_string(),
-                            ),
-                            location: Some(format!("Type: {}", wit_type.name)),
-                        });
-                    }
+                WitTypeDefinition::Record { fields } if fields.is_empty() => {
+                    validation_results.push(WitValidationIssue {
+                        issue_type: WitValidationIssueType::InvalidSignature {
+                            function: wit_type.name.clone(),
+                            issue: "Empty record type".to_string(),
+                        },
+                        severity: WitValidationSeverity::Warning,
+                        message: format!("Record type '{}' has no fields", wit_type.name),
+                        suggestion: Some(
+                            "Consider if this empty record is intentional".to_string(),
+                        ),
+                        location: Some(format!("Type: {}", wit_type.name)),
+                    });
                 }
-                WitTypeDefinition::Variant { cases } => {
-                    if cases.is_empty() {
-                        validation_results.push(WitValidationIssue {
-                            issue_type: WitValidationIssueType::InvalidSignature {
-                                function: wit_type.name.clone(),
-                                issue: "Empty variant type".to_string(),
-                            },
-                            severity: WitValidationSeverity::Error,
-                            message: format!("Variant type '{}' has no cases", wit_type.name),
-                            suggestion: Some("Add at least one variant case".to_string()),
-                            location: Some(format!("Type: {}", wit_type.name)),
-                        });
-                    }
+                WitTypeDefinition::Variant { cases } if cases.is_empty() => {
+                    validation_results.push(WitValidationIssue {
+                        issue_type: WitValidationIssueType::InvalidSignature {
+                            function: wit_type.name.clone(),
+                            issue: "Empty variant type".to_string(),
+                        },
+                        severity: WitValidationSeverity::Error,
+                        message: format!("Variant type '{}' has no cases", wit_type.name),
+                        suggestion: Some("Add at least one variant case".to_string()),
+                        location: Some(format!("Type: {}", wit_type.name)),
+                    });
                 }
-                WitTypeDefinition::Enum { cases } => {
-                    if cases.is_empty() {
-                        validation_results.push(WitValidationIssue {
-                            issue_type: WitValidationIssueType::InvalidSignature {
-                                function: wit_type.name.clone(),
-                                issue: "Empty enum type".to_string(),
-                            },
-                            severity: WitValidationSeverity::Error,
-                            message: format!("Enum type '{}' has no cases", wit_type.name),
-                            suggestion: Some("Add at least one enum value".to_string()),
-                            location: Some(format!("Type: {}", wit_type.name)),
-                        });
-                    }
+                WitTypeDefinition::Enum { cases } if cases.is_empty() => {
+                    validation_results.push(WitValidationIssue {
+                        issue_type: WitValidationIssueType::InvalidSignature {
+                            function: wit_type.name.clone(),
+                            issue: "Empty enum type".to_string(),
+                        },
+                        severity: WitValidationSeverity::Error,
+                        message: format!("Enum type '{}' has no cases", wit_type.name),
+                        suggestion: Some("Add at least one enum value".to_string()),
+                        location: Some(format!("Type: {}", wit_type.name)),
+                    });
                 }
                 _ => {}
             }
@@ -1059,6 +1076,27 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_reconstruct_wit_round_trips_a_package() {
+        let mut resolve = Resolve::default();
+        let package = resolve
+            .push_str(
+                "camera.wit",
+                "package adas:camera@0.1.0;\n\
+                 interface frames {\n    next-frame: func(width: u32) -> list<u8>;\n}\n\
+                 world camera {\n    export frames;\n}\n",
+            )
+            .unwrap();
+        let bytes = wit_component::encode(&resolve, package).unwrap();
+
+        let wit = WitAnalyzer::reconstruct_wit(&bytes).unwrap();
+        assert!(wit.contains("package adas:camera@0.1.0"));
+        assert!(wit.contains("next-frame: func(width: u32) -> list<u8>;"));
+        assert!(wit.contains("export frames;"));
+
+        assert!(WitAnalyzer::reconstruct_wit(b"not wasm").is_err());
+    }
+
     #[tokio::test]
     #[ignore] // Ignore by default since it requires specific test files
     async fn test_wit_analyzer_with_real_component() {
@@ -0,0 +1,251 @@
+//! Outbound webhooks for diagram changes
+//!
+//! Integrations that cannot hold an `/events` connection register a URL
+//! instead. After every successful change to a watched diagram the server
+//! POSTs a JSON [`WebhookPayload`] naming the diagram, its revision and the
+//! delta: the element operations the change made, in the form used by the
+//! operation log (see [`crate::oplog`]). In-place element edits also list
+//! each changed field with its old and new value. A non-2xx response or a
+//! transport error is retried with exponential backoff until the attempts run
+//! out; a delivery that still fails is logged and dropped.
+//!
+//! A webhook with a secret signs every request: [`SIGNATURE_HEADER`] holds
+//! `sha256=<hex>`, the HMAC-SHA256 of the raw body keyed by the secret, so the
+//! receiver can verify the request came from this server.
+
+use crate::events::{DiagramFilter, PropertyChange};
+use crate::oplog::LogOp;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{error, warn};
+
+/// Header carrying the HMAC signature of the request body
+pub const SIGNATURE_HEADER: &str = "X-GLSP-Signature";
+
+/// Default number of delivery attempts per change
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A registered webhook
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    pub filter: DiagramFilter,
+    /// Whether requests carry a signature
+    pub signed: bool,
+    #[serde(skip)]
+    secret: Option<String>,
+}
+
+/// Body of a webhook request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookPayload {
+    pub diagram_id: String,
+    /// Revision after the change; absent if the diagram was deleted
+    pub revision: Option<u32>,
+    pub tool: String,
+    /// What the change did; empty when the diagram was deleted
+    pub delta: Vec<LogOp>,
+    /// Old and new value of each field an in-place element edit changed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<PropertyChange>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// `sha256=<hex>` signature of `body` keyed by `secret`
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={:x}", mac.finalize().into_bytes())
+}
+
+/// Wait before retrying after failed attempt number `attempt` (from 1)
+fn backoff(attempt: u32) -> Duration {
+    INITIAL_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_BACKOFF)
+}
+
+/// Registered webhooks and their delivery
+pub struct WebhookRegistry {
+    hooks: Mutex<BTreeMap<String, Webhook>>,
+    client: reqwest::Client,
+    max_attempts: u32,
+}
+
+impl WebhookRegistry {
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            hooks: Mutex::new(BTreeMap::new()),
+            client: reqwest::Client::new(),
+            max_attempts: max_attempts.max(1),
+        }
+    }
+
+    /// Register `url` for changes to the diagrams in `filter`; requests are
+    /// signed when there is a `secret`
+    pub fn register(
+        &self,
+        url: &str,
+        filter: DiagramFilter,
+        secret: Option<String>,
+    ) -> Result<Webhook, String> {
+        let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL '{url}': {e}"))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(format!("Webhook URL '{url}' must use http or https"));
+        }
+        if secret.as_deref().is_some_and(str::is_empty) {
+            return Err("Webhook secret must not be empty".to_string());
+        }
+
+        let hook = Webhook {
+            id: uuid::Uuid::new_v4().to_string(),
+            url: parsed.to_string(),
+            filter,
+            signed: secret.is_some(),
+            secret,
+        };
+        self.hooks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(hook.id.clone(), hook.clone());
+        Ok(hook)
+    }
+
+    /// Remove a webhook; false if it is not registered
+    pub fn unregister(&self, id: &str) -> bool {
+        self.hooks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id)
+            .is_some()
+    }
+
+    pub fn list(&self) -> Vec<Webhook> {
+        self.hooks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    fn watching(&self, diagram_id: &str) -> Vec<Webhook> {
+        self.hooks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .filter(|hook| hook.filter.includes(diagram_id))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether any webhook receives changes to the diagram
+    pub fn is_watched(&self, diagram_id: &str) -> bool {
+        !self.watching(diagram_id).is_empty()
+    }
+
+    /// Deliver `payload` to every webhook watching its diagram, in the
+    /// background
+    pub fn notify(&self, payload: &WebhookPayload) {
+        let hooks = self.watching(&payload.diagram_id);
+        if hooks.is_empty() {
+            return;
+        }
+        let body = match serde_json::to_string(payload) {
+            Ok(body) => body,
+            Err(e) => {
+                error!("Failed to serialize webhook payload: {e}");
+                return;
+            }
+        };
+        for hook in hooks {
+            tokio::spawn(deliver(
+                self.client.clone(),
+                hook,
+                body.clone(),
+                self.max_attempts,
+            ));
+        }
+    }
+}
+
+async fn deliver(client: reqwest::Client, hook: Webhook, body: String, max_attempts: u32) {
+    for attempt in 1..=max_attempts {
+        let mut request = client
+            .post(&hook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .timeout(REQUEST_TIMEOUT)
+            .body(body.clone());
+        if let Some(secret) = &hook.secret {
+            request = request.header(SIGNATURE_HEADER, sign(secret, body.as_bytes()));
+        }
+        let failure = match request.send().await {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) => format!("HTTP {}", response.status()),
+            Err(e) => e.to_string(),
+        };
+        if attempt == max_attempts {
+            error!(
+                "Giving up on webhook {} ({}) after {attempt} attempts: {failure}",
+                hook.id, hook.url
+            );
+        } else {
+            let wait = backoff(attempt);
+            warn!(
+                "Webhook {} ({}) failed: {failure}; retrying in {wait:?}",
+                hook.id, hook.url
+            );
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registration_filters_and_signatures() {
+        // The widely published HMAC-SHA256 example
+        assert_eq!(
+            sign("key", b"The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+        assert_eq!(backoff(1), INITIAL_BACKOFF);
+        assert_eq!(backoff(3), INITIAL_BACKOFF * 4);
+        assert_eq!(backoff(30), MAX_BACKOFF);
+
+        let registry = WebhookRegistry::new(DEFAULT_MAX_ATTEMPTS);
+        assert!(registry
+            .register("ftp://example.com/hook", DiagramFilter::all(), None)
+            .is_err());
+        let hook = registry
+            .register(
+                "https://ci.example.com/hook",
+                DiagramFilter::diagrams(["d-1".to_string()]),
+                Some("secret".to_string()),
+            )
+            .unwrap();
+        assert!(hook.signed);
+        assert!(registry.is_watched("d-1"));
+        assert!(!registry.is_watched("d-2"));
+        assert!(serde_json::to_value(&hook).unwrap().get("secret").is_none());
+
+        assert!(registry.unregister(&hook.id));
+        assert!(!registry.unregister(&hook.id));
+        assert!(!registry.is_watched("d-1"));
+    }
+}
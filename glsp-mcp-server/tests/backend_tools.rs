@@ -0,0 +1,793 @@
+//! Tool-level tests that drive [`GlspBackend::call_tool`] against a backend
+//! whose workspace lives in a temporary directory
+
+use glsp_mcp_server::auth::{as_client, declared_scope};
+use glsp_mcp_server::persistence::DeadLetterStore;
+use glsp_mcp_server::{
+    CallToolRequestParam, CallToolResult, Content, DiagramModel, GlspBackend, GlspConfig,
+    GlspError, PaginatedRequestParam,
+};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+/// A configuration with every workspace directory inside `workspace`
+fn config(workspace: &TempDir) -> GlspConfig {
+    let path = |name: &str| workspace.path().join(name).to_string_lossy().into_owned();
+    GlspConfig {
+        wasm_path: path("components"),
+        diagrams_path: path("diagrams"),
+        dead_letter_path: path("dead-letter"),
+        state_snapshot_path: path("state-snapshots"),
+        export_path: path("exports"),
+        ..GlspConfig::default()
+    }
+}
+
+/// A backend with every workspace directory inside `workspace`
+async fn start(workspace: &TempDir, configure: impl FnOnce(&mut GlspConfig)) -> GlspBackend {
+    let mut config = config(workspace);
+    configure(&mut config);
+    std::fs::create_dir_all(&config.wasm_path).unwrap();
+    std::fs::create_dir_all(&config.diagrams_path).unwrap();
+    GlspBackend::initialize(config).await.unwrap()
+}
+
+/// A backend in a fresh temporary workspace
+async fn backend() -> (GlspBackend, TempDir) {
+    let workspace = TempDir::new().unwrap();
+    (start(&workspace, |_| {}).await, workspace)
+}
+
+async fn call(backend: &GlspBackend, name: &str, arguments: Value) -> CallToolResult {
+    backend
+        .call_tool(CallToolRequestParam {
+            name: name.to_string(),
+            arguments: Some(arguments),
+        })
+        .await
+        .unwrap_or_else(|e| panic!("{name} failed: {e}"))
+}
+
+/// The error of a call that is expected to fail
+async fn call_err(backend: &GlspBackend, name: &str, arguments: Value) -> GlspError {
+    match backend
+        .call_tool(CallToolRequestParam {
+            name: name.to_string(),
+            arguments: Some(arguments),
+        })
+        .await
+    {
+        Ok(result) => panic!("{name} succeeded: {result:?}"),
+        Err(e) => e,
+    }
+}
+
+/// Text of every content item
+fn texts(result: &CallToolResult) -> Vec<&str> {
+    result
+        .content
+        .iter()
+        .filter_map(|content| match content {
+            Content::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The first content item that parses as a JSON object
+fn json_item(result: &CallToolResult) -> Value {
+    texts(result)
+        .into_iter()
+        .filter_map(|text| serde_json::from_str::<Value>(text).ok())
+        .find(Value::is_object)
+        .unwrap_or_else(|| panic!("no JSON content in {result:?}"))
+}
+
+/// The ID a creating tool reports in its `... ID: <id>` text
+fn reported_id(result: &CallToolResult) -> String {
+    texts(result)
+        .into_iter()
+        .find_map(|text| text.split("ID: ").nth(1))
+        .and_then(|rest| rest.split_whitespace().next())
+        .unwrap_or_else(|| panic!("no ID in {result:?}"))
+        .to_string()
+}
+
+async fn create_diagram(backend: &GlspBackend, diagram_type: &str) -> String {
+    let result = call(
+        backend,
+        "create_diagram",
+        json!({"diagramType": diagram_type, "name": "Test"}),
+    )
+    .await;
+    json_item(&result)["diagramId"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+async fn create_node(backend: &GlspBackend, diagram_id: &str, args: Value) -> String {
+    let mut arguments = json!({"diagramId": diagram_id, "nodeType": "task"});
+    arguments
+        .as_object_mut()
+        .unwrap()
+        .extend(args.as_object().unwrap().clone());
+    let result = call(backend, "create_node", arguments).await;
+    assert_ne!(result.is_error, Some(true), "{result:?}");
+    reported_id(&result)
+}
+
+async fn create_edge(
+    backend: &GlspBackend,
+    diagram_id: &str,
+    source: &str,
+    target: &str,
+) -> String {
+    let result = call(
+        backend,
+        "create_edge",
+        json!({"diagramId": diagram_id, "edgeType": "flow", "sourceId": source, "targetId": target}),
+    )
+    .await;
+    assert_ne!(result.is_error, Some(true), "{result:?}");
+    reported_id(&result)
+}
+
+async fn diagram(backend: &GlspBackend, diagram_id: &str) -> Value {
+    json_item(&call(backend, "get_diagram", json!({"diagramId": diagram_id})).await)
+}
+
+async fn history_elements(backend: &GlspBackend, diagram_id: &str, operation: &str) -> Value {
+    let arguments = json!({"diagramId": diagram_id, "operation": operation});
+    let history = json_item(&call(backend, "get_history", arguments).await);
+    history["entries"][0]["elementIds"].clone()
+}
+
+async fn element(backend: &GlspBackend, diagram_id: &str, element_id: &str) -> Value {
+    let result = call(backend, "get_diagram", json!({"diagramId": diagram_id})).await;
+    json_item(&result)["elements"][element_id].clone()
+}
+
+#[tokio::test]
+async fn test_elements_record_their_author_and_timestamps() {
+    let (backend, _workspace) = backend().await;
+    let diagram_id = create_diagram(&backend, "workflow").await;
+    // As the http-direct transport runs the calls of a `client:alice` token
+    let node_id = as_client(
+        Some("alice".to_string()),
+        create_node(
+            &backend,
+            &diagram_id,
+            json!({"label": "Fetch", "clientId": "alice", "position": {"x": 0.0, "y": 0.0}}),
+        ),
+    )
+    .await;
+    // A clientId argument alone is only a claim
+    let claimed_id = create_node(
+        &backend,
+        &diagram_id,
+        json!({"label": "Claimed", "clientId": "mallory"}),
+    )
+    .await;
+    assert!(element(&backend, &diagram_id, &claimed_id).await["created_by"].is_null());
+
+    let created = element(&backend, &diagram_id, &node_id).await;
+    assert_eq!(created["created_by"], "alice");
+    assert!(created["created_at"].is_string());
+    assert_eq!(created["created_at"], created["updated_at"]);
+
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    let updated = call(
+        &backend,
+        "update_element",
+        json!({
+            "diagramId": diagram_id,
+            "elementId": node_id,
+            "clientId": "bob",
+            "properties": {"label": "Fetch all"},
+        }),
+    )
+    .await;
+    assert_ne!(updated.is_error, Some(true), "{updated:?}");
+
+    let updated = element(&backend, &diagram_id, &node_id).await;
+    // Editing does not change who created the element
+    assert_eq!(updated["created_by"], "alice");
+    assert_eq!(updated["created_at"], created["created_at"]);
+    assert_ne!(updated["updated_at"], created["updated_at"]);
+
+    let found = call(
+        &backend,
+        "find_nodes",
+        json!({"diagramId": diagram_id, "nodeType": "task"}),
+    )
+    .await;
+    assert_eq!(json_item(&found)["count"], 2);
+}
+
+#[tokio::test]
+async fn test_diagram_metadata_and_tags() {
+    let (backend, workspace) = backend().await;
+    let diagram_id = create_diagram(&backend, "workflow").await;
+    let other_id = create_diagram(&backend, "workflow").await;
+
+    let set = call(
+        &backend,
+        "set_diagram_metadata",
+        json!({"diagramId": diagram_id, "metadata": {"owner": "alice", "description": "ADAS"}}),
+    )
+    .await;
+    assert_ne!(set.is_error, Some(true), "{set:?}");
+    // A null value removes the key
+    call(
+        &backend,
+        "set_diagram_metadata",
+        json!({"diagramId": diagram_id, "metadata": {"description": null}}),
+    )
+    .await;
+
+    let tagged = call(
+        &backend,
+        "add_diagram_tags",
+        json!({"diagramId": diagram_id, "tags": ["adas", " perception ", ""]}),
+    )
+    .await;
+    assert_eq!(json_item(&tagged)["added"], json!(["adas", "perception"]));
+    let again = call(
+        &backend,
+        "add_diagram_tags",
+        json!({"diagramId": diagram_id, "tags": ["adas"]}),
+    )
+    .await;
+    assert_eq!(json_item(&again)["added"], json!([]));
+
+    let listed = call(&backend, "list_diagrams", json!({"tag": "adas"})).await;
+    let diagrams = json_item(&listed)["diagrams"].clone();
+    assert_eq!(diagrams.as_array().unwrap().len(), 1);
+    assert_eq!(diagrams[0]["id"], diagram_id.as_str());
+    assert_ne!(diagrams[0]["id"], other_id.as_str());
+
+    // Metadata and tags survive a restart
+    drop(backend);
+    let restarted = start(&workspace, |_| {}).await;
+    let diagram = call(&restarted, "get_diagram", json!({"diagramId": diagram_id})).await;
+    let diagram = json_item(&diagram);
+    assert_eq!(diagram["metadata"]["owner"], "alice");
+    assert!(diagram["metadata"].get("description").is_none());
+    assert_eq!(diagram["tags"], json!(["adas", "perception"]));
+}
+
+#[tokio::test]
+async fn test_acyclic_diagrams_reject_cycles_at_creation() {
+    let (backend, _workspace) = backend().await;
+    let diagram_id = create_diagram(&backend, "workflow").await;
+    let a = create_node(&backend, &diagram_id, json!({"label": "A"})).await;
+    let b = create_node(&backend, &diagram_id, json!({"label": "B"})).await;
+    let c = create_node(&backend, &diagram_id, json!({"label": "C"})).await;
+    let ab = create_edge(&backend, &diagram_id, &a, &b).await;
+    create_edge(&backend, &diagram_id, &b, &c).await;
+    call(
+        &backend,
+        "set_diagram_metadata",
+        json!({"diagramId": diagram_id, "metadata": {"mustBeAcyclic": true}}),
+    )
+    .await;
+
+    let checked = call(&backend, "assert_acyclic", json!({"diagramId": diagram_id})).await;
+    assert_ne!(checked.is_error, Some(true), "{checked:?}");
+    assert_eq!(json_item(&checked)["acyclic"], true);
+    assert_eq!(json_item(&checked)["mustBeAcyclic"], true);
+
+    let before = diagram(&backend, &diagram_id).await;
+    let closing =
+        json!({"diagramId": diagram_id, "edgeType": "flow", "sourceId": c, "targetId": a});
+    let error = call_err(&backend, "create_edge", closing.clone()).await;
+    assert!(
+        matches!(&error, GlspError::WouldCreateCycle { cycle } if cycle.len() == 3),
+        "{error:?}"
+    );
+    let reversed = json!({"diagramId": diagram_id, "edgeId": ab});
+    let error = call_err(&backend, "reverse_edge", reversed).await;
+    assert!(
+        matches!(error, GlspError::WouldCreateCycle { .. }),
+        "{error:?}"
+    );
+
+    // Rejected changes were never applied
+    let after = diagram(&backend, &diagram_id).await;
+    assert_eq!(after["revision"], before["revision"]);
+    assert_eq!(after["elements"], before["elements"]);
+
+    // In a batch only the edge closing the cycle fails
+    let batch = call(
+        &backend,
+        "create_edges",
+        json!({"diagramId": diagram_id, "edges": [
+            {"edgeType": "flow", "sourceId": a, "targetId": c},
+            {"edgeType": "flow", "sourceId": c, "targetId": a},
+        ]}),
+    )
+    .await;
+    let batch = json_item(&batch);
+    assert_eq!(batch["created"], 1);
+    assert_eq!(batch["errors"][0]["index"], 1);
+
+    // Undirected edges never form a cycle
+    let mut undirected = closing;
+    undirected["directed"] = json!(false);
+    let created = call(&backend, "create_edge", undirected).await;
+    assert_ne!(created.is_error, Some(true), "{created:?}");
+}
+
+#[tokio::test]
+async fn test_create_edges_in_one_batch() {
+    let (backend, _workspace) = backend().await;
+    let diagram_id = create_diagram(&backend, "workflow").await;
+    let a = create_node(&backend, &diagram_id, json!({"label": "A"})).await;
+    let b = create_node(&backend, &diagram_id, json!({"label": "B"})).await;
+    let c = create_node(&backend, &diagram_id, json!({"label": "C"})).await;
+    let edges = json!([
+        {"edgeType": "flow", "sourceId": a, "targetId": b},
+        {"edgeType": "flow", "sourceId": a, "targetId": "missing"},
+        {"edgeType": "flow", "sourceId": b, "targetId": c},
+    ]);
+
+    // An atomic batch with one invalid edge creates none
+    let before = diagram(&backend, &diagram_id).await;
+    let atomic = call(
+        &backend,
+        "create_edges",
+        json!({"diagramId": diagram_id, "edges": edges, "atomic": true}),
+    )
+    .await;
+    assert_eq!(atomic.is_error, Some(true));
+    assert_eq!(json_item(&atomic)["created"], 0);
+    assert_eq!(json_item(&atomic)["errors"][0]["index"], 1);
+    let after = diagram(&backend, &diagram_id).await;
+    assert_eq!(after["revision"], before["revision"]);
+    assert_eq!(after["elements"], before["elements"]);
+
+    // Otherwise the valid edges are created and IDs follow the request order
+    let partial = call(
+        &backend,
+        "create_edges",
+        json!({"diagramId": diagram_id, "edges": edges}),
+    )
+    .await;
+    assert_ne!(partial.is_error, Some(true), "{partial:?}");
+    let partial = json_item(&partial);
+    assert_eq!(partial["created"], 2);
+    assert_eq!(partial["errors"].as_array().unwrap().len(), 1);
+    let edge_ids = partial["edgeIds"].as_array().unwrap();
+    assert_eq!(edge_ids.len(), 3);
+    assert!(edge_ids[1].is_null());
+    for (edge_id, (source, target)) in [(&edge_ids[0], (&a, &b)), (&edge_ids[2], (&b, &c))] {
+        let edge = element(&backend, &diagram_id, edge_id.as_str().unwrap()).await;
+        assert_eq!(edge["source_id"], json!(source));
+        assert_eq!(edge["target_id"], json!(target));
+    }
+
+    // The batch is one change of the diagram
+    let after_partial = diagram(&backend, &diagram_id).await;
+    assert_eq!(
+        after_partial["revision"],
+        json!(after["revision"].as_u64().unwrap() + 1)
+    );
+    let root_children = after_partial["root"]["children"].as_array().unwrap();
+    assert!(root_children.contains(&edge_ids[0]) && root_children.contains(&edge_ids[2]));
+}
+
+#[tokio::test]
+async fn test_assert_acyclic_reports_cycles() {
+    let (backend, _workspace) = backend().await;
+    let diagram_id = create_diagram(&backend, "workflow").await;
+    let a = create_node(&backend, &diagram_id, json!({"label": "A"})).await;
+    let b = create_node(&backend, &diagram_id, json!({"label": "B"})).await;
+    create_edge(&backend, &diagram_id, &a, &b).await;
+    create_edge(&backend, &diagram_id, &b, &a).await;
+
+    let checked = call(&backend, "assert_acyclic", json!({"diagramId": diagram_id})).await;
+    assert_eq!(checked.is_error, Some(true));
+    let checked = json_item(&checked);
+    assert_eq!(checked["acyclic"], false);
+    assert_eq!(checked["mustBeAcyclic"], false);
+    assert_eq!(checked["cycles"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_merge_goes_through_the_change_pipeline() {
+    let (backend, _workspace) = backend().await;
+    let target_id = create_diagram(&backend, "workflow").await;
+    let source_id = create_diagram(&backend, "workflow").await;
+    create_node(&backend, &target_id, json!({"label": "A"})).await;
+    create_node(&backend, &source_id, json!({"label": "B"})).await;
+    let merge = json!({"targetId": target_id, "sourceId": source_id, "deleteSource": true});
+
+    // Both diagrams a merge changes must be writable
+    for diagram_id in [&target_id, &source_id] {
+        let readonly = json!({"diagramId": diagram_id, "readOnly": true});
+        call(&backend, "set_diagram_readonly", readonly).await;
+        let error = call_err(&backend, "merge_diagrams", merge.clone()).await;
+        assert!(
+            matches!(error, GlspError::DiagramReadOnly { .. }),
+            "{error:?}"
+        );
+        let writable = json!({"diagramId": diagram_id, "readOnly": false});
+        call(&backend, "set_diagram_readonly", writable).await;
+    }
+
+    let merged = call(&backend, "merge_diagrams", merge).await;
+    assert_ne!(merged.is_error, Some(true), "{merged:?}");
+    assert_eq!(json_item(&merged)["sourceDeleted"], true);
+    assert!(json_item(&merged)["sourceDeleteError"].is_null());
+    let history = call(
+        &backend,
+        "get_history",
+        json!({"diagramId": target_id, "operation": "merge_diagrams"}),
+    )
+    .await;
+    let history = json_item(&history);
+    assert_eq!(history["count"], 1);
+    assert_eq!(
+        history["entries"][0]["elementIds"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_element_locks_block_tools_that_change_unnamed_elements() {
+    let (backend, _workspace) = backend().await;
+    let diagram_id = create_diagram(&backend, "workflow").await;
+    let other_id = create_diagram(&backend, "workflow").await;
+    let a = create_node(&backend, &diagram_id, json!({"label": "A"})).await;
+    let b = create_node(&backend, &diagram_id, json!({"label": "B"})).await;
+    let edge_id = create_edge(&backend, &diagram_id, &a, &b).await;
+    call(
+        &backend,
+        "lock_element",
+        json!({"diagramId": diagram_id, "elementId": edge_id, "clientId": "alice"}),
+    )
+    .await;
+
+    let blocked = [
+        (
+            "apply_layout",
+            json!({"diagramId": diagram_id, "algorithm": "grid"}),
+        ),
+        ("normalize_coordinates", json!({"diagramId": diagram_id})),
+        (
+            "set_type_style",
+            json!({"diagramId": diagram_id, "elementType": "flow", "style": {"stroke": "#f00"}}),
+        ),
+        (
+            "merge_diagrams",
+            json!({"targetId": diagram_id, "sourceId": other_id}),
+        ),
+        (
+            "extract_subgraph",
+            json!({"diagramId": diagram_id, "nodeIds": [b], "newDiagramName": "Part"}),
+        ),
+        ("delete_diagram", json!({"diagramId": diagram_id})),
+        // Deleting a node deletes its edges
+        (
+            "delete_element",
+            json!({"diagramId": diagram_id, "elementId": a}),
+        ),
+    ];
+    for (tool, mut arguments) in blocked {
+        arguments["clientId"] = json!("bob");
+        let error = call_err(&backend, tool, arguments).await;
+        assert!(
+            matches!(&error, GlspError::ElementLocked { element_id, .. } if *element_id == edge_id),
+            "{tool}: {error:?}"
+        );
+    }
+    assert_eq!(
+        element(&backend, &diagram_id, &edge_id).await["id"],
+        edge_id
+    );
+
+    // The lock holder deletes the node and its edge together
+    let deleted = call(
+        &backend,
+        "delete_element",
+        json!({"diagramId": diagram_id, "elementId": a, "clientId": "alice"}),
+    )
+    .await;
+    assert_eq!(json_item(&deleted)["deletedEdges"], json!([edge_id]));
+    assert!(element(&backend, &diagram_id, &edge_id).await.is_null());
+    let mut removed = vec![a, edge_id];
+    removed.sort();
+    assert_eq!(
+        history_elements(&backend, &diagram_id, "delete_element").await,
+        json!(removed)
+    );
+}
+
+#[tokio::test]
+async fn test_history_records_the_elements_each_call_touched() {
+    let (backend, _workspace) = backend().await;
+    let diagram_id = create_diagram(&backend, "workflow").await;
+    let a = create_node(
+        &backend,
+        &diagram_id,
+        json!({"label": "A", "position": {"x": 1234.5, "y": 987.0}}),
+    )
+    .await;
+    let b = create_node(&backend, &diagram_id, json!({"label": "B"})).await;
+    let edge_id = create_edge(&backend, &diagram_id, &a, &b).await;
+    call(
+        &backend,
+        "update_element",
+        json!({"diagramId": diagram_id, "elementId": b, "properties": {"owner": "ops"}}),
+    )
+    .await;
+    call(
+        &backend,
+        "apply_layout",
+        json!({"diagramId": diagram_id, "algorithm": "grid"}),
+    )
+    .await;
+
+    // Handlers report what they touched
+    assert_eq!(
+        history_elements(&backend, &diagram_id, "create_edge").await,
+        json!([edge_id])
+    );
+    assert_eq!(
+        history_elements(&backend, &diagram_id, "update_element").await,
+        json!([b])
+    );
+    // Bulk operations fall back to a diff of the whole diagram
+    let laid_out = history_elements(&backend, &diagram_id, "apply_layout").await;
+    assert!(
+        laid_out.as_array().unwrap().contains(&json!(a)),
+        "{laid_out}"
+    );
+}
+
+#[tokio::test]
+async fn test_integrity_repairs_are_recorded_in_the_history() {
+    let (backend, _workspace) = backend().await;
+    let diagram_id = create_diagram(&backend, "workflow").await;
+    let node_id = create_node(&backend, &diagram_id, json!({"label": "A"})).await;
+
+    // Strict imports accept nodes no container holds
+    let mut archive = json_item(&call(&backend, "export_workspace", json!({})).await);
+    archive["diagrams"][0]["root"]["children"] = json!([]);
+    let imported = call(
+        &backend,
+        "import_workspace",
+        json!({"archive": archive, "strategy": "always-new"}),
+    )
+    .await;
+    let orphaned_id = json_item(&imported)["diagramIds"][0]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let repaired = call(
+        &backend,
+        "check_integrity",
+        json!({"repair": true, "clientId": "alice"}),
+    )
+    .await;
+    assert_eq!(
+        json_item(&repaired)["repairedDiagrams"],
+        json!([orphaned_id])
+    );
+    let history = call(
+        &backend,
+        "get_history",
+        json!({"diagramId": orphaned_id, "operation": "check_integrity"}),
+    )
+    .await;
+    let history = json_item(&history);
+    assert_eq!(history["count"], 1);
+    assert_eq!(history["entries"][0]["actor"], "alice");
+    assert_eq!(history["entries"][0]["elementIds"], json!([node_id]));
+    // Diagrams without issues are left alone
+    assert!(history_elements(&backend, &diagram_id, "check_integrity")
+        .await
+        .is_null());
+}
+
+#[tokio::test]
+async fn test_read_only_diagrams_refuse_edits_until_cleared() {
+    let workspace = TempDir::new().unwrap();
+    let backend = start(&workspace, |_| {}).await;
+    let diagram_id = create_diagram(&backend, "workflow").await;
+    let node_id = create_node(&backend, &diagram_id, json!({"label": "A"})).await;
+    call(
+        &backend,
+        "set_diagram_readonly",
+        json!({"diagramId": diagram_id, "readOnly": true}),
+    )
+    .await;
+
+    let new_node = json!({"diagramId": diagram_id, "nodeType": "task"});
+    let error = call_err(&backend, "create_node", new_node.clone()).await;
+    assert!(
+        matches!(&error, GlspError::DiagramReadOnly { diagram_id: id } if *id == diagram_id),
+        "{error:?}"
+    );
+    let update = json!({"diagramId": diagram_id, "elementId": node_id, "properties": {"x": 1}});
+    let error = call_err(&backend, "update_element", update).await;
+    assert!(
+        matches!(error, GlspError::DiagramReadOnly { .. }),
+        "{error:?}"
+    );
+    let repair = json!({"diagramId": diagram_id, "repair": true});
+    let error = call_err(&backend, "check_integrity", repair).await;
+    assert!(
+        matches!(error, GlspError::DiagramReadOnly { .. }),
+        "{error:?}"
+    );
+
+    // Reads still work
+    let read = diagram(&backend, &diagram_id).await;
+    assert_eq!(read["readOnly"], true);
+    let exported = call(
+        &backend,
+        "export_diagram",
+        json!({"diagramId": diagram_id, "format": "json"}),
+    )
+    .await;
+    assert_ne!(exported.is_error, Some(true), "{exported:?}");
+
+    // The flag is saved with the diagram
+    drop(backend);
+    let restarted = start(&workspace, |_| {}).await;
+    let error = call_err(&restarted, "create_node", new_node.clone()).await;
+    assert!(
+        matches!(error, GlspError::DiagramReadOnly { .. }),
+        "{error:?}"
+    );
+    call(
+        &restarted,
+        "set_diagram_readonly",
+        json!({"diagramId": diagram_id, "readOnly": false}),
+    )
+    .await;
+    let created = call(&restarted, "create_node", new_node).await;
+    assert_ne!(created.is_error, Some(true), "{created:?}");
+}
+
+#[tokio::test]
+async fn test_every_tool_declares_its_scope() {
+    let (backend, _workspace) = backend().await;
+    let params: PaginatedRequestParam = serde_json::from_value(json!({})).unwrap();
+    let tools = backend.list_tools(params).await.unwrap().tools;
+    assert!(!tools.is_empty());
+    let undeclared: Vec<&str> = tools
+        .iter()
+        .map(|tool| tool.name.as_str())
+        .filter(|name| declared_scope(name).is_none())
+        .collect();
+    assert!(
+        undeclared.is_empty(),
+        "tools without a scope: {undeclared:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_deleted_diagrams_stay_deleted_after_a_restart() {
+    let workspace = TempDir::new().unwrap();
+    // A diagram whose last save failed is only held as a dead letter
+    let mut pending = DiagramModel::new("workflow");
+    pending.name = "Pending".to_string();
+    DeadLetterStore::new(workspace.path().join("dead-letter"))
+        .record(&pending, "disk full")
+        .await
+        .unwrap();
+
+    let backend = start(&workspace, |_| {}).await;
+    let restored = call(&backend, "get_diagram", json!({"diagramId": pending.id})).await;
+    assert_ne!(restored.is_error, Some(true), "{restored:?}");
+    call(&backend, "delete_diagram", json!({"diagramId": pending.id})).await;
+
+    drop(backend);
+    let restarted = start(&workspace, |_| {}).await;
+    let error = call_err(&restarted, "get_diagram", json!({"diagramId": pending.id})).await;
+    assert!(matches!(error, GlspError::ToolExecution(_)), "{error:?}");
+}
+
+#[tokio::test]
+async fn test_unknown_persistence_format_fails_startup() {
+    let workspace = TempDir::new().unwrap();
+    let mut config = config(&workspace);
+    config.persistence_format = "yaml".to_string();
+    let error = GlspBackend::initialize(config)
+        .await
+        .err()
+        .expect("started with an unknown persistence format");
+    assert!(
+        matches!(&error, GlspError::NotImplemented(message) if message.contains("yaml")),
+        "{error:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_configured_default_diagram_type() {
+    let workspace = TempDir::new().unwrap();
+    let backend = start(&workspace, |config| {
+        config.default_diagram_type = "workflow".to_string();
+    })
+    .await;
+
+    let created = call(&backend, "create_diagram", json!({"name": "Untyped"})).await;
+    assert_ne!(created.is_error, Some(true), "{created:?}");
+    let created = json_item(&created);
+    assert_eq!(created["diagramType"], "workflow");
+    assert_eq!(created["defaultTypeApplied"], true);
+
+    // An explicit type is used as given
+    let explicit = call(
+        &backend,
+        "create_diagram",
+        json!({"name": "Typed", "diagramType": "uml-class"}),
+    )
+    .await;
+    assert_eq!(json_item(&explicit)["diagramType"], "uml-class");
+    assert_eq!(json_item(&explicit)["defaultTypeApplied"], false);
+}
+
+#[tokio::test]
+async fn test_unknown_default_diagram_type_fails_startup() {
+    let workspace = TempDir::new().unwrap();
+    let mut config = config(&workspace);
+    config.default_diagram_type = "flowchart-deluxe".to_string();
+    let error = GlspBackend::initialize(config)
+        .await
+        .err()
+        .expect("started with an unknown default diagram type");
+    assert!(
+        matches!(&error, GlspError::NotImplemented(message) if message.contains("flowchart-deluxe")),
+        "{error:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_auth_tokens_need_the_http_direct_transport() {
+    let workspace = TempDir::new().unwrap();
+    let mut config = config(&workspace);
+    config.auth_tokens = "ops-token=admin".to_string();
+    let error = GlspBackend::initialize(config)
+        .await
+        .err()
+        .expect("started with tokens the transport cannot enforce");
+    assert!(
+        matches!(&error, GlspError::NotImplemented(message) if message.contains("--auth-tokens")),
+        "{error:?}"
+    );
+
+    start(&workspace, |config| {
+        config.transport = "http-direct".to_string();
+        config.auth_tokens = "ops-token=admin".to_string();
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_cors_origins_need_the_http_direct_transport() {
+    let workspace = TempDir::new().unwrap();
+    let mut config = config(&workspace);
+    config.cors_allowed_origins = "http://localhost:5173".to_string();
+    let error = GlspBackend::initialize(config)
+        .await
+        .err()
+        .expect("started with CORS origins the transport ignores");
+    assert!(
+        matches!(&error, GlspError::NotImplemented(message) if message.contains("--cors-allowed-origins")),
+        "{error:?}"
+    );
+
+    start(&workspace, |config| {
+        config.transport = "http-direct".to_string();
+        config.cors_allowed_origins = "http://localhost:5173".to_string();
+    })
+    .await;
+}
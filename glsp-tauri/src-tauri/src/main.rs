@@ -48,6 +48,13 @@ async fn main() {
     // Create MCP client and app state
     let mcp_client = Arc::new(mcp_client::McpClient::new(port));
 
+    if let Err(e) = mcp_client
+        .wait_until_ready(tokio::time::Duration::from_secs(10))
+        .await
+    {
+        eprintln!("{}", e);
+    }
+
     // Initialize MCP client session
     if let Err(e) = mcp_client.initialize().await {
         eprintln!("Failed to initialize MCP client: {}", e);
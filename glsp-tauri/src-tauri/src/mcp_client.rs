@@ -1,13 +1,161 @@
+use glsp_mcp_server::generate_id;
+use glsp_mcp_server::idempotency::{IDEMPOTENCY_KEY_ARG, REPLAY_MARKER};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tracing::debug;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{debug, warn};
+
+/// Attempts made for a create call before giving up
+const MAX_CREATE_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry of a create call; doubles on each retry
+const CREATE_RETRY_DELAY_MS: u64 = 200;
+
+/// Delay before the second readiness probe; doubles up to `READY_POLL_MAX_DELAY_MS`
+const READY_POLL_DELAY_MS: u64 = 50;
+
+/// Longest pause between readiness probes
+const READY_POLL_MAX_DELAY_MS: u64 = 1000;
+
+/// JSON-RPC request id: a number, integral or not, or a string.
+///
+/// Numbers keep their exact JSON form, so `0.5` or integers beyond `u64`
+/// from other peers still compare equal to what was sent.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RequestId {
+    Number(serde_json::Number),
+    String(String),
+}
+
+impl From<u64> for RequestId {
+    fn from(id: u64) -> Self {
+        RequestId::Number(id.into())
+    }
+}
+
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RequestId::Number(n) => write!(f, "{}", n),
+            RequestId::String(s) => write!(f, "\"{}\"", s),
+        }
+    }
+}
+
+/// How an [`IdAllocator`] makes request ids
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdStrategy {
+    /// 1, 2, 3, ... per client; stays far below 2^53, so JavaScript peers
+    /// read the ids exactly
+    #[default]
+    Sequential,
+    /// Random UUID strings, unique across clients sharing a session
+    Uuid,
+}
+
+/// Hands out request ids that are unique for the lifetime of a client,
+/// including under concurrent calls
+#[derive(Debug)]
+pub struct IdAllocator {
+    strategy: IdStrategy,
+    next: AtomicU64,
+}
+
+impl IdAllocator {
+    pub fn new(strategy: IdStrategy) -> Self {
+        Self {
+            strategy,
+            next: AtomicU64::new(1),
+        }
+    }
+
+    pub fn next_id(&self) -> RequestId {
+        match self.strategy {
+            IdStrategy::Sequential => self.next.fetch_add(1, Ordering::Relaxed).into(),
+            IdStrategy::Uuid => RequestId::String(generate_id()),
+        }
+    }
+}
+
+/// Matches responses to the requests they answer.
+///
+/// Responses to a batch, or to concurrent requests on one connection, may
+/// arrive in any order; each is matched by id and returned in the order the
+/// requests were sent.
+#[derive(Debug)]
+pub struct ResponseCorrelator {
+    ids: Vec<RequestId>,
+    pending: HashMap<RequestId, usize>,
+}
+
+impl ResponseCorrelator {
+    /// Expect one response for each of `ids`, in this order
+    pub fn new(ids: impl IntoIterator<Item = RequestId>) -> Self {
+        let ids: Vec<RequestId> = ids.into_iter().collect();
+        Self {
+            pending: ids
+                .iter()
+                .cloned()
+                .enumerate()
+                .map(|(i, id)| (id, i))
+                .collect(),
+            ids,
+        }
+    }
+
+    /// The response to each request, in request order.
+    ///
+    /// A request the server answered without an id (it could not read the
+    /// request) or did not answer at all fails on its own; id-less errors go
+    /// to the unanswered requests in order. Fails as a whole on a response to
+    /// an unknown or already answered id, since then no response can be
+    /// trusted to answer the request it names.
+    pub fn correlate(
+        mut self,
+        responses: Vec<McpResponse>,
+    ) -> Result<Vec<Result<McpResponse, String>>, String> {
+        let mut ordered: Vec<Option<McpResponse>> = Vec::new();
+        ordered.resize_with(self.ids.len(), || None);
+        let mut rejections = Vec::new();
+        for response in responses {
+            let Some(id) = response.id.clone() else {
+                let reason = response
+                    .error
+                    .map(|e| e.message)
+                    .unwrap_or_else(|| "no error given".to_string());
+                rejections.push(format!("Server rejected the request: {}", reason));
+                continue;
+            };
+            let index = self
+                .pending
+                .remove(&id)
+                .ok_or_else(|| format!("Response to unknown or answered request id {}", id))?;
+            ordered[index] = Some(response);
+        }
+
+        let mut rejections = rejections.into_iter();
+        Ok(ordered
+            .into_iter()
+            .zip(&self.ids)
+            .map(|(response, id)| {
+                response.ok_or_else(|| {
+                    rejections
+                        .next()
+                        .unwrap_or_else(|| format!("No response to request id {}", id))
+                })
+            })
+            .collect())
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct McpRequest {
     pub jsonrpc: String,
     pub method: String,
     pub params: Value,
-    pub id: u64,
+    pub id: RequestId,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -15,7 +163,9 @@ pub struct McpResponse {
     pub jsonrpc: String,
     pub result: Option<Value>,
     pub error: Option<McpError>,
-    pub id: u64,
+    /// Absent or null when the server could not read the request's id
+    #[serde(default)]
+    pub id: Option<RequestId>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -44,24 +194,246 @@ pub struct McpContent {
     pub text: Option<String>,
 }
 
+/// A create call that is safe to retry.
+///
+/// Every request carries an idempotency key, generated unless one is given,
+/// and the same key is sent on every retry so the server creates the element
+/// at most once.
+#[derive(Debug, Clone)]
+pub struct CreateRequest {
+    tool: &'static str,
+    /// Key of the created ID in the structured item of the result
+    id_key: &'static str,
+    arguments: serde_json::Map<String, Value>,
+    idempotency_key: String,
+}
+
+impl CreateRequest {
+    fn new(tool: &'static str, id_key: &'static str, arguments: Value) -> Self {
+        Self {
+            tool,
+            id_key,
+            arguments: match arguments {
+                Value::Object(map) => map,
+                _ => serde_json::Map::new(),
+            },
+            idempotency_key: generate_id(),
+        }
+    }
+
+    /// Create a diagram
+    pub fn diagram(diagram_type: &str, name: &str) -> Self {
+        Self::new(
+            "create_diagram",
+            "diagramId",
+            serde_json::json!({ "diagramType": diagram_type, "name": name }),
+        )
+    }
+
+    /// Create a node; the server places it unless a position is set
+    pub fn node(diagram_id: &str, node_type: &str) -> Self {
+        Self::new(
+            "create_node",
+            "nodeId",
+            serde_json::json!({ "diagramId": diagram_id, "nodeType": node_type }),
+        )
+    }
+
+    /// Create an edge between two elements
+    pub fn edge(diagram_id: &str, edge_type: &str, source_id: &str, target_id: &str) -> Self {
+        Self::new(
+            "create_edge",
+            "edgeId",
+            serde_json::json!({
+                "diagramId": diagram_id,
+                "edgeType": edge_type,
+                "sourceId": source_id,
+                "targetId": target_id
+            }),
+        )
+    }
+
+    pub fn label(mut self, label: &str) -> Self {
+        self.arguments
+            .insert("label".to_string(), Value::from(label));
+        self
+    }
+
+    pub fn position(mut self, x: f64, y: f64) -> Self {
+        self.arguments.insert(
+            "position".to_string(),
+            serde_json::json!({ "x": x, "y": y }),
+        );
+        self
+    }
+
+    /// What to do when a diagram with the same name exists: "create",
+    /// "reuse" or "error"
+    pub fn if_exists(mut self, policy: &str) -> Self {
+        self.arguments
+            .insert("ifExists".to_string(), Value::from(policy));
+        self
+    }
+
+    /// Use a caller-chosen key instead of the generated one
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = key.into();
+        self
+    }
+
+    pub fn key(&self) -> &str {
+        &self.idempotency_key
+    }
+
+    fn arguments(&self) -> Value {
+        let mut arguments = self.arguments.clone();
+        arguments.insert(
+            IDEMPOTENCY_KEY_ARG.to_string(),
+            Value::from(self.idempotency_key.clone()),
+        );
+        Value::Object(arguments)
+    }
+}
+
+/// Result of a create call
+#[derive(Debug, Clone)]
+pub struct CreateOutcome {
+    /// ID of the created element or diagram; element IDs may carry a type
+    /// prefix such as `task-<uuid>`
+    pub id: String,
+    /// Whether the server replayed the result of an earlier attempt
+    pub replayed: bool,
+    /// Whether an existing diagram was returned instead of a new one
+    pub reused: bool,
+    pub message: String,
+}
+
+impl CreateOutcome {
+    /// Read the outcome of a create call; the ID comes from the structured
+    /// item the server adds to the result, under `id_key`
+    fn from_result(result: &McpToolResult, id_key: &str) -> Result<Self, String> {
+        let texts: Vec<&str> = result
+            .content
+            .iter()
+            .filter_map(|c| c.text.as_deref())
+            .collect();
+        let message = texts.first().copied().unwrap_or_default();
+
+        if result.is_error.unwrap_or(false) {
+            return Err(message.to_string());
+        }
+
+        let items: Vec<Value> = texts
+            .iter()
+            .filter_map(|text| serde_json::from_str::<Value>(text).ok())
+            .collect();
+        let field = |key: &str| items.iter().find_map(|item| item.get(key));
+        let id = field(id_key)
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("No {} in create result: {}", id_key, message))?;
+        let replayed = field(REPLAY_MARKER)
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let reused = field("created").and_then(Value::as_bool) == Some(false);
+
+        Ok(Self {
+            id: id.to_string(),
+            replayed,
+            reused,
+            message: message.to_string(),
+        })
+    }
+}
+
+/// Why a call to the server failed
+#[derive(Debug)]
+enum CallError {
+    /// The request or its response was lost on the way: sending failed or
+    /// timed out. Worth retrying
+    Transport(String),
+    /// The server answered, with an error or something unreadable. Sending
+    /// the same request again gets the same answer
+    Server(String),
+}
+
+impl From<CallError> for String {
+    fn from(error: CallError) -> Self {
+        match error {
+            CallError::Transport(message) | CallError::Server(message) => message,
+        }
+    }
+}
+
+/// Connection settings of the HTTP client that every call of an
+/// [`McpClient`] shares
+///
+/// Reusing connections saves a TCP handshake per call, which adds up over
+/// the many short sequential calls a session makes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientConfig {
+    /// Idle connections kept open to the server
+    pub pool_max_idle: usize,
+    /// How long an unused connection stays in the pool; `None` keeps it
+    /// until the server closes it
+    pub pool_idle_timeout: Option<std::time::Duration>,
+    /// Interval of TCP keepalive probes on open connections; `None` disables
+    /// them
+    pub tcp_keepalive: Option<std::time::Duration>,
+    /// How request ids are made
+    pub id_strategy: IdStrategy,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            pool_max_idle: 8,
+            pool_idle_timeout: Some(std::time::Duration::from_secs(90)),
+            tcp_keepalive: Some(std::time::Duration::from_secs(60)),
+            id_strategy: IdStrategy::default(),
+        }
+    }
+}
+
+impl ClientConfig {
+    fn build_client(&self) -> reqwest::Client {
+        reqwest::Client::builder()
+            .pool_max_idle_per_host(self.pool_max_idle)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .tcp_keepalive(self.tcp_keepalive)
+            .build()
+            .unwrap_or_else(|e| {
+                warn!(
+                    "Failed to build HTTP client from {:?}: {}; using defaults",
+                    self, e
+                );
+                reqwest::Client::new()
+            })
+    }
+}
+
 /// Simple MCP client for communicating with the embedded GLSP server
 #[derive(Debug)]
 pub struct McpClient {
     base_url: std::sync::Arc<std::sync::Mutex<String>>,
     client: reqwest::Client,
-    next_id: std::sync::atomic::AtomicU64,
+    ids: IdAllocator,
     session_id: std::sync::Mutex<Option<String>>,
 }
 
 impl McpClient {
     pub fn new(server_port: u16) -> Self {
+        Self::with_config(server_port, ClientConfig::default())
+    }
+
+    /// Client whose connections are pooled and kept alive as `config` says
+    pub fn with_config(server_port: u16, config: ClientConfig) -> Self {
         Self {
             base_url: std::sync::Arc::new(std::sync::Mutex::new(format!(
                 "http://localhost:{}/messages",
                 server_port
             ))),
-            client: reqwest::Client::new(),
-            next_id: std::sync::atomic::AtomicU64::new(1),
+            client: config.build_client(),
+            ids: IdAllocator::new(config.id_strategy),
             session_id: std::sync::Mutex::new(None),
         }
     }
@@ -72,48 +444,91 @@ impl McpClient {
         *url = format!("http://localhost:{}/messages", new_port);
     }
 
-    fn next_request_id(&self) -> u64 {
-        self.next_id
-            .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+    fn request(&self, method: &str, params: Value) -> McpRequest {
+        McpRequest {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+            id: self.ids.next_id(),
+        }
     }
 
-    /// Initialize MCP session and get session ID
-    pub async fn initialize(&self) -> Result<(), String> {
-        let request = McpRequest {
-            jsonrpc: "2.0".to_string(),
-            method: "initialize".to_string(),
-            params: serde_json::json!({
-                "protocolVersion": "2024-11-05",
-                "capabilities": {},
-                "clientInfo": {
-                    "name": "GLSP Tauri Client",
-                    "version": "1.0.0"
-                }
+    fn tool_request(&self, tool_name: &str, arguments: Option<Value>) -> McpRequest {
+        self.request(
+            "tools/call",
+            serde_json::json!({
+                "name": tool_name,
+                "arguments": arguments.unwrap_or(Value::Object(serde_json::Map::new()))
             }),
-            id: self.next_request_id(),
-        };
+        )
+    }
 
+    /// POST a request or batch and return the response body
+    async fn post<T: Serialize + ?Sized, R: serde::de::DeserializeOwned>(
+        &self,
+        body: &T,
+    ) -> Result<R, CallError> {
         let base_url = self.base_url.lock().unwrap().clone();
-        let req_builder = self
+        let mut req_builder = self
             .client
             .post(&base_url)
             .header("Content-Type", "application/json")
             .header("Accept", "application/json")
-            .json(&request);
+            .json(body);
+
+        // Add session ID header if available
+        if let Ok(session_guard) = self.session_id.lock() {
+            if let Some(ref session_id) = *session_guard {
+                req_builder = req_builder.header("Mcp-Session-Id", session_id);
+            }
+        }
 
         let response = req_builder
             .send()
             .await
-            .map_err(|e| format!("HTTP request failed: {}", e))?;
+            .map_err(|e| CallError::Transport(format!("HTTP request failed: {}", e)))?;
 
         if !response.status().is_success() {
-            return Err(format!("HTTP error: {}", response.status()));
+            return Err(CallError::Server(format!(
+                "HTTP error: {}",
+                response.status()
+            )));
         }
 
-        let mcp_response: McpResponse = response
-            .json()
-            .await
-            .map_err(|e| format!("Failed to parse response: {}", e))?;
+        response.json().await.map_err(|e| {
+            let message = format!("Failed to parse response: {}", e);
+            if e.is_timeout() {
+                CallError::Transport(message)
+            } else {
+                CallError::Server(message)
+            }
+        })
+    }
+
+    /// Send one request and return the response that answers it
+    async fn send(&self, request: &McpRequest) -> Result<McpResponse, CallError> {
+        let response: McpResponse = self.post(request).await?;
+        ResponseCorrelator::new([request.id.clone()])
+            .correlate(vec![response])
+            .and_then(|mut responses| responses.remove(0))
+            .map_err(CallError::Server)
+    }
+
+    /// Initialize MCP session and get session ID
+    pub async fn initialize(&self) -> Result<(), String> {
+        let request = self.request(
+            "initialize",
+            serde_json::json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": {},
+                "clientInfo": {
+                    "name": "GLSP Tauri Client",
+                    "version": "1.0.0"
+                }
+            }),
+        );
+
+        let mcp_response = self.send(&request).await?;
 
         if let Some(error) = mcp_response.error {
             return Err(format!(
@@ -140,49 +555,52 @@ impl McpClient {
         tool_name: &str,
         arguments: Option<Value>,
     ) -> Result<McpToolResult, String> {
-        let request = McpRequest {
-            jsonrpc: "2.0".to_string(),
-            method: "tools/call".to_string(),
-            params: serde_json::json!({
-                "name": tool_name,
-                "arguments": arguments.unwrap_or(Value::Object(serde_json::Map::new()))
-            }),
-            id: self.next_request_id(),
-        };
-
-        debug!("Sending MCP request: {:?}", request);
+        Ok(self.try_call_tool(tool_name, arguments).await?)
+    }
 
-        let base_url = self.base_url.lock().unwrap().clone();
-        let mut req_builder = self
-            .client
-            .post(&base_url)
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json")
-            .json(&request);
+    async fn try_call_tool(
+        &self,
+        tool_name: &str,
+        arguments: Option<Value>,
+    ) -> Result<McpToolResult, CallError> {
+        let request = self.tool_request(tool_name, arguments);
 
-        // Add session ID header if available
-        if let Ok(session_guard) = self.session_id.lock() {
-            if let Some(ref session_id) = *session_guard {
-                req_builder = req_builder.header("Mcp-Session-Id", session_id);
-            }
-        }
+        debug!("Sending MCP request: {:?}", request);
+        let mcp_response = self.send(&request).await?;
+        debug!("Received MCP response: {:?}", mcp_response);
 
-        let response = req_builder
-            .send()
-            .await
-            .map_err(|e| format!("HTTP request failed: {}", e))?;
+        Self::tool_result(mcp_response).map_err(CallError::Server)
+    }
 
-        if !response.status().is_success() {
-            return Err(format!("HTTP error: {}", response.status()));
+    /// Call several tools in one JSON-RPC batch.
+    ///
+    /// Needs a transport that accepts batches (http-direct). Results come
+    /// back in the order of `calls` whatever order the server answers in;
+    /// each call succeeds or fails on its own.
+    pub async fn call_tools_batch(
+        &self,
+        calls: Vec<(String, Option<Value>)>,
+    ) -> Result<Vec<Result<McpToolResult, String>>, String> {
+        if calls.is_empty() {
+            return Ok(Vec::new());
         }
+        let requests: Vec<McpRequest> = calls
+            .into_iter()
+            .map(|(tool_name, arguments)| self.tool_request(&tool_name, arguments))
+            .collect();
+
+        debug!("Sending MCP batch of {} requests", requests.len());
+        let responses: Vec<McpResponse> = self.post(&requests).await?;
+        let responses =
+            ResponseCorrelator::new(requests.iter().map(|r| r.id.clone())).correlate(responses)?;
+
+        Ok(responses
+            .into_iter()
+            .map(|response| response.and_then(Self::tool_result))
+            .collect())
+    }
 
-        let mcp_response: McpResponse = response
-            .json()
-            .await
-            .map_err(|e| format!("Failed to parse response: {}", e))?;
-
-        debug!("Received MCP response: {:?}", mcp_response);
-
+    fn tool_result(mcp_response: McpResponse) -> Result<McpToolResult, String> {
         if let Some(error) = mcp_response.error {
             return Err(format!(
                 "MCP error: {} (code: {})",
@@ -195,6 +613,51 @@ impl McpClient {
         serde_json::from_value(result).map_err(|e| format!("Failed to parse tool result: {}", e))
     }
 
+    /// Run a create call, retrying with the same idempotency key when the
+    /// request or its response is lost.
+    ///
+    /// Errors the server answers with, such as a locked or read-only
+    /// diagram, fail at once: retrying would get the same answer. A replayed
+    /// result means an earlier attempt already succeeded; it is treated as
+    /// success and its ID is returned.
+    pub async fn create(&self, request: &CreateRequest) -> Result<CreateOutcome, String> {
+        let mut delay = std::time::Duration::from_millis(CREATE_RETRY_DELAY_MS);
+        let mut attempt = 1;
+
+        loop {
+            match self
+                .try_call_tool(request.tool, Some(request.arguments()))
+                .await
+            {
+                Ok(result) => {
+                    let outcome = CreateOutcome::from_result(&result, request.id_key)?;
+                    if outcome.replayed {
+                        debug!(
+                            "{} with key {} already succeeded; reusing ID {}",
+                            request.tool,
+                            request.key(),
+                            outcome.id
+                        );
+                    }
+                    return Ok(outcome);
+                }
+                Err(CallError::Transport(e)) if attempt < MAX_CREATE_ATTEMPTS => {
+                    warn!(
+                        "{} attempt {} failed ({}); retrying with key {}",
+                        request.tool,
+                        attempt,
+                        e,
+                        request.key()
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
     /// Set workspace directory using MCP tool
     pub async fn set_workspace_directory(
         &self,
@@ -409,4 +872,115 @@ impl McpClient {
             Err(_) => Ok(false),
         }
     }
+
+    /// Poll `/health` until the server answers or `timeout` elapses.
+    ///
+    /// Probes back off exponentially, so a server that is already up is
+    /// detected immediately and a slow one is not hammered while it starts.
+    pub async fn wait_until_ready(&self, timeout: std::time::Duration) -> Result<(), String> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut delay = std::time::Duration::from_millis(READY_POLL_DELAY_MS);
+        let max_delay = std::time::Duration::from_millis(READY_POLL_MAX_DELAY_MS);
+
+        loop {
+            if self.health_check().await? {
+                return Ok(());
+            }
+            let now = tokio::time::Instant::now();
+            if now >= deadline {
+                let base_url = self.base_url.lock().unwrap().clone();
+                return Err(format!(
+                    "MCP server at {} not ready after {:?}",
+                    base_url, timeout
+                ));
+            }
+            tokio::time::sleep(delay.min(deadline - now)).await;
+            delay = (delay * 2).min(max_delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    fn response(id: Option<u64>, error: Option<&str>) -> McpResponse {
+        McpResponse {
+            jsonrpc: "2.0".to_string(),
+            result: error.is_none().then(|| serde_json::json!({"content": []})),
+            error: error.map(|message| McpError {
+                code: -32700,
+                message: message.to_string(),
+                data: None,
+            }),
+            id: id.map(RequestId::from),
+        }
+    }
+
+    fn correlator(ids: &[u64]) -> ResponseCorrelator {
+        ResponseCorrelator::new(ids.iter().map(|&id| RequestId::from(id)))
+    }
+
+    #[test]
+    fn test_ids_are_unique_across_threads() {
+        for strategy in [IdStrategy::Sequential, IdStrategy::Uuid] {
+            let ids = Arc::new(IdAllocator::new(strategy));
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    let ids = ids.clone();
+                    std::thread::spawn(move || (0..100).map(|_| ids.next_id()).collect::<Vec<_>>())
+                })
+                .collect();
+            let all: HashSet<RequestId> = handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect();
+            assert_eq!(all.len(), 400, "{strategy:?}");
+        }
+    }
+
+    #[test]
+    fn test_responses_come_back_in_request_order() {
+        let responses = vec![
+            response(Some(3), None),
+            response(Some(1), None),
+            response(Some(2), None),
+        ];
+        let ordered = correlator(&[1, 2, 3]).correlate(responses).unwrap();
+        let ids: Vec<_> = ordered
+            .into_iter()
+            .map(|response| response.unwrap().id.unwrap())
+            .collect();
+        assert_eq!(ids, [1, 2, 3].map(RequestId::from));
+    }
+
+    #[test]
+    fn test_duplicate_and_unknown_ids_fail_the_batch() {
+        let duplicate = vec![response(Some(1), None), response(Some(1), None)];
+        assert!(correlator(&[1, 2]).correlate(duplicate).is_err());
+
+        let unknown = vec![response(Some(1), None), response(Some(9), None)];
+        assert!(correlator(&[1, 2]).correlate(unknown).is_err());
+    }
+
+    #[test]
+    fn test_unanswered_requests_fail_on_their_own() {
+        // No response at all
+        let ordered = correlator(&[1, 2])
+            .correlate(vec![response(Some(2), None)])
+            .unwrap();
+        assert_eq!(
+            ordered[0].as_ref().unwrap_err(),
+            "No response to request id 1"
+        );
+        assert!(ordered[1].is_ok());
+
+        // An error the server could not attach to an id
+        let responses = vec![response(Some(1), None), response(None, Some("Parse error"))];
+        let ordered = correlator(&[1, 2]).correlate(responses).unwrap();
+        assert!(ordered[0].is_ok());
+        assert!(ordered[1].as_ref().unwrap_err().contains("Parse error"));
+    }
 }
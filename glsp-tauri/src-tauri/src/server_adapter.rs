@@ -93,6 +93,8 @@ pub async fn start_embedded_server_with_workspace(
             transport: "http-streaming".to_string(),
             wasm_path: format!("{}/wasm-components", workspace),
             diagrams_path: format!("{}/diagrams", workspace),
+            dead_letter_path: format!("{}/dead-letter", workspace),
+            export_path: format!("{}/exports", workspace),
             force: true,
             database_backend: "mock".to_string(),
             database_host: "localhost".to_string(),
@@ -102,6 +104,7 @@ pub async fn start_embedded_server_with_workspace(
             enable_database: false,
             server_name: "glsp-desktop".to_string(),
             server_version: "1.0.0".to_string(),
+            ..Default::default()
         }
     } else {
         info!("Using default app data directory");
@@ -110,6 +113,8 @@ pub async fn start_embedded_server_with_workspace(
             transport: "http-streaming".to_string(),
             wasm_path: get_app_dir("wasm-components"),
             diagrams_path: get_app_dir("diagrams"),
+            dead_letter_path: get_app_dir("dead-letter"),
+            export_path: get_app_dir("exports"),
             force: true,
             database_backend: "mock".to_string(),
             database_host: "localhost".to_string(),
@@ -119,6 +124,7 @@ pub async fn start_embedded_server_with_workspace(
             enable_database: false,
             server_name: "glsp-desktop".to_string(),
             server_version: "1.0.0".to_string(),
+            ..Default::default()
         }
     };
 
@@ -191,7 +191,8 @@ Respond with valid JSON only, no additional text.`;
                 }
                 
                 if (toolCall.tool === "create_node" && result.content?.[0]?.text) {
-                    const match = result.content[0].text.match(/ID: ([a-f0-9-]+)/);
+                    // Node IDs may carry a type prefix, e.g. "task-<uuid>"
+                    const match = result.content[0].text.match(/ID: ([A-Za-z0-9_-]+)/);
                     if (match) {
                         nodeIds.push(match[1]);
                         steps.push(`📦 Node ID: ${match[1]}`);
@@ -3,20 +3,20 @@
  * Implements proper UML conventions for class and component diagrams
  */
 
-import { Bounds, ModelElement } from '../model/diagram.js';
+import { Bounds, ModelElement, Visibility } from '../model/diagram.js';
 
 // UML-specific interfaces
 interface UMLComponentInterface {
     name: string;
     type: 'provided' | 'required'; // UML component interface types
-    visibility?: 'public' | 'private' | 'protected' | 'package';
+    visibility?: Visibility;
     stereotype?: string;
     methods?: UMLMethod[];
 }
 
 interface UMLMethod {
     name: string;
-    visibility: 'public' | 'private' | 'protected' | 'package';
+    visibility: Visibility;
     returnType?: string;
     parameters?: UMLParameter[];
     isStatic?: boolean;
@@ -32,7 +32,7 @@ interface UMLParameter {
 interface UMLAttribute {
     name: string;
     type: string;
-    visibility: 'public' | 'private' | 'protected' | 'package';
+    visibility: Visibility;
     isStatic?: boolean;
     defaultValue?: string;
 }
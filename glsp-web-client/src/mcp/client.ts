@@ -104,6 +104,30 @@ export interface TextContent {
     text: string;
 }
 
+export interface ToolWarning {
+    code: string;
+    message: string;
+}
+
+/** Warnings the server appended to a tool result as a `{"warnings": [...]}` content item */
+export function toolWarnings(result: CallToolResult): ToolWarning[] {
+    const warnings: ToolWarning[] = [];
+    for (const item of result.content ?? []) {
+        if (!item.text?.startsWith('{')) {
+            continue;
+        }
+        try {
+            const parsed = JSON.parse(item.text) as { warnings?: ToolWarning[] };
+            if (Array.isArray(parsed.warnings)) {
+                warnings.push(...parsed.warnings);
+            }
+        } catch {
+            // Not JSON; an ordinary text result
+        }
+    }
+    return warnings;
+}
+
 export interface Prompt {
     name: string;
     description?: string;
@@ -516,7 +540,11 @@ export class McpClient {
             name,
             arguments: args
         };
-        return await this.sendRequest('tools/call', params) as CallToolResult;
+        const result = await this.sendRequest('tools/call', params) as CallToolResult;
+        for (const warning of toolWarnings(result)) {
+            console.warn(`McpClient: ${name}: [${warning.code}] ${warning.message}`);
+        }
+        return result;
     }
 
     async listResources(): Promise<Resource[]> {
@@ -26,6 +26,18 @@ export interface ModelElement {
     [key: string]: unknown; // For additional properties
 }
 
+/** Class member visibility; mirrors `Visibility` in the server's model/uml.rs */
+export type Visibility = 'public' | 'private' | 'protected' | 'package';
+
+/** UML class relation types; mirrors `EdgeType` in the server's model/uml.rs */
+export type UmlEdgeType =
+    | 'association'
+    | 'aggregation'
+    | 'composition'
+    | 'inheritance'
+    | 'realization'
+    | 'dependency';
+
 export interface Bounds {
     x: number;
     y: number;